@@ -5,6 +5,223 @@
 
 use std::sync::Arc;
 
+use glow::HasContext;
+use serde::{Deserialize, Serialize};
+
+/// How the window is presented on the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WindowMode {
+    /// A regular, resizable window.
+    #[default]
+    Windowed,
+    /// A borderless window covering the whole display at the desktop resolution.
+    Borderless,
+    /// Exclusive fullscreen, which can change the resolution of the display.
+    Fullscreen,
+}
+
+impl WindowMode {
+    /// All window modes, in the order they are cycled through in the options.
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Borderless,
+        WindowMode::Fullscreen,
+    ];
+
+    /// Returns the next mode in [`WindowMode::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// Applies the given window mode to the window.
+///
+/// `resolution` is only used by [`WindowMode::Fullscreen`], where it selects the closest display
+/// mode supported by the display. If it is `None`, the desktop resolution is used.
+pub fn apply_window_mode(
+    window: &mut sdl2::video::Window,
+    mode: WindowMode,
+    resolution: Option<(u32, u32)>,
+) -> Result<(), String> {
+    let fullscreen_type = match mode {
+        WindowMode::Windowed => sdl2::video::FullscreenType::Off,
+        WindowMode::Borderless => sdl2::video::FullscreenType::Desktop,
+        WindowMode::Fullscreen => sdl2::video::FullscreenType::True,
+    };
+
+    if mode == WindowMode::Fullscreen {
+        let display_index = window.display_index()?;
+        let display_mode = match resolution {
+            Some((width, height)) => window.subsystem().closest_display_mode(
+                display_index,
+                &sdl2::video::DisplayMode::new(
+                    sdl2::pixels::PixelFormatEnum::Unknown,
+                    width as i32,
+                    height as i32,
+                    0,
+                ),
+            )?,
+            None => window.subsystem().desktop_display_mode(display_index)?,
+        };
+        log::info!(
+            "Using display mode {}x{}@{}Hz",
+            display_mode.w,
+            display_mode.h,
+            display_mode.refresh_rate
+        );
+        window.set_display_mode(display_mode)?;
+    }
+
+    window.set_fullscreen(fullscreen_type)?;
+    log::info!("Applied window mode: {:?}", mode);
+
+    Ok(())
+}
+
+/// Returns all resolutions supported by the display the window is on, largest first.
+pub fn available_resolutions(window: &sdl2::video::Window) -> Vec<(u32, u32)> {
+    let video = window.subsystem();
+    let display_index = window.display_index().unwrap_or(0);
+    let mode_count = video.num_display_modes(display_index).unwrap_or(0);
+
+    let mut resolutions: Vec<(u32, u32)> = (0..mode_count)
+        .filter_map(|i| video.display_mode(display_index, i).ok())
+        .map(|mode| (mode.w as u32, mode.h as u32))
+        .collect();
+    resolutions.sort_by(|a, b| b.cmp(a));
+    resolutions.dedup();
+    resolutions
+}
+
+/// Sets the title of the window, skipping the call into SDL if it did not change. Scenes call this
+/// every frame, so it is cheap to keep a title that includes e.g. the frame rate up to date.
+pub fn update_title(window: &mut sdl2::video::Window, title: &str) {
+    if window.title() != title
+        && let Err(e) = window.set_title(title)
+    {
+        log::error!("Failed to set window title: {}", e);
+    }
+}
+
+/// Enables or disables vertical synchronization for the current OpenGL context.
+pub fn set_vsync(sdl: &sdl2::Sdl, enabled: bool) -> Result<(), String> {
+    let interval = if enabled {
+        sdl2::video::SwapInterval::VSync
+    } else {
+        sdl2::video::SwapInterval::Immediate
+    };
+    sdl.video()?.gl_set_swap_interval(interval)?;
+    log::info!("Set vsync: {}", enabled);
+    Ok(())
+}
+
+/// The OpenGL contexts to try creating, in order of preference. The shaders are written for desktop
+/// OpenGL 3.3 and translated when running on OpenGL ES 3.0.
+const CONTEXT_VERSIONS: [(sdl2::video::GLProfile, u8, u8); 2] = [
+    (sdl2::video::GLProfile::Core, 3, 3),
+    (sdl2::video::GLProfile::GLES, 3, 0),
+];
+
+/// Logs the messages of the OpenGL debug output with a log level matching their severity.
+fn enable_debug_output(gl: &mut glow::Context) {
+    if !gl.supports_debug() {
+        log::warn!("OpenGL debug output is not supported by this context");
+        return;
+    }
+
+    unsafe {
+        gl.enable(glow::DEBUG_OUTPUT);
+        // Report messages from within the offending call so the log lines up with what caused them
+        gl.enable(glow::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl.debug_message_callback(|source, kind, id, severity, message| {
+            let source = match source {
+                glow::DEBUG_SOURCE_API => "API",
+                glow::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+                glow::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+                glow::DEBUG_SOURCE_THIRD_PARTY => "third party",
+                glow::DEBUG_SOURCE_APPLICATION => "application",
+                _ => "other",
+            };
+            let kind = match kind {
+                glow::DEBUG_TYPE_ERROR => "error",
+                glow::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+                glow::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+                glow::DEBUG_TYPE_PORTABILITY => "portability",
+                glow::DEBUG_TYPE_PERFORMANCE => "performance",
+                _ => "other",
+            };
+            let level = match severity {
+                glow::DEBUG_SEVERITY_HIGH => log::Level::Error,
+                glow::DEBUG_SEVERITY_MEDIUM => log::Level::Warn,
+                glow::DEBUG_SEVERITY_LOW => log::Level::Info,
+                _ => log::Level::Debug,
+            };
+            log::log!(level, "GL {} {} ({}): {}", source, kind, id, message);
+        });
+    }
+    log::info!("Enabled OpenGL debug output");
+}
+
+/// Creates an OpenGL context for the window, makes it current and loads the function pointers.
+fn create_gl_context(
+    video_subsystem: &sdl2::VideoSubsystem,
+    window: &sdl2::video::Window,
+    debug: bool,
+) -> Result<(sdl2::video::GLContext, glow::Context), String> {
+    let gl_attr = video_subsystem.gl_attr();
+    if debug {
+        gl_attr.set_context_flags().debug().set();
+    }
+
+    // Prefer desktop OpenGL, but fall back to OpenGL ES on devices that only support that
+    let mut gl_context = Err(String::new());
+    for (profile, major, minor) in CONTEXT_VERSIONS {
+        gl_attr.set_context_profile(profile);
+        gl_attr.set_context_version(major, minor);
+        log::info!(
+            "Requesting OpenGL context: {}.{} {:?}{}",
+            major,
+            minor,
+            profile,
+            if debug { " (debug)" } else { "" }
+        );
+        gl_context = window.gl_create_context();
+        match &gl_context {
+            Ok(_) => break,
+            Err(e) => log::warn!("Failed to create OpenGL context: {}", e),
+        }
+    }
+    let gl_context =
+        gl_context.map_err(|e| format!("No supported OpenGL context could be created: {}", e))?;
+    window.gl_make_current(&gl_context)?;
+    log::info!("Created and made current OpenGL context");
+
+    let mut gl = unsafe {
+        glow::Context::from_loader_function(|s| video_subsystem.gl_get_proc_address(s) as *const _)
+    };
+    log::info!("Loaded OpenGL function pointers");
+
+    if debug {
+        enable_debug_output(&mut gl);
+    }
+    log::info!(
+        "OpenGL ES: {}, float color attachments: {}",
+        gl.version().is_embedded,
+        super::framebuffer::supports_float_color(&gl)
+    );
+    Ok((gl_context, gl))
+}
+
 /// The [`App`] struct encapsulates the SDL2 and OpenGL context.
 pub struct App {
     pub sdl: sdl2::Sdl,
@@ -15,15 +232,17 @@ pub struct App {
 }
 
 impl App {
-    /// Creates a new [`App`] instance with the specified title, width, and height.
-    /// The width and height options are ignored if `fullscreen` is set to `true`.
-    pub fn new(title: &str, width: u32, height: u32, fullscreen: bool) -> Self {
+    /// Creates a new [`App`] instance with the specified title, width, height and window mode.
+    /// The width and height options are ignored if the mode is not [`WindowMode::Windowed`].
+    ///
+    /// If `debug` is set, a debug context is requested and the messages of the driver are logged.
+    pub fn new(title: &str, width: u32, height: u32, mode: WindowMode, debug: bool) -> Self {
         log::info!(
-            "Creating app: title='{}', requested_size={}x{}, fullscreen={}",
+            "Creating app: title='{}', requested_size={}x{}, mode={:?}",
             title,
             width,
             height,
-            fullscreen
+            mode
         );
 
         let sdl = sdl2::init().unwrap();
@@ -37,7 +256,7 @@ impl App {
         let desktop_height = display_mode.h as u32;
         log::info!("Primary display mode: {}x{}", desktop_width, desktop_height);
 
-        let (width, height) = if fullscreen {
+        let (width, height) = if mode != WindowMode::Windowed {
             log::info!(
                 "Fullscreen enabled, using desktop size {}x{}",
                 desktop_width,
@@ -56,23 +275,9 @@ impl App {
             .unwrap();
         log::info!("Created SDL2 window");
 
-        window
-            .set_fullscreen(if fullscreen {
-                sdl2::video::FullscreenType::Desktop
-            } else {
-                sdl2::video::FullscreenType::Off
-            })
-            .unwrap();
-        log::info!("Applied fullscreen mode: {}", fullscreen);
-
-        let gl_attr = video_subsystem.gl_attr();
-        gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
-        gl_attr.set_context_version(3, 3);
-        log::info!("Requested OpenGL context: 3.3 Core");
+        apply_window_mode(&mut window, mode, None).unwrap();
 
-        let gl_context = window.gl_create_context().unwrap();
-        window.gl_make_current(&gl_context).unwrap();
-        log::info!("Created and made current OpenGL context");
+        let (gl_context, gl) = create_gl_context(&video_subsystem, &window, debug).unwrap();
 
         let (window_w, window_h) = window.size();
         let (drawable_w, drawable_h) = window.drawable_size();
@@ -84,13 +289,7 @@ impl App {
             drawable_h
         );
 
-        let gl = unsafe {
-            glow::Context::from_loader_function(|s| {
-                video_subsystem.gl_get_proc_address(s) as *const _
-            })
-        };
         let gl = Arc::new(gl);
-        log::info!("Loaded OpenGL function pointers");
 
         let event_pump = sdl.event_pump().unwrap();
         log::info!("Created SDL2 event pump");
@@ -108,4 +307,42 @@ impl App {
             event_pump,
         }
     }
+
+    /// Creates a new [`App`] without showing a window, for rendering into framebuffers only, e.g.
+    /// to exercise the renderer in tests or to render thumbnails.
+    ///
+    /// SDL still needs a window to create the OpenGL context, so a hidden one of the given size is
+    /// used. On machines without a display, setting `SDL_VIDEODRIVER=offscreen` lets SDL create
+    /// the context without one.
+    #[allow(dead_code)]
+    pub fn new_headless(width: u32, height: u32) -> Result<Self, String> {
+        log::info!("Creating headless app: size={}x{}", width, height);
+
+        let sdl = sdl2::init()?;
+        let video_subsystem = sdl.video()?;
+        log::info!(
+            "Initialized SDL2 video subsystem with driver {}",
+            video_subsystem.current_video_driver()
+        );
+
+        let window = video_subsystem
+            .window("Mineplace3D", width, height)
+            .opengl()
+            .hidden()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let (gl_context, gl) = create_gl_context(&video_subsystem, &window, false)?;
+        let gl = Arc::new(gl);
+        let event_pump = sdl.event_pump()?;
+
+        log::info!("Headless app initialization complete");
+
+        Ok(Self {
+            sdl,
+            window,
+            _gl_context: gl_context,
+            gl,
+            event_pump,
+        })
+    }
 }
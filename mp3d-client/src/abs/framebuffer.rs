@@ -23,6 +23,36 @@ pub enum ColorUsage {
     R32F,
 }
 
+impl ColorUsage {
+    /// Returns the internal format, format and type of the texture backing this attachment.
+    ///
+    /// OpenGL ES can only render to floating point textures with an extension. Without it float
+    /// attachments fall back to 8 bits per channel, which loses the HDR range but still renders.
+    fn texture_formats(self, gl: &glow::Context) -> (i32, u32, u32) {
+        let embedded = gl.version().is_embedded;
+        match self {
+            ColorUsage::RGBA8 => (glow::RGBA8 as i32, glow::RGBA, glow::UNSIGNED_BYTE),
+            ColorUsage::R8 => (glow::R8 as i32, glow::RED, glow::UNSIGNED_BYTE),
+            ColorUsage::RGB16F | ColorUsage::R32F if !supports_float_color(gl) => {
+                (glow::RGBA8 as i32, glow::RGBA, glow::UNSIGNED_BYTE)
+            }
+            // RGB16F is not color renderable on OpenGL ES, even with the extension
+            ColorUsage::RGB16F if embedded => (glow::RGBA16F as i32, glow::RGBA, glow::HALF_FLOAT),
+            ColorUsage::RGB16F => (glow::RGB16F as i32, glow::RGB, glow::HALF_FLOAT),
+            ColorUsage::R32F => (glow::R32F as i32, glow::RED, glow::FLOAT),
+        }
+    }
+}
+
+/// Returns whether the context can render to floating point color attachments.
+pub fn supports_float_color(gl: &glow::Context) -> bool {
+    !gl.version().is_embedded || {
+        let extensions = gl.supported_extensions();
+        extensions.contains("GL_EXT_color_buffer_float")
+            || extensions.contains("GL_EXT_color_buffer_half_float")
+    }
+}
+
 /// Represents an OpenGL framebuffer.
 pub struct Framebuffer {
     gl: Arc<glow::Context>,
@@ -61,12 +91,7 @@ impl Framebuffer {
                     let tex = gl.create_texture().unwrap();
                     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
 
-                    let (internal, format, ty) = match color_usage {
-                        ColorUsage::RGBA8 => (glow::RGBA8 as i32, glow::RGBA, glow::UNSIGNED_BYTE),
-                        ColorUsage::R8 => (glow::R8 as i32, glow::RED, glow::UNSIGNED_BYTE),
-                        ColorUsage::RGB16F => (glow::RGB16F as i32, glow::RGB, glow::HALF_FLOAT),
-                        ColorUsage::R32F => (glow::R32F as i32, glow::RED, glow::FLOAT),
-                    };
+                    let (internal, format, ty) = color_usage.texture_formats(gl);
 
                     gl.tex_image_2d(
                         glow::TEXTURE_2D,
@@ -204,6 +229,7 @@ impl Framebuffer {
             }
 
             gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            crate::check_gl_error!(gl, "creating a {}x{} framebuffer", width, height);
 
             Self {
                 gl: gl.clone(),
@@ -239,17 +265,37 @@ impl Framebuffer {
         FramebufferGuard::new(self)
     }
 
+    /// Reads back the first color attachment as an image, e.g. to save what was rendered
+    /// offscreen. The rows are flipped so the image is upright.
+    #[allow(dead_code)]
+    pub fn read_pixels(&self) -> image::RgbaImage {
+        let mut pixels = vec![0u8; self.width as usize * self.height as usize * 4];
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.fbo));
+            self.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+            self.gl.read_pixels(
+                0,
+                0,
+                self.width,
+                self.height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(Some(&mut pixels)),
+            );
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        let image = image::RgbaImage::from_raw(self.width as u32, self.height as u32, pixels)
+            .expect("Pixel buffer has the size of the framebuffer");
+        image::imageops::flip_vertical(&image)
+    }
+
     /// Resizes the framebuffer to the specified width and height.
     pub fn resize(&mut self, width: i32, height: i32) {
         unsafe {
             for (i, color_tex) in self.color_texes.iter().enumerate() {
                 self.gl.bind_texture(glow::TEXTURE_2D, Some(color_tex.id));
-                let (internal, format, ty) = match self.color_usages[i] {
-                    ColorUsage::RGBA8 => (glow::RGBA8 as i32, glow::RGBA, glow::UNSIGNED_BYTE),
-                    ColorUsage::R8 => (glow::R8 as i32, glow::RED, glow::UNSIGNED_BYTE),
-                    ColorUsage::RGB16F => (glow::RGB16F as i32, glow::RGB, glow::HALF_FLOAT),
-                    ColorUsage::R32F => (glow::R32F as i32, glow::RED, glow::FLOAT),
-                };
+                let (internal, format, ty) = self.color_usages[i].texture_formats(&self.gl);
                 self.gl.tex_image_2d(
                     glow::TEXTURE_2D,
                     0,
@@ -281,16 +327,25 @@ impl Framebuffer {
                 self.width = width;
                 self.height = height;
             }
+            crate::check_gl_error!(self.gl, "resizing a framebuffer to {}x{}", width, height);
+            for tex in self.color_texes.iter_mut().chain(self.depth_tex.as_mut()) {
+                tex.width = width as u32;
+                tex.height = height as u32;
+            }
         }
     }
 
+    /// Returns the size of the framebuffer in pixels.
+    pub fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
     /// Returns the color texture of the framebuffer.
     pub fn textures(&self) -> &[Texture] {
         &self.color_texes
     }
 
     /// Returns the depth texture of the framebuffer, if it exists.
-    #[allow(unused)]
     pub fn depth_texture(&self) -> Option<&Texture> {
         self.depth_tex.as_ref()
     }
@@ -305,9 +360,12 @@ impl Drop for Framebuffer {
     }
 }
 
+/// Binds a framebuffer for as long as it is alive, restoring the previously bound framebuffer and
+/// viewport when dropped.
 pub struct FramebufferGuard {
     gl: Arc<glow::Context>,
     previous: Option<glow::NativeFramebuffer>,
+    previous_viewport: [i32; 4],
 }
 
 impl FramebufferGuard {
@@ -317,12 +375,17 @@ impl FramebufferGuard {
                 framebuffer.gl.get_parameter_i32(glow::FRAMEBUFFER_BINDING) as u32,
             )
             .map(glow::NativeFramebuffer);
+            let mut previous_viewport = [0; 4];
+            framebuffer
+                .gl
+                .get_parameter_i32_slice(glow::VIEWPORT, &mut previous_viewport);
 
             framebuffer.bind();
 
             Self {
                 gl: framebuffer.gl.clone(),
                 previous,
+                previous_viewport,
             }
         }
     }
@@ -332,6 +395,46 @@ impl Drop for FramebufferGuard {
     fn drop(&mut self) {
         unsafe {
             self.gl.bind_framebuffer(glow::FRAMEBUFFER, self.previous);
+            let [x, y, width, height] = self.previous_viewport;
+            self.gl.viewport(x, y, width, height);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abs::App;
+
+    #[test]
+    fn test_read_pixels_is_upright() {
+        // Machines without a display or OpenGL driver can't create the context at all
+        let app = match App::new_headless(4, 4) {
+            Ok(app) => app,
+            Err(e) => {
+                eprintln!("Skipping, no headless OpenGL context: {}", e);
+                return;
+            }
+        };
+        let framebuffer = Framebuffer::new(&app.gl, 4, 4, false, &[ColorUsage::RGBA8]);
+
+        {
+            let _guard = framebuffer.guard();
+            unsafe {
+                app.gl.clear_color(1.0, 0.0, 0.0, 1.0);
+                app.gl.clear(glow::COLOR_BUFFER_BIT);
+                // OpenGL counts rows from the bottom, so this clears the bottom half
+                app.gl.enable(glow::SCISSOR_TEST);
+                app.gl.scissor(0, 0, 4, 2);
+                app.gl.clear_color(0.0, 0.0, 1.0, 1.0);
+                app.gl.clear(glow::COLOR_BUFFER_BIT);
+                app.gl.disable(glow::SCISSOR_TEST);
+            }
         }
+
+        let image = framebuffer.read_pixels();
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 3).0, [0, 0, 255, 255]);
     }
 }
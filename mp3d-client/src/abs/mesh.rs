@@ -101,6 +101,12 @@ impl Mesh {
             gl.bind_vertex_array(None);
             gl.bind_buffer(glow::ARRAY_BUFFER, None);
             gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+            crate::check_gl_error!(
+                gl,
+                "creating a mesh with {} vertices and {} indices",
+                vertices.len(),
+                indices.len()
+            );
 
             Self {
                 gl: Arc::clone(gl),
@@ -155,6 +161,9 @@ impl Mesh {
         unsafe {
             self.index_count = indices.len();
 
+            // The element buffer binding is part of the VAO state, so bind it to not touch
+            // whatever VAO is currently bound.
+            self.gl.bind_vertex_array(Some(self.vao));
             self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
             self.gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
@@ -176,8 +185,8 @@ impl Mesh {
                 glow::DYNAMIC_DRAW,
             );
 
+            self.gl.bind_vertex_array(None);
             self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
-            self.gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
         }
     }
 
@@ -236,6 +245,9 @@ impl Drop for Mesh {
         unsafe {
             self.gl.delete_buffer(self.vbo);
             self.gl.delete_buffer(self.ebo);
+            if let Some(instance_vbo) = self.instance_vbo {
+                self.gl.delete_buffer(instance_vbo);
+            }
             self.gl.delete_vertex_array(self.vao);
         }
     }
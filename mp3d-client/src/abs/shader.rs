@@ -28,11 +28,16 @@ impl Shader {
             glow::COMPUTE_SHADER => "compute",
             _ => "unknown",
         };
+        let source = if gl.version().is_embedded {
+            to_gles(source)
+        } else {
+            source.to_string()
+        };
         unsafe {
             let shader = gl
                 .create_shader(shader_type)
                 .map_err(|e| format!("Failed to create {} shader: {}", kind, e))?;
-            gl.shader_source(shader, source);
+            gl.shader_source(shader, &source);
             gl.compile_shader(shader);
 
             if !gl.get_shader_compile_status(shader) {
@@ -52,6 +57,18 @@ impl Shader {
     }
 }
 
+/// Converts a `#version 330 core` shader to GLSL ES 3.00.
+///
+/// The shaders only use features both versions share, so apart from the version directive only
+/// default precisions have to be declared, which desktop GLSL does not require.
+fn to_gles(source: &str) -> String {
+    source.replacen(
+        "#version 330 core",
+        "#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2D;",
+        1,
+    )
+}
+
 impl Drop for Shader {
     fn drop(&mut self) {
         unsafe {
@@ -227,6 +244,7 @@ impl ShaderProgram {
             for shader in shaders {
                 gl.detach_shader(program, shader.id);
             }
+            crate::check_gl_error!(gl, "linking a shader program");
 
             log::info!("Linked shader program successfully");
 
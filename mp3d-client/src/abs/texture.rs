@@ -47,6 +47,17 @@ impl Texture {
         Self::new_bytes(gl, width, height, data)
     }
 
+    /// Creates the pink and black checkerboard used in place of missing textures.
+    pub fn placeholder(gl: &Arc<glow::Context>) -> Self {
+        let image_bytes = [
+            255, 0, 255, 255, // Pink
+            0, 0, 0, 255, // Black
+            0, 0, 0, 255, // Black
+            255, 0, 255, 255, // Pink
+        ];
+        Self::new_bytes(gl, 2, 2, image_bytes.to_vec())
+    }
+
     /// Creates a new texture from the given raw byte data.
     pub fn new_bytes(gl: &Arc<glow::Context>, width: u32, height: u32, data: Vec<u8>) -> Self {
         unsafe {
@@ -77,6 +88,7 @@ impl Texture {
                 glow::NEAREST as i32,
             );
             gl.bind_texture(glow::TEXTURE_2D, None);
+            crate::check_gl_error!(gl, "creating a {}x{} texture", width, height);
 
             log::info!("Created texture with ID {}", texture.0);
 
@@ -99,6 +111,25 @@ impl Texture {
         self.height
     }
 
+    /// Switches the texture to linear filtering and clamps it at the edges, for textures that are
+    /// sampled as data rather than drawn as pixel art.
+    pub fn smooth(self) -> Self {
+        unsafe {
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
+            for (param, value) in [
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+            ] {
+                self.gl
+                    .tex_parameter_i32(glow::TEXTURE_2D, param, value as i32);
+            }
+            self.gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        self
+    }
+
     /// Returns a handle to the texture.
     pub fn handle(&self) -> TextureHandle {
         TextureHandle(self.id.0, self.width, self.height)
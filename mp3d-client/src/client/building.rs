@@ -0,0 +1,76 @@
+//! Placing many blocks at once, by dragging a line or a rectangle of blocks.
+
+use glam::IVec3;
+use mp3d_core::{direction::Direction, protocol::MAX_PLACED_BLOCKS};
+
+/// The shape of the blocks placed by dragging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildShape {
+    /// A straight line along one axis, snapped to the axis the drag moved along the most.
+    Line,
+    /// A rectangle on the plane of the clicked face.
+    Rectangle,
+}
+
+/// Blocks being dragged out while the right mouse button is held, placed once it is released.
+#[derive(Clone, Copy, Debug)]
+pub struct BuildDrag {
+    pub shape: BuildShape,
+    /// The position of the first block, in front of the clicked face.
+    pub start: IVec3,
+    pub face: Direction,
+    /// The position the drag currently reaches, snapped to the shape.
+    pub end: IVec3,
+}
+
+impl BuildDrag {
+    pub fn new(shape: BuildShape, start: IVec3, face: Direction) -> Self {
+        Self {
+            shape,
+            start,
+            face,
+            end: start,
+        }
+    }
+
+    /// Moves the end of the drag to the block in front of a targeted face, snapping it to the
+    /// shape.
+    pub fn drag_to(&mut self, target: IVec3) {
+        let delta = target - self.start;
+        let snapped = match self.shape {
+            BuildShape::Line => {
+                let abs = delta.abs();
+                if abs.x >= abs.y && abs.x >= abs.z {
+                    IVec3::new(delta.x, 0, 0)
+                } else if abs.y >= abs.z {
+                    IVec3::new(0, delta.y, 0)
+                } else {
+                    IVec3::new(0, 0, delta.z)
+                }
+            }
+            BuildShape::Rectangle => {
+                let normal = IVec3::ZERO + self.face;
+                delta - normal * delta.dot(normal)
+            }
+        };
+        self.end = self.start + snapped;
+    }
+
+    /// Returns the positions of the blocks to place, starting from the start of the drag and
+    /// cut off at [`MAX_PLACED_BLOCKS`].
+    pub fn positions(&self) -> Vec<IVec3> {
+        let min = self.start.min(self.end);
+        let max = self.start.max(self.end);
+        let mut positions = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    positions.push(IVec3::new(x, y, z));
+                }
+            }
+        }
+        positions.sort_by_key(|position| (*position - self.start).abs().element_sum());
+        positions.truncate(MAX_PLACED_BLOCKS);
+        positions
+    }
+}
@@ -0,0 +1,112 @@
+//! The camera the world is drawn from.
+//!
+//! A [`Camera`] is placed every frame by [`crate::client::Client::place_camera`], at the eyes of
+//! the player, behind them in third person, along a flythrough or at the spectated player. It
+//! keeps the aspect ratio of the window between frames, which [`Camera::resize`] updates.
+
+use glam::{IVec3, Mat4, UVec2, Vec3, Vec4};
+use mp3d_core::physics;
+
+use crate::client::world::ClientWorld;
+
+/// The near and far planes of the camera projection.
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 1000.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    pub position: Vec3,
+    /// The direction the camera looks in, of length 1.
+    pub direction: Vec3,
+    pub up: Vec3,
+    /// The vertical field of view in degrees.
+    pub fov: f32,
+    pub aspect_ratio: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    /// Creates a camera at `position` looking along the given yaw and pitch in degrees.
+    pub fn new(position: Vec3, yaw: f32, pitch: f32, fov: f32) -> Self {
+        Self {
+            position,
+            direction: physics::look_direction(yaw, pitch),
+            up: Vec3::Y,
+            fov,
+            aspect_ratio: 1.0,
+            near: NEAR_PLANE,
+            far: FAR_PLANE,
+        }
+    }
+
+    /// Turns the camera to look along the given yaw and pitch in degrees.
+    pub fn look_at_angles(&mut self, yaw: f32, pitch: f32) {
+        self.direction = physics::look_direction(yaw, pitch);
+        self.up = Vec3::Y;
+    }
+
+    /// Fits the projection to a window of the given size.
+    pub fn resize(&mut self, size: UVec2) {
+        self.aspect_ratio = size.x.max(1) as f32 / size.y.max(1) as f32;
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_to_rh(self.position, self.direction, self.up)
+    }
+
+    pub fn projection(&self) -> Mat4 {
+        Mat4::perspective_rh_gl(
+            self.fov.to_radians(),
+            self.aspect_ratio,
+            self.near,
+            self.far,
+        )
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+
+    /// Returns the frustum planes of the camera, which can be used for frustum culling of chunks.
+    pub fn frustum_planes(&self) -> [Vec4; 6] {
+        let m = self.view_projection().to_cols_array_2d();
+
+        let row0 = Vec4::new(m[0][0], m[1][0], m[2][0], m[3][0]);
+        let row1 = Vec4::new(m[0][1], m[1][1], m[2][1], m[3][1]);
+        let row2 = Vec4::new(m[0][2], m[1][2], m[2][2], m[3][2]);
+        let row3 = Vec4::new(m[0][3], m[1][3], m[2][3], m[3][3]);
+
+        let mut planes = [
+            row3 + row0, // left
+            row3 - row0, // right
+            row3 + row1, // bottom
+            row3 - row1, // top
+            row3 + row2, // near
+            row3 - row2, // far
+        ];
+
+        // normalize planes
+        for plane in planes.iter_mut() {
+            let n = plane.truncate().length();
+            *plane /= n;
+        }
+
+        planes
+    }
+
+    /// Returns the position and normal of the first block the camera looks at within
+    /// `max_distance`, or `None` if no block is hit.
+    pub fn cast_ray(&self, world: &ClientWorld, max_distance: f32) -> Option<(IVec3, IVec3)> {
+        physics::cast_ray(
+            |pos| {
+                world
+                    .get_block_at(pos)
+                    .map(|(block, state)| (block, *state))
+            },
+            self.position,
+            self.direction,
+            max_distance,
+        )
+    }
+}
@@ -0,0 +1,55 @@
+//! The chat messages shown to the player, kept up to a limit and optionally logged to disk.
+
+use std::collections::VecDeque;
+
+use mp3d_core::{chatlog::ChatLog, textcomponent::TextComponent};
+
+/// The received chat messages, oldest first. Once there are more than the limit, the oldest ones
+/// are dropped.
+pub struct ChatHistory {
+    messages: VecDeque<TextComponent>,
+    limit: usize,
+    log: Option<ChatLog>,
+}
+
+impl ChatHistory {
+    /// Creates an empty history keeping at most `limit` messages, writing every message and sent
+    /// line to `log` if given.
+    pub fn new(limit: usize, log: Option<ChatLog>) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            limit: limit.max(1),
+            log,
+        }
+    }
+
+    pub fn push(&mut self, message: TextComponent) {
+        if let Some(log) = &mut self.log {
+            log.write(&message.plain_text());
+        }
+        if self.messages.len() >= self.limit {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Logs a message or command the player sent. It isn't added to the history, the server sends
+    /// back what should be shown.
+    pub fn log_sent(&mut self, line: &str) {
+        if let Some(log) = &mut self.log {
+            log.write(&format!("> {}", line));
+        }
+    }
+
+    /// Returns the number of lines the messages take up.
+    pub fn line_count(&self) -> usize {
+        self.messages
+            .iter()
+            .map(|message| message.lines().len())
+            .sum()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &TextComponent> {
+        self.messages.iter()
+    }
+}
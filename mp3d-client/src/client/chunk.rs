@@ -3,6 +3,7 @@
 use glam::IVec3;
 use mp3d_core::{
     block::{BlockId, BlockState},
+    direction::Direction,
     world::chunk::Chunk,
 };
 
@@ -16,12 +17,19 @@ pub struct ClientChunk {
     pub chunk: Chunk,
     /// Indicates whether the chunk needs to be re-rendered.
     pub dirty: bool,
+    /// The sides of the chunk made only of opaque full blocks, one bit for every
+    /// [`Direction`](mp3d_core::direction::Direction). Worked out when the chunk is meshed.
+    pub full_sides: u8,
 }
 
 impl ClientChunk {
     /// Creates a new [`ClientChunk`] with the given core [`Chunk`].
     pub fn new(chunk: Chunk) -> Self {
-        Self { chunk, dirty: true }
+        Self {
+            chunk,
+            dirty: true,
+            full_sides: 0,
+        }
     }
 
     /// Gets a block at the given local position within the chunk.
@@ -29,6 +37,11 @@ impl ClientChunk {
         self.chunk.get_block(local_pos)
     }
 
+    /// Returns whether the given side of the chunk is made only of opaque full blocks.
+    pub fn is_side_full(&self, side: Direction) -> bool {
+        self.full_sides & (1 << side as u8) != 0
+    }
+
     /// Sets a block at the given local position within the chunk.
     pub fn set_block(&mut self, local_pos: IVec3, block: BlockId, state: BlockState) {
         self.chunk.set_block(local_pos, block, state);
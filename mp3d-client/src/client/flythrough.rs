@@ -0,0 +1,73 @@
+//! Flythroughs, which move the camera smoothly through keyframes recorded with the /camera
+//! command.
+
+use glam::Vec3;
+use mp3d_core::protocol::CameraKeyframe;
+
+/// A flythrough being played. The camera passes through every keyframe on a Catmull-Rom spline,
+/// spending the same time between each pair of keyframes.
+#[derive(Clone, Debug)]
+pub struct Flythrough {
+    keyframes: Vec<CameraKeyframe>,
+    /// How long the whole flythrough takes, in seconds.
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Flythrough {
+    /// Makes a flythrough through at least one keyframe. The yaw of every keyframe is unwrapped
+    /// so the camera turns the short way around to the next one.
+    pub fn new(mut keyframes: Vec<CameraKeyframe>, duration: f32) -> Self {
+        for i in 1..keyframes.len() {
+            let previous = keyframes[i - 1].yaw;
+            let delta = (keyframes[i].yaw - previous + 180.0).rem_euclid(360.0) - 180.0;
+            keyframes[i].yaw = previous + delta;
+        }
+        Self {
+            keyframes,
+            duration: duration.max(f32::EPSILON),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Moves the flythrough forward by `dt` seconds. Returns true once it is done.
+    pub fn advance(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        self.elapsed >= self.duration
+    }
+
+    /// Returns the camera at the current point of the flythrough.
+    pub fn camera(&self) -> CameraKeyframe {
+        let last = self.keyframes.len() - 1;
+        let progress = (self.elapsed / self.duration).clamp(0.0, 1.0) * last as f32;
+        let i = (progress.floor() as usize).min(last.saturating_sub(1));
+        let t = progress - i as f32;
+
+        let keyframe = |index: isize| self.keyframes[index.clamp(0, last as isize) as usize];
+        let i = i as isize;
+        let points = [
+            keyframe(i - 1),
+            keyframe(i),
+            keyframe(i + 1),
+            keyframe(i + 2),
+        ];
+        let spline = |values: [Vec3; 4]| catmull_rom(values, t);
+        let position = spline(points.map(|k| k.position));
+        let angles = spline(points.map(|k| Vec3::new(k.yaw, k.pitch, 0.0)));
+        CameraKeyframe {
+            position,
+            yaw: angles.x,
+            pitch: angles.y.clamp(-89.0, 89.0),
+        }
+    }
+}
+
+/// Interpolates between `p[1]` at `t = 0` and `p[2]` at `t = 1` on a uniform Catmull-Rom spline.
+fn catmull_rom(p: [Vec3; 4], t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p[1]
+        + (p[2] - p[0]) * t
+        + (2.0 * p[0] - 5.0 * p[1] + 4.0 * p[2] - p[3]) * t2
+        + (3.0 * p[1] - p[0] - 3.0 * p[2] + p[3]) * t3)
+}
@@ -0,0 +1,58 @@
+//! The measuring tool, which shows how far apart two clicked blocks are.
+
+use glam::IVec3;
+
+/// The blocks picked with the measuring tool. Picking a block after both are picked starts a new
+/// measurement.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Measurement {
+    pub first: Option<IVec3>,
+    pub second: Option<IVec3>,
+}
+
+impl Measurement {
+    /// Picks the next block of the measurement.
+    pub fn pick(&mut self, position: IVec3) {
+        if self.first.is_none() || self.second.is_some() {
+            self.first = Some(position);
+            self.second = None;
+        } else {
+            self.second = Some(position);
+        }
+    }
+
+    /// Returns the corners of the box spanned by the picked blocks, which is just the first block
+    /// until the second is picked.
+    pub fn bounds(&self) -> Option<(IVec3, IVec3)> {
+        let first = self.first?;
+        let second = self.second.unwrap_or(first);
+        Some((first.min(second), first.max(second)))
+    }
+
+    /// Describes the measurement: the distance along every axis and in a straight line between
+    /// the centers of the picked blocks, and the size and volume of the box they span.
+    pub fn describe(&self) -> String {
+        let (Some(first), Some(second)) = (self.first, self.second) else {
+            return match self.first {
+                Some(first) => format!(
+                    "Measuring from {} {} {}, click a second block",
+                    first.x, first.y, first.z
+                ),
+                None => "Measuring, click two blocks".to_string(),
+            };
+        };
+        let delta = second - first;
+        let size = delta.abs() + IVec3::ONE;
+        format!(
+            "dX {} dY {} dZ {} | Distance {:.2} | Box {}x{}x{} = {} blocks",
+            delta.x,
+            delta.y,
+            delta.z,
+            delta.as_vec3().length(),
+            size.x,
+            size.y,
+            size.z,
+            size.element_product()
+        )
+    }
+}
@@ -1,34 +1,101 @@
-//! Client to interact with a local server.
+//! Client to interact with a local or remote server.
 //!
 //! This module provides functionality to connect to a server, where if the client is using a local
-//! connection, it directly calls the server's message handling functions. Remote connections are
-//! not implemented yet.
+//! connection, it directly calls the server's message handling functions. A remote connection
+//! sends the messages over TCP to a dedicated server instead.
 //!
-//! The module also provides a [`Connection`] trait and a [`LocalConnection`] struct that implements
-//! this trait for local server interactions.
+//! The module also provides a [`Connection`] trait with the [`LocalConnection`] and
+//! [`RemoteConnection`] structs that implement it.
 
+pub mod building;
+pub mod camera;
+pub mod chat;
 pub mod chunk;
 mod emoji;
+pub mod flythrough;
+pub mod measure;
 pub mod player;
+mod shortcuts;
+pub mod spectate;
 pub mod world;
 
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufReader, BufWriter, Write},
+    net::{TcpStream, ToSocketAddrs},
+    rc::Rc,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
 
-use glam::{IVec3, Vec3};
+use glam::{IVec3, Vec2, Vec3};
 use mp3d_core::{
     block::block_registry,
-    protocol::{C2SMessage, MoveInstructions, S2CMessage},
+    direction::Direction,
+    entity::{Entity, MAX_HEALTH, PlayerEntity},
+    item::{ItemId, item_registry},
+    mirror::Mirror,
+    net::{self, read_message, write_message},
+    physics,
+    preferences::Preferences,
+    protocol::{C2SMessage, MAX_PLACED_BLOCKS, MoveInstructions, S2CMessage},
     server::Server,
-    textcomponent::TextComponent,
+    textcomponent::{TextComponent, sanitize},
 };
 use sdl2::keyboard::Keycode;
 
 use crate::{
-    client::{player::ClientInventory, world::ClientWorld},
-    other::UpdateContext,
+    client::{
+        building::{BuildDrag, BuildShape},
+        camera::Camera,
+        flythrough::Flythrough,
+        measure::Measurement,
+        player::{ClientContainer, ClientInventory},
+        spectate::Spectating,
+        world::{ClientEntity, ClientWorld},
+    },
+    input::{Action, UpdateContext},
     render::particles::ParticleSystem,
+    resource::lang::Translations,
+    scenes::options::ClientConfig,
 };
 
+/// How quickly the camera catches up with the mouse in cinematic mode, higher is snappier.
+const CINEMATIC_SMOOTHING: f32 = 6.0;
+
+/// How quickly the camera catches up with the mouse when mouse smoothing is enabled. This is
+/// much snappier than cinematic mode and only takes the edge off jittery mice.
+const MOUSE_SMOOTHING: f32 = 30.0;
+
+/// How many seconds a message stays above the hotbar.
+const ACTIONBAR_TIME: f32 = 3.0;
+
+/// How many seconds a death stays in the kill feed.
+const KILL_FEED_TIME: f32 = 6.0;
+/// The most deaths shown in the kill feed at once, older ones are dropped.
+const KILL_FEED_LEN: usize = 5;
+
+/// How long to wait for a server to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The number of chat lines shown at once.
+pub const CHAT_LINES: usize = 10;
+
+/// The number of sent messages and commands remembered to recall with the arrow keys, also
+/// between sessions.
+const CHAT_HISTORY_LEN: usize = 50;
+
+/// How many lines a step of the mouse wheel scrolls the chat by.
+const CHAT_SCROLL_STEP: usize = 2;
+
+/// The number of recently placed blocks remembered for the palette.
+pub const RECENT_BLOCKS: usize = 8;
+
+/// How far the mouse has to move after opening the palette to point at one of its blocks.
+const PALETTE_DEADZONE: f32 = 40.0;
+
 /// The [`Connection`] trait defines the interface for client-server communication.
 pub trait Connection {
     /// Sends a message to the server.
@@ -42,6 +109,33 @@ pub trait Connection {
 
     // Receives messages from the server.
     fn receive(&mut self) -> Vec<S2CMessage>;
+
+    /// The server running in this process, if the connection is local.
+    fn local_server(&mut self) -> Option<&mut Server> {
+        None
+    }
+}
+
+impl<C: Connection + ?Sized> Connection for Box<C> {
+    fn send(&mut self, message: C2SMessage) {
+        (**self).send(message);
+    }
+
+    fn flush(&mut self) {
+        (**self).flush();
+    }
+
+    fn tick(&mut self, tps: u8) {
+        (**self).tick(tps);
+    }
+
+    fn receive(&mut self) -> Vec<S2CMessage> {
+        (**self).receive()
+    }
+
+    fn local_server(&mut self) -> Option<&mut Server> {
+        (**self).local_server()
+    }
 }
 
 /// A local connection that directly interacts with a server instance.
@@ -91,11 +185,89 @@ impl Connection for LocalConnection {
             vec![]
         }
     }
+
+    fn local_server(&mut self) -> Option<&mut Server> {
+        Some(&mut self.server)
+    }
+}
+
+/// A connection to a dedicated server over TCP, see [`mp3d_core::net`].
+///
+/// Sent messages are buffered until the connection is flushed. Received messages are read on a
+/// separate thread, so that waiting for the server never holds up a frame. When the connection
+/// breaks, a [`S2CMessage::ConnectionFailed`] with the reason is received.
+pub struct RemoteConnection {
+    writer: BufWriter<TcpStream>,
+    messages: Receiver<S2CMessage>,
+}
+
+impl RemoteConnection {
+    /// Connects to the server at the given address, using [`net::DEFAULT_PORT`] if the address
+    /// has no port.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let address = net::with_default_port(address);
+        log::info!("Connecting to {}", address);
+        let socket_address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "The address could not be resolved")
+        })?;
+        let stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let (sender, messages) = mpsc::channel();
+        thread::Builder::new()
+            .name("connection reader".to_string())
+            .spawn(move || {
+                loop {
+                    match read_message(&mut reader) {
+                        Ok(message) => {
+                            if sender.send(message).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => {
+                            let reason = if e.kind() == io::ErrorKind::UnexpectedEof {
+                                "The server closed the connection".to_string()
+                            } else {
+                                e.to_string()
+                            };
+                            let _ = sender.send(S2CMessage::ConnectionFailed { reason });
+                            return;
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            writer: BufWriter::new(stream),
+            messages,
+        })
+    }
+}
+
+impl Connection for RemoteConnection {
+    fn send(&mut self, message: C2SMessage) {
+        // A broken connection is noticed by the reader, which reports it through `receive`
+        if let Err(e) = write_message(&mut self.writer, &message) {
+            log::warn!("Failed to send message: {}", e);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            log::warn!("Failed to send messages: {}", e);
+        }
+    }
+
+    fn receive(&mut self) -> Vec<S2CMessage> {
+        self.messages.try_iter().collect()
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct ChatGUI {
     pub message: String,
+    /// How many lines the chat is scrolled up from the latest one.
     pub scroll: usize,
     pub ghost: Option<usize>,
 }
@@ -108,6 +280,46 @@ impl ChatGUI {
             ghost: None,
         }
     }
+
+    /// Scrolls the chat up by `lines`, or down for negative values, without going past the oldest
+    /// or the latest line of the `line_count` lines.
+    pub fn scroll_by(&mut self, lines: isize, line_count: usize) {
+        let max = line_count.saturating_sub(CHAT_LINES);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+}
+
+/// The palette of recently placed blocks, open while its key is held. The blocks are laid out in
+/// a circle, and the mouse points at one of them.
+#[derive(Debug, Default)]
+pub struct PaletteGUI {
+    /// How far the mouse moved since the palette was opened, kept within the deadzone radius.
+    pub pointer: Vec2,
+}
+
+impl PaletteGUI {
+    /// Returns which of `count` blocks around the circle is pointed at, starting at the top and
+    /// going clockwise.
+    pub fn hovered(&self, count: usize) -> Option<usize> {
+        if count == 0 || self.pointer.length() < PALETTE_DEADZONE * 0.5 {
+            return None;
+        }
+        let step = std::f32::consts::TAU / count as f32;
+        let angle = (self.pointer.y.atan2(self.pointer.x) + std::f32::consts::FRAC_PI_2)
+            .rem_euclid(std::f32::consts::TAU);
+        Some((angle / step).round() as usize % count)
+    }
+}
+
+/// Why the client stopped being connected to the server.
+#[derive(Debug, Clone)]
+pub enum Disconnection {
+    /// The connection broke or the server refused it, with the reason.
+    Lost(String),
+    /// The server kicked the player, with the reason.
+    Kicked(String),
+    /// The server sent the player to the server at the address.
+    Transferred(String),
 }
 
 /// An enum representing the different GUIs that can be opened on the client.
@@ -116,7 +328,20 @@ pub enum CurrentGUI {
     None,
     Chat(ChatGUI),
     Inventory,
+    /// The container in [`Client::container`].
+    Container,
+    /// The list of all items, to pick them into the hotbar.
+    CreativePicker,
+    /// The palette of recently placed blocks, see [`Client::recent_blocks`].
+    RecentPalette(PaletteGUI),
     PauseMenu,
+    /// The editor for moving and resizing the HUD, opened from the pause menu.
+    HudEditor,
+    /// The overlay listing the controls, shown on the first launch and opened from the pause
+    /// menu.
+    Controls,
+    /// The screen shown while the player is dead, until they respawn.
+    Death,
 }
 
 impl CurrentGUI {
@@ -136,38 +361,130 @@ impl CurrentGUI {
         matches!(self, CurrentGUI::Inventory)
     }
 
+    pub fn container(&self) -> bool {
+        matches!(self, CurrentGUI::Container)
+    }
+
+    pub fn creative_picker(&self) -> bool {
+        matches!(self, CurrentGUI::CreativePicker)
+    }
+
+    pub fn recent_palette(&self) -> Option<&PaletteGUI> {
+        if let CurrentGUI::RecentPalette(gui) = self {
+            Some(gui)
+        } else {
+            None
+        }
+    }
+
     pub fn pause_menu(&self) -> bool {
         matches!(self, CurrentGUI::PauseMenu)
     }
+
+    pub fn hud_editor(&self) -> bool {
+        matches!(self, CurrentGUI::HudEditor)
+    }
+
+    pub fn controls(&self) -> bool {
+        matches!(self, CurrentGUI::Controls)
+    }
+
+    pub fn death(&self) -> bool {
+        matches!(self, CurrentGUI::Death)
+    }
+}
+
+/// Keeps track of the last movement sent to the server, to skip sending unchanged movement.
+#[derive(Default)]
+struct MoveDelta {
+    last: Option<MoveInstructions>,
+}
+
+impl MoveDelta {
+    /// Returns the movement to send to the server, or `None` if it already has this movement.
+    /// Without `compress` every movement is sent.
+    fn next(&mut self, input: MoveInstructions, compress: bool) -> Option<MoveInstructions> {
+        if compress && self.last == Some(input) {
+            return None;
+        }
+        self.last = Some(input);
+        Some(input)
+    }
+
+    /// Forgets the last movement sent, so the next one is sent even if it didn't change. The
+    /// server stops the player when they die, so holding the same keys after respawning has to
+    /// send them again.
+    fn reset(&mut self) {
+        self.last = None;
+    }
 }
 
 /// The client struct that uses a connection to communicate with the server.
 pub struct Client<C: Connection> {
     pub connection: C,
+    pub username: String,
     pub player: player::ClientPlayer,
     pub user_id: Option<u64>,
     pub entity_id: Option<u64>,
+    /// The seed of the stars, see [`mp3d_core::world::sky`].
+    pub sky_seed: Option<u64>,
     pub gui: CurrentGUI,
-    pub messages: Vec<TextComponent>,
+    pub messages: chat::ChatHistory,
     pub world: ClientWorld,
     pub chat_hist: Vec<String>,
+    /// The team color code of every player in a team, keyed by username.
+    pub team_colors: HashMap<String, u8>,
+    /// The last movement sent to the server, used to skip sending unchanged movement.
+    move_delta: MoveDelta,
+    /// Whether the left mouse button is held down on a block, so the server is breaking it.
+    breaking: bool,
+    /// The block the player is breaking, to mirror it once it breaks.
+    break_target: Option<IVec3>,
+    /// How far the player is in breaking the block they are looking at, from `0.0` to `1.0`.
+    pub break_progress: f32,
+    /// The container the player has open, shown while the GUI is [`CurrentGUI::Container`].
+    pub container: Option<Rc<RefCell<ClientContainer>>>,
+    /// The items of the blocks the player placed most recently, the latest first.
+    pub recent_blocks: Vec<ItemId>,
+    /// The planes placed and broken blocks are mirrored over, if mirrored building is on.
+    pub mirror: Option<Mirror>,
+    /// The line or rectangle of blocks being dragged out with the right mouse button.
+    pub build_drag: Option<BuildDrag>,
+    /// The blocks picked with the measuring tool, while left clicks measure instead of breaking.
+    pub measurement: Option<Measurement>,
+    /// The message shown above the hotbar and for how many more seconds, see
+    /// [`Client::actionbar`].
+    actionbar: Option<(TextComponent, f32)>,
+    /// The recent deaths shown in the top right corner, oldest first, with how many more seconds
+    /// each is shown.
+    pub kill_feed: Vec<(TextComponent, f32)>,
+    /// How the player died last, shown on the death screen.
+    pub death_message: Option<TextComponent>,
+    /// The flythrough moving the camera, started with the /camera command.
+    pub flythrough: Option<Flythrough>,
+    /// The player being spectated, started with the /spectate command.
+    pub spectating: Option<Spectating>,
 }
 
 impl<C: Connection> Client<C> {
     /// Creates a new `Client` with the given connection and credentials. If password is `None`, it
     /// will use default password "SINGLEPLAYER". The client will send a `Connect` message to the
-    /// server with the provided credentials upon initialization.
-    pub fn new(mut connection: C, username: String, password: Option<String>) -> Self {
+    /// server with the provided credentials and preferences upon initialization. Received chat
+    /// messages are kept in `messages`.
+    pub fn new(
+        mut connection: C,
+        username: String,
+        preferences: Preferences,
+        password: Option<String>,
+        messages: chat::ChatHistory,
+    ) -> Self {
         log::info!("Creating client with username '{}'", username);
 
-        if let Some(password) = password {
-            connection.send(C2SMessage::Connect { username, password });
-        } else {
-            connection.send(C2SMessage::Connect {
-                username,
-                password: "SINGLEPLAYER".to_string(),
-            });
-        }
+        connection.send(C2SMessage::Connect {
+            username: username.clone(),
+            password: password.unwrap_or_else(|| "SINGLEPLAYER".to_string()),
+            preferences,
+        });
 
         let game_dir = crate::get_game_dir();
         let chat_hist = std::fs::read_to_string(game_dir.join("chat_history.txt"))
@@ -178,50 +495,244 @@ impl<C: Connection> Client<C> {
 
         Self {
             connection,
+            username,
             player: player::ClientPlayer {
                 position: Vec3::new(0.0, 100.0, 0.0),
                 velocity: Vec3::ZERO,
                 yaw: 0.0,
                 delta_yaw: 0.0,
                 pitch: 0.0,
-                fov: 90.0,
+                fov: player::DEFAULT_FOV,
                 flying: false,
                 on_ground: false,
                 input: MoveInstructions::default(),
                 inventory: Rc::new(RefCell::new(ClientInventory::new())),
                 third_person: false,
+                cinematic: false,
+                smoothed_look: Vec2::ZERO,
+                health: MAX_HEALTH,
             },
             user_id: None,
             entity_id: None,
+            sky_seed: None,
             gui: CurrentGUI::None,
-            messages: vec![],
+            messages,
             world: ClientWorld::new(),
             chat_hist,
+            team_colors: HashMap::new(),
+            move_delta: MoveDelta::default(),
+            breaking: false,
+            break_target: None,
+            break_progress: 0.0,
+            container: None,
+            recent_blocks: Vec::new(),
+            mirror: None,
+            build_drag: None,
+            measurement: None,
+            actionbar: None,
+            kill_feed: Vec::new(),
+            death_message: None,
+            flythrough: None,
+            spectating: None,
+        }
+    }
+
+    /// Shows a message above the hotbar for [`ACTIONBAR_TIME`] seconds.
+    pub fn show_actionbar(&mut self, message: &str) {
+        self.actionbar = Some((message.parse().unwrap(), ACTIONBAR_TIME));
+    }
+
+    /// Returns what the crosshair points at within reach.
+    pub fn crosshair_target(&self) -> CrosshairTarget {
+        let camera = self.player.eye_camera();
+        let block = camera.cast_ray(&self.world, physics::REACH_DISTANCE);
+        let block_distance = block
+            .map(|(pos, _)| camera.position.distance(pos.as_vec3() + Vec3::splat(0.5)))
+            .unwrap_or(physics::REACH_DISTANCE);
+
+        // Every entity is as big as a player for now
+        let half_width = PlayerEntity::width() / 2.0;
+        let height = PlayerEntity::height();
+        let entity_hit = self
+            .world
+            .entities
+            .iter()
+            .filter(|(id, _)| Some(**id) != self.entity_id)
+            .filter_map(|(_, entity)| {
+                let min = entity.position - Vec3::new(half_width, 0.0, half_width);
+                let max = entity.position + Vec3::new(half_width, height, half_width);
+                ray_box_distance(camera.position, camera.direction, min, max)
+            })
+            .any(|distance| distance < block_distance);
+        if entity_hit {
+            return CrosshairTarget::Entity;
+        }
+
+        match block.and_then(|(pos, _)| self.world.get_block_at(pos)) {
+            Some((block, _)) => match block_registry()
+                .get(block)
+                .and_then(|def| def.interact_hint)
+            {
+                Some(hint) => CrosshairTarget::Interactable(hint),
+                None => CrosshairTarget::Block,
+            },
+            None => CrosshairTarget::None,
+        }
+    }
+
+    /// Returns the message to show above the hotbar. While measuring, this is the measurement.
+    pub fn actionbar(&self) -> Option<TextComponent> {
+        if let Some(spectating) = &self.spectating {
+            return format!(
+                "Spectating {}, sneak to stop",
+                sanitize(&spectating.username)
+            )
+            .parse()
+            .ok();
+        }
+        if let Some(measurement) = &self.measurement {
+            return measurement.describe().parse().ok();
         }
+        self.actionbar.as_ref().map(|(message, _)| message.clone())
+    }
+
+    /// Moves the camera to where the world is seen from, which is the player unless a flythrough
+    /// is playing or another player is spectated.
+    pub fn place_camera(&self, camera: &mut Camera) {
+        camera.fov = self.player.fov;
+        let keyframe = match &self.flythrough {
+            Some(flythrough) => Some(flythrough.camera()),
+            None => self.spectating.as_ref().and_then(Spectating::camera),
+        };
+        if let Some(keyframe) = keyframe {
+            camera.position = keyframe.position;
+            camera.look_at_angles(keyframe.yaw, keyframe.pitch);
+            return;
+        }
+        camera.position = if self.player.third_person {
+            self.player.third_person_eye(&self.world)
+        } else {
+            self.player.first_person_eye()
+        };
+        camera.look_at_angles(self.player.yaw, self.player.pitch);
+    }
+
+    /// Returns the position chunks are loaded around, which follows the spectated player.
+    pub fn view_center(&self) -> Vec3 {
+        self.spectating
+            .as_ref()
+            .and_then(Spectating::position)
+            .unwrap_or(self.player.position)
+    }
+
+    /// Sends a chat message or command to the server without adding it to the history recalled
+    /// with the arrow keys, like one sent by clicking a message.
+    pub fn send_chat(&mut self, message: String) {
+        self.messages.log_sent(&message);
+        self.connection.send(C2SMessage::SendMessage { message });
+    }
+
+    /// Asks the server to bring the dead player back to life. The death screen closes once the
+    /// server restores their health.
+    pub fn respawn(&mut self) {
+        self.connection.send(C2SMessage::Respawn);
     }
 
     /// Takes in player input and sends it to the server through the connection.
-    pub fn send_input(&mut self, update_context: &UpdateContext, dt: f32, sensitivity: f32) {
-        if update_context.keyboard.pressed.contains(&Keycode::Escape) {
+    pub fn send_input(
+        &mut self,
+        update_context: &UpdateContext,
+        dt: f32,
+        config: &ClientConfig,
+        translations: &Translations,
+    ) {
+        if let Some((_, time_left)) = &mut self.actionbar {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                self.actionbar = None;
+            }
+        }
+        for (_, time_left) in &mut self.kill_feed {
+            *time_left -= dt;
+        }
+        self.kill_feed.retain(|(_, time_left)| *time_left > 0.0);
+        let bindings = config.key_bindings();
+
+        // Escape stops a flythrough instead of opening the pause menu
+        let mut escape = update_context.keyboard.pressed.contains(&Keycode::Escape);
+        if let Some(flythrough) = &mut self.flythrough
+            && (flythrough.advance(dt) || escape)
+        {
+            self.flythrough = None;
+            escape = false;
+        }
+        if let Some(spectating) = &mut self.spectating {
+            spectating.update(dt);
+        }
+
+        if escape {
             self.gui = match self.gui {
                 CurrentGUI::None => CurrentGUI::PauseMenu,
                 CurrentGUI::PauseMenu => CurrentGUI::None,
+                CurrentGUI::HudEditor => CurrentGUI::PauseMenu,
                 CurrentGUI::Chat(_) => CurrentGUI::None,
                 CurrentGUI::Inventory => CurrentGUI::None,
+                CurrentGUI::Container => CurrentGUI::None,
+                CurrentGUI::CreativePicker => CurrentGUI::None,
+                CurrentGUI::RecentPalette(_) => CurrentGUI::None,
+                CurrentGUI::Controls => CurrentGUI::None,
+                // Only respawning closes the death screen
+                CurrentGUI::Death => CurrentGUI::Death,
             };
         }
 
-        if !self.gui.none() {
+        if !self.gui.container()
+            && let Some(container) = self.container.take()
+        {
+            let id = container.borrow().id;
+            self.connection.send(C2SMessage::CloseContainer { id });
+        }
+
+        if !self.gui.none() || self.flythrough.is_some() || self.spectating.is_some() {
+            self.build_drag = None;
             self.player.input = MoveInstructions::default();
+            if self.breaking {
+                self.connection.send(C2SMessage::StopBreaking);
+                self.breaking = false;
+            }
         }
 
-        let chat_messages = &self.messages;
+        let chat_messages = &mut self.messages;
         let chat_hist = &mut self.chat_hist;
 
         // woah is that a state machine
         match &mut self.gui {
+            CurrentGUI::None if self.flythrough.is_some() || self.spectating.is_some() => {
+                // The flythrough or the spectated player has the camera, sneaking stops spectating
+                if self.spectating.is_some()
+                    && bindings.pressed(update_context.keyboard, Action::Sneak)
+                {
+                    self.connection.send(C2SMessage::StopSpectating);
+                    self.spectating = None;
+                }
+            }
+
             CurrentGUI::None => {
-                let mouse_delta = update_context.mouse.delta;
+                let mut mouse_delta = update_context.mouse.delta;
+                let smoothing = if self.player.cinematic {
+                    Some(CINEMATIC_SMOOTHING)
+                } else if config.mouse_smoothing() {
+                    Some(MOUSE_SMOOTHING)
+                } else {
+                    None
+                };
+                if let Some(smoothing) = smoothing {
+                    // Ease towards the actual mouse movement for smooth camera motion
+                    let t = 1.0 - (-smoothing * dt).exp();
+                    self.player.smoothed_look = self.player.smoothed_look.lerp(mouse_delta, t);
+                    mouse_delta = self.player.smoothed_look;
+                }
+                let sensitivity = config.sensitivity();
                 let previous_yaw = self.player.yaw;
                 self.player.yaw -= mouse_delta.x * 0.1 * sensitivity;
                 self.player.pitch += mouse_delta.y * 0.1 * sensitivity;
@@ -231,86 +742,193 @@ impl<C: Connection> Client<C> {
 
                 let kb = &update_context.keyboard;
 
-                self.player.input.forward = if kb.down.contains(&Keycode::W) {
-                    if kb.down.contains(&Keycode::LCtrl) {
+                self.player.input.forward = if bindings.down(kb, Action::Forward) {
+                    if bindings.down(kb, Action::Sprint) {
                         2
                     } else {
                         1
                     }
-                } else if kb.down.contains(&Keycode::S) {
+                } else if bindings.down(kb, Action::Back) {
                     -1
                 } else {
                     0
                 };
 
-                self.player.input.strafe = if kb.down.contains(&Keycode::A) {
+                self.player.input.strafe = if bindings.down(kb, Action::StrafeLeft) {
                     1
-                } else if kb.down.contains(&Keycode::D) {
+                } else if bindings.down(kb, Action::StrafeRight) {
                     -1
                 } else {
                     0
                 };
 
-                self.player.input.jump = kb.down.contains(&Keycode::Space);
-                self.player.input.sneak = kb.down.contains(&Keycode::LShift);
+                self.player.input.jump = bindings.down(kb, Action::Jump);
+                self.player.input.sneak = bindings.down(kb, Action::Sneak);
 
-                if kb.pressed.contains(&Keycode::F5) {
+                if bindings.pressed(kb, Action::ThirdPerson) {
                     self.player.third_person = !self.player.third_person;
                 }
 
+                if bindings.pressed(kb, Action::Cinematic) {
+                    self.player.cinematic = !self.player.cinematic;
+                    self.player.smoothed_look = Vec2::ZERO;
+                }
+
                 if update_context
                     .mouse
                     .pressed
                     .contains(&sdl2::mouse::MouseButton::Left)
-                    && let Some((position, face)) = cast_ray(&self.world, &self.player, 5.0)
+                    && let Some(measurement) = &mut self.measurement
+                {
+                    if let Some((position, _)) =
+                        cast_ray(&self.world, &self.player, physics::REACH_DISTANCE)
+                    {
+                        measurement.pick(position);
+                    }
+                } else if update_context
+                    .mouse
+                    .pressed
+                    .contains(&sdl2::mouse::MouseButton::Left)
+                    && cast_ray(&self.world, &self.player, physics::REACH_DISTANCE).is_some()
                 {
                     self.connection.send(C2SMessage::BlockClick {
-                        position,
-                        face: face.try_into().unwrap(),
+                        look: physics::look_direction(self.player.yaw, self.player.pitch),
+                        slot: self.player.inventory.borrow().slot,
                         right: false,
                     });
+                    self.breaking = true;
+                }
+
+                if self.breaking {
+                    self.break_target =
+                        cast_ray(&self.world, &self.player, physics::REACH_DISTANCE)
+                            .map(|(position, _)| position);
+                }
+
+                if update_context
+                    .mouse
+                    .released
+                    .contains(&sdl2::mouse::MouseButton::Left)
+                    && self.breaking
+                {
+                    self.connection.send(C2SMessage::StopBreaking);
+                    self.breaking = false;
+                }
+
+                if bindings.pressed(kb, Action::SwapOffhand) {
+                    self.connection.send(C2SMessage::SwapOffhand);
                 }
 
+                let (slot, held) = {
+                    let inventory = self.player.inventory.borrow();
+                    (inventory.slot, *inventory.inner.hotbar_slot(inventory.slot))
+                };
+                let holding_block = !held.is_empty()
+                    && item_registry()
+                        .get(held.item)
+                        .unwrap()
+                        .assoc_block
+                        .is_some();
+                let target = cast_ray(&self.world, &self.player, physics::REACH_DISTANCE);
+                let build_shape = if bindings.down(kb, Action::BuildLine) {
+                    Some(BuildShape::Line)
+                } else if bindings.down(kb, Action::BuildRectangle) {
+                    Some(BuildShape::Rectangle)
+                } else {
+                    None
+                };
+
                 if update_context
                     .mouse
                     .pressed
                     .contains(&sdl2::mouse::MouseButton::Right)
-                    && let Some((position, face)) = cast_ray(&self.world, &self.player, 5.0)
+                    && let Some((position, normal)) = target
                 {
-                    self.connection.send(C2SMessage::BlockClick {
-                        position,
-                        face: face.try_into().unwrap(),
-                        right: true,
+                    if let Some(shape) = build_shape
+                        && holding_block
+                        && let Ok(face) = Direction::try_from(normal)
+                    {
+                        self.build_drag = Some(BuildDrag::new(shape, position + normal, face));
+                    } else {
+                        self.connection.send(C2SMessage::BlockClick {
+                            look: physics::look_direction(self.player.yaw, self.player.pitch),
+                            slot,
+                            right: true,
+                        });
+                        if holding_block {
+                            self.remember_placed(held.item);
+                            self.send_mirrored(position + normal, true);
+                        }
+                    }
+                }
+
+                if let Some(drag) = &mut self.build_drag
+                    && let Some((position, normal)) = target
+                {
+                    drag.drag_to(position + normal);
+                }
+
+                if update_context
+                    .mouse
+                    .released
+                    .contains(&sdl2::mouse::MouseButton::Right)
+                    && let Some(drag) = self.build_drag.take()
+                    && holding_block
+                {
+                    self.connection.send(C2SMessage::PlaceBlocks {
+                        positions: self.drag_positions(&drag),
+                        face: drag.face,
+                        slot,
                     });
+                    self.remember_placed(held.item);
+                }
+
+                if bindings.pressed(kb, Action::Measure) {
+                    self.measurement = match self.measurement {
+                        Some(_) => {
+                            self.show_actionbar("Stopped measuring");
+                            None
+                        }
+                        None => Some(Measurement::default()),
+                    };
                 }
 
-                if kb.pressed.contains(&Keycode::T) {
+                if bindings.pressed(kb, Action::Mirror) {
+                    self.mirror =
+                        Mirror::cycle(self.mirror, self.player.position.floor().as_ivec3());
+                    self.connection.send(C2SMessage::SetMirror {
+                        mirror: self.mirror,
+                    });
+                    let message = match &self.mirror {
+                        Some(mirror) => mirror.describe(),
+                        None => "Stopped mirroring".to_string(),
+                    };
+                    self.messages
+                        .push(format!("%b7F{}%r", message).parse().unwrap());
+                }
+
+                if bindings.pressed(kb, Action::Chat) {
                     self.gui = CurrentGUI::Chat(ChatGUI::default());
                 }
 
-                if kb.pressed.contains(&Keycode::Slash) {
+                if bindings.pressed(kb, Action::Command) {
                     self.gui = CurrentGUI::Chat(ChatGUI::slash());
                 }
 
-                if kb.pressed.contains(&Keycode::E) {
+                if bindings.pressed(kb, Action::Inventory) {
                     self.gui = CurrentGUI::Inventory;
                 }
 
-                for (i, key) in [
-                    Keycode::Num1,
-                    Keycode::Num2,
-                    Keycode::Num3,
-                    Keycode::Num4,
-                    Keycode::Num5,
-                    Keycode::Num6,
-                    Keycode::Num7,
-                    Keycode::Num8,
-                    Keycode::Num9,
-                ]
-                .iter()
-                .enumerate()
-                {
-                    if kb.pressed.contains(key) {
+                if bindings.pressed(kb, Action::CreativePicker) {
+                    self.gui = CurrentGUI::CreativePicker;
+                }
+
+                if bindings.pressed(kb, Action::RecentBlocks) && !self.recent_blocks.is_empty() {
+                    self.gui = CurrentGUI::RecentPalette(PaletteGUI::default());
+                }
+
+                for (i, action) in Action::HOTBAR.into_iter().enumerate() {
+                    if bindings.pressed(kb, action) {
                         self.connection.send(C2SMessage::HotbarChange { idx: i });
                         self.player.inventory.borrow_mut().slot = i;
                         break;
@@ -330,19 +948,23 @@ impl<C: Connection> Client<C> {
             }
 
             CurrentGUI::Chat(gui) => {
+                let line_count = chat_messages.line_count();
                 let mouse_scroll = update_context.mouse.scroll_delta.y;
                 if mouse_scroll != 0.0 {
-                    let old = gui.scroll as isize;
-                    let new = old + mouse_scroll.signum() as isize * 2;
-                    let lines: Vec<TextComponent> =
-                        chat_messages.iter().flat_map(|msg| msg.lines()).collect();
-                    if new > 0 && new + 10 < lines.len() as isize {
-                        gui.scroll = new as usize;
-                    }
+                    let step = mouse_scroll.signum() as isize * CHAT_SCROLL_STEP as isize;
+                    gui.scroll_by(step, line_count);
+                }
+                // A page keeps one line of the previous page in view
+                let page = CHAT_LINES as isize - 1;
+                if update_context.keyboard.pressed.contains(&Keycode::PageUp) {
+                    gui.scroll_by(page, line_count);
+                }
+                if update_context.keyboard.pressed.contains(&Keycode::PageDown) {
+                    gui.scroll_by(-page, line_count);
                 }
 
                 let input = &update_context.keyboard.text_input;
-                if input.len() > 0 {
+                if !input.is_empty() {
                     if let Some(ghost_idx) = gui.ghost.take() {
                         // Unwrap is fine here as we check for bounds when handling Up and Down
                         gui.message = chat_hist.get(ghost_idx).unwrap().to_string();
@@ -353,27 +975,40 @@ impl<C: Connection> Client<C> {
                 if kb.pressed.contains(&Keycode::Return)
                     && (!gui.message.trim().is_empty() || gui.ghost.is_some())
                 {
+                    // The history keeps the shortcuts, so that sending a message again expands
+                    // them again
+                    let inventory = self.player.inventory.borrow();
+                    let held = inventory.inner.slot(inventory.slot).copied();
+                    drop(inventory);
+                    let expand = |message: &str| {
+                        shortcuts::expand_shortcuts(
+                            message,
+                            self.player.position,
+                            held,
+                            translations,
+                        )
+                    };
                     if let Some(i) = gui.ghost.take() {
                         let c = chat_hist.get(i).unwrap();
                         if !c.trim().is_empty() {
+                            chat_messages.log_sent(c);
                             self.connection
-                                .send(C2SMessage::SendMessage { message: c.clone() });
-                            // Check if we only stepped once
-                            if i != chat_hist.len() - 1 {
-                                chat_hist.push(c.clone());
-                            }
+                                .send(C2SMessage::SendMessage { message: expand(c) });
+                            remember_sent(chat_hist, c.clone());
                             self.gui = CurrentGUI::None;
                         }
                     } else {
                         let c = std::mem::take(&mut gui.message);
-                        self.connection
-                            .send(C2SMessage::SendMessage { message: c.clone() });
-                        chat_hist.push(c);
+                        chat_messages.log_sent(&c);
+                        self.connection.send(C2SMessage::SendMessage {
+                            message: expand(&c),
+                        });
+                        remember_sent(chat_hist, c);
                         self.gui = CurrentGUI::None;
                     }
                 } else if kb.pressed.contains(&Keycode::Backspace) {
                     gui.message.pop();
-                } else if kb.pressed.contains(&Keycode::Up) {
+                } else if kb.pressed.contains(&Keycode::Up) && !chat_hist.is_empty() {
                     let start = gui
                         .ghost
                         .map(|i| i.saturating_sub(1))
@@ -409,20 +1044,38 @@ impl<C: Connection> Client<C> {
                 }
             }
 
-            CurrentGUI::Inventory => {
+            CurrentGUI::Inventory | CurrentGUI::Container | CurrentGUI::CreativePicker => {
                 // Handled elsewhere
             }
 
-            CurrentGUI::PauseMenu => {}
+            CurrentGUI::RecentPalette(gui) => {
+                gui.pointer =
+                    (gui.pointer + update_context.mouse.delta).clamp_length_max(PALETTE_DEADZONE);
+                if !bindings.down(update_context.keyboard, Action::RecentBlocks) {
+                    let hovered = gui.hovered(self.recent_blocks.len());
+                    self.gui = CurrentGUI::None;
+                    if let Some(item) = hovered.map(|i| self.recent_blocks[i]) {
+                        self.select_block(item);
+                    }
+                }
+            }
+
+            CurrentGUI::PauseMenu
+            | CurrentGUI::HudEditor
+            | CurrentGUI::Controls
+            | CurrentGUI::Death => {}
         }
 
         self.player.optimistic(dt, &self.world);
 
         self.player.input.yaw = self.player.yaw;
         self.player.input.pitch = self.player.pitch;
-        self.connection.send(C2SMessage::Move(self.player.input));
+        let input = self.player.input.quantized();
+        if let Some(input) = self.move_delta.next(input, config.move_delta_compression()) {
+            self.connection.send(C2SMessage::Move(input));
+        }
 
-        let needed_chunks = self.world.needs_chunks(self.player.position.as_ivec3());
+        let needed_chunks = self.world.needs_chunks(self.view_center().as_ivec3());
         self.connection.send(C2SMessage::RequestChunks {
             chunk_positions: needed_chunks,
         });
@@ -432,10 +1085,75 @@ impl<C: Connection> Client<C> {
             self.connection
                 .send(C2SMessage::InventoryClick { idx, right });
         }
+
+        let picks = std::mem::take(&mut self.player.inventory.borrow_mut().picks);
+        for (item, slot) in picks {
+            self.connection
+                .send(C2SMessage::CreativePick { item, slot });
+        }
+
+        if let Some(container) = &self.container {
+            for message in std::mem::take(&mut container.borrow_mut().pending) {
+                self.connection.send(message);
+            }
+        }
+    }
+
+    /// Repeats a block edit at the positions mirroring it, if mirrored building is on.
+    fn send_mirrored(&mut self, position: IVec3, place: bool) {
+        let Some(mirror) = self.mirror else {
+            return;
+        };
+        for position in mirror.mirrored(position) {
+            self.connection
+                .send(C2SMessage::SetBlock { position, place });
+        }
+    }
+
+    /// Returns the positions of the blocks placed by a drag, with their mirrored copies if
+    /// mirrored building is on.
+    pub fn drag_positions(&self, drag: &BuildDrag) -> Vec<IVec3> {
+        let mut positions = drag.positions();
+        if let Some(mirror) = self.mirror {
+            let mirrored: Vec<_> = positions
+                .iter()
+                .flat_map(|&position| mirror.mirrored(position))
+                .collect();
+            positions.extend(mirrored);
+            positions.truncate(MAX_PLACED_BLOCKS);
+        }
+        positions
+    }
+
+    /// Moves a placed block to the front of the recently placed blocks.
+    fn remember_placed(&mut self, item: ItemId) {
+        self.recent_blocks.retain(|&recent| recent != item);
+        self.recent_blocks.insert(0, item);
+        self.recent_blocks.truncate(RECENT_BLOCKS);
+    }
+
+    /// Switches to a block chosen in the palette, selecting the hotbar slot holding it, or
+    /// picking it into the selected slot if the hotbar doesn't have it.
+    fn select_block(&mut self, item: ItemId) {
+        let mut inventory = self.player.inventory.borrow_mut();
+        let held_at = (0..9).find(|&i| {
+            let stack = inventory.inner.hotbar_slot(i);
+            !stack.is_empty() && stack.item == item
+        });
+        if let Some(idx) = held_at {
+            self.connection.send(C2SMessage::HotbarChange { idx });
+            inventory.slot = idx;
+        } else {
+            inventory.pick(item);
+        }
     }
 
     /// Updates any state on the client side from all received messages from the server.
-    pub fn receive_state(&mut self, particle_system: &mut ParticleSystem) -> Result<(), String> {
+    pub fn receive_state(
+        &mut self,
+        particle_system: &mut ParticleSystem,
+        translations: &Translations,
+    ) -> Result<(), Disconnection> {
         let messages = self.connection.receive();
         for message in messages {
             match message {
@@ -443,6 +1161,7 @@ impl<C: Connection> Client<C> {
                     user_id,
                     entity_id,
                     inventory,
+                    sky_seed,
                 } => {
                     log::info!(
                         "Connected to server with user ID {} and entity ID {}",
@@ -451,6 +1170,7 @@ impl<C: Connection> Client<C> {
                     );
                     self.user_id = Some(user_id);
                     self.entity_id = Some(entity_id);
+                    self.sky_seed = Some(sky_seed);
                     self.player
                         .inventory
                         .borrow_mut()
@@ -458,28 +1178,61 @@ impl<C: Connection> Client<C> {
                 }
                 S2CMessage::ConnectionFailed { reason } => {
                     log::error!("Connection failed!");
-                    return Err(reason);
+                    return Err(Disconnection::Lost(reason));
+                }
+                S2CMessage::Kicked { reason } => {
+                    log::warn!("Kicked from the server: {}", reason);
+                    return Err(Disconnection::Kicked(reason));
+                }
+                S2CMessage::Transfer { address } => {
+                    log::info!("The server sends the player to {}", address);
+                    return Err(Disconnection::Transferred(address));
                 }
                 S2CMessage::EntitySpawned {
-                    entity_id: _,
+                    entity_id,
                     entity_type,
                     entity_snapshot,
+                    name,
                 } => {
-                    if entity_type == mp3d_core::entity::EntityType::Player as u8 {
+                    if Some(entity_id) == self.entity_id {
                         log::info!("Player snapshot received, {} bytes", entity_snapshot.len());
-                        if u64::from_le_bytes(entity_snapshot[0..8].try_into().unwrap())
-                            == self.entity_id.unwrap()
-                        {
-                            self.player.update_from_snapshot(&entity_snapshot);
+                        self.player.update_from_snapshot(&entity_snapshot);
+                        if self.player.is_dead() {
+                            self.gui = CurrentGUI::Death;
+                            self.move_delta.reset();
                         }
+                    } else if let Some(mut entity) =
+                        ClientEntity::from_snapshot(entity_type, &entity_snapshot)
+                    {
+                        entity.name = name;
+                        self.world.entities.insert(entity_id, entity);
+                    } else {
+                        log::warn!("Invalid snapshot of entity {}", entity_id);
+                    }
+                }
+                S2CMessage::EntityUpdated {
+                    entity_id,
+                    entity_snapshot,
+                } => {
+                    if let Some(entity) = self.world.entities.get_mut(&entity_id)
+                        && entity.update_from_snapshot(&entity_snapshot).is_none()
+                    {
+                        log::warn!("Invalid snapshot of entity {}", entity_id);
                     }
                 }
+                S2CMessage::EntityRemoved { entity_id } => {
+                    self.world.entities.remove(&entity_id);
+                }
                 S2CMessage::PlayerMoved {
                     entity_id,
                     position,
-                    ..
+                    yaw,
+                    pitch,
                 } => {
                     if Some(entity_id) != self.entity_id {
+                        if let Some(entity) = self.world.entities.get_mut(&entity_id) {
+                            entity.move_to(position, yaw, pitch);
+                        }
                         continue;
                     }
                     let delta = position - self.player.position;
@@ -495,13 +1248,17 @@ impl<C: Connection> Client<C> {
                         .borrow_mut()
                         .update_from_inventory(inventory);
                 }
+                S2CMessage::InventorySlotsUpdated { slots, temp } => {
+                    self.player.inventory.borrow_mut().update_slots(slots, temp);
+                }
                 S2CMessage::ChunkData {
                     chunk_position,
                     chunk,
                 } => {
-                    self.world.chunks.insert(chunk_position, (*chunk).into());
+                    self.world.insert_chunk(chunk_position, *chunk);
                     self.world.remesh_queue.push(chunk_position, true);
-                    // also push the other neighbor chunks to the remesh queue
+                    // also push the other loaded neighbor chunks to the remesh queue, unloaded
+                    // ones are meshed once they arrive anyway
                     for neighbor in [
                         chunk_position + IVec3::new(0, 0, -1),
                         chunk_position + IVec3::new(0, 0, 1),
@@ -510,7 +1267,9 @@ impl<C: Connection> Client<C> {
                         chunk_position + IVec3::new(0, 1, 0),
                         chunk_position + IVec3::new(0, -1, 0),
                     ] {
-                        self.world.remesh_queue.push(neighbor, false);
+                        if self.world.chunks.contains_key(&neighbor) {
+                            self.world.remesh_queue.push(neighbor, false);
+                        }
                     }
                 }
                 S2CMessage::ChatMessage { message } => {
@@ -518,6 +1277,12 @@ impl<C: Connection> Client<C> {
                 }
                 S2CMessage::BlocksUpdated { updates } => {
                     for update in updates {
+                        if update.kind == mp3d_core::protocol::BlockUpdateKind::Removed
+                            && self.breaking
+                            && self.break_target == Some(update.position)
+                        {
+                            self.send_mirrored(update.position, false);
+                        }
                         if update.kind == mp3d_core::protocol::BlockUpdateKind::Removed {
                             let Some((old_block, old_state)) =
                                 self.world.get_block_at(update.position)
@@ -534,9 +1299,90 @@ impl<C: Connection> Client<C> {
                         );
                     }
                 }
+                S2CMessage::OpenContainer { id, rows, title } => {
+                    self.container = Some(Rc::new(RefCell::new(ClientContainer::new(
+                        id,
+                        rows,
+                        title,
+                        &self.player.inventory,
+                    ))));
+                    self.gui = CurrentGUI::Container;
+                }
+                S2CMessage::ContainerUpdated { id, slots } => {
+                    if let Some(container) = &self.container {
+                        let mut container = container.borrow_mut();
+                        if container.id == id && container.inner.slots.len() == slots.len() {
+                            container.inner.slots = slots;
+                        }
+                    }
+                }
+                S2CMessage::ContainerClosed { id }
+                    if self
+                        .container
+                        .as_ref()
+                        .is_some_and(|container| container.borrow().id == id) =>
+                {
+                    self.container = None;
+                    self.gui = CurrentGUI::None;
+                }
+                S2CMessage::BreakProgress { progress } => {
+                    self.break_progress = progress;
+                }
                 S2CMessage::HotbarChanged { idx } => {
                     self.player.inventory.borrow_mut().slot = idx;
                 }
+                S2CMessage::TimeUpdate { time, advancing } => {
+                    self.world.set_time(time, advancing);
+                }
+                S2CMessage::TeamsUpdated { colors } => {
+                    self.team_colors = colors.into_iter().collect();
+                }
+                S2CMessage::PlayCameraPath {
+                    keyframes,
+                    duration,
+                } if !keyframes.is_empty() => {
+                    self.gui = CurrentGUI::None;
+                    self.flythrough = Some(Flythrough::new(keyframes, duration));
+                }
+                S2CMessage::StopCameraPath => {
+                    self.flythrough = None;
+                }
+                S2CMessage::SpectateStarted { username } => {
+                    self.spectating = Some(Spectating::new(username));
+                }
+                S2CMessage::SpectatorCamera { camera } => {
+                    if let Some(spectating) = &mut self.spectating {
+                        spectating.set_target(camera);
+                    }
+                }
+                S2CMessage::PlayerDied { event } => {
+                    if event.victim == self.username {
+                        self.death_message = Some(translations.death_message(&event));
+                    }
+                    self.messages.push(translations.death_message(&event));
+                    if self.kill_feed.len() >= KILL_FEED_LEN {
+                        self.kill_feed.remove(0);
+                    }
+                    self.kill_feed
+                        .push((translations.kill_feed_entry(&event), KILL_FEED_TIME));
+                }
+                S2CMessage::SpectateStopped => {
+                    self.spectating = None;
+                }
+                S2CMessage::HealthChanged { entity_id, health } => {
+                    if Some(entity_id) != self.entity_id {
+                        continue;
+                    }
+                    self.player.health = health;
+                    if self.player.is_dead() {
+                        self.player.velocity = Vec3::ZERO;
+                        self.gui = CurrentGUI::Death;
+                        self.move_delta.reset();
+                    } else if self.gui.death() {
+                        self.gui = CurrentGUI::None;
+                        self.move_delta.reset();
+                    }
+                }
                 _ => {}
             }
         }
@@ -555,7 +1401,7 @@ impl<C: Connection> Drop for Client<C> {
             .chat_hist
             .iter()
             .rev()
-            .take(50)
+            .take(CHAT_HISTORY_LEN)
             .rev()
             .map(|v| v.as_str())
             .collect::<Vec<&str>>()
@@ -566,6 +1412,18 @@ impl<C: Connection> Drop for Client<C> {
     }
 }
 
+/// Adds a sent message or command to the history recalled with the arrow keys, unless it is the
+/// same as the one sent before it.
+fn remember_sent(chat_hist: &mut Vec<String>, message: String) {
+    if chat_hist.last() == Some(&message) {
+        return;
+    }
+    chat_hist.push(message);
+    if chat_hist.len() > CHAT_HISTORY_LEN {
+        chat_hist.remove(0);
+    }
+}
+
 /// Performs a raycast from the player's position in the direction they are looking, returning the
 /// position and normal of the first block hit within the specified range, or `None` if no block is
 /// hit.
@@ -574,34 +1432,50 @@ pub fn cast_ray(
     player: &player::ClientPlayer,
     max_distance: f32,
 ) -> Option<(IVec3, IVec3)> {
-    let mut pos = player.first_person_eye();
-    let yaw_rad = player.yaw.to_radians();
-    let pitch_rad = player.pitch.to_radians();
-    let direction = Vec3::new(
-        yaw_rad.sin() * pitch_rad.cos(),
-        -pitch_rad.sin(),
-        yaw_rad.cos() * pitch_rad.cos(),
-    )
-    .normalize();
-    let step = 0.003;
-
-    for _ in 0..(max_distance / step) as usize {
-        let block_pos = pos.floor().as_ivec3();
-
-        let (block, state) = world.get_block_at(block_pos)?;
-
-        let local = pos - block_pos.as_vec3();
-
-        let block_def = block_registry().get(block).unwrap();
-        if block_def.visible {
-            let ray_intersection = block_def.ray_intersect(local, direction, *state);
-            if let Some(normal) = ray_intersection {
-                return Some((block_pos, normal));
-            }
-        }
+    player.eye_camera().cast_ray(world, max_distance)
+}
 
-        pos += direction * step;
-    }
+/// What the crosshair points at within reach, which changes how it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrosshairTarget {
+    None,
+    Block,
+    /// A block that does something when right clicked, with its
+    /// [`interact_hint`](mp3d_core::block::BlockDef::interact_hint).
+    Interactable(&'static str),
+    /// An entity other than the player, in front of any targeted block.
+    Entity,
+}
+
+/// Returns the distance along a ray to where it enters a box, if it hits the box.
+fn ray_box_distance(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let inverse = direction.recip();
+    let t1 = (min - origin) * inverse;
+    let t2 = (max - origin) * inverse;
+    let near = t1.min(t2).max_element();
+    let far = t1.max(t2).min_element();
+    (near <= far && far >= 0.0).then_some(near.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    None
+    #[test]
+    fn test_move_delta_resends_after_reset() {
+        let walking = MoveInstructions {
+            forward: 1,
+            yaw: 90.0,
+            ..Default::default()
+        };
+        let mut delta = MoveDelta::default();
+        assert_eq!(delta.next(walking, true), Some(walking));
+        assert_eq!(delta.next(walking, true), None);
+        assert_eq!(delta.next(walking, false), Some(walking));
+
+        // Dying and respawning stops the player on the server, even with the keys still held
+        delta.reset();
+        assert_eq!(delta.next(walking, true), Some(walking));
+        assert_eq!(delta.next(walking, true), None);
+    }
 }
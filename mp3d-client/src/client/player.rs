@@ -1,20 +1,28 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use glam::{Mat4, Vec3, Vec4};
+use glam::{Mat4, Vec2, Vec3};
 use mp3d_core::{
-    block::block_registry,
+    block::{block_registry, container::Container},
     entity::{Entity, PlayerEntity},
-    item::Inventory,
+    item::{Inventory, ItemId, ItemStack},
     physics::{self, PhysicsState},
-    protocol::MoveInstructions,
+    protocol::{C2SMessage, MAX_PITCH, MoveInstructions},
     world::chunk::CHUNK_SIZE,
 };
 
-use crate::client::world::ClientWorld;
+use crate::client::{camera::Camera, world::ClientWorld};
+/// The vertical field of view in degrees unless changed in the options.
+pub const DEFAULT_FOV: f32 = 90.0;
 
 pub struct ClientInventory {
     pub inner: Inventory,
     pub clicks: Vec<(usize, bool)>,
+    /// The items picked from the creative picker and the hotbar slots they went into.
+    pub picks: Vec<(ItemId, usize)>,
     pub slot: usize,
 }
 
@@ -23,6 +31,7 @@ impl ClientInventory {
         Self {
             inner: Inventory::new(),
             clicks: Vec::new(),
+            picks: Vec::new(),
             slot: 0,
         }
     }
@@ -32,9 +41,115 @@ impl ClientInventory {
         self.clicks.push((index, right));
     }
 
+    /// Puts a full stack of an item into the selected hotbar slot.
+    pub fn pick(&mut self, item: ItemId) {
+        self.inner.pick(self.slot, item);
+        self.picks.push((item, self.slot));
+    }
+
     pub fn update_from_inventory(&mut self, inventory: Inventory) {
         self.inner = inventory;
         self.clicks.clear();
+        self.picks.clear();
+    }
+
+    /// Applies the slots that changed on the server, dropping the predicted actions like
+    /// [`Self::update_from_inventory`].
+    pub fn update_slots(&mut self, slots: Vec<(usize, ItemStack)>, temp: Option<ItemStack>) {
+        for (index, stack) in slots {
+            if let Some(slot) = self.inner.slot_mut(index) {
+                *slot = stack;
+            }
+        }
+        if let Some(temp) = temp {
+            self.inner.temp = temp;
+        }
+        self.clicks.clear();
+        self.picks.clear();
+    }
+}
+
+/// How long after picking up a stack a second click on the same slot counts as a double click,
+/// which collects more items like it.
+pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+/// A container the player has open, with the actions predicted on it that still have to be sent.
+pub struct ClientContainer {
+    pub id: u64,
+    pub inner: Container,
+    pub inventory: Rc<RefCell<ClientInventory>>,
+    /// The messages for the predicted actions, sent with the next input.
+    pub pending: Vec<C2SMessage>,
+    /// The slot items were picked up from by pressing the left button on it, so that releasing
+    /// the button on another slot puts them down there.
+    pub dragging: Option<usize>,
+    /// The slots dragged over while holding items and a button, which the held items are spread
+    /// over once the button is released.
+    pub spreading: Option<Spreading>,
+    /// The slot a stack was last picked up from and when, to notice double clicks.
+    pub last_pickup: Option<(usize, Instant)>,
+}
+
+/// Slots dragged over to spread the held items over them.
+pub struct Spreading {
+    /// Whether the right button is held, which puts one item in every slot.
+    pub right: bool,
+    pub slots: Vec<usize>,
+}
+
+impl ClientContainer {
+    pub fn new(id: u64, rows: u8, title: String, inventory: &Rc<RefCell<ClientInventory>>) -> Self {
+        Self {
+            id,
+            inner: Container::new(title, rows),
+            inventory: Rc::clone(inventory),
+            pending: Vec::new(),
+            dragging: None,
+            spreading: None,
+            last_pickup: None,
+        }
+    }
+
+    pub fn click(&mut self, index: usize, right: bool, shift: bool) {
+        let mut inventory = self.inventory.borrow_mut();
+        self.inner.click(&mut inventory.inner, index, right, shift);
+        self.pending.push(C2SMessage::ContainerClick {
+            id: self.id,
+            idx: index,
+            right,
+            shift,
+        });
+    }
+
+    /// Spreads the held items over the given slots, see [`Container::distribute`].
+    pub fn distribute(&mut self, slots: Vec<usize>, right: bool) {
+        let mut inventory = self.inventory.borrow_mut();
+        self.inner.distribute(&mut inventory.inner, &slots, right);
+        self.pending.push(C2SMessage::ContainerDistribute {
+            id: self.id,
+            slots,
+            right,
+        });
+    }
+
+    /// Collects items like the held ones into the held stack, see [`Container::collect`].
+    pub fn collect(&mut self) {
+        let mut inventory = self.inventory.borrow_mut();
+        self.inner.collect(&mut inventory.inner);
+        self.pending
+            .push(C2SMessage::ContainerCollect { id: self.id });
+    }
+
+    /// Gets a slot of the view of the container, see [`Container::slot`].
+    pub fn slot(&self, index: usize) -> Option<ItemStack> {
+        self.inner
+            .slot(&self.inventory.borrow().inner, index)
+            .copied()
+    }
+
+    /// Returns whether the player holds items with the cursor.
+    pub fn holding(&self) -> bool {
+        !self.inventory.borrow().inner.temp.is_empty()
     }
 }
 
@@ -50,11 +165,17 @@ pub struct ClientPlayer {
     pub input: MoveInstructions,
     pub inventory: Rc<RefCell<ClientInventory>>,
     pub third_person: bool,
+    /// Hides the HUD and smooths out camera motion, toggled with F1.
+    pub cinematic: bool,
+    /// The smoothed mouse movement used while in cinematic mode.
+    pub smoothed_look: Vec2,
+    /// The player is dead at 0, see [`PlayerEntity::health`].
+    pub health: f32,
 }
 
 impl ClientPlayer {
     pub fn first_person_eye(&self) -> Vec3 {
-        self.position + Vec3::new(0.0, 1.62, 0.0)
+        self.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0)
     }
 
     pub fn third_person_eye(&self, world: &ClientWorld) -> Vec3 {
@@ -100,36 +221,10 @@ impl ClientPlayer {
         pivot + backward * desired_distance
     }
 
-    pub fn first_person_view(&self) -> Mat4 {
-        let eye = self.first_person_eye();
-
-        let pitch_rad = self.pitch.to_radians();
-        let yaw_rad = self.yaw.to_radians();
-
-        let forward = Vec3::new(
-            yaw_rad.sin() * pitch_rad.cos(),
-            -pitch_rad.sin(),
-            yaw_rad.cos() * pitch_rad.cos(),
-        )
-        .normalize();
-
-        Mat4::look_at_rh(eye, eye + forward, Vec3::Y)
-    }
-
-    pub fn third_person_view(&self, world: &ClientWorld) -> Mat4 {
-        let eye = self.third_person_eye(world);
-
-        let pitch_rad = self.pitch.to_radians();
-        let yaw_rad = self.yaw.to_radians();
-
-        let forward = Vec3::new(
-            yaw_rad.sin() * pitch_rad.cos(),
-            -pitch_rad.sin(),
-            yaw_rad.cos() * pitch_rad.cos(),
-        )
-        .normalize();
-
-        Mat4::look_at_rh(eye, eye + forward, Vec3::Y)
+    /// Returns the camera at the eyes of the player, which blocks and entities are targeted
+    /// from whatever the world is seen from.
+    pub fn eye_camera(&self) -> Camera {
+        Camera::new(self.first_person_eye(), self.yaw, self.pitch, self.fov)
     }
 
     pub fn model(&self) -> Mat4 {
@@ -139,46 +234,6 @@ impl ClientPlayer {
         )
     }
 
-    pub fn view(&self, world: &ClientWorld) -> Mat4 {
-        if self.third_person {
-            self.third_person_view(world)
-        } else {
-            self.first_person_view()
-        }
-    }
-
-    pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
-        Mat4::perspective_rh_gl(self.fov.to_radians(), aspect_ratio, 0.1, 1000.0)
-    }
-
-    /// Returns the frustum planes, which can be used for frustum culling of chunks.
-    pub fn frustum_planes(&self, aspect_ratio: f32, world: &ClientWorld) -> [Vec4; 6] {
-        let vp = self.projection(aspect_ratio) * self.view(world);
-        let m = vp.to_cols_array_2d();
-
-        let row0 = Vec4::new(m[0][0], m[1][0], m[2][0], m[3][0]);
-        let row1 = Vec4::new(m[0][1], m[1][1], m[2][1], m[3][1]);
-        let row2 = Vec4::new(m[0][2], m[1][2], m[2][2], m[3][2]);
-        let row3 = Vec4::new(m[0][3], m[1][3], m[2][3], m[3][3]);
-
-        let mut planes = [
-            row3 + row0, // left
-            row3 - row0, // right
-            row3 + row1, // bottom
-            row3 - row1, // top
-            row3 + row2, // near
-            row3 - row2, // far
-        ];
-
-        // normalize planes
-        for plane in planes.iter_mut() {
-            let n = plane.truncate().length();
-            *plane /= n;
-        }
-
-        planes
-    }
-
     pub fn update_from_snapshot(&mut self, snapshot: &[u8]) {
         use mp3d_core::saving::{Saveable, io::*};
         let mut snapshot = snapshot.iter().cloned();
@@ -192,19 +247,26 @@ impl ClientPlayer {
             Inventory::load(&mut snapshot, mp3d_core::saving::SAVE_VERSION).unwrap(),
         );
         self.inventory.borrow_mut().slot =
-            read_u8(&mut snapshot, "ClientPlayer reading inventory slot").unwrap() as usize;
+            read_u64(&mut snapshot, "ClientPlayer reading inventory slot").unwrap() as usize;
         self.flying = read_u8(&mut snapshot, "ClientPlayer reading flying").unwrap() != 0;
+        self.health = read_f32(&mut snapshot, "ClientPlayer reading health").unwrap();
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
     }
 
     pub fn optimistic(&mut self, dt: f32, world: &ClientWorld) {
-        if !world
-            .chunks
-            .contains_key(&(self.position.as_ivec3() / CHUNK_SIZE as i32))
+        // Dead players stay where they died
+        if self.is_dead()
+            || !world
+                .chunks
+                .contains_key(&(self.position.as_ivec3() / CHUNK_SIZE as i32))
         {
             return;
         }
 
-        self.pitch = self.pitch.clamp(-89.9, 89.9);
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
         self.yaw = self.yaw.rem_euclid(360.0);
 
         let state = PhysicsState {
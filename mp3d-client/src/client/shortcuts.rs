@@ -0,0 +1,48 @@
+//! Chat shortcuts, expanded when a chat message is sent.
+//!
+//! `[coords]` becomes the block position of the player, which puts a `/tp` to it into the chat
+//! input of whoever clicks it, and `[hand]` becomes the name of the held item, which shows its
+//! count and identifier when hovered. Commands are sent as typed.
+
+use glam::Vec3;
+use mp3d_core::{
+    item::{ItemStack, item_registry},
+    textcomponent::sanitize,
+};
+
+use crate::resource::lang::Translations;
+
+/// Expands the shortcuts in a chat message. `[hand]` is left as typed if nothing is held.
+pub fn expand_shortcuts(
+    message: &str,
+    position: Vec3,
+    held: Option<ItemStack>,
+    translations: &Translations,
+) -> String {
+    if message.starts_with('/') {
+        return message.to_string();
+    }
+
+    let mut message = message.to_string();
+    if message.contains("[coords]") {
+        let pos = position.floor().as_ivec3();
+        let coords = format!(
+            "%s[/tp {0} {1} {2}]%b7F[{0}, {1}, {2}]%r",
+            pos.x, pos.y, pos.z
+        );
+        message = message.replace("[coords]", &coords);
+    }
+    if message.contains("[hand]")
+        && let Some(stack) = held.filter(|stack| !stack.is_empty())
+        && let Some(item_def) = item_registry().get(stack.item)
+    {
+        // A `]` would end the hover text early
+        let name = sanitize(&translations.item_name(item_def.ident)).replace(']', "");
+        let hand = format!(
+            "%h[{} x{}\n{}]%b7F[{}]%r",
+            name, stack.count, item_def.ident, name
+        );
+        message = message.replace("[hand]", &hand);
+    }
+    message
+}
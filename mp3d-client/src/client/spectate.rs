@@ -0,0 +1,65 @@
+//! Spectating, which shows the game through the eyes of another player.
+
+use glam::Vec3;
+use mp3d_core::protocol::CameraKeyframe;
+
+/// How quickly the camera catches up with the spectated player, higher is snappier. The server
+/// only sends their view once a tick, so the camera eases between updates.
+const SPECTATE_SMOOTHING: f32 = 20.0;
+/// How far the spectated player can move in one update before the camera jumps to them instead
+/// of easing, like when they teleport.
+const SPECTATE_SNAP_DISTANCE: f32 = 8.0;
+
+/// The player being spectated and the camera following them.
+#[derive(Clone, Debug)]
+pub struct Spectating {
+    pub username: String,
+    /// The last view of the spectated player sent by the server, `None` until the first one.
+    target: Option<CameraKeyframe>,
+    /// The view shown, easing towards the target.
+    camera: Option<CameraKeyframe>,
+}
+
+impl Spectating {
+    pub fn new(username: String) -> Self {
+        Self {
+            username,
+            target: None,
+            camera: None,
+        }
+    }
+
+    /// Sets the view of the spectated player sent by the server.
+    pub fn set_target(&mut self, target: CameraKeyframe) {
+        let far = self.camera.is_none_or(|camera| {
+            camera.position.distance(target.position) > SPECTATE_SNAP_DISTANCE
+        });
+        if far {
+            self.camera = Some(target);
+        }
+        self.target = Some(target);
+    }
+
+    /// Eases the camera towards the spectated player.
+    pub fn update(&mut self, dt: f32) {
+        let (Some(camera), Some(target)) = (&mut self.camera, self.target) else {
+            return;
+        };
+        let t = 1.0 - (-SPECTATE_SMOOTHING * dt).exp();
+        let delta_yaw = (target.yaw - camera.yaw + 180.0).rem_euclid(360.0) - 180.0;
+        camera.position = camera.position.lerp(target.position, t);
+        camera.yaw = (camera.yaw + delta_yaw * t).rem_euclid(360.0);
+        camera.pitch += (target.pitch - camera.pitch) * t;
+    }
+
+    /// Returns the position of the camera, if the server sent the view of the spectated player.
+    pub fn position(&self) -> Option<Vec3> {
+        self.camera.map(|camera| camera.position)
+    }
+
+    /// Returns where the camera is and where it looks, if the server sent the view of the
+    /// spectated player.
+    pub fn camera(&self) -> Option<CameraKeyframe> {
+        self.camera
+    }
+}
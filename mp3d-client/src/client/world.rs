@@ -2,18 +2,77 @@
 
 use std::collections::HashMap;
 
-use glam::{IVec3, Vec3};
+use glam::{IVec3, Vec3, Vec3Swizzles};
 use mp3d_core::{
     block::{BlockId, BlockState, block_registry},
+    direction::Direction,
     physics::CollisionWorld,
     uniquequeue::UniqueQueue,
-    world::chunk::CHUNK_SIZE,
+    world::{
+        DAY_LENGTH,
+        chunk::{CHUNK_SIZE, Chunk},
+        entitystore::entity_chunk,
+        light::{self, LightWorld},
+    },
 };
 
 use crate::client::chunk::ClientChunk;
 
-/// Number of chunks to render around the player
-const RENDER_DISTANCE: i32 = 8;
+/// Number of chunks to render around the player unless changed in the options.
+pub const DEFAULT_RENDER_DISTANCE: i32 = 8;
+/// How far an entity has to move horizontally between two snapshots to count as walking.
+const MOVING_DISTANCE: f32 = 0.001;
+/// How fast the shown time catches up with a jump in time, in ticks per second.
+const TIME_FAST_FORWARD_SPEED: f64 = 6000.0;
+
+/// An entity other than the player of this client, as far as the client knows it.
+pub struct ClientEntity {
+    /// The identifier of the entity type, like `"dummy"`, which picks how the entity is drawn.
+    pub entity_type: String,
+    pub position: Vec3,
+    pub yaw: f32,
+    /// The pitch the entity looks at, which tilts its head. Only players send it.
+    pub pitch: f32,
+    /// Whether the entity walked since its previous snapshot, which plays the walk animation.
+    pub moving: bool,
+    /// The name shown above the entity, which is the username for players.
+    pub name: Option<String>,
+}
+
+impl ClientEntity {
+    /// Reads the entity from the start of its snapshot, see [`mp3d_core::entity::Entity::snapshot`].
+    pub fn from_snapshot(entity_type: String, snapshot: &[u8]) -> Option<Self> {
+        let float = |i: usize| {
+            let bytes = snapshot.get(8 + i * 4..12 + i * 4)?;
+            Some(f32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        Some(Self {
+            entity_type,
+            position: Vec3::new(float(0)?, float(1)?, float(2)?),
+            yaw: float(3)?,
+            pitch: 0.0,
+            moving: false,
+            name: None,
+        })
+    }
+
+    /// Moves the entity to where a newer snapshot of it says it is.
+    pub fn update_from_snapshot(&mut self, snapshot: &[u8]) -> Option<()> {
+        let updated = Self::from_snapshot(String::new(), snapshot)?;
+        self.moving = (updated.position - self.position).xz().length() > MOVING_DISTANCE;
+        self.position = updated.position;
+        self.yaw = updated.yaw;
+        Some(())
+    }
+
+    /// Moves a player to where a [`mp3d_core::protocol::S2CMessage::PlayerMoved`] says it is.
+    pub fn move_to(&mut self, position: Vec3, yaw: f32, pitch: f32) {
+        self.moving = (position - self.position).xz().length() > MOVING_DISTANCE;
+        self.position = position;
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+}
 
 /// Client-side world representation.
 ///
@@ -22,11 +81,22 @@ const RENDER_DISTANCE: i32 = 8;
 pub struct ClientWorld {
     /// A mapping of chunk positions to their corresponding client-side chunk data.
     pub chunks: HashMap<IVec3, ClientChunk>,
+    /// The entities in the loaded chunks other than the player, by entity ID.
+    pub entities: HashMap<u64, ClientEntity>,
     /// Changes done to the world that haven't been sent to the server yet.
     #[allow(unused)]
     pub pending_changes: Vec<(IVec3, (BlockId, BlockState))>,
     /// Queue of chunks that need to be remeshed.
     pub remesh_queue: RemeshQueue,
+    /// The world time in ticks as shown to the player, which catches up smoothly with jumps in
+    /// time.
+    pub time: f64,
+    /// The world time in ticks according to the server.
+    target_time: f64,
+    /// Whether the world time advances, which is false if the daylight cycle is stopped.
+    time_advancing: bool,
+    /// Number of chunks to load and render around the player.
+    pub render_distance: i32,
 }
 
 impl ClientWorld {
@@ -34,11 +104,39 @@ impl ClientWorld {
     pub fn new() -> Self {
         Self {
             chunks: HashMap::new(),
+            entities: HashMap::new(),
             pending_changes: Vec::new(),
             remesh_queue: RemeshQueue::default(),
+            time: 0.0,
+            target_time: 0.0,
+            time_advancing: true,
+            render_distance: DEFAULT_RENDER_DISTANCE,
         }
     }
 
+    /// Updates the world time received from the server. Small jumps forward, like skipping the
+    /// night, are fast-forwarded, while anything else is applied immediately.
+    pub fn set_time(&mut self, time: u64, advancing: bool) {
+        let time = time as f64;
+        if time < self.time || time - self.time > DAY_LENGTH as f64 {
+            self.time = time;
+        }
+        self.target_time = time;
+        self.time_advancing = advancing;
+    }
+
+    /// Advances the world time by `dt` seconds at `tick_rate` ticks per second.
+    pub fn advance_time(&mut self, dt: f32, tick_rate: f32) {
+        let ticks = if self.time_advancing {
+            (dt * tick_rate) as f64
+        } else {
+            0.0
+        };
+        self.target_time += ticks;
+        let step = ticks.max(dt as f64 * TIME_FAST_FORWARD_SPEED);
+        self.time = (self.time + step).min(self.target_time);
+    }
+
     /// Gets a block at the given world position.
     pub fn get_block_at(&self, world_pos: IVec3) -> Option<(BlockId, &BlockState)> {
         let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
@@ -49,6 +147,23 @@ impl ClientWorld {
             .and_then(|c| c.get_block(local_pos))
     }
 
+    /// Adds a chunk received from the server and lights it.
+    pub fn insert_chunk(&mut self, chunk_pos: IVec3, chunk: Chunk) {
+        self.chunks.insert(chunk_pos, chunk.into());
+        let changed = light::light_chunk(self, chunk_pos);
+        self.mark_relit(changed, false);
+    }
+
+    /// Queues the chunks whose light changed to be re-meshed.
+    fn mark_relit(&mut self, changed: impl IntoIterator<Item = IVec3>, urgent: bool) {
+        for chunk_pos in changed {
+            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+                chunk.dirty = true;
+                self.remesh_queue.push(chunk_pos, urgent);
+            }
+        }
+    }
+
     /// Sets a block at the given world position.
     pub fn set_block_at(
         &mut self,
@@ -68,48 +183,56 @@ impl ClientWorld {
             self.remesh_queue.push(chunk_pos, urgent);
         }
 
-        // Mark neighboring chunks as dirty if the block is on the edge of the chunk
-        if local_pos.x == 0 {
-            if let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(-1, 0, 0))) {
-                neighbor.dirty = true;
-                self.remesh_queue
-                    .push(chunk_pos + IVec3::new(-1, 0, 0), urgent);
+        // Only the chunks the block touches need to be re-meshed: the ones sharing a face with it
+        // for face culling, and along edges and corners also the diagonal ones for ambient
+        // occlusion.
+        let axis_offsets = |local: i32| -> &'static [i32] {
+            if local == 0 {
+                &[0, -1]
+            } else if local == CHUNK_SIZE as i32 - 1 {
+                &[0, 1]
+            } else {
+                &[0]
             }
-        } else if local_pos.x == CHUNK_SIZE as i32 - 1
-            && let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(1, 0, 0)))
-        {
-            neighbor.dirty = true;
-            self.remesh_queue
-                .push(chunk_pos + IVec3::new(1, 0, 0), urgent);
-        }
-
-        if local_pos.y == 0 {
-            if let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(0, -1, 0))) {
-                neighbor.dirty = true;
-                self.remesh_queue
-                    .push(chunk_pos + IVec3::new(0, -1, 0), urgent);
+        };
+        for &dx in axis_offsets(local_pos.x) {
+            for &dy in axis_offsets(local_pos.y) {
+                for &dz in axis_offsets(local_pos.z) {
+                    let offset = IVec3::new(dx, dy, dz);
+                    if offset == IVec3::ZERO {
+                        continue;
+                    }
+                    if let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + offset)) {
+                        neighbor.dirty = true;
+                        self.remesh_queue.push(chunk_pos + offset, urgent);
+                    }
+                }
             }
-        } else if local_pos.y == CHUNK_SIZE as i32 - 1
-            && let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(0, 1, 0)))
-        {
-            neighbor.dirty = true;
-            self.remesh_queue
-                .push(chunk_pos + IVec3::new(0, 1, 0), urgent);
         }
 
-        if local_pos.z == 0 {
-            if let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(0, 0, -1))) {
-                neighbor.dirty = true;
-                self.remesh_queue
-                    .push(chunk_pos + IVec3::new(0, 0, -1), urgent);
-            }
-        } else if local_pos.z == CHUNK_SIZE as i32 - 1
-            && let Some(neighbor) = self.chunks.get_mut(&(chunk_pos + IVec3::new(0, 0, 1)))
-        {
-            neighbor.dirty = true;
-            self.remesh_queue
-                .push(chunk_pos + IVec3::new(0, 0, 1), urgent);
-        }
+        let changed = light::relight_block(self, world_pos);
+        self.mark_relit(changed, urgent);
+    }
+
+    /// Gets the sky light and block light at the given world position, if its chunk is loaded.
+    pub fn get_light_at(&self, world_pos: IVec3) -> Option<(u8, u8)> {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let chunk = &self.chunks.get(&chunk_pos)?.chunk;
+        Some((
+            chunk.get_light(local_pos, light::LightChannel::Sky),
+            chunk.get_light(local_pos, light::LightChannel::Block),
+        ))
+    }
+
+    /// Returns whether every neighbour of a chunk is loaded and covers it with a full side, so
+    /// that nothing in it can be seen from outside of it.
+    pub fn is_enclosed(&self, chunk_pos: IVec3) -> bool {
+        Direction::ALL.into_iter().all(|dir| {
+            self.chunks
+                .get(&(chunk_pos + dir))
+                .is_some_and(|neighbor| neighbor.is_side_full(dir.opposite()))
+        })
     }
 
     /// Checks if the client-side world requires more chunks, and if so returns their coordinates.
@@ -117,12 +240,12 @@ impl ClientWorld {
         let mut chunks = Vec::new();
         let chunk_pos = pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
 
-        for x in -RENDER_DISTANCE..=RENDER_DISTANCE {
-            for y in -RENDER_DISTANCE..=RENDER_DISTANCE {
-                for z in -RENDER_DISTANCE..=RENDER_DISTANCE {
+        for x in -self.render_distance..=self.render_distance {
+            for y in -self.render_distance..=self.render_distance {
+                for z in -self.render_distance..=self.render_distance {
                     let offset = IVec3::new(x, y, z);
                     let distance = offset.length_squared();
-                    if distance > RENDER_DISTANCE * RENDER_DISTANCE
+                    if distance > self.render_distance * self.render_distance
                         || self.chunks.contains_key(&(chunk_pos + offset))
                     {
                         continue;
@@ -138,7 +261,59 @@ impl ClientWorld {
         chunks
     }
 
-    /// Unloads chunks that are outside the render distance.
+    /// Returns the fraction of the chunks within render distance of `pos` that are loaded.
+    pub fn load_progress(&self, pos: IVec3) -> f32 {
+        let chunk_pos = pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let mut total = 0;
+        let mut loaded = 0;
+
+        for x in -self.render_distance..=self.render_distance {
+            for y in -self.render_distance..=self.render_distance {
+                for z in -self.render_distance..=self.render_distance {
+                    let offset = IVec3::new(x, y, z);
+                    if offset.length_squared() > self.render_distance * self.render_distance {
+                        continue;
+                    }
+                    total += 1;
+                    if self.chunks.contains_key(&(chunk_pos + offset)) {
+                        loaded += 1;
+                    }
+                }
+            }
+        }
+
+        loaded as f32 / total as f32
+    }
+
+    /// Returns the distance in blocks from `pos` to the closest chunk within render distance that
+    /// is not loaded. If all of them are, this is one chunk less than the render distance, which is
+    /// about as far as the corners of the loaded sphere of chunks reach in every direction.
+    pub fn loaded_distance(&self, pos: Vec3) -> f32 {
+        let chunk_size = CHUNK_SIZE as f32;
+        let chunk_pos = pos.div_euclid(Vec3::splat(chunk_size)).as_ivec3();
+        let mut nearest = ((self.render_distance - 1) as f32 * chunk_size).powi(2);
+
+        for x in -self.render_distance..=self.render_distance {
+            for y in -self.render_distance..=self.render_distance {
+                for z in -self.render_distance..=self.render_distance {
+                    let offset = IVec3::new(x, y, z);
+                    if offset.length_squared() > self.render_distance * self.render_distance
+                        || self.chunks.contains_key(&(chunk_pos + offset))
+                    {
+                        continue;
+                    }
+                    let min = (chunk_pos + offset).as_vec3() * chunk_size;
+                    let max = min + Vec3::splat(chunk_size);
+                    let outside = (min - pos).max(pos - max).max(Vec3::ZERO);
+                    nearest = nearest.min(outside.length_squared());
+                }
+            }
+        }
+
+        nearest.sqrt()
+    }
+
+    /// Unloads chunks that are outside the render distance, along with the entities in them.
     pub fn unload_chunks(&mut self, player_pos: Vec3) -> Vec<IVec3> {
         let chunk_pos = player_pos.div_euclid(Vec3::splat(CHUNK_SIZE as f32));
         let mut to_remove = Vec::new();
@@ -146,7 +321,7 @@ impl ClientWorld {
         for pos in self.chunks.keys() {
             let pos_float = pos.as_vec3() + Vec3::splat(0.5);
             let distance = pos_float.distance_squared(chunk_pos);
-            if distance > (RENDER_DISTANCE * RENDER_DISTANCE) as f32 {
+            if distance > (self.render_distance * self.render_distance) as f32 {
                 to_remove.push(*pos);
             }
         }
@@ -155,6 +330,8 @@ impl ClientWorld {
             self.chunks.remove(pos);
             self.remesh_queue.remove(pos);
         }
+        self.entities
+            .retain(|_, entity| !to_remove.contains(&entity_chunk(entity.position)));
 
         to_remove
     }
@@ -195,6 +372,18 @@ impl CollisionWorld for ClientWorld {
     }
 }
 
+impl LightWorld for ClientWorld {
+    fn chunk(&self, chunk_pos: IVec3) -> Option<&Chunk> {
+        self.chunks.get(&chunk_pos).map(|chunk| &chunk.chunk)
+    }
+
+    fn chunk_mut(&mut self, chunk_pos: IVec3) -> Option<&mut Chunk> {
+        self.chunks
+            .get_mut(&chunk_pos)
+            .map(|chunk| &mut chunk.chunk)
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct RemeshQueue {
     urgent: UniqueQueue<IVec3>,
@@ -238,6 +427,14 @@ impl RemeshQueue {
         }
         drained
     }
+
+    /// Takes up to `n` chunks from the queue, urgent ones first and otherwise nearest to `center`
+    /// first. The rest stay queued for the following frames.
+    pub fn drain_nearest(&mut self, n: usize, center: IVec3) -> Vec<IVec3> {
+        self.urgent.sort_by_key(|pos| pos.distance_squared(center));
+        self.normal.sort_by_key(|pos| pos.distance_squared(center));
+        self.drain(n)
+    }
 }
 
 impl Iterator for RemeshQueue {
@@ -0,0 +1,379 @@
+//! The state of the keyboard and the mouse, shared by the scenes, the in-game client and the
+//! widgets.
+//!
+//! [`Input`] turns the SDL events of every frame into what is held down and what happened during
+//! the frame, which everything reads through an [`UpdateContext`] instead of looking at events on
+//! its own. Whether the mouse is captured for looking around is decided by the scene on top, see
+//! [`crate::scenes::Scene::captures_mouse`].
+//!
+//! The keys for playing are looked up through [`KeyBindings`] by the [`Action`] they trigger, so
+//! they can be changed in the options. Keys for moving around menus and editing text, like Escape
+//! and Return, are fixed.
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    mouse::MouseButton,
+};
+use serde::{Deserialize, Serialize};
+
+/// The current state of the keyboard.
+#[derive(Default)]
+pub struct KeyboardState {
+    pub down: HashSet<Keycode>,
+    /// The keys pressed this frame, including the presses repeated while a key is held.
+    pub repeated: HashSet<Keycode>,
+    pub pressed: HashSet<Keycode>,
+    pub released: HashSet<Keycode>,
+    /// The text typed this frame.
+    pub text_input: String,
+}
+
+/// The current state of the mouse.
+#[derive(Default)]
+pub struct MouseState {
+    pub position: Vec2,
+    /// How far the mouse moved this frame.
+    pub delta: Vec2,
+    pub down: HashSet<MouseButton>,
+    pub pressed: HashSet<MouseButton>,
+    pub released: HashSet<MouseButton>,
+    /// How far the mouse wheel turned this frame.
+    pub scroll_delta: Vec2,
+}
+
+/// Context provided to widgets during the update phase.
+pub struct UpdateContext<'a> {
+    pub keyboard: &'a KeyboardState,
+    pub mouse: &'a MouseState,
+    pub delta_time: f32,
+}
+
+impl<'a> UpdateContext<'a> {
+    /// Creates a new `UpdateContext` from the given keyboard and mouse states and delta time.
+    pub fn new(keyboard: &'a KeyboardState, mouse: &'a MouseState, delta_time: f32) -> Self {
+        Self {
+            keyboard,
+            mouse,
+            delta_time,
+        }
+    }
+}
+
+/// Tracks the keyboard and the mouse from the events of the window.
+#[derive(Default)]
+pub struct Input {
+    pub keyboard: KeyboardState,
+    pub mouse: MouseState,
+}
+
+impl Input {
+    /// Forgets what happened during the previous frame, keeping what is still held down.
+    pub fn begin_frame(&mut self) {
+        self.keyboard.repeated.clear();
+        self.keyboard.pressed.clear();
+        self.keyboard.released.clear();
+        self.keyboard.text_input.clear();
+        self.mouse.delta = Vec2::ZERO;
+        self.mouse.scroll_delta = Vec2::ZERO;
+        self.mouse.pressed.clear();
+        self.mouse.released.clear();
+    }
+
+    /// Records an event. Movement, scrolling and typing add up over the frame, so nothing is lost
+    /// when several events arrive at once.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                // The window doesn't hear about keys let go of elsewhere, so nothing stays held
+                self.keyboard.released.extend(self.keyboard.down.drain());
+                self.mouse.released.extend(self.mouse.down.drain());
+            }
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => {
+                self.mouse.position = Vec2::new(*x as f32, *y as f32);
+                self.mouse.delta += Vec2::new(*xrel as f32, *yrel as f32);
+            }
+            Event::MouseWheel { x, y, .. } => {
+                self.mouse.scroll_delta += Vec2::new(*x as f32, *y as f32);
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.mouse.down.insert(*mouse_btn);
+                self.mouse.pressed.insert(*mouse_btn);
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                self.mouse.down.remove(mouse_btn);
+                self.mouse.released.insert(*mouse_btn);
+            }
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat,
+                ..
+            } => {
+                self.keyboard.repeated.insert(*keycode);
+                if !repeat {
+                    self.keyboard.down.insert(*keycode);
+                }
+                self.keyboard.pressed.insert(*keycode);
+            }
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => {
+                self.keyboard.down.remove(keycode);
+                self.keyboard.released.insert(*keycode);
+            }
+            Event::TextInput { text, .. } => {
+                self.keyboard.text_input.push_str(text);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the context the scenes and widgets read the input of this frame from.
+    pub fn context(&self, delta_time: f32) -> UpdateContext<'_> {
+        UpdateContext::new(&self.keyboard, &self.mouse, delta_time)
+    }
+}
+
+/// Something the player does with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    Jump,
+    Sneak,
+    /// Held together with [`Action::Forward`].
+    Sprint,
+    Inventory,
+    CreativePicker,
+    /// Held to pick from the recently placed blocks.
+    RecentBlocks,
+    SwapOffhand,
+    Chat,
+    Command,
+    BuildLine,
+    BuildRectangle,
+    Measure,
+    Mirror,
+    ThirdPerson,
+    Cinematic,
+    DebugInfo,
+    ReloadAssets,
+    Panorama,
+    Fullscreen,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
+}
+
+impl Action {
+    /// Every action, in the order they are listed in the options.
+    pub const ALL: [Action; 32] = [
+        Action::Forward,
+        Action::Back,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Jump,
+        Action::Sneak,
+        Action::Sprint,
+        Action::Inventory,
+        Action::CreativePicker,
+        Action::RecentBlocks,
+        Action::SwapOffhand,
+        Action::Chat,
+        Action::Command,
+        Action::BuildLine,
+        Action::BuildRectangle,
+        Action::Measure,
+        Action::Mirror,
+        Action::ThirdPerson,
+        Action::Cinematic,
+        Action::DebugInfo,
+        Action::ReloadAssets,
+        Action::Panorama,
+        Action::Fullscreen,
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+        Action::Hotbar6,
+        Action::Hotbar7,
+        Action::Hotbar8,
+        Action::Hotbar9,
+    ];
+
+    /// The actions selecting the hotbar slots, in order.
+    pub const HOTBAR: [Action; 9] = [
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+        Action::Hotbar6,
+        Action::Hotbar7,
+        Action::Hotbar8,
+        Action::Hotbar9,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Forward => "Walk Forward",
+            Action::Back => "Walk Backward",
+            Action::StrafeLeft => "Strafe Left",
+            Action::StrafeRight => "Strafe Right",
+            Action::Jump => "Jump",
+            Action::Sneak => "Sneak",
+            Action::Sprint => "Sprint",
+            Action::Inventory => "Inventory",
+            Action::CreativePicker => "Creative Items",
+            Action::RecentBlocks => "Recent Blocks",
+            Action::SwapOffhand => "Swap Offhand",
+            Action::Chat => "Chat",
+            Action::Command => "Command",
+            Action::BuildLine => "Build Line",
+            Action::BuildRectangle => "Build Rectangle",
+            Action::Measure => "Measure",
+            Action::Mirror => "Mirror",
+            Action::ThirdPerson => "Third Person",
+            Action::Cinematic => "Cinematic Mode",
+            Action::DebugInfo => "Debug Info",
+            Action::ReloadAssets => "Reload Assets",
+            Action::Panorama => "Capture Panorama",
+            Action::Fullscreen => "Toggle Fullscreen",
+            Action::Hotbar1 => "Hotbar Slot 1",
+            Action::Hotbar2 => "Hotbar Slot 2",
+            Action::Hotbar3 => "Hotbar Slot 3",
+            Action::Hotbar4 => "Hotbar Slot 4",
+            Action::Hotbar5 => "Hotbar Slot 5",
+            Action::Hotbar6 => "Hotbar Slot 6",
+            Action::Hotbar7 => "Hotbar Slot 7",
+            Action::Hotbar8 => "Hotbar Slot 8",
+            Action::Hotbar9 => "Hotbar Slot 9",
+        }
+    }
+
+    pub fn default_key(self) -> Keycode {
+        match self {
+            Action::Forward => Keycode::W,
+            Action::Back => Keycode::S,
+            Action::StrafeLeft => Keycode::A,
+            Action::StrafeRight => Keycode::D,
+            Action::Jump => Keycode::Space,
+            Action::Sneak => Keycode::LShift,
+            Action::Sprint => Keycode::LCtrl,
+            Action::Inventory => Keycode::E,
+            Action::CreativePicker => Keycode::C,
+            Action::RecentBlocks => Keycode::R,
+            Action::SwapOffhand => Keycode::F,
+            Action::Chat => Keycode::T,
+            Action::Command => Keycode::Slash,
+            Action::BuildLine => Keycode::LAlt,
+            Action::BuildRectangle => Keycode::V,
+            Action::Measure => Keycode::N,
+            Action::Mirror => Keycode::M,
+            Action::ThirdPerson => Keycode::F5,
+            Action::Cinematic => Keycode::F1,
+            Action::DebugInfo => Keycode::F3,
+            Action::ReloadAssets => Keycode::F6,
+            Action::Panorama => Keycode::F9,
+            Action::Fullscreen => Keycode::F11,
+            Action::Hotbar1 => Keycode::Num1,
+            Action::Hotbar2 => Keycode::Num2,
+            Action::Hotbar3 => Keycode::Num3,
+            Action::Hotbar4 => Keycode::Num4,
+            Action::Hotbar5 => Keycode::Num5,
+            Action::Hotbar6 => Keycode::Num6,
+            Action::Hotbar7 => Keycode::Num7,
+            Action::Hotbar8 => Keycode::Num8,
+            Action::Hotbar9 => Keycode::Num9,
+        }
+    }
+}
+
+/// The key bound to every action. Actions that weren't rebound use their
+/// [`Action::default_key`].
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Keycode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Creates the bindings from the keys chosen in the options, named like in SDL. Keys that
+    /// can't be read keep their default.
+    pub fn from_names(names: &HashMap<Action, String>) -> Self {
+        let mut bindings = Self::default();
+        for (action, name) in names {
+            match Keycode::from_name(name) {
+                Some(key) => {
+                    bindings.keys.insert(*action, key);
+                }
+                None => log::warn!("Unknown key {:?} bound to {:?}", name, action),
+            }
+        }
+        bindings
+    }
+
+    /// Returns the names of the keys that differ from the defaults, as stored in the options.
+    pub fn to_names(&self) -> HashMap<Action, String> {
+        self.keys
+            .iter()
+            .filter(|(action, key)| action.default_key() != **key)
+            .map(|(action, key)| (*action, key.name()))
+            .collect()
+    }
+
+    pub fn key(&self, action: Action) -> Keycode {
+        self.keys[&action]
+    }
+
+    /// Binds `key` to `action`. An action the key was bound to before gets the previous key of
+    /// `action`, so no key triggers two actions.
+    pub fn bind(&mut self, action: Action, key: Keycode) {
+        let previous = self.key(action);
+        for bound in self.keys.values_mut() {
+            if *bound == key {
+                *bound = previous;
+            }
+        }
+        self.keys.insert(action, key);
+    }
+
+    /// Returns whether the key of `action` is held down.
+    pub fn down(&self, keyboard: &KeyboardState, action: Action) -> bool {
+        keyboard.down.contains(&self.key(action))
+    }
+
+    /// Returns whether the key of `action` was pressed this frame.
+    pub fn pressed(&self, keyboard: &KeyboardState, action: Action) -> bool {
+        keyboard.pressed.contains(&self.key(action))
+    }
+}
@@ -12,10 +12,14 @@ use crate::{abs::*, render::ui::uirenderer::UIRenderer};
 
 mod abs;
 mod client;
+mod input;
+mod narrator;
 mod other;
+mod plugins;
 mod render;
 mod resource;
 mod scenes;
+mod update;
 
 #[macro_export]
 macro_rules! shader_program {
@@ -47,6 +51,44 @@ macro_rules! shader_program {
     }};
 }
 
+/// Logs every pending OpenGL error along with the location of the check and a description of what
+/// was being done. Unlike the debug output this works on every context, so it is used after creating
+/// GL objects, where a silent error would otherwise only show up as missing geometry or a black
+/// screen.
+#[macro_export]
+macro_rules! check_gl_error {
+    ($gl:expr, $($what:tt)+) => {{
+        let gl: &glow::Context = &$gl;
+        loop {
+            // The macro may be used both inside and outside of unsafe blocks
+            #[allow(unused_unsafe)]
+            let error = unsafe { glow::HasContext::get_error(gl) };
+            if error == glow::NO_ERROR {
+                break;
+            }
+            let name = match error {
+                glow::INVALID_ENUM => "GL_INVALID_ENUM",
+                glow::INVALID_VALUE => "GL_INVALID_VALUE",
+                glow::INVALID_OPERATION => "GL_INVALID_OPERATION",
+                glow::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+                glow::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+                _ => "unknown error",
+            };
+            log::error!(
+                "{} (0x{:x}) at {}:{} while {}",
+                name,
+                error,
+                file!(),
+                line!(),
+                format_args!($($what)+)
+            );
+        }
+    }};
+}
+
+/// The splash screen has nothing to animate, so there is no point in drawing it more often.
+const SPLASH_FPS_LIMIT: u32 = 30;
+
 pub const ASSETS: include_dir::Dir<'_> =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/src/assets");
 
@@ -97,9 +139,52 @@ pub fn get_dbg_dir() -> PathBuf {
     dbg_dir
 }
 
-fn main() {
+/// Prints the save migrations that loading a world would run without changing it, for the
+/// `--migrate-dry-run <world>` flag. The world is the name of a folder in the saves directory or
+/// a path to one.
+fn migrate_dry_run(world: &str) -> std::process::ExitCode {
+    let saves_path = get_saves_dir().join(world);
+    let world_path = if saves_path.is_dir() {
+        saves_path
+    } else {
+        PathBuf::from(world)
+    };
+    match mp3d_core::saving::migration::migrate(&world_path, true) {
+        Ok(steps) if steps.is_empty() => {
+            println!("{} is up to date", world_path.display());
+            std::process::ExitCode::SUCCESS
+        }
+        Ok(steps) => {
+            println!("Loading {} would run:", world_path.display());
+            for migration in steps {
+                println!(
+                    "  {} -> {}: {}",
+                    migration.from,
+                    migration.from + 1,
+                    migration.description
+                );
+            }
+            std::process::ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Can't migrate {}: {}", world_path.display(), e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> std::process::ExitCode {
     mp3d_core::init();
 
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--migrate-dry-run") {
+        let Some(world) = args.get(index + 1) else {
+            eprintln!("Usage: --migrate-dry-run <world>");
+            return std::process::ExitCode::FAILURE;
+        };
+        return migrate_dry_run(world);
+    }
+
     let log_file_path = get_game_dir().join("game.log");
 
     if log_file_path.exists() {
@@ -152,7 +237,34 @@ fn main() {
 
     log::info!("Mineplace3D {}", env!("CARGO_PKG_VERSION"));
 
-    let mut app = App::new("Mineplace3D", 1280, 720, false);
+    log::info!("Loading config...");
+    let config = scenes::options::ClientConfig::load();
+
+    let (window_width, window_height) = config.window_size();
+    let mut app = App::new(
+        "Mineplace3D",
+        window_width,
+        window_height,
+        config.window_mode(),
+        config.gl_debug(),
+    );
+    if !config.vsync()
+        && let Err(e) = set_vsync(&app.sdl, false)
+    {
+        log::error!("Failed to disable vsync: {}", e);
+    }
+    if let Some((x, y)) = config.window_position {
+        app.window.set_position(
+            sdl2::video::WindowPos::Positioned(x),
+            sdl2::video::WindowPos::Positioned(y),
+        );
+    }
+    if config.window_mode() == WindowMode::Fullscreen
+        && let Err(e) =
+            apply_window_mode(&mut app.window, WindowMode::Fullscreen, config.resolution)
+    {
+        log::error!("Failed to apply fullscreen resolution: {}", e);
+    }
 
     log::info!("Initialized SDL2 and OpenGL context");
     unsafe {
@@ -187,30 +299,24 @@ fn main() {
 
     let shader_program = shader_program!(ui, app.gl, ".");
 
-    let mut keyboard_state = other::KeyboardState::default();
-    let mut mouse_state = other::MouseState::default();
+    let mut input = input::Input::default();
 
+    let (width, height) = app.window.size();
+    unsafe {
+        app.gl.viewport(0, 0, width as i32, height as i32);
+    }
     let mut ui_renderer = UIRenderer::new(
         &app.gl,
         shader_program,
-        Mat4::orthographic_rh_gl(0.0, 1280.0, 720.0, 0.0, -20.0, 20.0),
+        Mat4::orthographic_rh_gl(0.0, width as f32, height as f32, 0.0, -20.0, 20.0),
     );
 
-    log::info!("Loading config...");
-    let config = scenes::options::ClientConfig::load();
-
     log::info!("Loading assets...");
-    let assets = Arc::new(
-        scenes::Assets::load(&app.gl, &mut app.window, &config).unwrap_or_else(|e| {
-            panic!("Failed to load assets: {}", e);
-        }),
-    );
-
-    if config.fullscreen() {
-        app.window
-            .set_fullscreen(sdl2::video::FullscreenType::Desktop)
-            .unwrap();
-    }
+    let Some(assets) = load_assets(&mut app, &mut ui_renderer, &config) else {
+        log::info!("Window closed while loading assets");
+        return std::process::ExitCode::SUCCESS;
+    };
+    let assets = Arc::new(assets);
 
     log::info!(
         "Using resource packs: {}",
@@ -218,33 +324,33 @@ fn main() {
     );
 
     let mut scene_manager = scenes::SceneManager::new(
-        Box::new(scenes::titlescreen::TitleScreen::new(&assets, (1280, 720))),
+        Box::new(scenes::titlescreen::TitleScreen::new(
+            &assets,
+            app.window.size(),
+            &config,
+        )),
         assets,
         config,
     );
 
     let mut last_frame_time = std::time::Instant::now();
+    let mut frame_limiter = other::FrameLimiter::new();
+    let mut focused = true;
 
     'running: loop {
         let now = std::time::Instant::now();
         let delta_time = now.duration_since(last_frame_time).as_secs_f32();
         last_frame_time = now;
 
-        mouse_state.delta = Vec2::ZERO;
-        mouse_state.scroll_delta = Vec2::ZERO;
-        keyboard_state.repeated.clear();
-        keyboard_state.pressed.clear();
-        keyboard_state.released.clear();
-        keyboard_state.text_input.clear();
-        mouse_state.pressed.clear();
-        mouse_state.released.clear();
+        input.begin_frame();
 
         for event in app.event_pump.poll_iter() {
             scene_manager.handle_event(&app.gl, &event);
+            input.handle_event(&event);
             match event {
                 sdl2::event::Event::Quit { .. } => break 'running,
                 sdl2::event::Event::Window {
-                    win_event: sdl2::event::WindowEvent::Resized(width, height),
+                    win_event: sdl2::event::WindowEvent::SizeChanged(width, height),
                     ..
                 } => {
                     unsafe {
@@ -259,58 +365,121 @@ fn main() {
                         20.0,
                     );
                 }
-                sdl2::event::Event::MouseMotion {
-                    x, y, xrel, yrel, ..
-                } => {
-                    mouse_state.position = Vec2::new(x as f32, y as f32);
-                    mouse_state.delta = Vec2::new(xrel as f32, yrel as f32);
-                }
-                sdl2::event::Event::MouseWheel { x, y, .. } => {
-                    mouse_state.scroll_delta = Vec2::new(x as f32, y as f32);
-                }
-                sdl2::event::Event::MouseButtonDown { mouse_btn, .. } => {
-                    mouse_state.down.insert(mouse_btn);
-                    mouse_state.pressed.insert(mouse_btn);
-                }
-                sdl2::event::Event::MouseButtonUp { mouse_btn, .. } => {
-                    mouse_state.down.remove(&mouse_btn);
-                    mouse_state.released.insert(mouse_btn);
-                }
-                sdl2::event::Event::KeyDown {
-                    keycode: Some(keycode),
-                    repeat,
+                sdl2::event::Event::Window {
+                    win_event: sdl2::event::WindowEvent::FocusGained,
                     ..
-                } => {
-                    if repeat {
-                        keyboard_state.repeated.insert(keycode);
-                    } else {
-                        keyboard_state.repeated.insert(keycode);
-                        keyboard_state.down.insert(keycode);
-                    }
-                    keyboard_state.pressed.insert(keycode);
-                }
-                sdl2::event::Event::KeyUp {
-                    keycode: Some(keycode),
+                } => focused = true,
+                sdl2::event::Event::Window {
+                    win_event: sdl2::event::WindowEvent::FocusLost,
                     ..
-                } => {
-                    keyboard_state.down.remove(&keycode);
-                    keyboard_state.released.insert(keycode);
-                }
-                sdl2::event::Event::TextInput { text, .. } => {
-                    keyboard_state.text_input = text;
-                }
+                } => focused = false,
                 _ => {}
             }
         }
 
-        let update_ctx = other::UpdateContext::new(&keyboard_state, &mouse_state, delta_time);
+        let update_ctx = input.context(delta_time);
         if !scene_manager.update(&app.gl, &update_ctx, &mut app.window, &app.sdl) {
             break 'running;
         }
 
         scene_manager.render(&app.gl, &mut ui_renderer);
         app.window.gl_swap_window();
+
+        let fps_limit = {
+            let config = scene_manager.config().read().unwrap();
+            if !focused && config.background_throttle() {
+                Some(scenes::options::BACKGROUND_FPS_LIMIT)
+            } else {
+                config.fps_limit()
+            }
+        };
+        frame_limiter.wait(fps_limit);
+    }
+
+    {
+        let mut config = scene_manager.config().write().unwrap();
+        if config.window_mode() == WindowMode::Windowed {
+            config.window_size = Some(app.window.size());
+            config.window_position = Some(app.window.position());
+        }
+        config.save();
     }
 
     log::info!("Quitting!");
+    std::process::ExitCode::SUCCESS
+}
+
+/// Decodes the assets on a background thread while showing a splash screen with the progress,
+/// then uploads them to the GPU. Returns `None` if the window was closed while loading.
+fn load_assets(
+    app: &mut App,
+    ui_renderer: &mut UIRenderer,
+    config: &scenes::options::ClientConfig,
+) -> Option<scenes::Assets> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let decode_config = config.clone();
+    let decoder = std::thread::Builder::new()
+        .name("asset-loader".to_string())
+        .spawn(move || {
+            scenes::DecodedAssets::decode(&decode_config, &|fraction, stage| {
+                let _ = sender.send((fraction, stage.to_string()));
+            })
+        })
+        .expect("Failed to spawn asset loading thread");
+
+    let mut frame_limiter = other::FrameLimiter::new();
+    let mut progress = 0.0;
+    let mut stage = String::new();
+    while !decoder.is_finished() {
+        for event in app.event_pump.poll_iter() {
+            match event {
+                sdl2::event::Event::Quit { .. } => return None,
+                sdl2::event::Event::Window {
+                    win_event: sdl2::event::WindowEvent::SizeChanged(width, height),
+                    ..
+                } => {
+                    unsafe {
+                        app.gl.viewport(0, 0, width, height);
+                    }
+                    ui_renderer.projection_matrix = Mat4::orthographic_rh_gl(
+                        0.0,
+                        width as f32,
+                        height as f32,
+                        0.0,
+                        -20.0,
+                        20.0,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        for (fraction, new_stage) in receiver.try_iter() {
+            progress = fraction;
+            if new_stage != stage {
+                log::info!("{}...", new_stage);
+                stage = new_stage;
+            }
+        }
+
+        let (width, height) = app.window.size();
+        render::splash::draw_splash(
+            &app.gl,
+            ui_renderer,
+            Vec2::new(width as f32, height as f32),
+            progress,
+        );
+        app.window.gl_swap_window();
+        frame_limiter.wait(Some(SPLASH_FPS_LIMIT));
+    }
+
+    let decoded = decoder
+        .join()
+        .expect("Asset loading thread panicked")
+        .unwrap_or_else(|e| panic!("Failed to load assets: {}", e));
+    Some(
+        decoded
+            .upload(&app.gl, &mut app.window)
+            .unwrap_or_else(|e| panic!("Failed to load assets: {}", e)),
+    )
 }
@@ -0,0 +1,83 @@
+//! Reading out the focused widget for players who can't see the screen well.
+//!
+//! Whenever another widget gets the focus, or the focused one changes what it says, the narrator
+//! speaks its [`Widget::semantic_label`]. The text goes to the log under the `narrator` target,
+//! and to the text to speech command of the system: `say` on macOS, the speech synthesizer of
+//! PowerShell on Windows and `spd-say` from speech-dispatcher anywhere else. Speaking something new
+//! stops whatever is still being spoken.
+
+use std::process::{Child, Command};
+
+use crate::render::ui::widgets::Widget;
+
+/// Reads out the focused widget of the current scene while enabled in the options.
+#[derive(Default)]
+pub struct Narrator {
+    /// The label spoken last, which isn't repeated while it stays focused.
+    spoken: Option<String>,
+    /// The text to speech command still speaking, if any.
+    speech: Option<Child>,
+}
+
+impl Narrator {
+    /// Speaks the label of the widget focused in `ui`, if it changed since the last call.
+    pub fn update(&mut self, ui: Option<&dyn Widget>) {
+        let label = ui
+            .and_then(|ui| ui.focused())
+            .and_then(|widget| widget.semantic_label());
+        if label == self.spoken {
+            return;
+        }
+        if let Some(label) = &label {
+            self.speak(label);
+        }
+        self.spoken = label;
+    }
+
+    fn speak(&mut self, text: &str) {
+        log::info!(target: "narrator", "{}", text);
+        if let Some(mut speech) = self.speech.take() {
+            let _ = speech.kill();
+            let _ = speech.wait();
+        }
+        match speech_command(text).spawn() {
+            Ok(child) => self.speech = Some(child),
+            Err(e) => log::warn!("Failed to run the text to speech command: {}", e),
+        }
+    }
+}
+
+impl Drop for Narrator {
+    fn drop(&mut self) {
+        if let Some(mut speech) = self.speech.take() {
+            let _ = speech.kill();
+            let _ = speech.wait();
+        }
+    }
+}
+
+/// Returns the command speaking `text` aloud on this system.
+fn speech_command(text: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        command.arg(text);
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-Command",
+            &format!(
+                "Add-Type -AssemblyName System.Speech; \
+                 (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
+                text.replace('\'', "''")
+            ),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("spd-say");
+        // Waiting keeps the command running while speaking, so it can be stopped
+        command.args(["--wait", "--", text]);
+        command
+    }
+}
@@ -1,43 +1,43 @@
-use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
-use glam::Vec2;
-use sdl2::{keyboard::Keycode, mouse::MouseButton};
+/// `thread::sleep` can overshoot by a millisecond or two, so the last part of the wait is spent
+/// busy waiting instead.
+const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
 
-/// The current state of the keyboard.
-#[derive(Default)]
-pub struct KeyboardState {
-    pub down: HashSet<Keycode>,
-    pub repeated: HashSet<Keycode>,
-    pub pressed: HashSet<Keycode>,
-    pub released: HashSet<Keycode>,
-    pub text_input: String,
+/// Caps the frame rate by waiting at the end of every frame.
+pub struct FrameLimiter {
+    next_frame: Instant,
 }
 
-/// The current state of the mouse.
-#[derive(Default)]
-pub struct MouseState {
-    pub position: Vec2,
-    pub delta: Vec2,
-    pub down: HashSet<MouseButton>,
-    pub pressed: HashSet<MouseButton>,
-    pub released: HashSet<MouseButton>,
-    pub scroll_delta: Vec2,
-}
+impl FrameLimiter {
+    /// Creates a new `FrameLimiter`, starting the first frame now.
+    pub fn new() -> Self {
+        Self {
+            next_frame: Instant::now(),
+        }
+    }
 
-/// Context provided to widgets during the update phase.
-pub struct UpdateContext<'a> {
-    pub keyboard: &'a KeyboardState,
-    pub mouse: &'a MouseState,
-    pub delta_time: f32,
-}
+    /// Waits until the next frame should start. A limit of `None` does not wait at all.
+    pub fn wait(&mut self, fps_limit: Option<u32>) {
+        let Some(fps_limit) = fps_limit.filter(|fps| *fps > 0) else {
+            self.next_frame = Instant::now();
+            return;
+        };
 
-impl<'a> UpdateContext<'a> {
-    /// Creates a new `UpdateContext` from the given keyboard and mouse states and delta time.
-    pub fn new(keyboard: &'a KeyboardState, mouse: &'a MouseState, delta_time: f32) -> Self {
-        Self {
-            keyboard,
-            mouse,
-            delta_time,
+        self.next_frame += Duration::from_secs_f64(1.0 / fps_limit as f64);
+        let now = Instant::now();
+        if self.next_frame <= now {
+            // The frame took longer than the limit allows, don't try to catch up on the next ones
+            self.next_frame = now;
+            return;
+        }
+
+        let remaining = self.next_frame - now;
+        if remaining > SPIN_THRESHOLD {
+            std::thread::sleep(remaining - SPIN_THRESHOLD);
+        }
+        while Instant::now() < self.next_frame {
+            std::thread::yield_now();
         }
     }
 }
@@ -0,0 +1,408 @@
+//! Client plugins, which add overlays, key bindings and markers to the game without changing the
+//! client.
+//!
+//! A plugin is a program in its own directory under `plugins` in the game directory, described by
+//! a `plugin.json` like `{"name": "Coordinates", "command": ["python3", "coords.py"]}`. The command
+//! runs in that directory while a world is open and talks to the game with one JSON object per
+//! line. Events are written to its standard input and requests are read from its standard output,
+//! while whatever it writes to standard error goes to the log of the game.
+//!
+//! The game sends these events:
+//! - `{"event": "state", "position": [x, y, z], "yaw": 0.0, "pitch": 0.0, "health": 20.0,
+//!   "time": 0.0}` a few times per second.
+//! - `{"event": "key", "action": "..."}` when a key bound by the plugin is pressed in game.
+//! - `{"event": "block_click", "position": [x, y, z], "face": [x, y, z], "block": "stone",
+//!   "right": false}` when the player clicks a block within reach.
+//!
+//! A plugin can make these requests:
+//! - `{"bind_key": {"action": "...", "key": "M"}}` to hear about presses of a key, named like in
+//!   SDL. The key still does whatever it does in the game.
+//! - `{"overlay": {"id": "...", "position": [x, y], "lines": ["..."]}}` to show text on the HUD,
+//!   `position` pixels from the top left corner of the screen. Lines can use color codes like chat
+//!   messages, and an overlay with the same `id` is replaced.
+//! - `{"remove_overlay": "..."}` to hide an overlay again.
+//! - `{"marker": {"id": "...", "position": [x, y, z], "text": "..."}}` to float a label in the
+//!   world, drawn like a name tag.
+//! - `{"remove_marker": "..."}` to take a marker down.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError},
+    thread,
+};
+
+use glam::{IVec3, Vec2, Vec3};
+use mp3d_core::textcomponent::TextComponent;
+use sdl2::keyboard::Keycode;
+use serde::{Deserialize, Serialize};
+
+use crate::input::KeyboardState;
+
+/// How often the state of the player is sent to plugins, in seconds.
+const STATE_INTERVAL: f32 = 0.1;
+/// How many events can wait to be written to a plugin. Events sent while the queue is full are
+/// dropped, so that a plugin that doesn't keep up can't stall the game.
+const EVENT_QUEUE_SIZE: usize = 256;
+
+/// The description of a plugin in its `plugin.json`.
+#[derive(Debug, Deserialize)]
+struct PluginManifest {
+    name: String,
+    /// The program to run and its arguments.
+    command: Vec<String>,
+}
+
+/// Something that happened in the game, sent to every plugin.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PluginEvent {
+    State {
+        position: [f32; 3],
+        yaw: f32,
+        pitch: f32,
+        health: f32,
+        time: f64,
+    },
+    Key {
+        action: String,
+    },
+    BlockClick {
+        position: [i32; 3],
+        face: [i32; 3],
+        block: &'static str,
+        right: bool,
+    },
+}
+
+impl PluginEvent {
+    pub fn block_click(position: IVec3, face: IVec3, block: &'static str, right: bool) -> Self {
+        Self::BlockClick {
+            position: position.to_array(),
+            face: face.to_array(),
+            block,
+            right,
+        }
+    }
+}
+
+/// Text shown on the HUD by a plugin.
+#[derive(Debug, Deserialize)]
+struct OverlayRequest {
+    id: String,
+    position: [f32; 2],
+    lines: Vec<String>,
+}
+
+/// A label floating in the world, placed by a plugin.
+#[derive(Debug, Deserialize)]
+struct MarkerRequest {
+    id: String,
+    position: [f32; 3],
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyBindingRequest {
+    action: String,
+    key: String,
+}
+
+/// A request read from a plugin.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PluginRequest {
+    BindKey(KeyBindingRequest),
+    Overlay(OverlayRequest),
+    RemoveOverlay(String),
+    Marker(MarkerRequest),
+    RemoveMarker(String),
+}
+
+pub struct Overlay {
+    pub position: Vec2,
+    pub lines: Vec<TextComponent>,
+}
+
+pub struct Marker {
+    pub position: Vec3,
+    pub text: TextComponent,
+}
+
+/// A running plugin and what it asked the game to show.
+struct Plugin {
+    name: String,
+    process: Child,
+    /// The lines to write to the plugin, which a thread writes to its standard input.
+    events: SyncSender<String>,
+    requests: Receiver<PluginRequest>,
+    key_bindings: Vec<(Keycode, String)>,
+    overlays: BTreeMap<String, Overlay>,
+    markers: BTreeMap<String, Marker>,
+}
+
+impl Plugin {
+    /// Starts the plugin described by the `plugin.json` in `dir`.
+    fn start(dir: &Path) -> Result<Self, String> {
+        let manifest = std::fs::read_to_string(dir.join("plugin.json"))
+            .map_err(|e| format!("Failed to read plugin.json: {}", e))?;
+        let manifest: PluginManifest =
+            serde_json::from_str(&manifest).map_err(|e| format!("Invalid plugin.json: {}", e))?;
+        let Some((program, args)) = manifest.command.split_first() else {
+            return Err("The command of the plugin is empty".to_string());
+        };
+
+        let mut process = Command::new(program)
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        let stdin = process.stdin.take().unwrap();
+        let stdout = process.stdout.take().unwrap();
+        let stderr = process.stderr.take().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let name = manifest.name.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(request) => {
+                        if tx.send(request).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => log::warn!("Invalid request from plugin {}: {}", name, e),
+                }
+            }
+        });
+        let name = manifest.name.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                log::info!(target: "plugin", "{}: {}", name, line);
+            }
+        });
+        let (events, rx) = mpsc::sync_channel::<String>(EVENT_QUEUE_SIZE);
+        thread::spawn(move || {
+            let mut stdin = stdin;
+            for line in rx {
+                if stdin
+                    .write_all(line.as_bytes())
+                    .and_then(|_| stdin.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            name: manifest.name,
+            process,
+            events,
+            requests,
+            key_bindings: Vec::new(),
+            overlays: BTreeMap::new(),
+            markers: BTreeMap::new(),
+        })
+    }
+
+    /// Handles the requests the plugin made since the last call. Returns `false` once the plugin
+    /// stopped.
+    fn poll(&mut self) -> bool {
+        loop {
+            match self.requests.try_recv() {
+                Ok(request) => self.handle(request),
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    fn handle(&mut self, request: PluginRequest) {
+        match request {
+            PluginRequest::BindKey(binding) => match Keycode::from_name(&binding.key) {
+                Some(key) => self.key_bindings.push((key, binding.action)),
+                None => log::warn!("Plugin {} bound unknown key {}", self.name, binding.key),
+            },
+            PluginRequest::Overlay(overlay) => {
+                let lines = overlay
+                    .lines
+                    .iter()
+                    .filter_map(|line| match line.parse() {
+                        Ok(line) => Some(line),
+                        Err(e) => {
+                            log::warn!("Plugin {} sent an invalid overlay line: {}", self.name, e);
+                            None
+                        }
+                    })
+                    .collect();
+                self.overlays.insert(
+                    overlay.id,
+                    Overlay {
+                        position: Vec2::from_array(overlay.position),
+                        lines,
+                    },
+                );
+            }
+            PluginRequest::RemoveOverlay(id) => {
+                self.overlays.remove(&id);
+            }
+            PluginRequest::Marker(marker) => match marker.text.parse() {
+                Ok(text) => {
+                    self.markers.insert(
+                        marker.id,
+                        Marker {
+                            position: Vec3::from_array(marker.position),
+                            text,
+                        },
+                    );
+                }
+                Err(e) => log::warn!("Plugin {} sent an invalid marker: {}", self.name, e),
+            },
+            PluginRequest::RemoveMarker(id) => {
+                self.markers.remove(&id);
+            }
+        }
+    }
+
+    /// Queues an event to be written to the plugin. Returns `false` if the plugin doesn't read
+    /// anymore.
+    fn send(&mut self, event: &PluginEvent) -> bool {
+        let mut line = serde_json::to_string(event).unwrap();
+        line.push('\n');
+        match self.events.try_send(line) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                log::warn!("Plugin {} is behind on events, dropped one", self.name);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// The plugins running while a world is open.
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<Plugin>,
+    /// The time since the state of the player was last sent, in seconds.
+    state_timer: f32,
+}
+
+impl PluginHost {
+    /// Starts every plugin in the `plugins` directory of the game. Plugins that fail to start are
+    /// logged and left out.
+    pub fn load() -> Self {
+        let dir = crate::get_game_dir().join("plugins");
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Self::default();
+        };
+        let mut dirs: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.join("plugin.json").is_file())
+            .collect();
+        dirs.sort();
+
+        let plugins = dirs
+            .iter()
+            .filter_map(|dir| match Plugin::start(dir) {
+                Ok(plugin) => {
+                    log::info!("Started plugin {}", plugin.name);
+                    Some(plugin)
+                }
+                Err(e) => {
+                    log::warn!("Failed to start the plugin in {}: {}", dir.display(), e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            plugins,
+            state_timer: 0.0,
+        }
+    }
+
+    /// Handles the requests of the plugins, tells them about the keys they bound that were
+    /// pressed and sends them the state from `state` every [`STATE_INTERVAL`] seconds. Key presses
+    /// are only passed on while `in_game`, not while typing into a menu.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        keyboard: &KeyboardState,
+        in_game: bool,
+        state: impl FnOnce() -> PluginEvent,
+    ) {
+        self.plugins.retain_mut(|plugin| {
+            let running = plugin.poll();
+            if !running {
+                log::info!("Plugin {} stopped", plugin.name);
+            }
+            running
+        });
+        if self.plugins.is_empty() {
+            return;
+        }
+
+        if in_game {
+            for plugin in &mut self.plugins {
+                let actions: Vec<_> = plugin
+                    .key_bindings
+                    .iter()
+                    .filter(|(key, _)| keyboard.pressed.contains(key))
+                    .map(|(_, action)| action.clone())
+                    .collect();
+                for action in actions {
+                    plugin.send(&PluginEvent::Key { action });
+                }
+            }
+        }
+
+        self.state_timer += delta_time;
+        if self.state_timer >= STATE_INTERVAL {
+            self.state_timer = 0.0;
+            self.send(&state());
+        }
+    }
+
+    /// Sends an event to every plugin, stopping those that don't read anymore.
+    pub fn send(&mut self, event: &PluginEvent) {
+        self.plugins.retain_mut(|plugin| {
+            let running = plugin.send(event);
+            if !running {
+                log::info!("Plugin {} stopped reading events", plugin.name);
+            }
+            running
+        });
+    }
+
+    pub fn overlays(&self) -> impl Iterator<Item = &Overlay> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| plugin.overlays.values())
+    }
+
+    pub fn markers(&self) -> impl Iterator<Item = &Marker> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| plugin.markers.values())
+    }
+}
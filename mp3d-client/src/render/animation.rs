@@ -0,0 +1,129 @@
+//! Keyframed animations of entity model parts.
+//!
+//! An animation rotates the parts of a model around their pivots. The rotations of each part are
+//! given as keyframes in an animation file, which are linearly interpolated. The [`Animator`]
+//! blends the idle, walk and attack animations of an entity depending on what it is doing.
+
+use std::collections::HashMap;
+
+use glam::Vec3;
+use serde::Deserialize;
+
+/// How quickly the walk animation fades in and out, in weight per second.
+const WALK_BLEND_SPEED: f32 = 6.0;
+
+/// The rotation of a part at a point in time.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    /// The rotation around the X, Y and Z axes in degrees.
+    pub rotation: [f32; 3],
+}
+
+/// A single animation, mapping part names to their keyframes sorted by time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Animation {
+    /// The length of the animation in seconds.
+    pub length: f32,
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+    pub parts: HashMap<String, Vec<Keyframe>>,
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+impl Animation {
+    /// Returns the rotation of the part at the given time in degrees, or zero if the animation
+    /// does not move the part.
+    pub fn sample(&self, part: &str, time: f32) -> Vec3 {
+        let Some(keyframes) = self.parts.get(part) else {
+            return Vec3::ZERO;
+        };
+        let time = if self.looping {
+            time.rem_euclid(self.length)
+        } else {
+            time.min(self.length)
+        };
+
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        match (keyframes.get(next.wrapping_sub(1)), keyframes.get(next)) {
+            (Some(a), Some(b)) => {
+                let t = (time - a.time) / (b.time - a.time).max(f32::EPSILON);
+                Vec3::from(a.rotation).lerp(Vec3::from(b.rotation), t)
+            }
+            (Some(keyframe), None) | (None, Some(keyframe)) => Vec3::from(keyframe.rotation),
+            (None, None) => Vec3::ZERO,
+        }
+    }
+}
+
+/// All animations of a model, by name.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct AnimationSet {
+    pub animations: HashMap<String, Animation>,
+}
+
+impl crate::resource::Asset for AnimationSet {
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// The animation state of a single entity.
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    time: f32,
+    /// How much of the walk animation is mixed in, from 0 (standing) to 1 (walking).
+    walk_weight: f32,
+    /// The time since the current attack started, if one is playing.
+    attack_time: Option<f32>,
+    /// How far the head is tilted down in degrees, on top of the animations.
+    pub head_pitch: f32,
+}
+
+impl Animator {
+    /// Advances the animations, fading the walk animation in or out depending on `moving`.
+    pub fn update(&mut self, delta_time: f32, moving: bool) {
+        self.time += delta_time;
+        let target = if moving { 1.0 } else { 0.0 };
+        let step = WALK_BLEND_SPEED * delta_time;
+        self.walk_weight += (target - self.walk_weight).clamp(-step, step);
+        if let Some(attack_time) = &mut self.attack_time {
+            *attack_time += delta_time;
+        }
+    }
+
+    /// Starts the attack animation, restarting it if it is already playing.
+    pub fn attack(&mut self) {
+        self.attack_time = Some(0.0);
+    }
+
+    /// Returns the blended rotation of the part in degrees.
+    pub fn rotation(&self, animations: &AnimationSet, part: &str) -> Vec3 {
+        let sample = |name: &str, time: f32| {
+            animations
+                .animations
+                .get(name)
+                .map(|animation| animation.sample(part, time))
+                .unwrap_or(Vec3::ZERO)
+        };
+
+        let mut rotation =
+            sample("idle", self.time).lerp(sample("walk", self.time), self.walk_weight);
+        if let Some(attack_time) = self.attack_time
+            && animations
+                .animations
+                .get("attack")
+                .is_some_and(|attack| attack_time < attack.length)
+        {
+            rotation += sample("attack", attack_time);
+        }
+        if part == "head" {
+            rotation.x += self.head_pitch;
+        }
+        rotation
+    }
+}
@@ -1,9 +1,17 @@
 //! All utilities related to rendering clouds.
+//!
+//! Clouds are a few flat layers at different altitudes, drifting at different speeds so that they
+//! move with some parallax. Every layer samples the same noise texture at its own scale, and the
+//! shader turns the noise into soft edged clouds, thicker and darker at their core, that fade out
+//! in the distance and when the camera gets close to the layer.
 
 use glam::Vec2;
 use glow::HasContext;
 
-use crate::abs::{Mesh, ShaderProgram, Texture, Vertex};
+use crate::{
+    abs::{Mesh, ShaderProgram, Texture, Vertex},
+    resource::lazy::LazyTexture,
+};
 
 #[repr(C)]
 pub struct CloudVertex(pub Vec2);
@@ -24,37 +32,68 @@ impl Vertex for CloudVertex {
     }
 }
 
+/// One layer of clouds.
+struct CloudLayer {
+    /// The height of the layer in blocks.
+    altitude: f32,
+    /// How fast the layer drifts, in texture repeats per second.
+    speed: f32,
+    /// Moves the layer's pattern along, so that layers of the same scale don't line up.
+    offset: f32,
+    /// The blocks covered by one repeat of the noise texture.
+    scale: f32,
+    /// The opacity of the thickest parts of the layer.
+    opacity: f32,
+}
+
+/// The cloud layers, lowest first. Higher layers are bigger, thinner and drift slower.
+const CLOUD_LAYERS: [CloudLayer; 3] = [
+    CloudLayer {
+        altitude: 100.0,
+        speed: 0.004,
+        offset: 0.0,
+        scale: 200.0,
+        opacity: 0.8,
+    },
+    CloudLayer {
+        altitude: 140.0,
+        speed: 0.0025,
+        offset: 0.37,
+        scale: 320.0,
+        opacity: 0.6,
+    },
+    CloudLayer {
+        altitude: 190.0,
+        speed: 0.0012,
+        offset: 0.71,
+        scale: 520.0,
+        opacity: 0.4,
+    },
+];
+
+/// The share of the sky covered by clouds in clear weather.
+pub const DEFAULT_COVERAGE: f32 = 0.45;
+
+/// The distance in blocks from the camera at which clouds are completely faded out.
+const CLOUD_DISTANCE: f32 = 240.0;
+
 pub struct CloudRenderer {
-    pub texture: Texture,
+    pub texture: LazyTexture,
     pub mesh: Mesh,
     pub shader: ShaderProgram,
+    /// The share of the sky covered by clouds, from 0 for a clear sky to 1 for an overcast one.
+    /// Weather doesn't exist yet, so this stays at [`DEFAULT_COVERAGE`].
+    pub coverage: f32,
 }
 
 impl CloudRenderer {
     pub fn new(gl: &std::sync::Arc<glow::Context>) -> Self {
-        let seed = rand::random::<i32>();
-        log::info!("Generating cloud texture with seed: {}", seed);
-        let mut noise = fastnoise_lite::FastNoiseLite::new();
-        noise.set_noise_type(Some(fastnoise_lite::NoiseType::Perlin));
-        noise.set_fractal_type(Some(fastnoise_lite::FractalType::FBm));
-        noise.set_fractal_octaves(Some(4));
-        noise.set_fractal_gain(Some(0.5));
-        noise.set_fractal_lacunarity(Some(2.0));
-        noise.set_seed(Some(seed));
-        let mut data = Vec::new();
-        let width = 256;
-        let height = width;
-        for z in 0..width {
-            for x in 0..height {
-                let value = noise.get_noise_2d(x as f32 * 7.0, z as f32 * 5.0);
-                let alpha = ((value + 1.0) / 2.0 * 255.0) as u8;
-                data.push(255);
-                data.push(255);
-                data.push(255);
-                data.push(if alpha > 128 { 255 } else { 0 });
-            }
-        }
-        let texture = Texture::new_bytes(gl, width, height, data);
+        // Generating the noise takes a while, so there are no clouds until it is done
+        let texture = LazyTexture::load(
+            "cloud texture",
+            Texture::new_bytes(gl, 1, 1, vec![0; 4]),
+            || Ok(cloud_image()),
+        );
 
         let vertices = [
             CloudVertex(Vec2::new(-1.0, -1.0)),
@@ -73,17 +112,26 @@ impl CloudRenderer {
             texture,
             mesh,
             shader,
+            coverage: DEFAULT_COVERAGE,
         }
     }
 
     pub fn draw(
-        &self,
+        &mut self,
         gl: &std::sync::Arc<glow::Context>,
         projection: glam::Mat4,
         view: glam::Mat4,
         position: glam::Vec3,
         timer: f32,
+        daylight: f32,
     ) {
+        // The layers are blended over each other, so the one furthest from the camera goes first
+        let mut layers: Vec<_> = CLOUD_LAYERS.iter().collect();
+        layers.sort_by(|a, b| {
+            let distance = |layer: &CloudLayer| (layer.altitude - position.y).abs();
+            distance(b).total_cmp(&distance(a))
+        });
+
         unsafe {
             gl.disable(glow::CULL_FACE);
             gl.depth_mask(false);
@@ -92,18 +140,48 @@ impl CloudRenderer {
             self.shader.set_uniform("u_projection", projection);
             self.shader.set_uniform("u_camera_pos", position);
             self.shader.set_uniform("u_time", timer);
-            self.shader.set_uniform("u_offset", 0.0);
-            self.shader.set_uniform("u_speed", 0.004);
-            self.shader.set_uniform("u_altitude", 100_u32);
+            self.shader.set_uniform("u_daylight", daylight);
+            self.shader
+                .set_uniform("u_coverage", self.coverage.clamp(0.0, 1.0));
+            self.shader.set_uniform("u_distance", CLOUD_DISTANCE);
             self.shader.set_uniform("u_texture", 0);
-            self.texture.bind(0);
-            self.mesh.draw();
-
-            self.shader.use_program();
-            self.shader.set_uniform("u_offset", 30.0);
-            self.shader.set_uniform("u_speed", 0.01);
-            self.shader.set_uniform("u_altitude", 140_u32);
-            self.mesh.draw();
+            self.texture.get(gl).bind(0);
+            for layer in layers {
+                self.shader.set_uniform("u_altitude", layer.altitude);
+                self.shader.set_uniform("u_speed", layer.speed);
+                self.shader.set_uniform("u_offset", layer.offset);
+                self.shader.set_uniform("u_scale", layer.scale);
+                self.shader.set_uniform("u_opacity", layer.opacity);
+                self.mesh.draw();
+            }
         }
     }
 }
+
+/// Generates the cloud pattern. The red channel holds the broad shape of the clouds, which the
+/// shader compares against the coverage, and the green channel finer detail that breaks up their
+/// edges.
+fn cloud_image() -> image::RgbaImage {
+    let seed = rand::random::<i32>();
+    log::info!("Generating cloud texture with seed: {}", seed);
+    let mut noise = fastnoise_lite::FastNoiseLite::new();
+    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Perlin));
+    noise.set_fractal_type(Some(fastnoise_lite::FractalType::FBm));
+    noise.set_fractal_octaves(Some(4));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_seed(Some(seed));
+    let mut detail = fastnoise_lite::FastNoiseLite::new();
+    detail.set_noise_type(Some(fastnoise_lite::NoiseType::OpenSimplex2));
+    detail.set_fractal_type(Some(fastnoise_lite::FractalType::FBm));
+    detail.set_fractal_octaves(Some(2));
+    detail.set_seed(Some(seed.wrapping_add(1)));
+    let width = 256;
+    let height = width;
+    let to_byte = |value: f32| ((value + 1.0) / 2.0 * 255.0).clamp(0.0, 255.0) as u8;
+    image::RgbaImage::from_fn(width, height, |x, z| {
+        let shape = noise.get_noise_2d(x as f32 * 7.0, z as f32 * 5.0);
+        let detail = detail.get_noise_2d(x as f32 * 31.0, z as f32 * 29.0);
+        image::Rgba([to_byte(shape), to_byte(detail), 255, 255])
+    })
+}
@@ -1,12 +1,15 @@
 // TODO: move more entity rendering code here.
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use glam::{Vec2, Vec3, vec2, vec3};
+use glam::{EulerRot, Mat4, Quat, Vec2, Vec3, Vec4, vec2, vec3};
 use glow::HasContext;
-use mp3d_core::entity::Entity;
 
-use crate::abs::{Mesh, Vertex};
+use crate::{
+    abs::{InstanceData, Mesh, Vertex},
+    render::animation::{AnimationSet, Animator},
+    scenes::Assets,
+};
 
 #[repr(C)]
 pub struct EntityVertex {
@@ -49,12 +52,270 @@ impl Vertex for EntityVertex {
     }
 }
 
-pub fn player_model(gl: &Arc<glow::Context>) -> Mesh {
-    let width = mp3d_core::entity::PlayerEntity::width();
-    let height = mp3d_core::entity::PlayerEntity::height();
-    let hw = width / 2.0;
+/// Per-instance data of an entity model, so every entity sharing a model is drawn in a single call.
+#[repr(C)]
+pub struct EntityInstance {
+    pub model: Mat4,
+}
+
+impl InstanceData for EntityInstance {
+    fn instance_attribs(gl: &glow::Context) {
+        unsafe {
+            // A mat4 attribute takes up four consecutive locations, one per column
+            for column in 0..4 {
+                let location = 3 + column;
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_f32(
+                    location,
+                    4,
+                    glow::FLOAT,
+                    false,
+                    std::mem::size_of::<EntityInstance>() as i32,
+                    (column as usize * std::mem::size_of::<Vec4>()) as i32,
+                );
+                gl.vertex_attrib_divisor(location, 1);
+            }
+        }
+    }
+}
+
+/// A box shaped part of an entity model, which animations rotate around its pivot.
+pub struct ModelPart {
+    pub name: &'static str,
+    /// The center of the bottom face of the part, relative to the entity position.
+    pub offset: Vec3,
+    pub size: Vec3,
+    /// The point the part rotates around, relative to the entity position.
+    pub pivot: Vec3,
+}
+
+/// The parts of the player model, with the proportions of a 16 by 32 pixel character stretched
+/// to the size of the player hitbox.
+pub const PLAYER_PARTS: [ModelPart; 6] = {
+    const X: f32 = 0.6 / 16.0;
+    const Y: f32 = 1.8 / 32.0;
+    [
+        ModelPart {
+            name: "right_leg",
+            offset: vec3(2.0 * X, 0.0, 0.0),
+            size: vec3(4.0 * X, 12.0 * Y, 4.0 * X),
+            pivot: vec3(2.0 * X, 12.0 * Y, 0.0),
+        },
+        ModelPart {
+            name: "left_leg",
+            offset: vec3(-2.0 * X, 0.0, 0.0),
+            size: vec3(4.0 * X, 12.0 * Y, 4.0 * X),
+            pivot: vec3(-2.0 * X, 12.0 * Y, 0.0),
+        },
+        ModelPart {
+            name: "body",
+            offset: vec3(0.0, 12.0 * Y, 0.0),
+            size: vec3(8.0 * X, 12.0 * Y, 4.0 * X),
+            pivot: vec3(0.0, 12.0 * Y, 0.0),
+        },
+        ModelPart {
+            name: "right_arm",
+            offset: vec3(6.0 * X, 12.0 * Y, 0.0),
+            size: vec3(4.0 * X, 12.0 * Y, 4.0 * X),
+            pivot: vec3(6.0 * X, 22.0 * Y, 0.0),
+        },
+        ModelPart {
+            name: "left_arm",
+            offset: vec3(-6.0 * X, 12.0 * Y, 0.0),
+            size: vec3(4.0 * X, 12.0 * Y, 4.0 * X),
+            pivot: vec3(-6.0 * X, 22.0 * Y, 0.0),
+        },
+        ModelPart {
+            name: "head",
+            offset: vec3(0.0, 24.0 * Y, 0.0),
+            size: vec3(8.0 * X, 8.0 * Y, 8.0 * X),
+            pivot: vec3(0.0, 24.0 * Y, 0.0),
+        },
+    ]
+};
+
+/// The parts of the critter model, a small animal on four legs facing along the positive Z axis.
+pub const CRITTER_PARTS: [ModelPart; 6] = [
+    ModelPart {
+        name: "front_right_leg",
+        offset: vec3(0.15, 0.0, 0.2),
+        size: vec3(0.15, 0.3, 0.15),
+        pivot: vec3(0.15, 0.3, 0.2),
+    },
+    ModelPart {
+        name: "front_left_leg",
+        offset: vec3(-0.15, 0.0, 0.2),
+        size: vec3(0.15, 0.3, 0.15),
+        pivot: vec3(-0.15, 0.3, 0.2),
+    },
+    ModelPart {
+        name: "back_right_leg",
+        offset: vec3(0.15, 0.0, -0.2),
+        size: vec3(0.15, 0.3, 0.15),
+        pivot: vec3(0.15, 0.3, -0.2),
+    },
+    ModelPart {
+        name: "back_left_leg",
+        offset: vec3(-0.15, 0.0, -0.2),
+        size: vec3(0.15, 0.3, 0.15),
+        pivot: vec3(-0.15, 0.3, -0.2),
+    },
+    ModelPart {
+        name: "body",
+        offset: vec3(0.0, 0.3, 0.0),
+        size: vec3(0.5, 0.3, 0.7),
+        pivot: vec3(0.0, 0.3, 0.0),
+    },
+    ModelPart {
+        name: "head",
+        offset: vec3(0.0, 0.4, 0.4),
+        size: vec3(0.3, 0.3, 0.3),
+        pivot: vec3(0.0, 0.5, 0.3),
+    },
+];
+
+/// Returns an instance of [`part_cube`] for every part, posed by the animator.
+pub fn part_instances(
+    parts: &[ModelPart],
+    model: Mat4,
+    animator: &Animator,
+    animations: &AnimationSet,
+) -> Vec<EntityInstance> {
+    parts
+        .iter()
+        .map(|part| {
+            let rotation = animator.rotation(animations, part.name);
+            let rotation = Quat::from_euler(
+                EulerRot::YXZ,
+                rotation.y.to_radians(),
+                rotation.x.to_radians(),
+                rotation.z.to_radians(),
+            );
+            let pose = Mat4::from_translation(part.pivot)
+                * Mat4::from_quat(rotation)
+                * Mat4::from_translation(part.offset - part.pivot);
+            EntityInstance {
+                model: model * pose * Mat4::from_scale(part.size),
+            }
+        })
+        .collect()
+}
+
+/// Draws the entities of a type, as instances of [`part_cube`] which are drawn all at once.
+pub trait EntityRenderer {
+    /// Adds the instances drawing an entity placed and rotated by `model`.
+    fn instances(
+        &self,
+        model: Mat4,
+        animator: &Animator,
+        assets: &Assets,
+        instances: &mut Vec<EntityInstance>,
+    );
+}
+
+/// Draws a model made of parts, posed by animations from the assets.
+pub struct ModelRenderer {
+    pub parts: &'static [ModelPart],
+    pub animations: fn(&Assets) -> &AnimationSet,
+}
+
+impl EntityRenderer for ModelRenderer {
+    fn instances(
+        &self,
+        model: Mat4,
+        animator: &Animator,
+        assets: &Assets,
+        instances: &mut Vec<EntityInstance>,
+    ) {
+        instances.extend(part_instances(
+            self.parts,
+            model,
+            animator,
+            (self.animations)(assets),
+        ));
+    }
+}
+
+/// Draws a plain box standing on the entity position.
+pub struct BoxRenderer {
+    pub size: Vec3,
+}
+
+impl EntityRenderer for BoxRenderer {
+    fn instances(
+        &self,
+        model: Mat4,
+        _animator: &Animator,
+        _assets: &Assets,
+        instances: &mut Vec<EntityInstance>,
+    ) {
+        instances.push(EntityInstance {
+            model: model * Mat4::from_scale(self.size),
+        });
+    }
+}
+
+/// The renderers of entity types by their identifier, like `"player"`. Entity types without a
+/// renderer are drawn as a box, so they still show up before they get one.
+pub struct EntityRenderers {
+    renderers: HashMap<String, Box<dyn EntityRenderer>>,
+    fallback: BoxRenderer,
+}
+
+impl EntityRenderers {
+    /// Sets the renderer of an entity type, replacing the previous one.
+    pub fn register(&mut self, entity_type: &str, renderer: impl EntityRenderer + 'static) {
+        self.renderers
+            .insert(entity_type.to_string(), Box::new(renderer));
+    }
+
+    /// Returns the renderer of an entity type.
+    pub fn get(&self, entity_type: &str) -> &dyn EntityRenderer {
+        self.renderers
+            .get(entity_type)
+            .map_or(&self.fallback, |renderer| renderer.as_ref())
+    }
+}
+
+impl Default for EntityRenderers {
+    fn default() -> Self {
+        let mut renderers = Self {
+            renderers: HashMap::new(),
+            fallback: BoxRenderer {
+                size: Vec3::splat(0.5),
+            },
+        };
+        renderers.register(
+            "player",
+            ModelRenderer {
+                parts: &PLAYER_PARTS,
+                animations: |assets| &assets.player_animations,
+            },
+        );
+        // The target dummy is shaped like a player, but never walks or attacks
+        renderers.register(
+            "dummy",
+            ModelRenderer {
+                parts: &PLAYER_PARTS,
+                animations: |assets| &assets.player_animations,
+            },
+        );
+        renderers.register(
+            "critter",
+            ModelRenderer {
+                parts: &CRITTER_PARTS,
+                animations: |assets| &assets.critter_animations,
+            },
+        );
+        renderers
+    }
+}
 
-    let (y0, y1) = (0.0, height);
+/// Creates a unit cube with its bottom face centered on the origin, which is scaled and posed per
+/// instance to draw the parts of entity models.
+pub fn part_cube(gl: &Arc<glow::Context>) -> Mesh {
+    let hw = 0.5;
+    let (y0, y1) = (0.0, 1.0);
 
     #[rustfmt::skip]
     let vertices: Vec<EntityVertex> = vec![
@@ -97,5 +358,5 @@ pub fn player_model(gl: &Arc<glow::Context>) -> Mesh {
         indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
     }
 
-    Mesh::new(gl, &vertices, &indices, glow::TRIANGLES)
+    Mesh::new_instanced::<_, EntityInstance>(gl, &vertices, &indices, &[], glow::TRIANGLES)
 }
@@ -1,13 +1,16 @@
 //! All utilities related to meshing worlds and chunks.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 use glam::{IVec3, Vec2, Vec3};
 use glow::HasContext;
 use mp3d_core::{
     block::{BlockId, BlockState, block_registry},
     direction::Direction,
-    world::chunk::CHUNK_SIZE,
+    world::{
+        chunk::CHUNK_SIZE,
+        light::{self, LightChannel, MAX_LIGHT},
+    },
 };
 
 use crate::{
@@ -22,6 +25,8 @@ pub struct ChunkVertex {
     pub normal: Vec3,
     pub uv: Vec2,
     pub ao: u8,
+    /// The sky light and block light reaching the vertex, scaled from 0 to 255.
+    pub light: [u8; 2],
 }
 
 impl Vertex for ChunkVertex {
@@ -48,6 +53,11 @@ impl Vertex for ChunkVertex {
             // AO attribute
             gl.enable_vertex_attrib_array(3);
             gl.vertex_attrib_pointer_i32(3, 1, glow::UNSIGNED_BYTE, stride, offset);
+            offset += std::mem::size_of::<u8>() as i32;
+
+            // Light attribute
+            gl.enable_vertex_attrib_array(4);
+            gl.vertex_attrib_pointer_f32(4, 2, glow::UNSIGNED_BYTE, true, stride, offset);
         }
     }
 }
@@ -130,6 +140,51 @@ fn block_is_full_cube(
         .is_some_and(|model| model.is_full_cube())
 }
 
+/// Returns whether nothing behind a block can be seen through it, which is when it is a full cube
+/// whose faces all occlude.
+fn block_is_opaque_cube(
+    block: Option<(BlockId, &BlockState)>,
+    block_models: &HashMap<(BlockId, u16), crate::resource::block::BlockModel>,
+) -> bool {
+    block_is_full_cube(block, block_models)
+        && block.is_some_and(|(block, state)| {
+            block_models[&(block, state.data())]
+                .elements
+                .iter()
+                .all(|element| element.faces.iter().all(|face| face.occludes))
+        })
+}
+
+/// Returns the sides of a chunk made only of opaque full blocks as a bit for every direction, see
+/// [`ClientChunk::full_sides`].
+fn full_sides(
+    chunk: &ClientChunk,
+    block_models: &HashMap<(BlockId, u16), crate::resource::block::BlockModel>,
+) -> u8 {
+    let mut sides = 0;
+    for dir in Direction::ALL {
+        if light::layer(IVec3::ZERO, dir)
+            .all(|local| block_is_opaque_cube(chunk.get_block(local), block_models))
+        {
+            sides |= 1 << dir as u8;
+        }
+    }
+    sides
+}
+
+/// Scales a light level from 0 to [`MAX_LIGHT`] to a vertex attribute from 0 to 255.
+fn scale_light(level: u8) -> u8 {
+    (level as u32 * 255 / MAX_LIGHT as u32) as u8
+}
+
+/// Averages the sky light and block light of several blocks, scaled like [`scale_light`].
+fn average_light(samples: &[[u8; 2]]) -> [u8; 2] {
+    [0, 1].map(|channel| {
+        let sum: u32 = samples.iter().map(|light| light[channel] as u32).sum();
+        (sum * 255 / (samples.len() as u32 * MAX_LIGHT as u32)) as u8
+    })
+}
+
 fn ao_for_vertex(side1: bool, side2: bool, corner: bool) -> u8 {
     if side1 && side2 {
         0
@@ -281,46 +336,56 @@ const AO_NEIGHBORS: [[[IVec3; 3]; 4]; 6] = [
     ],
 ];
 
-/// Generates meshes for all chunks that require being meshed again.
+/// The maximum number of unused chunk meshes kept around to be reused, any more are deleted so the
+/// GPU memory of unloaded chunks is freed.
+const MAX_POOLED_CHUNK_MESHES: usize = 64;
+
+/// Re-meshes at most `budget` chunks from the remesh queue of the world, nearest to the chunk at
+/// `center` first. Remaining chunks are meshed in the following calls.
 pub fn mesh_world(
     gl: &Arc<glow::Context>,
     world: &mut ClientWorld,
     chunk_meshes: &mut HashMap<IVec3, Mesh>,
     chunk_mesh_pool: &mut Vec<Mesh>,
-    block_textures: &crate::resource::block::TextureAtlas,
-    block_models: &HashMap<(BlockId, u16), crate::resource::block::BlockModel>,
+    assets: &crate::scenes::Assets,
+    budget: usize,
+    center: IVec3,
 ) {
     use rayon::prelude::*;
 
-    const MAX_MESHES_PER_FRAME: usize = 12;
+    let block_textures = &assets.block_textures;
+    let block_models = &assets.block_models;
 
     if world.remesh_queue.is_empty() {
         return;
     }
 
-    let batch_size = world.remesh_queue.len().min(MAX_MESHES_PER_FRAME);
-
-    let batch: Vec<IVec3> = world.remesh_queue.drain(batch_size);
+    let batch: Vec<IVec3> = world.remesh_queue.drain_nearest(budget.max(1), center);
 
     let world_ref = &*world;
 
-    let new_meshes: Vec<(IVec3, Vec<ChunkVertex>, Vec<u32>)> = batch
+    let new_meshes: Vec<(IVec3, Vec<ChunkVertex>, Vec<u32>, u8)> = batch
         .par_iter()
         .filter_map(|chunk_pos| {
             if let Some(chunk) = world_ref.chunks.get(chunk_pos) {
                 let (chunk_vertices, chunk_indices) =
                     mesh_chunk(chunk, *chunk_pos, world_ref, block_textures, block_models);
-                Some((*chunk_pos, chunk_vertices, chunk_indices))
+                let full_sides = full_sides(chunk, block_models);
+                Some((*chunk_pos, chunk_vertices, chunk_indices, full_sides))
             } else {
                 None
             }
         })
         .collect();
 
-    for (chunk_pos, chunk_vertices, chunk_indices) in new_meshes {
-        world.chunks.get_mut(&chunk_pos).unwrap().dirty = false;
+    for (chunk_pos, chunk_vertices, chunk_indices, full_sides) in new_meshes {
+        let chunk = world.chunks.get_mut(&chunk_pos).unwrap();
+        chunk.dirty = false;
+        chunk.full_sides = full_sides;
 
-        if let Some(mut mesh) = chunk_mesh_pool.pop() {
+        if let Some(mesh) = chunk_meshes.get_mut(&chunk_pos) {
+            mesh.update(&chunk_vertices, &chunk_indices);
+        } else if let Some(mut mesh) = chunk_mesh_pool.pop() {
             mesh.update(&chunk_vertices, &chunk_indices);
             chunk_meshes.insert(chunk_pos, mesh);
         } else {
@@ -330,6 +395,24 @@ pub fn mesh_world(
     }
 }
 
+/// Returns the mesh of a chunk that is no longer drawn to the pool, or deletes it if the pool is
+/// already full.
+pub fn recycle_chunk_mesh(chunk_mesh_pool: &mut Vec<Mesh>, mesh: Mesh) {
+    if chunk_mesh_pool.len() < MAX_POOLED_CHUNK_MESHES {
+        chunk_mesh_pool.push(mesh);
+    }
+}
+
+thread_local! {
+    /// Buffers reused by every chunk meshed on the same thread. Reserving space for the worst case
+    /// of a chunk full of faces is several megabytes, which would otherwise be allocated for every
+    /// meshed chunk.
+    static MESH_SCRATCH: RefCell<(Vec<ChunkVertex>, Vec<u32>)> = RefCell::new((
+        Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 24),
+        Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 36),
+    ));
+}
+
 /// Generates the mesh for a single chunk at the given position in the world.
 /// Returns a tuple containing the list of vertices and the list of indices.
 fn mesh_chunk(
@@ -339,10 +422,33 @@ fn mesh_chunk(
     block_textures: &crate::resource::block::TextureAtlas,
     block_models: &HashMap<(BlockId, u16), crate::resource::block::BlockModel>,
 ) -> (Vec<ChunkVertex>, Vec<u32>) {
-    let chunk_origin = chunk_pos * (CHUNK_SIZE as i32);
+    MESH_SCRATCH.with_borrow_mut(|(vertices, indices)| {
+        vertices.clear();
+        indices.clear();
+        mesh_chunk_into(
+            chunk,
+            chunk_pos,
+            world,
+            block_textures,
+            block_models,
+            vertices,
+            indices,
+        );
+        // Copying out only allocates what the mesh actually uses
+        (vertices.to_vec(), indices.to_vec())
+    })
+}
 
-    let mut vertices = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 24);
-    let mut indices = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 36);
+fn mesh_chunk_into(
+    chunk: &ClientChunk,
+    chunk_pos: glam::IVec3,
+    world: &ClientWorld,
+    block_textures: &crate::resource::block::TextureAtlas,
+    block_models: &HashMap<(BlockId, u16), crate::resource::block::BlockModel>,
+    vertices: &mut Vec<ChunkVertex>,
+    indices: &mut Vec<u32>,
+) {
+    let chunk_origin = chunk_pos * (CHUNK_SIZE as i32);
 
     let mut neighbors = [[[None; 3]; 3]; 3];
 
@@ -387,6 +493,20 @@ fn mesh_chunk(
         chunk_ref.get_block(IVec3::new(lx, ly, lz))
     }
 
+    #[inline(always)]
+    fn get_light(
+        chunk_origin: IVec3,
+        world_pos: IVec3,
+        neighbors: [[[Option<&ClientChunk>; 3]; 3]; 3],
+    ) -> Option<[u8; 2]> {
+        let local = world_pos - chunk_origin;
+        let chunk_size = CHUNK_SIZE as i32;
+        let c = local.div_euclid(IVec3::splat(chunk_size)) + IVec3::ONE;
+        let chunk_ref = neighbors[c.x as usize][c.y as usize][c.z as usize]?;
+        let local = local.rem_euclid(IVec3::splat(chunk_size));
+        Some(LightChannel::ALL.map(|channel| chunk_ref.chunk.get_light(local, channel)))
+    }
+
     #[inline(always)]
     fn ident(block: BlockId, state: &BlockState) -> (BlockId, u16) {
         (block, state.data())
@@ -447,8 +567,16 @@ fn mesh_chunk(
 
                             // AO for the 4 vertices of this face
                             let mut aos = [3u8; 4];
+                            // Light for the 4 vertices, which is that of the block itself for
+                            // blocks light goes through and otherwise smoothed over the blocks in
+                            // front of the face
+                            let own_light =
+                                get_light(chunk_origin, block_world_pos, neighbors).unwrap();
+                            let mut lights = [own_light.map(scale_light); 4];
 
                             if model.is_full_cube() {
+                                let front_light = get_light(chunk_origin, neighbor_pos, neighbors)
+                                    .unwrap_or(own_light);
                                 for vert_idx in 0..4 {
                                     let [side1_off, side2_off, corner_off] =
                                         AO_NEIGHBORS[dir as usize][vert_idx];
@@ -475,6 +603,26 @@ fn mesh_chunk(
 
                                     aos[vert_idx] =
                                         ao_for_vertex(side1_full, side2_full, corner_full);
+
+                                    let mut samples = [front_light; 4];
+                                    let mut count = 1;
+                                    for (offset, full) in [
+                                        (side1_off, side1_full),
+                                        (side2_off, side2_full),
+                                        (corner_off, corner_full || (side1_full && side2_full)),
+                                    ] {
+                                        if !full
+                                            && let Some(light) = get_light(
+                                                chunk_origin,
+                                                block_world_pos + offset,
+                                                neighbors,
+                                            )
+                                        {
+                                            samples[count] = light;
+                                            count += 1;
+                                        }
+                                    }
+                                    lights[vert_idx] = average_light(&samples[..count]);
                                 }
                             }
 
@@ -496,6 +644,7 @@ fn mesh_chunk(
                                     normal,
                                     uv: uvs[i],
                                     ao: aos[i],
+                                    light: lights[i],
                                 });
                             }
 
@@ -524,6 +673,4 @@ fn mesh_chunk(
             }
         }
     }
-
-    (vertices, indices)
 }
@@ -3,10 +3,17 @@
 //! This module contains submodules and functions for meshing worlds and chunks and all used
 //! shaders.
 
+pub mod animation;
 pub mod clouds;
 pub mod dialog;
 pub mod entities;
 pub mod meshing;
+pub mod nametags;
+pub mod panorama;
 pub mod particles;
+pub mod postprocess;
 pub mod profiler;
+pub mod shadows;
+pub mod sky;
+pub mod splash;
 pub mod ui;
@@ -0,0 +1,132 @@
+//! Name tags floating above entities.
+//!
+//! Name tags are projected to the screen and drawn with the UI renderer after the world, scaled
+//! by their distance so they appear to be part of the world. Since they are drawn on top of the
+//! world, tags behind terrain are hidden by marching a ray through the blocks between the camera
+//! and the tag.
+
+use glam::{Mat4, Vec2, Vec3};
+use mp3d_core::{block::block_registry, textcomponent::TextComponent};
+
+use crate::{
+    client::world::ClientWorld,
+    render::ui::{
+        font::ColorlessTextParams,
+        uirenderer::{DrawCommand, UIRenderMode, UIRenderer},
+    },
+    scenes::Assets,
+};
+
+/// The height of the name tag text in blocks.
+const NAME_TAG_TEXT_HEIGHT: f32 = 0.25;
+/// The gap between the top of the entity and the name tag in blocks.
+const NAME_TAG_OFFSET: f32 = 0.3;
+/// Tags smaller than this on screen, in pixels, are not drawn.
+const MIN_NAME_TAG_SIZE: f32 = 4.0;
+/// The padding around the text of a name tag, relative to the text height.
+const NAME_TAG_PADDING: f32 = 0.15;
+/// The distance between samples when checking whether a name tag is behind terrain.
+const OCCLUSION_STEP: f32 = 0.05;
+
+/// A name tag to draw above an entity.
+pub struct NameTag {
+    pub text: TextComponent,
+    /// The position of the top center of the entity.
+    pub position: Vec3,
+}
+
+/// Where and what to draw for the name tags of a frame.
+pub struct NameTagView<'a> {
+    pub world: &'a ClientWorld,
+    pub eye: Vec3,
+    pub view_projection: Mat4,
+    pub screen_size: Vec2,
+    pub max_distance: f32,
+}
+
+/// Draws the name tags that are within the view distance and not hidden behind terrain.
+pub fn draw_name_tags(ui: &mut UIRenderer, assets: &Assets, view: &NameTagView, tags: &[NameTag]) {
+    for tag in tags {
+        let anchor = tag.position + Vec3::Y * NAME_TAG_OFFSET;
+        if anchor.distance(view.eye) > view.max_distance
+            || is_occluded(view.world, view.eye, anchor)
+        {
+            continue;
+        }
+
+        let (Some(bottom), Some(top)) = (
+            project(view, anchor),
+            project(view, anchor + Vec3::Y * NAME_TAG_TEXT_HEIGHT),
+        ) else {
+            continue;
+        };
+        let font_size = bottom.y - top.y;
+        if font_size < MIN_NAME_TAG_SIZE {
+            continue;
+        }
+
+        let params = ColorlessTextParams {
+            font_size,
+            word_wrap_width: None,
+        };
+        let size = assets.font.measure_component(&tag.text, params);
+        let padding = font_size * NAME_TAG_PADDING;
+        let offset = Vec2::new(bottom.x - size.x / 2.0, bottom.y - size.y);
+
+        ui.add_command(DrawCommand::Quad {
+            rect: [offset - padding, offset + size + padding],
+            uv_rect: [Vec2::ZERO, Vec2::ONE],
+            mode: UIRenderMode::Color(assets.theme.backdrop()),
+            layer: 0,
+        });
+        for mut cmd in assets.font.text_component(&tag.text, params) {
+            if let DrawCommand::Quad { rect, .. } = &mut cmd {
+                rect[0] += offset;
+                rect[1] += offset;
+            } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
+                for vertex in vertices {
+                    vertex.position += offset.extend(0.0);
+                }
+            }
+            ui.add_command(cmd);
+        }
+    }
+}
+
+/// Projects a point in the world to screen coordinates, or `None` if it is behind the camera.
+fn project(view: &NameTagView, point: Vec3) -> Option<Vec2> {
+    let clip = view.view_projection * point.extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    Some(Vec2::new(
+        (ndc.x + 1.0) / 2.0 * view.screen_size.x,
+        (1.0 - ndc.y) / 2.0 * view.screen_size.y,
+    ))
+}
+
+/// Returns whether a visible block lies between `from` and `to`.
+fn is_occluded(world: &ClientWorld, from: Vec3, to: Vec3) -> bool {
+    let distance = from.distance(to);
+    let direction = (to - from) / distance.max(f32::EPSILON);
+
+    let mut traveled = 0.0;
+    while traveled < distance {
+        let pos = from + direction * traveled;
+        let block_pos = pos.floor().as_ivec3();
+        if let Some((block, state)) = world.get_block_at(block_pos) {
+            let block_def = block_registry().get(block).unwrap();
+            if block_def.visible
+                && block_def
+                    .ray_intersect(pos - block_pos.as_vec3(), direction, *state)
+                    .is_some()
+            {
+                return true;
+            }
+        }
+        traveled += OCCLUSION_STEP;
+    }
+
+    false
+}
@@ -0,0 +1,238 @@
+//! Capturing panoramas of the world and drawing them behind the title screen.
+//!
+//! A panorama is six square pictures of the world taken from one spot, one along every axis, that
+//! together cover every direction. They are saved in the `panorama` directory of the game as
+//! `panorama_0.png` to `panorama_5.png` in the order of the faces of an OpenGL cube map: +X, -X,
+//! +Y, -Y, +Z, -Z. Each is stored as it is uploaded into the cube map, so the pictures of the
+//! sides look mirrored in an image viewer.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use glam::{Mat4, Vec3};
+use glow::HasContext;
+
+use crate::{
+    abs::{Mesh, ShaderProgram},
+    client::camera::Camera,
+    render::postprocess::fullscreen_quad_ndc,
+    resource::lazy::Lazy,
+};
+
+/// The direction and the up vector of the camera for each face of a cube map, in order.
+const FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Y),
+    (Vec3::NEG_X, Vec3::NEG_Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+    (Vec3::Z, Vec3::NEG_Y),
+    (Vec3::NEG_Z, Vec3::NEG_Y),
+];
+
+/// The field of view panoramas are captured with, so that the faces meet at the edges.
+pub const CAPTURE_FOV: f32 = 90.0;
+/// The field of view panoramas are drawn with.
+const VIEW_FOV: f32 = 70.0;
+
+/// Counts the panoramas saved since the game started, so the title screen can load a new one.
+static SAVED_PANORAMAS: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the directory panoramas are saved in and loaded from.
+pub fn panorama_dir() -> PathBuf {
+    crate::get_game_dir().join("panorama")
+}
+
+fn face_path(face: usize) -> PathBuf {
+    panorama_dir().join(format!("panorama_{}.png", face))
+}
+
+/// A panorama being captured, one face every frame.
+#[derive(Default)]
+pub struct PanoramaCapture {
+    faces: Vec<image::RgbaImage>,
+}
+
+impl PanoramaCapture {
+    /// Turns the camera towards the face captured next, widening it to [`CAPTURE_FOV`].
+    pub fn aim(&self, camera: &mut Camera) {
+        (camera.direction, camera.up) = FACES[self.faces.len()];
+        camera.fov = CAPTURE_FOV;
+    }
+
+    /// Reads the face rendered after [`Self::aim`] from the middle of the screen, which must have
+    /// been drawn with a vertical field of view of [`CAPTURE_FOV`]. Returns whether that was the
+    /// last face.
+    pub fn capture_face(&mut self, gl: &glow::Context, screen_size: (u32, u32)) -> bool {
+        let size = screen_size.0.min(screen_size.1);
+        let mut pixels = vec![0u8; size as usize * size as usize * 4];
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+            gl.read_pixels(
+                ((screen_size.0 - size) / 2) as i32,
+                ((screen_size.1 - size) / 2) as i32,
+                size as i32,
+                size as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(Some(&mut pixels)),
+            );
+        }
+        let image = image::RgbaImage::from_raw(size, size, pixels)
+            .expect("Pixel buffer has the size of the face");
+        self.faces.push(image);
+        self.faces.len() == FACES.len()
+    }
+
+    /// Saves the captured faces on a background thread, replacing the panorama shown on the
+    /// title screen.
+    pub fn save(self) {
+        std::thread::spawn(move || {
+            if let Err(e) = std::fs::create_dir_all(panorama_dir()) {
+                log::error!("Failed to create the panorama directory: {}", e);
+                return;
+            }
+            for (face, image) in self.faces.iter().enumerate() {
+                if let Err(e) = image.save(face_path(face)) {
+                    log::error!("Failed to save panorama face {}: {}", face, e);
+                    return;
+                }
+            }
+            SAVED_PANORAMAS.fetch_add(1, Ordering::Relaxed);
+            log::info!("Saved panorama to {}", panorama_dir().display());
+        });
+    }
+}
+
+/// Draws a saved panorama around the camera, loading it in the background. Nothing is drawn if
+/// no panorama was captured yet.
+pub struct PanoramaRenderer {
+    faces: Lazy<Option<Vec<image::RgbaImage>>>,
+    /// The value of [`SAVED_PANORAMAS`] when the faces started loading.
+    loaded_version: u32,
+    cube_map: Option<glow::Texture>,
+    shader: ShaderProgram,
+    quad: Mesh,
+    gl: Arc<glow::Context>,
+}
+
+impl PanoramaRenderer {
+    pub fn new(gl: &Arc<glow::Context>) -> Self {
+        Self {
+            faces: Self::load(),
+            loaded_version: SAVED_PANORAMAS.load(Ordering::Relaxed),
+            cube_map: None,
+            shader: crate::shader_program!(panorama, gl, ".."),
+            quad: fullscreen_quad_ndc(gl),
+            gl: Arc::clone(gl),
+        }
+    }
+
+    fn load() -> Lazy<Option<Vec<image::RgbaImage>>> {
+        Lazy::load("panorama", || {
+            if !face_path(0).exists() {
+                return Ok(None);
+            }
+            (0..FACES.len())
+                .map(|face| {
+                    let path = face_path(face);
+                    image::open(&path)
+                        .map(|image| image.to_rgba8())
+                        .map_err(|e| format!("{}: {}", path.display(), e))
+                })
+                .collect::<Result<_, _>>()
+                .map(Some)
+        })
+    }
+
+    /// Uploads the faces into a cube map once they are loaded, starting over if a new panorama
+    /// was saved. Returns whether the panorama can be drawn.
+    fn upload(&mut self) -> bool {
+        let version = SAVED_PANORAMAS.load(Ordering::Relaxed);
+        if version != self.loaded_version {
+            self.loaded_version = version;
+            self.faces = Self::load();
+            if let Some(texture) = self.cube_map.take() {
+                unsafe {
+                    self.gl.delete_texture(texture);
+                }
+            }
+        }
+
+        if self.cube_map.is_none()
+            && let Some(Some(faces)) = self.faces.get()
+        {
+            let gl = &self.gl;
+            let texture = unsafe { gl.create_texture().unwrap() };
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(texture));
+                for (i, face) in faces.iter().enumerate() {
+                    gl.tex_image_2d(
+                        glow::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                        0,
+                        glow::RGBA as i32,
+                        face.width() as i32,
+                        face.height() as i32,
+                        0,
+                        glow::RGBA,
+                        glow::UNSIGNED_BYTE,
+                        glow::PixelUnpackData::Slice(Some(face.as_raw())),
+                    );
+                }
+                for (param, value) in [
+                    (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                    (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+                    (glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE),
+                    (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                    (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                ] {
+                    gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, param, value as i32);
+                }
+                gl.bind_texture(glow::TEXTURE_CUBE_MAP, None);
+                crate::check_gl_error!(gl, "creating the panorama cube map");
+            }
+            self.cube_map = Some(texture);
+        }
+        self.cube_map.is_some()
+    }
+
+    /// Draws the panorama over the whole screen, looking in the direction given by `yaw` and
+    /// `pitch` in degrees, if there is one.
+    pub fn draw(&mut self, yaw: f32, pitch: f32, aspect_ratio: f32) {
+        if !self.upload() {
+            return;
+        }
+        let direction = Vec3::new(
+            yaw.to_radians().cos() * pitch.to_radians().cos(),
+            pitch.to_radians().sin(),
+            yaw.to_radians().sin() * pitch.to_radians().cos(),
+        );
+        let view = Mat4::look_to_rh(Vec3::ZERO, direction, Vec3::Y);
+        let projection = Mat4::perspective_rh_gl(VIEW_FOV.to_radians(), aspect_ratio, 0.1, 10.0);
+        unsafe {
+            self.gl.disable(glow::DEPTH_TEST);
+            self.shader.use_program();
+            self.shader.set_uniform("u_panorama", 0);
+            self.shader
+                .set_uniform("u_inverse_view_projection", (projection * view).inverse());
+            self.gl.active_texture(glow::TEXTURE0);
+            self.gl.bind_texture(glow::TEXTURE_CUBE_MAP, self.cube_map);
+            self.quad.draw();
+            self.gl.bind_texture(glow::TEXTURE_CUBE_MAP, None);
+        }
+    }
+}
+
+impl Drop for PanoramaRenderer {
+    fn drop(&mut self) {
+        if let Some(texture) = self.cube_map {
+            unsafe {
+                self.gl.delete_texture(texture);
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! The post-processing chain applied to the rendered world before the UI is drawn.
+//!
+//! The world is rendered into a scene framebuffer, which the [`PostProcessor`] then runs through a
+//! series of fullscreen passes, the last of which writes to the currently bound framebuffer.
+
+use std::sync::Arc;
+
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abs::{
+        Mesh, ShaderProgram, Texture,
+        framebuffer::{ColorUsage, Framebuffer},
+    },
+    scenes::options::ClientConfig,
+    shader_program,
+};
+
+/// The quality of the FXAA pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FxaaQuality {
+    /// FXAA is disabled.
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl FxaaQuality {
+    /// All quality levels, in the order they are cycled through in the options.
+    pub const ALL: [FxaaQuality; 4] = [
+        FxaaQuality::Off,
+        FxaaQuality::Low,
+        FxaaQuality::Medium,
+        FxaaQuality::High,
+    ];
+
+    /// Returns the next quality level in [`FxaaQuality::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|q| *q == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the quality level.
+    pub fn name(self) -> &'static str {
+        match self {
+            FxaaQuality::Off => "Off",
+            FxaaQuality::Low => "Low",
+            FxaaQuality::Medium => "Medium",
+            FxaaQuality::High => "High",
+        }
+    }
+
+    /// The maximum number of steps taken along an edge to find its ends, and how strongly sub-pixel
+    /// aliasing is smoothed out.
+    fn parameters(self) -> (i32, f32) {
+        match self {
+            FxaaQuality::Off => (0, 0.0),
+            FxaaQuality::Low => (4, 0.5),
+            FxaaQuality::Medium => (8, 0.75),
+            FxaaQuality::High => (12, 1.0),
+        }
+    }
+}
+
+/// A color grading look applied at the end of the composite pass through a lookup table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorGrading {
+    /// No color grading.
+    #[default]
+    None,
+    Warm,
+    Cool,
+    Vintage,
+    Vivid,
+}
+
+impl ColorGrading {
+    /// All looks, in the order they are cycled through at world creation.
+    pub const ALL: [ColorGrading; 5] = [
+        ColorGrading::None,
+        ColorGrading::Warm,
+        ColorGrading::Cool,
+        ColorGrading::Vintage,
+        ColorGrading::Vivid,
+    ];
+
+    /// Returns the next look in [`ColorGrading::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|g| *g == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the look.
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorGrading::None => "None",
+            ColorGrading::Warm => "Warm",
+            ColorGrading::Cool => "Cool",
+            ColorGrading::Vintage => "Vintage",
+            ColorGrading::Vivid => "Vivid",
+        }
+    }
+
+    /// Applies the look to a color.
+    fn grade(self, color: Vec3) -> Vec3 {
+        let luma = color.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+        let graded = match self {
+            ColorGrading::None => color,
+            ColorGrading::Warm => color * Vec3::new(1.08, 1.02, 0.9),
+            ColorGrading::Cool => color * Vec3::new(0.92, 1.0, 1.08),
+            ColorGrading::Vintage => {
+                let faded = Vec3::splat(luma).lerp(color, 0.6) * 0.9 + Vec3::splat(0.06);
+                faded * Vec3::new(1.06, 1.0, 0.85)
+            }
+            ColorGrading::Vivid => {
+                let saturated = Vec3::splat(luma).lerp(color, 1.35);
+                (saturated - Vec3::splat(0.5)) * 1.1 + Vec3::splat(0.5)
+            }
+        };
+        graded.clamp(Vec3::ZERO, Vec3::ONE)
+    }
+
+    /// Builds the lookup table of the look, with the blue channel split into
+    /// [`LUT_SIZE`] slices laid out next to each other.
+    fn lut_image(self) -> image::RgbaImage {
+        let size = LUT_SIZE as u32;
+        let max = (LUT_SIZE - 1) as f32;
+        image::RgbaImage::from_fn(size * size, size, |x, y| {
+            let color = Vec3::new((x % size) as f32, y as f32, (x / size) as f32) / max;
+            let graded = self.grade(color) * 255.0;
+            image::Rgba([
+                graded.x.round() as u8,
+                graded.y.round() as u8,
+                graded.z.round() as u8,
+                255,
+            ])
+        })
+    }
+}
+
+/// The number of entries per channel in color grading lookup tables.
+const LUT_SIZE: usize = 16;
+
+/// The brightness above which pixels contribute to the bloom.
+const BLOOM_THRESHOLD: f32 = 0.8;
+const BLOOM_INTENSITY: f32 = 0.6;
+/// How many times the bloom is blurred horizontally and vertically.
+const BLOOM_BLUR_PASSES: usize = 2;
+
+/// How far, in pixels, the most out of focus parts of the scene are blurred.
+const DOF_MAX_BLUR: f32 = 8.0;
+
+/// Parameters of the depth of field pass.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthOfField {
+    /// The distance from the camera that is in perfect focus.
+    pub focus_distance: f32,
+    /// The near and far planes of the projection the scene was rendered with.
+    pub near: f32,
+    pub far: f32,
+}
+
+/// A pass that runs on the composited scene.
+#[derive(Debug, Clone, Copy)]
+enum Pass {
+    DepthOfField(DepthOfField),
+    Fxaa(FxaaQuality),
+}
+
+/// Settings of the post-processing chain, taken from the client config every frame.
+#[derive(Debug, Clone, Copy)]
+pub struct PostProcessSettings {
+    pub fxaa: FxaaQuality,
+    pub bloom: bool,
+    pub tonemapping: bool,
+    /// Multiplier applied to the scene color before tone mapping.
+    pub brightness: f32,
+    /// Only set in cinematic mode, see [`ClientConfig::depth_of_field`].
+    pub depth_of_field: Option<DepthOfField>,
+    /// Set per world rather than in the config.
+    pub color_grading: ColorGrading,
+}
+
+impl PostProcessSettings {
+    /// Creates the settings from the video settings in the config.
+    pub fn from_config(config: &ClientConfig) -> Self {
+        Self {
+            fxaa: config.fxaa_quality(),
+            bloom: config.bloom(),
+            tonemapping: config.tonemapping(),
+            brightness: config.brightness(),
+            depth_of_field: None,
+            color_grading: ColorGrading::None,
+        }
+    }
+}
+
+/// Runs the rendered scene through the post-processing passes.
+pub struct PostProcessor {
+    fullscreen_quad: Mesh,
+    composite_shader: ShaderProgram,
+    fxaa_shader: ShaderProgram,
+    depth_of_field_shader: ShaderProgram,
+    bloom_extract_shader: ShaderProgram,
+    blur_shader: ShaderProgram,
+    /// Ping-pong targets at half the scene resolution used to extract and blur the bloom.
+    bloom_targets: [Framebuffer; 2],
+    /// Ping-pong targets holding the composited scene for the passes after the composite pass.
+    ldr_targets: [Framebuffer; 2],
+    /// The lookup table of the current color grading look, built when the look changes.
+    lut: Option<(ColorGrading, Texture)>,
+    gl: Arc<glow::Context>,
+}
+
+impl PostProcessor {
+    /// Creates a new [`PostProcessor`] for a scene of the given size.
+    pub fn new(gl: &Arc<glow::Context>, width: i32, height: i32) -> Self {
+        Self {
+            fullscreen_quad: fullscreen_quad_ndc(gl),
+            composite_shader: shader_program!(postprocess, gl, ".."),
+            fxaa_shader: shader_program!(fxaa, gl, ".."),
+            depth_of_field_shader: shader_program!(depth_of_field, gl, ".."),
+            bloom_extract_shader: shader_program!(bloom_extract, gl, ".."),
+            blur_shader: shader_program!(blur, gl, ".."),
+            bloom_targets: std::array::from_fn(|_| {
+                Framebuffer::new(
+                    gl,
+                    (width / 2).max(1),
+                    (height / 2).max(1),
+                    false,
+                    &[ColorUsage::RGB16F],
+                )
+            }),
+            ldr_targets: std::array::from_fn(|_| {
+                Framebuffer::new(gl, width, height, false, &[ColorUsage::RGBA8])
+            }),
+            lut: None,
+            gl: Arc::clone(gl),
+        }
+    }
+
+    /// Resizes the intermediate targets to match the new scene size.
+    pub fn resize(&mut self, width: i32, height: i32) {
+        for target in &mut self.ldr_targets {
+            target.resize(width, height);
+        }
+        for target in &mut self.bloom_targets {
+            target.resize((width / 2).max(1), (height / 2).max(1));
+        }
+    }
+
+    /// Runs all enabled passes on the color texture of `scene`, writing the result into the
+    /// currently bound framebuffer.
+    pub fn run(&mut self, scene: &Framebuffer, settings: &PostProcessSettings, time: f32) {
+        let grading = settings.color_grading;
+        if grading != ColorGrading::None
+            && self.lut.as_ref().is_none_or(|(look, _)| *look != grading)
+        {
+            let image = image::DynamicImage::ImageRgba8(grading.lut_image());
+            self.lut = Some((grading, Texture::new(&self.gl, &image).smooth()));
+        }
+
+        if settings.bloom {
+            self.bloom(scene);
+        }
+
+        let passes = [
+            settings.depth_of_field.map(Pass::DepthOfField),
+            (settings.fxaa != FxaaQuality::Off).then_some(Pass::Fxaa(settings.fxaa)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if passes.is_empty() {
+            self.composite(scene, settings, time);
+            return;
+        }
+
+        {
+            let _fb = self.ldr_targets[0].guard();
+            self.composite(scene, settings, time);
+        }
+
+        for (i, pass) in passes.iter().enumerate() {
+            let input = &self.ldr_targets[i % 2];
+            // The last pass writes to whatever was bound before
+            let _fb = (i + 1 < passes.len()).then(|| self.ldr_targets[(i + 1) % 2].guard());
+            match pass {
+                Pass::DepthOfField(dof) => self.depth_of_field(input, scene, dof),
+                Pass::Fxaa(quality) => self.fxaa(input, *quality),
+            }
+        }
+    }
+
+    fn fxaa(&self, input: &Framebuffer, quality: FxaaQuality) {
+        let (width, height) = input.size();
+        let (search_steps, subpixel) = quality.parameters();
+        self.fxaa_shader.use_program();
+        self.fxaa_shader.set_uniform("u_texture", 0);
+        self.fxaa_shader.set_uniform(
+            "u_texel_size",
+            Vec2::ONE / Vec2::new(width as f32, height as f32),
+        );
+        self.fxaa_shader.set_uniform("u_search_steps", search_steps);
+        self.fxaa_shader.set_uniform("u_subpixel", subpixel);
+        input.textures()[0].bind(0);
+        self.fullscreen_quad.draw();
+    }
+
+    /// Blurs `input` based on how far the depth of `scene` is from the focus distance.
+    fn depth_of_field(&self, input: &Framebuffer, scene: &Framebuffer, dof: &DepthOfField) {
+        let Some(depth) = scene.depth_texture() else {
+            return;
+        };
+
+        let (width, height) = input.size();
+        self.depth_of_field_shader.use_program();
+        self.depth_of_field_shader.set_uniform("u_texture", 0);
+        self.depth_of_field_shader.set_uniform("u_depth", 1);
+        self.depth_of_field_shader.set_uniform(
+            "u_texel_size",
+            Vec2::ONE / Vec2::new(width as f32, height as f32),
+        );
+        self.depth_of_field_shader.set_uniform("u_near", dof.near);
+        self.depth_of_field_shader.set_uniform("u_far", dof.far);
+        self.depth_of_field_shader
+            .set_uniform("u_focus_distance", dof.focus_distance);
+        self.depth_of_field_shader
+            .set_uniform("u_max_blur", DOF_MAX_BLUR);
+        input.textures()[0].bind(0);
+        depth.bind(1);
+        self.fullscreen_quad.draw();
+    }
+
+    /// Extracts the bright parts of the scene into `bloom_targets[0]` and blurs them.
+    fn bloom(&self, scene: &Framebuffer) {
+        {
+            let _fb = self.bloom_targets[0].guard();
+            self.bloom_extract_shader.use_program();
+            self.bloom_extract_shader.set_uniform("u_texture", 0);
+            self.bloom_extract_shader
+                .set_uniform("u_threshold", BLOOM_THRESHOLD);
+            scene.textures()[0].bind(0);
+            self.fullscreen_quad.draw();
+        }
+
+        let (width, height) = self.bloom_targets[0].size();
+        let texel_size = Vec2::ONE / Vec2::new(width as f32, height as f32);
+
+        self.blur_shader.use_program();
+        self.blur_shader.set_uniform("u_texture", 0);
+        for _ in 0..BLOOM_BLUR_PASSES {
+            for (src, dst, direction) in [
+                (0, 1, Vec2::new(texel_size.x, 0.0)),
+                (1, 0, Vec2::new(0.0, texel_size.y)),
+            ] {
+                let _fb = self.bloom_targets[dst].guard();
+                self.blur_shader.set_uniform("u_direction", direction);
+                self.bloom_targets[src].textures()[0].bind(0);
+                self.fullscreen_quad.draw();
+            }
+        }
+    }
+
+    fn composite(&self, scene: &Framebuffer, settings: &PostProcessSettings, time: f32) {
+        let grading = settings.color_grading;
+        if grading != ColorGrading::None
+            && let Some((_, lut)) = &self.lut
+        {
+            lut.bind(2);
+        }
+
+        self.composite_shader.use_program();
+        self.composite_shader.set_uniform("u_texture", 0);
+        self.composite_shader.set_uniform("u_bloom", 1);
+        self.composite_shader.set_uniform("u_time", time);
+        self.composite_shader
+            .set_uniform("u_bloom_enabled", settings.bloom);
+        self.composite_shader
+            .set_uniform("u_bloom_intensity", BLOOM_INTENSITY);
+        self.composite_shader
+            .set_uniform("u_tonemap", settings.tonemapping);
+        self.composite_shader
+            .set_uniform("u_exposure", settings.brightness);
+        self.composite_shader.set_uniform("u_lut", 2);
+        self.composite_shader
+            .set_uniform("u_grading", grading != ColorGrading::None);
+        self.composite_shader
+            .set_uniform("u_lut_size", LUT_SIZE as f32);
+        scene.textures()[0].bind(0);
+        self.bloom_targets[0].textures()[0].bind(1);
+        self.fullscreen_quad.draw();
+    }
+}
+
+/// Builds a quad covering the whole screen, in normalized device coordinates.
+pub fn fullscreen_quad_ndc(gl: &Arc<glow::Context>) -> Mesh {
+    Mesh::new(
+        gl,
+        &[
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+        ],
+        &[0, 1, 2, 2, 3, 0],
+        glow::TRIANGLES,
+    )
+}
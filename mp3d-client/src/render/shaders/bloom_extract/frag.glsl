@@ -0,0 +1,15 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+uniform sampler2D u_texture;
+uniform float u_threshold;
+
+void main() {
+	vec3 color = texture(u_texture, v_uv).rgb;
+	float brightness = dot(color, vec3(0.2126, 0.7152, 0.0722));
+	// Soft knee so that the bloom fades in instead of popping at the threshold
+	float weight = smoothstep(u_threshold, u_threshold + 0.1, brightness);
+	frag_color = vec4(color * weight, 1.0);
+}
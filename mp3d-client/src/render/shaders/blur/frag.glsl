@@ -0,0 +1,19 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+uniform sampler2D u_texture;
+// The offset between two samples, one texel along either the x or the y axis
+uniform vec2 u_direction;
+
+const float WEIGHTS[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+	vec3 result = texture(u_texture, v_uv).rgb * WEIGHTS[0];
+	for (int i = 1; i < 5; i++) {
+		result += texture(u_texture, v_uv + u_direction * float(i)).rgb * WEIGHTS[i];
+		result += texture(u_texture, v_uv - u_direction * float(i)).rgb * WEIGHTS[i];
+	}
+	frag_color = vec4(result, 1.0);
+}
@@ -0,0 +1,33 @@
+#version 330 core
+
+in vec2 v_pos;
+out vec4 frag_color;
+
+uniform bool u_moon;
+// The phase of the moon in radians, 0 being a full moon and pi a new moon
+uniform float u_phase;
+uniform float u_visibility;
+
+void main() {
+	float radius = length(v_pos);
+
+	if (!u_moon) {
+		// A bright disk with a glow around it
+		float disk = 1.0 - smoothstep(0.45, 0.5, radius);
+		float glow = pow(max(1.0 - radius, 0.0), 3.0) * 0.6;
+		frag_color = vec4(1.0, 0.95, 0.75, max(disk, glow) * u_visibility);
+		return;
+	}
+
+	if (radius > 0.6) {
+		discard;
+	}
+	vec2 pos = v_pos / 0.6;
+	// The terminator between the lit and the dark side, an ellipse across the disk. The moon wanes
+	// from the right and waxes from the left
+	float edge = sqrt(max(1.0 - pos.y * pos.y, 0.0)) * cos(u_phase);
+	bool lit = u_phase < 3.14159265 ? pos.x <= edge : pos.x >= -edge;
+	float antialias = 1.0 - smoothstep(0.95, 1.0, length(pos));
+	vec3 color = lit ? vec3(0.92, 0.93, 0.95) : vec3(0.2, 0.22, 0.3);
+	frag_color = vec4(color, (lit ? 1.0 : 0.25) * antialias * u_visibility);
+}
@@ -0,0 +1,17 @@
+#version 330 core
+
+layout(location = 0) in vec2 a_pos;
+
+out vec2 v_pos;
+
+uniform mat4 u_projection;
+uniform mat4 u_view;
+uniform vec3 u_center;
+uniform vec3 u_right;
+uniform vec3 u_up;
+
+void main() {
+	vec3 world_pos = u_center + u_right * a_pos.x + u_up * a_pos.y;
+	gl_Position = u_projection * u_view * vec4(world_pos, 1.0);
+	v_pos = a_pos;
+}
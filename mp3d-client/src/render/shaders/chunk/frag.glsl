@@ -5,11 +5,25 @@ layout(location = 1) out vec4 frag_normal;
 
 in vec3 v_normal;
 in float v_ao;
+in vec2 v_light;
 in vec2 v_uv;
+in vec3 v_world_pos;
 
 const float NORM_EPSILON = 0.01;
+// How much dimmer every light level is than the one above it
+const float LIGHT_FALLOFF = 0.8;
+// The brightness of blocks no light reaches at all
+const float MIN_LIGHT = 0.05;
+// Light given off by blocks is a little warmer than sunlight
+const vec3 BLOCK_LIGHT_COLOR = vec3(1.0, 0.9, 0.75);
 
 uniform sampler2D u_texture;
+uniform vec3 u_camera_pos;
+uniform vec3 u_fog_color;
+uniform float u_fog_start;
+uniform float u_fog_end;
+// How brightly the sky lights the world at the time of day, which dims sky light at night
+uniform float u_daylight;
 
 void main() {
 	frag_color = texture(u_texture, v_uv);
@@ -27,4 +41,12 @@ void main() {
 	frag_color.rgb *= intensity;
 	frag_normal = vec4(v_normal * 0.5 + 0.5, 1.0);
 	frag_color.rgb *= v_ao;
+	vec2 light = pow(vec2(LIGHT_FALLOFF), (1.0 - v_light) * 15.0);
+	vec3 light_color = max(vec3(light.x * u_daylight), light.y * BLOCK_LIGHT_COLOR);
+	frag_color.rgb *= mix(vec3(MIN_LIGHT), vec3(1.0), light_color);
+
+	// Fade into the sky towards the edge of the loaded world
+	float frag_distance = length(v_world_pos - u_camera_pos);
+	float fog = smoothstep(u_fog_start, u_fog_end, frag_distance);
+	frag_color.rgb = mix(frag_color.rgb, u_fog_color, fog);
 }
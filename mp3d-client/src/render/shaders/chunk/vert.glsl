@@ -4,10 +4,13 @@ layout(location = 0) in vec3 a_pos;
 layout(location = 1) in vec3 a_normal;
 layout(location = 2) in vec2 a_uv;
 layout(location = 3) in uint a_ao;
+layout(location = 4) in vec2 a_light;
 
 out vec3 v_normal;
 out float v_ao;
+out vec2 v_light;
 out vec2 v_uv;
+out vec3 v_world_pos;
 
 uniform mat4 u_projection;
 uniform mat4 u_view;
@@ -16,5 +19,7 @@ void main() {
 	gl_Position = u_projection * u_view * vec4(a_pos, 1.0);
 	v_normal = a_normal;
 	v_ao = mix(0.4, 1.0, float(a_ao) / 3.0);
+	v_light = a_light;
 	v_uv = a_uv;
+	v_world_pos = a_pos;
 }
@@ -1,7 +1,9 @@
 #version 330 core
 
+uniform vec4 u_color;
+
 out vec4 frag_color;
 
 void main() {
-	frag_color = vec4(1.0, 1.0, 0.0, 1.0);
+	frag_color = u_color;
 }
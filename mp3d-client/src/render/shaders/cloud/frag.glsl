@@ -1,14 +1,38 @@
 #version 330 core
 
 in vec2 v_uv;
+in vec3 v_world_pos;
 out vec4 frag_color;
 
 uniform sampler2D u_texture;
+uniform vec3 u_camera_pos;
+uniform float u_coverage;
+uniform float u_opacity;
+uniform float u_distance;
+// How brightly the sky lights the clouds, see the chunk shader
+uniform float u_daylight;
 
 void main() {
-	frag_color = texture(u_texture, v_uv);
-	if (frag_color.a < 0.1) {
+	vec4 noise = texture(u_texture, v_uv);
+	float shape = noise.r + (noise.g - 0.5) * 0.15;
+
+	// More coverage lowers the threshold the noise has to pass to become a cloud
+	float threshold = 1.0 - u_coverage;
+	float density = smoothstep(threshold - 0.05, threshold + 0.15, shape);
+	if (density < 0.01) {
+		discard;
+	}
+
+	// Fade out towards the edge of the layer, and when the camera is about to pass through it so
+	// that it never shows as a flat sheet seen edge on
+	float horizontal = length(v_world_pos.xz - u_camera_pos.xz);
+	float distance_fade = 1.0 - smoothstep(u_distance * 0.5, u_distance, horizontal);
+	float altitude_fade = smoothstep(2.0, 16.0, abs(v_world_pos.y - u_camera_pos.y));
+
+	// Thick cores are darker, which makes the clouds look like they have some volume
+	vec3 color = mix(vec3(1.0), vec3(0.78, 0.8, 0.85), density * density) * u_daylight;
+	frag_color = vec4(color, density * u_opacity * distance_fade * altitude_fade);
+	if (frag_color.a < 0.01) {
 		discard;
 	}
-	frag_color.a *= 0.7;
 }
@@ -5,6 +5,7 @@
 layout(location = 0) in vec2 a_pos;
 
 out vec2 v_uv;
+out vec3 v_world_pos;
 
 uniform mat4 u_projection;
 uniform mat4 u_view;
@@ -12,17 +13,21 @@ uniform vec3 u_camera_pos;
 uniform float u_time;
 uniform float u_offset;
 uniform float u_speed;
-uniform uint u_altitude;
+uniform float u_altitude;
+uniform float u_scale;
+uniform float u_distance;
 
 void main() {
 	vec3 world_pos = vec3(
-		a_pos.x * 200.0 + u_camera_pos.x,
-		float(u_altitude) - 0.23, // Slight offset to prevent z-fighting with the blocks.
-		a_pos.y * 200.0 + u_camera_pos.z
+		a_pos.x * u_distance + u_camera_pos.x,
+		u_altitude - 0.23, // Slight offset to prevent z-fighting with the blocks.
+		a_pos.y * u_distance + u_camera_pos.z
 	);
+	v_world_pos = world_pos;
 
 	gl_Position = u_projection * u_view * vec4(world_pos, 1.0);
 
-	v_uv = a_pos * 0.5 + 0.5 + vec2(u_camera_pos.x, u_camera_pos.z) / 200.0;
-	v_uv.x += u_time * u_speed + u_offset / 200.0;
+	v_uv = world_pos.xz / u_scale;
+	v_uv.x += u_time * u_speed + u_offset;
+	v_uv.y += u_offset;
 }
@@ -0,0 +1,52 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+uniform sampler2D u_texture;
+uniform sampler2D u_depth;
+uniform vec2 u_texel_size;
+uniform float u_near;
+uniform float u_far;
+uniform float u_focus_distance;
+uniform float u_max_blur;
+
+const int SAMPLES = 24;
+const float GOLDEN_ANGLE = 2.39996323;
+
+float linear_depth(vec2 uv) {
+	float z = texture(u_depth, uv).r * 2.0 - 1.0;
+	return (2.0 * u_near * u_far) / (u_far + u_near - z * (u_far - u_near));
+}
+
+// How blurry a pixel at the given depth is, from 0 (in focus) to 1 (fully blurred)
+float circle_of_confusion(float depth) {
+	return clamp(abs(depth - u_focus_distance) / max(u_focus_distance, 1.0), 0.0, 1.0);
+}
+
+void main() {
+	float coc = circle_of_confusion(linear_depth(v_uv));
+	vec3 center = texture(u_texture, v_uv).rgb;
+	if (coc < 0.01) {
+		frag_color = vec4(center, 1.0);
+		return;
+	}
+
+	float radius = coc * u_max_blur;
+	vec3 color = center;
+	float total = 1.0;
+	// Sample a disc along a golden angle spiral
+	for (int i = 1; i < SAMPLES; i++) {
+		float r = sqrt(float(i) / float(SAMPLES)) * radius;
+		float theta = float(i) * GOLDEN_ANGLE;
+		vec2 uv = v_uv + vec2(cos(theta), sin(theta)) * r * u_texel_size;
+
+		// Keep sharp foreground objects from bleeding into the blurred background
+		float sample_coc = circle_of_confusion(linear_depth(uv));
+		float weight = sample_coc >= coc * 0.5 ? 1.0 : sample_coc;
+		color += texture(u_texture, uv).rgb * weight;
+		total += weight;
+	}
+
+	frag_color = vec4(color / total, 1.0);
+}
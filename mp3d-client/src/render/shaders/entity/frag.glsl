@@ -5,11 +5,13 @@ in vec3 v_normal;
 out vec4 frag_color;
 
 uniform sampler2D u_texture;
+// How brightly the sky lights the world, see the chunk shader
+uniform float u_daylight;
 
 void main() {
 	vec3 light_dir = normalize(vec3(-0.5, -1.0, -0.5));
 	float intensity = max(dot(v_normal, light_dir), 0.0);
 	float ambient = 0.4;
 	frag_color = texture(u_texture, v_uv);
-	frag_color.rgb *= (ambient + intensity * 0.8);
+	frag_color.rgb *= (ambient + intensity * 0.8) * u_daylight;
 }
@@ -0,0 +1,140 @@
+#version 330 core
+
+// Based on FXAA 3.11 by Timothy Lottes.
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+uniform sampler2D u_texture;
+uniform vec2 u_texel_size;
+uniform int u_search_steps;
+uniform float u_subpixel;
+
+const float EDGE_THRESHOLD_MIN = 0.0312;
+const float EDGE_THRESHOLD_MAX = 0.125;
+
+float luma(vec3 color) {
+	return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+	vec3 color_center = texture(u_texture, v_uv).rgb;
+
+	float luma_center = luma(color_center);
+	float luma_down = luma(textureOffset(u_texture, v_uv, ivec2(0, -1)).rgb);
+	float luma_up = luma(textureOffset(u_texture, v_uv, ivec2(0, 1)).rgb);
+	float luma_left = luma(textureOffset(u_texture, v_uv, ivec2(-1, 0)).rgb);
+	float luma_right = luma(textureOffset(u_texture, v_uv, ivec2(1, 0)).rgb);
+
+	float luma_min = min(luma_center, min(min(luma_down, luma_up), min(luma_left, luma_right)));
+	float luma_max = max(luma_center, max(max(luma_down, luma_up), max(luma_left, luma_right)));
+	float luma_range = luma_max - luma_min;
+
+	// Not an edge, leave the pixel untouched
+	if (luma_range < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD_MAX)) {
+		frag_color = vec4(color_center, 1.0);
+		return;
+	}
+
+	float luma_down_left = luma(textureOffset(u_texture, v_uv, ivec2(-1, -1)).rgb);
+	float luma_up_right = luma(textureOffset(u_texture, v_uv, ivec2(1, 1)).rgb);
+	float luma_up_left = luma(textureOffset(u_texture, v_uv, ivec2(-1, 1)).rgb);
+	float luma_down_right = luma(textureOffset(u_texture, v_uv, ivec2(1, -1)).rgb);
+
+	float luma_down_up = luma_down + luma_up;
+	float luma_left_right = luma_left + luma_right;
+	float luma_left_corners = luma_down_left + luma_up_left;
+	float luma_down_corners = luma_down_left + luma_down_right;
+	float luma_right_corners = luma_down_right + luma_up_right;
+	float luma_up_corners = luma_up_right + luma_up_left;
+
+	// Estimate the direction of the edge
+	float edge_horizontal = abs(-2.0 * luma_left + luma_left_corners)
+		+ abs(-2.0 * luma_center + luma_down_up) * 2.0
+		+ abs(-2.0 * luma_right + luma_right_corners);
+	float edge_vertical = abs(-2.0 * luma_up + luma_up_corners)
+		+ abs(-2.0 * luma_center + luma_left_right) * 2.0
+		+ abs(-2.0 * luma_down + luma_down_corners);
+	bool is_horizontal = edge_horizontal >= edge_vertical;
+
+	// Pick the side of the edge with the steepest gradient
+	float luma1 = is_horizontal ? luma_down : luma_left;
+	float luma2 = is_horizontal ? luma_up : luma_right;
+	float gradient1 = luma1 - luma_center;
+	float gradient2 = luma2 - luma_center;
+	bool is_1_steepest = abs(gradient1) >= abs(gradient2);
+	float gradient_scaled = 0.25 * max(abs(gradient1), abs(gradient2));
+
+	float step_length = is_horizontal ? u_texel_size.y : u_texel_size.x;
+	float luma_local_average;
+	if (is_1_steepest) {
+		step_length = -step_length;
+		luma_local_average = 0.5 * (luma1 + luma_center);
+	} else {
+		luma_local_average = 0.5 * (luma2 + luma_center);
+	}
+
+	vec2 current_uv = v_uv;
+	if (is_horizontal) {
+		current_uv.y += step_length * 0.5;
+	} else {
+		current_uv.x += step_length * 0.5;
+	}
+
+	// Walk along the edge in both directions until its ends are found
+	vec2 offset = is_horizontal ? vec2(u_texel_size.x, 0.0) : vec2(0.0, u_texel_size.y);
+	vec2 uv1 = current_uv - offset;
+	vec2 uv2 = current_uv + offset;
+
+	float luma_end1 = 0.0;
+	float luma_end2 = 0.0;
+	bool reached1 = false;
+	bool reached2 = false;
+	for (int i = 0; i < u_search_steps; i++) {
+		if (!reached1) {
+			luma_end1 = luma(texture(u_texture, uv1).rgb) - luma_local_average;
+			reached1 = abs(luma_end1) >= gradient_scaled;
+		}
+		if (!reached2) {
+			luma_end2 = luma(texture(u_texture, uv2).rgb) - luma_local_average;
+			reached2 = abs(luma_end2) >= gradient_scaled;
+		}
+		if (reached1 && reached2) {
+			break;
+		}
+
+		float step_scale = i < 4 ? 1.0 : 2.0;
+		if (!reached1) {
+			uv1 -= offset * step_scale;
+		}
+		if (!reached2) {
+			uv2 += offset * step_scale;
+		}
+	}
+
+	float distance1 = is_horizontal ? (v_uv.x - uv1.x) : (v_uv.y - uv1.y);
+	float distance2 = is_horizontal ? (uv2.x - v_uv.x) : (uv2.y - v_uv.y);
+	bool is_direction1 = distance1 < distance2;
+	float distance_final = min(distance1, distance2);
+	float edge_thickness = distance1 + distance2;
+	float pixel_offset = -distance_final / edge_thickness + 0.5;
+
+	bool is_luma_center_smaller = luma_center < luma_local_average;
+	bool correct_variation = ((is_direction1 ? luma_end1 : luma_end2) < 0.0) != is_luma_center_smaller;
+	float final_offset = correct_variation ? pixel_offset : 0.0;
+
+	// Subpixel anti-aliasing for thin features the edge search misses
+	float luma_average = (1.0 / 12.0) * (2.0 * (luma_down_up + luma_left_right) + luma_left_corners + luma_right_corners);
+	float subpixel_offset1 = clamp(abs(luma_average - luma_center) / luma_range, 0.0, 1.0);
+	float subpixel_offset2 = (-2.0 * subpixel_offset1 + 3.0) * subpixel_offset1 * subpixel_offset1;
+	final_offset = max(final_offset, subpixel_offset2 * subpixel_offset2 * u_subpixel);
+
+	vec2 final_uv = v_uv;
+	if (is_horizontal) {
+		final_uv.y += final_offset * step_length;
+	} else {
+		final_uv.x += final_offset * step_length;
+	}
+
+	frag_color = vec4(texture(u_texture, final_uv).rgb, 1.0);
+}
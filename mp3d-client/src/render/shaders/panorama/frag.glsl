@@ -0,0 +1,13 @@
+#version 330 core
+
+in vec2 v_pos;
+out vec4 frag_color;
+
+uniform samplerCube u_panorama;
+// Turns a point on the screen into a direction, from the projection and the rotation of the camera
+uniform mat4 u_inverse_view_projection;
+
+void main() {
+	vec4 far = u_inverse_view_projection * vec4(v_pos, 1.0, 1.0);
+	frag_color = vec4(texture(u_panorama, normalize(far.xyz / far.w)).rgb, 1.0);
+}
@@ -4,8 +4,51 @@ in vec2 v_uv;
 out vec4 frag_color;
 
 uniform sampler2D u_texture;
+uniform sampler2D u_bloom;
 uniform float u_time;
+uniform bool u_bloom_enabled;
+uniform float u_bloom_intensity;
+uniform bool u_tonemap;
+uniform float u_exposure;
+uniform bool u_grading;
+uniform sampler2D u_lut;
+uniform float u_lut_size;
+
+// ACES filmic curve fit by Krzysztof Narkowicz
+vec3 aces(vec3 x) {
+	return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+// Looks up a color in a lookup table with the blue channel split into slices next to each other,
+// interpolating between the two closest slices.
+vec3 grade(vec3 color) {
+	color = clamp(color, 0.0, 1.0) * (u_lut_size - 1.0);
+	float slice = floor(color.b);
+	float next_slice = min(slice + 1.0, u_lut_size - 1.0);
+	vec2 uv = vec2((color.r + 0.5) / (u_lut_size * u_lut_size), (color.g + 0.5) / u_lut_size);
+	vec3 a = texture(u_lut, uv + vec2(slice / u_lut_size, 0.0)).rgb;
+	vec3 b = texture(u_lut, uv + vec2(next_slice / u_lut_size, 0.0)).rgb;
+	return mix(a, b, color.b - slice);
+}
 
 void main() {
-	frag_color = texture(u_texture, v_uv);
+	vec3 color = texture(u_texture, v_uv).rgb;
+
+	if (u_bloom_enabled) {
+		color += texture(u_bloom, v_uv).rgb * u_bloom_intensity;
+	}
+
+	if (u_tonemap) {
+		// Tone map in linear space and convert back to sRGB afterwards
+		vec3 linear = pow(color, vec3(2.2)) * u_exposure;
+		color = pow(aces(linear), vec3(1.0 / 2.2));
+	} else {
+		color *= u_exposure;
+	}
+
+	if (u_grading) {
+		color = grade(color);
+	}
+
+	frag_color = vec4(color, 1.0);
 }
@@ -0,0 +1,11 @@
+#version 330 core
+
+in vec2 v_pos;
+out vec4 frag_color;
+
+uniform float u_opacity;
+
+void main() {
+	float falloff = 1.0 - smoothstep(0.4, 1.0, length(v_pos));
+	frag_color = vec4(0.0, 0.0, 0.0, u_opacity * falloff);
+}
@@ -0,0 +1,17 @@
+#version 330 core
+
+layout(location = 0) in vec2 a_pos;
+
+out vec2 v_pos;
+
+uniform mat4 u_projection;
+uniform mat4 u_view;
+uniform vec3 u_center;
+uniform float u_radius;
+
+void main() {
+	// Lifted slightly above the surface to prevent z-fighting with the blocks.
+	vec3 world_pos = u_center + vec3(a_pos.x * u_radius, 0.01, a_pos.y * u_radius);
+	gl_Position = u_projection * u_view * vec4(world_pos, 1.0);
+	v_pos = a_pos;
+}
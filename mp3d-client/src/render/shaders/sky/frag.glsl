@@ -0,0 +1,25 @@
+#version 330 core
+
+in vec2 v_pos;
+out vec4 frag_color;
+
+// Turns a point on the screen into a direction, from the projection and the rotation of the camera
+uniform mat4 u_inverse_view_projection;
+uniform vec3 u_zenith;
+uniform vec3 u_horizon;
+uniform vec3 u_sun;
+uniform vec3 u_sunset_color;
+// How strongly the sky glows around the sun, which it only does while the sun rises and sets
+uniform float u_sunset;
+
+void main() {
+	vec4 far = u_inverse_view_projection * vec4(v_pos, 1.0, 1.0);
+	vec3 direction = normalize(far.xyz / far.w);
+
+	// Below the horizon the sky is the same color as at the horizon, so the fog matches it
+	float up = max(direction.y, 0.0);
+	vec3 color = mix(u_horizon, u_zenith, pow(up, 0.6));
+	float glow = pow(max(dot(direction, u_sun), 0.0), 6.0) * (1.0 - up);
+	color = mix(color, u_sunset_color, glow * u_sunset);
+	frag_color = vec4(color, 1.0);
+}
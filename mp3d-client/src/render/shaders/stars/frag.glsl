@@ -0,0 +1,10 @@
+#version 330 core
+
+in float v_brightness;
+out vec4 frag_color;
+
+uniform float u_visibility;
+
+void main() {
+	frag_color = vec4(vec3(0.9, 0.92, 1.0), v_brightness * u_visibility);
+}
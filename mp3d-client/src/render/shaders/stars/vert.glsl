@@ -0,0 +1,15 @@
+#version 330 core
+
+layout(location = 0) in vec3 a_pos;
+layout(location = 1) in float a_brightness;
+
+out float v_brightness;
+
+uniform mat4 u_projection;
+uniform mat4 u_view;
+uniform mat4 u_rotation;
+
+void main() {
+	gl_Position = u_projection * u_view * u_rotation * vec4(a_pos, 1.0);
+	v_brightness = a_brightness;
+}
@@ -0,0 +1,113 @@
+//! Blob shadows under entities.
+//!
+//! Each entity gets a dark, round quad on the highest solid surface below it, which shrinks and
+//! fades the higher the entity is above the surface. This is a cheap stand-in for shadow mapping
+//! that mostly helps judging where entities are in the air.
+
+use std::sync::Arc;
+
+use glam::{Mat4, Vec2, Vec3};
+use glow::HasContext;
+use mp3d_core::block::block_registry;
+
+use crate::{
+    abs::{Mesh, ShaderProgram},
+    client::world::ClientWorld,
+    shader_program,
+};
+
+/// How far below an entity to look for a surface to cast the shadow on.
+const MAX_SHADOW_DEPTH: f32 = 6.0;
+/// The precision with which the surface below an entity is found.
+const SURFACE_STEP: f32 = 1.0 / 16.0;
+/// The opacity of the shadow of an entity standing on the ground.
+const MAX_SHADOW_OPACITY: f32 = 0.5;
+/// The shadow radius relative to the entity width.
+const SHADOW_SCALE: f32 = 0.8;
+
+/// An entity casting a shadow.
+pub struct ShadowCaster {
+    /// The position of the bottom center of the entity.
+    pub position: Vec3,
+    pub width: f32,
+}
+
+/// Draws blob shadows under entities.
+pub struct ShadowRenderer {
+    mesh: Mesh,
+    shader: ShaderProgram,
+}
+
+impl ShadowRenderer {
+    pub fn new(gl: &Arc<glow::Context>) -> Self {
+        let vertices = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+        ];
+        let indices = [0u32, 1, 2, 2, 3, 0];
+
+        Self {
+            mesh: Mesh::new(gl, &vertices, &indices, glow::TRIANGLES),
+            shader: shader_program!(shadow, gl, ".."),
+        }
+    }
+
+    /// Draws the shadows of the casters into the current framebuffer, which should already
+    /// contain the depth of the terrain.
+    pub fn draw(
+        &self,
+        gl: &Arc<glow::Context>,
+        world: &ClientWorld,
+        casters: &[ShadowCaster],
+        view: Mat4,
+        projection: Mat4,
+    ) {
+        unsafe {
+            gl.disable(glow::CULL_FACE);
+            gl.depth_mask(false);
+        }
+
+        self.shader.use_program();
+        self.shader.set_uniform("u_view", view);
+        self.shader.set_uniform("u_projection", projection);
+        for caster in casters {
+            let Some(surface) = surface_below(world, caster.position) else {
+                continue;
+            };
+            let strength = 1.0 - (caster.position.y - surface) / MAX_SHADOW_DEPTH;
+            self.shader.set_uniform(
+                "u_center",
+                Vec3::new(caster.position.x, surface, caster.position.z),
+            );
+            self.shader
+                .set_uniform("u_radius", caster.width * SHADOW_SCALE * strength);
+            self.shader
+                .set_uniform("u_opacity", MAX_SHADOW_OPACITY * strength);
+            self.mesh.draw();
+        }
+
+        unsafe {
+            gl.depth_mask(true);
+            gl.enable(glow::CULL_FACE);
+        }
+    }
+}
+
+/// Returns the height of the highest solid surface below `position`, if there is one within
+/// [`MAX_SHADOW_DEPTH`].
+fn surface_below(world: &ClientWorld, position: Vec3) -> Option<f32> {
+    let mut depth = 0.0;
+    while depth <= MAX_SHADOW_DEPTH {
+        let pos = position - Vec3::Y * depth;
+        let block_pos = pos.floor().as_ivec3();
+        let (block, state) = world.get_block_at(block_pos)?;
+        let block_def = block_registry().get(block).unwrap();
+        if block_def.collides_with_player(0.0, 0.0, pos - block_pos.as_vec3(), *state) {
+            return Some(pos.y + SURFACE_STEP);
+        }
+        depth += SURFACE_STEP;
+    }
+    None
+}
@@ -0,0 +1,221 @@
+//! Rendering the sky with the sun, the moon and the stars.
+//!
+//! The sky is drawn before everything else, around the camera and without depth, so that the
+//! world covers it. Its color fades from the horizon up, darkening at night and glowing around
+//! the sun while it rises and sets. The stars come from the sky seed sent by the server and turn
+//! with the sun, fading in as it sets. See [`mp3d_core::world::sky`] for where everything is in
+//! the sky.
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+use glow::HasContext;
+use mp3d_core::world::sky::{self, NIGHT_DAYLIGHT, daylight, moon_phase, sun_angle, sun_direction};
+
+use crate::abs::{Mesh, ShaderProgram, Vertex};
+
+/// The distance from the camera the sky is drawn at, well within the far plane.
+const SKY_DISTANCE: f32 = 100.0;
+
+/// The size of the sun and the moon, as half the width of their square.
+const SUN_SIZE: f32 = 8.0;
+const MOON_SIZE: f32 = 5.0;
+
+/// The color of the sky overhead and at the horizon, during the day and at night.
+const DAY_ZENITH: Vec3 = Vec3::new(0.4, 0.58, 0.92);
+const DAY_HORIZON: Vec3 = Vec3::new(0.7, 0.7, 0.9);
+const NIGHT_ZENITH: Vec3 = Vec3::new(0.01, 0.01, 0.04);
+const NIGHT_HORIZON: Vec3 = Vec3::new(0.04, 0.05, 0.1);
+/// The color of the glow around the sun while it rises and sets.
+const SUNSET_COLOR: Vec3 = Vec3::new(1.0, 0.5, 0.25);
+
+/// Returns the color of the sky overhead and at the horizon at the given world time. The horizon
+/// is what the terrain fades into in the distance.
+pub fn sky_colors(time: f64) -> (Vec3, Vec3) {
+    let day = (daylight(time) - NIGHT_DAYLIGHT) / (1.0 - NIGHT_DAYLIGHT);
+    let horizon = NIGHT_HORIZON
+        .lerp(DAY_HORIZON, day)
+        .lerp(SUNSET_COLOR, sunset(time) * 0.25);
+    (NIGHT_ZENITH.lerp(DAY_ZENITH, day), horizon)
+}
+
+/// Returns how strongly the sky glows around the sun at the given world time, from 0 when it is
+/// high up or well below the horizon to 1 when it is on the horizon.
+fn sunset(time: f64) -> f32 {
+    1.0 - smoothstep(0.0, 0.3, sun_angle(time).sin().abs())
+}
+
+#[repr(C)]
+pub struct StarVertex {
+    pub position: Vec3,
+    pub brightness: f32,
+}
+
+impl Vertex for StarVertex {
+    fn vertex_attribs(gl: &glow::Context) {
+        unsafe {
+            let stride = size_of::<StarVertex>() as i32;
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(1);
+            gl.vertex_attrib_pointer_f32(1, 1, glow::FLOAT, false, stride, 12);
+        }
+    }
+}
+
+#[repr(C)]
+pub struct CelestialVertex(pub Vec2);
+
+impl Vertex for CelestialVertex {
+    fn vertex_attribs(gl: &glow::Context) {
+        unsafe {
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(
+                0,
+                2,
+                glow::FLOAT,
+                false,
+                size_of::<CelestialVertex>() as i32,
+                0,
+            );
+        }
+    }
+}
+
+pub struct SkyRenderer {
+    /// The stars, with the sky seed they were generated from.
+    stars: Option<(u64, Mesh)>,
+    star_shader: ShaderProgram,
+    /// A square covering the screen when drawn without transforming it, like in the gradient
+    /// shader, and the sun and the moon otherwise.
+    celestial_mesh: Mesh,
+    celestial_shader: ShaderProgram,
+    gradient_shader: ShaderProgram,
+}
+
+impl SkyRenderer {
+    pub fn new(gl: &std::sync::Arc<glow::Context>) -> Self {
+        let vertices = [
+            CelestialVertex(Vec2::new(-1.0, -1.0)),
+            CelestialVertex(Vec2::new(1.0, -1.0)),
+            CelestialVertex(Vec2::new(1.0, 1.0)),
+            CelestialVertex(Vec2::new(-1.0, 1.0)),
+        ];
+        let indices = [0u32, 1, 2, 2, 3, 0];
+
+        Self {
+            stars: None,
+            star_shader: crate::shader_program!(stars, gl, ".."),
+            celestial_mesh: Mesh::new(gl, &vertices, &indices, glow::TRIANGLES),
+            celestial_shader: crate::shader_program!(celestial, gl, ".."),
+            gradient_shader: crate::shader_program!(sky, gl, ".."),
+        }
+    }
+
+    /// Draws the sky at the given world time. The stars are only drawn once the sky seed is
+    /// known.
+    pub fn draw(
+        &mut self,
+        gl: &std::sync::Arc<glow::Context>,
+        projection: Mat4,
+        view: Mat4,
+        time: f64,
+        sky_seed: Option<u64>,
+        sunrise_yaw: f32,
+    ) {
+        if let Some(seed) = sky_seed
+            && self.stars.as_ref().is_none_or(|(built, _)| *built != seed)
+        {
+            self.stars = Some((seed, star_mesh(gl, seed)));
+        }
+
+        // Only the rotation of the camera matters, the sky is infinitely far away
+        let view = Mat4::from_quat(Quat::from_mat4(&view));
+        let sun = sun_direction(time, sunrise_yaw);
+        let sunrise = sun_direction(0.0, sunrise_yaw);
+        // The stars turn with the sun around the axis of its path
+        let axis = sunrise.cross(Vec3::Y).normalize();
+        let rotation = Mat4::from_axis_angle(axis, sun_angle(time));
+        let night = smoothstep(0.1, -0.2, sun.y);
+        let phase = moon_phase(time as u64);
+
+        unsafe {
+            gl.disable(glow::DEPTH_TEST);
+            gl.disable(glow::CULL_FACE);
+            gl.depth_mask(false);
+
+            let (zenith, horizon) = sky_colors(time);
+            self.gradient_shader.use_program();
+            self.gradient_shader
+                .set_uniform("u_inverse_view_projection", (projection * view).inverse());
+            self.gradient_shader.set_uniform("u_zenith", zenith);
+            self.gradient_shader.set_uniform("u_horizon", horizon);
+            self.gradient_shader.set_uniform("u_sun", sun);
+            self.gradient_shader
+                .set_uniform("u_sunset_color", SUNSET_COLOR);
+            self.gradient_shader.set_uniform("u_sunset", sunset(time));
+            self.celestial_mesh.draw();
+
+            if let Some((_, stars)) = &self.stars
+                && night > 0.0
+            {
+                self.star_shader.use_program();
+                self.star_shader.set_uniform("u_projection", projection);
+                self.star_shader.set_uniform("u_view", view);
+                self.star_shader.set_uniform("u_rotation", rotation);
+                self.star_shader.set_uniform("u_visibility", night);
+                stars.draw();
+            }
+
+            self.celestial_shader.use_program();
+            self.celestial_shader
+                .set_uniform("u_projection", projection);
+            self.celestial_shader.set_uniform("u_view", view);
+            for (direction, size, moon) in [(sun, SUN_SIZE, false), (-sun, MOON_SIZE, true)] {
+                let right = direction.cross(axis).normalize();
+                let up = right.cross(direction);
+                self.celestial_shader
+                    .set_uniform("u_center", direction * SKY_DISTANCE);
+                self.celestial_shader.set_uniform("u_right", right * size);
+                self.celestial_shader.set_uniform("u_up", up * size);
+                self.celestial_shader.set_uniform("u_moon", moon);
+                self.celestial_shader.set_uniform(
+                    "u_phase",
+                    phase as f32 / sky::MOON_PHASES.len() as f32 * std::f32::consts::TAU,
+                );
+                self.celestial_shader
+                    .set_uniform("u_visibility", smoothstep(-0.15, 0.05, direction.y));
+                self.celestial_mesh.draw();
+            }
+
+            gl.enable(glow::DEPTH_TEST);
+            gl.enable(glow::CULL_FACE);
+            gl.depth_mask(true);
+        }
+    }
+}
+
+/// Builds a small square facing the camera for every star.
+fn star_mesh(gl: &std::sync::Arc<glow::Context>, sky_seed: u64) -> Mesh {
+    let stars = sky::stars(sky_seed);
+    let mut vertices = Vec::with_capacity(stars.len() * 4);
+    let mut indices = Vec::with_capacity(stars.len() * 6);
+    for star in stars {
+        let center = star.direction * SKY_DISTANCE;
+        let size = 0.12 + 0.2 * star.brightness;
+        let right = star.direction.any_orthonormal_vector() * size;
+        let up = star.direction.cross(right);
+        let first = vertices.len() as u32;
+        for corner in [-right - up, right - up, right + up, -right + up] {
+            vertices.push(StarVertex {
+                position: center + corner,
+                brightness: star.brightness,
+            });
+        }
+        indices.extend([0, 1, 2, 2, 3, 0].map(|i| first + i));
+    }
+    Mesh::new(gl, &vertices, &indices, glow::TRIANGLES)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
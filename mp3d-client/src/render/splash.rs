@@ -0,0 +1,46 @@
+//! The splash screen shown while the assets are loading.
+//!
+//! Nothing but the UI shader is available at that point, so the splash screen only consists of
+//! plain colored quads.
+
+use glam::{Vec2, Vec4};
+use glow::HasContext;
+
+use crate::render::ui::uirenderer::{DrawCommand, UIRenderMode, UIRenderer};
+
+const BAR_SIZE: Vec2 = Vec2::new(400.0, 12.0);
+/// How far the border around the progress bar extends past it.
+const BAR_BORDER: f32 = 2.0;
+
+/// Draws a progress bar in the middle of the screen, `progress` ranging from 0 to 1.
+pub fn draw_splash(gl: &glow::Context, ui: &mut UIRenderer, screen_size: Vec2, progress: f32) {
+    unsafe {
+        gl.clear_color(0.1, 0.1, 0.2, 1.0);
+        gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+    }
+
+    let min = (screen_size - BAR_SIZE) / 2.0;
+    let max = min + BAR_SIZE;
+    let filled = min.x + BAR_SIZE.x * progress.clamp(0.0, 1.0);
+    for (rect, color, layer) in [
+        (
+            [min - BAR_BORDER, max + BAR_BORDER],
+            Vec4::new(1.0, 1.0, 1.0, 0.8),
+            0,
+        ),
+        ([min, max], Vec4::new(0.1, 0.1, 0.2, 1.0), 1),
+        (
+            [min, Vec2::new(filled, max.y)],
+            Vec4::new(1.0, 1.0, 1.0, 0.8),
+            2,
+        ),
+    ] {
+        ui.add_command(DrawCommand::Quad {
+            rect,
+            uv_rect: [Vec2::ZERO, Vec2::ONE],
+            mode: UIRenderMode::Color(color),
+            layer,
+        });
+    }
+    ui.finish();
+}
@@ -2,7 +2,9 @@ use glam::{Vec2, Vec4};
 use mp3d_core::textcomponent::TextComponent;
 
 use crate::{
-    abs::Texture, render::ui::uirenderer::DrawCommand, resource::fontsettings::FontSettings,
+    abs::Texture,
+    render::ui::{theme::TextPalette, uirenderer::DrawCommand},
+    resource::fontsettings::FontSettings,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -57,6 +59,7 @@ pub struct Font {
     char_size: Vec2,
     first_char: char,
     strikethrough: Option<u32>,
+    palette: TextPalette,
 }
 
 impl Font {
@@ -69,9 +72,16 @@ impl Font {
             ),
             first_char: font_settings.first_char,
             strikethrough: font_settings.strikethrough_idx,
+            palette: TextPalette::default(),
         }
     }
 
+    /// Sets the palette used to resolve the colors of text components.
+    pub fn with_palette(mut self, palette: TextPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
     pub fn atlas(&self) -> &Texture {
         &self.atlas
     }
@@ -169,7 +179,7 @@ impl Font {
         .into_iter()
         .zip(component.to_styled_chars())
         .map(|(mut pg, sc)| {
-            pg.color = sc.color.into();
+            pg.color = self.palette.resolve(sc.color);
             pg
         })
         .collect()
@@ -0,0 +1,334 @@
+//! Placing the elements of the HUD, which players can move and resize to their liking.
+//!
+//! Every [`HudElement`] is drawn as if its top left corner was at the origin, and is then moved
+//! into place by its [`HudPlacement`]: a point of the screen it is anchored to, an offset from
+//! there and a scale. Anchoring keeps elements in the same spot relative to the nearest edges or
+//! corner when the window is resized. The placements are kept in the config and changed with the
+//! [`HudEditor`] from the pause menu.
+
+use std::collections::HashMap;
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use sdl2::{keyboard::Keycode, mouse::MouseButton};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::UpdateContext,
+    render::ui::{
+        font::TextParams,
+        uirenderer::{DrawCommand, UIRenderMode, UIRenderer},
+    },
+    scenes::{Assets, options::ClientConfig},
+};
+
+/// The smallest and largest scale of a HUD element.
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 3.0;
+
+/// How much the scale of the selected element changes per step of the mouse wheel.
+const SCALE_STEP: f32 = 0.1;
+
+/// The thickness of the outlines around the elements in the editor.
+const OUTLINE_THICKNESS: f32 = 2.0;
+
+/// The parts of the HUD that can be moved and resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HudElement {
+    Hotbar,
+    Chat,
+    KillFeed,
+    Debug,
+    Health,
+}
+
+impl HudElement {
+    pub const ALL: [HudElement; 5] = [
+        HudElement::Hotbar,
+        HudElement::Health,
+        HudElement::Chat,
+        HudElement::KillFeed,
+        HudElement::Debug,
+    ];
+
+    /// Returns a human readable name for the element.
+    pub fn name(self) -> &'static str {
+        match self {
+            HudElement::Hotbar => "Hotbar",
+            HudElement::Chat => "Chat",
+            HudElement::KillFeed => "Kill Feed",
+            HudElement::Debug => "Debug",
+            HudElement::Health => "Health",
+        }
+    }
+
+    /// Returns where the element is placed unless the player moved it.
+    pub fn default_placement(self) -> HudPlacement {
+        let (anchor, offset) = match self {
+            HudElement::Hotbar => (Anchor::Bottom, [0.0, -10.0]),
+            HudElement::Chat => (Anchor::BottomLeft, [5.0, -92.0]),
+            HudElement::KillFeed => (Anchor::TopRight, [-10.0, 10.0]),
+            HudElement::Debug => (Anchor::TopLeft, [10.0, 10.0]),
+            HudElement::Health => (Anchor::Top, [0.0, 10.0]),
+        };
+        HudPlacement {
+            anchor,
+            offset,
+            scale: 1.0,
+        }
+    }
+
+    /// Returns the size the element takes up in the editor when it is smaller, so that elements
+    /// without anything to show, like an empty chat, can still be grabbed.
+    fn placeholder_size(self) -> Vec2 {
+        match self {
+            HudElement::Hotbar => Vec2::ZERO,
+            HudElement::Chat => Vec2::new(480.0, 120.0),
+            HudElement::KillFeed => Vec2::new(240.0, 40.0),
+            HudElement::Debug => Vec2::new(320.0, 360.0),
+            HudElement::Health => Vec2::ZERO,
+        }
+    }
+}
+
+/// A point of the screen a HUD element is placed relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Returns where the anchor is on the screen, and on the element, as a fraction of their size.
+    fn fraction(self) -> Vec2 {
+        match self {
+            Anchor::TopLeft => Vec2::new(0.0, 0.0),
+            Anchor::Top => Vec2::new(0.5, 0.0),
+            Anchor::TopRight => Vec2::new(1.0, 0.0),
+            Anchor::Left => Vec2::new(0.0, 0.5),
+            Anchor::Center => Vec2::new(0.5, 0.5),
+            Anchor::Right => Vec2::new(1.0, 0.5),
+            Anchor::BottomLeft => Vec2::new(0.0, 1.0),
+            Anchor::Bottom => Vec2::new(0.5, 1.0),
+            Anchor::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+
+    /// Returns the anchor of the third of the screen the point is in along each axis.
+    fn nearest(point: Vec2, screen_size: Vec2) -> Self {
+        let third = |value: f32, size: f32| (value / size * 3.0).clamp(0.0, 2.0) as usize;
+        let anchors = [
+            [Anchor::TopLeft, Anchor::Top, Anchor::TopRight],
+            [Anchor::Left, Anchor::Center, Anchor::Right],
+            [Anchor::BottomLeft, Anchor::Bottom, Anchor::BottomRight],
+        ];
+        anchors[third(point.y, screen_size.y)][third(point.x, screen_size.x)]
+    }
+}
+
+/// Where a HUD element is on the screen and how large it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HudPlacement {
+    pub anchor: Anchor,
+    /// The distance of the anchor of the element from the anchor on the screen, in pixels.
+    pub offset: [f32; 2],
+    pub scale: f32,
+}
+
+impl HudPlacement {
+    /// Returns the rectangle an element of the given unscaled size covers on the screen.
+    pub fn rect(&self, size: Vec2, screen_size: Vec2) -> [Vec2; 2] {
+        let fraction = self.anchor.fraction();
+        let size = size * self.scale;
+        let min = screen_size * fraction + Vec2::from(self.offset) - size * fraction;
+        [min, min + size]
+    }
+
+    /// Draws an element of the given unscaled size in place. `draw` draws the element with its top
+    /// left corner at the origin.
+    pub fn draw(
+        &self,
+        ui: &mut UIRenderer,
+        size: Vec2,
+        screen_size: Vec2,
+        draw: impl FnOnce(&mut UIRenderer),
+    ) {
+        let [min, _] = self.rect(size, screen_size);
+        let projection = ui.projection_matrix;
+        ui.finish();
+        ui.projection_matrix = projection
+            * Mat4::from_translation(min.extend(0.0))
+            * Mat4::from_scale(Vec3::new(self.scale, self.scale, 1.0));
+        draw(ui);
+        ui.finish();
+        ui.projection_matrix = projection;
+    }
+
+    /// Moves an element of the given unscaled size so that its top left corner is at `min`, kept
+    /// on the screen and anchored to the closest point of the screen.
+    fn move_to(&mut self, min: Vec2, size: Vec2, screen_size: Vec2) {
+        let size = size * self.scale;
+        let min = min.clamp(Vec2::ZERO, (screen_size - size).max(Vec2::ZERO));
+        self.anchor = Anchor::nearest(min + size / 2.0, screen_size);
+        let fraction = self.anchor.fraction();
+        self.offset = (min + size * fraction - screen_size * fraction).to_array();
+    }
+}
+
+/// Returns the size of an element in the editor, which is at least its placeholder size.
+fn editor_size(element: HudElement, sizes: &HashMap<HudElement, Vec2>) -> Vec2 {
+    let size = sizes.get(&element).copied().unwrap_or_default();
+    size.max(element.placeholder_size())
+}
+
+/// The screen for moving and resizing the HUD elements. Elements are dragged with the mouse and
+/// resized with the mouse wheel, and R puts the selected element back where it was by default.
+#[derive(Debug, Default)]
+pub struct HudEditor {
+    selected: Option<HudElement>,
+    /// The position of the mouse relative to the top left corner of the element being dragged.
+    grab: Option<Vec2>,
+}
+
+impl HudEditor {
+    /// Moves and resizes the elements, given their unscaled sizes when they were last drawn, and
+    /// updates their placements in the config.
+    pub fn update(
+        &mut self,
+        ctx: &UpdateContext,
+        config: &mut ClientConfig,
+        sizes: &HashMap<HudElement, Vec2>,
+        screen_size: Vec2,
+    ) {
+        let mouse = ctx.mouse.position;
+        let size_of = |element| editor_size(element, sizes);
+
+        if ctx.mouse.pressed.contains(&MouseButton::Left) {
+            // The elements drawn last are on top, so they are grabbed first
+            let hovered = HudElement::ALL.into_iter().rev().find(|&element| {
+                let [min, max] = config
+                    .hud_placement(element)
+                    .rect(size_of(element), screen_size);
+                mouse.cmpge(min).all() && mouse.cmple(max).all()
+            });
+            self.selected = hovered;
+            self.grab = hovered.map(|element| {
+                mouse
+                    - config
+                        .hud_placement(element)
+                        .rect(size_of(element), screen_size)[0]
+            });
+        }
+        if !ctx.mouse.down.contains(&MouseButton::Left) {
+            self.grab = None;
+        }
+
+        let Some(selected) = self.selected else {
+            return;
+        };
+        if ctx.keyboard.pressed.contains(&Keycode::R) {
+            if let Some(layout) = &mut config.hud_layout {
+                layout.remove(&selected);
+            }
+            return;
+        }
+        let mut placement = config.hud_placement(selected);
+        if let Some(grab) = self.grab {
+            placement.move_to(mouse - grab, size_of(selected), screen_size);
+        }
+        if ctx.mouse.scroll_delta.y != 0.0 {
+            // Resizing keeps the element in place around its anchor
+            placement.scale = (placement.scale + ctx.mouse.scroll_delta.y.signum() * SCALE_STEP)
+                .clamp(MIN_SCALE, MAX_SCALE);
+            let [min, _] = placement.rect(size_of(selected), screen_size);
+            placement.move_to(min, size_of(selected), screen_size);
+        }
+        if placement != config.hud_placement(selected) {
+            config
+                .hud_layout
+                .get_or_insert_default()
+                .insert(selected, placement);
+        }
+    }
+
+    /// Draws an outline around every element with its name, and how to use the editor.
+    pub fn draw(
+        &self,
+        ui: &mut UIRenderer,
+        assets: &Assets,
+        config: &ClientConfig,
+        sizes: &HashMap<HudElement, Vec2>,
+        screen_size: Vec2,
+    ) {
+        for element in HudElement::ALL {
+            let [min, max] = config
+                .hud_placement(element)
+                .rect(editor_size(element, sizes), screen_size);
+            let color = if self.selected == Some(element) {
+                Vec4::new(1.0, 0.9, 0.0, 1.0)
+            } else {
+                Vec4::new(1.0, 1.0, 1.0, 0.6)
+            };
+            let t = OUTLINE_THICKNESS;
+            for rect in [
+                [min, Vec2::new(max.x, min.y + t)],
+                [Vec2::new(min.x, max.y - t), max],
+                [min, Vec2::new(min.x + t, max.y)],
+                [Vec2::new(max.x - t, min.y), max],
+            ] {
+                ui.add_command(DrawCommand::Quad {
+                    rect,
+                    uv_rect: [Vec2::ZERO, Vec2::ONE],
+                    mode: UIRenderMode::Color(color),
+                    layer: 0,
+                });
+            }
+            Self::text(
+                ui,
+                assets,
+                element.name(),
+                min + Vec2::splat(t * 2.0),
+                color,
+            );
+        }
+
+        let help = "Drag to move, scroll to resize, R to reset, Escape when done";
+        let params = TextParams::default();
+        let width = assets.font.measure_text(help, params.without_color()).x;
+        Self::text(
+            ui,
+            assets,
+            help,
+            Vec2::new((screen_size.x - width) / 2.0, screen_size.y / 2.0),
+            params.color,
+        );
+        ui.finish();
+    }
+
+    fn text(ui: &mut UIRenderer, assets: &Assets, text: &str, position: Vec2, color: Vec4) {
+        let params = TextParams {
+            color,
+            ..TextParams::default()
+        };
+        for mut cmd in assets.font.text(text, params) {
+            match &mut cmd {
+                DrawCommand::Quad { rect, .. } => {
+                    rect[0] += position;
+                    rect[1] += position;
+                }
+                DrawCommand::Mesh { vertices, .. } => {
+                    for v in vertices {
+                        v.position += position.extend(0.0);
+                    }
+                }
+            }
+            ui.add_command(cmd);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Drawing of item stacks and their tooltips in the UI.
+
+use glam::{Mat4, Vec2, Vec4};
+use mp3d_core::{
+    block::{BlockState, block_registry},
+    item::{ItemStack, item_registry},
+};
+
+use crate::{
+    render::ui::{
+        font::{ColorlessTextParams, TextParams},
+        uirenderer::{DrawCommand, UIRenderMode, UIRenderer},
+    },
+    scenes::Assets,
+};
+
+/// The layer of the count of a stack, above the models of blocks.
+const COUNT_LAYER: i32 = 500;
+/// The layer of the background of tooltips, above everything but text.
+const TOOLTIP_LAYER: i32 = 1000;
+const TOOLTIP_PADDING: f32 = 8.0;
+const TOOLTIP_LINE_SPACING: f32 = 4.0;
+/// Where tooltips are drawn relative to the mouse, so the cursor doesn't cover them.
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+const TOOLTIP_BACKGROUND: Vec4 = Vec4::new(0.1, 0.05, 0.15, 0.9);
+const TOOLTIP_DETAIL_COLOR: Vec4 = Vec4::new(0.7, 0.7, 0.7, 1.0);
+const TOOLTIP_IDENT_COLOR: Vec4 = Vec4::new(0.45, 0.45, 0.45, 1.0);
+
+/// Draws item stacks in slots of a given size. Items with a block are drawn as a small model of
+/// the block, the others flat from their texture, and the count of the stack goes in the bottom
+/// right corner of the slot.
+pub struct ItemRenderer {
+    pub slot_size: Vec2,
+    /// The size the items are drawn at, which may be smaller than the slot.
+    pub item_size: Vec2,
+}
+
+impl ItemRenderer {
+    pub const fn new(slot_size: Vec2, item_size: Vec2) -> Self {
+        Self {
+            slot_size,
+            item_size,
+        }
+    }
+
+    /// Returns the draw commands for a stack in a slot centered on `position`.
+    pub fn stack_commands(
+        &self,
+        stack: ItemStack,
+        assets: &Assets,
+        position: Vec2,
+        ui: &UIRenderer,
+    ) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        if stack.is_empty() {
+            return commands;
+        }
+        if let Some(block) = item_registry().get(stack.item).unwrap().assoc_block {
+            let block = **block;
+            let block_def = block_registry().get(block).unwrap();
+            if block_def.visible {
+                let item_block_state = BlockState::default_state(block_def.state_type).unwrap();
+                let item_block_model = assets
+                    .block_models
+                    .get(&(block, item_block_state.data()))
+                    .unwrap();
+                commands.extend(item_block_model.draw_commands(
+                    &ui.gl,
+                    &assets.block_textures,
+                    position,
+                    self.item_size / 1.75,
+                    Mat4::from_rotation_x(30f32.to_radians())
+                        * Mat4::from_rotation_y(-std::f32::consts::FRAC_PI_4),
+                ));
+            }
+        } else {
+            commands.extend(self.flat_item_commands(stack, assets, position, ui));
+        }
+
+        // Draw the item count if greater than 1
+        if stack.count > 1 {
+            let bottom_right = position + self.slot_size / 2.0;
+            let count_text = stack.count.to_string();
+            let text_position = bottom_right
+                - assets
+                    .font
+                    .measure_text(&count_text, ColorlessTextParams::default())
+                - Vec2::new(4.0, 4.0);
+            commands.extend(
+                assets
+                    .font
+                    .text(&count_text, TextParams::default())
+                    .into_iter()
+                    .map(|cmd| moved(cmd, text_position, Some(COUNT_LAYER))),
+            );
+        }
+        commands
+    }
+
+    /// Returns the draw commands for an item without a block, which is drawn flat from its
+    /// texture. Worn tools also get a durability bar below them.
+    fn flat_item_commands(
+        &self,
+        stack: ItemStack,
+        assets: &Assets,
+        position: Vec2,
+        ui: &UIRenderer,
+    ) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        let size = self.item_size * 0.75;
+        let item_def = item_registry().get(stack.item).unwrap();
+        let texture_name = crate::scenes::item_texture_name(item_def.ident);
+        if let Some([uv_a, uv_b]) = assets
+            .block_textures
+            .get_uv(&texture_name, [Vec2::ZERO, Vec2::ONE])
+        {
+            commands.push(DrawCommand::Quad {
+                rect: [position - size / 2.0, position + size / 2.0],
+                uv_rect: [uv_b, uv_a],
+                mode: UIRenderMode::Texture(
+                    assets.block_textures.upload(&ui.gl).handle(),
+                    Vec4::ONE,
+                ),
+                layer: 1,
+            });
+        }
+
+        if let Some(tool) = item_def.tool
+            && stack.damage > 0
+        {
+            let left = 1.0 - stack.damage as f32 / tool.durability as f32;
+            let min = position + Vec2::new(-size.x * 0.4, size.y * 0.4);
+            let max = min + Vec2::new(size.x * 0.8, 4.0);
+            commands.push(DrawCommand::Quad {
+                rect: [min, max],
+                uv_rect: [Vec2::ZERO, Vec2::ONE],
+                mode: UIRenderMode::Color(Vec4::new(0.0, 0.0, 0.0, 1.0)),
+                layer: 2,
+            });
+            commands.push(DrawCommand::Quad {
+                rect: [min, Vec2::new(min.x + (max.x - min.x) * left, max.y - 1.0)],
+                uv_rect: [Vec2::ZERO, Vec2::ONE],
+                mode: UIRenderMode::Color(Vec4::new(1.0 - left, left, 0.0, 1.0)),
+                layer: 3,
+            });
+        }
+        commands
+    }
+
+    /// Returns the draw commands for the tooltip of a stack hovered at `mouse_pos`, showing the
+    /// translated name of the item, the durability of tools and the identifier of the item.
+    pub fn tooltip_commands(
+        stack: ItemStack,
+        assets: &Assets,
+        mouse_pos: Vec2,
+    ) -> Vec<DrawCommand> {
+        if stack.is_empty() {
+            return Vec::new();
+        }
+        let item_def = item_registry().get(stack.item).unwrap();
+        let mut lines = vec![(assets.translations.item_name(item_def.ident), Vec4::ONE)];
+        if let Some(tool) = item_def.tool {
+            lines.push((
+                format!(
+                    "Durability: {}/{}",
+                    tool.durability - stack.damage,
+                    tool.durability
+                ),
+                TOOLTIP_DETAIL_COLOR,
+            ));
+        }
+        lines.push((item_def.ident.to_string(), TOOLTIP_IDENT_COLOR));
+        Self::text_tooltip_commands(&lines, assets, mouse_pos)
+    }
+
+    /// Returns the draw commands for the tooltip of a chat message part hovered at `mouse_pos`,
+    /// showing the first line of its hover text like the name of an item and the others dimmed.
+    pub fn hover_tooltip_commands(
+        text: &str,
+        assets: &Assets,
+        mouse_pos: Vec2,
+    ) -> Vec<DrawCommand> {
+        let lines: Vec<_> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let color = if i == 0 {
+                    Vec4::ONE
+                } else {
+                    TOOLTIP_DETAIL_COLOR
+                };
+                (line.to_string(), color)
+            })
+            .collect();
+        Self::text_tooltip_commands(&lines, assets, mouse_pos)
+    }
+
+    /// Returns the draw commands for a tooltip at `mouse_pos` with the given lines and colors.
+    fn text_tooltip_commands(
+        lines: &[(String, Vec4)],
+        assets: &Assets,
+        mouse_pos: Vec2,
+    ) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        let origin = mouse_pos + TOOLTIP_OFFSET;
+        let mut cursor = origin + Vec2::splat(TOOLTIP_PADDING);
+        let mut width = 0.0f32;
+        for (text, color) in lines {
+            let params = TextParams {
+                color: *color,
+                ..TextParams::default()
+            };
+            let size = assets.font.measure_text(text, params.without_color());
+            commands.extend(
+                assets
+                    .font
+                    .text(text, params)
+                    .into_iter()
+                    .map(|cmd| moved(cmd, cursor, None)),
+            );
+            width = width.max(size.x);
+            cursor.y += size.y + TOOLTIP_LINE_SPACING;
+        }
+
+        let max = Vec2::new(
+            origin.x + width + TOOLTIP_PADDING * 2.0,
+            cursor.y - TOOLTIP_LINE_SPACING + TOOLTIP_PADDING,
+        );
+        commands.push(DrawCommand::Quad {
+            rect: [origin, max],
+            uv_rect: [Vec2::ZERO, Vec2::ONE],
+            mode: UIRenderMode::Color(TOOLTIP_BACKGROUND),
+            layer: TOOLTIP_LAYER,
+        });
+        commands
+    }
+}
+
+/// Moves a draw command by `offset`, putting quads on another layer if given.
+fn moved(mut cmd: DrawCommand, offset: Vec2, new_layer: Option<i32>) -> DrawCommand {
+    match &mut cmd {
+        DrawCommand::Quad { rect, layer, .. } => {
+            rect[0] += offset;
+            rect[1] += offset;
+            if let Some(new_layer) = new_layer {
+                *layer = new_layer;
+            }
+        }
+        DrawCommand::Mesh { vertices, .. } => {
+            for vertex in vertices {
+                vertex.position += offset.extend(0.0);
+            }
+        }
+    }
+    cmd
+}
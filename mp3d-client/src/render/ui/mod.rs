@@ -49,5 +49,8 @@ impl Vertex for UIVertex {
 }
 
 pub mod font;
+pub mod hud;
+pub mod itemrenderer;
+pub mod theme;
 pub mod uirenderer;
 pub mod widgets;
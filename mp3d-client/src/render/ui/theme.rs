@@ -0,0 +1,124 @@
+//! Accessibility related UI theming.
+//!
+//! This module contains the [`Theme`] used by widgets to pick contrast-dependent colors, the
+//! [`TextPalette`] which remaps the basic [`TextComponentColor`] codes to user chosen colors and
+//! the available [`CrosshairStyle`]s.
+
+use std::collections::HashMap;
+
+use glam::Vec4;
+use mp3d_core::textcomponent::TextComponentColor;
+use serde::{Deserialize, Serialize};
+
+use crate::scenes::options::ClientConfig;
+
+/// The available crosshair styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CrosshairStyle {
+    /// A plain cross.
+    #[default]
+    Cross,
+    /// A cross with a dark outline, visible on both bright and dark backgrounds.
+    OutlinedCross,
+    /// A single small square in the center of the screen.
+    Dot,
+}
+
+impl CrosshairStyle {
+    /// All crosshair styles, in the order they are cycled through in the options.
+    pub const ALL: [CrosshairStyle; 3] = [
+        CrosshairStyle::Cross,
+        CrosshairStyle::OutlinedCross,
+        CrosshairStyle::Dot,
+    ];
+
+    /// Returns the next style in [`CrosshairStyle::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the style.
+    pub fn name(self) -> &'static str {
+        match self {
+            CrosshairStyle::Cross => "Cross",
+            CrosshairStyle::OutlinedCross => "Outlined Cross",
+            CrosshairStyle::Dot => "Dot",
+        }
+    }
+}
+
+/// Preset crosshair colors that can be cycled through in the options. Any other RGBA color can be
+/// set directly in the config file.
+pub const CROSSHAIR_COLORS: [(&str, Vec4); 6] = [
+    ("White", Vec4::new(1.0, 1.0, 1.0, 0.8)),
+    ("Yellow", Vec4::new(1.0, 0.9, 0.0, 0.9)),
+    ("Cyan", Vec4::new(0.0, 1.0, 1.0, 0.9)),
+    ("Magenta", Vec4::new(1.0, 0.0, 1.0, 0.9)),
+    ("Green", Vec4::new(0.0, 1.0, 0.0, 0.9)),
+    ("Black", Vec4::new(0.0, 0.0, 0.0, 0.9)),
+];
+
+/// Remaps the basic 2-bit [`TextComponentColor`] codes to user chosen colors.
+///
+/// Overrides are keyed by the color code with its alpha bits cleared (`code & 0xFC`), so a single
+/// override applies to every transparency level of the same color. The alpha of the original code
+/// is kept.
+#[derive(Debug, Clone, Default)]
+pub struct TextPalette {
+    overrides: HashMap<u8, Vec4>,
+}
+
+impl TextPalette {
+    /// Creates a new palette from the given overrides of RGB values.
+    pub fn new(overrides: &HashMap<u8, [f32; 3]>) -> Self {
+        Self {
+            overrides: overrides
+                .iter()
+                .map(|(code, rgb)| (code & 0xFC, Vec4::new(rgb[0], rgb[1], rgb[2], 1.0)))
+                .collect(),
+        }
+    }
+
+    /// Resolves the given color into an RGBA value, taking the overrides into account.
+    pub fn resolve(&self, color: TextComponentColor) -> Vec4 {
+        let resolved: Vec4 = color.into();
+        match color {
+            TextComponentColor::Basic(code) => match self.overrides.get(&(code & 0xFC)) {
+                Some(rgb) => rgb.truncate().extend(resolved.w),
+                None => resolved,
+            },
+            _ => resolved,
+        }
+    }
+}
+
+/// UI theme settings derived from the client config.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Theme {
+    /// Whether the high contrast theme is enabled.
+    pub high_contrast: bool,
+}
+
+impl Theme {
+    /// Creates the theme from the accessibility settings in the config.
+    pub fn from_config(config: &ClientConfig) -> Self {
+        Self {
+            high_contrast: config.high_contrast(),
+        }
+    }
+
+    /// The color of translucent backdrops drawn behind text, such as the chat.
+    pub fn backdrop(&self) -> Vec4 {
+        if self.high_contrast {
+            Vec4::new(0.0, 0.0, 0.0, 0.85)
+        } else {
+            Vec4::new(0.0, 0.0, 0.0, 0.5)
+        }
+    }
+
+    /// The color of text shadows, or `None` if text should be drawn without a shadow.
+    pub fn text_shadow(&self) -> Option<Vec4> {
+        self.high_contrast.then_some(Vec4::new(0.0, 0.0, 0.0, 1.0))
+    }
+}
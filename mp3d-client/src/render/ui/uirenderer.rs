@@ -41,6 +41,9 @@ pub struct UIRenderer {
     last_command: Option<DrawCommand>,
     vertices: Vec<UIVertex>,
     indices: Vec<u32>,
+    /// The mesh every batch is uploaded into, created with the first batch and reused afterwards
+    /// instead of creating new GPU buffers for each batch.
+    mesh: Option<Mesh>,
     pub scissor_rect: Option<[Vec2; 2]>,
 }
 
@@ -58,6 +61,7 @@ impl UIRenderer {
             last_command: None,
             vertices: Vec::new(),
             indices: Vec::new(),
+            mesh: None,
             scissor_rect: None,
         }
     }
@@ -103,7 +107,17 @@ impl UIRenderer {
             return;
         }
 
-        let mesh = Mesh::new(&self.gl, &self.vertices, &self.indices, glow::TRIANGLES);
+        match &mut self.mesh {
+            Some(mesh) => mesh.update(&self.vertices, &self.indices),
+            None => {
+                self.mesh = Some(Mesh::new(
+                    &self.gl,
+                    &self.vertices,
+                    &self.indices,
+                    glow::TRIANGLES,
+                ))
+            }
+        }
 
         self.shader_program.use_program();
 
@@ -164,7 +178,9 @@ impl UIRenderer {
             }
         }
 
-        mesh.draw();
+        if let Some(mesh) = &self.mesh {
+            mesh.draw();
+        }
     }
 
     /// Appends a draw command's vertices and indices to the current batch.
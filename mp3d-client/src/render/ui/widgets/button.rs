@@ -161,7 +161,7 @@ impl Widget for Button {
         self.size
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         self.is_down_last = self.is_down;
         self.hover_last = self.hovered;
         let mouse_pos = ctx.mouse.position;
@@ -196,4 +196,16 @@ impl Widget for Button {
     ) {
         self.stack.draw(ui_renderer, assets);
     }
+
+    fn semantic_label(&self) -> Option<String> {
+        Some(if self.disabled {
+            format!("{}, button, unavailable", self.text)
+        } else {
+            format!("{}, button", self.text)
+        })
+    }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.hovered.then_some(self as &dyn Widget)
+    }
 }
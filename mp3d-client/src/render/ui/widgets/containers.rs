@@ -251,7 +251,7 @@ impl Widget for Column {
         )
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         if let Some(viewport_height) = self.viewport_height {
             self.scroll_vel -= ctx.mouse.scroll_delta.y * 280.0;
             self.scroll_offset += self.scroll_vel * ctx.delta_time;
@@ -282,6 +282,10 @@ impl Widget for Column {
             widget.draw(ui_renderer, assets);
         }
     }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.widgets.iter().find_map(|widget| widget.focused())
+    }
 }
 
 /// A horizontal row container that arranges its child widgets horizontally.
@@ -439,7 +443,7 @@ impl Widget for Row {
         Vec2::new(width, height).max(self.min_size)
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         for widget in &mut self.widgets {
             widget.update(ctx);
         }
@@ -507,6 +511,10 @@ impl Widget for Row {
             widget.draw(ui_renderer, assets);
         }
     }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.widgets.iter().find_map(|widget| widget.focused())
+    }
 }
 
 /// A stack container that overlays its child widgets on top of each other.
@@ -643,7 +651,7 @@ impl Widget for Stack {
         Vec2::new(width, height)
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         for widget in &mut self.widgets {
             widget.update(ctx);
         }
@@ -689,6 +697,10 @@ impl Widget for Stack {
             widget.draw(ui_renderer, assets);
         }
     }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.widgets.iter().find_map(|widget| widget.focused())
+    }
 }
 
 /// Aranges the child widgets in a grid layout with specified number of columns, spacing, alignment
@@ -836,7 +848,7 @@ impl Widget for Grid {
         )
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         for widget in &mut self.widgets {
             widget.update(ctx);
         }
@@ -907,4 +919,8 @@ impl Widget for Grid {
             widget.draw(ui_renderer, assets);
         }
     }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.widgets.iter().find_map(|widget| widget.focused())
+    }
 }
@@ -0,0 +1,178 @@
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+use glam::{UVec2, UVec4, Vec2, Vec4};
+use sdl2::{keyboard::Keycode, mouse::MouseButton};
+
+use crate::{
+    client::player::{ClientContainer, DOUBLE_CLICK_TIME, Spreading},
+    render::ui::{
+        itemrenderer::ItemRenderer,
+        widgets::{INVENTORY_ITEMS, INVENTORY_SLOT_SIZE, NineSlice, Widget},
+    },
+};
+
+/// A slot of an open container, or of the inventory below it. Items can be moved by clicking on
+/// two slots or by dragging them from one slot to another, and shift clicking moves the whole
+/// stack between the container and the inventory. Dragging over slots while holding items spreads
+/// them over the slots, and double clicking collects more items like the picked up ones.
+pub struct ContainerSlot {
+    position: Vec2,
+    nineslice: NineSlice,
+    container: Rc<RefCell<ClientContainer>>,
+    idx: usize,
+    /// The mouse position if it is over the slot, to show a tooltip there.
+    hovered: Option<Vec2>,
+}
+
+impl ContainerSlot {
+    pub fn new(container: &Rc<RefCell<ClientContainer>>, idx: usize) -> Self {
+        let nineslice = NineSlice::new(
+            [UVec2::new(16, 16), UVec2::new(16, 16)],
+            INVENTORY_SLOT_SIZE,
+            UVec4::splat(1),
+            4,
+            1,
+            Vec4::ONE,
+        );
+        Self {
+            position: Vec2::ZERO,
+            nineslice,
+            container: Rc::clone(container),
+            idx,
+            hovered: None,
+        }
+    }
+}
+
+impl Widget for ContainerSlot {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn size_hint(&self, _ctx: &super::LayoutContext) -> Vec2 {
+        INVENTORY_SLOT_SIZE
+    }
+
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
+        let button = |right| {
+            if right {
+                MouseButton::Right
+            } else {
+                MouseButton::Left
+            }
+        };
+        let mut container = self.container.borrow_mut();
+
+        // Spreading ends when its button is released, wherever the mouse is
+        if let Some(spreading) = &container.spreading
+            && ctx.mouse.released.contains(&button(spreading.right))
+        {
+            let Spreading { right, slots } = container.spreading.take().unwrap();
+            if let [index] = slots[..] {
+                container.click(index, right, false);
+            } else {
+                container.distribute(slots, right);
+            }
+        }
+
+        let mouse_pos = ctx.mouse.position;
+        let slot_pos = self.position;
+        let slot_size = INVENTORY_SLOT_SIZE;
+        if mouse_pos.x >= slot_pos.x
+            && mouse_pos.x <= slot_pos.x + slot_size.x
+            && mouse_pos.y >= slot_pos.y
+            && mouse_pos.y <= slot_pos.y + slot_size.y
+        {
+            let left = ctx.mouse.pressed.contains(&MouseButton::Left);
+            let right = ctx.mouse.pressed.contains(&MouseButton::Right);
+            let shift = ctx.keyboard.down.contains(&Keycode::LShift)
+                || ctx.keyboard.down.contains(&Keycode::RShift);
+            if let Some(spreading) = &mut container.spreading {
+                if ctx.mouse.down.contains(&button(spreading.right))
+                    && !spreading.slots.contains(&self.idx)
+                {
+                    spreading.slots.push(self.idx);
+                }
+            } else if (left || right) && shift {
+                container.click(self.idx, right, true);
+            } else if (left || right) && container.holding() {
+                let double_click = left
+                    && container.last_pickup.is_some_and(|(slot, time)| {
+                        slot == self.idx && time.elapsed() < DOUBLE_CLICK_TIME
+                    });
+                container.dragging = None;
+                if double_click {
+                    container.last_pickup = None;
+                    container.collect();
+                } else {
+                    container.spreading = Some(Spreading {
+                        right,
+                        slots: vec![self.idx],
+                    });
+                }
+            } else if left || right {
+                container.click(self.idx, right, false);
+                if left && container.holding() {
+                    container.dragging = Some(self.idx);
+                    container.last_pickup = Some((self.idx, Instant::now()));
+                }
+            } else if ctx.mouse.released.contains(&MouseButton::Left)
+                && let Some(origin) = container.dragging.take()
+                && origin != self.idx
+                && container.holding()
+            {
+                // Dropping items dragged from another slot
+                container.click(self.idx, false, false);
+            }
+            self.nineslice.tint = Vec4::new(1.1, 1.1, 1.1, 1.0);
+            self.hovered = Some(mouse_pos);
+        } else {
+            self.nineslice.tint = Vec4::ONE;
+            self.hovered = None;
+        }
+    }
+
+    fn layout(&mut self, ctx: &super::LayoutContext) -> Vec2 {
+        let measured_size = self.size_hint(ctx).min(ctx.max_size);
+        self.position = ctx.cursor;
+        let layout_ctx = super::LayoutContext {
+            max_size: measured_size,
+            cursor: self.position,
+            assets: ctx.assets,
+        };
+        self.nineslice.layout(&layout_ctx);
+        measured_size
+    }
+
+    fn draw(
+        &self,
+        ui_renderer: &mut crate::render::ui::uirenderer::UIRenderer,
+        assets: &crate::scenes::Assets,
+    ) {
+        self.nineslice.draw(ui_renderer, assets);
+
+        let container = self.container.borrow();
+        if let Some(item_stack) = container.slot(self.idx) {
+            let mut commands = INVENTORY_ITEMS.stack_commands(
+                item_stack,
+                assets,
+                self.position + INVENTORY_SLOT_SIZE / 2.0,
+                ui_renderer,
+            );
+            if let Some(mouse_pos) = self.hovered
+                && !container.holding()
+            {
+                commands.extend(ItemRenderer::tooltip_commands(
+                    item_stack, assets, mouse_pos,
+                ));
+            }
+            for cmd in commands {
+                ui_renderer.add_command(cmd);
+            }
+        }
+    }
+}
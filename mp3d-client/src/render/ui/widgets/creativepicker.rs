@@ -0,0 +1,353 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{UVec2, UVec4, Vec2, Vec4};
+use mp3d_core::{
+    block::{Material, block_registry},
+    item::{ItemDef, ItemId, ItemStack, item_registry, items},
+};
+
+use crate::{
+    client::player::ClientInventory,
+    render::ui::{
+        itemrenderer::ItemRenderer,
+        widgets::{
+            Alignment, Button, Column, Grid, INVENTORY_ITEMS, INVENTORY_SLOT_SIZE, InputField,
+            Label, NineSlice, Row, Widget,
+        },
+    },
+    scenes::Assets,
+};
+
+/// The number of rows of items shown at once, the others are reached by scrolling.
+const VISIBLE_ROWS: usize = 5;
+const COLUMNS: usize = 9;
+const SPACING: f32 = 8.0;
+const PADDING: f32 = 16.0;
+const TAB_HEIGHT: f32 = 48.0;
+
+/// The tabs of the creative picker, each showing a part of the items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemCategory {
+    All,
+    Building,
+    Nature,
+    Tools,
+}
+
+impl ItemCategory {
+    pub const ALL: [ItemCategory; 4] = [
+        ItemCategory::All,
+        ItemCategory::Building,
+        ItemCategory::Nature,
+        ItemCategory::Tools,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemCategory::All => "All",
+            ItemCategory::Building => "Building",
+            ItemCategory::Nature => "Nature",
+            ItemCategory::Tools => "Tools",
+        }
+    }
+
+    /// Returns whether an item is listed in this category. Blocks of soil and plants are nature,
+    /// the other blocks are for building.
+    pub fn includes(self, item_def: &ItemDef) -> bool {
+        let material = item_def
+            .assoc_block
+            .map(|block| block_registry().get(**block).unwrap().material);
+        match self {
+            ItemCategory::All => true,
+            ItemCategory::Building => {
+                item_def.tool.is_none()
+                    && material.is_some_and(|m| !matches!(m, Material::Soil | Material::Plant))
+            }
+            ItemCategory::Nature => {
+                item_def.tool.is_none()
+                    && material.is_some_and(|m| matches!(m, Material::Soil | Material::Plant))
+            }
+            ItemCategory::Tools => item_def.tool.is_some(),
+        }
+    }
+}
+
+/// A slot of the creative picker showing an item, which clicking puts into the selected hotbar
+/// slot as a full stack.
+struct PickerSlot {
+    position: Vec2,
+    nineslice: NineSlice,
+    inventory: Rc<RefCell<ClientInventory>>,
+    item: Option<ItemId>,
+    /// The mouse position if it is over the slot, to show a tooltip there.
+    hovered: Option<Vec2>,
+}
+
+impl PickerSlot {
+    fn new(inventory: &Rc<RefCell<ClientInventory>>) -> Self {
+        let nineslice = NineSlice::new(
+            [UVec2::new(16, 16), UVec2::new(16, 16)],
+            INVENTORY_SLOT_SIZE,
+            UVec4::splat(1),
+            4,
+            1,
+            Vec4::ONE,
+        );
+        Self {
+            position: Vec2::ZERO,
+            nineslice,
+            inventory: Rc::clone(inventory),
+            item: None,
+            hovered: None,
+        }
+    }
+}
+
+impl Widget for PickerSlot {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn size_hint(&self, _ctx: &super::LayoutContext) -> Vec2 {
+        INVENTORY_SLOT_SIZE
+    }
+
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
+        let mouse_pos = ctx.mouse.position;
+        let slot_pos = self.position;
+        let slot_size = INVENTORY_SLOT_SIZE;
+        if mouse_pos.x >= slot_pos.x
+            && mouse_pos.x <= slot_pos.x + slot_size.x
+            && mouse_pos.y >= slot_pos.y
+            && mouse_pos.y <= slot_pos.y + slot_size.y
+        {
+            if let Some(item) = self.item
+                && ctx.mouse.pressed.contains(&sdl2::mouse::MouseButton::Left)
+            {
+                self.inventory.borrow_mut().pick(item);
+            }
+            self.nineslice.tint = Vec4::new(1.1, 1.1, 1.1, 1.0);
+            self.hovered = Some(mouse_pos);
+        } else {
+            self.nineslice.tint = Vec4::ONE;
+            self.hovered = None;
+        }
+    }
+
+    fn layout(&mut self, ctx: &super::LayoutContext) -> Vec2 {
+        let measured_size = self.size_hint(ctx).min(ctx.max_size);
+        self.position = ctx.cursor;
+        let layout_ctx = super::LayoutContext {
+            max_size: measured_size,
+            cursor: self.position,
+            assets: ctx.assets,
+        };
+        self.nineslice.layout(&layout_ctx);
+        measured_size
+    }
+
+    fn draw(&self, ui_renderer: &mut crate::render::ui::uirenderer::UIRenderer, assets: &Assets) {
+        self.nineslice.draw(ui_renderer, assets);
+
+        if let Some(item) = self.item {
+            let stack = ItemStack::new(item, 1);
+            let mut commands = INVENTORY_ITEMS.stack_commands(
+                stack,
+                assets,
+                self.position + INVENTORY_SLOT_SIZE / 2.0,
+                ui_renderer,
+            );
+            if let Some(mouse_pos) = self.hovered {
+                commands.extend(ItemRenderer::tooltip_commands(stack, assets, mouse_pos));
+            }
+            for cmd in commands {
+                ui_renderer.add_command(cmd);
+            }
+        }
+    }
+}
+
+/// A screen listing every item, which can be searched by translated name and narrowed down with
+/// category tabs. Clicking an item puts a full stack of it into the selected hotbar slot.
+pub struct CreativePicker {
+    position: Vec2,
+    background: NineSlice,
+    /// The title, the category tabs and the search field.
+    header: Column,
+    grid: Grid,
+    category: ItemCategory,
+    query: String,
+    /// The items matching the category and the query, `None` until they are listed again.
+    matches: Option<Vec<ItemId>>,
+    /// The first row of matching items that is shown.
+    scroll: usize,
+}
+
+impl CreativePicker {
+    pub fn new(inventory: &Rc<RefCell<ClientInventory>>) -> Self {
+        let grid_width = COLUMNS as f32 * INVENTORY_SLOT_SIZE.x + (COLUMNS - 1) as f32 * SPACING;
+        let tab_count = ItemCategory::ALL.len() as f32;
+        let tab_width = (grid_width - (tab_count - 1.0) * SPACING) / tab_count;
+        let header = Column::new(SPACING)
+            .alignment(Alignment::Start)
+            .with(Label::new("Items").font_size(36.0))
+            .with(
+                Row::new(SPACING).with_many(ItemCategory::ALL.into_iter().map(|category| {
+                    Button::new(category.name()).size(Vec2::new(tab_width, TAB_HEIGHT))
+                })),
+            )
+            .with(InputField::new("Search...").size(Vec2::new(grid_width, TAB_HEIGHT)));
+        let grid = Grid::new(COLUMNS, SPACING, Alignment::Center, Vec4::ZERO)
+            .with_many((0..COLUMNS * VISIBLE_ROWS).map(|_| PickerSlot::new(inventory)));
+        let background = NineSlice::new(
+            [UVec2::new(0, 16), UVec2::new(16, 16)],
+            Vec2::ZERO,
+            UVec4::new(4, 4, 3, 3),
+            4,
+            0,
+            Vec4::ONE,
+        );
+        Self {
+            position: Vec2::ZERO,
+            background,
+            header,
+            grid,
+            category: ItemCategory::All,
+            query: String::new(),
+            matches: None,
+            scroll: 0,
+        }
+    }
+
+    /// Lists the items in the current category whose translated name or identifier contains the
+    /// query, ignoring case.
+    fn find_matches(&self, assets: &Assets) -> Vec<ItemId> {
+        let query = self.query.trim().to_lowercase();
+        item_registry()
+            .iter_enumerate()
+            .filter(|&(item, item_def)| {
+                item != *items::AIR
+                    && self.category.includes(item_def)
+                    && (assets
+                        .translations
+                        .item_name(item_def.ident)
+                        .to_lowercase()
+                        .contains(&query)
+                        || item_def.ident.contains(&query))
+            })
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    /// Shows the matching items from the scrolled to row in the slots of the grid.
+    fn fill_slots(&mut self) {
+        let matches = self.matches.as_deref().unwrap_or_default();
+        for i in 0..COLUMNS * VISIBLE_ROWS {
+            if let Some(slot) = self.grid.get_widget_mut::<PickerSlot>(i) {
+                slot.item = matches.get(self.scroll * COLUMNS + i).copied();
+            }
+        }
+    }
+}
+
+impl Widget for CreativePicker {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn size_hint(&self, ctx: &super::LayoutContext) -> Vec2 {
+        let header = self.header.size_hint(ctx);
+        let grid = self.grid.size_hint(ctx);
+        Vec2::new(
+            header.x.max(grid.x) + PADDING * 2.0,
+            header.y + SPACING + grid.y + PADDING * 2.0,
+        )
+    }
+
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
+        self.header.update(ctx);
+        self.grid.update(ctx);
+
+        if let Some(tabs) = self.header.get_widget_mut::<Row>(1) {
+            for (i, category) in ItemCategory::ALL.into_iter().enumerate() {
+                if tabs
+                    .get_widget::<Button>(i)
+                    .is_some_and(|tab| tab.is_released())
+                    && self.category != category
+                {
+                    self.category = category;
+                    self.matches = None;
+                    self.scroll = 0;
+                }
+            }
+            for (i, category) in ItemCategory::ALL.into_iter().enumerate() {
+                if let Some(tab) = tabs.get_widget_mut::<Button>(i) {
+                    tab.always_hovered = self.category == category;
+                }
+            }
+        }
+        if let Some(search) = self.header.get_widget::<InputField>(2)
+            && search.text != self.query
+        {
+            self.query = search.text.clone();
+            self.matches = None;
+            self.scroll = 0;
+        }
+
+        let scroll = ctx.mouse.scroll_delta.y;
+        if scroll != 0.0
+            && let Some(matches) = &self.matches
+        {
+            let rows = matches.len().div_ceil(COLUMNS);
+            self.scroll = self
+                .scroll
+                .saturating_add_signed(-scroll.signum() as isize)
+                .min(rows.saturating_sub(VISIBLE_ROWS));
+            self.fill_slots();
+        }
+    }
+
+    fn layout(&mut self, ctx: &super::LayoutContext) -> Vec2 {
+        // Searching needs the translations, which only layout has access to
+        if self.matches.is_none() {
+            self.matches = Some(self.find_matches(ctx.assets));
+            self.fill_slots();
+        }
+
+        let size = self.size_hint(ctx);
+        self.position = ctx.cursor;
+        self.background.size = size;
+        self.background.layout(&super::LayoutContext {
+            max_size: size,
+            cursor: self.position,
+            assets: ctx.assets,
+        });
+        let header_size = self.header.size_hint(ctx);
+        let header_pos = self.position + Vec2::splat(PADDING);
+        self.header.layout(&super::LayoutContext {
+            max_size: header_size,
+            cursor: header_pos,
+            assets: ctx.assets,
+        });
+        self.grid.layout(&super::LayoutContext {
+            max_size: self.grid.size_hint(ctx),
+            cursor: header_pos + Vec2::new(0.0, header_size.y + SPACING),
+            assets: ctx.assets,
+        });
+        size
+    }
+
+    fn draw(&self, ui_renderer: &mut crate::render::ui::uirenderer::UIRenderer, assets: &Assets) {
+        self.background.draw(ui_renderer, assets);
+        self.header.draw(ui_renderer, assets);
+        self.grid.draw(ui_renderer, assets);
+    }
+}
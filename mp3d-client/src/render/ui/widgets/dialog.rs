@@ -81,7 +81,7 @@ impl Widget for Dialog {
         Vec2::new(self.width, size.y + 16.0 + 32.0)
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         self.stack.update(ctx);
     }
 
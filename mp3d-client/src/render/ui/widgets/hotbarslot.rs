@@ -1,12 +1,11 @@
 use std::{cell::RefCell, rc::Rc};
 
-use glam::{Mat4, UVec2, UVec4, Vec2, Vec4};
-use mp3d_core::{block::block_registry, item::*};
+use glam::{UVec2, UVec4, Vec2, Vec4};
 
 use crate::{
     client::player::ClientInventory,
     render::ui::{
-        font::{ColorlessTextParams, Font, TextParams},
+        itemrenderer::ItemRenderer,
         uirenderer::DrawCommand,
         widgets::{NineSlice, Widget},
     },
@@ -16,6 +15,8 @@ pub const HOTBAR_SLOT_SIZE: Vec2 = Vec2::new(72.0, 72.0);
 pub const ITEM_RENDER_SIZE: Vec2 = Vec2::new(64.0, 64.0);
 pub const ITEM_ELEVATION: f32 = 12.0;
 
+const HOTBAR_ITEMS: ItemRenderer = ItemRenderer::new(HOTBAR_SLOT_SIZE, ITEM_RENDER_SIZE);
+
 pub struct HotbarSlot {
     position: Vec2,
     nineslice: NineSlice,
@@ -40,64 +41,6 @@ impl HotbarSlot {
             idx,
         }
     }
-
-    pub fn draw_stack(
-        stack: ItemStack,
-        assets: &crate::scenes::Assets,
-        position: Vec2,
-        ui: &super::UIRenderer,
-        font: &Font,
-    ) -> Vec<DrawCommand> {
-        let mut commands = Vec::new();
-        let item = stack.item;
-        if let Some(block) = item_registry().get(item).unwrap().assoc_block {
-            let block = **block;
-            let block_def = block_registry().get(block).unwrap();
-            if block_def.visible {
-                let item_block_state =
-                    mp3d_core::block::BlockState::default_state(block_def.state_type).unwrap();
-                let item_block_model = assets
-                    .block_models
-                    .get(&(block, item_block_state.data()))
-                    .unwrap();
-                commands.extend(item_block_model.draw_commands(
-                    &ui.gl,
-                    &assets.block_textures,
-                    position,
-                    ITEM_RENDER_SIZE / 1.75,
-                    Mat4::from_rotation_x(30f32.to_radians())
-                        * Mat4::from_rotation_y(-std::f32::consts::FRAC_PI_4),
-                ));
-            }
-        } else {
-            todo!("Implement item rendering for non-block items");
-        }
-        // Draw the item count if greater than 1
-        if stack.count > 1 {
-            let bottom_right = position + HOTBAR_SLOT_SIZE / 2.0;
-            let count_text = stack.count.to_string();
-            let text_position = bottom_right
-                - font.measure_text(&count_text, ColorlessTextParams::default())
-                - Vec2::new(4.0, 4.0);
-            let text_commands = font
-                .text(&count_text, TextParams::default())
-                .into_iter()
-                .map(|mut cmd| {
-                    if let DrawCommand::Quad { rect, .. } = &mut cmd {
-                        rect[0] += text_position;
-                        rect[1] += text_position;
-                    } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
-                        for vertex in vertices {
-                            vertex.position += text_position.extend(0.0);
-                        }
-                    }
-                    cmd
-                })
-                .collect::<Vec<_>>();
-            commands.extend(text_commands);
-        }
-        commands
-    }
 }
 
 impl Widget for HotbarSlot {
@@ -113,7 +56,7 @@ impl Widget for HotbarSlot {
         HOTBAR_SLOT_SIZE
     }
 
-    fn update(&mut self, _ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, _ctx: &crate::input::UpdateContext) {
         let current_stack_idx = self.inventory.borrow().slot;
         if self.idx == current_stack_idx + 9 * 3 {
             self.nineslice.tint = Vec4::new(1.2, 1.2, 1.2, 1.0);
@@ -145,13 +88,12 @@ impl Widget for HotbarSlot {
         self.nineslice.draw(ui_renderer, assets);
 
         let inventory = self.inventory.borrow();
-        if let Some(item_stack) = inventory.inner.main.get(self.idx) {
-            let commands = Self::draw_stack(
+        if let Some(item_stack) = inventory.inner.slot(self.idx) {
+            let commands = HOTBAR_ITEMS.stack_commands(
                 *item_stack,
                 assets,
                 self.position + HOTBAR_SLOT_SIZE / 2.0,
                 ui_renderer,
-                &assets.font,
             );
             for mut cmd in commands {
                 match &mut cmd {
@@ -16,6 +16,8 @@ pub struct InputField {
     pub cursor_pos: usize,
     pub placeholder: String,
     pub sanitize: Option<String>,
+    /// Whether the text is shown as asterisks, for passwords.
+    pub masked: bool,
     hovered: bool,
     hover_last: bool,
     focused: bool,
@@ -34,6 +36,7 @@ impl InputField {
             cursor_pos: 0,
             placeholder: placeholder.to_string(),
             sanitize: None,
+            masked: false,
             hovered: false,
             hover_last: false,
             focused: false,
@@ -65,6 +68,20 @@ impl InputField {
         self
     }
 
+    pub fn masked(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+
+    /// Returns the text as shown, which is hidden behind asterisks if the field is masked.
+    fn shown(&self, text: &str) -> String {
+        if self.masked {
+            "*".repeat(text.chars().count())
+        } else {
+            text.to_string()
+        }
+    }
+
     pub fn text(mut self, text: &str) -> Self {
         self.text = text.to_string();
         self.cursor_pos = text.len();
@@ -73,9 +90,9 @@ impl InputField {
 
     fn setup_stack(&mut self) {
         let text = if self.text.is_empty() && !self.focused {
-            &self.placeholder
+            self.placeholder.clone()
         } else {
-            &self.text
+            self.shown(&self.text)
         };
         self.stack = Stack::new(super::Alignment::Start, super::Alignment::Center, 0.0)
             .with(NineSlice::new(
@@ -109,12 +126,13 @@ impl InputField {
         } else {
             self.setup_stack();
         }
+        let shown = self.shown(&self.text);
         if let Some(label) = self.stack.get_widget_mut::<Label>(1) {
             if self.text.is_empty() && !self.focused {
                 label.text = format!("  {}", self.placeholder);
                 label.color = self.color * Vec4::new(1.0, 1.0, 1.0, 0.5);
             } else {
-                label.text = format!("  {}", self.text);
+                label.text = format!("  {}", shown);
                 label.color = self.color;
             }
             label.font_size = self.font_size;
@@ -145,7 +163,7 @@ impl Widget for InputField {
         self.size
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         self.hover_last = self.hovered;
         let mouse_pos = ctx.mouse.position;
         let mouse_pressed = ctx.mouse.down.contains(&sdl2::mouse::MouseButton::Left);
@@ -219,7 +237,7 @@ impl Widget for InputField {
                 + assets
                     .font
                     .measure_text(
-                        &format!("  {}", &self.text[..self.cursor_pos]),
+                        &format!("  {}", self.shown(&self.text[..self.cursor_pos])),
                         ColorlessTextParams {
                             font_size: self.font_size,
                             ..Default::default()
@@ -238,4 +256,17 @@ impl Widget for InputField {
             });
         }
     }
+
+    // The text isn't part of the label, so typing doesn't read out the whole field again
+    fn semantic_label(&self) -> Option<String> {
+        Some(if self.masked {
+            format!("{}, password field", self.placeholder)
+        } else {
+            format!("{}, text field", self.placeholder)
+        })
+    }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        self.focused.then_some(self as &dyn Widget)
+    }
 }
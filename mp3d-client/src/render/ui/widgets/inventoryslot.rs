@@ -1,24 +1,28 @@
 use std::{cell::RefCell, rc::Rc};
 
-use glam::{Mat4, UVec2, UVec4, Vec2, Vec4};
-use mp3d_core::{block::block_registry, item::*};
+use glam::{UVec2, UVec4, Vec2, Vec4};
 
 use crate::{
     client::player::ClientInventory,
     render::ui::{
-        font::{ColorlessTextParams, Font, TextParams},
-        uirenderer::DrawCommand,
+        itemrenderer::ItemRenderer,
         widgets::{NineSlice, Widget},
     },
 };
 
 pub const INVENTORY_SLOT_SIZE: Vec2 = Vec2::new(64.0, 64.0);
 
+/// Draws the items in inventory slots, and the items held by the cursor.
+pub const INVENTORY_ITEMS: ItemRenderer =
+    ItemRenderer::new(INVENTORY_SLOT_SIZE, INVENTORY_SLOT_SIZE);
+
 pub struct InventorySlot {
     position: Vec2,
     nineslice: NineSlice,
     inventory: Rc<RefCell<ClientInventory>>,
     idx: usize,
+    /// The mouse position if it is over the slot, to show a tooltip there.
+    hovered: Option<Vec2>,
 }
 
 impl InventorySlot {
@@ -36,66 +40,9 @@ impl InventorySlot {
             nineslice,
             inventory: Rc::clone(inventory),
             idx,
+            hovered: None,
         }
     }
-
-    pub fn draw_stack(
-        stack: ItemStack,
-        assets: &crate::scenes::Assets,
-        position: Vec2,
-        ui: &super::UIRenderer,
-        font: &Font,
-    ) -> Vec<DrawCommand> {
-        let mut commands = Vec::new();
-        let item = stack.item;
-        if let Some(block) = item_registry().get(item).unwrap().assoc_block {
-            let block = **block;
-            let block_def = block_registry().get(block).unwrap();
-            if block_def.visible {
-                let item_block_state =
-                    mp3d_core::block::BlockState::default_state(block_def.state_type).unwrap();
-                let item_block_model = assets
-                    .block_models
-                    .get(&(block, item_block_state.data()))
-                    .unwrap();
-                commands.extend(item_block_model.draw_commands(
-                    &ui.gl,
-                    &assets.block_textures,
-                    position,
-                    INVENTORY_SLOT_SIZE / 1.75,
-                    Mat4::from_rotation_x(30f32.to_radians())
-                        * Mat4::from_rotation_y(-std::f32::consts::FRAC_PI_4),
-                ));
-            }
-        } else {
-            todo!("Implement item rendering for non-block items");
-        }
-        // Draw the item count if greater than 1
-        if stack.count > 1 {
-            let bottom_right = position + INVENTORY_SLOT_SIZE / 2.0;
-            let count_text = stack.count.to_string();
-            let text_position = bottom_right
-                - font.measure_text(&count_text, ColorlessTextParams::default())
-                - Vec2::new(4.0, 4.0);
-            let text_commands = font
-                .text(&count_text, TextParams::default())
-                .into_iter()
-                .map(|mut cmd| {
-                    if let DrawCommand::Quad { rect, .. } = &mut cmd {
-                        rect[0] += text_position;
-                        rect[1] += text_position;
-                    } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
-                        for vertex in vertices {
-                            vertex.position += text_position.extend(0.0);
-                        }
-                    }
-                    cmd
-                })
-                .collect::<Vec<_>>();
-            commands.extend(text_commands);
-        }
-        commands
-    }
 }
 
 impl Widget for InventorySlot {
@@ -111,7 +58,7 @@ impl Widget for InventorySlot {
         INVENTORY_SLOT_SIZE
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         let right = ctx.mouse.pressed.contains(&sdl2::mouse::MouseButton::Right);
         let clicked = right || ctx.mouse.pressed.contains(&sdl2::mouse::MouseButton::Left);
         let mouse_pos = ctx.mouse.position;
@@ -127,8 +74,10 @@ impl Widget for InventorySlot {
                 inventory.click(self.idx, right);
             }
             self.nineslice.tint = Vec4::new(1.1, 1.1, 1.1, 1.0);
+            self.hovered = Some(mouse_pos);
         } else {
             self.nineslice.tint = Vec4::ONE;
+            self.hovered = None;
         }
     }
 
@@ -155,14 +104,22 @@ impl Widget for InventorySlot {
         self.nineslice.draw(ui_renderer, assets);
 
         let inventory = self.inventory.borrow();
-        if let Some(item_stack) = inventory.inner.main.get(self.idx) {
-            let commands = Self::draw_stack(
+        if let Some(item_stack) = inventory.inner.slot(self.idx) {
+            let mut commands = INVENTORY_ITEMS.stack_commands(
                 *item_stack,
                 assets,
                 self.position + INVENTORY_SLOT_SIZE / 2.0,
                 ui_renderer,
-                &assets.font,
             );
+            if let Some(mouse_pos) = self.hovered
+                && inventory.inner.temp.is_empty()
+            {
+                commands.extend(ItemRenderer::tooltip_commands(
+                    *item_stack,
+                    assets,
+                    mouse_pos,
+                ));
+            }
             for cmd in commands {
                 ui_renderer.add_command(cmd);
             }
@@ -60,7 +60,7 @@ impl Widget for Label {
         )
     }
 
-    fn update(&mut self, _ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, _ctx: &crate::input::UpdateContext) {
         // Labels are static; no update logic needed.
     }
 
@@ -78,33 +78,50 @@ impl Widget for Label {
         ui_renderer: &mut crate::render::ui::uirenderer::UIRenderer,
         assets: &crate::scenes::Assets,
     ) {
-        let commands = assets
-            .font
-            .text(
-                &self.text,
-                TextParams {
-                    font_size: self.font_size,
-                    color: self.color,
-                    word_wrap_width: self.wrap,
-                },
-            )
-            .into_iter()
-            .map(|mut cmd| {
-                if let DrawCommand::Quad { rect, .. } = &mut cmd {
-                    rect[0] += self.position;
-                    rect[1] += self.position;
-                } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
-                    for vertex in vertices {
-                        vertex.position += self.position.extend(0.0);
+        let text_commands = |color: Vec4, offset: Vec2, layer_offset: i32| {
+            assets
+                .font
+                .text(
+                    &self.text,
+                    TextParams {
+                        font_size: self.font_size,
+                        color,
+                        word_wrap_width: self.wrap,
+                    },
+                )
+                .into_iter()
+                .map(move |mut cmd| {
+                    let position = self.position + offset;
+                    if let DrawCommand::Quad { rect, layer, .. } = &mut cmd {
+                        rect[0] += position;
+                        rect[1] += position;
+                        *layer += layer_offset;
+                    } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
+                        for vertex in vertices {
+                            vertex.position += position.extend(0.0);
+                        }
                     }
-                }
-                cmd
-            });
+                    cmd
+                })
+        };
 
-        for command in commands {
+        if let Some(shadow) = assets.theme.text_shadow() {
+            let offset = Vec2::splat(self.font_size / 12.0);
+            // The shadow goes one layer below the text so it never covers it.
+            for command in text_commands(shadow.with_w(self.color.w), offset, -1) {
+                ui_renderer.add_command(command);
+            }
+            ui_renderer.finish();
+        }
+
+        for command in text_commands(self.color, Vec2::ZERO, 0) {
             ui_renderer.add_command(command);
         }
 
         ui_renderer.finish();
     }
+
+    fn semantic_label(&self) -> Option<String> {
+        Some(self.text.clone())
+    }
 }
@@ -22,31 +22,49 @@ pub trait Widget {
     }
 
     /// Updates the widget state.
-    fn update(&mut self, ctx: &crate::other::UpdateContext);
+    fn update(&mut self, ctx: &crate::input::UpdateContext);
 
     /// Updates the widget layout given the available space.
     fn layout(&mut self, ctx: &LayoutContext) -> Vec2;
 
     /// Draws the widget with the given UI renderer.
     fn draw(&self, ui_renderer: &mut UIRenderer, assets: &crate::scenes::Assets);
+
+    /// What the widget is and what it says, as read out by the narrator, like `"Done, button"`.
+    /// See [`crate::narrator`].
+    fn semantic_label(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the widget that has the focus among this one and its children, which is the one
+    /// the mouse is over or that is typed into.
+    fn focused(&self) -> Option<&dyn Widget> {
+        None
+    }
 }
 
 pub mod button;
 pub mod containers;
+pub mod containerslot;
+pub mod creativepicker;
 pub mod dialog;
 pub mod hotbarslot;
 pub mod inputfield;
 pub mod inventoryslot;
 pub mod label;
 pub mod nineslice;
+pub mod picture;
 pub mod slider;
 
 pub use button::*;
 pub use containers::*;
+pub use containerslot::*;
+pub use creativepicker::*;
 pub use dialog::*;
 pub use hotbarslot::*;
 pub use inputfield::*;
 pub use inventoryslot::*;
 pub use label::*;
 pub use nineslice::*;
+pub use picture::*;
 pub use slider::*;
@@ -49,7 +49,7 @@ impl Widget for NineSlice {
         self.size
     }
 
-    fn update(&mut self, _ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, _ctx: &crate::input::UpdateContext) {
         // NineSlice is static; no update logic needed.
     }
 
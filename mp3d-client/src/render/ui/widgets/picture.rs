@@ -0,0 +1,72 @@
+use glam::{Vec2, Vec4};
+
+use crate::{
+    abs::TextureHandle,
+    render::ui::{
+        uirenderer::{DrawCommand, UIRenderMode},
+        widgets::Widget,
+    },
+};
+
+/// Shows a whole texture stretched over its size. The texture is owned by whatever created the
+/// picture, which can swap it at any time; until there is one a flat color is shown instead.
+pub struct Picture {
+    position: Vec2,
+    pub size: Vec2,
+    pub texture: Option<TextureHandle>,
+    pub placeholder: Vec4,
+    pub layer: i32,
+}
+
+impl Picture {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            position: Vec2::ZERO,
+            size,
+            texture: None,
+            placeholder: Vec4::new(0.0, 0.0, 0.0, 0.4),
+            layer: 0,
+        }
+    }
+}
+
+impl Widget for Picture {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn size_hint(&self, _ctx: &super::LayoutContext) -> Vec2 {
+        self.size
+    }
+
+    fn update(&mut self, _ctx: &crate::input::UpdateContext) {
+        // Pictures are static; no update logic needed.
+    }
+
+    fn layout(&mut self, ctx: &super::LayoutContext) -> Vec2 {
+        self.position = ctx.cursor;
+        self.size.min(ctx.max_size)
+    }
+
+    fn draw(
+        &self,
+        ui_renderer: &mut crate::render::ui::uirenderer::UIRenderer,
+        _assets: &crate::scenes::Assets,
+    ) {
+        let mode = match self.texture {
+            Some(texture) => UIRenderMode::Texture(texture, Vec4::ONE),
+            None => UIRenderMode::Color(self.placeholder),
+        };
+        ui_renderer.add_command(DrawCommand::Quad {
+            rect: [self.position, self.position + self.size],
+            uv_rect: [Vec2::ZERO, Vec2::ONE],
+            mode,
+            layer: self.layer,
+        });
+        ui_renderer.finish();
+    }
+}
@@ -127,7 +127,7 @@ impl Widget for Slider {
         self.size
     }
 
-    fn update(&mut self, ctx: &crate::other::UpdateContext) {
+    fn update(&mut self, ctx: &crate::input::UpdateContext) {
         let mouse_pos = ctx.mouse.position;
         self.hovered = mouse_pos.x >= self.position.x
             && mouse_pos.x <= self.position.x + self.size.x
@@ -175,4 +175,12 @@ impl Widget for Slider {
         self.knob.draw(ui_renderer, assets);
         self.stack.draw(ui_renderer, assets);
     }
+
+    fn semantic_label(&self) -> Option<String> {
+        Some(format!("{}, slider", self.text()))
+    }
+
+    fn focused(&self) -> Option<&dyn Widget> {
+        (self.hovered || self.is_dragging).then_some(self as &dyn Widget)
+    }
 }
@@ -0,0 +1,87 @@
+//! Translations of the names and messages shown to the player.
+
+use std::collections::HashMap;
+
+use mp3d_core::{
+    protocol::DeathEvent,
+    textcomponent::{TextComponent, sanitize},
+};
+
+use crate::resource::Asset;
+
+/// The translated names for a locale, read from `lang/<locale>.json` as a map of translation keys
+/// like `item.stone` to names.
+#[derive(Clone, Debug, Default)]
+pub struct Translations {
+    entries: HashMap<String, String>,
+}
+
+impl Asset for Translations {
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let entries = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+        Ok(Self { entries })
+    }
+}
+
+impl Translations {
+    /// Adds the entries of other translations, replacing the ones with the same key.
+    pub fn extend(&mut self, other: Translations) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Gets the translation for the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Returns the name of the item with the given identifier. Items without a translation are
+    /// named after their identifier, so `stone_slab` becomes "Stone Slab".
+    pub fn item_name(&self, ident: &str) -> String {
+        if let Some(name) = self.get(&format!("item.{}", ident)) {
+            return name.to_string();
+        }
+        ident
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the hint shown next to the crosshair for a block's
+    /// [`interact_hint`](mp3d_core::block::BlockDef::interact_hint), like "Right click to open".
+    pub fn interact_hint(&self, hint: &str) -> TextComponent {
+        let text = match self.get(&format!("hint.{}", hint)) {
+            Some(text) => sanitize(text),
+            None => format!("Right click to {}", sanitize(hint)),
+        };
+        format!("%b7F{}%r", text).parse().unwrap()
+    }
+
+    /// Returns the chat message telling how a player died. Deaths without a translation show
+    /// their translation key.
+    pub fn death_message(&self, event: &DeathEvent) -> TextComponent {
+        let key = event.translation_key();
+        match self.get(&key) {
+            Some(template) => event.message(template),
+            None => event.message(&format!("{{victim}} ({})", key)),
+        }
+    }
+
+    /// Returns the short line shown in the kill feed for a death, like "Alice [Void] Bob".
+    pub fn kill_feed_entry(&self, event: &DeathEvent) -> TextComponent {
+        let cause = self
+            .get(&format!("killfeed.{}", event.cause.name()))
+            .unwrap_or(event.cause.name());
+        let template = match event.attacker {
+            Some(_) => format!("{{attacker}} %b7F[{}]%r {{victim}}", sanitize(cause)),
+            None => format!("%b7F[{}]%r {{victim}}", sanitize(cause)),
+        };
+        event.message(&template)
+    }
+}
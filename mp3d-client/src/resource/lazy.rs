@@ -0,0 +1,97 @@
+//! Assets that are loaded in the background instead of up front.
+//!
+//! A [`Lazy`] runs its loader on its own thread and can be polled every frame, so whatever uses
+//! it can draw a placeholder until the asset arrives. Assets that need the OpenGL context, like
+//! textures, are split into a part decoded in the background and a part created from it on the
+//! main thread once it is ready, see [`LazyTexture`].
+
+use std::{sync::Arc, thread::JoinHandle};
+
+use crate::abs::Texture;
+
+enum LazyState<T> {
+    Loading(JoinHandle<Result<T, String>>),
+    Ready(T),
+    Failed,
+}
+
+/// A value loaded on a background thread.
+pub struct Lazy<T> {
+    name: String,
+    state: LazyState<T>,
+}
+
+impl<T: Send + 'static> Lazy<T> {
+    /// Starts loading the value with `load` on a new thread. `name` is used in log messages.
+    pub fn load(name: &str, load: impl FnOnce() -> Result<T, String> + Send + 'static) -> Self {
+        log::info!("Loading {} in the background", name);
+        let handle = std::thread::Builder::new()
+            .name(format!("load-{}", name))
+            .spawn(load)
+            .expect("Failed to spawn asset loading thread");
+        Self {
+            name: name.to_string(),
+            state: LazyState::Loading(handle),
+        }
+    }
+
+    /// Returns the value if it finished loading. If loading failed, the error is logged once and
+    /// `None` is returned from then on, so the placeholder stays in use.
+    pub fn get(&mut self) -> Option<&T> {
+        if let LazyState::Loading(handle) = &self.state
+            && handle.is_finished()
+        {
+            let LazyState::Loading(handle) = std::mem::replace(&mut self.state, LazyState::Failed)
+            else {
+                unreachable!()
+            };
+            match handle.join() {
+                Ok(Ok(value)) => {
+                    log::info!("Finished loading {}", self.name);
+                    self.state = LazyState::Ready(value);
+                }
+                Ok(Err(e)) => log::error!("Failed to load {}: {}", self.name, e),
+                Err(_) => log::error!("Loading {} panicked", self.name),
+            }
+        }
+
+        match &self.state {
+            LazyState::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A texture whose image is decoded in the background and uploaded once it is ready.
+pub struct LazyTexture {
+    image: Lazy<image::RgbaImage>,
+    texture: Option<Texture>,
+    placeholder: Texture,
+}
+
+impl LazyTexture {
+    /// Starts decoding the image with `load`, using `placeholder` until it is uploaded.
+    pub fn load(
+        name: &str,
+        placeholder: Texture,
+        load: impl FnOnce() -> Result<image::RgbaImage, String> + Send + 'static,
+    ) -> Self {
+        Self {
+            image: Lazy::load(name, load),
+            texture: None,
+            placeholder,
+        }
+    }
+
+    /// Returns the texture, uploading it first if its image just finished decoding, or the
+    /// placeholder if it is not ready yet.
+    pub fn get(&mut self, gl: &Arc<glow::Context>) -> &Texture {
+        if self.texture.is_none()
+            && let Some(image) = self.image.get()
+        {
+            let (width, height) = image.dimensions();
+            self.texture = Some(Texture::new_bytes(gl, width, height, image.to_vec()));
+        }
+        self.texture.as_ref().unwrap_or(&self.placeholder)
+    }
+}
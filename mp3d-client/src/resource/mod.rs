@@ -4,10 +4,16 @@
 
 pub mod block;
 pub mod fontsettings;
+pub mod lang;
+pub mod lazy;
 pub mod pack;
 
+use std::marker::PhantomData;
+
 pub trait AssetSource: Send + Sync {
     fn read(&self, path: &std::path::Path) -> Option<Vec<u8>>;
+    /// A short description of where the assets come from, used in error messages.
+    fn name(&self) -> String;
 }
 
 pub struct EmbeddedAssetSource;
@@ -16,6 +22,10 @@ impl AssetSource for EmbeddedAssetSource {
     fn read(&self, path: &std::path::Path) -> Option<Vec<u8>> {
         crate::ASSETS.get_file(path).map(|f| f.contents().to_vec())
     }
+
+    fn name(&self) -> String {
+        "built-in assets".to_string()
+    }
 }
 
 pub struct FolderAssetSource {
@@ -27,8 +37,63 @@ impl AssetSource for FolderAssetSource {
         let full_path = self.root.join(path);
         std::fs::read(full_path).ok()
     }
+
+    fn name(&self) -> String {
+        format!(
+            "resource pack '{}'",
+            self.root
+                .file_name()
+                .unwrap_or(self.root.as_os_str())
+                .to_string_lossy()
+        )
+    }
+}
+
+/// A type that can be decoded from the contents of an asset file.
+pub trait Asset: Sized {
+    fn decode(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl Asset for image::DynamicImage {
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Asset for fontsettings::FontSettings {
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// A typed key of an asset with a fixed path, so the asset is always decoded as the same type.
+pub struct AssetKey<T: Asset> {
+    pub path: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Asset> AssetKey<T> {
+    pub const fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            _marker: PhantomData,
+        }
+    }
 }
 
+pub const FONT_TEXTURE: AssetKey<image::DynamicImage> = AssetKey::new("font.png");
+pub const FONT_SETTINGS: AssetKey<fontsettings::FontSettings> = AssetKey::new("font.json");
+pub const GUI_TEXTURE: AssetKey<image::DynamicImage> = AssetKey::new("gui.png");
+pub const WINDOW_ICON: AssetKey<image::DynamicImage> = AssetKey::new("window_icon.png");
+/// The English translations, which are used for anything the locale of the player doesn't
+/// translate.
+pub const ENGLISH: AssetKey<lang::Translations> = AssetKey::new("lang/en_US.json");
+pub const PLAYER_ANIMATIONS: AssetKey<crate::render::animation::AnimationSet> =
+    AssetKey::new("animations/player.json");
+pub const CRITTER_ANIMATIONS: AssetKey<crate::render::animation::AnimationSet> =
+    AssetKey::new("animations/critter.json");
+
 pub struct ResourceManager {
     // last has lower priority
     sources: Vec<Box<dyn AssetSource>>,
@@ -72,6 +137,29 @@ impl ResourceManager {
         None
     }
 
+    /// Like [`ResourceManager::read`], but the error names the sources that were searched.
+    pub fn try_read(&self, path: &std::path::Path) -> Result<Vec<u8>, String> {
+        self.read(path).ok_or_else(|| {
+            format!(
+                "'{}' was not found in any of: {}",
+                path.display(),
+                self.sources
+                    .iter()
+                    .rev()
+                    .map(|source| source.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+
+    /// Reads and decodes the asset with the given key.
+    pub fn load<T: Asset>(&self, key: &AssetKey<T>) -> Result<T, String> {
+        let path = std::path::Path::new(key.path);
+        let bytes = self.try_read(path)?;
+        T::decode(&bytes).map_err(|e| format!("Failed to decode '{}': {}", path.display(), e))
+    }
+
     pub fn read_utf8(&self, path: &std::path::Path) -> Option<String> {
         self.read(path)
             .and_then(|bytes| String::from_utf8(bytes).ok())
@@ -0,0 +1,232 @@
+//! The accessibility options scene implementation.
+
+use std::sync::{Arc, RwLock};
+
+use glam::Vec2;
+use glow::HasContext;
+
+use crate::{
+    render::ui::{theme::CROSSHAIR_COLORS, uirenderer::UIRenderer, widgets::*},
+    scenes::{Assets, SceneAction, SceneUpdateContext, options::ClientConfig},
+};
+
+/// The [`Accessibility`] struct represents the accessibility options scene.
+pub struct Accessibility {
+    container: Column,
+    initial_high_contrast: bool,
+}
+
+impl Accessibility {
+    /// Creates a new [`Accessibility`] instance.
+    pub fn new(
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        config: &Arc<RwLock<ClientConfig>>,
+    ) -> Self {
+        let config = config.read().unwrap();
+        let mut container = Column::new(40.0)
+            .justification(Justification::Center)
+            .with(Label::new("Accessibility").font_size(48.0))
+            .with(
+                Column::new(20.0)
+                    .with(Button::new(&crosshair_style_text(&config)))
+                    .with(Button::new(&crosshair_color_text(&config)))
+                    .with(Button::new(&high_contrast_text(&config)))
+                    .with(Button::new(&mouse_smoothing_text(&config)))
+                    .with(Button::new(&narrator_text(&config)))
+                    .with(Button::new("Done")),
+            );
+
+        container.layout(&LayoutContext {
+            max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        Self {
+            container,
+            initial_high_contrast: config.high_contrast(),
+        }
+    }
+}
+
+fn crosshair_style_text(config: &ClientConfig) -> String {
+    format!("Crosshair: {}", config.crosshair_style().name())
+}
+
+fn crosshair_color_text(config: &ClientConfig) -> String {
+    let color = config.crosshair_color();
+    let name = CROSSHAIR_COLORS
+        .iter()
+        .find(|(_, preset)| *preset == color)
+        .map(|(name, _)| *name)
+        .unwrap_or("Custom");
+    format!("Crosshair Color: {}", name)
+}
+
+fn high_contrast_text(config: &ClientConfig) -> String {
+    format!(
+        "High Contrast: {}",
+        if config.high_contrast() { "On" } else { "Off" }
+    )
+}
+
+fn mouse_smoothing_text(config: &ClientConfig) -> String {
+    format!(
+        "Mouse Smoothing: {}",
+        if config.mouse_smoothing() {
+            "On"
+        } else {
+            "Off"
+        }
+    )
+}
+
+fn narrator_text(config: &ClientConfig) -> String {
+    format!("Narrator: {}", if config.narrator() { "On" } else { "Off" })
+}
+
+impl super::Scene for Accessibility {
+    fn update(&mut self, ctx: &mut SceneUpdateContext) -> Vec<SceneAction> {
+        let SceneUpdateContext {
+            ctx,
+            window,
+            assets,
+            config,
+            ..
+        } = ctx;
+
+        crate::abs::update_title(window, "Mineplace3D - Accessibility");
+
+        self.container.update(ctx);
+        self.container.layout(&LayoutContext {
+            max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 0])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.crosshair_style = Some(config_guard.crosshair_style().next());
+            log::info!(
+                "Changed crosshair style: {:?}",
+                config_guard.crosshair_style()
+            );
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 1])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let current = config_guard.crosshair_color();
+            let idx = CROSSHAIR_COLORS
+                .iter()
+                .position(|(_, preset)| *preset == current)
+                .map(|i| (i + 1) % CROSSHAIR_COLORS.len())
+                .unwrap_or(0);
+            config_guard.crosshair_color = Some(CROSSHAIR_COLORS[idx].1.to_array());
+            log::info!("Changed crosshair color: {}", CROSSHAIR_COLORS[idx].0);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 2])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.high_contrast = Some(!config_guard.high_contrast());
+            log::info!("Toggled high contrast: {}", config_guard.high_contrast());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 3])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.mouse_smoothing = Some(!config_guard.mouse_smoothing());
+            log::info!(
+                "Toggled mouse smoothing: {}",
+                config_guard.mouse_smoothing()
+            );
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 4])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.narrator = Some(!config_guard.narrator());
+            log::info!("Toggled narrator: {}", config_guard.narrator());
+        }
+
+        {
+            let config_guard = config.read().unwrap();
+            self.container
+                .find_widget_mut::<Button>(&[1, 0])
+                .unwrap()
+                .text = crosshair_style_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 1])
+                .unwrap()
+                .text = crosshair_color_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 2])
+                .unwrap()
+                .text = high_contrast_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 3])
+                .unwrap()
+                .text = mouse_smoothing_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 4])
+                .unwrap()
+                .text = narrator_text(&config_guard);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 5])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let config_guard = config.read().unwrap();
+            config_guard.save();
+
+            log::info!("Saved config: {:?}", *config_guard);
+
+            // The theme is part of the assets, so they need to be reloaded for it to apply.
+            if config_guard.high_contrast() != self.initial_high_contrast {
+                return vec![SceneAction::ReloadAssets, SceneAction::Pop];
+            }
+            return vec![SceneAction::Pop];
+        }
+
+        Vec::new()
+    }
+
+    fn render(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        ui: &mut UIRenderer,
+        assets: &Arc<Assets>,
+        _config: &Arc<RwLock<ClientConfig>>,
+    ) {
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            self.container.draw(ui, assets);
+        }
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
+}
@@ -0,0 +1,165 @@
+//! The controls options scene implementation, where the keys of the actions are rebound.
+
+use std::sync::{Arc, RwLock};
+
+use glam::{Vec2, Vec4};
+use glow::HasContext;
+use sdl2::keyboard::Keycode;
+
+use crate::{
+    input::{Action, KeyBindings},
+    render::ui::{uirenderer::UIRenderer, widgets::*},
+    scenes::{Assets, SceneAction, SceneUpdateContext, options::ClientConfig},
+};
+
+const BINDING_SIZE: Vec2 = Vec2::new(400.0, 60.0);
+
+/// The [`Controls`] struct represents the controls options scene.
+pub struct Controls {
+    container: Column,
+    bindings: KeyBindings,
+    /// The action waiting for a key to be pressed, after its button was clicked.
+    rebinding: Option<Action>,
+}
+
+impl Controls {
+    /// Creates a new [`Controls`] instance.
+    pub fn new(
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        config: &Arc<RwLock<ClientConfig>>,
+    ) -> Self {
+        let bindings = config.read().unwrap().key_bindings();
+        let mut container = Column::new(40.0)
+            .justification(Justification::Center)
+            .with(Label::new("Controls").font_size(48.0))
+            .with(
+                Column::new(0.0)
+                    .viewport_height(window_size.1 as f32 - 300.0)
+                    .with(Grid::new(2, 15.0, Alignment::Center, Vec4::ZERO).with_many(
+                        Action::ALL.iter().map(|action| {
+                            Button::new(&binding_text(&bindings, *action, false)).size(BINDING_SIZE)
+                        }),
+                    )),
+            )
+            .with(
+                Row::new(20.0)
+                    .with(Button::new("Reset Keys").size(Vec2::new(240.0, 80.0)))
+                    .with(Button::new("Done").size(Vec2::new(240.0, 80.0))),
+            );
+
+        container.layout(&LayoutContext {
+            max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        Self {
+            container,
+            bindings,
+            rebinding: None,
+        }
+    }
+}
+
+fn binding_text(bindings: &KeyBindings, action: Action, rebinding: bool) -> String {
+    if rebinding {
+        format!("{}: > Press a key <", action.name())
+    } else {
+        format!("{}: {}", action.name(), bindings.key(action).name())
+    }
+}
+
+impl super::Scene for Controls {
+    fn update(&mut self, ctx: &mut SceneUpdateContext) -> Vec<SceneAction> {
+        let SceneUpdateContext {
+            ctx,
+            window,
+            assets,
+            config,
+            ..
+        } = ctx;
+
+        crate::abs::update_title(window, "Mineplace3D - Controls");
+
+        self.container.update(ctx);
+        self.container.layout(&LayoutContext {
+            max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        if let Some(action) = self.rebinding
+            && let Some(key) = ctx.keyboard.pressed.iter().next()
+        {
+            // Escape cancels, so it can't be bound and the pause menu always stays reachable
+            if *key != Keycode::Escape {
+                self.bindings.bind(action, *key);
+                log::info!("Bound {:?} to {}", action, key.name());
+            }
+            self.rebinding = None;
+        }
+
+        for (i, action) in Action::ALL.iter().enumerate() {
+            if self
+                .container
+                .find_widget::<Button>(&[1, 0, i])
+                .is_some_and(|btn| btn.is_released())
+            {
+                self.rebinding = Some(*action);
+            }
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[2, 0])
+            .is_some_and(|btn| btn.is_released())
+        {
+            self.bindings = KeyBindings::default();
+            self.rebinding = None;
+            log::info!("Reset the key bindings");
+        }
+
+        for (i, action) in Action::ALL.iter().enumerate() {
+            self.container
+                .find_widget_mut::<Button>(&[1, 0, i])
+                .unwrap()
+                .text = binding_text(&self.bindings, *action, self.rebinding == Some(*action));
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[2, 1])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.key_bindings = Some(self.bindings.to_names());
+            config_guard.save();
+
+            log::info!("Saved config: {:?}", *config_guard);
+
+            return vec![SceneAction::Pop];
+        }
+
+        Vec::new()
+    }
+
+    fn render(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        ui: &mut UIRenderer,
+        assets: &Arc<Assets>,
+        _config: &Arc<RwLock<ClientConfig>>,
+    ) {
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            self.container.draw(ui, assets);
+        }
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
+}
@@ -10,16 +10,30 @@ use std::{
 
 use glow::HasContext;
 use image::GenericImageView;
-use mp3d_core::block::{BlockId, BlockState, block_registry};
+use mp3d_core::{
+    block::{BlockId, BlockState, block_registry},
+    item::item_registry,
+    registry::ident_path,
+};
 
 use crate::{
+    input::Action,
+    narrator::Narrator,
     render::{
+        animation::AnimationSet,
         dialog::draw_dialog,
-        ui::{font::Font, uirenderer::UIRenderer},
+        ui::{
+            font::Font,
+            theme::{TextPalette, Theme},
+            uirenderer::UIRenderer,
+            widgets::Widget,
+        },
     },
     resource::{
-        ResourceManager,
+        self, Asset, ResourceManager,
         block::{BlockModel, States, TextureAtlas},
+        fontsettings::FontSettings,
+        lang::Translations,
     },
     scenes::options::ClientConfig,
 };
@@ -38,6 +52,10 @@ pub enum SceneActionError {
     Debug,
     FailedReloadingAssets(String),
     FailedLoadingWorld(String),
+    /// Connecting to the server at an address failed, with the reason.
+    FailedConnecting(String, String),
+    /// The server kicked the player or didn't let them join, with the reason.
+    Kicked(String),
     Unexpected(String),
 }
 
@@ -58,6 +76,12 @@ impl std::fmt::Display for SceneActionError {
                     e
                 )
             }
+            SceneActionError::FailedConnecting(address, e) => {
+                write!(f, "Failed connecting to '{}'\n\n{}", address, e)
+            }
+            SceneActionError::Kicked(reason) => {
+                write!(f, "Disconnected from the server\n\n{}", reason)
+            }
             SceneActionError::Unexpected(e) => {
                 write!(f, "An unexpected error occurred, but is not fatal\n\n{}", e)
             }
@@ -69,6 +93,9 @@ impl std::error::Error for SceneActionError {}
 
 pub type SceneActionResult = Result<(), SceneActionError>;
 
+/// The part of the loading progress spent on block models, the rest is spent on the other textures.
+const BLOCK_LOADING_SHARE: f32 = 0.9;
+
 /// Assets given to scenes during update and render, which they can use to access resources such
 /// as block textures and models.
 pub struct Assets {
@@ -76,31 +103,73 @@ pub struct Assets {
     pub block_models: HashMap<(BlockId, u16), BlockModel>,
     pub font: Font,
     pub gui_tex: crate::abs::Texture,
+    pub theme: Theme,
+    pub player_animations: AnimationSet,
+    pub critter_animations: AnimationSet,
+    pub translations: Translations,
 }
 
 impl Assets {
     /// Loads all assets needed for the scenes.
-    ///
-    /// A blank `TextureAtlas` is created and passed to each `BlockModel` as it is loaded, allowing
-    /// them to add their textures to the atlas as they are loaded. This ensures that only the
-    /// needed textures are loaded into the atlas.
     pub fn load(
         gl: &Arc<glow::Context>,
         window: &mut sdl2::video::Window,
         config: &ClientConfig,
     ) -> Result<Self, String> {
+        DecodedAssets::decode(config, &|_, _| {})?.upload(gl, window)
+    }
+}
+
+/// Returns the name of the texture of an item without a block in the block texture atlas.
+pub fn item_texture_name(ident: &str) -> String {
+    format!("item/{}", ident)
+}
+
+/// Assets that were read and decoded, but not uploaded to the GPU yet. Decoding does not need the
+/// OpenGL context, so it can happen on another thread while the main thread shows a splash screen.
+pub struct DecodedAssets {
+    block_textures: TextureAtlas,
+    block_models: HashMap<(BlockId, u16), BlockModel>,
+    font_image: image::DynamicImage,
+    font_settings: FontSettings,
+    text_palette: TextPalette,
+    gui_image: image::DynamicImage,
+    window_icon: image::DynamicImage,
+    theme: Theme,
+    player_animations: AnimationSet,
+    critter_animations: AnimationSet,
+    translations: Translations,
+}
+
+impl DecodedAssets {
+    /// Reads and decodes all assets from the resource packs in the config. `progress` is called
+    /// with the fraction of the work done so far and a description of the current stage.
+    ///
+    /// A blank `TextureAtlas` is created and passed to each `BlockModel` as it is loaded, allowing
+    /// them to add their textures to the atlas as they are loaded. This ensures that only the
+    /// needed textures are loaded into the atlas.
+    pub fn decode(config: &ClientConfig, progress: &dyn Fn(f32, &str)) -> Result<Self, String> {
         let resource_manager = ResourceManager::new(config.resource_packs());
         let mut block_textures = TextureAtlas::new(256, 16);
         let mut block_models = HashMap::new();
-        for (block_id, block) in block_registry().iter_enumerate() {
+        let block_count = block_registry().len();
+        for (i, (block_id, block)) in block_registry().iter_enumerate().enumerate() {
+            progress(
+                i as f32 / block_count as f32 * BLOCK_LOADING_SHARE,
+                "Loading block models",
+            );
             let mut possible_state_data_values = BlockState::possible_data_values(block.state_type)
                 .unwrap()
                 .iter()
                 .collect::<std::collections::HashSet<_>>();
-            let blockstate_path = PathBuf::from(format!("blocks/states/{}.json", block.ident));
-            let blockstate_data = resource_manager
-                .read(&blockstate_path)
-                .ok_or_else(|| format!("Failed to load blockstate for block '{}'", block.ident))?;
+            let blockstate_path =
+                PathBuf::from(format!("blocks/states/{}.json", ident_path(block.ident)));
+            let blockstate_data = resource_manager.try_read(&blockstate_path).map_err(|e| {
+                format!(
+                    "Failed to load blockstate for block '{}': {}",
+                    block.ident, e
+                )
+            })?;
             let blockstate_str = std::str::from_utf8(&blockstate_data).map_err(|e| {
                 format!(
                     "Failed to parse blockstate for block '{}': {}",
@@ -115,11 +184,10 @@ impl Assets {
             })?;
             for (state_data, state) in states.states {
                 let model_path = state.model;
-                let model_file = resource_manager.read(&model_path).ok_or_else(|| {
+                let model_file = resource_manager.try_read(&model_path).map_err(|e| {
                     format!(
-                        "Failed to load model file '{}' for block '{}'",
-                        model_path.display(),
-                        block.ident
+                        "Failed to load model file for block '{}': {}",
+                        block.ident, e
                     )
                 })?;
                 let model_file = std::str::from_utf8(&model_file).map_err(|e| {
@@ -157,8 +225,72 @@ impl Assets {
                 panic!("Invalid blockstate file for block '{}'", block.ident);
             }
         }
-        block_textures.upload(gl);
-        if let Err(e) = block_textures
+        // Items without a block are drawn flat, using a texture in the same atlas
+        for item in item_registry().iter() {
+            if item.assoc_block.is_some() {
+                continue;
+            }
+            let texture_path = PathBuf::from(format!("items/{}.png", ident_path(item.ident)));
+            let texture_data = resource_manager.try_read(&texture_path)?;
+            let image = image::load_from_memory(&texture_data)
+                .map_err(|e| format!("Failed to load texture for item '{}': {}", item.ident, e))?
+                .to_rgba8();
+            block_textures
+                .add_texture(item_texture_name(item.ident), image)
+                .ok_or("Atlas is full, cannot add more textures")?;
+        }
+
+        log::info!(
+            "Loaded {} block textures and {} block models for {} blocks",
+            block_textures.texture_count(),
+            block_models.len(),
+            block_count
+        );
+
+        progress(BLOCK_LOADING_SHARE, "Loading textures");
+        let font_image = resource_manager.load(&resource::FONT_TEXTURE)?;
+        let font_settings = resource_manager.load(&resource::FONT_SETTINGS)?;
+        let gui_image = resource_manager.load(&resource::GUI_TEXTURE)?;
+        let window_icon = resource_manager.load(&resource::WINDOW_ICON)?;
+        let player_animations = resource_manager.load(&resource::PLAYER_ANIMATIONS)?;
+        let critter_animations = resource_manager.load(&resource::CRITTER_ANIMATIONS)?;
+        let mut translations = resource_manager.load(&resource::ENGLISH)?;
+        let locale = config.locale();
+        let locale_path = PathBuf::from(format!("lang/{}.json", locale));
+        if locale != "en_US"
+            && let Some(bytes) = resource_manager.read(&locale_path)
+        {
+            match Translations::decode(&bytes) {
+                Ok(locale_translations) => translations.extend(locale_translations),
+                Err(e) => log::warn!("Failed to decode '{}': {}", locale_path.display(), e),
+            }
+        }
+        progress(1.0, "Uploading textures");
+
+        Ok(Self {
+            block_textures,
+            block_models,
+            font_image,
+            font_settings,
+            text_palette: TextPalette::new(&config.text_palette()),
+            gui_image,
+            window_icon,
+            theme: Theme::from_config(config),
+            player_animations,
+            critter_animations,
+            translations,
+        })
+    }
+
+    /// Uploads the decoded textures to the GPU and sets the window icon.
+    pub fn upload(
+        mut self,
+        gl: &Arc<glow::Context>,
+        window: &mut sdl2::video::Window,
+    ) -> Result<Assets, String> {
+        self.block_textures.upload(gl);
+        if let Err(e) = self
+            .block_textures
             .take_atlas()
             .unwrap()
             .save(crate::get_dbg_dir().join("block_atlas.png"))
@@ -170,50 +302,14 @@ impl Assets {
                 crate::get_dbg_dir().join("block_atlas.png").display()
             );
         }
-        log::info!(
-            "Loaded {} block textures and {} block models for {} blocks",
-            block_textures.texture_count(),
-            block_models.len(),
-            block_registry().len()
-        );
         let font = Font::new(
-            crate::abs::Texture::new(
-                gl,
-                &image::load_from_memory_with_format(
-                    &resource_manager
-                        .read(std::path::Path::new("font.png"))
-                        .ok_or_else(|| "Failed to load font texture".to_string())?,
-                    image::ImageFormat::Png,
-                )
-                .unwrap(),
-            ),
-            resource_manager
-                .read(std::path::Path::new("font.json"))
-                .ok_or_else(|| "Failed to load font metadata".to_string())
-                .and_then(|data| {
-                    serde_json::from_slice(&data)
-                        .map_err(|e| format!("Failed to parse font metadata: {}", e))
-                })?,
-        );
-        let gui_tex = crate::abs::Texture::new(
-            gl,
-            &image::load_from_memory_with_format(
-                &resource_manager
-                    .read(std::path::Path::new("gui.png"))
-                    .ok_or_else(|| "Failed to load GUI texture".to_string())?,
-                image::ImageFormat::Png,
-            )
-            .unwrap(),
-        );
-        let window_icon = image::load_from_memory_with_format(
-            &resource_manager
-                .read(std::path::Path::new("window_icon.png"))
-                .ok_or_else(|| "Failed to load window icon".to_string())?,
-            image::ImageFormat::Png,
+            crate::abs::Texture::new(gl, &self.font_image),
+            self.font_settings,
         )
-        .unwrap();
-        let (icon_width, icon_height) = window_icon.dimensions();
-        let mut icon_rgba = window_icon.into_rgba8().into_raw();
+        .with_palette(self.text_palette);
+        let gui_tex = crate::abs::Texture::new(gl, &self.gui_image);
+        let (icon_width, icon_height) = self.window_icon.dimensions();
+        let mut icon_rgba = self.window_icon.into_rgba8().into_raw();
         let icon = sdl2::surface::Surface::from_data(
             &mut icon_rgba,
             icon_width,
@@ -223,11 +319,15 @@ impl Assets {
         )
         .map_err(|e| format!("Failed to create window icon surface: {}", e))?;
         window.set_icon(icon);
-        Ok(Self {
-            block_textures,
-            block_models,
+        Ok(Assets {
+            block_textures: self.block_textures,
+            block_models: self.block_models,
             font,
             gui_tex,
+            theme: self.theme,
+            player_animations: self.player_animations,
+            critter_animations: self.critter_animations,
+            translations: self.translations,
         })
     }
 }
@@ -235,7 +335,7 @@ impl Assets {
 #[allow(unused)]
 pub struct SceneUpdateContext<'a> {
     pub gl: &'a Arc<glow::Context>,
-    pub ctx: &'a crate::other::UpdateContext<'a>,
+    pub ctx: &'a crate::input::UpdateContext<'a>,
     pub window: &'a mut sdl2::video::Window,
     pub sdl_ctx: &'a sdl2::Sdl,
     pub assets: &'a Arc<Assets>,
@@ -261,6 +361,16 @@ pub trait Scene {
         assets: &Arc<Assets>,
         config: &Arc<RwLock<ClientConfig>>,
     );
+
+    /// Returns the widgets shown by the scene, which the narrator reads the focused one from.
+    fn ui(&self) -> Option<&dyn Widget> {
+        None
+    }
+
+    /// Returns whether the mouse is captured to look around instead of moving a cursor.
+    fn captures_mouse(&self) -> bool {
+        false
+    }
 }
 
 /// Manages the stack of scenes.
@@ -273,6 +383,7 @@ pub struct SceneManager {
     last_err_time: f32,
     last_err: Option<SceneActionError>,
     result: SceneActionResult,
+    narrator: Narrator,
 }
 
 impl SceneManager {
@@ -287,9 +398,15 @@ impl SceneManager {
             last_err_time: 0.0,
             last_err: None,
             result: Ok(()),
+            narrator: Narrator::default(),
         }
     }
 
+    /// Returns the shared client config.
+    pub fn config(&self) -> &Arc<RwLock<ClientConfig>> {
+        &self.config
+    }
+
     /// Handles an event by passing it to the current scene.
     pub fn handle_event(&mut self, gl: &std::sync::Arc<glow::Context>, event: &sdl2::event::Event) {
         if let Some(current_scene) = self.scenes.last_mut() {
@@ -301,7 +418,7 @@ impl SceneManager {
     pub fn update(
         &mut self,
         gl: &Arc<glow::Context>,
-        ctx: &crate::other::UpdateContext,
+        ctx: &crate::input::UpdateContext,
         window: &mut sdl2::video::Window,
         sdl_ctx: &sdl2::Sdl,
     ) -> bool {
@@ -310,6 +427,20 @@ impl SceneManager {
             self.just_switched = false;
             return true;
         }
+        let bindings = self.config.read().unwrap().key_bindings();
+        if bindings.pressed(ctx.keyboard, Action::Fullscreen) {
+            let mut config = self.config.write().unwrap();
+            let mode = if config.window_mode() == crate::abs::WindowMode::Windowed {
+                crate::abs::WindowMode::Borderless
+            } else {
+                crate::abs::WindowMode::Windowed
+            };
+            config.window_mode = Some(mode);
+            config.save();
+            if let Err(e) = crate::abs::apply_window_mode(window, mode, config.resolution) {
+                log::error!("Failed to change window mode: {}", e);
+            }
+        }
         if ctx.keyboard.pressed.contains(&sdl2::keyboard::Keycode::F12) {
             self.result = Err(SceneActionError::Debug);
             self.last_err_time = self.timer;
@@ -317,6 +448,9 @@ impl SceneManager {
             return true;
         }
         if let Some(current_scene) = self.scenes.last_mut() {
+            sdl_ctx
+                .mouse()
+                .set_relative_mouse_mode(current_scene.captures_mouse());
             let actions = current_scene.update(&mut SceneUpdateContext {
                 gl,
                 ctx,
@@ -378,6 +512,10 @@ impl SceneManager {
                 self.result = result;
             }
         }
+        if self.config.read().unwrap().narrator() {
+            self.narrator
+                .update(self.scenes.last().and_then(|scene| scene.ui()));
+        }
         if let Err(e) = &self.result {
             self.last_err_time = self.timer;
             self.last_err = Some(e.clone());
@@ -408,9 +546,14 @@ impl SceneManager {
     }
 }
 
+pub mod accessibility;
+pub mod controls;
+pub mod multiplayer;
 pub mod options;
 pub mod packselection;
 pub mod singleplayer;
 pub mod titlescreen;
+pub mod transfer;
+pub mod video;
 pub mod worldcreation;
 pub mod worldselection;
@@ -0,0 +1,150 @@
+//! The scene for connecting to a dedicated server.
+
+use std::sync::{Arc, RwLock};
+
+use glam::{Vec2, Vec4};
+use glow::HasContext;
+
+use crate::{
+    render::ui::{uirenderer::UIRenderer, widgets::*},
+    scenes::{Assets, SceneAction, SceneActionError, SceneUpdateContext},
+};
+
+pub struct Multiplayer {
+    container: Column,
+}
+
+impl Multiplayer {
+    pub fn new(assets: &Arc<Assets>, window_size: (u32, u32), last_server: &str) -> Self {
+        let mut container = Column::new(20.0)
+            .padding(Vec4::new(0.0, 0.0, 40.0, 60.0))
+            .justification(Justification::SpaceBetween)
+            .with(Label::new("Join Server").font_size(48.0))
+            .with(
+                Column::new(20.0)
+                    .with(InputField::new("Server Address").text(last_server))
+                    .with(
+                        Label::new(&format!(
+                            "The port is {} unless given, like example.com:{}",
+                            mp3d_core::net::DEFAULT_PORT,
+                            mp3d_core::net::DEFAULT_PORT
+                        ))
+                        .color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
+                    )
+                    .with(InputField::new("Password").masked()),
+            )
+            .with(
+                Row::new(60.0)
+                    .with(Button::new("Cancel"))
+                    .with(Button::new("Connect")),
+            );
+
+        container.layout(&LayoutContext {
+            max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        Self { container }
+    }
+}
+
+impl super::Scene for Multiplayer {
+    fn update(&mut self, ctx: &mut SceneUpdateContext) -> Vec<SceneAction> {
+        let SceneUpdateContext {
+            gl,
+            ctx,
+            window,
+            assets,
+            config,
+            ..
+        } = ctx;
+
+        crate::abs::update_title(window, "Mineplace3D - Join server");
+
+        self.container.update(ctx);
+        self.container.layout(&LayoutContext {
+            max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        if ctx
+            .keyboard
+            .pressed
+            .contains(&sdl2::keyboard::Keycode::Escape)
+        {
+            return vec![SceneAction::Pop];
+        }
+
+        let address = self
+            .container
+            .find_widget::<InputField>(&[1, 0])
+            .map(|input| input.text.trim().to_string())
+            .unwrap_or_default();
+        let password = self
+            .container
+            .find_widget::<InputField>(&[1, 2])
+            .map(|input| input.text.clone())
+            .unwrap_or_default();
+
+        if let Some(connect_button) = self.container.find_widget_mut::<Button>(&[2, 1]) {
+            connect_button.disabled = address.is_empty() || password.is_empty();
+        }
+
+        if let Some(cancel_button) = self.container.find_widget::<Button>(&[2, 0])
+            && cancel_button.is_pressed()
+        {
+            return vec![SceneAction::Pop];
+        }
+
+        if let Some(connect_button) = self.container.find_widget::<Button>(&[2, 1])
+            && connect_button.is_pressed()
+        {
+            {
+                let mut config = config.write().unwrap();
+                config.last_server = Some(address.clone());
+                config.save();
+            }
+            let config = config.read().unwrap();
+            return match super::singleplayer::SinglePlayer::connect(
+                &address,
+                password,
+                gl,
+                assets,
+                window.size(),
+                &config,
+            ) {
+                Ok(scene) => vec![SceneAction::Push(Box::new(scene))],
+                Err(e) => {
+                    log::error!("Failed to connect to {}: {}", address, e);
+                    vec![SceneAction::ShowError(SceneActionError::FailedConnecting(
+                        address,
+                        e.to_string(),
+                    ))]
+                }
+            };
+        }
+
+        Vec::new()
+    }
+
+    fn render(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        ui: &mut UIRenderer,
+        assets: &Arc<Assets>,
+        _config: &Arc<RwLock<super::options::ClientConfig>>,
+    ) {
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            self.container.draw(ui, assets);
+        }
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
+}
@@ -1,30 +1,165 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
-use glam::Vec2;
+use glam::{Vec2, Vec4};
 use glow::HasContext;
+use mp3d_core::{
+    chatlog::{ChatLog, DEFAULT_MAX_LOG_SIZE},
+    preferences::{Preferences, Units},
+    server::MAX_RENDER_DIST,
+};
 
 use crate::{
-    render::ui::{uirenderer::UIRenderer, widgets::*},
+    abs::WindowMode,
+    client::{chat::ChatHistory, player::DEFAULT_FOV, world::DEFAULT_RENDER_DISTANCE},
+    input::{Action, KeyBindings},
+    render::{
+        postprocess::FxaaQuality,
+        ui::{
+            hud::{HudElement, HudPlacement},
+            theme::CrosshairStyle,
+            uirenderer::UIRenderer,
+            widgets::*,
+        },
+    },
     scenes::{Assets, SceneAction, SceneUpdateContext},
+    update::DEFAULT_UPDATE_URL,
 };
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The frame rate limit used while the window is unfocused, if enabled.
+pub const BACKGROUND_FPS_LIMIT: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
     pub username: String,
+    /// Superseded by `window_mode`, only read to migrate older configs.
+    #[serde(skip_serializing)]
     pub fullscreen: Option<bool>,
+    pub window_mode: Option<WindowMode>,
+    /// The resolution used in exclusive fullscreen, `None` uses the desktop resolution.
+    pub resolution: Option<(u32, u32)>,
+    pub window_size: Option<(u32, u32)>,
+    pub window_position: Option<(i32, i32)>,
+    /// The resolution of the 3D scene relative to the window, the UI is always drawn at full
+    /// resolution.
+    pub render_scale: Option<f32>,
+    pub fxaa_quality: Option<FxaaQuality>,
+    pub bloom: Option<bool>,
+    pub tonemapping: Option<bool>,
+    pub brightness: Option<f32>,
+    /// Whether cinematic mode blurs everything that is not at the distance of the targeted block.
+    pub depth_of_field: Option<bool>,
+    pub vsync: Option<bool>,
+    /// The maximum frame rate, `None` or `0` for no limit.
+    pub fps_limit: Option<u32>,
+    /// Whether to limit the frame rate to [`BACKGROUND_FPS_LIMIT`] while the window is unfocused.
+    pub background_throttle: Option<bool>,
+    /// The maximum number of chunks re-meshed per frame.
+    pub chunk_updates_per_frame: Option<usize>,
+    /// Whether to request an OpenGL debug context and log the messages of the driver. Defaults to
+    /// on in debug builds.
+    pub gl_debug: Option<bool>,
+    /// The distance in blocks up to which name tags are shown above players.
+    pub name_tag_distance: Option<f32>,
     pub sensitivity: Option<f32>,
+    /// The vertical field of view in degrees.
+    pub fov: Option<f32>,
+    /// The number of chunks loaded around the player, at most [`MAX_RENDER_DIST`].
+    pub render_distance: Option<i32>,
+    /// Whether mouse movement is smoothed outside of cinematic mode as well.
+    pub mouse_smoothing: Option<bool>,
+    /// Whether the focused widget is read out, see [`crate::narrator`].
+    pub narrator: Option<bool>,
+    /// Whether movement is only sent to the server when it changed.
+    pub move_delta_compression: Option<bool>,
     pub resource_packs: Option<Vec<String>>,
+    pub crosshair_style: Option<CrosshairStyle>,
+    pub crosshair_color: Option<[f32; 4]>,
+    pub high_contrast: Option<bool>,
+    /// Overrides for the basic text colors, see [`crate::render::ui::theme::TextPalette`].
+    pub text_palette: Option<HashMap<u8, [f32; 3]>>,
+    /// The locale like `en_US`, `None` uses the locale of the system.
+    pub locale: Option<String>,
+    pub clock_24h: Option<bool>,
+    pub units: Option<Units>,
+    /// The address of the last server joined, filled in when joining a server again.
+    pub last_server: Option<String>,
+    /// The direction the sun and the moon rise in, in degrees from the positive X axis towards the
+    /// positive Z axis.
+    pub sunrise_yaw: Option<f32>,
+    /// Where the HUD elements moved by the player are placed, see
+    /// [`crate::render::ui::hud`].
+    pub hud_layout: Option<HashMap<HudElement, HudPlacement>>,
+    /// The keys rebound by the player, named like in SDL, see [`KeyBindings`].
+    pub key_bindings: Option<HashMap<Action, String>>,
+    /// The most chat messages kept, older ones are dropped.
+    pub chat_history_limit: Option<usize>,
+    /// Whether the chat and the sent commands are written to `logs/chat.log` in the game folder.
+    pub chat_log: Option<bool>,
+    /// The size in bytes the chat log grows to before it is rotated, see
+    /// [`mp3d_core::chatlog`].
+    pub chat_log_max_size: Option<u64>,
+    /// Whether the title screen checks for a newer version of the game, see [`crate::update`].
+    pub update_check: Option<bool>,
+    /// Where the latest release is looked up, `None` uses [`DEFAULT_UPDATE_URL`].
+    pub update_url: Option<String>,
+    /// Whether the controls overlay was shown already. A new config starts with `false` so it is
+    /// shown on the first launch, configs from before it existed count as having seen it.
+    pub controls_shown: Option<bool>,
+    /// The servers allowed to send the player to another server without asking, by address.
+    pub trusted_servers: Option<Vec<String>>,
 }
 
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
             username: "Player".to_string(),
-            fullscreen: Some(false),
+            fullscreen: None,
+            window_mode: Some(WindowMode::Windowed),
+            resolution: None,
+            window_size: Some((1280, 720)),
+            window_position: None,
+            render_scale: Some(1.0),
+            fxaa_quality: Some(FxaaQuality::Off),
+            bloom: Some(false),
+            tonemapping: Some(false),
+            brightness: Some(1.0),
+            depth_of_field: Some(true),
+            vsync: Some(true),
+            fps_limit: None,
+            background_throttle: Some(true),
+            chunk_updates_per_frame: Some(12),
+            gl_debug: None,
+            name_tag_distance: Some(64.0),
             sensitivity: Some(1.0),
+            fov: Some(DEFAULT_FOV),
+            render_distance: Some(DEFAULT_RENDER_DISTANCE),
+            mouse_smoothing: Some(false),
+            narrator: Some(false),
+            move_delta_compression: Some(true),
             resource_packs: Some(vec![]),
+            crosshair_style: Some(CrosshairStyle::Cross),
+            crosshair_color: Some([1.0, 1.0, 1.0, 0.8]),
+            high_contrast: Some(false),
+            text_palette: Some(HashMap::new()),
+            locale: None,
+            clock_24h: Some(true),
+            units: Some(Units::Metric),
+            last_server: None,
+            sunrise_yaw: Some(0.0),
+            hud_layout: Some(HashMap::new()),
+            key_bindings: Some(HashMap::new()),
+            chat_history_limit: Some(500),
+            chat_log: Some(false),
+            chat_log_max_size: Some(DEFAULT_MAX_LOG_SIZE),
+            update_check: Some(true),
+            update_url: None,
+            controls_shown: Some(false),
+            trusted_servers: Some(Vec::new()),
         }
     }
 }
@@ -46,17 +181,209 @@ impl ClientConfig {
         std::fs::write(config_path, config_data).unwrap();
     }
 
-    pub fn fullscreen(&self) -> bool {
-        self.fullscreen.unwrap_or(false)
+    pub fn window_mode(&self) -> WindowMode {
+        self.window_mode
+            .unwrap_or(if self.fullscreen.unwrap_or(false) {
+                WindowMode::Borderless
+            } else {
+                WindowMode::Windowed
+            })
+    }
+
+    pub fn window_size(&self) -> (u32, u32) {
+        self.window_size.unwrap_or((1280, 720))
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale.unwrap_or(1.0).clamp(0.25, 2.0)
+    }
+
+    pub fn fxaa_quality(&self) -> FxaaQuality {
+        self.fxaa_quality.unwrap_or_default()
+    }
+
+    pub fn bloom(&self) -> bool {
+        self.bloom.unwrap_or(false)
+    }
+
+    pub fn tonemapping(&self) -> bool {
+        self.tonemapping.unwrap_or(false)
+    }
+
+    pub fn brightness(&self) -> f32 {
+        self.brightness.unwrap_or(1.0)
+    }
+
+    pub fn depth_of_field(&self) -> bool {
+        self.depth_of_field.unwrap_or(true)
+    }
+
+    pub fn vsync(&self) -> bool {
+        self.vsync.unwrap_or(true)
+    }
+
+    pub fn fps_limit(&self) -> Option<u32> {
+        self.fps_limit.filter(|fps| *fps > 0)
+    }
+
+    pub fn sunrise_yaw(&self) -> f32 {
+        self.sunrise_yaw.unwrap_or(0.0).rem_euclid(360.0)
+    }
+
+    /// Returns where a HUD element is placed, which is its default placement unless the player
+    /// moved it.
+    pub fn hud_placement(&self, element: HudElement) -> HudPlacement {
+        self.hud_layout
+            .as_ref()
+            .and_then(|layout| layout.get(&element))
+            .copied()
+            .unwrap_or_else(|| element.default_placement())
+    }
+
+    pub fn key_bindings(&self) -> KeyBindings {
+        self.key_bindings
+            .as_ref()
+            .map(KeyBindings::from_names)
+            .unwrap_or_default()
+    }
+
+    /// Returns an empty chat history with the limit and logging set in the config.
+    pub fn chat_history(&self) -> ChatHistory {
+        let log = self.chat_log.unwrap_or(false).then(|| {
+            ChatLog::new(
+                crate::get_game_dir().join("logs/chat.log"),
+                self.chat_log_max_size.unwrap_or(DEFAULT_MAX_LOG_SIZE),
+            )
+        });
+        ChatHistory::new(self.chat_history_limit.unwrap_or(500), log)
+    }
+
+    pub fn background_throttle(&self) -> bool {
+        self.background_throttle.unwrap_or(true)
+    }
+
+    pub fn chunk_updates_per_frame(&self) -> usize {
+        self.chunk_updates_per_frame.unwrap_or(12).max(1)
+    }
+
+    pub fn gl_debug(&self) -> bool {
+        self.gl_debug.unwrap_or(cfg!(debug_assertions))
+    }
+
+    /// Returns whether the server at `address` may transfer the player without asking.
+    pub fn trusts_server(&self, address: &str) -> bool {
+        self.trusted_servers
+            .as_ref()
+            .is_some_and(|servers| servers.iter().any(|server| server == address))
+    }
+
+    pub fn name_tag_distance(&self) -> f32 {
+        self.name_tag_distance.unwrap_or(64.0).max(0.0)
     }
 
     pub fn sensitivity(&self) -> f32 {
         self.sensitivity.unwrap_or(1.0)
     }
 
+    pub fn fov(&self) -> f32 {
+        self.fov.unwrap_or(DEFAULT_FOV)
+    }
+
+    pub fn render_distance(&self) -> i32 {
+        self.render_distance
+            .unwrap_or(DEFAULT_RENDER_DISTANCE)
+            .clamp(2, MAX_RENDER_DIST)
+    }
+
+    pub fn mouse_smoothing(&self) -> bool {
+        self.mouse_smoothing.unwrap_or(false)
+    }
+
+    pub fn narrator(&self) -> bool {
+        self.narrator.unwrap_or(false)
+    }
+
+    pub fn move_delta_compression(&self) -> bool {
+        self.move_delta_compression.unwrap_or(true)
+    }
+
     pub fn resource_packs(&self) -> &[String] {
         self.resource_packs.as_deref().unwrap_or(&[])
     }
+
+    pub fn crosshair_style(&self) -> CrosshairStyle {
+        self.crosshair_style.unwrap_or_default()
+    }
+
+    pub fn crosshair_color(&self) -> Vec4 {
+        self.crosshair_color
+            .map(Vec4::from_array)
+            .unwrap_or(Vec4::new(1.0, 1.0, 1.0, 0.8))
+    }
+
+    pub fn high_contrast(&self) -> bool {
+        self.high_contrast.unwrap_or(false)
+    }
+
+    pub fn text_palette(&self) -> HashMap<u8, [f32; 3]> {
+        self.text_palette.clone().unwrap_or_default()
+    }
+
+    pub fn locale(&self) -> String {
+        self.locale
+            .clone()
+            .or_else(|| std::env::var("LANG").ok())
+            .and_then(|lang| {
+                let locale = lang.split('.').next().unwrap_or_default();
+                (!locale.is_empty() && locale != "C" && locale != "POSIX")
+                    .then(|| locale.to_string())
+            })
+            .unwrap_or_else(|| "en_US".to_string())
+    }
+
+    pub fn clock_24h(&self) -> bool {
+        self.clock_24h.unwrap_or(true)
+    }
+
+    pub fn units(&self) -> Units {
+        self.units.unwrap_or_default()
+    }
+
+    pub fn update_check(&self) -> bool {
+        self.update_check.unwrap_or(true)
+    }
+
+    pub fn update_url(&self) -> &str {
+        self.update_url.as_deref().unwrap_or(DEFAULT_UPDATE_URL)
+    }
+
+    pub fn controls_shown(&self) -> bool {
+        self.controls_shown.unwrap_or(true)
+    }
+
+    /// Returns the display preferences sent to the server when joining a world.
+    pub fn preferences(&self) -> Preferences {
+        Preferences {
+            locale: self.locale(),
+            clock_24h: self.clock_24h(),
+            units: self.units(),
+        }
+    }
+}
+
+fn clock_text(config: &ClientConfig) -> String {
+    format!("Clock: {}", if config.clock_24h() { "24h" } else { "12h" })
+}
+
+fn units_text(config: &ClientConfig) -> String {
+    format!("Units: {}", config.units().name())
+}
+
+fn update_check_text(config: &ClientConfig) -> String {
+    format!(
+        "Update Check: {}",
+        if config.update_check() { "On" } else { "Off" }
+    )
 }
 
 pub struct Options {
@@ -79,20 +406,36 @@ impl Options {
                             .sanitize("/\\?%*:|\"<> ")
                             .text(&config.read().unwrap().username),
                     )
-                    .with(Button::new(&format!(
-                        "Fullscreen: {}",
-                        if config.read().unwrap().fullscreen() {
-                            "On"
-                        } else {
-                            "Off"
-                        }
-                    )))
-                    .with(Button::new("Clear Logs"))
+                    .with(
+                        Row::new(20.0)
+                            .with(Button::new("Video Settings").size(Vec2::new(240.0, 80.0)))
+                            .with(Button::new("Accessibility").size(Vec2::new(240.0, 80.0)))
+                            .with(Button::new("Controls").size(Vec2::new(240.0, 80.0))),
+                    )
+                    .with(
+                        Row::new(20.0)
+                            .with(Button::new("Clear Logs").size(Vec2::new(240.0, 80.0)))
+                            .with(
+                                Button::new(&update_check_text(&config.read().unwrap()))
+                                    .size(Vec2::new(240.0, 80.0)),
+                            ),
+                    )
                     .with(
                         Slider::new("Mouse Sensitivity", Vec2::new(500.0, 80.0), 0.1..=2.0)
                             .value(config.read().unwrap().sensitivity()),
                     )
                     .with(Button::new("Resource Packs"))
+                    .with(
+                        Row::new(20.0)
+                            .with(
+                                Button::new(&clock_text(&config.read().unwrap()))
+                                    .size(Vec2::new(240.0, 80.0)),
+                            )
+                            .with(
+                                Button::new(&units_text(&config.read().unwrap()))
+                                    .size(Vec2::new(240.0, 80.0)),
+                            ),
+                    )
                     .with(Button::new("Back")),
             );
 
@@ -111,14 +454,12 @@ impl super::Scene for Options {
         let SceneUpdateContext {
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
         } = ctx;
 
-        window.set_title("Mineplace3D - Options").unwrap();
-        sdl_ctx.mouse().set_relative_mouse_mode(false);
+        crate::abs::update_title(window, "Mineplace3D - Options");
 
         self.container.update(ctx);
         self.container.layout(&LayoutContext {
@@ -127,39 +468,6 @@ impl super::Scene for Options {
             assets,
         });
 
-        self.container
-            .find_widget_mut::<Button>(&[1, 1])
-            .unwrap()
-            .text = format!(
-            "Fullscreen: {}",
-            if config.read().unwrap().fullscreen() {
-                "On"
-            } else {
-                "Off"
-            }
-        );
-
-        if self
-            .container
-            .find_widget::<Button>(&[1, 1])
-            .unwrap()
-            .is_released()
-        {
-            let mut config_guard = config.write().unwrap();
-            config_guard.fullscreen = Some(!config_guard.fullscreen());
-            config_guard.save();
-
-            log::info!("Toggled fullscreen: {}", config_guard.fullscreen());
-
-            window
-                .set_fullscreen(if config_guard.fullscreen() {
-                    sdl2::video::FullscreenType::Desktop
-                } else {
-                    sdl2::video::FullscreenType::Off
-                })
-                .unwrap();
-        }
-
         let input_text = self
             .container
             .find_widget::<InputField>(&[1, 0])
@@ -168,13 +476,13 @@ impl super::Scene for Options {
             .clone();
 
         self.container
-            .find_widget_mut::<Button>(&[1, 5])
+            .find_widget_mut::<Button>(&[1, 6])
             .unwrap()
             .disabled = input_text.trim().is_empty();
 
         if self
             .container
-            .find_widget::<Button>(&[1, 2])
+            .find_widget::<Button>(&[1, 2, 0])
             .unwrap()
             .is_released()
         {
@@ -200,7 +508,52 @@ impl super::Scene for Options {
 
         if self
             .container
-            .find_widget::<Button>(&[1, 5])
+            .find_widget::<Button>(&[1, 2, 1])
+            .unwrap()
+            .is_released()
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.update_check = Some(!config_guard.update_check());
+            log::info!("Changed update check: {}", config_guard.update_check());
+            self.container
+                .find_widget_mut::<Button>(&[1, 2, 1])
+                .unwrap()
+                .text = update_check_text(&config_guard);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 5, 0])
+            .unwrap()
+            .is_released()
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.clock_24h = Some(!config_guard.clock_24h());
+            log::info!("Changed clock: 24h = {}", config_guard.clock_24h());
+            self.container
+                .find_widget_mut::<Button>(&[1, 5, 0])
+                .unwrap()
+                .text = clock_text(&config_guard);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 5, 1])
+            .unwrap()
+            .is_released()
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.units = Some(config_guard.units().next());
+            log::info!("Changed units: {:?}", config_guard.units());
+            self.container
+                .find_widget_mut::<Button>(&[1, 5, 1])
+                .unwrap()
+                .text = units_text(&config_guard);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 6])
             .unwrap()
             .is_released()
         {
@@ -230,6 +583,41 @@ impl super::Scene for Options {
             ))];
         }
 
+        if self
+            .container
+            .find_widget::<Button>(&[1, 1, 0])
+            .unwrap()
+            .is_released()
+        {
+            return vec![SceneAction::Push(Box::new(
+                super::video::VideoSettings::new(assets, window.size(), config),
+            ))];
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 1, 1])
+            .unwrap()
+            .is_released()
+        {
+            return vec![SceneAction::Push(Box::new(
+                super::accessibility::Accessibility::new(assets, window.size(), config),
+            ))];
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 1, 2])
+            .unwrap()
+            .is_released()
+        {
+            return vec![SceneAction::Push(Box::new(super::controls::Controls::new(
+                assets,
+                window.size(),
+                config,
+            )))];
+        }
+
         Vec::new()
     }
 
@@ -247,4 +635,8 @@ impl super::Scene for Options {
             self.container.draw(ui, assets);
         }
     }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
 }
@@ -39,7 +39,7 @@ impl PackSelection {
                         Column::new(10.0)
                             .viewport_height(window_size.1 as f32 - 200.0)
                             .with(Button::new("Base Game").disabled())
-                            .with_many(loaded_packs.into_iter().map(|v| Button::new(&v))),
+                            .with_many(loaded_packs.iter().map(|v| Button::new(v))),
                     ),
             )
             .with(Button::new("Done").size(Vec2::new(250.0, 70.0)));
@@ -77,14 +77,12 @@ impl super::Scene for PackSelection {
         let SceneUpdateContext {
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
         } = ctx;
 
-        window.set_title("Mineplace3D - Resource packs").unwrap();
-        sdl_ctx.mouse().set_relative_mouse_mode(false);
+        crate::abs::update_title(window, "Mineplace3D - Resource packs");
 
         let new_available_packs = Self::get_packs();
         if self.available_packs != new_available_packs {
@@ -292,4 +290,8 @@ impl super::Scene for PackSelection {
             self.container.draw(ui, assets);
         }
     }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
 }
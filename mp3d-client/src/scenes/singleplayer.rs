@@ -1,30 +1,61 @@
 //! The single player scene implementation.
 
 use std::{
+    cell::RefCell,
     collections::HashMap,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    rc::Rc,
     sync::{Arc, RwLock},
 };
 
 use glam::{IVec3, Mat4, UVec2, UVec4, Vec2, Vec3, Vec4};
 use glow::HasContext;
-use mp3d_core::{textcomponent::TextComponent, world::chunk::CHUNK_SIZE};
+use mp3d_core::{
+    block::block_registry,
+    entity::{Entity, MAX_HEALTH, PlayerEntity},
+    item::{ItemId, ItemStack, OFFHAND_SLOT},
+    mirror::Mirror,
+    physics,
+    registry::DefId,
+    textcomponent::{TextComponent, TextComponentPart, sanitize},
+    world::{chunk::CHUNK_SIZE, generation::WORLD_FLOOR, sky::daylight},
+};
 
 use crate::{
-    abs::{Mesh, ShaderProgram, Texture, framebuffer::Framebuffer},
-    client::{Client, Connection, CurrentGUI, LocalConnection},
+    abs::{Mesh, ShaderProgram, Texture, TextureHandle, framebuffer::Framebuffer, update_title},
+    client::{
+        CHAT_LINES, Client, Connection, CrosshairTarget, CurrentGUI, Disconnection,
+        LocalConnection, PaletteGUI, RemoteConnection,
+        camera::Camera,
+        player::{ClientContainer, DEFAULT_FOV},
+    },
+    input::{Action, KeyBindings},
+    plugins::{PluginEvent, PluginHost},
     render::{
+        animation::Animator,
         clouds::CloudRenderer,
-        meshing::mesh_world,
+        entities::EntityRenderers,
+        meshing::{mesh_world, recycle_chunk_mesh},
+        nametags::{NameTag, NameTagView, draw_name_tags},
+        panorama::{PanoramaCapture, panorama_dir},
         particles::ParticleSystem,
+        postprocess::{DepthOfField, PostProcessSettings, PostProcessor},
         profiler::Profiler,
+        shadows::{ShadowCaster, ShadowRenderer},
+        sky::{SkyRenderer, sky_colors},
         ui::{
             font::{ColorlessTextParams, Font, TextParams},
+            hud::{HudEditor, HudElement},
+            itemrenderer::ItemRenderer,
+            theme::CrosshairStyle,
             uirenderer::{DrawCommand, UIRenderMode, UIRenderer},
             widgets::*,
         },
     },
-    scenes::{Assets, SceneAction, SceneUpdateContext},
+    scenes::{
+        Assets, SceneAction, SceneActionError, SceneUpdateContext, options::ClientConfig, transfer,
+        worldcreation::WorldSettings,
+    },
     shader_program,
 };
 
@@ -39,13 +70,91 @@ const PROFILER_GRAPH_WIDTH: f32 = 400.0;
 
 const CROSSHAIR_SIZE: f32 = 20.0;
 const CROSSHAIR_THICKNESS: f32 = 2.0;
-const CROSSHAIR_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 0.8);
+const CROSSHAIR_OUTLINE: f32 = 1.0;
+/// The size of the bar below the crosshair showing how far a block is broken.
+const BREAK_BAR_SIZE: Vec2 = Vec2::new(40.0, 5.0);
+const CROSSHAIR_DOT_SIZE: f32 = 4.0;
+/// The gap in the middle of the crosshair while it points at an entity.
+const CROSSHAIR_ENTITY_GAP: f32 = 6.0;
+/// The color the crosshair is tinted with while it points at an entity.
+const CROSSHAIR_ENTITY_TINT: Vec3 = Vec3::new(1.0, 0.3, 0.3);
+/// The space between the crosshair and the hint next to it.
+const INTERACT_HINT_MARGIN: f32 = 8.0;
+
+/// How many blocks the grid showing a mirror plane reaches from the player in every direction.
+const MIRROR_PLANE_EXTENT: i32 = 8;
+const MIRROR_PLANE_COLOR: Vec4 = Vec4::new(0.3, 0.8, 1.0, 1.0);
+
+/// The color of the outlines showing where dragged out blocks will be placed.
+const BUILD_PREVIEW_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 1.0);
+/// How far the outlines of dragged out blocks stick out of the blocks, so they aren't hidden by
+/// the faces of neighboring blocks.
+const BUILD_PREVIEW_INFLATE: f32 = 0.005;
+
+/// The color of the outline of the box spanned by the measured blocks.
+const MEASUREMENT_COLOR: Vec4 = Vec4::new(1.0, 0.4, 0.2, 1.0);
+
+/// The space between the message above the hotbar and the hotbar.
+const ACTIONBAR_MARGIN: f32 = 50.0;
+
+/// The width of the bar next to the chat showing how far it is scrolled.
+const CHAT_SCROLLBAR_WIDTH: f32 = 4.0;
+
+/// The space between a kill feed entry and the edge of its backdrop.
+const KILL_FEED_PADDING: f32 = 4.0;
+/// The size of the health bar.
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(200.0, 20.0);
+const HEALTH_BAR_COLOR: Vec4 = Vec4::new(0.8, 0.1, 0.1, 1.0);
+
+/// The distance from the center of the screen to the blocks of the recent blocks palette.
+const PALETTE_RADIUS: f32 = 140.0;
+
+/// How far to look for a block to focus on in cinematic mode.
+const DOF_FOCUS_RANGE: f32 = 64.0;
+
+/// Where the fog starts, relative to the distance at which it fully hides the terrain.
+const FOG_START: f32 = 0.7;
+/// How quickly the fog follows the edge of the loaded world, per second.
+const FOG_ADJUST_SPEED: f32 = 2.0;
+/// The color of the void below the world floor.
+const VOID_COLOR: Vec3 = Vec3::new(0.02, 0.02, 0.05);
+/// How far below the world floor the camera has to be for the void fog to reach full strength.
+const VOID_FADE_DEPTH: f32 = 16.0;
+/// The distance at which the void fog fully hides the terrain.
+const VOID_FOG_DISTANCE: f32 = 24.0;
+
+/// The horizontal speed in blocks per second above which the player model plays its walk
+/// animation.
+const PLAYER_MOVING_SPEED: f32 = 0.5;
+/// The red tint over the world while the player is dead.
+const DEATH_BACKDROP: Vec4 = Vec4::new(0.4, 0.0, 0.0, 0.5);
+
+/// The laid out chat history, kept until the visible messages or the font change.
+struct ChatLayout {
+    messages: Vec<TextComponent>,
+    font_atlas: TextureHandle,
+    size: Vec2,
+    /// The draw commands of the messages, relative to the top left corner of the first message.
+    commands: Vec<DrawCommand>,
+}
 
 struct SinglePlayerUI {
     chat_input_label: Label,
+    chat_layout: Option<ChatLayout>,
     pause_screen: Column,
+    /// The overlay listing the controls, see [`controls_ui`].
+    controls: Column,
+    /// The screen shown while the player is dead, see [`death_screen_ui`].
+    death_screen: Column,
     inventory: Stack,
+    /// The GUI of the open container, with the ID of the container it was built for.
+    container: Option<(u64, Stack)>,
+    creative_picker: CreativePicker,
     hotbar: Row,
+    /// The editor for the HUD layout, while it is open.
+    hud_editor: Option<HudEditor>,
+    /// The unscaled sizes of the HUD elements when they were last drawn.
+    hud_sizes: HashMap<HudElement, Vec2>,
     debug_opened: bool,
     fps_timer: f32,
     fps: f32,
@@ -56,79 +165,156 @@ struct WorldRenderer {
     chunk_meshes: HashMap<IVec3, Mesh>,
     chunk_mesh_pool: Vec<Mesh>,
     cloud_renderer: CloudRenderer,
+    sky_renderer: SkyRenderer,
     particle_system: ParticleSystem,
+    shadow_renderer: ShadowRenderer,
     framebuffer: Framebuffer,
+    postprocessor: PostProcessor,
 
     chunk_shader: ShaderProgram,
     entity_shader: ShaderProgram,
-    postprocess_shader: ShaderProgram,
     chunk_border_shader: ShaderProgram,
 
     entity_model: Mesh,
-    fullscreen_quad: Mesh,
+    entity_renderers: EntityRenderers,
+    player_animator: Animator,
+    /// Animates the entities other than the player, which the client doesn't know the movement of
+    /// so they all stand idle together.
+    /// The animations of the entities other than the player, by entity ID.
+    entity_animators: HashMap<u64, Animator>,
     cube_wireframe: Mesh,
 
     pink_black: Texture,
 
+    /// The distance at which the fog fully hides the terrain, following
+    /// [`crate::client::world::ClientWorld::loaded_distance`] smoothly.
+    fog_distance: f32,
+
     profiler: Profiler,
 }
 
-/// The [`SinglePlayer`] struct represents the single player scene.
+/// The [`SinglePlayer`] struct represents the single player scene, which is also used to play on
+/// a dedicated server.
 pub struct SinglePlayer {
-    client: Client<LocalConnection>,
+    client: Client<Box<dyn Connection>>,
     renderer: WorldRenderer,
     screen_size: UVec2,
+    /// The camera the world was last drawn from.
+    camera: Camera,
+    plugins: PluginHost,
     tick_acc: f32,
     tick_rate: f32,
     ui: SinglePlayerUI,
-    world_path: PathBuf,
+    /// The name of the world or the address of the server, shown in the title.
+    world_name: String,
+    /// The save of the world, `None` when playing on a dedicated server.
+    world_path: Option<PathBuf>,
+    /// The password the player joined the dedicated server with, also used when it transfers
+    /// them to another server.
+    password: Option<String>,
+    world_settings: WorldSettings,
     mouse_pos: Vec2,
     timer: f32,
+    /// Whether all chunks around the player arrived once, until then the title shows the progress.
+    world_loaded: bool,
+    /// The panorama being captured for the title screen, started with F9.
+    panorama_capture: Option<PanoramaCapture>,
 }
 
 impl SinglePlayer {
-    /// Creates a new [`SinglePlayer`] instance.
-    pub fn new(
-        gl: &Arc<glow::Context>,
-        assets: &Arc<Assets>,
-        window_size: (u32, u32),
-        seed: i32,
-        world_path: PathBuf,
-        username: String,
-    ) -> Self {
-        let server = mp3d_core::server::Server::new(true, seed, world_path.clone());
-        Self::setup(server, gl, assets, window_size, world_path, username)
-    }
-
     /// Loads a world from the given path and creates a new [`SinglePlayer`] instance.
     pub fn load(
         gl: &Arc<glow::Context>,
         assets: &Arc<Assets>,
         window_size: (u32, u32),
         world_path: PathBuf,
-        username: String,
+        config: &ClientConfig,
     ) -> Result<Self, std::io::Error> {
         let server = mp3d_core::server::Server::load(true, world_path.clone())?;
-        Ok(Self::setup(
+        let world_settings = WorldSettings::load(&world_path);
+        Ok(Self::new(
             server,
             gl,
             assets,
             window_size,
             world_path,
-            username,
+            world_settings,
+            config,
         ))
     }
 
-    fn setup(
+    /// Creates a new [`SinglePlayer`] instance playing on the given server.
+    pub fn new(
         server: mp3d_core::server::Server,
         gl: &Arc<glow::Context>,
         assets: &Arc<Assets>,
         window_size: (u32, u32),
         world_path: PathBuf,
-        username: String,
+        world_settings: WorldSettings,
+        config: &ClientConfig,
     ) -> Self {
         let connection = LocalConnection::new(server);
-        let client = Client::new(connection, username, None);
+        let client = Client::new(
+            Box::new(connection) as Box<dyn Connection>,
+            config.username.clone(),
+            config.preferences(),
+            None,
+            config.chat_history(),
+        );
+        let world_name = world_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::from_client(
+            client,
+            gl,
+            assets,
+            window_size,
+            world_name,
+            Some(world_path),
+            world_settings,
+        )
+    }
+
+    /// Connects to a dedicated server and creates a new [`SinglePlayer`] instance playing on it.
+    pub fn connect(
+        address: &str,
+        password: String,
+        gl: &Arc<glow::Context>,
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        config: &ClientConfig,
+    ) -> Result<Self, std::io::Error> {
+        let connection = RemoteConnection::connect(address)?;
+        let client = Client::new(
+            Box::new(connection) as Box<dyn Connection>,
+            config.username.clone(),
+            config.preferences(),
+            Some(password.clone()),
+            config.chat_history(),
+        );
+        let mut scene = Self::from_client(
+            client,
+            gl,
+            assets,
+            window_size,
+            address.trim().to_string(),
+            None,
+            WorldSettings::default(),
+        );
+        scene.password = Some(password);
+        Ok(scene)
+    }
+
+    fn from_client(
+        client: Client<Box<dyn Connection>>,
+        gl: &Arc<glow::Context>,
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        world_name: String,
+        world_path: Option<PathBuf>,
+        world_settings: WorldSettings,
+    ) -> Self {
         let layout_ctx = crate::render::ui::widgets::LayoutContext {
             max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
             cursor: Vec2::ZERO,
@@ -147,6 +333,11 @@ impl SinglePlayer {
                     Vec4::ZERO,
                 )
                 .with_many((0..36).map(|i| InventorySlot::new(&client.player.inventory, i))),
+            )
+            .with(
+                Row::new(8.0)
+                    .with(InventorySlot::new(&client.player.inventory, OFFHAND_SLOT))
+                    .with(Label::new("Offhand").font_size(24.0)),
             );
         let inventory_stack = Stack::new(Alignment::Center, Alignment::Center, 0.0)
             .with(NineSlice::new(
@@ -159,26 +350,32 @@ impl SinglePlayer {
             ))
             .with(inventory_col);
 
-        let hotbar_row = Row::new(4.0)
-            .justification(Justification::Center)
-            .with_many((0..9).map(|i| HotbarSlot::new(&client.player.inventory, i + 3 * 9)));
+        let hotbar_row =
+            Row::new(24.0)
+                .justification(Justification::Center)
+                .with(HotbarSlot::new(&client.player.inventory, OFFHAND_SLOT))
+                .with(Row::new(4.0).with_many(
+                    (0..9).map(|i| HotbarSlot::new(&client.player.inventory, i + 3 * 9)),
+                ));
+
+        let creative_picker = CreativePicker::new(&client.player.inventory);
 
         let pause_screen = Column::new(20.0)
             .justification(Justification::Center)
             .with(Button::new("Return to Game"))
-            .with(Button::new("Save and Quit"))
+            .with(Button::new("Edit HUD"))
+            .with(Button::new("Controls"))
+            .with(Button::new(if world_path.is_some() {
+                "Save and Quit"
+            } else {
+                "Disconnect"
+            }))
             .with(Button::new("Quit"));
 
         let cloud_renderer = CloudRenderer::new(gl);
         let particle_system = ParticleSystem::new(gl);
 
-        let image_bytes = [
-            255, 0, 255, 255, // Pink
-            0, 0, 0, 255, // Black
-            0, 0, 0, 255, // Black
-            255, 0, 255, 255, // Pink
-        ];
-        let pink_black = Texture::new_bytes(gl, 2, 2, image_bytes.to_vec());
+        let pink_black = Texture::placeholder(gl);
 
         Self {
             client,
@@ -186,7 +383,9 @@ impl SinglePlayer {
                 chunk_meshes: HashMap::new(),
                 chunk_mesh_pool: Vec::new(),
                 cloud_renderer,
+                sky_renderer: SkyRenderer::new(gl),
                 particle_system,
+                shadow_renderer: ShadowRenderer::new(gl),
                 framebuffer: Framebuffer::new(
                     gl,
                     window_size.0 as i32,
@@ -201,30 +400,107 @@ impl SinglePlayer {
                 ),
                 chunk_shader: shader_program!(chunk, gl, ".."),
                 entity_shader: shader_program!(entity, gl, ".."),
-                postprocess_shader: shader_program!(postprocess, gl, ".."),
+                postprocessor: PostProcessor::new(gl, window_size.0 as i32, window_size.1 as i32),
                 chunk_border_shader: shader_program!(chunk_border, gl, ".."),
-                entity_model: crate::render::entities::player_model(gl),
-                fullscreen_quad: fullscreen_quad_ndc(gl),
+                entity_model: crate::render::entities::part_cube(gl),
+                entity_renderers: EntityRenderers::default(),
+                player_animator: Animator::default(),
+                entity_animators: HashMap::new(),
                 cube_wireframe: cube_wireframe(gl),
                 pink_black,
+                fog_distance: 0.0,
                 profiler: Profiler::new(),
             },
             screen_size: UVec2::new(window_size.0, window_size.1),
+            camera: {
+                let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, DEFAULT_FOV);
+                camera.resize(UVec2::new(window_size.0, window_size.1));
+                camera
+            },
+            plugins: PluginHost::load(),
             tick_acc: 0.0,
             tick_rate: 48.0,
             ui: SinglePlayerUI {
                 chat_input_label: Label::new(""),
+                chat_layout: None,
                 pause_screen,
+                controls: controls_ui(&KeyBindings::default()),
+                death_screen: death_screen_ui(world_path.is_some()),
                 inventory: inventory_stack,
+                container: None,
+                creative_picker,
                 hotbar: hotbar_row,
+                hud_editor: None,
+                hud_sizes: HashMap::new(),
                 debug_opened: false,
                 fps_timer: 0.0,
                 fps: 0.0,
                 fps_history: [0.0; FPS_HISTORY_LEN],
             },
+            world_name,
             world_path,
+            password: None,
+            world_settings,
             mouse_pos: Vec2::ZERO,
             timer: 0.0,
+            world_loaded: false,
+            panorama_capture: None,
+        }
+    }
+
+    /// Leaves for the server the current one sent the player to. Trusted servers send the player
+    /// right away, otherwise the player is asked first.
+    /// Follows a transfer to `address`, asking first unless the server sending the player is
+    /// trusted. Only the address of a dedicated server can be trusted, so `source` is `None` for
+    /// local worlds, which always ask. Takes the fields separately like [`Self::save_world`].
+    fn transfer(
+        source: Option<&String>,
+        password: Option<&str>,
+        address: String,
+        gl: &Arc<glow::Context>,
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        config: &Arc<RwLock<ClientConfig>>,
+    ) -> Vec<SceneAction> {
+        let config = config.read().unwrap();
+        if let (Some(source), Some(password)) = (source, password)
+            && config.trusts_server(source)
+        {
+            log::info!("Transferring from trusted server {} to {}", source, address);
+            return transfer::join(
+                &address,
+                password.to_string(),
+                gl,
+                assets,
+                window_size,
+                &config,
+            );
+        }
+        vec![SceneAction::Replace(Box::new(transfer::Transfer::new(
+            assets,
+            window_size,
+            source.cloned(),
+            address,
+            password.unwrap_or_default().to_string(),
+        )))]
+    }
+
+    /// Saves the world and its client settings, if it is played locally. Dedicated servers save
+    /// their worlds themselves. Takes the fields separately, as the scene is borrowed by the
+    /// profiler while updating.
+    fn save_world(
+        connection: &mut dyn Connection,
+        world_path: Option<&Path>,
+        world_settings: &WorldSettings,
+    ) {
+        let (Some(world_path), Some(server)) = (world_path, connection.local_server()) else {
+            return;
+        };
+        log::info!("Saving world...");
+        std::fs::create_dir_all(world_path).expect("Failed to create world directory");
+        server.save().expect("Failed to save world");
+        if let Err(e) = world_settings.save(world_path) {
+            log::warn!("Failed to save world settings: {}", e);
         }
     }
 
@@ -245,18 +521,12 @@ impl SinglePlayer {
             .into_iter()
             .rev()
             .skip(scroll)
-            .take(10)
+            .take(CHAT_LINES)
             .rev()
             .collect()
     }
 
-    fn draw_chunks(
-        &mut self,
-        gl: &Arc<glow::Context>,
-        assets: &Arc<Assets>,
-        view: Mat4,
-        projection: Mat4,
-    ) {
+    fn draw_chunks(&mut self, gl: &Arc<glow::Context>, assets: &Arc<Assets>, camera: &Camera) {
         let _p = self.renderer.profiler.start_scope("draw_chunks");
 
         let mut visible: Vec<_> = self.renderer.chunk_meshes.iter().collect();
@@ -269,18 +539,39 @@ impl SinglePlayer {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        let frustum_planes = self.client.player.frustum_planes(
-            self.screen_size.x as f32 / self.screen_size.y as f32,
-            &self.client.world,
-        );
+        let frustum_planes = camera.frustum_planes();
 
         self.renderer.chunk_shader.use_program();
-        self.renderer.chunk_shader.set_uniform("u_view", view);
         self.renderer
             .chunk_shader
-            .set_uniform("u_projection", projection);
+            .set_uniform("u_view", camera.view());
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_projection", camera.projection());
         self.renderer.chunk_shader.set_uniform("u_texture", 0);
+        let camera_pos = camera.position;
+        let time = self.client.world.time;
+        let (fog_color, fog_distance) = fog(self.renderer.fog_distance, camera_pos, time);
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_camera_pos", camera_pos);
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_fog_color", fog_color);
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_fog_start", fog_distance * FOG_START);
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_fog_end", fog_distance);
+        self.renderer
+            .chunk_shader
+            .set_uniform("u_daylight", daylight(time));
         assets.block_textures.upload(gl).bind(0);
+        let camera_chunk = camera_pos
+            .floor()
+            .as_ivec3()
+            .div_euclid(IVec3::splat(CHUNK_SIZE as i32));
         for (pos, mesh) in visible {
             let [aabb_min, aabb_max] = [
                 pos.as_vec3() * CHUNK_SIZE as f32,
@@ -289,29 +580,174 @@ impl SinglePlayer {
             if !is_aabb_in_frustum(aabb_min, aabb_max, &frustum_planes) {
                 continue;
             }
+            // Chunks walled in by their neighbours can only be seen from inside
+            if *pos != camera_chunk && self.client.world.is_enclosed(*pos) {
+                continue;
+            }
 
             mesh.draw();
         }
     }
 
-    fn draw_entities(&mut self, view: Mat4, projection: Mat4, player_model_mat: Mat4) {
+    fn draw_entities(
+        &mut self,
+        assets: &Assets,
+        view: Mat4,
+        projection: Mat4,
+        player_model_mat: Mat4,
+    ) {
         let _p = self.renderer.profiler.start_scope("draw_entities");
+        let renderers = &self.renderer.entity_renderers;
+        let mut instances = Vec::new();
+        renderers.get("player").instances(
+            player_model_mat,
+            &self.renderer.player_animator,
+            assets,
+            &mut instances,
+        );
+        let standing = Animator::default();
+        for (entity_id, entity) in &self.client.world.entities {
+            let model = Mat4::from_rotation_translation(
+                glam::Quat::from_rotation_y(entity.yaw.to_radians()),
+                entity.position,
+            );
+            renderers.get(&entity.entity_type).instances(
+                model,
+                self.renderer
+                    .entity_animators
+                    .get(entity_id)
+                    .unwrap_or(&standing),
+                assets,
+                &mut instances,
+            );
+        }
+        self.renderer.entity_model.update_instances(&instances);
+
         self.renderer.entity_shader.use_program();
-        self.renderer
-            .entity_shader
-            .set_uniform("u_model", player_model_mat);
         self.renderer.entity_shader.set_uniform("u_view", view);
         self.renderer
             .entity_shader
             .set_uniform("u_projection", projection);
         self.renderer.entity_shader.set_uniform("u_texture", 0);
+        self.renderer
+            .entity_shader
+            .set_uniform("u_daylight", daylight(self.client.world.time));
         // TODO: use a proper texture atlas for entities.
         self.renderer.pink_black.bind(0);
 
-        self.renderer.entity_model.draw();
+        self.renderer.entity_model.draw_instanced();
     }
 
-    fn draw_crosshair(ui: &mut UIRenderer, screen_size: Vec2) {
+    /// Draws the name tags of the players in view, and of the markers placed by plugins. The tag
+    /// of the own player is only visible in third person.
+    fn draw_name_tags(
+        &self,
+        ui: &mut UIRenderer,
+        assets: &Assets,
+        camera: &Camera,
+        config: &ClientConfig,
+    ) {
+        let player = &self.client.player;
+        let name_tag = |name: &str, position: Vec3| {
+            let text = match self.client.team_colors.get(name) {
+                Some(color) => format!("%b{:02X}{}%r", color, sanitize(name)),
+                None => sanitize(name),
+            };
+            NameTag {
+                text: text.parse().unwrap(),
+                position: position + Vec3::Y * PlayerEntity::height(),
+            }
+        };
+        // Sneaking hides the tag, but the same key makes flying players descend
+        let sneaking = player.input.sneak && !player.flying;
+        let mut tags = Vec::new();
+        if player.third_person && !sneaking {
+            tags.push(name_tag(&self.client.username, player.position));
+        }
+        tags.extend(
+            self.client
+                .world
+                .entities
+                .values()
+                .filter_map(|entity| Some(name_tag(entity.name.as_ref()?, entity.position))),
+        );
+        tags.extend(self.plugins.markers().map(|marker| NameTag {
+            text: marker.text.clone(),
+            position: marker.position,
+        }));
+
+        draw_name_tags(
+            ui,
+            assets,
+            &NameTagView {
+                world: &self.client.world,
+                eye: camera.position,
+                view_projection: camera.view_projection(),
+                screen_size: self.screen_size.as_vec2(),
+                max_distance: config.name_tag_distance(),
+            },
+            &tags,
+        );
+    }
+
+    /// Draws a grid on the planes of the mirror around the player, with lines between the blocks.
+    fn draw_mirror_planes(&self, mirror: Mirror, view: Mat4, projection: Mat4) {
+        let shader = &self.renderer.chunk_border_shader;
+        shader.use_program();
+        shader.set_uniform("u_view", view);
+        shader.set_uniform("u_projection", projection);
+        shader.set_uniform("u_color", MIRROR_PLANE_COLOR);
+
+        let player = self.client.player.position.floor();
+        let plane_center = mirror.origin.as_vec3() + Vec3::splat(0.5);
+        let extent = MIRROR_PLANE_EXTENT as f32;
+        let size = extent * 2.0;
+        for (enabled, axis) in [(mirror.x, Vec3::X), (mirror.z, Vec3::Z)] {
+            if !enabled {
+                continue;
+            }
+            // The axis along the plane, across the axis it mirrors
+            let across = Vec3::ONE - axis - Vec3::Y;
+            let corner = (player - Vec3::splat(extent)) * (Vec3::ONE - axis) + plane_center * axis;
+            for i in 0..MIRROR_PLANE_EXTENT * 2 {
+                let i = i as f32;
+                // One block tall rows, drawing the horizontal lines
+                shader.set_uniform("u_offset", corner + Vec3::Y * i);
+                shader.set_uniform("u_scale", across * size + Vec3::Y);
+                self.renderer.cube_wireframe.draw();
+                // One block wide columns, drawing the vertical lines
+                shader.set_uniform("u_offset", corner + across * i);
+                shader.set_uniform("u_scale", across + Vec3::Y * size);
+                self.renderer.cube_wireframe.draw();
+            }
+        }
+    }
+
+    /// Focuses the depth of field on the targeted block, or far away if there is none.
+    fn depth_of_field(&self) -> DepthOfField {
+        let eye = self.client.player.eye_camera();
+        let focus_distance = eye
+            .cast_ray(&self.client.world, DOF_FOCUS_RANGE)
+            .map(|(position, _)| eye.position.distance(position.as_vec3() + Vec3::splat(0.5)))
+            .unwrap_or(DOF_FOCUS_RANGE);
+
+        DepthOfField {
+            focus_distance,
+            near: self.camera.near,
+            far: self.camera.far,
+        }
+    }
+
+    /// Draws the crosshair, which is faded when nothing is in reach, split and tinted when
+    /// pointing at an entity and framed by four dots when pointing at a block that can be
+    /// interacted with.
+    fn draw_crosshair(
+        ui: &mut UIRenderer,
+        screen_size: Vec2,
+        style: CrosshairStyle,
+        mut color: Vec4,
+        target: CrosshairTarget,
+    ) {
         let center = screen_size / 2.0;
 
         let hs = CROSSHAIR_SIZE / 2.0;
@@ -320,93 +756,460 @@ impl SinglePlayer {
         let h_rect = [center - Vec2::new(hs, ht), center + Vec2::new(hs, ht)];
         let v_rect = [center - Vec2::new(ht, hs), center + Vec2::new(ht, hs)];
 
+        let mut rects = match (style, target) {
+            (CrosshairStyle::Cross | CrosshairStyle::OutlinedCross, CrosshairTarget::Entity) => {
+                let gap = CROSSHAIR_ENTITY_GAP / 2.0;
+                vec![
+                    [center - Vec2::new(hs, ht), center + Vec2::new(-gap, ht)],
+                    [center + Vec2::new(gap, -ht), center + Vec2::new(hs, ht)],
+                    [center - Vec2::new(ht, hs), center + Vec2::new(ht, -gap)],
+                    [center + Vec2::new(-ht, gap), center + Vec2::new(ht, hs)],
+                ]
+            }
+            (CrosshairStyle::Cross | CrosshairStyle::OutlinedCross, _) => vec![h_rect, v_rect],
+            (CrosshairStyle::Dot, _) => {
+                let hd = CROSSHAIR_DOT_SIZE / 2.0;
+                vec![[center - Vec2::splat(hd), center + Vec2::splat(hd)]]
+            }
+        };
+        match target {
+            CrosshairTarget::None => color.w *= 0.5,
+            CrosshairTarget::Block => {}
+            CrosshairTarget::Interactable(_) => {
+                for corner in [
+                    Vec2::ONE,
+                    Vec2::NEG_ONE,
+                    Vec2::new(1.0, -1.0),
+                    Vec2::new(-1.0, 1.0),
+                ] {
+                    let dot = center + corner * hs * 0.8;
+                    rects.push([dot - Vec2::splat(ht), dot + Vec2::splat(ht)]);
+                }
+            }
+            CrosshairTarget::Entity => {
+                color = color
+                    .truncate()
+                    .lerp(CROSSHAIR_ENTITY_TINT, 0.7)
+                    .extend(color.w);
+            }
+        }
+
+        if style == CrosshairStyle::OutlinedCross {
+            // The outline is drawn on a lower layer so the cross itself stays on top.
+            let outline_color = Vec4::new(0.0, 0.0, 0.0, color.w);
+            for [min, max] in &rects {
+                ui.add_command(DrawCommand::Quad {
+                    rect: [
+                        *min - Vec2::splat(CROSSHAIR_OUTLINE),
+                        *max + Vec2::splat(CROSSHAIR_OUTLINE),
+                    ],
+                    uv_rect: DEFAULT_UV_RECT,
+                    mode: UIRenderMode::Color(outline_color),
+                    layer: -1,
+                });
+            }
+        }
+
+        for rect in rects {
+            ui.add_command(DrawCommand::Quad {
+                rect,
+                uv_rect: DEFAULT_UV_RECT,
+                mode: UIRenderMode::Color(color),
+                layer: 0,
+            });
+        }
+    }
+
+    /// Draws what right clicking the targeted block does to the right of the crosshair.
+    fn draw_interact_hint(ui: &mut UIRenderer, assets: &Assets, screen_size: Vec2, hint: &str) {
+        let text = assets.translations.interact_hint(hint);
+        let params = ColorlessTextParams::default();
+        let size = assets.font.measure_component(&text, params);
+        let position = screen_size / 2.0
+            + Vec2::new(CROSSHAIR_SIZE / 2.0 + INTERACT_HINT_MARGIN, -size.y / 2.0);
+        for mut cmd in assets.font.text_component(&text, params) {
+            match &mut cmd {
+                DrawCommand::Quad { rect, .. } => {
+                    rect[0] += position;
+                    rect[1] += position;
+                }
+                DrawCommand::Mesh { vertices, .. } => {
+                    for v in vertices {
+                        v.position += position.extend(0.0);
+                    }
+                }
+            }
+            ui.add_command(cmd);
+        }
+    }
+
+    /// Draws a bar below the crosshair showing how far the targeted block is broken.
+    fn draw_break_progress(ui: &mut UIRenderer, screen_size: Vec2, progress: f32) {
+        let min = screen_size / 2.0 + Vec2::new(-BREAK_BAR_SIZE.x / 2.0, CROSSHAIR_SIZE);
+        let max = min + BREAK_BAR_SIZE;
         ui.add_command(DrawCommand::Quad {
-            rect: h_rect,
+            rect: [min, max],
             uv_rect: DEFAULT_UV_RECT,
-            mode: UIRenderMode::Color(CROSSHAIR_COLOR),
+            mode: UIRenderMode::Color(Vec4::new(0.0, 0.0, 0.0, 0.6)),
             layer: 0,
         });
-
         ui.add_command(DrawCommand::Quad {
-            rect: v_rect,
+            rect: [
+                min + Vec2::ONE,
+                Vec2::new(
+                    min.x + 1.0 + (BREAK_BAR_SIZE.x - 2.0) * progress.min(1.0),
+                    max.y - 1.0,
+                ),
+            ],
             uv_rect: DEFAULT_UV_RECT,
-            mode: UIRenderMode::Color(CROSSHAIR_COLOR),
-            layer: 0,
+            mode: UIRenderMode::Color(Vec4::ONE),
+            layer: 1,
         });
     }
 
-    fn draw_chat(
+    /// Draws the recently placed blocks in a circle around the center of the screen, with the
+    /// pointed at block highlighted and its tooltip shown.
+    fn draw_recent_palette(
+        ui: &mut UIRenderer,
+        assets: &Assets,
+        screen_size: Vec2,
+        recent_blocks: &[ItemId],
+        palette: &PaletteGUI,
+    ) {
+        let center = screen_size / 2.0;
+        let hovered = palette.hovered(recent_blocks.len());
+        let step = std::f32::consts::TAU / recent_blocks.len() as f32;
+        for (i, &item) in recent_blocks.iter().enumerate() {
+            let angle = i as f32 * step - std::f32::consts::FRAC_PI_2;
+            let position = center + Vec2::from_angle(angle) * PALETTE_RADIUS;
+            let color = if hovered == Some(i) {
+                Vec4::new(1.0, 1.0, 1.0, 0.5)
+            } else {
+                Vec4::new(0.0, 0.0, 0.0, 0.5)
+            };
+            ui.add_command(DrawCommand::Quad {
+                rect: [
+                    position - INVENTORY_SLOT_SIZE / 2.0,
+                    position + INVENTORY_SLOT_SIZE / 2.0,
+                ],
+                uv_rect: DEFAULT_UV_RECT,
+                mode: UIRenderMode::Color(color),
+                layer: 0,
+            });
+            let stack = ItemStack::new(item, 1);
+            for cmd in INVENTORY_ITEMS.stack_commands(stack, assets, position, ui) {
+                ui.add_command(cmd);
+            }
+            if hovered == Some(i) {
+                for cmd in ItemRenderer::tooltip_commands(stack, assets, position) {
+                    ui.add_command(cmd);
+                }
+            }
+        }
+    }
+
+    /// Returns the part of a chat message at a point of the screen, if there is one.
+    fn chat_part_at(
         &self,
+        point: Vec2,
+        assets: &Assets,
+        config: &ClientConfig,
+    ) -> Option<&TextComponentPart> {
+        let layout = self.ui.chat_layout.as_ref()?;
+        let size = *self.ui.hud_sizes.get(&HudElement::Chat)?;
+        let placement = config.hud_placement(HudElement::Chat);
+        let [min, _] = placement.rect(size, self.screen_size.as_vec2());
+        let local = (point - min) / placement.scale - Vec2::splat(5.0);
+
+        let params = ColorlessTextParams {
+            font_size: 24.0,
+            word_wrap_width: Some(700.0),
+        };
+        let mut top = 0.0;
+        for message in &layout.messages {
+            let height = assets.font.measure_component(message, params).y;
+            if (top..top + height).contains(&local.y) {
+                // Parts are found by measuring the text up to them, so only parts before the line
+                // wraps can be clicked or hovered
+                let mut left = 0.0;
+                for end in 1..=message.parts.len() {
+                    let prefix = TextComponent {
+                        parts: message.parts[..end].to_vec(),
+                    };
+                    let right = assets
+                        .font
+                        .measure_component(&prefix, ColorlessTextParams::default())
+                        .x;
+                    if (left..right).contains(&local.x) {
+                        return message.parts.get(end - 1);
+                    }
+                    left = right;
+                }
+                return None;
+            }
+            top += height;
+        }
+        None
+    }
+
+    fn draw_chat(
+        &mut self,
         ui: &mut UIRenderer,
         layout_ctx: &crate::render::ui::widgets::LayoutContext,
         assets: &Assets,
+        config: &ClientConfig,
     ) {
         let messages = self.get_recent_messages();
-        let message_size = measure_messages(&assets.font, &messages, 24.0);
-
-        let hotbar_size = self.ui.hotbar.size_hint(layout_ctx);
-
-        let mut messages_start_y =
-            self.screen_size.y as f32 - message_size.y - 10.0 - hotbar_size.y - 15.0;
-
-        if self.client.gui.chat().is_some() {
-            messages_start_y -= 24.0 + 10.0;
+        let font_atlas = assets.font.atlas().handle();
+        if self
+            .ui
+            .chat_layout
+            .as_ref()
+            .is_none_or(|layout| layout.messages != messages || layout.font_atlas != font_atlas)
+        {
+            self.ui.chat_layout = Some(ChatLayout {
+                size: measure_messages(&assets.font, &messages, 24.0),
+                commands: text_messages(&assets.font, &messages, 24.0, Vec2::ZERO),
+                messages,
+                font_atlas,
+            });
+        }
+        let Some(chat_layout) = &self.ui.chat_layout else {
+            return;
+        };
+        let message_size = chat_layout.size;
+
+        // The messages with the input line below them while chatting, relative to the top left
+        // corner of the chat
+        let mut size = message_size + Vec2::splat(10.0);
+        let chatting = self.client.gui.chat().is_some();
+        // The scrollbar is only shown while there are lines out of view
+        let line_count = self.client.messages.line_count();
+        let scrollbar = self
+            .client
+            .gui
+            .chat()
+            .filter(|_| line_count > CHAT_LINES)
+            .map(|gui| {
+                let track = [
+                    Vec2::new(size.x, 0.0),
+                    Vec2::new(size.x + CHAT_SCROLLBAR_WIDTH, size.y),
+                ];
+                let thumb_height = size.y * CHAT_LINES as f32 / line_count as f32;
+                let thumb_bottom = size.y * (1.0 - gui.scroll as f32 / line_count as f32);
+                let thumb = [
+                    Vec2::new(track[0].x, thumb_bottom - thumb_height),
+                    Vec2::new(track[1].x, thumb_bottom),
+                ];
+                (track, thumb)
+            });
+        if let Some((track, _)) = scrollbar {
+            size = size.max(track[1]);
+        }
+        let mut input_rect = [Vec2::ZERO; 2];
+        if chatting {
             let label_size = self.ui.chat_input_label.size_hint(layout_ctx);
+            input_rect[0] = Vec2::new(0.0, size.y + 5.0);
+            input_rect[1] = input_rect[0] + label_size + Vec2::splat(10.0);
+            self.ui
+                .chat_input_label
+                .layout(&crate::render::ui::widgets::LayoutContext {
+                    max_size: layout_ctx.max_size,
+                    cursor: input_rect[0] + Vec2::splat(5.0),
+                    assets,
+                });
+            size = size.max(input_rect[1]);
+        }
+        self.ui.hud_sizes.insert(HudElement::Chat, size);
+
+        let placement = config.hud_placement(HudElement::Chat);
+        let input_label = &self.ui.chat_input_label;
+        placement.draw(ui, size, self.screen_size.as_vec2(), |ui| {
             ui.add_command(DrawCommand::Quad {
-                rect: [
-                    Vec2::new(
-                        5.0,
-                        self.screen_size.y as f32 - label_size.y - 15.0 - hotbar_size.y - 10.0,
-                    ),
-                    Vec2::new(
-                        5.0 + label_size.x + 10.0,
-                        self.screen_size.y as f32 - 5.0 - hotbar_size.y - 15.0,
-                    ),
-                ],
+                rect: [Vec2::ZERO, message_size + Vec2::splat(10.0)],
                 uv_rect: DEFAULT_UV_RECT,
-                mode: UIRenderMode::Color(Vec4::new(0.0, 0.0, 0.0, 0.5)),
+                mode: UIRenderMode::Color(assets.theme.backdrop()),
                 layer: 0,
             });
-            self.ui.chat_input_label.draw(ui, assets);
-        }
+            let offset = Vec2::splat(5.0);
+            for mut cmd in chat_layout.commands.iter().cloned() {
+                if let DrawCommand::Quad { rect, .. } = &mut cmd {
+                    rect[0] += offset;
+                    rect[1] += offset;
+                } else if let DrawCommand::Mesh { vertices, .. } = &mut cmd {
+                    for vertex in vertices {
+                        vertex.position += offset.extend(0.0);
+                    }
+                }
+                ui.add_command(cmd);
+            }
+            if let Some((track, thumb)) = scrollbar {
+                for (rect, color) in [
+                    (track, assets.theme.backdrop()),
+                    (thumb, Vec4::new(1.0, 1.0, 1.0, 0.7)),
+                ] {
+                    ui.add_command(DrawCommand::Quad {
+                        rect,
+                        uv_rect: DEFAULT_UV_RECT,
+                        mode: UIRenderMode::Color(color),
+                        layer: 0,
+                    });
+                }
+            }
+            if chatting {
+                ui.add_command(DrawCommand::Quad {
+                    rect: input_rect,
+                    uv_rect: DEFAULT_UV_RECT,
+                    mode: UIRenderMode::Color(assets.theme.backdrop()),
+                    layer: 0,
+                });
+                input_label.draw(ui, assets);
+            }
+        });
+    }
 
-        ui.add_command(crate::render::ui::uirenderer::DrawCommand::Quad {
-            rect: [
-                Vec2::new(5.0, messages_start_y - 5.0),
-                Vec2::new(
-                    5.0 + message_size.x + 10.0,
-                    messages_start_y + message_size.y + 5.0,
-                ),
-            ],
-            uv_rect: DEFAULT_UV_RECT,
-            mode: crate::render::ui::uirenderer::UIRenderMode::Color(Vec4::new(0.0, 0.0, 0.0, 0.5)),
-            layer: 0,
+    /// Draws the recent deaths, the newest on top, each lined up with the right edge of the kill
+    /// feed.
+    fn draw_kill_feed(&mut self, ui: &mut UIRenderer, assets: &Assets, config: &ClientConfig) {
+        let params = ColorlessTextParams::default();
+        let entries: Vec<_> = self
+            .client
+            .kill_feed
+            .iter()
+            .rev()
+            .map(|(entry, _)| (entry, assets.font.measure_component(entry, params)))
+            .collect();
+        let width =
+            entries.iter().map(|(_, size)| size.x).fold(0.0, f32::max) + KILL_FEED_PADDING * 2.0;
+        let height = entries
+            .iter()
+            .map(|(_, size)| size.y + KILL_FEED_PADDING * 3.0)
+            .sum::<f32>()
+            - KILL_FEED_PADDING;
+        let size = Vec2::new(width, height.max(0.0));
+        self.ui.hud_sizes.insert(HudElement::KillFeed, size);
+
+        let placement = config.hud_placement(HudElement::KillFeed);
+        placement.draw(ui, size, self.screen_size.as_vec2(), |ui| {
+            let mut top = 0.0;
+            for (entry, entry_size) in entries {
+                let position = Vec2::new(
+                    width - KILL_FEED_PADDING - entry_size.x,
+                    top + KILL_FEED_PADDING,
+                );
+                ui.add_command(DrawCommand::Quad {
+                    rect: [
+                        position - Vec2::splat(KILL_FEED_PADDING),
+                        position + entry_size + Vec2::splat(KILL_FEED_PADDING),
+                    ],
+                    uv_rect: DEFAULT_UV_RECT,
+                    mode: UIRenderMode::Color(assets.theme.backdrop()),
+                    layer: 0,
+                });
+                for mut cmd in assets.font.text_component(entry, params) {
+                    match &mut cmd {
+                        DrawCommand::Quad { rect, .. } => {
+                            rect[0] += position;
+                            rect[1] += position;
+                        }
+                        DrawCommand::Mesh { vertices, .. } => {
+                            for v in vertices {
+                                v.position += position.extend(0.0);
+                            }
+                        }
+                    }
+                    ui.add_command(cmd);
+                }
+                top += entry_size.y + KILL_FEED_PADDING * 3.0;
+            }
         });
-        for cmd in text_messages(
-            &assets.font,
-            &messages,
-            24.0,
-            Vec2::new(10.0, messages_start_y),
-        ) {
-            ui.add_command(cmd);
+    }
+
+    /// Draws a bar filled as far as the player has health left, with the amount on top.
+    /// Draws the text the plugins show on the HUD, one line below the other.
+    fn draw_plugin_overlays(&self, ui: &mut UIRenderer, assets: &Assets) {
+        let params = ColorlessTextParams::default();
+        for overlay in self.plugins.overlays() {
+            let mut position = overlay.position;
+            for line in &overlay.lines {
+                for mut cmd in assets.font.text_component(line, params) {
+                    match &mut cmd {
+                        DrawCommand::Quad { rect, .. } => {
+                            rect[0] += position;
+                            rect[1] += position;
+                        }
+                        DrawCommand::Mesh { vertices, .. } => {
+                            for v in vertices {
+                                v.position += position.extend(0.0);
+                            }
+                        }
+                    }
+                    ui.add_command(cmd);
+                }
+                position.y += assets.font.measure_component(line, params).y;
+            }
         }
-        ui.finish();
+    }
+
+    fn draw_health_bar(&mut self, ui: &mut UIRenderer, assets: &Assets, config: &ClientConfig) {
+        self.ui
+            .hud_sizes
+            .insert(HudElement::Health, HEALTH_BAR_SIZE);
+        let health = self.client.player.health;
+        let text: TextComponent = format!("{} / {}", health.ceil(), MAX_HEALTH)
+            .parse()
+            .unwrap();
+        let params = ColorlessTextParams::default();
+        let text_size = assets.font.measure_component(&text, params);
+
+        let placement = config.hud_placement(HudElement::Health);
+        placement.draw(ui, HEALTH_BAR_SIZE, self.screen_size.as_vec2(), |ui| {
+            ui.add_command(DrawCommand::Quad {
+                rect: [Vec2::ZERO, HEALTH_BAR_SIZE],
+                uv_rect: DEFAULT_UV_RECT,
+                mode: UIRenderMode::Color(assets.theme.backdrop()),
+                layer: 0,
+            });
+            let filled = (health / MAX_HEALTH).clamp(0.0, 1.0);
+            ui.add_command(DrawCommand::Quad {
+                rect: [Vec2::ZERO, HEALTH_BAR_SIZE * Vec2::new(filled, 1.0)],
+                uv_rect: DEFAULT_UV_RECT,
+                mode: UIRenderMode::Color(HEALTH_BAR_COLOR),
+                layer: 0,
+            });
+            let position = (HEALTH_BAR_SIZE - text_size) / 2.0;
+            for mut cmd in assets.font.text_component(&text, params) {
+                match &mut cmd {
+                    DrawCommand::Quad { rect, .. } => {
+                        rect[0] += position;
+                        rect[1] += position;
+                    }
+                    DrawCommand::Mesh { vertices, .. } => {
+                        for v in vertices {
+                            v.position += position.extend(0.0);
+                        }
+                    }
+                }
+                ui.add_command(cmd);
+            }
+        });
     }
 }
 
 impl super::Scene for SinglePlayer {
     fn handle_event(&mut self, gl: &Arc<glow::Context>, event: &sdl2::event::Event) {
         if let sdl2::event::Event::Window {
-            win_event: sdl2::event::WindowEvent::Resized(width, height),
+            win_event: sdl2::event::WindowEvent::SizeChanged(width, height),
             ..
         } = event
         {
             self.screen_size.x = *width as u32;
             self.screen_size.y = *height as u32;
+            self.camera.resize(self.screen_size);
             unsafe {
                 gl.viewport(0, 0, *width, *height);
             }
-            self.renderer.framebuffer.resize(*width, *height);
+            // The framebuffer is resized in `render` where the render scale is known.
         }
     }
 
@@ -415,7 +1218,6 @@ impl super::Scene for SinglePlayer {
             gl,
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
@@ -429,13 +1231,15 @@ impl super::Scene for SinglePlayer {
             assets,
         };
 
-        window.set_title("Mineplace3D - Single Player").unwrap();
-        sdl_ctx
-            .mouse()
-            .set_relative_mouse_mode(self.client.gui.none());
         self.timer += ctx.delta_time;
         self.ui.fps_timer += ctx.delta_time;
 
+        {
+            let config = config.read().unwrap();
+            self.client.player.fov = config.fov();
+            self.client.world.render_distance = config.render_distance();
+        }
+
         let fps = 1.0 / ctx.delta_time;
         self.fps_entry(fps);
         if self.ui.fps_timer > 0.5 {
@@ -443,40 +1247,217 @@ impl super::Scene for SinglePlayer {
             self.ui.fps_timer = 0.0;
         }
 
-        if ctx.keyboard.pressed.contains(&sdl2::keyboard::Keycode::F6) {
+        let world_name = &self.world_name;
+        let title = if self.world_loaded {
+            format!("Mineplace3D - {} - {} FPS", world_name, self.ui.fps as u32)
+        } else {
+            let progress = self
+                .client
+                .world
+                .load_progress(self.client.player.position.as_ivec3());
+            self.world_loaded = progress >= 1.0;
+            format!(
+                "Mineplace3D - {} - Loading world {}%",
+                world_name,
+                (progress * 100.0) as u32
+            )
+        };
+        update_title(window, &title);
+
+        let bindings = config.read().unwrap().key_bindings();
+        if bindings.pressed(ctx.keyboard, Action::ReloadAssets) {
             return vec![SceneAction::ReloadAssets];
         }
 
+        if bindings.pressed(ctx.keyboard, Action::Panorama)
+            && self.client.gui.none()
+            && self.panorama_capture.is_none()
+        {
+            log::info!("Capturing a panorama");
+            self.panorama_capture = Some(PanoramaCapture::default());
+        }
+
+        if self.client.gui.chat().is_some()
+            && ctx.mouse.pressed.contains(&sdl2::mouse::MouseButton::Left)
+            && let Some(part) =
+                self.chat_part_at(ctx.mouse.position, assets, &config.read().unwrap())
+        {
+            if let Some(message) = part.click.clone() {
+                self.client.send_chat(message);
+            } else if let Some(suggestion) = part.suggest.clone()
+                && let CurrentGUI::Chat(gui) = &mut self.client.gui
+            {
+                gui.message = suggestion;
+                gui.ghost = None;
+            }
+        }
+
         {
             let _p = self.renderer.profiler.start_scope("client_update");
 
-            self.client
-                .send_input(ctx, ctx.delta_time, config.read().unwrap().sensitivity());
+            self.client.send_input(
+                ctx,
+                ctx.delta_time,
+                &config.read().unwrap(),
+                &assets.translations,
+            );
+            self.client.connection.flush();
+
+            let in_game = self.client.gui.none() && !self.client.player.is_dead();
+            let player = &self.client.player;
+            self.plugins
+                .update(ctx.delta_time, ctx.keyboard, in_game, || {
+                    PluginEvent::State {
+                        position: player.position.to_array(),
+                        yaw: player.yaw,
+                        pitch: player.pitch,
+                        health: player.health,
+                        time: self.client.world.time,
+                    }
+                });
+            if in_game {
+                for (button, right) in [
+                    (sdl2::mouse::MouseButton::Left, false),
+                    (sdl2::mouse::MouseButton::Right, true),
+                ] {
+                    if ctx.mouse.pressed.contains(&button)
+                        && let Some((position, face)) = player
+                            .eye_camera()
+                            .cast_ray(&self.client.world, physics::REACH_DISTANCE)
+                        && let Some((block, _)) = self.client.world.get_block_at(position)
+                    {
+                        let block = block_registry().get(block).unwrap().ident;
+                        self.plugins
+                            .send(&PluginEvent::block_click(position, face, block, right));
+                    }
+                }
+            }
+
+            let velocity = self.client.player.velocity;
+            let moving = velocity.x.hypot(velocity.z) > PLAYER_MOVING_SPEED;
+            self.renderer.player_animator.update(ctx.delta_time, moving);
+            self.renderer.player_animator.head_pitch = self.client.player.pitch;
+            let entities = &self.client.world.entities;
+            let animators = &mut self.renderer.entity_animators;
+            animators.retain(|entity_id, _| entities.contains_key(entity_id));
+            for (entity_id, entity) in entities {
+                let animator = animators.entry(*entity_id).or_default();
+                animator.update(ctx.delta_time, entity.moving);
+                animator.head_pitch = entity.pitch;
+            }
+
+            let loaded_distance = self
+                .client
+                .world
+                .loaded_distance(self.client.player.position);
+            let t = 1.0 - (-FOG_ADJUST_SPEED * ctx.delta_time).exp();
+            self.renderer.fog_distance += (loaded_distance - self.renderer.fog_distance) * t;
+            if self.client.gui.none() && ctx.mouse.pressed.contains(&sdl2::mouse::MouseButton::Left)
+            {
+                self.renderer.player_animator.attack();
+            }
+
+            if self.client.gui.none() && !config.read().unwrap().controls_shown() {
+                self.ui.controls = controls_ui(&bindings);
+                self.client.gui = CurrentGUI::Controls;
+                let mut config = config.write().unwrap();
+                config.controls_shown = Some(true);
+                config.save();
+            }
+
+            if self.client.gui.controls() {
+                self.ui.controls.update(ctx);
+                self.ui
+                    .controls
+                    .layout(&crate::render::ui::widgets::LayoutContext {
+                        max_size: Vec2::new(self.screen_size.x as f32, self.screen_size.y as f32),
+                        cursor: Vec2::ZERO,
+                        assets,
+                    });
+                if self
+                    .ui
+                    .controls
+                    .get_widget::<Button>(2)
+                    .is_some_and(|btn| btn.is_released())
+                {
+                    self.client.gui = CurrentGUI::None;
+                }
+            }
+
+            if self.client.gui.death() {
+                if let Some(label) = self.ui.death_screen.get_widget_mut::<Label>(1) {
+                    label.text = self
+                        .client
+                        .death_message
+                        .as_ref()
+                        .map(|message| message.plain_text())
+                        .unwrap_or_default();
+                }
+                self.ui.death_screen.update(ctx);
+                self.ui
+                    .death_screen
+                    .layout(&crate::render::ui::widgets::LayoutContext {
+                        max_size: Vec2::new(self.screen_size.x as f32, self.screen_size.y as f32),
+                        cursor: Vec2::ZERO,
+                        assets,
+                    });
+                if self
+                    .ui
+                    .death_screen
+                    .get_widget::<Button>(2)
+                    .is_some_and(|btn| btn.is_released())
+                {
+                    self.client.respawn();
+                }
+                if self
+                    .ui
+                    .death_screen
+                    .get_widget::<Button>(3)
+                    .is_some_and(|btn| btn.is_released())
+                {
+                    Self::save_world(
+                        &mut self.client.connection,
+                        self.world_path.as_deref(),
+                        &self.world_settings,
+                    );
+                    return vec![SceneAction::Pop];
+                }
+            }
 
             if !self.client.gui.pause_menu() {
-                if ctx.keyboard.pressed.contains(&sdl2::keyboard::Keycode::F3) {
+                if bindings.pressed(ctx.keyboard, Action::DebugInfo) {
                     self.ui.debug_opened = !self.ui.debug_opened;
                 }
 
-                if let Err(reason) = self
+                if let Err(disconnection) = self
                     .client
-                    .receive_state(&mut self.renderer.particle_system)
+                    .receive_state(&mut self.renderer.particle_system, &assets.translations)
                 {
-                    log::error!("Connection lost: {}", reason);
-                    log::info!("Saving world...");
-                    std::fs::create_dir_all(&self.world_path)
-                        .expect("Failed to create world directory");
-                    self.client
-                        .connection
-                        .server
-                        .save()
-                        .expect("Failed to save world");
-                    return vec![
-                        SceneAction::ShowError(crate::scenes::SceneActionError::Unexpected(
-                            format!("Connection lost: {}", reason),
-                        )),
-                        SceneAction::Pop,
-                    ];
+                    Self::save_world(
+                        &mut self.client.connection,
+                        self.world_path.as_deref(),
+                        &self.world_settings,
+                    );
+                    let error = match disconnection {
+                        Disconnection::Lost(reason) => {
+                            log::error!("Connection lost: {}", reason);
+                            SceneActionError::Unexpected(format!("Connection lost: {}", reason))
+                        }
+                        Disconnection::Kicked(reason) => SceneActionError::Kicked(reason),
+                        Disconnection::Transferred(address) => {
+                            let source = self.world_path.is_none().then_some(&self.world_name);
+                            return Self::transfer(
+                                source,
+                                self.password.as_deref(),
+                                address,
+                                gl,
+                                assets,
+                                window.size(),
+                                config,
+                            );
+                        }
+                    };
+                    return vec![SceneAction::ShowError(error), SceneAction::Pop];
                 }
             } else {
                 self.ui.pause_screen.update(ctx);
@@ -501,22 +1482,36 @@ impl super::Scene for SinglePlayer {
                     .get_widget::<Button>(1)
                     .is_some_and(|btn| btn.is_released())
                 {
-                    log::info!("Saving world...");
-                    std::fs::create_dir_all(&self.world_path)
-                        .expect("Failed to create world directory");
-                    self.client
-                        .connection
-                        .server
-                        .save()
-                        .expect("Failed to save world");
-
-                    return vec![SceneAction::Pop];
+                    self.client.gui = CurrentGUI::HudEditor;
                 }
                 if self
                     .ui
                     .pause_screen
                     .get_widget::<Button>(2)
                     .is_some_and(|btn| btn.is_released())
+                {
+                    // The keys may have been rebound in the options since it was built
+                    self.ui.controls = controls_ui(&bindings);
+                    self.client.gui = CurrentGUI::Controls;
+                }
+                if self
+                    .ui
+                    .pause_screen
+                    .get_widget::<Button>(3)
+                    .is_some_and(|btn| btn.is_released())
+                {
+                    Self::save_world(
+                        &mut self.client.connection,
+                        self.world_path.as_deref(),
+                        &self.world_settings,
+                    );
+                    return vec![SceneAction::Pop];
+                }
+                if self
+                    .ui
+                    .pause_screen
+                    .get_widget::<Button>(4)
+                    .is_some_and(|btn| btn.is_released())
                 {
                     return vec![SceneAction::Pop];
                 }
@@ -539,11 +1534,12 @@ impl super::Scene for SinglePlayer {
             }
 
             if self.tick_acc >= tick_time {
-                self.tick_acc = self.tick_acc % tick_time;
+                self.tick_acc %= tick_time;
             }
         }
-
-        let hotbar_size = self.ui.hotbar.size_hint(&layout_ctx);
+        self.client
+            .world
+            .advance_time(ctx.delta_time, self.tick_rate);
 
         if let Some(chat) = self.client.gui.chat() {
             self.ui.chat_input_label.text = chat
@@ -555,16 +1551,6 @@ impl super::Scene for SinglePlayer {
             self.ui.chat_input_label.text = "".to_string();
         }
         self.ui.chat_input_label.update(ctx);
-        self.ui
-            .chat_input_label
-            .layout(&crate::render::ui::widgets::LayoutContext {
-                max_size: Vec2::new(self.screen_size.x as f32, self.screen_size.y as f32),
-                cursor: Vec2::new(
-                    10.0,
-                    self.screen_size.y as f32 - 24.0 - 10.0 - hotbar_size.y - 15.0,
-                ),
-                assets,
-            });
         if self.client.gui.inventory() {
             self.ui.inventory.update(ctx);
             let inventory_size = self.ui.inventory.size_hint(&layout_ctx);
@@ -576,22 +1562,63 @@ impl super::Scene for SinglePlayer {
                     assets,
                 });
         }
+        if self.client.gui.creative_picker() {
+            self.ui.creative_picker.update(ctx);
+            let picker_size = self.ui.creative_picker.size_hint(&layout_ctx);
+            self.ui
+                .creative_picker
+                .layout(&crate::render::ui::widgets::LayoutContext {
+                    max_size: picker_size,
+                    cursor: self.screen_size.as_vec2() / 2.0 - picker_size / 2.0,
+                    assets,
+                });
+        }
+        if self.client.gui.container()
+            && let Some(container) = &self.client.container
+        {
+            let id = container.borrow().id;
+            if self
+                .ui
+                .container
+                .as_ref()
+                .is_none_or(|(shown, _)| *shown != id)
+            {
+                self.ui.container = Some((id, container_ui(container, &layout_ctx)));
+            }
+            let (_, container_ui) = self.ui.container.as_mut().unwrap();
+            container_ui.update(ctx);
+            let container_size = container_ui.size_hint(&layout_ctx);
+            container_ui.layout(&crate::render::ui::widgets::LayoutContext {
+                max_size: container_size,
+                cursor: self.screen_size.as_vec2() / 2.0 - container_size / 2.0,
+                assets,
+            });
+        } else {
+            self.ui.container = None;
+        }
         self.ui.hotbar.update(ctx);
         let hotbar_size = self.ui.hotbar.size_hint(&layout_ctx);
         self.ui
             .hotbar
             .layout(&crate::render::ui::widgets::LayoutContext {
                 max_size: hotbar_size,
-                cursor: Vec2::new(
-                    self.screen_size.x as f32 / 2.0 - hotbar_size.x / 2.0,
-                    self.screen_size.y as f32 - hotbar_size.y - 10.0,
-                ),
+                cursor: Vec2::ZERO,
                 assets,
             });
-        let unloaded = self.client.world.unload_chunks(self.client.player.position);
+        if self.client.gui.hud_editor() {
+            self.ui.hud_editor.get_or_insert_default().update(
+                ctx,
+                &mut config.write().unwrap(),
+                &self.ui.hud_sizes,
+                self.screen_size.as_vec2(),
+            );
+        } else if self.ui.hud_editor.take().is_some() {
+            config.read().unwrap().save();
+        }
+        let unloaded = self.client.world.unload_chunks(self.client.view_center());
         for pos in unloaded {
             if let Some(mesh) = self.renderer.chunk_meshes.remove(&pos) {
-                self.renderer.chunk_mesh_pool.push(mesh);
+                recycle_chunk_mesh(&mut self.renderer.chunk_mesh_pool, mesh);
             }
         }
         {
@@ -606,8 +1633,13 @@ impl super::Scene for SinglePlayer {
                     &mut self.client.world,
                     &mut self.renderer.chunk_meshes,
                     &mut self.renderer.chunk_mesh_pool,
-                    &assets.block_textures,
-                    &assets.block_models,
+                    assets,
+                    config.read().unwrap().chunk_updates_per_frame(),
+                    self.client
+                        .player
+                        .position
+                        .as_ivec3()
+                        .div_euclid(IVec3::splat(CHUNK_SIZE as i32)),
                 );
             }
         }
@@ -621,7 +1653,7 @@ impl super::Scene for SinglePlayer {
         gl: &Arc<glow::Context>,
         ui: &mut UIRenderer,
         assets: &Arc<Assets>,
-        _config: &Arc<RwLock<super::options::ClientConfig>>,
+        config: &Arc<RwLock<super::options::ClientConfig>>,
     ) {
         let layout_ctx = crate::render::ui::widgets::LayoutContext {
             max_size: Vec2::new(self.screen_size.x as f32, self.screen_size.y as f32),
@@ -630,11 +1662,37 @@ impl super::Scene for SinglePlayer {
         };
 
         let player_model_mat = self.client.player.model();
-        let view = self.client.player.view(&self.client.world);
-        let projection = self
-            .client
-            .player
-            .projection(self.screen_size.x as f32 / self.screen_size.y as f32);
+        self.client.place_camera(&mut self.camera);
+        // While capturing a panorama every frame draws one of its faces instead
+        if let Some(capture) = &self.panorama_capture {
+            capture.aim(&mut self.camera);
+        }
+        let camera = self.camera;
+        let view = camera.view();
+        let projection = camera.projection();
+        let (sky_color, _) = fog(
+            self.renderer.fog_distance,
+            camera.position,
+            self.client.world.time,
+        );
+
+        // The world is drawn at a fraction (or multiple) of the window resolution and scaled to
+        // fit in the postprocess pass, so the UI stays crisp regardless of the render scale.
+        let render_size = (self.screen_size.as_vec2() * config.read().unwrap().render_scale())
+            .round()
+            .max(Vec2::ONE)
+            .as_ivec2();
+        if self.renderer.framebuffer.size() != render_size.into() {
+            self.renderer
+                .framebuffer
+                .resize(render_size.x, render_size.y);
+            self.renderer
+                .postprocessor
+                .resize(render_size.x, render_size.y);
+            unsafe {
+                gl.viewport(0, 0, self.screen_size.x as i32, self.screen_size.y as i32);
+            }
+        }
 
         unsafe {
             // SETUP
@@ -646,7 +1704,7 @@ impl super::Scene for SinglePlayer {
             gl.front_face(glow::CCW);
             gl.enable(glow::BLEND);
             gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
-            gl.clear_color(0.7, 0.7, 0.9, 1.0);
+            gl.clear_color(sky_color.x, sky_color.y, sky_color.z, 1.0);
             gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
 
             // WORLD
@@ -654,16 +1712,43 @@ impl super::Scene for SinglePlayer {
             {
                 let _fb = self.renderer.framebuffer.guard();
 
-                gl.clear_color(0.7, 0.7, 0.9, 1.0);
+                gl.clear_color(sky_color.x, sky_color.y, sky_color.z, 1.0);
                 gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
 
+                // SKY
+
+                self.renderer.sky_renderer.draw(
+                    gl,
+                    projection,
+                    view,
+                    self.client.world.time,
+                    self.client.sky_seed,
+                    config.read().unwrap().sunrise_yaw(),
+                );
+
                 // CHUNKS
 
-                self.draw_chunks(gl, assets, view, projection);
+                self.draw_chunks(gl, assets, &camera);
 
-                // PLAYER
+                // SHADOWS
 
-                self.draw_entities(view, projection, player_model_mat);
+                {
+                    let _p = self.renderer.profiler.start_scope("shadows");
+                    self.renderer.shadow_renderer.draw(
+                        gl,
+                        &self.client.world,
+                        &[ShadowCaster {
+                            position: self.client.player.position,
+                            width: PlayerEntity::width(),
+                        }],
+                        view,
+                        projection,
+                    );
+                }
+
+                // ENTITIES
+
+                self.draw_entities(assets, view, projection, player_model_mat);
 
                 // PARTICLES
 
@@ -682,6 +1767,7 @@ impl super::Scene for SinglePlayer {
                     view,
                     self.client.player.position,
                     self.timer,
+                    daylight(self.client.world.time),
                 );
 
                 // DEBUG - CHUNK BORDERS
@@ -694,6 +1780,9 @@ impl super::Scene for SinglePlayer {
                     self.renderer
                         .chunk_border_shader
                         .set_uniform("u_projection", projection);
+                    self.renderer
+                        .chunk_border_shader
+                        .set_uniform("u_color", Vec4::new(1.0, 1.0, 0.0, 1.0));
 
                     for pos in self.renderer.chunk_meshes.keys() {
                         let world_pos = pos.as_vec3() * CHUNK_SIZE as f32;
@@ -703,8 +1792,57 @@ impl super::Scene for SinglePlayer {
                             .set_uniform("u_offset", world_pos);
                         self.renderer
                             .chunk_border_shader
-                            .set_uniform("u_scale", CHUNK_SIZE as f32);
+                            .set_uniform("u_scale", Vec3::splat(CHUNK_SIZE as f32));
+
+                        self.renderer.cube_wireframe.draw();
+                    }
+                }
+
+                // MIRROR PLANES
+
+                if let Some(mirror) = self.client.mirror {
+                    self.draw_mirror_planes(mirror, view, projection);
+                }
+
+                // MEASUREMENT
+
+                if let Some((min, max)) = self
+                    .client
+                    .measurement
+                    .as_ref()
+                    .and_then(|measurement| measurement.bounds())
+                {
+                    let shader = &self.renderer.chunk_border_shader;
+                    shader.use_program();
+                    shader.set_uniform("u_view", view);
+                    shader.set_uniform("u_projection", projection);
+                    shader.set_uniform("u_color", MEASUREMENT_COLOR);
+                    shader.set_uniform(
+                        "u_offset",
+                        min.as_vec3() - Vec3::splat(BUILD_PREVIEW_INFLATE),
+                    );
+                    shader.set_uniform(
+                        "u_scale",
+                        (max - min + IVec3::ONE).as_vec3()
+                            + Vec3::splat(BUILD_PREVIEW_INFLATE * 2.0),
+                    );
+                    self.renderer.cube_wireframe.draw();
+                }
 
+                // BUILD PREVIEW
+
+                if let Some(drag) = &self.client.build_drag {
+                    let shader = &self.renderer.chunk_border_shader;
+                    shader.use_program();
+                    shader.set_uniform("u_view", view);
+                    shader.set_uniform("u_projection", projection);
+                    shader.set_uniform("u_color", BUILD_PREVIEW_COLOR);
+                    shader.set_uniform("u_scale", Vec3::splat(1.0 + BUILD_PREVIEW_INFLATE * 2.0));
+                    for position in self.client.drag_positions(drag) {
+                        shader.set_uniform(
+                            "u_offset",
+                            position.as_vec3() - Vec3::splat(BUILD_PREVIEW_INFLATE),
+                        );
                         self.renderer.cube_wireframe.draw();
                     }
                 }
@@ -715,48 +1853,182 @@ impl super::Scene for SinglePlayer {
             gl.disable(glow::CULL_FACE);
             gl.depth_mask(false);
 
-            self.renderer.postprocess_shader.use_program();
-            self.renderer.postprocess_shader.set_uniform("u_texture", 0);
+            let mut settings = PostProcessSettings::from_config(&config.read().unwrap());
+            settings.color_grading = self.world_settings.color_grading();
+            if self.client.player.cinematic && config.read().unwrap().depth_of_field() {
+                settings.depth_of_field = Some(self.depth_of_field());
+            }
             self.renderer
-                .postprocess_shader
-                .set_uniform("u_time", self.timer);
-            self.renderer.framebuffer.textures()[0].bind(0);
-            self.renderer.fullscreen_quad.draw();
+                .postprocessor
+                .run(&self.renderer.framebuffer, &settings, self.timer);
+
+            if let Some(capture) = &mut self.panorama_capture
+                && capture.capture_face(gl, self.screen_size.into())
+            {
+                self.panorama_capture.take().unwrap().save();
+                self.client.messages.push(
+                    format!(
+                        "Captured a panorama for the title screen, saving it to %b7F{}%r",
+                        sanitize(&panorama_dir().display().to_string())
+                    )
+                    .parse()
+                    .unwrap(),
+                );
+            }
 
             // UI
 
             gl.clear(glow::DEPTH_BUFFER_BIT);
             gl.disable(glow::DEPTH_TEST);
 
+            // Cinematic mode hides the HUD unless a GUI is open, flythroughs always hide it
+            let hud_visible = (!self.client.player.cinematic || !self.client.gui.none())
+                && self.client.flythrough.is_none();
+
+            // NAME TAGS
+
+            if hud_visible {
+                self.draw_name_tags(ui, assets, &camera, &config.read().unwrap());
+            }
+
             // CROSSHAIR
 
-            Self::draw_crosshair(ui, self.screen_size.as_vec2());
+            if hud_visible {
+                let config = config.read().unwrap();
+                let target = if self.client.gui.none() {
+                    self.client.crosshair_target()
+                } else {
+                    CrosshairTarget::Block
+                };
+                Self::draw_crosshair(
+                    ui,
+                    self.screen_size.as_vec2(),
+                    config.crosshair_style(),
+                    config.crosshair_color(),
+                    target,
+                );
+                if let CrosshairTarget::Interactable(hint) = target
+                    && self.client.break_progress <= 0.0
+                {
+                    Self::draw_interact_hint(ui, assets, self.screen_size.as_vec2(), hint);
+                }
+                if self.client.break_progress > 0.0 {
+                    Self::draw_break_progress(
+                        ui,
+                        self.screen_size.as_vec2(),
+                        self.client.break_progress,
+                    );
+                }
+            }
 
             // CHAT MESSAGES
 
-            self.draw_chat(ui, &layout_ctx, assets);
+            if hud_visible {
+                let config = config.read().unwrap();
+                self.draw_chat(ui, &layout_ctx, assets, &config);
+                if self.client.gui.chat().is_some()
+                    && let Some(hover) = self
+                        .chat_part_at(self.mouse_pos, assets, &config)
+                        .and_then(|part| part.hover.as_deref())
+                {
+                    for cmd in ItemRenderer::hover_tooltip_commands(hover, assets, self.mouse_pos) {
+                        ui.add_command(cmd);
+                    }
+                }
+            }
+
+            // ACTIONBAR
+
+            if hud_visible && let Some(message) = self.client.actionbar() {
+                let params = ColorlessTextParams::default();
+                let size = assets.font.measure_component(&message, params);
+                let hotbar_size = self.ui.hotbar.size_hint(&layout_ctx);
+                let [hotbar_min, hotbar_max] = config
+                    .read()
+                    .unwrap()
+                    .hud_placement(HudElement::Hotbar)
+                    .rect(hotbar_size, self.screen_size.as_vec2());
+                let position = Vec2::new(
+                    (hotbar_min.x + hotbar_max.x - size.x) / 2.0,
+                    hotbar_min.y - size.y - ACTIONBAR_MARGIN,
+                );
+                for mut cmd in assets.font.text_component(&message, params) {
+                    match &mut cmd {
+                        DrawCommand::Quad { rect, .. } => {
+                            rect[0] += position;
+                            rect[1] += position;
+                        }
+                        DrawCommand::Mesh { vertices, .. } => {
+                            for v in vertices {
+                                v.position += position.extend(0.0);
+                            }
+                        }
+                    }
+                    ui.add_command(cmd);
+                }
+            }
+
+            // KILL FEED
+
+            if hud_visible {
+                self.draw_kill_feed(ui, assets, &config.read().unwrap());
+            }
+
+            // HEALTH
+
+            if hud_visible {
+                self.draw_health_bar(ui, assets, &config.read().unwrap());
+            }
+
+            // PLUGIN OVERLAYS
+
+            if hud_visible {
+                self.draw_plugin_overlays(ui, assets);
+            }
 
             // INVENTORY & HOTBAR
 
             if self.client.gui.inventory() {
                 self.ui.inventory.draw(ui, assets);
-
+            }
+            if let Some((_, container_ui)) = &self.ui.container {
+                container_ui.draw(ui, assets);
+            }
+            if self.client.gui.creative_picker() {
+                self.ui.creative_picker.draw(ui, assets);
+            }
+            if let Some(palette) = self.client.gui.recent_palette() {
+                Self::draw_recent_palette(
+                    ui,
+                    assets,
+                    self.screen_size.as_vec2(),
+                    &self.client.recent_blocks,
+                    palette,
+                );
+            }
+            if self.client.gui.inventory() || self.client.gui.container() {
                 let temp_stack = &self.client.player.inventory.borrow().inner.temp;
                 if !temp_stack.is_empty() {
                     // Draw the temp stack at the mouse position
-                    let temp_stack_commands = InventorySlot::draw_stack(
-                        *temp_stack,
-                        assets,
-                        self.mouse_pos,
-                        ui,
-                        &assets.font,
-                    );
+                    let temp_stack_commands =
+                        INVENTORY_ITEMS.stack_commands(*temp_stack, assets, self.mouse_pos, ui);
                     for cmd in temp_stack_commands {
                         ui.add_command(cmd);
                     }
                 }
             }
-            self.ui.hotbar.draw(ui, assets);
+            if hud_visible {
+                let hotbar_size = self.ui.hotbar.size_hint(&layout_ctx);
+                self.ui.hud_sizes.insert(HudElement::Hotbar, hotbar_size);
+                let hotbar = &self.ui.hotbar;
+                config
+                    .read()
+                    .unwrap()
+                    .hud_placement(HudElement::Hotbar)
+                    .draw(ui, hotbar_size, self.screen_size.as_vec2(), |ui| {
+                        hotbar.draw(ui, assets)
+                    });
+            }
 
             // DEBUG - TEXT & GRAPHS
 
@@ -764,23 +2036,67 @@ impl super::Scene for SinglePlayer {
                 let block_pos = self.client.player.position.as_ivec3();
                 let chunk = block_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
                 let chunk_local = block_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+                let preferences = config.read().unwrap().preferences();
+                let (sky_light, block_light) = self
+                    .client
+                    .world
+                    .get_light_at(block_pos)
+                    .unwrap_or_default();
+                let target = self
+                    .client
+                    .player
+                    .eye_camera()
+                    .cast_ray(&self.client.world, physics::REACH_DISTANCE)
+                    .and_then(|(pos, normal)| {
+                    let (block, state) = self.client.world.get_block_at(pos)?;
+                    let def = block_registry().get(block)?;
+                    let target_chunk = pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+                    // Faces are drawn with the light of the block in front of them
+                    let (sky_light, block_light) = self
+                        .client
+                        .world
+                        .get_light_at(pos + normal)
+                        .unwrap_or_default();
+                    Some(format!(
+                        "Looking at: {} (ID {})\nTarget: X: {} Y: {} Z: {} Chunk: X: {} Y: {} Z: {}\nState: {}\nFace light: Sky: {} Block: {}",
+                        def.ident,
+                        block.get(),
+                        pos.x,
+                        pos.y,
+                        pos.z,
+                        target_chunk.x,
+                        target_chunk.y,
+                        target_chunk.z,
+                        state,
+                        sky_light,
+                        block_light,
+                    ))
+                })
+                .unwrap_or_else(|| "Looking at: nothing".to_string());
 
                 let text = format!(
                     r#"Mineplace3D v{}
 
 {} FPS
+{}
 
-X: {:.2} Y: {:.2} Z: {:.2}
+X: {} Y: {} Z: {}
 Yaw: {:.2} Pitch: {:.2}
 
 Block: X: {} Y: {} Z: {}
 Chunk: X: {} Y: {} Z: {}
-Chunk local: X: {} Y: {} Z: {}"#,
+Chunk local: X: {} Y: {} Z: {}
+Light: Sky: {} Block: {}
+
+{}
+
+Queued chunk updates: {}"#,
                     env!("CARGO_PKG_VERSION"),
                     self.ui.fps as u32,
-                    self.client.player.position.x,
-                    self.client.player.position.y,
-                    self.client.player.position.z,
+                    preferences.format_time(self.client.world.time as u64),
+                    preferences.format_distance(self.client.player.position.x),
+                    preferences.format_distance(self.client.player.position.y),
+                    preferences.format_distance(self.client.player.position.z),
                     self.client.player.yaw,
                     self.client.player.pitch,
                     block_pos.x,
@@ -792,22 +2108,24 @@ Chunk local: X: {} Y: {} Z: {}"#,
                     chunk_local.x,
                     chunk_local.y,
                     chunk_local.z,
+                    sky_light,
+                    block_light,
+                    target,
+                    self.client.world.remesh_queue.len(),
                 );
 
-                for mut cmd in assets.font.text(&text, TextParams::default()) {
-                    match &mut cmd {
-                        DrawCommand::Quad { rect, .. } => {
-                            rect[0] += Vec2::new(10.0, 10.0);
-                            rect[1] += Vec2::new(10.0, 10.0);
-                        }
-                        DrawCommand::Mesh { vertices, .. } => {
-                            for v in vertices {
-                                v.position += Vec3::new(10.0, 10.0, 0.0);
-                            }
+                let params = TextParams::default();
+                let size = assets.font.measure_text(&text, params.without_color());
+                self.ui.hud_sizes.insert(HudElement::Debug, size);
+                config
+                    .read()
+                    .unwrap()
+                    .hud_placement(HudElement::Debug)
+                    .draw(ui, size, self.screen_size.as_vec2(), |ui| {
+                        for cmd in assets.font.text(&text, params) {
+                            ui.add_command(cmd);
                         }
-                    }
-                    ui.add_command(cmd);
-                }
+                    });
 
                 // draw the fps graph on the top right side and also show the current, average, min
                 // and max fps
@@ -902,16 +2220,173 @@ Chunk local: X: {} Y: {} Z: {}"#,
                 ui.add_command(DrawCommand::Quad {
                     rect: [Vec2::ZERO, self.screen_size.as_vec2()],
                     uv_rect: DEFAULT_UV_RECT,
-                    mode: UIRenderMode::Color(Vec4::new(0.0, 0.0, 0.0, 0.5)),
+                    mode: UIRenderMode::Color(assets.theme.backdrop()),
                     layer: -1,
                 });
 
                 self.ui.pause_screen.draw(ui, assets);
             }
+
+            // CONTROLS
+
+            if self.client.gui.controls() {
+                ui.add_command(DrawCommand::Quad {
+                    rect: [Vec2::ZERO, self.screen_size.as_vec2()],
+                    uv_rect: DEFAULT_UV_RECT,
+                    mode: UIRenderMode::Color(assets.theme.backdrop()),
+                    layer: -1,
+                });
+
+                self.ui.controls.draw(ui, assets);
+            }
+
+            // DEATH SCREEN
+
+            if self.client.gui.death() {
+                ui.add_command(DrawCommand::Quad {
+                    rect: [Vec2::ZERO, self.screen_size.as_vec2()],
+                    uv_rect: DEFAULT_UV_RECT,
+                    mode: UIRenderMode::Color(DEATH_BACKDROP),
+                    layer: -1,
+                });
+
+                self.ui.death_screen.draw(ui, assets);
+            }
+
+            // HUD EDITOR
+
+            if let Some(editor) = &self.ui.hud_editor {
+                editor.draw(
+                    ui,
+                    assets,
+                    &config.read().unwrap(),
+                    &self.ui.hud_sizes,
+                    self.screen_size.as_vec2(),
+                );
+            }
         }
 
         self.renderer.profiler.end_frame();
     }
+
+    fn captures_mouse(&self) -> bool {
+        self.client.gui.none() || self.client.gui.recent_palette().is_some()
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        if self.client.gui.pause_menu() {
+            Some(&self.ui.pause_screen)
+        } else if self.client.gui.controls() {
+            Some(&self.ui.controls)
+        } else if self.client.gui.death() {
+            Some(&self.ui.death_screen)
+        } else {
+            None
+        }
+    }
+}
+
+/// The keys of the controls overlay, next to what they do.
+/// The keys listed in the controls overlay with what they do.
+fn controls(bindings: &KeyBindings) -> Vec<(String, &'static str)> {
+    let key = |action| bindings.key(action).name();
+    vec![
+        (
+            [
+                Action::Forward,
+                Action::StrafeLeft,
+                Action::Back,
+                Action::StrafeRight,
+            ]
+            .map(key)
+            .join(" "),
+            "Move",
+        ),
+        (key(Action::Jump), "Jump"),
+        (key(Action::Sneak), "Sneak"),
+        (
+            format!("{} + {}", key(Action::Sprint), key(Action::Forward)),
+            "Sprint",
+        ),
+        ("Left Click".to_string(), "Break block"),
+        ("Right Click".to_string(), "Place block"),
+        (
+            format!("{} - {}", key(Action::Hotbar1), key(Action::Hotbar9)),
+            "Select hotbar slot",
+        ),
+        (key(Action::Inventory), "Inventory"),
+        (key(Action::CreativePicker), "Creative items"),
+        (key(Action::SwapOffhand), "Swap offhand"),
+        (key(Action::Chat), "Chat"),
+        (key(Action::Command), "Command"),
+        (key(Action::ThirdPerson), "Third person"),
+        ("Esc".to_string(), "Pause menu"),
+    ]
+}
+
+/// Builds the overlay teaching the controls, which is shown on the first launch and can be
+/// opened again from the pause menu.
+fn controls_ui(bindings: &KeyBindings) -> Column {
+    Column::new(20.0)
+        .justification(Justification::Center)
+        .with(Label::new("Controls").font_size(48.0))
+        .with(
+            Grid::new(2, 8.0, Alignment::Start, Vec4::ZERO).with_many(
+                controls(bindings)
+                    .into_iter()
+                    .flat_map(|(key, action)| [Label::new(&key), Label::new(action)]),
+            ),
+        )
+        .with(Button::new("Got it"))
+}
+
+/// Builds the screen shown while the player is dead, with the way they died below the title.
+/// Quitting saves the world if `saves` is set, like in the pause menu.
+fn death_screen_ui(saves: bool) -> Column {
+    Column::new(20.0)
+        .justification(Justification::Center)
+        .with(Label::new("You Died!").font_size(48.0))
+        .with(Label::new(""))
+        .with(Button::new("Respawn"))
+        .with(Button::new(if saves {
+            "Save and Quit"
+        } else {
+            "Disconnect"
+        }))
+}
+
+/// Builds the GUI of a container, showing its slots above the general slots of the inventory.
+fn container_ui(
+    container: &Rc<RefCell<ClientContainer>>,
+    layout_ctx: &crate::render::ui::widgets::LayoutContext,
+) -> Stack {
+    let (title, size) = {
+        let container = container.borrow();
+        (container.inner.title.clone(), container.inner.slots.len())
+    };
+    let container_col = Column::new(8.0)
+        .alignment(Alignment::Start)
+        .padding(Vec4::splat(16.0))
+        .with(Label::new(&title).font_size(36.0))
+        .with(
+            Grid::new(9, 8.0, Alignment::Center, Vec4::ZERO)
+                .with_many((0..size).map(|i| ContainerSlot::new(container, i))),
+        )
+        .with(Label::new("Inventory").font_size(36.0))
+        .with(
+            Grid::new(9, 8.0, Alignment::Center, Vec4::ZERO)
+                .with_many((size..size + 36).map(|i| ContainerSlot::new(container, i))),
+        );
+    Stack::new(Alignment::Center, Alignment::Center, 0.0)
+        .with(NineSlice::new(
+            [UVec2::new(0, 16), UVec2::new(16, 16)],
+            container_col.size_hint(layout_ctx),
+            UVec4::new(4, 4, 3, 3),
+            4,
+            0,
+            Vec4::ONE,
+        ))
+        .with(container_col)
 }
 
 fn measure_messages(font: &Font, messages: &[TextComponent], font_size: f32) -> Vec2 {
@@ -969,6 +2444,19 @@ fn text_messages(
     commands
 }
 
+/// Returns the fog color and the distance at which it fully hides the terrain, given the distance
+/// it would have above ground. Above ground the terrain fades into the horizon of the sky at the
+/// world time. Below the world floor the fog turns into a dark void fog that closes in the deeper
+/// the camera goes.
+fn fog(fog_distance: f32, camera_pos: Vec3, time: f64) -> (Vec3, f32) {
+    let void = ((WORLD_FLOOR as f32 - camera_pos.y) / VOID_FADE_DEPTH).clamp(0.0, 1.0);
+    let (_, horizon) = sky_colors(time);
+    (
+        horizon.lerp(VOID_COLOR, void),
+        fog_distance + (VOID_FOG_DISTANCE.min(fog_distance) - fog_distance) * void,
+    )
+}
+
 fn is_aabb_in_frustum(aabb_min: Vec3, aabb_max: Vec3, planes: &[Vec4; 6]) -> bool {
     for plane in planes {
         let p = Vec3::new(
@@ -995,20 +2483,6 @@ fn is_aabb_in_frustum(aabb_min: Vec3, aabb_max: Vec3, planes: &[Vec4; 6]) -> boo
     true
 }
 
-fn fullscreen_quad_ndc(gl: &Arc<glow::Context>) -> Mesh {
-    Mesh::new(
-        gl,
-        &[
-            Vec3::new(-1.0, -1.0, 0.0),
-            Vec3::new(1.0, -1.0, 0.0),
-            Vec3::new(1.0, 1.0, 0.0),
-            Vec3::new(-1.0, 1.0, 0.0),
-        ],
-        &[0, 1, 2, 2, 3, 0],
-        glow::TRIANGLES,
-    )
-}
-
 fn cube_wireframe(gl: &Arc<glow::Context>) -> Mesh {
     let vertices = [
         Vec3::new(0.0, 0.0, 0.0),
@@ -6,8 +6,13 @@ use glam::{Vec2, Vec4};
 use glow::HasContext;
 
 use crate::{
-    render::ui::{uirenderer::UIRenderer, widgets::*},
-    scenes::{Assets, SceneAction, SceneUpdateContext},
+    render::{
+        panorama::PanoramaRenderer,
+        ui::{uirenderer::UIRenderer, widgets::*},
+    },
+    resource::lazy::Lazy,
+    scenes::{Assets, SceneAction, SceneUpdateContext, options::ClientConfig},
+    update::Release,
 };
 
 static SPLASHES: std::sync::OnceLock<Vec<(&str, Vec4)>> = std::sync::OnceLock::new();
@@ -39,14 +44,27 @@ fn get_random_splash() -> (&'static str, Vec4) {
     splashes[idx]
 }
 
+/// How fast the panorama behind the title screen turns, in degrees per second.
+const PANORAMA_SPEED: f32 = 2.0;
+/// How far the panorama is looked up, in degrees.
+const PANORAMA_PITCH: f32 = 8.0;
+
 /// The [`TitleScreen`] struct represents the title screen scene.
 pub struct TitleScreen {
     container: Column,
+    /// Drawn behind the buttons if a panorama was captured, created with the first frame.
+    panorama: Option<PanoramaRenderer>,
+    timer: f32,
+    aspect_ratio: f32,
+    /// The check for a newer version, if enabled, until its result is shown.
+    update_check: Option<Lazy<Option<Release>>>,
+    /// The newer version found, whose page is opened by clicking it.
+    update: Option<Release>,
 }
 
 impl TitleScreen {
     /// Creates a new [`TitleScreen`] instance.
-    pub fn new(assets: &Arc<Assets>, window_size: (u32, u32)) -> Self {
+    pub fn new(assets: &Arc<Assets>, window_size: (u32, u32), config: &ClientConfig) -> Self {
         let (splash_text, splash_color) = get_random_splash();
 
         let (button_size, half_button_size) = if window_size.0 >= 1050 {
@@ -69,6 +87,7 @@ impl TitleScreen {
             .with(
                 Column::new(10.0)
                     .with(Button::new("Singleplayer").size(button_size))
+                    .with(Button::new("Multiplayer").size(button_size))
                     .with(
                         Row::new(10.0)
                             .with(Button::new("Options").size(half_button_size))
@@ -91,7 +110,16 @@ impl TitleScreen {
             assets,
         });
 
-        Self { container }
+        Self {
+            container,
+            panorama: None,
+            timer: 0.0,
+            aspect_ratio: window_size.0 as f32 / window_size.1.max(1) as f32,
+            update_check: config
+                .update_check()
+                .then(|| crate::update::check(config.update_url())),
+            update: None,
+        }
     }
 }
 
@@ -100,16 +128,16 @@ impl super::Scene for TitleScreen {
         let SceneUpdateContext {
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
         } = ctx;
 
-        window.set_title("Mineplace3D").unwrap();
-        sdl_ctx.mouse().set_relative_mouse_mode(false);
+        crate::abs::update_title(window, "Mineplace3D");
+        self.timer += ctx.delta_time;
 
         let new_size = window.size();
+        self.aspect_ratio = new_size.0 as f32 / new_size.1.max(1) as f32;
         let container_padding_left_right = self.container.padding.x + self.container.padding.y;
         self.container.get_widget_mut::<Row>(2).unwrap().min_size =
             Vec2::new(new_size.0 as f32 - container_padding_left_right, 0.0);
@@ -120,11 +148,15 @@ impl super::Scene for TitleScreen {
                 .unwrap()
                 .size = Vec2::new(1010.0, 80.0);
             self.container
-                .find_widget_mut::<Button>(&[1, 1, 0])
+                .find_widget_mut::<Button>(&[1, 1])
+                .unwrap()
+                .size = Vec2::new(1010.0, 80.0);
+            self.container
+                .find_widget_mut::<Button>(&[1, 2, 0])
                 .unwrap()
                 .size = Vec2::new(500.0, 80.0);
             self.container
-                .find_widget_mut::<Button>(&[1, 1, 1])
+                .find_widget_mut::<Button>(&[1, 2, 1])
                 .unwrap()
                 .size = Vec2::new(500.0, 80.0);
         } else {
@@ -133,15 +165,37 @@ impl super::Scene for TitleScreen {
                 .unwrap()
                 .size = Vec2::new(new_size.0 as f32 - 40.0, 80.0);
             self.container
-                .find_widget_mut::<Button>(&[1, 1, 0])
+                .find_widget_mut::<Button>(&[1, 1])
+                .unwrap()
+                .size = Vec2::new(new_size.0 as f32 - 40.0, 80.0);
+            self.container
+                .find_widget_mut::<Button>(&[1, 2, 0])
                 .unwrap()
                 .size = Vec2::new((new_size.0 as f32 - 40.0 - 5.0) / 2.0, 80.0);
             self.container
-                .find_widget_mut::<Button>(&[1, 1, 1])
+                .find_widget_mut::<Button>(&[1, 2, 1])
                 .unwrap()
                 .size = Vec2::new((new_size.0 as f32 - 40.0 - 5.0) / 2.0, 80.0);
         }
 
+        if let Some(check) = &mut self.update_check
+            && let Some(release) = check.get()
+        {
+            if let Some(release) = release {
+                log::info!("Update available: {} at {}", release.version, release.url);
+                if let Some(column) = self.container.get_widget_mut::<Column>(0) {
+                    column.add_widget(
+                        Button::new(&format!("Update available: {}", release.version))
+                            .size(Vec2::new(360.0, 40.0))
+                            .font_size(20.0)
+                            .color(Vec4::new(1.0, 1.0, 0.4, 1.0)),
+                    );
+                }
+                self.update = Some(release.clone());
+            }
+            self.update_check = None;
+        }
+
         self.container.update(ctx);
         self.container.layout(&LayoutContext {
             max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
@@ -149,6 +203,16 @@ impl super::Scene for TitleScreen {
             assets,
         });
 
+        if let Some(release) = &self.update
+            && self
+                .container
+                .find_widget::<Button>(&[0, 2])
+                .is_some_and(|btn| btn.is_released())
+            && let Err(e) = sdl2::url::open_url(&release.url)
+        {
+            log::error!("Failed to open {}: {}", release.url, e);
+        }
+
         if self
             .container
             .find_widget::<Button>(&[1, 0])
@@ -161,7 +225,22 @@ impl super::Scene for TitleScreen {
 
         if self
             .container
-            .find_widget::<Button>(&[1, 1, 0])
+            .find_widget::<Button>(&[1, 1])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let last_server = config.read().unwrap().last_server.clone();
+            return vec![SceneAction::Push(Box::new(
+                super::multiplayer::Multiplayer::new(
+                    assets,
+                    window.size(),
+                    last_server.as_deref().unwrap_or_default(),
+                ),
+            ))];
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 2, 0])
             .is_some_and(|btn| btn.is_released())
         {
             return vec![SceneAction::Push(Box::new(super::options::Options::new(
@@ -173,7 +252,7 @@ impl super::Scene for TitleScreen {
 
         if self
             .container
-            .find_widget::<Button>(&[1, 1, 1])
+            .find_widget::<Button>(&[1, 2, 1])
             .is_some_and(|btn| btn.is_released())
         {
             return vec![SceneAction::Quit];
@@ -193,7 +272,19 @@ impl super::Scene for TitleScreen {
             gl.clear_color(0.1, 0.1, 0.2, 1.0);
             gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
 
+            self.panorama
+                .get_or_insert_with(|| PanoramaRenderer::new(gl))
+                .draw(
+                    self.timer * PANORAMA_SPEED,
+                    PANORAMA_PITCH,
+                    self.aspect_ratio,
+                );
+
             self.container.draw(ui, assets);
         }
     }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
 }
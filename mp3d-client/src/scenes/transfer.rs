@@ -0,0 +1,200 @@
+//! The scene asking the player whether to follow a server that sends them to another server.
+//!
+//! Servers like lobbies send players on with [`mp3d_core::protocol::S2CMessage::Transfer`]. Since
+//! joining the other server sends it the password of the player, the player is asked first unless
+//! they trust the server sending them, which the "Always Allow" button does for the future.
+
+use std::sync::{Arc, RwLock};
+
+use glam::{Vec2, Vec4};
+use glow::HasContext;
+
+use crate::{
+    render::ui::{uirenderer::UIRenderer, widgets::*},
+    scenes::{
+        Assets, SceneAction, SceneActionError, SceneUpdateContext, options::ClientConfig,
+        singleplayer::SinglePlayer,
+    },
+};
+
+/// Joins the server at `address`, replacing the current scene.
+pub fn join(
+    address: &str,
+    password: String,
+    gl: &Arc<glow::Context>,
+    assets: &Arc<Assets>,
+    window_size: (u32, u32),
+    config: &ClientConfig,
+) -> Vec<SceneAction> {
+    match SinglePlayer::connect(address, password, gl, assets, window_size, config) {
+        Ok(scene) => vec![SceneAction::Replace(Box::new(scene))],
+        Err(e) => {
+            log::error!("Failed to connect to {}: {}", address, e);
+            vec![
+                SceneAction::ShowError(SceneActionError::FailedConnecting(
+                    address.to_string(),
+                    e.to_string(),
+                )),
+                SceneAction::Pop,
+            ]
+        }
+    }
+}
+
+/// The [`Transfer`] struct represents the scene asking whether to follow a transfer.
+pub struct Transfer {
+    container: Column,
+    /// The address of the server sending the player, `None` for a local world.
+    source: Option<String>,
+    address: String,
+}
+
+impl Transfer {
+    /// Creates a new [`Transfer`] instance, with the password field filled in with `password`.
+    pub fn new(
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        source: Option<String>,
+        address: String,
+        password: String,
+    ) -> Self {
+        let question = match &source {
+            Some(source) => format!("'{}' wants to send you to '{}'.", source, address),
+            None => format!("This world wants to send you to '{}'.", address),
+        };
+        let mut container = Column::new(20.0)
+            .padding(Vec4::new(0.0, 0.0, 40.0, 60.0))
+            .justification(Justification::SpaceBetween)
+            .with(Label::new("Server Transfer").font_size(48.0))
+            .with(
+                Column::new(20.0)
+                    .with(Label::new(&question))
+                    .with(
+                        Label::new("Joining it sends your password to that server.")
+                            .color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
+                    )
+                    .with(InputField::new("Password").text(&password).masked()),
+            )
+            .with(
+                Row::new(60.0)
+                    .with(Button::new("Cancel"))
+                    .with(Button::new("Join"))
+                    .with(Button::new("Always Allow")),
+            );
+
+        container.layout(&LayoutContext {
+            max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        Self {
+            container,
+            source,
+            address,
+        }
+    }
+}
+
+impl super::Scene for Transfer {
+    fn update(&mut self, ctx: &mut SceneUpdateContext) -> Vec<SceneAction> {
+        let SceneUpdateContext {
+            gl,
+            ctx,
+            window,
+            assets,
+            config,
+            ..
+        } = ctx;
+
+        crate::abs::update_title(window, "Mineplace3D - Server transfer");
+
+        self.container.update(ctx);
+        self.container.layout(&LayoutContext {
+            max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        if ctx
+            .keyboard
+            .pressed
+            .contains(&sdl2::keyboard::Keycode::Escape)
+        {
+            return vec![SceneAction::Pop];
+        }
+
+        let password = self
+            .container
+            .find_widget::<InputField>(&[1, 2])
+            .map(|input| input.text.clone())
+            .unwrap_or_default();
+        if let Some(join_button) = self.container.find_widget_mut::<Button>(&[2, 1]) {
+            join_button.disabled = password.is_empty();
+        }
+        // Local worlds have no address to remember
+        let source = self.source.clone();
+        if let Some(always_button) = self.container.find_widget_mut::<Button>(&[2, 2]) {
+            always_button.disabled = password.is_empty() || source.is_none();
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[2, 0])
+            .is_some_and(|btn| btn.is_released())
+        {
+            return vec![SceneAction::Pop];
+        }
+
+        let always = self
+            .container
+            .find_widget::<Button>(&[2, 2])
+            .is_some_and(|btn| btn.is_released());
+        if always && let Some(source) = source {
+            let mut config = config.write().unwrap();
+            config
+                .trusted_servers
+                .get_or_insert_with(Vec::new)
+                .push(source);
+            config.save();
+            log::info!("Saved config: {:?}", *config);
+        }
+
+        if always
+            || self
+                .container
+                .find_widget::<Button>(&[2, 1])
+                .is_some_and(|btn| btn.is_released())
+        {
+            return join(
+                &self.address,
+                password,
+                gl,
+                assets,
+                window.size(),
+                &config.read().unwrap(),
+            );
+        }
+
+        Vec::new()
+    }
+
+    fn render(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        ui: &mut UIRenderer,
+        assets: &Arc<Assets>,
+        _config: &Arc<RwLock<ClientConfig>>,
+    ) {
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            self.container.draw(ui, assets);
+        }
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
+}
@@ -0,0 +1,441 @@
+//! The video settings scene implementation.
+
+use std::sync::{Arc, RwLock};
+
+use glam::{Vec2, Vec4};
+use glow::HasContext;
+
+use crate::{
+    abs::WindowMode,
+    render::ui::{uirenderer::UIRenderer, widgets::*},
+    scenes::{Assets, SceneAction, SceneUpdateContext, options::ClientConfig},
+};
+
+const SETTING_SIZE: Vec2 = Vec2::new(400.0, 60.0);
+
+/// The frame rate limits that can be cycled through, `None` being unlimited.
+const FPS_LIMITS: [Option<u32>; 6] = [None, Some(30), Some(60), Some(120), Some(144), Some(240)];
+
+/// The chunk update budgets that can be cycled through.
+const CHUNK_UPDATE_BUDGETS: [usize; 5] = [4, 8, 12, 24, 48];
+
+/// The fields of view that can be cycled through, in degrees.
+const FOVS: [f32; 6] = [60.0, 70.0, 80.0, 90.0, 100.0, 110.0];
+
+/// The render distances that can be cycled through, in chunks.
+const RENDER_DISTANCES: [i32; 6] = [2, 4, 6, 8, 10, 12];
+
+/// The [`VideoSettings`] struct represents the video settings scene.
+pub struct VideoSettings {
+    container: Column,
+}
+
+impl VideoSettings {
+    /// Creates a new [`VideoSettings`] instance.
+    pub fn new(
+        assets: &Arc<Assets>,
+        window_size: (u32, u32),
+        config: &Arc<RwLock<ClientConfig>>,
+    ) -> Self {
+        let config = config.read().unwrap();
+        let mut container = Column::new(40.0)
+            .justification(Justification::Center)
+            .with(Label::new("Video Settings").font_size(48.0))
+            .with(
+                Grid::new(2, 15.0, Alignment::Center, Vec4::ZERO)
+                    .with(Button::new(&window_mode_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&resolution_text(&config)).size(SETTING_SIZE))
+                    .with(
+                        Slider::new("Render Scale", SETTING_SIZE, 0.25..=2.0)
+                            .value(config.render_scale()),
+                    )
+                    .with(
+                        Slider::new("Brightness", SETTING_SIZE, 0.5..=2.0)
+                            .value(config.brightness()),
+                    )
+                    .with(Button::new(&fxaa_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&bloom_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&tonemapping_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&depth_of_field_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&vsync_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&fps_limit_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&background_throttle_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&chunk_updates_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&fov_text(&config)).size(SETTING_SIZE))
+                    .with(Button::new(&render_distance_text(&config)).size(SETTING_SIZE)),
+            )
+            .with(Button::new("Done"));
+
+        container.layout(&LayoutContext {
+            max_size: Vec2::new(window_size.0 as f32, window_size.1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        Self { container }
+    }
+}
+
+fn window_mode_text(config: &ClientConfig) -> String {
+    format!("Window Mode: {}", config.window_mode().name())
+}
+
+fn resolution_text(config: &ClientConfig) -> String {
+    match config.resolution {
+        Some((width, height)) => format!("Resolution: {}x{}", width, height),
+        None => "Resolution: Desktop".to_string(),
+    }
+}
+
+fn fxaa_text(config: &ClientConfig) -> String {
+    format!("FXAA: {}", config.fxaa_quality().name())
+}
+
+fn bloom_text(config: &ClientConfig) -> String {
+    format!("Bloom: {}", if config.bloom() { "On" } else { "Off" })
+}
+
+fn tonemapping_text(config: &ClientConfig) -> String {
+    format!(
+        "Tone Mapping: {}",
+        if config.tonemapping() { "On" } else { "Off" }
+    )
+}
+
+fn depth_of_field_text(config: &ClientConfig) -> String {
+    format!(
+        "Depth of Field: {}",
+        if config.depth_of_field() { "On" } else { "Off" }
+    )
+}
+
+fn vsync_text(config: &ClientConfig) -> String {
+    format!("VSync: {}", if config.vsync() { "On" } else { "Off" })
+}
+
+fn fps_limit_text(config: &ClientConfig) -> String {
+    match config.fps_limit() {
+        Some(fps) => format!("Max FPS: {}", fps),
+        None => "Max FPS: Unlimited".to_string(),
+    }
+}
+
+fn background_throttle_text(config: &ClientConfig) -> String {
+    format!(
+        "Background FPS: {}",
+        if config.background_throttle() {
+            "Throttled"
+        } else {
+            "Normal"
+        }
+    )
+}
+
+fn chunk_updates_text(config: &ClientConfig) -> String {
+    format!("Chunk Updates: {}/frame", config.chunk_updates_per_frame())
+}
+
+fn fov_text(config: &ClientConfig) -> String {
+    format!("FOV: {}", config.fov())
+}
+
+fn render_distance_text(config: &ClientConfig) -> String {
+    format!("Render Distance: {} chunks", config.render_distance())
+}
+
+impl super::Scene for VideoSettings {
+    fn update(&mut self, ctx: &mut SceneUpdateContext) -> Vec<SceneAction> {
+        let SceneUpdateContext {
+            ctx,
+            window,
+            sdl_ctx,
+            assets,
+            config,
+            ..
+        } = ctx;
+
+        crate::abs::update_title(window, "Mineplace3D - Video Settings");
+
+        self.container.update(ctx);
+        self.container.layout(&LayoutContext {
+            max_size: Vec2::new(window.size().0 as f32, window.size().1 as f32),
+            cursor: Vec2::ZERO,
+            assets,
+        });
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 0])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let mode = config_guard.window_mode().next();
+            config_guard.window_mode = Some(mode);
+            config_guard.save();
+
+            if let Err(e) = crate::abs::apply_window_mode(window, mode, config_guard.resolution) {
+                log::error!("Failed to change window mode: {}", e);
+            }
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 1])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let resolutions = crate::abs::available_resolutions(window);
+            let mut config_guard = config.write().unwrap();
+            let next = match config_guard
+                .resolution
+                .and_then(|res| resolutions.iter().position(|r| *r == res))
+            {
+                Some(idx) => resolutions.get(idx + 1).copied(),
+                None if config_guard.resolution.is_none() => resolutions.first().copied(),
+                None => None,
+            };
+            config_guard.resolution = next;
+            log::info!("Changed resolution: {:?}", next);
+
+            match config_guard.window_mode() {
+                WindowMode::Windowed => {
+                    if let Some((width, height)) = next {
+                        config_guard.window_size = Some((width, height));
+                        if let Err(e) = window.set_size(width, height) {
+                            log::error!("Failed to resize window: {}", e);
+                        }
+                    }
+                }
+                WindowMode::Borderless => {}
+                WindowMode::Fullscreen => {
+                    if let Err(e) =
+                        crate::abs::apply_window_mode(window, WindowMode::Fullscreen, next)
+                    {
+                        log::error!("Failed to change resolution: {}", e);
+                    }
+                }
+            }
+            config_guard.save();
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 4])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.fxaa_quality = Some(config_guard.fxaa_quality().next());
+            log::info!("Changed FXAA quality: {:?}", config_guard.fxaa_quality());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 5])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.bloom = Some(!config_guard.bloom());
+            log::info!("Toggled bloom: {}", config_guard.bloom());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 6])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.tonemapping = Some(!config_guard.tonemapping());
+            log::info!("Toggled tone mapping: {}", config_guard.tonemapping());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 7])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.depth_of_field = Some(!config_guard.depth_of_field());
+            log::info!("Toggled depth of field: {}", config_guard.depth_of_field());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 8])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.vsync = Some(!config_guard.vsync());
+            if let Err(e) = crate::abs::set_vsync(sdl_ctx, config_guard.vsync()) {
+                log::error!("Failed to change vsync: {}", e);
+            }
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 9])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let idx = FPS_LIMITS
+                .iter()
+                .position(|limit| *limit == config_guard.fps_limit())
+                .map(|i| (i + 1) % FPS_LIMITS.len())
+                .unwrap_or(0);
+            config_guard.fps_limit = FPS_LIMITS[idx];
+            log::info!("Changed FPS limit: {:?}", config_guard.fps_limit());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 10])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.background_throttle = Some(!config_guard.background_throttle());
+            log::info!(
+                "Toggled background throttle: {}",
+                config_guard.background_throttle()
+            );
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 11])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let idx = CHUNK_UPDATE_BUDGETS
+                .iter()
+                .position(|budget| *budget == config_guard.chunk_updates_per_frame())
+                .map(|i| (i + 1) % CHUNK_UPDATE_BUDGETS.len())
+                .unwrap_or(0);
+            config_guard.chunk_updates_per_frame = Some(CHUNK_UPDATE_BUDGETS[idx]);
+            log::info!(
+                "Changed chunk updates per frame: {}",
+                config_guard.chunk_updates_per_frame()
+            );
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 12])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let idx = FOVS
+                .iter()
+                .position(|fov| *fov == config_guard.fov())
+                .map(|i| (i + 1) % FOVS.len())
+                .unwrap_or(0);
+            config_guard.fov = Some(FOVS[idx]);
+            log::info!("Changed FOV: {}", config_guard.fov());
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 13])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            let idx = RENDER_DISTANCES
+                .iter()
+                .position(|distance| *distance == config_guard.render_distance())
+                .map(|i| (i + 1) % RENDER_DISTANCES.len())
+                .unwrap_or(0);
+            config_guard.render_distance = Some(RENDER_DISTANCES[idx]);
+            log::info!(
+                "Changed render distance: {}",
+                config_guard.render_distance()
+            );
+        }
+
+        {
+            let config_guard = config.read().unwrap();
+            self.container
+                .find_widget_mut::<Button>(&[1, 0])
+                .unwrap()
+                .text = window_mode_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 1])
+                .unwrap()
+                .text = resolution_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 4])
+                .unwrap()
+                .text = fxaa_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 5])
+                .unwrap()
+                .text = bloom_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 6])
+                .unwrap()
+                .text = tonemapping_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 7])
+                .unwrap()
+                .text = depth_of_field_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 8])
+                .unwrap()
+                .text = vsync_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 9])
+                .unwrap()
+                .text = fps_limit_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 10])
+                .unwrap()
+                .text = background_throttle_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 11])
+                .unwrap()
+                .text = chunk_updates_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 12])
+                .unwrap()
+                .text = fov_text(&config_guard);
+            self.container
+                .find_widget_mut::<Button>(&[1, 13])
+                .unwrap()
+                .text = render_distance_text(&config_guard);
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[2])
+            .is_some_and(|btn| btn.is_released())
+        {
+            let mut config_guard = config.write().unwrap();
+            config_guard.render_scale =
+                Some(self.container.find_widget::<Slider>(&[1, 2]).unwrap().value);
+            config_guard.brightness =
+                Some(self.container.find_widget::<Slider>(&[1, 3]).unwrap().value);
+            config_guard.save();
+
+            log::info!("Saved config: {:?}", *config_guard);
+
+            return vec![SceneAction::Pop];
+        }
+
+        Vec::new()
+    }
+
+    fn render(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        ui: &mut UIRenderer,
+        assets: &Arc<Assets>,
+        _config: &Arc<RwLock<ClientConfig>>,
+    ) {
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            self.container.draw(ui, assets);
+        }
+    }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
+}
@@ -1,16 +1,143 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
 use glam::{Vec2, Vec4};
 use glow::HasContext;
+use mp3d_core::{
+    saving::GENERATOR_VERSION,
+    server::Server,
+    world::{
+        chunk::CHUNK_SIZE,
+        difficulty::Difficulty,
+        generation::{Generator, structure::StructureData},
+    },
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    render::ui::{uirenderer::UIRenderer, widgets::*},
+    abs::Texture,
+    render::{
+        postprocess::ColorGrading,
+        ui::{uirenderer::UIRenderer, widgets::*},
+    },
+    resource::lazy::Lazy,
     scenes::{Assets, SceneAction, SceneUpdateContext},
 };
 
+/// How many blocks the seed preview shows in each direction from spawn. It shows one block per
+/// pixel.
+const PREVIEW_RADIUS: i32 = 120;
+const PREVIEW_SIZE: f32 = PREVIEW_RADIUS as f32 * 2.0;
+
+/// Client side settings of a world, chosen when creating it and stored next to the save.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorldSettings {
+    pub color_grading: Option<ColorGrading>,
+}
+
+impl WorldSettings {
+    const FILE_NAME: &str = "client_settings.json";
+
+    /// Loads the settings of the world at `world_path`, using the defaults for worlds that were
+    /// created without them.
+    pub fn load(world_path: &Path) -> Self {
+        std::fs::read_to_string(world_path.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, world_path: &Path) -> std::io::Result<()> {
+        let data = serde_json::to_string_pretty(self).unwrap();
+        std::fs::write(world_path.join(Self::FILE_NAME), data)
+    }
+
+    pub fn color_grading(&self) -> ColorGrading {
+        self.color_grading.unwrap_or_default()
+    }
+}
+
 pub struct WorldCreation {
     container: Column,
     world_path: std::path::PathBuf,
+    settings: WorldSettings,
+    difficulty: Difficulty,
+    /// The seed used when the seed field is empty, picked once so the preview matches the world.
+    random_seed: i32,
+    /// The seed the preview is of, with the preview being generated for it.
+    preview_seed: Option<i32>,
+    preview: Option<Lazy<image::RgbaImage>>,
+    preview_texture: Option<Texture>,
+}
+
+/// Draws a top-down map of the terrain around spawn for the seed, shaded by height and slope with
+/// the tops of trees on it. Only the generator is used, so it works without creating the world.
+fn generate_preview(seed: i32) -> Result<image::RgbaImage, String> {
+    let generator = Generator::new(GENERATOR_VERSION, seed)?;
+    let size = PREVIEW_RADIUS as u32 * 2;
+    // One more row and column on the top left for the slope of the first pixels
+    let heights: Vec<i32> = (-PREVIEW_RADIUS - 1..PREVIEW_RADIUS)
+        .flat_map(|z| (-PREVIEW_RADIUS - 1..PREVIEW_RADIUS).map(move |x| (x, z)))
+        .map(|(x, z)| generator.surface_height(x, z))
+        .collect();
+    let height_at = |x: u32, z: u32| heights[(z * (size + 1) + x) as usize];
+
+    let mut image = image::RgbaImage::from_fn(size, size, |x, z| {
+        let height = height_at(x + 1, z + 1);
+        // Lit from the top left, so slopes facing it are brighter
+        let slope = (height - height_at(x, z)) as f32;
+        let brightness = (0.6 + (height - 15) as f32 / 150.0 + slope * 0.08).clamp(0.3, 1.2);
+        let color = Vec4::new(0.36, 0.6, 0.25, 1.0) * brightness;
+        image::Rgba(color.to_array().map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8))
+    });
+
+    // Trees in the chunks just outside still reach into the preview with their leaves
+    let chunk_radius = PREVIEW_RADIUS / CHUNK_SIZE as i32 + 1;
+    for chunk_x in -chunk_radius..=chunk_radius {
+        for chunk_z in -chunk_radius..=chunk_radius {
+            for structure in generator.structures_in_column(chunk_x, chunk_z) {
+                let (color, radius) = match structure.data {
+                    StructureData::Tree { .. } => ([30, 90, 25, 255], 2),
+                };
+                for dx in -radius..=radius {
+                    for dz in -radius..=radius {
+                        let x = structure.pos.x + dx + PREVIEW_RADIUS;
+                        let z = structure.pos.z + dz + PREVIEW_RADIUS;
+                        if (0..size as i32).contains(&x) && (0..size as i32).contains(&z) {
+                            image.put_pixel(x as u32, z as u32, image::Rgba(color));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Mark spawn
+    for offset in -3..=3 {
+        let center = PREVIEW_RADIUS as u32;
+        image.put_pixel(
+            center.wrapping_add_signed(offset),
+            center,
+            image::Rgba([255; 4]),
+        );
+        image.put_pixel(
+            center,
+            center.wrapping_add_signed(offset),
+            image::Rgba([255; 4]),
+        );
+    }
+
+    Ok(image)
+}
+
+fn color_grading_text(settings: &WorldSettings) -> String {
+    format!("Color Grading: {}", settings.color_grading().name())
+}
+
+fn difficulty_text(difficulty: Difficulty) -> String {
+    format!("Difficulty: {}", difficulty.name())
 }
 
 impl WorldCreation {
@@ -22,17 +149,38 @@ impl WorldCreation {
             .justification(Justification::SpaceBetween)
             .with(Label::new("Create New World").font_size(48.0))
             .with(
-                Column::new(20.0)
+                Row::new(20.0)
                     .with(
-                        InputField::new("World Name")
-                            .sanitize("/\\?%*:|\"<> ")
-                            .text("New_World"),
+                        Column::new(20.0)
+                            .with(
+                                InputField::new("World Name")
+                                    .sanitize("/\\?%*:|\"<> ")
+                                    .size(Vec2::new(740.0, 80.0))
+                                    .text("New_World"),
+                            )
+                            .with(
+                                Label::new(&world_path.display().to_string())
+                                    .color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
+                            )
+                            .with(
+                                Row::new(20.0)
+                                    .with(
+                                        InputField::new("Seed (optional)")
+                                            .size(Vec2::new(520.0, 80.0)),
+                                    )
+                                    .with(Button::new("Random Seed").size(Vec2::new(200.0, 80.0))),
+                            )
+                            .with(Button::new(&color_grading_text(&WorldSettings::default())))
+                            .with(Button::new(&difficulty_text(Difficulty::default()))),
                     )
                     .with(
-                        Label::new(&world_path.display().to_string())
-                            .color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
-                    )
-                    .with(InputField::new("Seed (optional)")),
+                        Column::new(10.0)
+                            .with(Picture::new(Vec2::splat(PREVIEW_SIZE)))
+                            .with(
+                                Label::new("Generating preview...")
+                                    .color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
+                            ),
+                    ),
             )
             .with(
                 Row::new(60.0)
@@ -49,6 +197,41 @@ impl WorldCreation {
         Self {
             container,
             world_path,
+            settings: WorldSettings::default(),
+            difficulty: Difficulty::default(),
+            random_seed: rand::random(),
+            preview_seed: None,
+            preview: None,
+            preview_texture: None,
+        }
+    }
+
+    /// Starts generating the preview if the seed changed, and shows it once it is done.
+    fn update_preview(&mut self, gl: &Arc<glow::Context>, seed: i32) {
+        if self.preview_seed != Some(seed) {
+            // A preview still generating for the old seed finishes on its own and is discarded
+            self.preview_seed = Some(seed);
+            self.preview = Some(Lazy::load("seed preview", move || generate_preview(seed)));
+            self.preview_texture = None;
+            if let Some(picture) = self.container.find_widget_mut::<Picture>(&[1, 1, 0]) {
+                picture.texture = None;
+            }
+            if let Some(label) = self.container.find_widget_mut::<Label>(&[1, 1, 1]) {
+                label.text = "Generating preview...".to_string();
+            }
+        }
+
+        if self.preview_texture.is_none()
+            && let Some(image) = self.preview.as_mut().and_then(|preview| preview.get())
+        {
+            let texture = Texture::new_bytes(gl, image.width(), image.height(), image.to_vec());
+            if let Some(picture) = self.container.find_widget_mut::<Picture>(&[1, 1, 0]) {
+                picture.texture = Some(texture.handle());
+            }
+            if let Some(label) = self.container.find_widget_mut::<Label>(&[1, 1, 1]) {
+                label.text = "Terrain around spawn".to_string();
+            }
+            self.preview_texture = Some(texture);
         }
     }
 }
@@ -59,14 +242,12 @@ impl super::Scene for WorldCreation {
             gl,
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
         } = ctx;
 
-        window.set_title("Mineplace3D - Create world").unwrap();
-        sdl_ctx.mouse().set_relative_mouse_mode(false);
+        crate::abs::update_title(window, "Mineplace3D - Create world");
 
         self.container.update(ctx);
         self.container.layout(&LayoutContext {
@@ -85,7 +266,7 @@ impl super::Scene for WorldCreation {
 
         self.world_path = crate::get_saves_dir().join(
             self.container
-                .find_widget::<InputField>(&[1, 0])
+                .find_widget::<InputField>(&[1, 0, 0])
                 .and_then(|input| {
                     let text = input.text.trim();
                     if text.is_empty() {
@@ -97,7 +278,7 @@ impl super::Scene for WorldCreation {
                 .unwrap_or_else(|| "New_World".to_string()),
         );
 
-        if let Some(label) = self.container.find_widget_mut::<Label>(&[1, 1]) {
+        if let Some(label) = self.container.find_widget_mut::<Label>(&[1, 0, 1]) {
             label.text = self.world_path.display().to_string();
         }
 
@@ -111,36 +292,75 @@ impl super::Scene for WorldCreation {
             return vec![SceneAction::Pop];
         }
 
-        let seed =
-            self.container
-                .find_widget::<InputField>(&[1, 2])
-                .map_or(rand::random(), |input| {
-                    let text = input.text.trim();
-                    if let Ok(num) = text.parse::<i32>() {
-                        num
-                    } else if !text.trim().is_empty() {
-                        fxhash::hash32(text.as_bytes()) as i32
-                    } else {
-                        rand::random()
-                    }
-                });
+        if self
+            .container
+            .find_widget::<Button>(&[1, 0, 2, 1])
+            .is_some_and(|btn| btn.is_released())
+            && let Some(input) = self.container.find_widget_mut::<InputField>(&[1, 0, 2, 0])
+        {
+            input.text = rand::random::<i32>().to_string();
+            input.cursor_pos = input.text.len();
+        }
+
+        let seed = self
+            .container
+            .find_widget::<InputField>(&[1, 0, 2, 0])
+            .map_or(self.random_seed, |input| {
+                let text = input.text.trim();
+                if let Ok(num) = text.parse::<i32>() {
+                    num
+                } else if !text.trim().is_empty() {
+                    fxhash::hash32(text.as_bytes()) as i32
+                } else {
+                    self.random_seed
+                }
+            });
+        self.update_preview(gl, seed);
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 0, 3])
+            .is_some_and(|btn| btn.is_released())
+        {
+            self.settings.color_grading = Some(self.settings.color_grading().next());
+            log::info!("Changed color grading: {:?}", self.settings.color_grading());
+            if let Some(button) = self.container.find_widget_mut::<Button>(&[1, 0, 3]) {
+                button.text = color_grading_text(&self.settings);
+            }
+        }
+
+        if self
+            .container
+            .find_widget::<Button>(&[1, 0, 4])
+            .is_some_and(|btn| btn.is_released())
+        {
+            self.difficulty = self.difficulty.next();
+            log::info!("Changed difficulty: {:?}", self.difficulty);
+            if let Some(button) = self.container.find_widget_mut::<Button>(&[1, 0, 4]) {
+                button.text = difficulty_text(self.difficulty);
+            }
+        }
 
         if let Some(create_button) = self.container.find_widget::<Button>(&[2, 1])
             && create_button.is_pressed()
         {
             log::info!(
-                "New world at {} with seed {}",
+                "New world at {} with seed {} on {}",
                 self.world_path.display(),
-                seed
+                seed,
+                self.difficulty.name()
             );
+            let mut server = Server::new(true, seed, self.world_path.clone());
+            server.world.difficulty = self.difficulty;
             return vec![SceneAction::Replace(Box::new(
                 super::singleplayer::SinglePlayer::new(
+                    server,
                     gl,
                     assets,
                     window.size(),
-                    seed,
                     self.world_path.clone(),
-                    config.read().unwrap().username.clone(),
+                    self.settings.clone(),
+                    &config.read().unwrap(),
                 ),
             ))];
         }
@@ -162,4 +382,8 @@ impl super::Scene for WorldCreation {
             self.container.draw(ui, assets);
         }
     }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
 }
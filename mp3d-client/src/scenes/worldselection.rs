@@ -77,14 +77,12 @@ impl super::Scene for WorldSelection {
             gl,
             ctx,
             window,
-            sdl_ctx,
             assets,
             config,
             ..
         } = ctx;
 
-        window.set_title("Mineplace3D - Select world").unwrap();
-        sdl_ctx.mouse().set_relative_mouse_mode(false);
+        crate::abs::update_title(window, "Mineplace3D - Select world");
 
         self.container
             .get_widget_mut::<Column>(1)
@@ -177,7 +175,7 @@ impl super::Scene for WorldSelection {
                 assets,
                 window.size(),
                 crate::get_saves_dir().join(world_name.clone()),
-                config.read().unwrap().username.clone(),
+                &config.read().unwrap(),
             );
             if let Ok(singleplayer_instance) = singleplayer_instance {
                 log::info!("Joining world {}", world_name);
@@ -237,4 +235,8 @@ impl super::Scene for WorldSelection {
             self.container.draw(ui, assets);
         }
     }
+
+    fn ui(&self) -> Option<&dyn Widget> {
+        Some(&self.container)
+    }
 }
@@ -0,0 +1,81 @@
+//! Checking whether a newer version of the game was released, shown on the title screen.
+//!
+//! The check downloads a release description in the format of the GitHub releases API from a
+//! configurable URL, with the `curl` command that comes with every supported system. It only
+//! runs if enabled in the options, and sends nothing but the request for that URL.
+
+use serde::Deserialize;
+
+use crate::resource::lazy::Lazy;
+
+/// The latest release of the game on GitHub.
+pub const DEFAULT_UPDATE_URL: &str =
+    "https://api.github.com/repos/Muhtasim-Rasheed/mineplace3d/releases/latest";
+
+/// How long the check waits for an answer before giving up, in seconds.
+const TIMEOUT_SECONDS: u32 = 10;
+
+/// A release newer than the running version.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    /// The version, like `v0.1.4-beta`.
+    #[serde(rename = "tag_name")]
+    pub version: String,
+    /// The page of the release.
+    #[serde(rename = "html_url")]
+    pub url: String,
+}
+
+/// Starts checking `url` for a release newer than the running version in the background. The
+/// check finishes with `None` if the latest release is not newer.
+pub fn check(url: &str) -> Lazy<Option<Release>> {
+    let url = url.to_string();
+    Lazy::load("update check", move || {
+        let output = std::process::Command::new("curl")
+            .args(["--silent", "--fail", "--location", "--max-time"])
+            .arg(TIMEOUT_SECONDS.to_string())
+            .args(["--header", "Accept: application/json", "--user-agent"])
+            .arg(format!("mineplace3d/{}", env!("CARGO_PKG_VERSION")))
+            .arg(&url)
+            .output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("Requesting {} failed with {}", url, output.status));
+        }
+        let release: Release = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid release from {}: {}", url, e))?;
+        Ok(is_newer(&release.version, env!("CARGO_PKG_VERSION")).then_some(release))
+    })
+}
+
+/// Splits a version like `v1.2.3-beta` into its numbers and its pre-release part.
+fn parse_version(version: &str) -> Option<(Vec<u32>, Option<&str>)> {
+    let version = version.trim().trim_start_matches('v');
+    let (numbers, pre_release) = match version.split_once('-') {
+        Some((numbers, pre_release)) => (numbers, Some(pre_release)),
+        None => (version, None),
+    };
+    let numbers = numbers
+        .split('.')
+        .map(|number| number.parse().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    Some((numbers, pre_release))
+}
+
+/// Returns whether `version` comes after `current`. A pre-release comes before the release with
+/// the same numbers, and versions that can't be read are never newer.
+fn is_newer(version: &str, current: &str) -> bool {
+    let (Some((numbers, pre_release)), Some((current_numbers, current_pre_release))) =
+        (parse_version(version), parse_version(current))
+    else {
+        return false;
+    };
+    match numbers.cmp(&current_numbers) {
+        std::cmp::Ordering::Equal => match (pre_release, current_pre_release) {
+            (None, Some(_)) => true,
+            (Some(pre_release), Some(current)) => pre_release > current,
+            _ => false,
+        },
+        ordering => ordering.is_gt(),
+    }
+}
@@ -1,7 +1,7 @@
 use glam::IVec3;
 
 use crate::{
-    block::{BlockId, BlockState},
+    block::{BlockId, BlockState, OnClick},
     direction::Direction,
     world::World,
 };
@@ -15,7 +15,7 @@ pub fn on_click(
     + Send
     + Sync
     + 'static,
-) -> Box<dyn Fn(BlockId, &mut World, u64, IVec3, BlockState, Direction) -> bool + Send + Sync> {
+) -> OnClick {
     Box::new(move |id, world, entity, pos, state, face| {
         if first(id, world, entity, pos, state, face) {
             return true;
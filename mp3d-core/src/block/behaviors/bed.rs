@@ -0,0 +1,23 @@
+use glam::IVec3;
+
+use crate::{
+    block::{BlockId, BlockState},
+    direction::Direction,
+    world::World,
+};
+
+/// Lies the player down to sleep if it is night. The night is skipped by the server once all
+/// players are sleeping.
+pub fn on_click(
+    _: BlockId,
+    world: &mut World,
+    player_entity_id: u64,
+    _: IVec3,
+    _: BlockState,
+    _: Direction,
+) -> bool {
+    if world.is_night() {
+        world.sleeping.insert(player_entity_id);
+    }
+    true
+}
@@ -0,0 +1,55 @@
+use glam::IVec3;
+
+use crate::{
+    block::{BlockId, BlockState, container::Container},
+    direction::Direction,
+    entity::PlayerEntity,
+    world::World,
+};
+
+/// The number of rows of slots in a chest.
+pub const CHEST_ROWS: u8 = 3;
+
+/// Opens the chest for the player, creating its contents on first use. The server sends the
+/// contents to the player once it handles the opening.
+pub fn on_click(
+    _: BlockId,
+    world: &mut World,
+    player_entity_id: u64,
+    block_pos: IVec3,
+    _: BlockState,
+    _: Direction,
+) -> bool {
+    world
+        .containers
+        .entry(block_pos)
+        .or_insert_with(|| Container::new("Chest", CHEST_ROWS));
+    world.container_opens.push((player_entity_id, block_pos));
+    true
+}
+
+/// Gives the contents of the chest to the player breaking it. The chest only breaks once
+/// everything fit into their inventory, otherwise it keeps what is left.
+pub fn on_break(
+    _: BlockId,
+    world: &mut World,
+    player_entity_id: u64,
+    block_pos: IVec3,
+    _: BlockState,
+) -> bool {
+    let Some(mut container) = world.containers.remove(&block_pos) else {
+        return true;
+    };
+    // TODO: drop the contents as item entities once they exist
+    if let Some(player) = world.get_entity_mut::<PlayerEntity>(player_entity_id) {
+        for stack in container.slots.iter_mut() {
+            player.inventory.insert(stack);
+        }
+    }
+    if container.slots.iter().all(|stack| stack.is_empty()) {
+        return true;
+    }
+    container.dirty = true;
+    world.containers.insert(block_pos, container);
+    false
+}
@@ -15,12 +15,17 @@ pub fn on_click(
     _: BlockState,
     _: Direction,
 ) -> bool {
+    if !world.game_rules.tnt_griefing {
+        return false;
+    }
+
     let radius_sq = 8 * 8;
     for x in -8..=8 {
         for y in -8..=8 {
             for z in -8..=8 {
-                if x * x + y * y + z * z <= radius_sq {
-                    let pos = block_pos + IVec3::new(x, y, z);
+                let pos = block_pos + IVec3::new(x, y, z);
+                // Containers keep their items, as there is nowhere to put them
+                if x * x + y * y + z * z <= radius_sq && !world.holds_items(pos) {
                     world.urgent_set_block_at(
                         pos,
                         *blocks::AIR,
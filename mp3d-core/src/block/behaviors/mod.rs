@@ -5,6 +5,8 @@ use crate::{
 };
 
 pub mod and_then;
+pub mod bed;
+pub mod chest;
 pub mod explode;
 pub mod facing;
 pub mod slab;
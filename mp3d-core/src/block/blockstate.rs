@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::direction::Direction;
 
 /// Struct to store the block state of a block in the world.
@@ -10,7 +12,7 @@ use crate::direction::Direction;
 /// type of the block state is stored in the lower 16 bits, and the data is stored in the upper 16
 /// bits. This allows for up to 65536 different block state types, each with up to 65536 different
 /// data values.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct BlockState(u32);
 
 impl BlockState {
@@ -140,3 +142,29 @@ impl BlockState {
         }
     }
 }
+
+// Shown by /blockinfo and the debug screen, like "stairs facing North" or "type 7, data 2" for
+// types this build doesn't know.
+impl std::fmt::Display for BlockState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_none() {
+            return write!(f, "none");
+        }
+        if let Some(half) = self.is_slab() {
+            let half = match half {
+                0 => "bottom",
+                1 => "top",
+                2 => "double",
+                _ => "invalid",
+            };
+            return write!(f, "{} slab", half);
+        }
+        if let Some(dir) = self.is_stairs() {
+            return write!(f, "stairs facing {}", dir);
+        }
+        if let Some(dir) = self.is_facing() {
+            return write!(f, "facing {}", dir);
+        }
+        write!(f, "type {}, data {}", self.state_type(), self.data())
+    }
+}
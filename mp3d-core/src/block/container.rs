@@ -0,0 +1,176 @@
+//! Containers, the block entities storing items, like the contents of a chest.
+
+use crate::{
+    block::{BlockId, blocks},
+    item::{Inventory, ItemStack},
+    saving::{Saveable, WorldLoadError, io::*},
+};
+
+/// The number of slots in a row of a container.
+pub const ROW_SLOTS: usize = 9;
+
+/// Returns whether a block stores its items in a [`Container`].
+pub fn holds_container(block: BlockId) -> bool {
+    block == *blocks::CHEST
+}
+
+/// A block entity storing rows of item stacks. It is viewed together with the general slots of
+/// the inventory of the player who opened it, so the slots of a view are the slots of the
+/// container followed by the 36 general slots of the inventory.
+#[derive(Clone, Debug)]
+pub struct Container {
+    pub title: String,
+    pub slots: Vec<ItemStack>,
+    /// Whether the contents changed since they were last sent to the players viewing them.
+    pub dirty: bool,
+}
+
+impl Container {
+    /// Makes a new empty container with the given title and number of rows.
+    pub fn new(title: impl Into<String>, rows: u8) -> Self {
+        Self {
+            title: title.into(),
+            slots: vec![ItemStack::empty(); rows as usize * ROW_SLOTS],
+            dirty: false,
+        }
+    }
+
+    /// Returns the number of rows of the container.
+    pub fn rows(&self) -> u8 {
+        (self.slots.len() / ROW_SLOTS) as u8
+    }
+
+    /// Returns the number of slots in a view of the container.
+    pub fn view_size(&self) -> usize {
+        self.slots.len() + 36
+    }
+
+    /// Gets a slot of a view of the container.
+    pub fn slot<'a>(&'a self, inventory: &'a Inventory, index: usize) -> Option<&'a ItemStack> {
+        if index < self.slots.len() {
+            self.slots.get(index)
+        } else {
+            inventory.main.get(index - self.slots.len())
+        }
+    }
+
+    /// Simulates a click on a slot of a view of the container, see [`ItemStack::click`]. A shift
+    /// click moves the whole stack between the container and the inventory instead. Returns
+    /// false if the slot doesn't exist.
+    pub fn click(
+        &mut self,
+        inventory: &mut Inventory,
+        index: usize,
+        right: bool,
+        shift: bool,
+    ) -> bool {
+        let size = self.slots.len();
+        if index >= self.view_size() {
+            return false;
+        }
+
+        if shift {
+            if index < size {
+                inventory.insert(&mut self.slots[index]);
+            } else {
+                inventory.main[index - size].move_into(&mut self.slots);
+            }
+        } else {
+            let slot = view_slot_mut(&mut self.slots, &mut inventory.main, index);
+            slot.click(&mut inventory.temp, right);
+        }
+        self.dirty = true;
+        inventory.dirty = true;
+        true
+    }
+
+    /// Spreads the stack held by the cursor over slots of a view of the container, in the order
+    /// they were dragged over. A left drag splits the stack evenly, a right drag puts one item in
+    /// every slot. Slots that can't take the items are skipped. Returns false if nothing is held,
+    /// or if a slot doesn't exist or is given twice.
+    pub fn distribute(&mut self, inventory: &mut Inventory, slots: &[usize], right: bool) -> bool {
+        let mut seen = vec![false; self.view_size()];
+        for &index in slots {
+            match seen.get_mut(index) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return false,
+            }
+        }
+        if slots.is_empty() || inventory.temp.is_empty() {
+            return false;
+        }
+
+        let per_slot = if right {
+            1
+        } else {
+            (inventory.temp.count / slots.len() as u16).max(1)
+        };
+        for &index in slots {
+            let slot = view_slot_mut(&mut self.slots, &mut inventory.main, index);
+            slot.take_from(&mut inventory.temp, per_slot);
+        }
+        self.dirty = true;
+        inventory.dirty = true;
+        true
+    }
+
+    /// Collects items like the stack held by the cursor from the slots of a view of the
+    /// container into the held stack, until it is full. Returns false if nothing is held.
+    pub fn collect(&mut self, inventory: &mut Inventory) -> bool {
+        let temp = &mut inventory.temp;
+        if temp.is_empty() {
+            return false;
+        }
+        for slot in self.slots.iter_mut().chain(inventory.main.iter_mut()) {
+            if !slot.is_empty() && slot.can_merge(temp) {
+                let count = slot.count;
+                temp.take_from(slot, count);
+            }
+        }
+        self.dirty = true;
+        inventory.dirty = true;
+        true
+    }
+}
+
+/// Gets a slot of a view of a container from the slots of the container and the general slots of
+/// the inventory. The index must exist.
+fn view_slot_mut<'a>(
+    slots: &'a mut [ItemStack],
+    main: &'a mut [ItemStack],
+    index: usize,
+) -> &'a mut ItemStack {
+    if index < slots.len() {
+        &mut slots[index]
+    } else {
+        &mut main[index - slots.len()]
+    }
+}
+
+impl Saveable for Container {
+    fn save(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let title_bytes = self.title.as_bytes();
+        data.push(title_bytes.len() as u8);
+        data.extend(title_bytes);
+        data.push(self.rows());
+        for slot in &self.slots {
+            data.extend_from_slice(&slot.save());
+        }
+        data
+    }
+
+    fn load<I: Iterator<Item = u8>>(data: &mut I, version: u8) -> Result<Self, WorldLoadError>
+    where
+        Self: Sized,
+    {
+        let title_len = read_u8(data, "Container::title_len")? as usize;
+        let title = read_string(data, title_len, "Container::title")?;
+        let rows = read_u8(data, "Container::rows")?;
+        let mut container = Container::new(title, rows);
+        for slot in container.slots.iter_mut() {
+            *slot = ItemStack::load(data, version)?;
+        }
+        Ok(container)
+    }
+}
@@ -8,6 +8,7 @@ use crate::define_blocks;
 
 pub mod behaviors;
 mod blockstate;
+pub mod container;
 mod registration;
 mod save_impls;
 
@@ -18,9 +19,10 @@ define_blocks! {
         visible: false,
         collision_shape: CollisionShape::None,
         interact_shape: CollisionShape::None,
+        hardness: 0.0,
     },
-    GRASS => { ident: "grass" },
-    DIRT => { ident: "dirt" },
+    GRASS => { ident: "grass", material: Material::Soil, hardness: 0.6 },
+    DIRT => { ident: "dirt", material: Material::Soil, hardness: 0.5 },
     STONE => { ident: "stone" },
     STONE_SLAB => {
         ident: "stone_slab",
@@ -41,15 +43,31 @@ define_blocks! {
         state_type: BlockState::FACING_TYPE,
         on_place: Box::new(facing::on_place),
     },
-    COBBLESTONE => { ident: "cobblestone" },
+    COBBLESTONE => { ident: "cobblestone", hardness: 2.0 },
     GRANITE => { ident: "granite" },
-    LOG => { ident: "log" },
-    LEAVES => { ident: "leaves" },
-    GLUNGUS => { ident: "glungus", on_click: Box::new(explode::on_click) },
+    LOG => { ident: "log", material: Material::Wood, hardness: 2.0 },
+    LEAVES => {
+        ident: "leaves",
+        material: Material::Plant,
+        hardness: 0.2,
+        light_opacity: 1,
+    },
+    GLUNGUS => {
+        ident: "glungus",
+        material: Material::Soil,
+        hardness: 0.5,
+        interact_hint: "explode",
+        light_emission: 10,
+        on_click: Box::new(explode::on_click),
+    },
     GLUNGUS_SLAB => {
         ident: "glungus_slab",
         collision_shape: CollisionShape::Slab,
         state_type: BlockState::SLAB_TYPE,
+        material: Material::Soil,
+        hardness: 0.5,
+        interact_hint: "explode",
+        light_emission: 10,
         on_click: and_then::on_click(
             slab::on_click,
             explode::on_click,
@@ -60,6 +78,10 @@ define_blocks! {
         ident: "glungus_stairs",
         collision_shape: CollisionShape::Stairs,
         state_type: BlockState::STAIR_TYPE,
+        material: Material::Soil,
+        hardness: 0.5,
+        interact_hint: "explode",
+        light_emission: 10,
         on_click: Box::new(explode::on_click),
         on_place: Box::new(stairs::on_place),
     },
@@ -67,6 +89,10 @@ define_blocks! {
         ident: "glungus_vslab",
         collision_shape: CollisionShape::VSlab,
         state_type: BlockState::FACING_TYPE,
+        material: Material::Soil,
+        hardness: 0.5,
+        interact_hint: "explode",
+        light_emission: 10,
         on_click: Box::new(explode::on_click),
         on_place: Box::new(facing::on_place),
     },
@@ -74,13 +100,21 @@ define_blocks! {
         ident: "short_grass",
         collision_shape: CollisionShape::None,
         interact_shape: CollisionShape::FullBlock,
+        material: Material::Plant,
+        hardness: 0.0,
+    },
+    GLASS => {
+        ident: "glass",
+        material: Material::Glass,
+        hardness: 0.3,
+        light_opacity: 0,
     },
-    GLASS => { ident: "glass" },
-    BRICKS => { ident: "bricks" },
+    BRICKS => { ident: "bricks", hardness: 2.0 },
     BRICK_SLAB => {
         ident: "brick_slab",
         collision_shape: CollisionShape::Slab,
         state_type: BlockState::SLAB_TYPE,
+        hardness: 2.0,
         on_click: Box::new(slab::on_click),
         on_place: Box::new(slab::on_place),
     },
@@ -88,16 +122,53 @@ define_blocks! {
         ident: "brick_stairs",
         collision_shape: CollisionShape::Stairs,
         state_type: BlockState::STAIR_TYPE,
+        hardness: 2.0,
         on_place: Box::new(stairs::on_place),
     },
     BRICK_VSLAB => {
         ident: "brick_vslab",
         collision_shape: CollisionShape::VSlab,
         state_type: BlockState::FACING_TYPE,
+        hardness: 2.0,
         on_place: Box::new(facing::on_place),
     },
-    GOLD => { ident: "gold" },
-    DIAMOND => { ident: "diamond" },
+    GOLD => { ident: "gold", material: Material::Metal, hardness: 3.0 },
+    DIAMOND => { ident: "diamond", material: Material::Metal, hardness: 5.0 },
+    BED => {
+        ident: "bed",
+        collision_shape: CollisionShape::Slab,
+        state_type: BlockState::SLAB_TYPE,
+        material: Material::Wood,
+        hardness: 0.2,
+        interact_hint: "sleep",
+        on_click: Box::new(bed::on_click),
+    },
+    CHEST => {
+        ident: "chest",
+        material: Material::Wood,
+        hardness: 2.5,
+        interact_hint: "open",
+        on_click: Box::new(chest::on_click),
+        on_break: Box::new(chest::on_break),
+    },
+}
+
+/// What a block is made of, which decides which tools break it faster.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+    Stone,
+    Metal,
+    Soil,
+    Wood,
+    Plant,
+    Glass,
+}
+
+impl Material {
+    /// Returns whether blocks of this material break much slower without a fitting tool.
+    pub fn needs_tool(self) -> bool {
+        matches!(self, Material::Stone | Material::Metal)
+    }
 }
 
 /// Collision shape used for collision detection.
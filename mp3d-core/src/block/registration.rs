@@ -1,7 +1,8 @@
 use glam::{IVec3, Vec3};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _, ser::Error as _};
 
 use crate::{
-    block::{BlockState, CollisionShape},
+    block::{BlockState, CollisionShape, Material},
     direction::Direction,
     registry::{Def, DefId, LazyId, Registry, RegistryToken},
     world::World,
@@ -20,11 +21,32 @@ impl DefId for BlockId {
     }
 }
 
+// IDs depend on the order blocks were registered in, so they are serialized as identifiers to stay
+// valid between different builds of the game.
+impl Serialize for BlockId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let def = block_registry()
+            .get(*self)
+            .ok_or_else(|| S::Error::custom("unregistered block ID"))?;
+        serializer.serialize_str(def.ident)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ident = String::deserialize(deserializer)?;
+        block_registry()
+            .get_id(&ident)
+            .ok_or_else(|| D::Error::custom(format!("unknown block identifier: {}", ident)))
+    }
+}
+
 pub type OnClick =
     Box<dyn Fn(BlockId, &mut World, u64, IVec3, BlockState, Direction) -> bool + Send + Sync>;
 pub type OnPlace =
     Box<dyn Fn(BlockId, &mut World, u64, IVec3, Direction) -> BlockState + Send + Sync>;
-pub type OnBreak = Box<dyn Fn(BlockId, &mut World, u64, IVec3, BlockState) + Send + Sync>;
+/// Returns whether the block breaks, so that it can refuse to.
+pub type OnBreak = Box<dyn Fn(BlockId, &mut World, u64, IVec3, BlockState) -> bool + Send + Sync>;
 
 pub struct BlockDef {
     pub visible: bool,
@@ -32,6 +54,20 @@ pub struct BlockDef {
     pub interact_shape: Option<CollisionShape>,
     pub ident: &'static str,
     pub state_type: u16,
+    /// The material of the block, which decides which tools break it faster.
+    pub material: Material,
+    /// The number of seconds it takes to break the block by hand, `0.0` breaks it instantly.
+    pub hardness: f32,
+    /// What right clicking the block does, like `"open"`, for the hint next to the crosshair.
+    /// Clients translate it with the key `hint.<interact_hint>`.
+    pub interact_hint: Option<&'static str>,
+    /// How much dimmer light gets passing through the block, from 0 for air to
+    /// [`MAX_LIGHT`](crate::world::light::MAX_LIGHT) for blocks light can't pass through. Full
+    /// blocks are opaque unless they say otherwise, other blocks let light through.
+    pub light_opacity: u8,
+    /// How much light the block gives off, from 0 to
+    /// [`MAX_LIGHT`](crate::world::light::MAX_LIGHT).
+    pub light_emission: u8,
 
     pub on_click: Option<OnClick>,
     pub on_place: Option<OnPlace>,
@@ -63,6 +99,25 @@ pub struct BlockRegistration {
 
 inventory::collect!(BlockRegistration);
 
+/// The blocks registered with [`register_block`], waiting for the registry to be initialized.
+static RUNTIME_BLOCKS: std::sync::Mutex<Vec<BlockDef>> = std::sync::Mutex::new(Vec::new());
+
+/// Registers a block that isn't defined with [`define_blocks!`](crate::define_blocks), like one
+/// read from a file at startup. This has to happen before [`init_block_registry`], and the ID of
+/// the block is found with [`BlockRegistry::get_id`] afterwards. Blocks registered this way come
+/// after the built in ones, so the built in blocks keep their IDs.
+///
+/// Clients look for the block's states at `blocks/states/<ident_path>.json` in the resource
+/// packs, see [`ident_path`](crate::registry::ident_path).
+pub fn register_block(def: BlockDef) {
+    assert!(
+        BLOCK_REGISTRY.get().is_none(),
+        "block {} registered after init_block_registry",
+        def.ident
+    );
+    RUNTIME_BLOCKS.lock().unwrap().push(def);
+}
+
 pub fn init_block_registry() {
     let mut registry = BlockRegistry::new();
 
@@ -76,6 +131,11 @@ pub fn init_block_registry() {
             .set(id)
             .unwrap_or_else(|_| panic!("block static for {} set twice", def_ident));
     }
+    for def in std::mem::take(&mut *RUNTIME_BLOCKS.lock().unwrap()) {
+        registry
+            .register(def)
+            .unwrap_or_else(|e| panic!("duplicate block ident: {}", e.ident));
+    }
 
     BLOCK_REGISTRY
         .set(registry)
@@ -92,6 +152,11 @@ macro_rules! define_blocks {
                 $(, collision_shape: $collision_shape:expr)?
                 $(, interact_shape: $interact_shape:expr)?
                 $(, state_type: $state_type:expr)?
+                $(, material: $material:expr)?
+                $(, hardness: $hardness:expr)?
+                $(, interact_hint: $interact_hint:expr)?
+                $(, light_opacity: $light_opacity:expr)?
+                $(, light_emission: $light_emission:expr)?
                 $(, on_click: $on_click:expr)?
                 $(, on_place: $on_place:expr)?
                 $(, on_break: $on_break:expr)?
@@ -113,6 +178,14 @@ macro_rules! define_blocks {
                             interact_shape: define_blocks!(@interact_shape $( $interact_shape )?),
                             ident: $ident,
                             state_type: define_blocks!(@state_type $( $state_type )?),
+                            material: define_blocks!(@material $( $material )?),
+                            hardness: define_blocks!(@hardness $( $hardness )?),
+                            interact_hint: define_blocks!(@interact_hint $( $interact_hint )?),
+                            light_opacity: define_blocks!(
+                                @light_opacity $( $light_opacity )?;
+                                $( $collision_shape )?
+                            ),
+                            light_emission: define_blocks!(@light_emission $( $light_emission )?),
                             on_click: define_blocks!(@on_click $( $on_click )?),
                             on_place: define_blocks!(@on_place $( $on_place )?),
                             on_break: define_blocks!(@on_break $( $on_break )?),
@@ -136,6 +209,26 @@ macro_rules! define_blocks {
     (@state_type $state_type:expr) => { $state_type };
     (@state_type) => { BlockState::NONE_TYPE };
 
+    (@material $material:expr) => { $material };
+    (@material) => { Material::Stone };
+
+    (@hardness $hardness:expr) => { $hardness };
+    (@hardness) => { 1.5 };
+
+    (@interact_hint $interact_hint:expr) => { Some($interact_hint) };
+    (@interact_hint) => { None };
+
+    (@light_opacity $light_opacity:expr; $( $collision_shape:expr )?) => { $light_opacity };
+    (@light_opacity ; $( $collision_shape:expr )?) => {
+        match define_blocks!(@collision_shape $( $collision_shape )?) {
+            CollisionShape::FullBlock => $crate::world::light::MAX_LIGHT,
+            _ => 0,
+        }
+    };
+
+    (@light_emission $light_emission:expr) => { $light_emission };
+    (@light_emission) => { 0 };
+
     (@on_click $on_click:expr) => { Some($on_click) };
     (@on_click) => { None };
 
@@ -22,8 +22,7 @@ impl Saveable for BlockId {
     {
         let ident_str = if version >= 0x06 {
             let ident_len = read_u8(data, "Block::ident_len")? as usize;
-            let ident_str = read_string(data, ident_len, "Block::ident")?;
-            ident_str
+            read_string(data, ident_len, "Block::ident")?
         } else {
             read_u8(data, "Block::visible")?;
             let ident_len = read_u8(data, "Block::ident_len")? as usize;
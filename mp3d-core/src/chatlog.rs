@@ -0,0 +1,112 @@
+//! Writing the chat to a log file, used by both servers and clients for their own logs.
+//!
+//! Every line is written with the local date and time in front of it. Once the log grows past its
+//! size limit it is rotated: `chat.log` becomes `chat.1.log`, `chat.1.log` becomes `chat.2.log` and
+//! so on, and only the latest [`KEPT_LOGS`] old logs are kept.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The size a log grows to before it is rotated, in bytes, unless given another limit.
+pub const DEFAULT_MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// The number of rotated logs kept next to the current one.
+pub const KEPT_LOGS: usize = 5;
+
+/// A log file the chat is appended to.
+pub struct ChatLog {
+    path: PathBuf,
+    max_size: u64,
+    /// The open log file with its size, opened on the first write.
+    file: Option<(File, u64)>,
+    /// Whether writing failed, so that the error is only logged once.
+    failed: bool,
+}
+
+impl ChatLog {
+    /// Creates a log writing to the file at `path`, rotated once it grows past `max_size` bytes.
+    /// The file and its folder are created on the first write.
+    pub fn new(path: PathBuf, max_size: u64) -> Self {
+        Self {
+            path,
+            max_size,
+            file: None,
+            failed: false,
+        }
+    }
+
+    /// Appends a line to the log, like a chat message or a command.
+    pub fn write(&mut self, line: &str) {
+        let line = format!(
+            "[{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            line.trim_end()
+        );
+        match self.append(&line) {
+            Ok(()) => self.failed = false,
+            Err(e) => {
+                if !self.failed {
+                    log::error!("Failed to write chat log {}: {}", self.path.display(), e);
+                }
+                self.failed = true;
+                self.file = None;
+            }
+        }
+    }
+
+    fn append(&mut self, line: &str) -> io::Result<()> {
+        if self
+            .file
+            .as_ref()
+            .is_some_and(|(_, size)| *size > 0 && size + line.len() as u64 > self.max_size)
+        {
+            self.file = None;
+            self.rotate()?;
+        }
+        let (file, size) = match &mut self.file {
+            Some(file) => file,
+            None => {
+                if let Some(parent) = self.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?;
+                let size = file.metadata()?.len();
+                self.file.insert((file, size))
+            }
+        };
+        file.write_all(line.as_bytes())?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Moves every log one number up, dropping the oldest, so that the current log starts empty.
+    fn rotate(&self) -> io::Result<()> {
+        let oldest = rotated_path(&self.path, KEPT_LOGS);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for n in (1..KEPT_LOGS).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                std::fs::rename(from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+/// Returns the path of the `n`th rotated log, like `chat.1.log` for `chat.log`.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, n, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, n),
+    };
+    path.with_file_name(name)
+}
@@ -0,0 +1,110 @@
+//! Implementation of the /ban command
+
+use crate::{
+    command::{
+        ArgStream, Command, CommandArg, CommandContext,
+        docs::CommandHelp,
+        parser::{GreedyString, Word},
+    },
+    server::access::DEFAULT_BAN_REASON,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct BanCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Keeps a player from joining the server, kicking them if they are online. Bans are lifted with /unban. Only operators can use this command.",
+    usage: &[
+        (
+            "/ban <player: player> [reason: text]",
+            "Ban `player`, showing them `reason` whenever they try to join.",
+        ),
+        ("/ban list", "Output the banned players and why."),
+    ],
+    examples: &[("/ban Steve Griefing", "Bans Steve for griefing.")],
+};
+
+enum Subcommand {
+    Ban(String, String),
+    List,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.peek() {
+            Some("list") => {
+                args.next();
+                Ok(Self::List)
+            }
+            Some(_) => {
+                let username = Word::parse(args)?.0;
+                let reason = GreedyString::parse(args)?.0.trim().to_string();
+                Ok(Self::Ban(username, reason))
+            }
+            None => Err("Expected a player or list but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for BanCommand {
+    fn name(&self) -> &'static str {
+        "ban"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        match sub {
+            Subcommand::Ban(username, reason) => {
+                let reason = if reason.is_empty() {
+                    DEFAULT_BAN_REASON.to_string()
+                } else {
+                    reason
+                };
+                ctx.access
+                    .rules
+                    .bans
+                    .insert(username.clone(), reason.clone());
+                if let Err(e) = ctx.access.save() {
+                    log::error!("Failed to save the access rules: {}", e);
+                }
+                if let Some(session) = ctx
+                    .sessions
+                    .values_mut()
+                    .find(|session| session.username == username)
+                {
+                    session.kick(format!("You are banned from this server: {}", reason));
+                }
+                Ok(format!("%b7FBanned {}%r", sanitize(&username))
+                    .parse()
+                    .unwrap())
+            }
+            Subcommand::List => {
+                if ctx.access.rules.bans.is_empty() {
+                    return Ok("Nobody is banned.%r".parse().unwrap());
+                }
+                let list = ctx
+                    .access
+                    .rules
+                    .bans
+                    .iter()
+                    .map(|(username, reason)| {
+                        format!("%b7F{}%r: {}", sanitize(username), sanitize(reason))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(list.parse().unwrap())
+            }
+        }
+    }
+}
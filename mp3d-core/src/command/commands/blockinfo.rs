@@ -0,0 +1,115 @@
+//! Implementation of the /blockinfo command
+
+use glam::{IVec3, Vec3};
+
+use crate::{
+    block::block_registry,
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Coord3},
+    physics::EYE_HEIGHT,
+    registry::{DEFAULT_NAMESPACE, DefId},
+    textcomponent::{TextComponent, sanitize},
+    world::chunk::CHUNK_SIZE,
+};
+
+pub struct BlockInfoCommand;
+
+/// How far away the block looked at can be, further than players can reach.
+const MAX_DISTANCE: f32 = 64.0;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Shows what a block is made of: its identifier and numeric ID, its state, its light levels and the chunk it is in.\nThe numeric ID depends on the order blocks were registered in, so it can differ between builds and with other mods.",
+    usage: &[
+        ("/blockinfo", "Inspect the block you are looking at."),
+        (
+            "/blockinfo <x: coordinate> <y: coordinate> <z: coordinate>",
+            "Inspect the block at the coordinates.",
+        ),
+    ],
+    examples: &[(
+        "/blockinfo ~ ~-1 ~",
+        "Inspects the block you are standing on.",
+    )],
+};
+
+impl Command for BlockInfoCommand {
+    fn name(&self) -> &'static str {
+        "blockinfo"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let coord3 = Option::<Coord3>::parse(&mut args)?;
+        args.ensure_empty()?;
+        let (position, forward) = match ctx.get_sender() {
+            Ok(sender) => (sender.position(), sender.forward()),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        // The face looked at, whose light is what the block is lit with when drawn
+        let (block_pos, face) = match coord3 {
+            Some(coord3) => (coord3.as_ivec3(position, forward), None),
+            None => {
+                let eye = position + Vec3::new(0.0, EYE_HEIGHT, 0.0);
+                let (block_pos, normal) = ctx
+                    .world
+                    .cast_ray(eye, forward, MAX_DISTANCE)
+                    .ok_or("You aren't looking at a block")?;
+                (block_pos, Some(block_pos + normal))
+            }
+        };
+        let (block, state) = ctx
+            .world
+            .get_block_at(block_pos)
+            .map(|(block, state)| (block, *state))
+            .ok_or("That block isn't loaded")?;
+        let def = block_registry()
+            .get(block)
+            .ok_or("That block isn't registered")?;
+        let ident = if def.ident.contains(':') {
+            def.ident.to_string()
+        } else {
+            format!("{}:{}", DEFAULT_NAMESPACE, def.ident)
+        };
+
+        let format_light = |pos: IVec3| match ctx.world.light_at(pos) {
+            Some((sky, block)) => format!("sky %b7F{}%r, block %b7F{}%r", sky, block),
+            None => "not loaded".to_string(),
+        };
+        let mut light = format_light(block_pos);
+        if let Some(face) = face {
+            light.push_str(&format!(" (in front: {})", format_light(face)));
+        }
+        let chunk = block_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local = block_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+
+        Ok(format!(
+            "%bF3Block at {}, {}, {}%r\n  Identifier: %b7F{}%r (ID {})\n  State: %b7F{}%r (bits 0x{:08X})\n  Light: {}\n  Chunk: {}, {}, {} (local {}, {}, {})",
+            block_pos.x,
+            block_pos.y,
+            block_pos.z,
+            sanitize(&ident),
+            block.get(),
+            state,
+            state.bits(),
+            light,
+            chunk.x,
+            chunk.y,
+            chunk.z,
+            local.x,
+            local.y,
+            local.z,
+        )
+        .parse()
+        .unwrap())
+    }
+}
@@ -0,0 +1,191 @@
+//! Implementation of the /camera command
+
+use glam::Vec3;
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    entity::player::PlayerEntity,
+    physics::EYE_HEIGHT,
+    protocol::{CameraKeyframe, S2CMessage},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct CameraCommand;
+
+/// How long a flythrough takes if no duration is given, in seconds.
+const DEFAULT_DURATION: f32 = 10.0;
+/// The longest a flythrough can take, in seconds.
+const MAX_DURATION: f32 = 600.0;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Records camera positions and flies smoothly through them, for trailers and tours.\nThe HUD is hidden while the flythrough plays. Pressing escape stops it early.",
+    usage: &[
+        (
+            "/camera add",
+            "Record the current position and looking direction as the next keyframe.",
+        ),
+        ("/camera remove", "Remove the last recorded keyframe."),
+        ("/camera clear", "Remove all recorded keyframes."),
+        ("/camera list", "Output all recorded keyframes."),
+        (
+            "/camera play [seconds: number]",
+            "Fly through the keyframes in `seconds` seconds, 10 by default.",
+        ),
+        ("/camera stop", "Stop the flythrough."),
+    ],
+    examples: &[(
+        "/camera play 30",
+        "Flies through the keyframes in 30 seconds.",
+    )],
+};
+
+enum Subcommand {
+    Add,
+    Remove,
+    Clear,
+    List,
+    Play(f32),
+    Stop,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("add") => Ok(Self::Add),
+            Some("remove") => Ok(Self::Remove),
+            Some("clear") => Ok(Self::Clear),
+            Some("list") => Ok(Self::List),
+            Some("play") => {
+                let duration = match args.peek() {
+                    Some(_) => f32::parse(args)?,
+                    None => DEFAULT_DURATION,
+                };
+                if !(duration > 0.0 && duration <= MAX_DURATION) {
+                    return Err(format!(
+                        "The duration must be more than 0 and at most {} seconds",
+                        MAX_DURATION
+                    ));
+                }
+                Ok(Self::Play(duration))
+            }
+            Some("stop") => Ok(Self::Stop),
+            Some(sub) => Err(format!(
+                "Unknown subcommand for camera: '{}'",
+                sanitize(sub)
+            )),
+            None => {
+                Err("Expected add, remove, clear, list, play or stop but got nothing".to_string())
+            }
+        }
+    }
+}
+
+impl Command for CameraCommand {
+    fn name(&self) -> &'static str {
+        "camera"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+        let preferences = ctx.sender_preferences();
+
+        let keyframe = match ctx.get_sender() {
+            Ok(sender) => sender
+                .as_any()
+                .downcast_ref::<PlayerEntity>()
+                .map(|player| CameraKeyframe {
+                    position: player.position + Vec3::new(0.0, EYE_HEIGHT, 0.0),
+                    yaw: player.yaw,
+                    pitch: player.pitch,
+                }),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+        let session = ctx.get_sender_session()?;
+        let keyframes = &mut session.camera_keyframes;
+
+        match sub {
+            Subcommand::Add => {
+                let keyframe = keyframe.ok_or("You aren't a player")?;
+                keyframes.push(keyframe);
+                Ok(format!(
+                    "%b7FRecorded keyframe {} at {}, {}, {}%r",
+                    keyframes.len(),
+                    preferences.format_number(keyframe.position.x, 2),
+                    preferences.format_number(keyframe.position.y, 2),
+                    preferences.format_number(keyframe.position.z, 2),
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Remove => match keyframes.pop() {
+                Some(_) => Ok(format!("%b7FRemoved keyframe {}%r", keyframes.len() + 1)
+                    .parse()
+                    .unwrap()),
+                None => Err("There are no keyframes to remove".to_string()),
+            },
+            Subcommand::Clear => {
+                let count = keyframes.len();
+                keyframes.clear();
+                Ok(format!("%b7FRemoved {} keyframes%r", count)
+                    .parse()
+                    .unwrap())
+            }
+            Subcommand::List => {
+                if keyframes.is_empty() {
+                    return Ok("There are no keyframes.%r".parse().unwrap());
+                }
+                let list = keyframes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, keyframe)| {
+                        format!(
+                            "%b7F{}%r: {}, {}, {} looking {}, {}",
+                            i + 1,
+                            preferences.format_number(keyframe.position.x, 2),
+                            preferences.format_number(keyframe.position.y, 2),
+                            preferences.format_number(keyframe.position.z, 2),
+                            preferences.format_number(keyframe.yaw, 1),
+                            preferences.format_number(keyframe.pitch, 1),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(format!("Keyframes:\n{}%r", list).parse().unwrap())
+            }
+            Subcommand::Play(duration) => {
+                if keyframes.len() < 2 {
+                    return Err("A flythrough needs at least 2 keyframes".to_string());
+                }
+                let count = keyframes.len();
+                let message = S2CMessage::PlayCameraPath {
+                    keyframes: keyframes.clone(),
+                    duration,
+                };
+                session.pending_messages.push(message);
+                Ok(format!(
+                    "%b7FPlaying {} keyframes over {} seconds, press escape to stop%r",
+                    count,
+                    preferences.format_number(duration, 1),
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Stop => {
+                session.pending_messages.push(S2CMessage::StopCameraPath);
+                Ok("%b7FStopped the flythrough%r".parse().unwrap())
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Implementation of the /claim command
+
+use crate::{
+    command::{
+        ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Coord3,
+        parser::Word,
+    },
+    server::claims::Region,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct ClaimCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Protects a region of the world from other players.\nOnly the owner of a region and the players they trust (see `/trust`) can change blocks inside of it. Regions of different players can't overlap. A region can hold at most 1048576 blocks and the regions of a player 4194304 together, except for operators.",
+    usage: &[(
+        "/claim <name: word> <x1: coordinate> <y1: coordinate> <z1: coordinate> <x2: coordinate> <y2: coordinate> <z2: coordinate>",
+        "Claim the cuboid between the two corners as `name`.",
+    )],
+    examples: &[(
+        "/claim home ~-8 ~-4 ~-8 ~8 ~12 ~8",
+        "Claims the area around the player as 'home'.",
+    )],
+};
+
+impl Command for ClaimCommand {
+    fn name(&self) -> &'static str {
+        "claim"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let username = match ctx.get_sender_session() {
+            Ok(session) => session.username.clone(),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+        let sender = ctx.get_sender()?;
+        let (pos, fwd) = (sender.position(), sender.forward());
+
+        let Word(name) = Word::parse(&mut args)?;
+        let from = Coord3::parse(&mut args)?.as_ivec3(pos, fwd);
+        let to = Coord3::parse(&mut args)?.as_ivec3(pos, fwd);
+        args.ensure_empty()?;
+
+        let region = Region::new(username.clone(), from, to);
+        let unlimited = ctx.require_operator().is_ok();
+        ctx.claims
+            .claim(name.clone(), region, unlimited)
+            .map_err(|e| sanitize(&e))?;
+        log::info!("{} claimed region '{}'", username, name);
+
+        Ok(format!(
+            "%b7FClaimed region '{}' from {}, {}, {} to {}, {}, {}%r",
+            sanitize(&name),
+            from.x.min(to.x),
+            from.y.min(to.y),
+            from.z.min(to.z),
+            from.x.max(to.x),
+            from.y.max(to.y),
+            from.z.max(to.z),
+        )
+        .parse()
+        .unwrap())
+    }
+}
@@ -1,28 +1,26 @@
 //! Implementation of the /clear command
 
 use crate::{
-    command::{ArgStream, Command, CommandContext},
+    command::{ArgStream, Command, CommandContext, docs::CommandHelp},
     entity::PlayerEntity,
     textcomponent::TextComponent,
 };
 
 pub struct ClearCommand;
 
-const DESC: &str = r#"
-`clear` - Clears the sender's inventory.
-
-Usage: `/clear`
-
-Example: `/clear` will clear the sender's inventory.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Clears the sender's inventory.",
+    usage: &[("/clear", "Clear your inventory.")],
+    examples: &[],
+};
 
 impl Command for ClearCommand {
     fn name(&self) -> &'static str {
         "clear"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(&self, ctx: &mut CommandContext, args: ArgStream) -> Result<TextComponent, String> {
@@ -0,0 +1,67 @@
+//! Implementation of the /compression command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    saving::compression::Compression,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct CompressionCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Output or modify how chunks of the world are compressed when saving. Only operators can change it.\nZstd makes the smallest saves, LZ4 saves fastest and none stores chunks as is. Chunks saved before keep their compression until they are saved again.",
+    usage: &[
+        (
+            "/compression",
+            "Output the current compression and the number of unloaded chunks kept in memory.",
+        ),
+        (
+            "/compression <none | lz4 | zstd>",
+            "Compress chunks with the given method from the next save on.",
+        ),
+    ],
+    examples: &[(
+        "/compression lz4",
+        "Makes saving faster at the cost of larger saves.",
+    )],
+};
+
+impl Command for CompressionCommand {
+    fn name(&self) -> &'static str {
+        "compression"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let method = Option::<Word>::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let Some(Word(method)) = method else {
+            return Ok(format!(
+                "Current chunk compression: {} ({} unloaded chunks cached)%r",
+                ctx.world.chunk_compression.name(),
+                ctx.world.cached_chunk_count()
+            )
+            .parse()
+            .unwrap());
+        };
+
+        ctx.require_operator()?;
+        let compression = Compression::from_name(&method)
+            .ok_or_else(|| format!("Unknown compression: '{}'", sanitize(&method)))?;
+        ctx.world.chunk_compression = compression;
+        log::info!("Set chunk compression to {}", compression.name());
+        Ok(
+            format!("Set the chunk compression to {}.%r", compression.name())
+                .parse()
+                .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,141 @@
+//! Implementation of the /debug command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct DebugCommand;
+
+/// The most problems listed in the output, the rest are only counted.
+const MAX_LISTED_PROBLEMS: usize = 10;
+/// The number of chunks listed by `/debug chunk`.
+const LISTED_CHUNKS: usize = 10;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Tools for finding problems in the world. Only operators can repair the world.",
+    usage: &[
+        (
+            "/debug verify",
+            "Check the loaded chunks, containers and entities for problems, like unknown blocks, invalid block states, containers without a chest and entities out of range.",
+        ),
+        ("/debug verify repair", "Check for problems and fix them."),
+        (
+            "/debug chunk",
+            "List the chunks taking the most time to tick, with their entities and the entity ticks put off to keep the server running smoothly.",
+        ),
+    ],
+    examples: &[(
+        "/debug verify repair",
+        "Finds and repairs problems in the loaded part of the world.",
+    )],
+};
+
+enum Subcommand {
+    Verify { repair: bool },
+    Chunk,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("verify") => match args.next() {
+                Some("repair") => Ok(Self::Verify { repair: true }),
+                Some(other) => Err(format!("Expected repair but got '{}'", sanitize(other))),
+                None => Ok(Self::Verify { repair: false }),
+            },
+            Some("chunk") => Ok(Self::Chunk),
+            Some(sub) => Err(format!("Unknown subcommand for debug: '{}'", sanitize(sub))),
+            None => Err("Expected verify or chunk but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for DebugCommand {
+    fn name(&self) -> &'static str {
+        "debug"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        match sub {
+            Subcommand::Verify { repair } => {
+                if repair {
+                    ctx.require_operator()?;
+                }
+                let problems = ctx.world.verify(repair);
+                for problem in &problems {
+                    log::warn!("World problem: {}", problem.describe());
+                }
+                if problems.is_empty() {
+                    return Ok("%b7FNo problems found%r".parse().unwrap());
+                }
+
+                let mut lines: Vec<_> = problems
+                    .iter()
+                    .take(MAX_LISTED_PROBLEMS)
+                    .map(|problem| format!("  {}", problem.describe()))
+                    .collect();
+                if problems.len() > MAX_LISTED_PROBLEMS {
+                    lines.push(format!(
+                        "  ...and {} more",
+                        problems.len() - MAX_LISTED_PROBLEMS
+                    ));
+                }
+                let action = if repair { "Repaired" } else { "Found" };
+                Ok(format!(
+                    "%b7F{} {} problems:%r\n{}%r",
+                    action,
+                    problems.len(),
+                    lines.join("\n")
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Chunk => {
+                let budget = &ctx.world.tick_budget;
+                let (ticked, loaded) = budget.random_ticked();
+                let chunks = budget.most_expensive(LISTED_CHUNKS);
+                if chunks.is_empty() {
+                    return Ok("%b7FNo chunks were ticked recently%r".parse().unwrap());
+                }
+
+                let lines: Vec<_> = chunks
+                    .iter()
+                    .map(|(pos, cost)| {
+                        let mut line = format!(
+                            "  {}, {}, {}: {:.3} ms, {} entities",
+                            pos.x,
+                            pos.y,
+                            pos.z,
+                            cost.micros / 1000.0,
+                            cost.entities
+                        );
+                        if cost.deferred > 0 {
+                            line.push_str(&format!(", {} put off", cost.deferred));
+                        }
+                        line
+                    })
+                    .collect();
+                Ok(format!(
+                    "%b7FThe most expensive chunks per tick, {} of {} chunks random ticked:%r\n{}%r",
+                    ticked,
+                    loaded,
+                    lines.join("\n")
+                )
+                .parse()
+                .unwrap())
+            }
+        }
+    }
+}
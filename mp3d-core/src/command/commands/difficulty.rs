@@ -0,0 +1,57 @@
+//! Implementation of the /difficulty command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+    world::difficulty::Difficulty,
+};
+
+pub struct DifficultyCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Output or modify the difficulty of the world. Only operators can change it.\nOn peaceful no hostile mobs spawn and players take no damage. Easy halves and hard increases the damage players take.",
+    usage: &[
+        ("/difficulty", "Output the current difficulty."),
+        (
+            "/difficulty <peaceful | easy | normal | hard>",
+            "Set the difficulty.",
+        ),
+    ],
+    examples: &[("/difficulty hard", "Makes the world harder.")],
+};
+
+impl Command for DifficultyCommand {
+    fn name(&self) -> &'static str {
+        "difficulty"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let level = Option::<Word>::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let Some(Word(level)) = level else {
+            return Ok(
+                format!("Current difficulty: {}%r", ctx.world.difficulty.name())
+                    .parse()
+                    .unwrap(),
+            );
+        };
+
+        ctx.require_operator()?;
+        let difficulty = Difficulty::from_name(&level)
+            .ok_or_else(|| format!("Unknown difficulty: '{}'", sanitize(&level)))?;
+        ctx.world.difficulty = difficulty;
+        log::info!("Set difficulty to {}", difficulty.name());
+        Ok(format!("Set the difficulty to {}.%r", difficulty.name())
+            .parse()
+            .unwrap())
+    }
+}
@@ -0,0 +1,71 @@
+//! Implementation of the /gamerule command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+    world::gamerules::GameRules,
+};
+
+pub struct GameRuleCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Output or modify the game rules of the world. Only operators can change them.\nGame rules: `keep_inventory`, `mob_spawning`, `daylight_cycle`, `fall_damage`, `tnt_griefing`.",
+    usage: &[
+        ("/gamerule", "Output all game rules."),
+        (
+            "/gamerule <name: word>",
+            "Output the value of the game rule `name`.",
+        ),
+        (
+            "/gamerule <name: word> <value: boolean>",
+            "Set the game rule `name` to `value`.",
+        ),
+    ],
+    examples: &[("/gamerule daylight_cycle false", "Stops the time of day.")],
+};
+
+impl Command for GameRuleCommand {
+    fn name(&self) -> &'static str {
+        "gamerule"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let name = Option::<Word>::parse(&mut args)?;
+        let value = Option::<bool>::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let Some(Word(name)) = name else {
+            let rules = GameRules::NAMES
+                .iter()
+                .map(|name| format!("{}: {}", name, ctx.world.game_rules.get(name).unwrap()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Ok(format!("Game rules:\n{}%r", rules).parse().unwrap());
+        };
+
+        match value {
+            Some(value) => {
+                ctx.require_operator()?;
+                ctx.world.game_rules.set(&name, value)?;
+                log::info!("Set game rule {} to {}", name, value);
+                Ok(format!("Set game rule {} to {}.%r", name, value)
+                    .parse()
+                    .unwrap())
+            }
+            None => match ctx.world.game_rules.get(&name) {
+                Some(value) => Ok(format!("Game rule {} is {}.%r", name, value)
+                    .parse()
+                    .unwrap()),
+                None => Err(format!("Unknown game rule: '{}'", sanitize(&name))),
+            },
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Implementation of the /give command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext, parser::Word},
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
     entity::PlayerEntity,
     item::item_registry,
     textcomponent::TextComponent,
@@ -9,22 +9,22 @@ use crate::{
 
 pub struct GiveCommand;
 
-const DESC: &str = r#"
-`give` - Gives an item the specified amount of times to the sender.
-
-Usage: `/give item_ident [count]`
-The item identifier a string that identifies an item. The count is optional and defaults to 1.
-
-Example: `/give grass_block 10` will give the sender 10 grass blocks.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Gives an item the specified amount of times to the sender. Only operators can use this command.",
+    usage: &[(
+        "/give <item: item> [count: integer]",
+        "Give yourself `count` of `item`, 1 by default.",
+    )],
+    examples: &[("/give grass_block 10", "Gives the sender 10 grass blocks.")],
+};
 
 impl Command for GiveCommand {
     fn name(&self) -> &'static str {
         "give"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -32,6 +32,7 @@ impl Command for GiveCommand {
         ctx: &mut CommandContext,
         mut args: ArgStream,
     ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
         let sender = match ctx.get_sender() {
             Ok(entity) => entity,
             Err(e) => {
@@ -0,0 +1,66 @@
+//! Implementation of the /heal command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    entity::MAX_HEALTH,
+    protocol::DeathCause,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct HealCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Restores the full health of a player. Dead players have to respawn instead. Only operators can heal other players.",
+    usage: &[
+        ("/heal", "Heal yourself."),
+        ("/heal <player: player>", "Heal `player`."),
+    ],
+    examples: &[("/heal Steve", "Heals Steve.")],
+};
+
+impl Command for HealCommand {
+    fn name(&self) -> &'static str {
+        "heal"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let target = Option::<Word>::parse(&mut args)?;
+        args.ensure_empty()?;
+        if target.is_some() {
+            ctx.require_operator()?;
+        }
+
+        let (entity_id, username) = match target {
+            Some(Word(username)) => ctx
+                .sessions
+                .values()
+                .find(|session| session.username == username)
+                .map(|session| (session.entity_id, username.clone()))
+                .ok_or_else(|| format!("Player '{}' isn't online", sanitize(&username)))?,
+            None => match ctx.get_sender_session() {
+                Ok(session) => (session.entity_id, session.username.clone()),
+                Err(e) => {
+                    log::error!("{}", e);
+                    return Err("You must be connected to use this command".to_string());
+                }
+            },
+        };
+
+        // The cause doesn't matter, healing never kills
+        ctx.world
+            .player_damage
+            .push((entity_id, -MAX_HEALTH, DeathCause::Killed, None));
+
+        Ok(format!("%b7FHealed {}%r", sanitize(&username))
+            .parse()
+            .unwrap())
+    }
+}
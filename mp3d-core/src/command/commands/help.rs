@@ -1,21 +1,33 @@
 //! Implementation of the /help command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext},
-    textcomponent::TextComponent,
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    textcomponent::{TextComponent, sanitize},
 };
 
 pub struct HelpCommand;
 
-const DESC: &str = r#"
-`help` - Output all commands in a page format without descriptions, or output the description of a specific command.
+/// The number of commands on a page of the command list, so that a page with its heading and
+/// the links to the other pages fits in the chat.
+const PAGE_SIZE: usize = 8;
 
-Usage: `/help <page | command>`
-  - `/help page` Output page `page`.
-  - `/help command` Output details on a command.
-
-Example: `/help 2` outputs the second page of the command list, and `/help tp` outputs the description of the `tp` command.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Lists the commands, or shows how to use one of them.",
+    usage: &[
+        (
+            "/help [page: integer]",
+            "Output a page of the command list, the first by default.",
+        ),
+        (
+            "/help <command: command>",
+            "Output the documentation of `command`.",
+        ),
+    ],
+    examples: &[
+        ("/help 2", "Outputs the second page of the command list."),
+        ("/help tp", "Outputs the documentation of the `tp` command."),
+    ],
+};
 
 enum Subcommand {
     Page(usize),
@@ -41,8 +53,8 @@ impl Command for HelpCommand {
         "help"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -56,7 +68,7 @@ impl Command for HelpCommand {
         match arg {
             Subcommand::Page(page) => {
                 let commands = ctx.command_manager.iter().collect::<Vec<_>>();
-                let total_pages = commands.len().div_ceil(20);
+                let total_pages = commands.len().div_ceil(PAGE_SIZE);
                 if page == 0 || page > total_pages {
                     return Err(format!(
                         "Invalid page number. There are {} pages.",
@@ -64,26 +76,37 @@ impl Command for HelpCommand {
                     ));
                 }
 
-                let start = (page - 1) * 20;
-                let end = usize::min(start + 20, commands.len());
-                let list = commands[start..end]
-                    .iter()
-                    .map(|cmd| format!("%b7F/{}%r", cmd.name()))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                Ok(
-                    format!("Commands (Page {}/{}): {}%r", page, total_pages, list)
-                        .parse()
-                        .unwrap(),
-                )
+                let start = (page - 1) * PAGE_SIZE;
+                let end = usize::min(start + PAGE_SIZE, commands.len());
+                let mut text = format!("%bF3Commands (Page {}/{}):%r", page, total_pages);
+                // Clicking a command shows its documentation
+                for cmd in &commands[start..end] {
+                    text.push_str(&format!(
+                        "\n%c[/help {0}]%b7F/{0}%r - {1}",
+                        cmd.name(),
+                        sanitize(cmd.help().summary())
+                    ));
+                }
+                let link = |label: &str, page: usize| {
+                    if page >= 1 && page <= total_pages {
+                        format!("%c[/help {}]%b7F{}%r", page, label)
+                    } else {
+                        format!("%bAB{}%r", label)
+                    }
+                };
+                text.push_str(&format!(
+                    "\n{}  {}",
+                    link("<< Previous", page - 1),
+                    link("Next >>", page + 1)
+                ));
+                Ok(text.parse().unwrap())
             }
             Subcommand::Command(name) => {
-                if let Some(cmd) = ctx.command_manager.get(&name) {
-                    Ok(format!("%b7F/{}%bF3\n{}%r", cmd.name(), cmd.description())
-                        .parse()
-                        .unwrap())
+                let name = name.strip_prefix('/').unwrap_or(&name);
+                if let Some(cmd) = ctx.command_manager.get(name) {
+                    Ok(cmd.help().to_text(cmd.name()))
                 } else {
-                    Err(format!("Unknown command: {}", name))
+                    Err(format!("Unknown command: {}", sanitize(name)))
                 }
             }
         }
@@ -0,0 +1,61 @@
+//! Implementation of the /kick command
+
+use crate::{
+    command::{
+        ArgStream, Command, CommandArg, CommandContext,
+        docs::CommandHelp,
+        parser::{GreedyString, Word},
+    },
+    server::access::DEFAULT_KICK_REASON,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct KickCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Disconnects a player from the server. They can join again right away, unless they are also banned. Only operators can use this command.",
+    usage: &[(
+        "/kick <player: player> [reason: text]",
+        "Kick `player`, showing them `reason`.",
+    )],
+    examples: &[(
+        "/kick Steve Please stop spamming",
+        "Kicks Steve and tells them why.",
+    )],
+};
+
+impl Command for KickCommand {
+    fn name(&self) -> &'static str {
+        "kick"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let username = Word::parse(&mut args)?.0;
+        let reason = GreedyString::parse(&mut args)?.0.trim().to_string();
+        args.ensure_empty()?;
+
+        let session = ctx
+            .sessions
+            .values_mut()
+            .find(|session| session.username == username)
+            .ok_or_else(|| format!("Player '{}' isn't online", sanitize(&username)))?;
+        let reason = if reason.is_empty() {
+            DEFAULT_KICK_REASON.to_string()
+        } else {
+            reason
+        };
+        session.kick(reason);
+        Ok(format!("%b7FKicked {}%r", sanitize(&username))
+            .parse()
+            .unwrap())
+    }
+}
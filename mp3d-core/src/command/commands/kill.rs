@@ -0,0 +1,66 @@
+//! Implementation of the /kill command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    protocol::DeathCause,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct KillCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Kills a player, who can respawn at the spawn position afterwards. Only operators can kill other players.",
+    usage: &[
+        ("/kill", "Kill yourself."),
+        ("/kill <player: player>", "Kill `player`."),
+    ],
+    examples: &[("/kill Steve", "Kills Steve.")],
+};
+
+impl Command for KillCommand {
+    fn name(&self) -> &'static str {
+        "kill"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let target = Option::<Word>::parse(&mut args)?;
+        args.ensure_empty()?;
+        if target.is_some() {
+            ctx.require_operator()?;
+        }
+
+        let sender = match ctx.get_sender_session() {
+            Ok(session) => (session.entity_id, session.username.clone()),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+        let (entity_id, username) = match target {
+            Some(Word(username)) => ctx
+                .sessions
+                .values()
+                .find(|session| session.username == username)
+                .map(|session| (session.entity_id, username.clone()))
+                .ok_or_else(|| format!("Player '{}' isn't online", sanitize(&username)))?,
+            None => sender.clone(),
+        };
+
+        let attacker = (entity_id != sender.0).then_some(sender.1);
+        ctx.world
+            .player_damage
+            .push((entity_id, f32::INFINITY, DeathCause::Killed, attacker));
+
+        Ok(format!("%b7FKilled {}%r", sanitize(&username))
+            .parse()
+            .unwrap())
+    }
+}
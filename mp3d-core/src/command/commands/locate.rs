@@ -0,0 +1,88 @@
+//! Implementation of the /locate command
+
+use glam::Vec2;
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+    world::generation::structure::StructureData,
+};
+
+pub struct LocateCommand;
+
+/// How far structures are searched for, in chunks.
+const SEARCH_RADIUS: i32 = 64;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Finds the nearest generated structure of a kind.\nStructures are found where the world generator places them, so terrain that hasn't been generated yet is searched too, up to 1024 blocks away. Structures that were broken since are still found. Click the coordinates to teleport on top of the structure.",
+    usage: &[(
+        "/locate structure <kind: structure>",
+        "Find the nearest structure of the kind.",
+    )],
+    examples: &[("/locate structure tree", "Finds the nearest tree.")],
+};
+
+impl Command for LocateCommand {
+    fn name(&self) -> &'static str {
+        "locate"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let category = Word::parse(&mut args)?;
+        match category.0.as_str() {
+            "structure" => {}
+            "biome" => return Err("Worlds don't have biomes yet".to_string()),
+            other => {
+                return Err(format!("Expected structure but got '{}'", sanitize(other)));
+            }
+        }
+        let kind = Word::parse(&mut args)?.0;
+        args.ensure_empty()?;
+        if !StructureData::NAMES.contains(&kind.as_str()) {
+            return Err(format!(
+                "Unknown structure '{}', expected one of: {}",
+                sanitize(&kind),
+                StructureData::NAMES.join(", ")
+            ));
+        }
+
+        let preferences = ctx.sender_preferences();
+        let from = match ctx.get_sender() {
+            Ok(sender) => sender.position(),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+        let structure = ctx
+            .world
+            .generator
+            .locate_structure(&kind, from.floor().as_ivec3(), SEARCH_RADIUS)
+            .ok_or_else(|| format!("There is no {} within {} chunks", kind, SEARCH_RADIUS))?;
+
+        let pos = structure.pos;
+        let offset = pos.as_vec3() - from;
+        let distance = Vec2::new(offset.x, offset.z).length();
+        Ok(format!(
+            "The nearest {} is at %c[/tp {} {} {}]%b7F[{}, {}, {}]%r, {} away",
+            kind,
+            pos.x as f32 + 0.5,
+            pos.y + structure.data.height(),
+            pos.z as f32 + 0.5,
+            pos.x,
+            pos.y,
+            pos.z,
+            preferences.format_distance(distance)
+        )
+        .parse()
+        .unwrap())
+    }
+}
@@ -0,0 +1,155 @@
+//! Implementation of the /log command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    server::eventlog::{EventFilter, now},
+    textcomponent::{TextComponent, sanitize},
+    world::entitystore::entity_chunk,
+};
+
+pub struct LogCommand;
+
+/// The most events listed at once.
+const MAX_LISTED_EVENTS: usize = 15;
+/// How far back events are listed without a number of minutes.
+const DEFAULT_MINUTES: u64 = 60;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Shows what players did recently, to look into griefing and complaints.\nPlaced and broken blocks, opened containers and deaths are logged. Only the latest 8192 events are kept. The log is lost when the world is closed unless it is persisted. Only operators can use this command.",
+    usage: &[
+        (
+            "/log <player: player> [minutes: integer]",
+            "List the events `player` took part in, from the last hour unless given a number of minutes.",
+        ),
+        (
+            "/log here [minutes: integer]",
+            "List the events in the chunk you are standing in, from the last hour unless given a number of minutes.",
+        ),
+        (
+            "/log persist [enabled: boolean]",
+            "Output or set whether the log is saved with the world.",
+        ),
+    ],
+    examples: &[(
+        "/log here 10",
+        "Lists what happened in your chunk in the last 10 minutes.",
+    )],
+};
+
+enum Subcommand {
+    Query { target: String, minutes: u64 },
+    Persist(Option<bool>),
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("persist") => Ok(Self::Persist(Option::<bool>::parse(args)?)),
+            Some(target) => Ok(Self::Query {
+                target: target.to_string(),
+                minutes: Option::<u64>::parse(args)?.unwrap_or(DEFAULT_MINUTES),
+            }),
+            None => Err("Expected a player, here or persist but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for LogCommand {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let (target, minutes) = match sub {
+            Subcommand::Persist(None) => {
+                let state = if ctx.event_log.persist { "" } else { "not " };
+                return Ok(format!("%b7FThe log is {}saved with the world%r", state)
+                    .parse()
+                    .unwrap());
+            }
+            Subcommand::Persist(Some(persist)) => {
+                ctx.event_log.persist = persist;
+                let state = if persist { "now" } else { "no longer" };
+                return Ok(format!("%b7FThe log is {} saved with the world%r", state)
+                    .parse()
+                    .unwrap());
+            }
+            Subcommand::Query { target, minutes } => (target, minutes),
+        };
+
+        let filter = if target == "here" {
+            let sender = match ctx.get_sender() {
+                Ok(entity) => entity,
+                Err(e) => {
+                    log::error!("{}", e);
+                    return Err("You must be connected to use /log here".to_string());
+                }
+            };
+            EventFilter::Chunk(entity_chunk(sender.position()))
+        } else {
+            EventFilter::Player(&target)
+        };
+        let events: Vec<_> = ctx
+            .event_log
+            .query(filter, minutes.saturating_mul(60))
+            .collect();
+        if events.is_empty() {
+            return Ok(format!("%b7FNo events in the last {} minutes%r", minutes)
+                .parse()
+                .unwrap());
+        }
+
+        let time = now();
+        let mut lines: Vec<_> = events
+            .iter()
+            .take(MAX_LISTED_EVENTS)
+            .map(|event| {
+                let [x, y, z] = event.position;
+                format!(
+                    "  {} ago: {} {} at {}, {}, {}",
+                    format_age(time.saturating_sub(event.time)),
+                    sanitize(&event.player),
+                    sanitize(&event.describe()),
+                    x,
+                    y,
+                    z
+                )
+            })
+            .collect();
+        if events.len() > MAX_LISTED_EVENTS {
+            lines.push(format!(
+                "  ...and {} more",
+                events.len() - MAX_LISTED_EVENTS
+            ));
+        }
+        Ok(format!(
+            "%b7F{} events in the last {} minutes, newest first:%r\n{}%r",
+            events.len(),
+            minutes,
+            lines.join("\n")
+        )
+        .parse()
+        .unwrap())
+    }
+}
+
+/// Formats a number of seconds as a short duration, like "5m 12s".
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds / 60 % 60),
+    }
+}
@@ -1,25 +1,73 @@
 use crate::command::CommandManager;
 
+mod ban;
+mod blockinfo;
+mod camera;
+mod claim;
 mod clear;
+mod compression;
+mod debug;
+mod difficulty;
+mod gamerule;
 mod give;
+mod heal;
 mod help;
+mod kick;
+mod kill;
+mod locate;
+mod log;
+mod moon;
+mod region;
+mod rollback;
 mod say;
 mod seed;
 mod setblock;
+mod spectate;
+mod summon;
+mod team;
 mod test;
+mod tick;
 mod time;
 mod tp;
 mod tps;
+mod transfer;
+mod trust;
+mod unban;
+mod whitelist;
 
 pub fn init_command_mgr(mgr: &mut CommandManager) {
+    mgr.register(ban::BanCommand);
+    mgr.register(blockinfo::BlockInfoCommand);
+    mgr.register(camera::CameraCommand);
+    mgr.register(claim::ClaimCommand);
     mgr.register(clear::ClearCommand);
+    mgr.register(compression::CompressionCommand);
+    mgr.register(debug::DebugCommand);
+    mgr.register(difficulty::DifficultyCommand);
+    mgr.register(gamerule::GameRuleCommand);
     mgr.register(give::GiveCommand);
+    mgr.register(heal::HealCommand);
     mgr.register(help::HelpCommand);
+    mgr.register(kick::KickCommand);
+    mgr.register(kill::KillCommand);
+    mgr.register(locate::LocateCommand);
+    mgr.register(log::LogCommand);
+    mgr.register(moon::MoonCommand);
+    mgr.register(region::RegionCommand);
+    mgr.register(rollback::RollbackCommand);
     mgr.register(say::SayCommand);
     mgr.register(seed::SeedCommand);
     mgr.register(setblock::SetBlockCommand);
+    mgr.register(spectate::SpectateCommand);
+    mgr.register(summon::SummonCommand);
+    mgr.register(team::TeamCommand);
     mgr.register(tp::TpCommand);
     mgr.register(tps::TpsCommand);
     mgr.register(test::TestCommand);
+    mgr.register(tick::TickCommand);
     mgr.register(time::TimeCommand);
+    mgr.register(transfer::TransferCommand);
+    mgr.register(trust::TrustCommand);
+    mgr.register(unban::UnbanCommand);
+    mgr.register(whitelist::WhitelistCommand);
 }
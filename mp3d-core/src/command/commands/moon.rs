@@ -0,0 +1,123 @@
+//! Implementation of the /moon command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    textcomponent::{TextComponent, sanitize},
+    world::{
+        DAY_LENGTH,
+        sky::{MOON_PHASES, moon_illumination, moon_phase},
+    },
+};
+
+pub struct MoonCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Output or change the phase of the moon. Only operators can change it.\nThe moon goes through its 8 phases once every 8 days, starting with a full moon.",
+    usage: &[
+        (
+            "/moon [get]",
+            "Output the current phase and when the next one starts.",
+        ),
+        (
+            "/moon set <phase>",
+            "Skip ahead to the next day with the given phase, keeping the time of day. The phase is a number from 0 (full moon) to 7, or one of `full`, `new`, `first_quarter` and `last_quarter`.",
+        ),
+    ],
+    examples: &[("/moon set new", "Skips ahead to the next new moon.")],
+};
+
+enum Subcommand {
+    Get,
+    Set(Phase),
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("get") => Ok(Self::Get),
+            Some("set") => Ok(Self::Set(Phase::parse(args)?)),
+            Some(sub) => Err(format!("Unknown subcommand for moon: '{}'", sanitize(sub))),
+            None => Ok(Self::Get),
+        }
+    }
+}
+
+/// An index into [`MOON_PHASES`].
+struct Phase(usize);
+
+impl CommandArg for Phase {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        let named = match args.peek() {
+            Some("full") => Some(0),
+            Some("last_quarter") => Some(2),
+            Some("new") => Some(4),
+            Some("first_quarter") => Some(6),
+            _ => None,
+        };
+        if let Some(phase) = named {
+            args.next();
+            return Ok(Self(phase));
+        }
+
+        let phase = u8::parse(args)? as usize;
+        if phase >= MOON_PHASES.len() {
+            return Err(format!(
+                "The moon phase must be less than {}.",
+                MOON_PHASES.len()
+            ));
+        }
+        Ok(Self(phase))
+    }
+}
+
+impl Command for MoonCommand {
+    fn name(&self) -> &'static str {
+        "moon"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        match sub {
+            Subcommand::Get => {
+                let phase = moon_phase(ctx.world.time);
+                Ok(format!(
+                    "Day {}: {} ({}%% lit), phase {} of {}. The next phase starts in {} ticks.%r",
+                    ctx.world.time / DAY_LENGTH,
+                    MOON_PHASES[phase],
+                    (moon_illumination(phase) * 100.0).round(),
+                    phase,
+                    MOON_PHASES.len(),
+                    DAY_LENGTH - ctx.world.time_of_day()
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Set(Phase(phase)) => {
+                ctx.require_operator()?;
+                let days =
+                    (phase + MOON_PHASES.len() - moon_phase(ctx.world.time)) % MOON_PHASES.len();
+                let Some(time) = ctx.world.time.checked_add(days as u64 * DAY_LENGTH) else {
+                    return Err("Skipping ahead would cause the time to overflow.%r".to_string());
+                };
+                ctx.world.time = time;
+                Ok(format!(
+                    "Skipped {} day(s) ahead to the {}.%r",
+                    days,
+                    MOON_PHASES[phase].to_lowercase()
+                )
+                .parse()
+                .unwrap())
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Implementation of the /region command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct RegionCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Lists or removes protected regions (see `/claim`).",
+    usage: &[
+        (
+            "/region list",
+            "Output all regions with their owner and bounds.",
+        ),
+        (
+            "/region remove <name: region>",
+            "Remove the region `name`, which you must own.",
+        ),
+    ],
+    examples: &[(
+        "/region remove home",
+        "Removes the protection of the region 'home'.",
+    )],
+};
+
+enum Subcommand {
+    List,
+    Remove(String),
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("list") => Ok(Self::List),
+            Some("remove") => Ok(Self::Remove(Word::parse(args)?.0)),
+            Some(sub) => Err(format!(
+                "Unknown subcommand for region: '{}'",
+                sanitize(sub)
+            )),
+            None => Err("Expected list or remove but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for RegionCommand {
+    fn name(&self) -> &'static str {
+        "region"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        match sub {
+            Subcommand::List => {
+                if ctx.claims.regions.is_empty() {
+                    return Ok("There are no regions.%r".parse().unwrap());
+                }
+                let list = ctx
+                    .claims
+                    .regions
+                    .iter()
+                    .map(|(name, region)| {
+                        format!(
+                            "%b7F{}%r by {}: {:?} to {:?}",
+                            sanitize(name),
+                            sanitize(&region.owner),
+                            region.min,
+                            region.max
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(format!("Regions:\n{}%r", list).parse().unwrap())
+            }
+            Subcommand::Remove(name) => {
+                let username = match ctx.get_sender_session() {
+                    Ok(session) => session.username.clone(),
+                    Err(e) => {
+                        log::error!("{}", e);
+                        return Err("You must be connected to use this command".to_string());
+                    }
+                };
+                let region = ctx
+                    .claims
+                    .regions
+                    .get(&name)
+                    .ok_or_else(|| format!("Unknown region: '{}'", sanitize(&name)))?;
+                if region.owner != username {
+                    return Err(format!("You don't own the region '{}'", sanitize(&name)));
+                }
+                ctx.claims.regions.remove(&name);
+                log::info!("{} removed region '{}'", username, name);
+                Ok(format!("%b7FRemoved region '{}'%r", sanitize(&name))
+                    .parse()
+                    .unwrap())
+            }
+        }
+    }
+}
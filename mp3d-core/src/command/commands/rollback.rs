@@ -0,0 +1,85 @@
+//! Implementation of the /rollback command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct RollbackCommand;
+
+/// How far around the sender, in blocks, edits are undone without a radius.
+const DEFAULT_RADIUS: f32 = 32.0;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Undoes the blocks a player placed and broke recently, to repair griefing.\nThe edits are taken from the log (see `/log`), so only edits still in the log can be undone. Blocks that someone else changed since are left alone, and items the player got or used up aren't taken back or returned. Only operators can use this command.",
+    usage: &[(
+        "/rollback <player: player> <minutes: integer> [radius: number]",
+        "Undo the edits of `player` in the last `minutes` minutes within `radius` blocks around you, 32 by default.",
+    )],
+    examples: &[(
+        "/rollback Steve 30 64",
+        "Undoes what Steve built and broke in the last half hour within 64 blocks.",
+    )],
+};
+
+impl Command for RollbackCommand {
+    fn name(&self) -> &'static str {
+        "rollback"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let center = match ctx.get_sender() {
+            Ok(entity) => entity.position(),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        let Word(player) = Word::parse(&mut args)?;
+        let minutes = u64::parse(&mut args)?;
+        let radius = Option::<f32>::parse(&mut args)?.unwrap_or(DEFAULT_RADIUS);
+        args.ensure_empty()?;
+        if radius.is_nan() || radius < 0.0 {
+            return Err("The radius must be a positive number".to_string());
+        }
+
+        let result = ctx.event_log.rollback(
+            ctx.world,
+            &player,
+            minutes.saturating_mul(60),
+            center,
+            radius,
+        );
+        log::info!(
+            "Rolled back {} edits of {} in the last {} minutes, skipped {}",
+            result.reverted,
+            player,
+            minutes,
+            result.skipped
+        );
+
+        let mut message = format!(
+            "%b7FUndid {} edits of {} from the last {} minutes",
+            result.reverted,
+            sanitize(&player),
+            minutes
+        );
+        if result.skipped > 0 {
+            message += &format!(
+                ", {} blocks were changed by someone else since",
+                result.skipped
+            );
+        }
+        Ok(format!("{}%r", message).parse().unwrap())
+    }
+}
@@ -1,28 +1,31 @@
 //! Implementation of the /say command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext, parser::GreedyString},
+    command::{
+        ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::GreedyString,
+    },
     server::PlayerSession,
     textcomponent::{TextComponent, sanitize},
 };
 
 pub struct SayCommand;
 
-const DESC: &str = r#"
-`say` - Make the sender say something in the chat.
-
-Usage: `/say word1 word2 ... wordN`
-
-Example: `/say Hello world!` will make the sender say "Hello world!" in the chat.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Make the sender say something in the chat.",
+    usage: &[("/say <message: text>", "Say `message` in the chat.")],
+    examples: &[(
+        "/say Hello world!",
+        "Makes the sender say \"Hello world!\" in the chat.",
+    )],
+};
 
 impl Command for SayCommand {
     fn name(&self) -> &'static str {
         "say"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -41,7 +44,7 @@ impl Command for SayCommand {
         let text = GreedyString::parse(&mut args)?.0;
         args.ensure_empty()?;
 
-        PlayerSession::send_chat_message(sender_id, ctx.sessions, &text);
+        PlayerSession::send_chat_message(sender_id, ctx.sessions, ctx.teams, &text);
         Ok(format!("%bA9You said: {}%r", sanitize(&text))
             .parse()
             .unwrap())
@@ -1,27 +1,25 @@
 //! Implementation of the /seed command
 
 use crate::{
-    command::{ArgStream, Command, CommandContext},
+    command::{ArgStream, Command, CommandContext, docs::CommandHelp},
     textcomponent::TextComponent,
 };
 
 pub struct SeedCommand;
 
-const DESC: &str = r#"
-`seed` - Output the world seed.
-
-Usage: `/seed`
-
-Example: `/seed` outputs the world seed.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Output the world seed.",
+    usage: &[("/seed", "Output the world seed.")],
+    examples: &[],
+};
 
 impl Command for SeedCommand {
     fn name(&self) -> &'static str {
         "seed"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(&self, ctx: &mut CommandContext, args: ArgStream) -> Result<TextComponent, String> {
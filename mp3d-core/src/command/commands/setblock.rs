@@ -4,6 +4,7 @@ use crate::{
     block::{BlockState, block_registry},
     command::{
         ArgStream, Command, CommandArg, CommandContext,
+        docs::CommandHelp,
         parser::{Coord3, Word},
     },
     textcomponent::TextComponent,
@@ -11,22 +12,25 @@ use crate::{
 
 pub struct SetBlockCommand;
 
-const DESC: &str = r#"
-`setblock` - Set a block at the specified coordinates, optionally specifying blockstate aswell.
-
-Usage: `/setblock block_ident x y z [state_data]`
-The block identifier is a string that identifies a block. A coordinate can be a number (e.g. "100.5"), be relative from the player's position (e.g. "~4") or scale on the player's forward direction (e.g. "^10"). Finally, the state_data is a 16-bit integer that defines the blocks behavior and appearance.
-
-Example: `/setblock stone_slab ~ ~10 ~ 1` places a top-slab 10 blocks above the player.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Set a block at the specified coordinates, optionally specifying blockstate as well. Only operators can use this command.",
+    usage: &[(
+        "/setblock <block: block> <x: coordinate> <y: coordinate> <z: coordinate> [state: integer]",
+        "Set the block at the coordinates. The state is a 16-bit integer that defines the behavior and appearance of the block.",
+    )],
+    examples: &[(
+        "/setblock stone_slab ~ ~10 ~ 1",
+        "Places a top slab 10 blocks above the player.",
+    )],
+};
 
 impl Command for SetBlockCommand {
     fn name(&self) -> &'static str {
         "setblock"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -34,6 +38,7 @@ impl Command for SetBlockCommand {
         ctx: &mut CommandContext,
         mut args: ArgStream,
     ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
         let sender = match ctx.get_sender() {
             Ok(entity) => entity,
             Err(e) => {
@@ -64,6 +69,9 @@ impl Command for SetBlockCommand {
             BlockState::default_state(block_def.state_type).unwrap()
         };
 
+        if ctx.world.holds_items(ivec3) {
+            return Err("That block has items in it, take them out first".to_string());
+        }
         ctx.world.urgent_set_block_at(
             ivec3,
             block,
@@ -0,0 +1,98 @@
+//! Implementation of the /spectate command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    protocol::S2CMessage,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct SpectateCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Watches the game through the eyes of another player.\nThe camera follows their position and looking direction until you stop spectating, which sneaking also does. Only operators can use this command.",
+    usage: &[
+        ("/spectate <player: player>", "Start spectating `player`."),
+        ("/spectate stop", "Stop spectating."),
+    ],
+    examples: &[("/spectate Steve", "Shows what Steve is looking at.")],
+};
+
+enum Subcommand {
+    Player(String),
+    Stop,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.peek() {
+            Some("stop") => {
+                args.next();
+                Ok(Self::Stop)
+            }
+            Some(_) => Ok(Self::Player(Word::parse(args)?.0)),
+            None => Err("Expected a player or stop but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for SpectateCommand {
+    fn name(&self) -> &'static str {
+        "spectate"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let target = match &sub {
+            Subcommand::Player(username) => Some(
+                ctx.sessions
+                    .values()
+                    .find(|session| &session.username == username)
+                    .map(|session| session.entity_id)
+                    .ok_or_else(|| format!("Player '{}' isn't online", sanitize(username)))?,
+            ),
+            Subcommand::Stop => None,
+        };
+        let session = match ctx.get_sender_session() {
+            Ok(session) => session,
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        match sub {
+            Subcommand::Player(username) => {
+                if target == Some(session.entity_id) {
+                    return Err("You can't spectate yourself".to_string());
+                }
+                session.spectating = target;
+                session.pending_messages.push(S2CMessage::SpectateStarted {
+                    username: username.clone(),
+                });
+                Ok(
+                    format!("%b7FSpectating {}, sneak to stop%r", sanitize(&username))
+                        .parse()
+                        .unwrap(),
+                )
+            }
+            Subcommand::Stop => {
+                if session.spectating.take().is_none() {
+                    return Err("You aren't spectating anyone".to_string());
+                }
+                session.pending_messages.push(S2CMessage::SpectateStopped);
+                Ok("%b7FStopped spectating%r".parse().unwrap())
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Implementation of the /summon command
+
+use crate::{
+    command::{
+        ArgStream, Command, CommandArg, CommandContext,
+        docs::CommandHelp,
+        parser::{Coord3, Word},
+    },
+    entity::entity_registry,
+    textcomponent::TextComponent,
+};
+
+pub struct SummonCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Spawn an entity at the specified coordinates.\nPlayers can't be summoned. Only operators can use this command.",
+    usage: &[(
+        "/summon <entity: entity> [x: coordinate] [y: coordinate] [z: coordinate]",
+        "Spawn an entity at the coordinates, or at your position without them.",
+    )],
+    examples: &[(
+        "/summon dummy ^ ~ ^5",
+        "Places a target dummy 5 blocks in front of the player.",
+    )],
+};
+
+impl Command for SummonCommand {
+    fn name(&self) -> &'static str {
+        "summon"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let sender = match ctx.get_sender() {
+            Ok(entity) => entity,
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        let ident = Word::parse(&mut args)?;
+        let coord3 = Option::<Coord3>::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let position = match coord3 {
+            Some(coord3) => coord3.as_vec3(sender.position(), sender.forward()),
+            None => sender.position(),
+        };
+        let reg = entity_registry();
+        let def = reg
+            .get_id(&ident.0)
+            .and_then(|id| reg.get(id))
+            .ok_or("Unknown entity type")?;
+        let summon = def
+            .summon
+            .ok_or_else(|| format!("Entities of type '{}' can't be summoned", def.ident))?;
+
+        let entity_id = ctx.world.add_entity(summon(position));
+        Ok(format!(
+            "%b7FSummoned {} with ID {} at {:.1}, {:.1}, {:.1}%r",
+            def.ident, entity_id, position.x, position.y, position.z
+        )
+        .parse()
+        .unwrap())
+    }
+}
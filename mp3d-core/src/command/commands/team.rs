@@ -0,0 +1,237 @@
+//! Implementation of the /team command
+
+use crate::{
+    command::{
+        ArgStream, Command, CommandArg, CommandContext,
+        docs::CommandHelp,
+        parser::{GreedyString, Word},
+    },
+    protocol::S2CMessage,
+    server::{PlayerSession, teams::TeamColor},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct TeamCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Manages teams. The names of team members are shown in the team color.",
+    usage: &[
+        (
+            "/team create <name: word> [color]",
+            "Create and join the team `name`. Colors: red, orange, yellow, green, aqua, blue, purple, gray.",
+        ),
+        (
+            "/team invite <player: player>",
+            "Invite `player` to your team. Only the owner of the team can invite players.",
+        ),
+        (
+            "/team join <name: team>",
+            "Join the team `name` if you were invited, leaving your current team.",
+        ),
+        (
+            "/team leave",
+            "Leave your team. Teams without members are removed.",
+        ),
+        ("/team list", "Output all teams and their members."),
+        (
+            "/team friendlyfire <enabled: boolean>",
+            "Allow or disallow members of your team to hurt each other. Only the owner of the team can change this.",
+        ),
+        (
+            "/team chat <message: text>",
+            "Send a message only your team can read.",
+        ),
+    ],
+    examples: &[(
+        "/team create builders blue",
+        "Creates the blue team 'builders'.",
+    )],
+};
+
+enum Subcommand {
+    Create(String, Option<TeamColor>),
+    Invite(String),
+    Join(String),
+    Leave,
+    List,
+    FriendlyFire(bool),
+    Chat(String),
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("create") => {
+                let name = Word::parse(args)?.0;
+                let color = match Option::<Word>::parse(args)? {
+                    Some(Word(color)) => Some(
+                        TeamColor::from_name(&color)
+                            .ok_or_else(|| format!("Unknown color: '{}'", sanitize(&color)))?,
+                    ),
+                    None => None,
+                };
+                Ok(Self::Create(name, color))
+            }
+            Some("invite") => Ok(Self::Invite(Word::parse(args)?.0)),
+            Some("join") => Ok(Self::Join(Word::parse(args)?.0)),
+            Some("leave") => Ok(Self::Leave),
+            Some("list") => Ok(Self::List),
+            Some("friendlyfire") => Ok(Self::FriendlyFire(bool::parse(args)?)),
+            Some("chat") => Ok(Self::Chat(GreedyString::parse(args)?.0)),
+            Some(sub) => Err(format!("Unknown subcommand for team: '{}'", sanitize(sub))),
+            None => Err("Expected a subcommand but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for TeamCommand {
+    fn name(&self) -> &'static str {
+        "team"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        if let Subcommand::List = sub {
+            if ctx.teams.teams.is_empty() {
+                return Ok("There are no teams.%r".parse().unwrap());
+            }
+            let list = ctx
+                .teams
+                .teams
+                .iter()
+                .map(|(name, team)| {
+                    let members = team
+                        .members
+                        .iter()
+                        .map(|member| sanitize(member))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!(
+                        "%b{:02X}{}%r: {}",
+                        team.color.code(),
+                        sanitize(name),
+                        members
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Ok(format!("Teams:\n{}%r", list).parse().unwrap());
+        }
+
+        let (session_id, username) = match ctx.get_sender_session() {
+            Ok(session) => (session.user_id, session.username.clone()),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        match sub {
+            Subcommand::Create(name, color) => {
+                // Pick the least used color if none is given
+                let color = color.unwrap_or_else(|| {
+                    *TeamColor::ALL
+                        .iter()
+                        .min_by_key(|color| {
+                            ctx.teams
+                                .teams
+                                .values()
+                                .filter(|team| team.color == **color)
+                                .count()
+                        })
+                        .unwrap()
+                });
+                ctx.teams
+                    .create(name.clone(), color, username.clone())
+                    .map_err(|e| sanitize(&e))?;
+                log::info!("{} created team '{}'", username, name);
+                Ok(format!(
+                    "%b7FCreated team %b{:02X}{}%b7F and joined it%r",
+                    color.code(),
+                    sanitize(&name)
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Invite(player) => {
+                let name = ctx
+                    .teams
+                    .invite(&username, player.clone())
+                    .map_err(|e| sanitize(&e))?;
+                log::info!("{} invited {} to team '{}'", username, player, name);
+                if let Some(session) = ctx
+                    .sessions
+                    .values_mut()
+                    .find(|session| session.username == player)
+                {
+                    session.pending_messages.push(S2CMessage::ChatMessage {
+                        message: format!(
+                            "%b7F{} invited you to team '{}', join with /team join {}%r",
+                            sanitize(&username),
+                            sanitize(&name),
+                            sanitize(&name)
+                        )
+                        .parse()
+                        .unwrap(),
+                    });
+                }
+                Ok(format!(
+                    "%b7FInvited {} to team '{}'%r",
+                    sanitize(&player),
+                    sanitize(&name)
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Join(name) => {
+                ctx.teams
+                    .join(&name, username.clone())
+                    .map_err(|e| sanitize(&e))?;
+                log::info!("{} joined team '{}'", username, name);
+                Ok(format!("%b7FJoined team '{}'%r", sanitize(&name))
+                    .parse()
+                    .unwrap())
+            }
+            Subcommand::Leave => {
+                let name = ctx.teams.leave(&username).ok_or("You aren't in a team")?;
+                log::info!("{} left team '{}'", username, name);
+                Ok(format!("%b7FLeft team '{}'%r", sanitize(&name))
+                    .parse()
+                    .unwrap())
+            }
+            Subcommand::FriendlyFire(enabled) => {
+                let (name, team) = ctx
+                    .teams
+                    .owned_team_mut(&username)
+                    .map_err(|e| sanitize(&e))?;
+                team.friendly_fire = enabled;
+                Ok(format!(
+                    "%b7FFriendly fire is now {} in '{}'%r",
+                    if enabled { "on" } else { "off" },
+                    sanitize(&name)
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Chat(message) => {
+                PlayerSession::send_team_message(session_id, ctx.sessions, ctx.teams, &message)?;
+                Ok(
+                    format!("%bA9You said to your team: {}%r", sanitize(&message))
+                        .parse()
+                        .unwrap(),
+                )
+            }
+            Subcommand::List => unreachable!(),
+        }
+    }
+}
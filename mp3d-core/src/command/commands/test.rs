@@ -1,19 +1,20 @@
 //! Implementation of the /test command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext},
-    textcomponent::TextComponent,
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    textcomponent::{TextComponent, sanitize},
 };
 
 pub struct TestCommand;
 
-const DESC: &str = r#"
-`test` - Runs a set of server-side tests
-
-Usage: `/test <pass | error>`
-
-Example: `/test error` will run a test of a command error
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Runs a set of server-side tests.",
+    usage: &[
+        ("/test pass", "Run a test of a command succeeding."),
+        ("/test error", "Run a test of a command error."),
+    ],
+    examples: &[],
+};
 
 enum Subcommand {
     Pass,
@@ -25,8 +26,8 @@ impl CommandArg for Subcommand {
         match args.next() {
             Some("pass") => Ok(Self::Pass),
             Some("error") => Ok(Self::Error),
-            Some(s) => Err(format!("Invalid test mode '{}'", s)),
-            None => Err(format!("Expected a test mode but got nothing")),
+            Some(s) => Err(format!("Invalid test mode '{}'", sanitize(s))),
+            None => Err("Expected a test mode but got nothing".to_string()),
         }
     }
 }
@@ -36,8 +37,8 @@ impl Command for TestCommand {
         "test"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -0,0 +1,136 @@
+//! Implementation of the /tick command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    textcomponent::{TextComponent, sanitize},
+    world::tickcontrol::{DEFAULT_TICK_RATE, TickControl},
+};
+
+pub struct TickCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Pauses, steps or slows down the world for debugging. Only operators can change the controls.\nRandom block ticks, the time and entities other than players follow these controls. Players keep moving and chunks are still sent.",
+    usage: &[
+        (
+            "/tick [query]",
+            "Output whether the world is frozen and its tick rate.",
+        ),
+        ("/tick freeze", "Pause the world."),
+        ("/tick unfreeze", "Resume the world."),
+        (
+            "/tick step [n: integer]",
+            "Advance the frozen world by `n` ticks, 1 by default.",
+        ),
+        (
+            "/tick rate <tps: integer>",
+            "Tick the world `tps` times per second, 48 by default.",
+        ),
+    ],
+    examples: &[(
+        "/tick rate 12",
+        "Runs the world in slow motion at a quarter of the normal speed.",
+    )],
+};
+
+enum Subcommand {
+    Query,
+    Freeze,
+    Unfreeze,
+    Step(u32),
+    Rate(u8),
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("query") => Ok(Self::Query),
+            Some("freeze") => Ok(Self::Freeze),
+            Some("unfreeze") => Ok(Self::Unfreeze),
+            Some("step") => match args.peek() {
+                Some(_) => Ok(Self::Step(u32::parse(args)?)),
+                None => Ok(Self::Step(1)),
+            },
+            Some("rate") => {
+                let rate = u8::parse(args)?;
+                if rate == 0 {
+                    return Err(
+                        "The tick rate must be at least 1, use /tick freeze to pause the world"
+                            .to_string(),
+                    );
+                }
+                Ok(Self::Rate(rate))
+            }
+            Some(sub) => Err(format!("Unknown subcommand for tick: '{}'", sanitize(sub))),
+            None => Ok(Self::Query),
+        }
+    }
+}
+
+fn describe(control: &TickControl) -> String {
+    let state = if control.frozen {
+        format!("frozen with {} steps left", control.steps)
+    } else {
+        "running".to_string()
+    };
+    format!(
+        "The world is {} at {} ticks per second (normally {}).%r",
+        state, control.rate, DEFAULT_TICK_RATE
+    )
+}
+
+impl Command for TickCommand {
+    fn name(&self) -> &'static str {
+        "tick"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+        if !matches!(sub, Subcommand::Query) {
+            ctx.require_operator()?;
+        }
+        let control = &mut ctx.world.tick_control;
+
+        match sub {
+            Subcommand::Query => Ok(describe(control).parse().unwrap()),
+            Subcommand::Freeze => {
+                if control.frozen {
+                    return Err("The world is already frozen".to_string());
+                }
+                control.frozen = true;
+                control.steps = 0;
+                Ok("%b7FFroze the world%r".parse().unwrap())
+            }
+            Subcommand::Unfreeze => {
+                if !control.frozen {
+                    return Err("The world isn't frozen".to_string());
+                }
+                control.frozen = false;
+                Ok("%b7FUnfroze the world%r".parse().unwrap())
+            }
+            Subcommand::Step(steps) => {
+                if !control.frozen {
+                    return Err("Only a frozen world can be stepped, use /tick freeze".to_string());
+                }
+                control.steps = control.steps.saturating_add(steps);
+                Ok(format!("%b7FStepping the world by {} ticks%r", steps)
+                    .parse()
+                    .unwrap())
+            }
+            Subcommand::Rate(rate) => {
+                control.rate = rate;
+                Ok(format!("%b7FSet the tick rate to {}%r", rate)
+                    .parse()
+                    .unwrap())
+            }
+        }
+    }
+}
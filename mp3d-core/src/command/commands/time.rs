@@ -1,27 +1,37 @@
 //! Implementation of the /time command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext},
-    textcomponent::TextComponent,
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp},
+    preferences::Preferences,
+    textcomponent::{TextComponent, sanitize},
+    world::DAY_LENGTH,
 };
 
 pub struct TimeCommand;
 
-const DESC: &str = r#"
-`time` - Output or modify current time.
-There is no day / night system yet, so this command really doesn't do anything other than keep track of time.
-
-Usage: `/time [<get | add | sub>]`
-  - `/time get` Output current time.
-  - `/time add inc` Increment time by `inc`.
-  - `/time sub dec` Decrement time by `dec`.
-  - `/time` Default to `/time get`.
-
-Example: `/time` outputs current time.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Output or modify current time. Only operators can change it.\nA day is 24000 ticks long, starting at sunrise. The second half of the day is night, which can be skipped by sleeping in a bed.",
+    usage: &[
+        ("/time [get | query]", "Output the current time."),
+        (
+            "/time set <time>",
+            "Set the time of the current day to `time`, which is either a number of ticks or one of `day`, `noon`, `night` and `midnight`.",
+        ),
+        (
+            "/time add <ticks: integer>",
+            "Move the time forward by `ticks`.",
+        ),
+        (
+            "/time sub <ticks: integer>",
+            "Move the time back by `ticks`.",
+        ),
+    ],
+    examples: &[("/time set night", "Sets the time to sunset.")],
+};
 
 enum Subcommand {
     Get,
+    Set(TimeOfDay),
     Add(u64),
     Sub(u64),
 }
@@ -29,22 +39,55 @@ enum Subcommand {
 impl CommandArg for Subcommand {
     fn parse(args: &mut ArgStream) -> Result<Self, String> {
         match args.next() {
-            Some("get") => Ok(Self::Get),
+            Some("get" | "query") => Ok(Self::Get),
+            Some("set") => Ok(Self::Set(TimeOfDay::parse(args)?)),
             Some("add") => Ok(Self::Add(u64::parse(args)?)),
             Some("sub") => Ok(Self::Sub(u64::parse(args)?)),
-            Some(sub) => Err(format!("Unknown subcommand for time: '{}'", sub)),
+            Some(sub) => Err(format!("Unknown subcommand for time: '{}'", sanitize(sub))),
             None => Ok(Self::Get),
         }
     }
 }
 
+/// A time within a day, in ticks since sunrise.
+struct TimeOfDay(u64);
+
+impl CommandArg for TimeOfDay {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        let named = match args.peek() {
+            Some("day") => Some(0),
+            Some("noon") => Some(DAY_LENGTH / 4),
+            Some("night") => Some(DAY_LENGTH / 2),
+            Some("midnight") => Some(DAY_LENGTH * 3 / 4),
+            _ => None,
+        };
+        if let Some(ticks) = named {
+            args.next();
+            return Ok(Self(ticks));
+        }
+
+        let ticks = u64::parse(args)?;
+        if ticks >= DAY_LENGTH {
+            return Err(format!(
+                "The time of day must be less than {} ticks.",
+                DAY_LENGTH
+            ));
+        }
+        Ok(Self(ticks))
+    }
+}
+
+fn format_time(time: u64, preferences: &Preferences) -> String {
+    format!("{} ({})", time, preferences.format_time(time))
+}
+
 impl Command for TimeCommand {
     fn name(&self) -> &'static str {
         "time"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -54,19 +97,37 @@ impl Command for TimeCommand {
     ) -> Result<TextComponent, String> {
         let sub = Subcommand::parse(&mut args)?;
         args.ensure_empty()?;
+        if !matches!(sub, Subcommand::Get) {
+            ctx.require_operator()?;
+        }
+        let preferences = ctx.sender_preferences();
 
         match sub {
-            Subcommand::Get => Ok(format!("Current time: {}%r", ctx.world.time)
+            Subcommand::Get => Ok(format!(
+                "Current time: {}%r",
+                format_time(ctx.world.time, &preferences)
+            )
+            .parse()
+            .unwrap()),
+            Subcommand::Set(TimeOfDay(time_of_day)) => {
+                ctx.world.time = ctx.world.time - ctx.world.time_of_day() + time_of_day;
+                Ok(format!(
+                    "Set the time to {}.%r",
+                    format_time(ctx.world.time, &preferences)
+                )
                 .parse()
-                .unwrap()),
+                .unwrap())
+            }
             Subcommand::Add(inc) => match ctx.world.time.checked_add(inc) {
                 Some(new) => {
                     ctx.world.time = new;
-                    Ok(
-                        format!("Added {} to current time and now it's {}.%r", inc, new)
-                            .parse()
-                            .unwrap(),
+                    Ok(format!(
+                        "Added {} to current time and now it's {}.%r",
+                        inc,
+                        format_time(new, &preferences)
                     )
+                    .parse()
+                    .unwrap())
                 }
                 None => Err(format!(
                     "Adding {} to the current time would cause the time to overflow.%r",
@@ -78,7 +139,8 @@ impl Command for TimeCommand {
                     ctx.world.time = new;
                     Ok(format!(
                         "Subtracted {} from current time and now it's {}.%r",
-                        dec, new
+                        dec,
+                        format_time(new, &preferences)
                     )
                     .parse()
                     .unwrap())
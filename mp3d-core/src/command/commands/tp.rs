@@ -1,28 +1,28 @@
 //! Implementation of the /tp command
 
 use crate::{
-    command::{ArgStream, Command, CommandArg, CommandContext, parser::Coord3},
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Coord3},
     textcomponent::TextComponent,
 };
 
 pub struct TpCommand;
 
-const DESC: &str = r#"
-`tp` - Teleports the sender to the specified coordinates.
-
-Usage: `/tp x y z`
-A coordinate can be a number (e.g. "100.5"), be relative from the player's position (e.g. "~4") or scale on the player's forward direction (e.g. "^10").
-
-Example: `/tp ~ ~10 ~` moves the player 10 blocks up.
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Teleports the sender to the specified coordinates. Only operators can use this command.",
+    usage: &[(
+        "/tp <x: coordinate> <y: coordinate> <z: coordinate>",
+        "Teleport to the coordinates.",
+    )],
+    examples: &[("/tp ~ ~10 ~", "Moves the player 10 blocks up.")],
+};
 
 impl Command for TpCommand {
     fn name(&self) -> &'static str {
         "tp"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(
@@ -30,6 +30,8 @@ impl Command for TpCommand {
         ctx: &mut CommandContext,
         mut args: ArgStream,
     ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let preferences = ctx.sender_preferences();
         let sender = match ctx.get_sender() {
             Ok(entity) => entity,
             Err(e) => {
@@ -47,10 +49,14 @@ impl Command for TpCommand {
         *sender.position_mut() = vec3;
         ctx.world.load_around(pos.as_ivec3());
 
-        Ok(
-            format!("%b7FTeleported you to {}, {}, {}%r", vec3.x, vec3.y, vec3.z,)
-                .parse()
-                .unwrap(),
+        Ok(format!(
+            "%b7FTeleported you to {}, {}, {} ({} away)%r",
+            preferences.format_number(vec3.x, 2),
+            preferences.format_number(vec3.y, 2),
+            preferences.format_number(vec3.z, 2),
+            preferences.format_distance(vec3.distance(pos)),
         )
+        .parse()
+        .unwrap())
     }
 }
@@ -1,27 +1,25 @@
 //! Implementation of the /tps command
 
 use crate::{
-    command::{ArgStream, Command, CommandContext},
+    command::{ArgStream, Command, CommandContext, docs::CommandHelp},
     textcomponent::TextComponent,
 };
 
 pub struct TpsCommand;
 
-const DESC: &str = r#"
-`tps` - Output current TPS (Ticks Per Second) of the server.
-
-Usage: `/tps`
-
-Example: `/tps` outputs current TPS (usually 48).
-"#;
+static HELP: CommandHelp = CommandHelp {
+    description: "Output current TPS (Ticks Per Second) of the server.",
+    usage: &[("/tps", "Output the current TPS, usually 48.")],
+    examples: &[],
+};
 
 impl Command for TpsCommand {
     fn name(&self) -> &'static str {
         "tps"
     }
 
-    fn description(&self) -> &'static str {
-        DESC.trim()
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
     }
 
     fn execute(&self, ctx: &mut CommandContext, args: ArgStream) -> Result<TextComponent, String> {
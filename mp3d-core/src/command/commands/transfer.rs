@@ -0,0 +1,59 @@
+//! Implementation of the /transfer command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    protocol::S2CMessage,
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct TransferCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Sends a player to another server, like from a lobby to a game. Their client asks them first unless they trust this server. Only operators can use this command.",
+    usage: &[(
+        "/transfer <player: player> <address: word>",
+        "Send `player` to the server at `address`.",
+    )],
+    examples: &[(
+        "/transfer Steve games.example.com:25565",
+        "Sends Steve to the server at games.example.com.",
+    )],
+};
+
+impl Command for TransferCommand {
+    fn name(&self) -> &'static str {
+        "transfer"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let username = Word::parse(&mut args)?.0;
+        let address = Word::parse(&mut args)?.0;
+        args.ensure_empty()?;
+
+        let session = ctx
+            .sessions
+            .values_mut()
+            .find(|session| session.username == username)
+            .ok_or_else(|| format!("Player '{}' isn't online", sanitize(&username)))?;
+        log::info!("Transferring {} to {}", username, address);
+        session.pending_messages.push(S2CMessage::Transfer {
+            address: address.clone(),
+        });
+        Ok(format!(
+            "%b7FSent {} to {}%r",
+            sanitize(&username),
+            sanitize(&address)
+        )
+        .parse()
+        .unwrap())
+    }
+}
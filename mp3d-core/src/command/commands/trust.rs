@@ -0,0 +1,109 @@
+//! Implementation of the /trust command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct TrustCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Allows or disallows a player to build in a region you own.\nUse `team:name` as the player to trust every member of the team `name`.",
+    usage: &[
+        (
+            "/trust add <region: region> <player: player>",
+            "Allow `player` to build in `region`.",
+        ),
+        (
+            "/trust remove <region: region> <player: player>",
+            "Disallow `player` to build in `region`.",
+        ),
+    ],
+    examples: &[(
+        "/trust add home Steve",
+        "Lets Steve build in the region 'home'.",
+    )],
+};
+
+enum Subcommand {
+    Add,
+    Remove,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("add") => Ok(Self::Add),
+            Some("remove") => Ok(Self::Remove),
+            Some(sub) => Err(format!("Unknown subcommand for trust: '{}'", sanitize(sub))),
+            None => Err("Expected add or remove but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for TrustCommand {
+    fn name(&self) -> &'static str {
+        "trust"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        let username = match ctx.get_sender_session() {
+            Ok(session) => session.username.clone(),
+            Err(e) => {
+                log::error!("{}", e);
+                return Err("You must be connected to use this command".to_string());
+            }
+        };
+
+        let sub = Subcommand::parse(&mut args)?;
+        let Word(name) = Word::parse(&mut args)?;
+        let Word(player) = Word::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let region = ctx
+            .claims
+            .regions
+            .get_mut(&name)
+            .ok_or_else(|| format!("Unknown region: '{}'", sanitize(&name)))?;
+        if region.owner != username {
+            return Err(format!("You don't own the region '{}'", sanitize(&name)));
+        }
+
+        match sub {
+            Subcommand::Add => {
+                region.trusted.insert(player.clone());
+                Ok(format!(
+                    "%b7F{} can now build in '{}'%r",
+                    sanitize(&player),
+                    sanitize(&name)
+                )
+                .parse()
+                .unwrap())
+            }
+            Subcommand::Remove => {
+                if !region.trusted.remove(&player) {
+                    return Err(format!(
+                        "{} isn't trusted in '{}'",
+                        sanitize(&player),
+                        sanitize(&name)
+                    ));
+                }
+                Ok(format!(
+                    "%b7F{} can no longer build in '{}'%r",
+                    sanitize(&player),
+                    sanitize(&name)
+                )
+                .parse()
+                .unwrap())
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Implementation of the /unban command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct UnbanCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Lifts the ban of a player, see /ban. Only operators can use this command.",
+    usage: &[("/unban <player: player>", "Let `player` join again.")],
+    examples: &[("/unban Steve", "Lets Steve join again.")],
+};
+
+impl Command for UnbanCommand {
+    fn name(&self) -> &'static str {
+        "unban"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let username = Word::parse(&mut args)?.0;
+        args.ensure_empty()?;
+
+        if ctx.access.rules.bans.remove(&username).is_none() {
+            return Err(format!("Player '{}' isn't banned", sanitize(&username)));
+        }
+        if let Err(e) = ctx.access.save() {
+            log::error!("Failed to save the access rules: {}", e);
+        }
+        Ok(format!("%b7FUnbanned {}%r", sanitize(&username))
+            .parse()
+            .unwrap())
+    }
+}
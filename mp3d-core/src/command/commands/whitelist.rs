@@ -0,0 +1,133 @@
+//! Implementation of the /whitelist command
+
+use crate::{
+    command::{ArgStream, Command, CommandArg, CommandContext, docs::CommandHelp, parser::Word},
+    textcomponent::{TextComponent, sanitize},
+};
+
+pub struct WhitelistCommand;
+
+static HELP: CommandHelp = CommandHelp {
+    description: "Manages the whitelist. While it is on, only the players on it can join the server, but nobody online is kicked. Only operators can use this command.",
+    usage: &[
+        (
+            "/whitelist add <player: player>",
+            "Put `player` on the whitelist.",
+        ),
+        (
+            "/whitelist remove <player: player>",
+            "Take `player` off the whitelist.",
+        ),
+        ("/whitelist <on | off>", "Turn the whitelist on or off."),
+        (
+            "/whitelist list",
+            "Output whether the whitelist is on and the players on it.",
+        ),
+    ],
+    examples: &[
+        (
+            "/whitelist add Steve",
+            "Lets Steve join while the whitelist is on.",
+        ),
+        (
+            "/whitelist on",
+            "Only lets whitelisted players join from now on.",
+        ),
+    ],
+};
+
+enum Subcommand {
+    Add(String),
+    Remove(String),
+    Enable(bool),
+    List,
+}
+
+impl CommandArg for Subcommand {
+    fn parse(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("add") => Ok(Self::Add(Word::parse(args)?.0)),
+            Some("remove") => Ok(Self::Remove(Word::parse(args)?.0)),
+            Some("on") => Ok(Self::Enable(true)),
+            Some("off") => Ok(Self::Enable(false)),
+            Some("list") => Ok(Self::List),
+            Some(sub) => Err(format!(
+                "Unknown subcommand for whitelist: '{}'",
+                sanitize(sub)
+            )),
+            None => Err("Expected a subcommand but got nothing".to_string()),
+        }
+    }
+}
+
+impl Command for WhitelistCommand {
+    fn name(&self) -> &'static str {
+        "whitelist"
+    }
+
+    fn help(&self) -> &'static CommandHelp {
+        &HELP
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext,
+        mut args: ArgStream,
+    ) -> Result<TextComponent, String> {
+        ctx.require_operator()?;
+        let sub = Subcommand::parse(&mut args)?;
+        args.ensure_empty()?;
+
+        let rules = &mut ctx.access.rules;
+        let message = match sub {
+            Subcommand::Add(username) => {
+                if !rules.whitelist.insert(username.clone()) {
+                    return Err(format!(
+                        "Player '{}' is already whitelisted",
+                        sanitize(&username)
+                    ));
+                }
+                format!("%b7FAdded {} to the whitelist%r", sanitize(&username))
+            }
+            Subcommand::Remove(username) => {
+                if !rules.whitelist.remove(&username) {
+                    return Err(format!(
+                        "Player '{}' isn't whitelisted",
+                        sanitize(&username)
+                    ));
+                }
+                format!("%b7FRemoved {} from the whitelist%r", sanitize(&username))
+            }
+            Subcommand::Enable(enabled) => {
+                rules.whitelist_enabled = enabled;
+                format!(
+                    "%b7FThe whitelist is now {}%r",
+                    if enabled { "on" } else { "off" }
+                )
+            }
+            Subcommand::List => {
+                let players = rules
+                    .whitelist
+                    .iter()
+                    .map(|username| sanitize(username))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Ok(format!(
+                    "The whitelist is %b7F{}%r. Players: {}",
+                    if rules.whitelist_enabled { "on" } else { "off" },
+                    if players.is_empty() {
+                        "none".to_string()
+                    } else {
+                        players
+                    }
+                )
+                .parse()
+                .unwrap());
+            }
+        };
+        if let Err(e) = ctx.access.save() {
+            log::error!("Failed to save the access rules: {}", e);
+        }
+        Ok(message.parse().unwrap())
+    }
+}
@@ -0,0 +1,132 @@
+//! The documentation of commands, shown by /help.
+//!
+//! In the syntax of a command, required arguments are written in angle brackets and optional ones
+//! in square brackets. Arguments are named, with their type after a colon like `<x: coordinate>`,
+//! or a choice of words like `<true | false>`. The types are explained in [`ARGUMENT_TYPES`].
+//! Text in backticks is highlighted.
+
+use crate::textcomponent::{TextComponent, sanitize};
+
+/// The color of commands and highlighted text.
+const HIGHLIGHT: &str = "%b7F";
+/// The color of headings.
+const HEADING: &str = "%bF3";
+
+/// The argument types with what they accept.
+pub const ARGUMENT_TYPES: &[(&str, &str)] = &[
+    (
+        "coordinate",
+        "A number like `100.5`, relative to your position like `~4`, or along the direction you \
+         are looking like `^10`.",
+    ),
+    ("number", "A number like `2` or `0.5`."),
+    ("integer", "A whole number like `10`."),
+    ("boolean", "`true` or `false`."),
+    ("word", "A single word without spaces."),
+    ("text", "Any text, up to the end of the command."),
+    ("player", "The name of a player."),
+    (
+        "block",
+        "A block identifier like `stone` or `mineplace:stone`.",
+    ),
+    ("item", "An item identifier like `grass_block`."),
+    ("entity", "An entity type like `dummy`."),
+    ("region", "The name of a protected region, see `/claim`."),
+    ("structure", "A kind of generated structure like `tree`."),
+    ("team", "The name of a team."),
+    ("command", "The name of a command, without the slash."),
+];
+
+/// How a command is used.
+pub struct CommandHelp {
+    /// What the command does. The first line is its summary in the list of commands.
+    pub description: &'static str,
+    /// The forms of the command with what each does, like
+    /// `("/tp <x: coordinate> <y: coordinate> <z: coordinate>", "Teleport to the coordinates.")`.
+    pub usage: &'static [(&'static str, &'static str)],
+    /// Uses of the command with what they do.
+    pub examples: &'static [(&'static str, &'static str)],
+}
+
+impl CommandHelp {
+    /// Returns the first line of the description.
+    pub fn summary(&self) -> &'static str {
+        self.description.lines().next().unwrap_or_default()
+    }
+
+    /// Returns the types of the arguments used in the syntax, in the order they first appear.
+    pub fn argument_types(&self) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        for (syntax, _) in self.usage {
+            for argument in syntax.split(['<', '[']).skip(1) {
+                let argument = argument.split(['>', ']']).next().unwrap_or_default();
+                if let Some((_, ty)) = argument.split_once(": ")
+                    && !types.contains(&ty)
+                {
+                    types.push(ty);
+                }
+            }
+        }
+        types
+    }
+
+    /// Formats the whole documentation of the command with the given name.
+    pub fn to_text(&self, name: &str) -> TextComponent {
+        let mut text = format!(
+            "{}/{}%r\n{}",
+            HIGHLIGHT,
+            sanitize(name),
+            highlight(self.description)
+        );
+        text.push_str(&format!("\n{}Usage:%r", HEADING));
+        for (syntax, description) in self.usage {
+            text.push_str(&format!(
+                "\n  {}{}%r - {}",
+                HIGHLIGHT,
+                sanitize(syntax),
+                highlight(description)
+            ));
+        }
+        let types = self.argument_types();
+        if !types.is_empty() {
+            text.push_str(&format!("\n{}Arguments:%r", HEADING));
+            for ty in types {
+                let description = ARGUMENT_TYPES
+                    .iter()
+                    .find(|(name, _)| *name == ty)
+                    .map_or("", |(_, description)| description);
+                text.push_str(&format!(
+                    "\n  {}{}%r - {}",
+                    HIGHLIGHT,
+                    sanitize(ty),
+                    highlight(description)
+                ));
+            }
+        }
+        if !self.examples.is_empty() {
+            text.push_str(&format!("\n{}Examples:%r", HEADING));
+            for (example, description) in self.examples {
+                text.push_str(&format!(
+                    "\n  {}{}%r - {}",
+                    HIGHLIGHT,
+                    sanitize(example),
+                    highlight(description)
+                ));
+            }
+        }
+        text.parse().unwrap()
+    }
+}
+
+/// Turns the text in backticks into highlighted text, escaping everything else.
+fn highlight(text: &str) -> String {
+    let mut highlighted = String::new();
+    for (i, part) in sanitize(text).split('`').enumerate() {
+        if i % 2 == 1 {
+            highlighted.push_str(&format!("{}{}%r", HIGHLIGHT, part));
+        } else {
+            highlighted.push_str(part);
+        }
+    }
+    highlighted
+}
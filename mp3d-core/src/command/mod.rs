@@ -1,8 +1,19 @@
 use fxhash::FxHashMap;
 
-use crate::{entity::Entity, server::PlayerSession, textcomponent::TextComponent, world::World};
+use crate::{
+    command::docs::CommandHelp,
+    entity::Entity,
+    preferences::Preferences,
+    server::{
+        PlayerSession, access::AccessList, claims::ClaimDatabase, eventlog::EventLog,
+        teams::TeamDatabase,
+    },
+    textcomponent::{TextComponent, sanitize},
+    world::World,
+};
 
 pub mod commands;
+pub mod docs;
 mod parser;
 
 /// Context passed to command execution, containing mutable access to the server and the connection
@@ -11,9 +22,15 @@ pub struct CommandContext<'a> {
     pub connections: &'a FxHashMap<u64, u64>,
     pub sessions: &'a mut FxHashMap<u64, PlayerSession>,
     pub world: &'a mut World,
+    pub claims: &'a mut ClaimDatabase,
+    pub teams: &'a mut TeamDatabase,
+    pub access: &'a mut AccessList,
+    pub event_log: &'a mut EventLog,
     pub command_manager: &'a CommandManager,
     pub connection_id: u64,
     pub tps: u8,
+    /// Whether this is a singleplayer world, where the local player may use every command.
+    pub singleplayer: bool,
 }
 
 impl<'a> CommandContext<'a> {
@@ -26,7 +43,7 @@ impl<'a> CommandContext<'a> {
                     self.connection_id
                 )
             })
-            .map(|v| *v)
+            .copied()
     }
 
     pub fn get_sender_session(&mut self) -> Result<&mut PlayerSession, String> {
@@ -39,6 +56,30 @@ impl<'a> CommandContext<'a> {
         })
     }
 
+    /// Returns an error unless the sender is an operator, see [`crate::server::access`].
+    pub fn require_operator(&mut self) -> Result<(), String> {
+        if self.singleplayer {
+            return Ok(());
+        }
+        let username = self
+            .get_sender_session()
+            .map_err(|_| "You must be connected to use this command".to_string())?
+            .username
+            .clone();
+        if self.access.is_operator(&username) {
+            Ok(())
+        } else {
+            Err("Only operators can use this command".to_string())
+        }
+    }
+
+    /// Returns the display preferences of the sender, or the defaults if there is no sender.
+    pub fn sender_preferences(&mut self) -> Preferences {
+        self.get_sender_session()
+            .map(|session| session.preferences.clone())
+            .unwrap_or_default()
+    }
+
     pub fn get_sender(&mut self) -> Result<&mut dyn Entity, String> {
         let session_id = self.get_sender_session_id()?;
         let entity_id = self
@@ -109,7 +150,7 @@ impl CommandManager {
             if let Some(command) = self.commands.get(name) {
                 command.execute(ctx, args).map(Some)
             } else {
-                Err(format!("Unknown command: {}", name))
+                Err(format!("Unknown command: {}", sanitize(name)))
             }
         } else {
             Ok(None)
@@ -171,7 +212,7 @@ impl<'a> ArgStream<'a> {
         if self.peek().is_none() {
             Ok(())
         } else {
-            Err(format!("Leftover arguments: {}", self.rest()))
+            Err(format!("Leftover arguments: {}", sanitize(&self.rest())))
         }
     }
 }
@@ -196,8 +237,8 @@ pub trait Command {
     /// Returns the name of the command, e.g. "tp".
     fn name(&self) -> &'static str;
 
-    /// Returns a short description of the command for help messages.
-    fn description(&self) -> &'static str;
+    /// Returns the documentation of the command for /help.
+    fn help(&self) -> &'static CommandHelp;
 
     /// Executes the command with the given context and arguments. The arguments are passed as a
     /// slice of strings, which the implementation should parse according to the expected argument
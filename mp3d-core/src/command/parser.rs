@@ -1,6 +1,9 @@
 use glam::{IVec3, Vec3};
 
-use crate::command::{ArgStream, CommandArg};
+use crate::{
+    command::{ArgStream, CommandArg},
+    textcomponent::sanitize,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CoordArg {
@@ -30,27 +33,22 @@ impl CommandArg for CoordArg {
             if stripped.is_empty() {
                 Ok(Self::Relative(0.0))
             } else {
-                Ok(Self::Relative(
-                    stripped
-                        .parse()
-                        .map_err(|_| format!("Invalid coordinate: {}", arg))?,
-                ))
+                Ok(Self::Relative(stripped.parse().map_err(|_| {
+                    format!("Invalid coordinate: {}", sanitize(arg))
+                })?))
             }
         } else if let Some(stripped) = arg.strip_prefix("^") {
             if stripped.is_empty() {
                 Ok(Self::ForwardRelative(0.0))
             } else {
-                Ok(Self::ForwardRelative(
-                    stripped
-                        .parse()
-                        .map_err(|_| format!("Invalid coordinate: {}", arg))?,
-                ))
+                Ok(Self::ForwardRelative(stripped.parse().map_err(|_| {
+                    format!("Invalid coordinate: {}", sanitize(arg))
+                })?))
             }
         } else {
-            Ok(Self::Absolute(
-                arg.parse()
-                    .map_err(|_| format!("Invalid coordinate: {}", arg))?,
-            ))
+            Ok(Self::Absolute(arg.parse().map_err(|_| {
+                format!("Invalid coordinate: {}", sanitize(arg))
+            })?))
         }
     }
 }
@@ -113,7 +111,8 @@ macro_rules! num_impl {
                     let arg = args
                         .next()
                         .ok_or_else(|| format!("Expected {} but got nothing", $desc))?;
-                    arg.parse().map_err(|e| format!("Invalid {} '{}': {}", $desc, arg, e))
+                    arg.parse()
+                        .map_err(|e| format!("Invalid {} '{}': {}", $desc, sanitize(arg), e))
                 }
             }
         )*
@@ -133,6 +132,20 @@ num_impl![
     f64 => "64-bit floating point",
 ];
 
+impl CommandArg for bool {
+    fn parse<'a>(args: &mut ArgStream) -> Result<Self, String> {
+        match args.next() {
+            Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            Some(arg) => Err(format!(
+                "Expected true or false but got '{}'",
+                sanitize(arg)
+            )),
+            None => Err("Expected true or false but got nothing".to_string()),
+        }
+    }
+}
+
 impl<A: CommandArg> CommandArg for Option<A> {
     fn parse<'a>(args: &mut ArgStream) -> Result<Self, String> {
         if args.peek().is_some() {
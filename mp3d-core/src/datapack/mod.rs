@@ -1,4 +1,4 @@
-//! Module to control block drops (that's it for now).
+//! Module to control block drops and the colors of blocks on maps.
 
 use fxhash::FxHashMap;
 
@@ -83,6 +83,8 @@ pub struct LootTable {
 pub struct GameData {
     sources: DataSources,
     loot_table: LootTable,
+    /// The colors of blocks seen from above, read from `map_colors.json` when first needed.
+    map_colors: Option<FxHashMap<BlockId, [u8; 3]>>,
 }
 
 impl Default for GameData {
@@ -98,6 +100,7 @@ impl GameData {
             loot_table: LootTable {
                 block_entries: FxHashMap::default(),
             },
+            map_colors: None,
         }
     }
 
@@ -108,7 +111,10 @@ impl GameData {
 
         let str_id = block_registry().get(id).unwrap().ident;
 
-        let path = std::path::PathBuf::from(format!("loot_table/blocks/{}.json", str_id));
+        let path = std::path::PathBuf::from(format!(
+            "loot_table/blocks/{}.json",
+            crate::registry::ident_path(str_id)
+        ));
 
         let contents = self.sources.read_utf8(&path)?;
 
@@ -132,4 +138,43 @@ impl GameData {
 
         self.loot_table.block_entries.get(&id)
     }
+
+    /// Returns the colors of blocks on maps, like the web map of servers. The colors are given as
+    /// hex like `"7D7D7D"` for each block identifier in `map_colors.json`, blocks without one
+    /// aren't in the map.
+    pub fn map_colors(&mut self) -> &FxHashMap<BlockId, [u8; 3]> {
+        self.map_colors.get_or_insert_with(|| {
+            let Some(contents) = self
+                .sources
+                .read_utf8(std::path::Path::new("map_colors.json"))
+            else {
+                return FxHashMap::default();
+            };
+            let raw = match serde_json::from_str::<FxHashMap<String, String>>(&contents) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    log::error!("Failed to read map colors: {}", e);
+                    return FxHashMap::default();
+                }
+            };
+            raw.into_iter()
+                .filter_map(|(ident, hex)| {
+                    let id = block_registry().get_id(&ident);
+                    let color = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .filter(|_| hex.len() == 6);
+                    match (id, color) {
+                        (Some(id), Some(color)) => {
+                            let [_, r, g, b] = color.to_be_bytes();
+                            Some((id, [r, g, b]))
+                        }
+                        _ => {
+                            log::warn!("Invalid map color {} for {}", hex, ident);
+                            None
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
 }
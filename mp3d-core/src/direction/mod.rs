@@ -5,12 +5,14 @@
 //! direction, converting between directions and vectors, and performing arithmetic operations with
 //! directions.
 
+use serde::{Deserialize, Serialize};
+
 use crate::axis::Axis;
 
 mod arith;
 mod convert;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Direction {
     North = 0,
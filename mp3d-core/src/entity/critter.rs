@@ -0,0 +1,242 @@
+//! The critter module provides the `CritterEntity`, a small harmless animal that wanders around,
+//! walking along paths found with [`crate::entity::pathfinding`].
+
+use fxhash::hash64;
+use glam::{IVec3, Vec3, Vec3Swizzles};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    entity::{pathfinding, *},
+    physics::{self, PhysicsState},
+    saving::{Saveable, WorldLoadError, io::*},
+    world::parallel::{TickEffects, WorldView},
+};
+
+/// How far a critter wanders from where it stands, in blocks along each axis.
+const WANDER_RADIUS: i32 = 8;
+/// How many blocks above or below the picked spot a critter looks for ground to wander to.
+const WANDER_HEIGHT: i32 = 3;
+/// The most positions visited when looking for a path, so a critter gives up on unreachable spots
+/// quickly.
+const PATH_SEARCH_LIMIT: usize = 256;
+/// How long a critter stands around before wandering again, in seconds.
+const IDLE_SECONDS: std::ops::Range<u32> = 2..8;
+/// How long a critter tries to reach the next block of its path before giving up, in seconds.
+const STUCK_SECONDS: u32 = 3;
+/// How close a critter has to get to the middle of a block of its path to go on to the next one.
+const WAYPOINT_DISTANCE: f32 = 0.25;
+/// The walking speed of a critter, as a share of the walking speed of players.
+const WALK_INPUT: f32 = 0.35;
+
+pub struct CritterEntity {
+    pub entity_id: u64,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub yaw: f32,
+    pub on_ground: bool,
+    /// The blocks left to walk through, the next one last.
+    path: Vec<IVec3>,
+    /// Ticks until the critter looks for another spot to wander to.
+    idle_ticks: u32,
+    /// Ticks spent walking towards the next block of the path.
+    stuck_ticks: u32,
+    /// Ticks lived, which the randomness of the critter is derived from so that it wanders the
+    /// same way however the world is ticked.
+    age: u64,
+}
+
+impl Summonable for CritterEntity {
+    fn summon(position: Vec3) -> Self {
+        Self {
+            entity_id: 0,
+            position,
+            velocity: Vec3::ZERO,
+            yaw: 0.0,
+            on_ground: false,
+            path: Vec::new(),
+            idle_ticks: 0,
+            stuck_ticks: 0,
+            age: 0,
+        }
+    }
+}
+
+impl CritterEntity {
+    /// Picks a random spot nearby and looks for a path to it.
+    fn wander(&mut self, world: &WorldView, rng: &mut StdRng) {
+        let start = (self.position + Vec3::Y * 0.01).floor().as_ivec3();
+        let target = start
+            + IVec3::new(
+                rng.random_range(-WANDER_RADIUS..=WANDER_RADIUS),
+                0,
+                rng.random_range(-WANDER_RADIUS..=WANDER_RADIUS),
+            );
+        let Some(goal) = (-WANDER_HEIGHT..=WANDER_HEIGHT)
+            .map(|dy| target + IVec3::Y * dy)
+            .find(|pos| pathfinding::can_stand(world, *pos, Self::width(), Self::height()))
+        else {
+            return;
+        };
+        if let Some(mut path) = pathfinding::find_path(
+            world,
+            start,
+            goal,
+            Self::width(),
+            Self::height(),
+            PATH_SEARCH_LIMIT,
+        ) {
+            path.reverse();
+            self.path = path;
+            self.stuck_ticks = 0;
+        }
+    }
+
+    /// Returns how the critter moves to follow its path, going on to the next block once it
+    /// reached one.
+    fn follow_path(&mut self, tps: u8) -> MoveInput {
+        let Some(next) = self.path.last() else {
+            return MoveInput::default();
+        };
+        let offset = next.as_vec3() + Vec3::new(0.5, 0.0, 0.5) - self.position;
+        if offset.xz().length() < WAYPOINT_DISTANCE {
+            self.path.pop();
+            self.stuck_ticks = 0;
+            return MoveInput::default();
+        }
+
+        self.stuck_ticks += 1;
+        if self.stuck_ticks > STUCK_SECONDS * tps as u32 {
+            self.path.clear();
+        }
+        self.yaw = offset.x.atan2(offset.z).to_degrees();
+        MoveInput {
+            forward: WALK_INPUT,
+            jump: offset.y > physics::STEP_HEIGHT,
+            ..Default::default()
+        }
+    }
+}
+
+impl Saveable for CritterEntity {
+    fn save(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in self.position.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in self.velocity.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&self.yaw.to_le_bytes());
+        data
+    }
+
+    fn load<I: Iterator<Item = u8>>(data: &mut I, _version: u8) -> Result<Self, WorldLoadError> {
+        let position = read_vec3(data, "Critter position")?;
+        let velocity = read_vec3(data, "Critter velocity")?;
+        let yaw = read_f32(data, "Critter yaw")?;
+        Ok(Self {
+            velocity,
+            yaw,
+            ..Self::summon(position)
+        })
+    }
+}
+
+impl Entity for CritterEntity {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+
+    fn entity_type(&self) -> EntityId {
+        *entities::CRITTER
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.entity_id = id;
+    }
+
+    fn id(&self) -> u64 {
+        self.entity_id
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.entity_id.to_le_bytes());
+        for value in self.position.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&self.yaw.to_le_bytes());
+        data
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> &mut Vec3 {
+        &mut self.position
+    }
+
+    fn forward(&self) -> Vec3 {
+        physics::look_direction(self.yaw, 0.0)
+    }
+
+    fn apply_velocity(&mut self, velocity: Vec3) {
+        self.velocity += velocity;
+    }
+
+    fn width() -> f32 {
+        0.6
+    }
+
+    fn height() -> f32 {
+        0.7
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (Self::width(), Self::height())
+    }
+
+    fn tick(&mut self, world: &WorldView, _effects: &mut TickEffects, tps: u8) {
+        self.age += 1;
+        if self.path.is_empty() && self.on_ground {
+            if self.idle_ticks > 0 {
+                self.idle_ticks -= 1;
+            } else {
+                let mut rng = StdRng::seed_from_u64(hash64(&(self.entity_id, self.age)));
+                self.idle_ticks = rng.random_range(IDLE_SECONDS) * tps as u32;
+                self.wander(world, &mut rng);
+            }
+        }
+        let input = self.follow_path(tps);
+
+        let state = PhysicsState {
+            position: self.position,
+            velocity: self.velocity,
+            on_ground: self.on_ground,
+            flying: false,
+        };
+
+        let new_state = physics::step(
+            state,
+            input,
+            self.yaw,
+            Self::width(),
+            Self::height(),
+            world,
+            1.0 / tps as f32,
+        );
+
+        self.position = new_state.position;
+        self.velocity = new_state.velocity;
+        self.on_ground = new_state.on_ground;
+    }
+}
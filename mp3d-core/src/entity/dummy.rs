@@ -0,0 +1,145 @@
+//! The dummy module provides the `DummyEntity`, a target dummy that stands still and falls when
+//! the ground below it is removed.
+
+use glam::Vec3;
+
+use crate::{
+    entity::*,
+    physics::{self, PhysicsState},
+    saving::{Saveable, WorldLoadError, io::*},
+    world::parallel::{TickEffects, WorldView},
+};
+
+pub struct DummyEntity {
+    pub entity_id: u64,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub yaw: f32,
+    pub on_ground: bool,
+}
+
+impl Summonable for DummyEntity {
+    fn summon(position: Vec3) -> Self {
+        Self {
+            entity_id: 0,
+            position,
+            velocity: Vec3::ZERO,
+            yaw: 0.0,
+            on_ground: false,
+        }
+    }
+}
+
+impl Saveable for DummyEntity {
+    fn save(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in self.position.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in self.velocity.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&self.yaw.to_le_bytes());
+        data
+    }
+
+    fn load<I: Iterator<Item = u8>>(data: &mut I, _version: u8) -> Result<Self, WorldLoadError> {
+        let position = read_vec3(data, "Dummy position")?;
+        let velocity = read_vec3(data, "Dummy velocity")?;
+        let yaw = read_f32(data, "Dummy yaw")?;
+        Ok(Self {
+            entity_id: 0,
+            position,
+            velocity,
+            yaw,
+            on_ground: false,
+        })
+    }
+}
+
+impl Entity for DummyEntity {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+
+    fn entity_type(&self) -> EntityId {
+        *entities::DUMMY
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.entity_id = id;
+    }
+
+    fn id(&self) -> u64 {
+        self.entity_id
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.entity_id.to_le_bytes());
+        for value in self.position.to_array() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&self.yaw.to_le_bytes());
+        data
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> &mut Vec3 {
+        &mut self.position
+    }
+
+    fn forward(&self) -> Vec3 {
+        physics::look_direction(self.yaw, 0.0)
+    }
+
+    fn apply_velocity(&mut self, velocity: Vec3) {
+        self.velocity += velocity;
+    }
+
+    fn width() -> f32 {
+        0.6
+    }
+
+    fn height() -> f32 {
+        1.8
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (Self::width(), Self::height())
+    }
+
+    fn tick(&mut self, world: &WorldView, _effects: &mut TickEffects, tps: u8) {
+        let state = PhysicsState {
+            position: self.position,
+            velocity: self.velocity,
+            on_ground: self.on_ground,
+            flying: false,
+        };
+
+        let new_state = physics::step(
+            state,
+            MoveInput::default(),
+            self.yaw,
+            Self::width(),
+            Self::height(),
+            world,
+            1.0 / tps as f32,
+        );
+
+        self.position = new_state.position;
+        self.velocity = new_state.velocity;
+        self.on_ground = new_state.on_ground;
+    }
+}
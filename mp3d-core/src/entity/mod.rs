@@ -1,16 +1,21 @@
 //! Game entities for Mineplace3D.
 //!
-//! This module provides the `Entity` trait and some implementations like the `Player` entity.
+//! This module provides the `Entity` trait, the registry of entity types and some
+//! implementations like the `Player` entity and the wandering `Critter`.
 
 use glam::Vec3;
 
-use crate::{saving::Saveable, world::World};
+use crate::{
+    define_entities,
+    saving::Saveable,
+    world::parallel::{TickEffects, WorldView},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
-pub enum EntityType {
-    Player = 0,
-}
+define_entities!(
+    PLAYER => { ident: "player" },
+    DUMMY => { ident: "dummy", summon: DummyEntity },
+    CRITTER => { ident: "critter", summon: CritterEntity },
+);
 
 /// Represents a game entity in the world.
 pub trait Entity: std::any::Any + Saveable + Send + Sync + 'static {
@@ -20,9 +25,15 @@ pub trait Entity: std::any::Any + Saveable + Send + Sync + 'static {
     fn name(&self) -> &'static str {
         std::any::type_name::<Self>().rsplit("::").next().unwrap()
     }
-    fn entity_type(&self) -> EntityType;
+    fn entity_type(&self) -> EntityId;
+    /// The identifier of the type of this entity, like `"player"`.
+    fn ident(&self) -> &'static str {
+        entity_registry().get(self.entity_type()).unwrap().ident
+    }
     fn set_id(&mut self, id: u64);
     fn id(&self) -> u64;
+    /// The state of this entity sent to clients. Every snapshot starts with the entity ID, the
+    /// position and the yaw, so that clients can place entities of types they don't know.
     fn snapshot(&self) -> Vec<u8>;
     fn position(&self) -> Vec3;
     fn position_mut(&mut self) -> &mut Vec3;
@@ -34,11 +45,14 @@ pub trait Entity: std::any::Any + Saveable + Send + Sync + 'static {
     fn height() -> f32
     where
         Self: Sized;
+    /// The width and height of this entity, for when the type isn't known.
+    fn dimensions(&self) -> (f32, f32);
     fn requests_removal(&self) -> bool {
         false
     }
-    /// Called every 48 ticks per second.
-    fn tick(&mut self, world: &mut World, tps: u8);
+    /// Called every 48 ticks per second, possibly on another thread at the same time as other
+    /// entities. Changes to the world go into `effects` and are applied once all entities ticked.
+    fn tick(&mut self, world: &WorldView, effects: &mut TickEffects, tps: u8);
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -71,6 +85,13 @@ impl From<crate::protocol::MoveInstructions> for MoveInput {
     }
 }
 
+pub mod critter;
+pub mod dummy;
+pub mod pathfinding;
 pub mod player;
+pub mod registration;
 
+pub use critter::*;
+pub use dummy::*;
 pub use player::*;
+pub use registration::*;
@@ -0,0 +1,118 @@
+//! Finding paths for entities walking over the blocks of the world.
+//!
+//! Paths lead from block to block through the positions an entity can stand at, which are the
+//! blocks its hitbox fits into with something solid right below. From each of them an entity can
+//! walk to the four neighbouring blocks, jump up one block or drop down up to [`MAX_DROP`] blocks.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use fxhash::{FxHashMap, FxHashSet};
+use glam::{IVec3, Vec3};
+
+use crate::physics::CollisionWorld;
+
+/// The most blocks a path drops down in a single step.
+pub const MAX_DROP: i32 = 3;
+
+/// The extra cost of jumping up a block, so paths prefer to stay level.
+const JUMP_COST: u32 = 1;
+
+const DIRECTIONS: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+/// Returns the position of the feet of an entity standing in the middle of a block.
+fn feet(pos: IVec3) -> Vec3 {
+    pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5)
+}
+
+/// Returns whether an entity of the given size fits into the block at `pos`.
+fn fits(world: &impl CollisionWorld, pos: IVec3, width: f32, height: f32) -> bool {
+    !world.collides(feet(pos), width, height)
+}
+
+/// Returns whether an entity of the given size can stand with its feet in the block at `pos`.
+/// Blocks that aren't loaded hold nothing up, so paths never lead into them.
+pub fn can_stand(world: &impl CollisionWorld, pos: IVec3, width: f32, height: f32) -> bool {
+    fits(world, pos, width, height) && world.collides(feet(pos) - Vec3::Y * 0.1, width, 0.05)
+}
+
+/// Returns the positions reachable in one step from `pos`, with the cost of getting there.
+fn neighbours(
+    world: &impl CollisionWorld,
+    pos: IVec3,
+    width: f32,
+    height: f32,
+) -> impl Iterator<Item = (IVec3, u32)> {
+    DIRECTIONS.into_iter().filter_map(move |direction| {
+        let next = pos + direction;
+        if !fits(world, next, width, height) {
+            let above = next + IVec3::Y;
+            return (fits(world, pos + IVec3::Y, width, height)
+                && can_stand(world, above, width, height))
+            .then_some((above, 1 + JUMP_COST));
+        }
+        (0..=MAX_DROP)
+            .map(|drop| next - IVec3::Y * drop)
+            .take_while(|below| fits(world, *below, width, height))
+            .find(|below| can_stand(world, *below, width, height))
+            .map(|below| (below, 1))
+    })
+}
+
+fn estimate(from: IVec3, to: IVec3) -> u32 {
+    let distance = (to - from).abs();
+    (distance.x + distance.y + distance.z) as u32
+}
+
+/// Finds the shortest path for an entity of the given size from `start` to `goal` with A*,
+/// visiting at most `max_nodes` positions. The path lists the positions to walk through after
+/// `start`, ending with `goal`, or is `None` if there is no path within the limit.
+pub fn find_path(
+    world: &impl CollisionWorld,
+    start: IVec3,
+    goal: IVec3,
+    width: f32,
+    height: f32,
+    max_nodes: usize,
+) -> Option<Vec<IVec3>> {
+    let mut open = BinaryHeap::new();
+    let mut costs: FxHashMap<IVec3, u32> = FxHashMap::default();
+    let mut came_from: FxHashMap<IVec3, IVec3> = FxHashMap::default();
+    let mut closed = FxHashSet::default();
+    open.push(Reverse((estimate(start, goal), start.to_array())));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((_, pos))) = open.pop() {
+        let pos = IVec3::from_array(pos);
+        if pos == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // A position is queued again whenever a cheaper way to it is found
+        if !closed.insert(pos) {
+            continue;
+        }
+        if closed.len() > max_nodes {
+            return None;
+        }
+        let cost = costs[&pos];
+        for (next, step_cost) in neighbours(world, pos, width, height) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|&known| next_cost < known) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, pos);
+                open.push(Reverse((next_cost + estimate(next, goal), next.to_array())));
+            }
+        }
+    }
+    None
+}
@@ -3,13 +3,23 @@
 use glam::Vec3;
 
 use crate::{
+    block::BlockDef,
     entity::*,
-    item::Inventory,
+    item::{Inventory, OFFHAND_SLOT, tool},
     physics::{self, PhysicsState},
     saving::{Saveable, WorldLoadError, io::*},
-    world::World,
+    world::parallel::{TickEffects, WorldView},
 };
 
+/// The number of ticks a player has to wait after breaking, placing or interacting with a block
+/// before doing so again.
+pub const BLOCK_EDIT_COOLDOWN: u8 = 12;
+/// The health of a player that wasn't hurt.
+pub const MAX_HEALTH: f32 = 20.0;
+/// How many blocks a player can fall without getting hurt. Every block further deals one point of
+/// damage.
+pub const SAFE_FALL_DISTANCE: f32 = 3.0;
+
 pub struct PlayerEntity {
     pub entity_id: u64,
     pub username: String,
@@ -21,8 +31,13 @@ pub struct PlayerEntity {
     pub inventory: Inventory,
     pub hotbar_index: usize,
     pub flying: bool,
+    /// Ticks left until the player can edit blocks again, see [`BLOCK_EDIT_COOLDOWN`].
     pub cooldown: u8,
     pub on_ground: bool,
+    /// The player is dead at 0 and stays where they died until they respawn.
+    pub health: f32,
+    /// Fall damage taken since the last tick, which the server applies and resets.
+    pub fall_damage: f32,
 }
 
 impl PlayerEntity {
@@ -40,8 +55,14 @@ impl PlayerEntity {
             flying: false,
             cooldown: 0,
             on_ground: false,
+            health: MAX_HEALTH,
+            fall_damage: 0.0,
         }
     }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
 }
 
 impl Saveable for PlayerEntity {
@@ -59,6 +80,7 @@ impl Saveable for PlayerEntity {
         data.extend_from_slice(&self.pitch.to_le_bytes());
         data.extend_from_slice(&self.inventory.save());
         data.extend_from_slice(&[self.flying as u8]);
+        data.extend_from_slice(&self.health.to_le_bytes());
         data
     }
 
@@ -75,6 +97,7 @@ impl Saveable for PlayerEntity {
             Inventory::load(data, version)?
         };
         let flying = read_u8(data, "Player flying state")? != 0;
+        let health = read_f32(data, "Player health")?;
         Ok(Self {
             entity_id: 0,
             username,
@@ -88,10 +111,34 @@ impl Saveable for PlayerEntity {
             flying,
             cooldown: 0,
             on_ground: false,
+            health,
+            fall_damage: 0.0,
         })
     }
 }
 
+impl PlayerEntity {
+    /// Returns the slot of the tool the player breaks the block fastest with and the number of
+    /// seconds it takes. Both the selected hotbar slot and the offhand slot are considered, and
+    /// the slot is `None` if the block breaks just as fast by hand.
+    pub fn break_tool(&self, block_def: &BlockDef) -> (Option<usize>, f32) {
+        let hand = (None, tool::break_time(block_def, None));
+        [3 * 9 + self.hotbar_index, OFFHAND_SLOT]
+            .into_iter()
+            .filter_map(|slot| {
+                let tool = self.inventory.slot(slot)?.tool()?;
+                Some((Some(slot), tool::break_time(block_def, Some(tool))))
+            })
+            .fold(hand, |best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    }
+}
+
 impl Entity for PlayerEntity {
     fn as_any(&self) -> &dyn std::any::Any {
         self
@@ -105,8 +152,8 @@ impl Entity for PlayerEntity {
         self
     }
 
-    fn entity_type(&self) -> EntityType {
-        EntityType::Player
+    fn entity_type(&self) -> EntityId {
+        *entities::PLAYER
     }
 
     fn set_id(&mut self, id: u64) {
@@ -128,6 +175,7 @@ impl Entity for PlayerEntity {
         data.extend_from_slice(&self.inventory.save());
         data.extend_from_slice(&self.hotbar_index.to_le_bytes());
         data.extend_from_slice(&[self.flying as u8]);
+        data.extend_from_slice(&self.health.to_le_bytes());
         data
     }
 
@@ -161,13 +209,21 @@ impl Entity for PlayerEntity {
         1.8
     }
 
+    fn dimensions(&self) -> (f32, f32) {
+        (Self::width(), Self::height())
+    }
+
     fn requests_removal(&self) -> bool {
         false
     }
 
-    fn tick(&mut self, world: &mut World, tps: u8) {
+    fn tick(&mut self, world: &WorldView, _effects: &mut TickEffects, tps: u8) {
         self.pitch = self.pitch.clamp(-89.9, 89.9);
         self.yaw = self.yaw.rem_euclid(360.0);
+        self.cooldown = self.cooldown.saturating_sub(1);
+        if self.is_dead() {
+            return;
+        }
 
         let state = PhysicsState {
             position: self.position,
@@ -186,6 +242,12 @@ impl Entity for PlayerEntity {
             1.0 / tps as f32,
         );
 
+        // Landing stops the fall, so the speed before the step tells how far the player fell
+        if !self.on_ground && new_state.on_ground && !self.flying {
+            let distance = self.velocity.y.min(0.0).powi(2) / (2.0 * physics::GRAVITY);
+            self.fall_damage += (distance - SAFE_FALL_DISTANCE).max(0.0).floor();
+        }
+
         self.position = new_state.position;
         self.velocity = new_state.velocity;
         self.on_ground = new_state.on_ground;
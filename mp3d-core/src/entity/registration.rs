@@ -0,0 +1,129 @@
+use glam::Vec3;
+
+use crate::{
+    entity::Entity,
+    registry::{Def, DefId, LazyId, Registry, RegistryToken},
+    saving::WorldLoadError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityId(usize);
+
+impl DefId for EntityId {
+    fn new(v: usize, _token: RegistryToken) -> Self {
+        Self(v)
+    }
+
+    fn get(&self) -> usize {
+        self.0
+    }
+}
+
+/// Loads an entity saved with a chunk, from the data written by its [`Saveable`] implementation.
+///
+/// [`Saveable`]: crate::saving::Saveable
+pub type EntityLoader =
+    fn(&mut dyn Iterator<Item = u8>, u8) -> Result<Box<dyn Entity>, WorldLoadError>;
+
+pub struct EntityDef {
+    pub ident: &'static str,
+    /// Creates a new entity of this type at a position, for `/summon`. `None` for types that can't
+    /// be summoned, like players.
+    pub summon: Option<fn(Vec3) -> Box<dyn Entity>>,
+    /// Loads an entity of this type saved with a chunk. `None` for types that aren't saved with
+    /// chunks, like players.
+    pub load: Option<EntityLoader>,
+}
+
+impl Def for EntityDef {
+    type Id = EntityId;
+    fn ident(&self) -> &'static str {
+        self.ident
+    }
+}
+
+pub type EntityRegistry = Registry<EntityDef>;
+
+static ENTITY_REGISTRY: std::sync::OnceLock<EntityRegistry> = std::sync::OnceLock::new();
+
+pub fn entity_registry() -> &'static EntityRegistry {
+    ENTITY_REGISTRY
+        .get()
+        .expect("entity registry not initialized - call init_entity_registry() first")
+}
+
+pub struct EntityRegistration {
+    pub build: fn() -> EntityDef,
+    pub id_slot: &'static LazyId<EntityId>,
+}
+
+inventory::collect!(EntityRegistration);
+
+pub fn init_entity_registry() {
+    let mut registry = EntityRegistry::new();
+
+    for reg in inventory::iter::<EntityRegistration> {
+        let def = (reg.build)();
+        let def_ident = def.ident;
+        let id = registry
+            .register(def)
+            .unwrap_or_else(|e| panic!("duplicate entity ident: {}", e.ident));
+        reg.id_slot
+            .set(id)
+            .unwrap_or_else(|_| panic!("entity static for {} set twice", def_ident));
+    }
+
+    ENTITY_REGISTRY
+        .set(registry)
+        .unwrap_or_else(|_| panic!("init_entity_registry called twice"));
+}
+
+/// Entities that can be created by players or the world, see [`EntityDef::summon`].
+pub trait Summonable: Entity + Sized {
+    fn summon(position: Vec3) -> Self;
+}
+
+#[macro_export]
+macro_rules! define_entities {
+    (
+        $(
+            $name:ident => {
+                ident: $ident:expr
+                $(, summon: $summon:ty)?
+                $(,)?
+            }
+        ),* $(,)?
+    ) => {
+        pub mod entities {
+            use super::*;
+
+            $(
+                pub static $name: $crate::registry::LazyId<EntityId> =
+                    $crate::registry::LazyId::new();
+
+                ::inventory::submit! {
+                    $crate::entity::EntityRegistration {
+                        build: || EntityDef {
+                            ident: $ident,
+                            summon: define_entities!(@summon $( $summon )?),
+                            load: define_entities!(@load $( $summon )?),
+                        },
+                        id_slot: &$name,
+                    }
+                }
+            )*
+        }
+    };
+
+    (@summon $summon:ty) => {
+        Some(|position| Box::new(<$summon as $crate::entity::Summonable>::summon(position)))
+    };
+    (@summon) => { None };
+
+    (@load $summon:ty) => {
+        Some(|mut data, version| {
+            Ok(Box::new(<$summon as $crate::saving::Saveable>::load(&mut data, version)?))
+        })
+    };
+    (@load) => { None };
+}
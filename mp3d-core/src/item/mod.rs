@@ -1,10 +1,16 @@
 //! Items for a voxel engine.
 
-pub use crate::item::registration::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+pub use crate::item::{registration::*, tool::*};
 use crate::{block::*, define_items};
 
 pub mod registration;
 mod save_impls;
+pub mod tool;
+
+/// The index of the offhand slot, which comes right after the 36 general slots.
+pub const OFFHAND_SLOT: usize = 36;
 
 impl Default for ItemId {
     fn default() -> Self {
@@ -36,15 +42,64 @@ define_items!(
     BRICK_VSLAB => { ident: "brick_vslab", block: blocks::BRICK_VSLAB },
     GOLD_BLOCK => { ident: "gold_block", block: blocks::GOLD },
     DIAMOND_BLOCK => { ident: "diamond_block", block: blocks::DIAMOND },
+    BED => { ident: "bed", block: blocks::BED },
+    CHEST => { ident: "chest", block: blocks::CHEST },
+    WOODEN_PICKAXE => {
+        ident: "wooden_pickaxe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Pickaxe, speed: 2.0, durability: 60 },
+    },
+    WOODEN_SHOVEL => {
+        ident: "wooden_shovel",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Shovel, speed: 2.0, durability: 60 },
+    },
+    WOODEN_AXE => {
+        ident: "wooden_axe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Axe, speed: 2.0, durability: 60 },
+    },
+    STONE_PICKAXE => {
+        ident: "stone_pickaxe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Pickaxe, speed: 4.0, durability: 130 },
+    },
+    STONE_SHOVEL => {
+        ident: "stone_shovel",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Shovel, speed: 4.0, durability: 130 },
+    },
+    STONE_AXE => {
+        ident: "stone_axe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Axe, speed: 4.0, durability: 130 },
+    },
+    DIAMOND_PICKAXE => {
+        ident: "diamond_pickaxe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Pickaxe, speed: 8.0, durability: 1500 },
+    },
+    DIAMOND_SHOVEL => {
+        ident: "diamond_shovel",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Shovel, speed: 8.0, durability: 1500 },
+    },
+    DIAMOND_AXE => {
+        ident: "diamond_axe",
+        max_stack: 1,
+        tool: Tool { kind: ToolKind::Axe, speed: 8.0, durability: 1500 },
+    },
 );
 
 /// A struct representing a stack of items, containing a the item and the count of how many of
 /// that item are in the stack. The count is limited by the max stack size of the item. An empty
 /// stack is represented by an item of AIR and a count of 0.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct ItemStack {
     pub item: ItemId,
     pub count: u16,
+    /// How worn out the stack is, only used by tools. Stacks with different damage don't merge.
+    pub damage: u16,
 }
 
 impl ItemStack {
@@ -53,6 +108,7 @@ impl ItemStack {
         Self {
             item,
             count: count.min(item_def.max_stack),
+            damage: 0,
         }
     }
 
@@ -80,6 +136,7 @@ impl ItemStack {
             ItemStack {
                 item: self.item,
                 count: count - to_add,
+                damage: self.damage,
             }
         } else {
             ItemStack::empty()
@@ -101,9 +158,10 @@ impl ItemStack {
 
         if self.is_empty() {
             self.item = other.item;
+            self.damage = other.damage;
         }
 
-        if self.item != other.item {
+        if self.item != other.item || self.damage != other.damage {
             return *other;
         }
 
@@ -123,6 +181,7 @@ impl ItemStack {
             ItemStack {
                 item: self.item,
                 count: to_remove,
+                damage: self.damage,
             }
         } else {
             ItemStack::empty()
@@ -130,6 +189,7 @@ impl ItemStack {
 
         if self.count == 0 {
             self.item = *items::AIR;
+            self.damage = 0;
         }
 
         removed_stack
@@ -156,15 +216,73 @@ impl ItemStack {
     /// method does not check if the total count of the merged stacks would exceed the max stack
     /// size of the item, it only checks if the items are compatible for merging.
     pub fn can_merge(&self, other: &ItemStack) -> bool {
-        self.item == other.item || self.is_empty() || other.is_empty()
+        self.item == other.item && self.damage == other.damage
+            || self.is_empty()
+            || other.is_empty()
+    }
+
+    /// Simulates a click on this stack with the given stack held by the cursor. A left click puts
+    /// the held stack down or picks this stack up, a right click puts one item down or picks half
+    /// of this stack up.
+    pub fn click(&mut self, temp: &mut ItemStack, right: bool) {
+        if right {
+            if temp.is_empty() {
+                let half_count = self.count.div_ceil(2);
+                temp.take_from(self, half_count);
+            } else {
+                self.take_from(temp, 1);
+            }
+        } else if temp.is_empty() {
+            let count = self.count;
+            temp.take_from(self, count);
+        } else {
+            let count = temp.count;
+            self.take_from(temp, count);
+        }
+    }
+
+    /// Moves as many items as possible from this stack into the given slots, topping up stacks of
+    /// the same item before filling empty slots.
+    pub fn move_into(&mut self, slots: &mut [ItemStack]) {
+        for fill_empty in [false, true] {
+            for slot in slots.iter_mut() {
+                if self.is_empty() {
+                    return;
+                }
+                if slot.is_empty() == fill_empty {
+                    let count = self.count;
+                    slot.take_from(self, count);
+                }
+            }
+        }
+    }
+
+    /// Returns the tool of the item, if it is one.
+    pub fn tool(&self) -> Option<&'static Tool> {
+        if self.is_empty() {
+            return None;
+        }
+        item_registry().get(self.item).unwrap().tool.as_ref()
+    }
+
+    /// Wears out the tool in the stack by one use, emptying the stack when the tool is used up.
+    pub fn wear(&mut self) {
+        if let Some(tool) = self.tool() {
+            self.damage += 1;
+            if self.damage >= tool.durability {
+                *self = ItemStack::empty();
+            }
+        }
     }
 }
 
-/// A struct representing an inventory, storing 36 general purpose item stacks and one temporary
-/// stack used for dragging items around in the UI.
+/// A struct representing an inventory, storing 36 general purpose item stacks, the offhand stack
+/// and one temporary stack used for dragging items around in the UI.
 #[derive(Clone, Debug)]
 pub struct Inventory {
     pub main: [ItemStack; 36],
+    /// A second stack held next to the selected hotbar slot, at [`OFFHAND_SLOT`].
+    pub offhand: ItemStack,
     pub temp: ItemStack,
     pub dirty: bool,
 }
@@ -175,54 +293,127 @@ impl Default for Inventory {
     }
 }
 
+// Serialized as the general slots, the offhand and the temporary stack. `dirty` is only used by the
+// server and isn't sent.
+impl Serialize for Inventory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.main[..], &self.offhand, &self.temp).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Inventory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (main, offhand, temp) =
+            <(Vec<ItemStack>, ItemStack, ItemStack)>::deserialize(deserializer)?;
+        let main = main
+            .try_into()
+            .map_err(|main: Vec<_>| D::Error::invalid_length(main.len(), &"36 slots"))?;
+        Ok(Self {
+            main,
+            offhand,
+            temp,
+            dirty: false,
+        })
+    }
+}
+
 impl Inventory {
     /// Makes a new inventory with all item stacks initialized to empty and the temporary stack
     /// also initialized to empty.
     pub fn new() -> Self {
         Self {
             main: [ItemStack::empty(); 36],
+            offhand: ItemStack::empty(),
             temp: ItemStack::empty(),
             dirty: false,
         }
     }
 
+    /// Gets a general slot, or the offhand slot for [`OFFHAND_SLOT`].
+    pub fn slot(&self, index: usize) -> Option<&ItemStack> {
+        if index == OFFHAND_SLOT {
+            Some(&self.offhand)
+        } else {
+            self.main.get(index)
+        }
+    }
+
+    /// Gets a mutable reference to a general slot, or the offhand slot for [`OFFHAND_SLOT`].
+    pub fn slot_mut(&mut self, index: usize) -> Option<&mut ItemStack> {
+        if index == OFFHAND_SLOT {
+            Some(&mut self.offhand)
+        } else {
+            self.main.get_mut(index)
+        }
+    }
+
+    /// Returns the slots, including [`OFFHAND_SLOT`], whose stacks differ from those in `old`.
+    pub fn changed_slots(&self, old: &Inventory) -> Vec<(usize, ItemStack)> {
+        (0..=OFFHAND_SLOT)
+            .filter_map(|index| {
+                let stack = *self.slot(index)?;
+                (old.slot(index) != Some(&stack)).then_some((index, stack))
+            })
+            .collect()
+    }
+
     /// Takes the general slot into the temporary slot and leaves the remainder back to the general
     /// slot.
     pub fn take_into_temp(&mut self, index: usize) {
-        let count = self.main[index].count;
-        self.temp.take_from(&mut self.main[index], count);
+        let Some((slot, temp)) = self.slot_and_temp(index) else {
+            return;
+        };
+        let count = slot.count;
+        temp.take_from(slot, count);
         self.dirty = true;
     }
 
     /// Takes the temporary slot into the general slot and leaves the remainder back to the
     /// temporary slot.
     pub fn take_from_temp(&mut self, index: usize) {
-        let count = self.temp.count;
-        self.main[index].take_from(&mut self.temp, count);
+        let Some((slot, temp)) = self.slot_and_temp(index) else {
+            return;
+        };
+        let count = temp.count;
+        slot.take_from(temp, count);
         self.dirty = true;
     }
 
-    /// Simulates a click on a general slot.
-    pub fn click(&mut self, index: usize, right: bool) {
-        if right {
-            // Right click: If the temporary stack is empty, halve the general slot stack and take
-            // the halved amount into the temporary stack. If the temporary stack is not empty,
-            // take one item from the temporary stack into the general slot.
-            if self.temp.is_empty() {
-                let half_count = self.main[index].count.div_ceil(2);
-                self.temp.take_from(&mut self.main[index], half_count);
-            } else {
-                self.main[index].take_from(&mut self.temp, 1);
-            }
-            self.dirty = true;
+    /// Gets mutable references to a slot and the temporary slot at the same time.
+    fn slot_and_temp(&mut self, index: usize) -> Option<(&mut ItemStack, &mut ItemStack)> {
+        let slot = if index == OFFHAND_SLOT {
+            &mut self.offhand
         } else {
-            // Left click: Swap the temporary stack with the general slot stack
-            if self.temp.is_empty() {
-                self.take_into_temp(index);
-            } else {
-                self.take_from_temp(index);
+            self.main.get_mut(index)?
+        };
+        Some((slot, &mut self.temp))
+    }
+
+    /// Simulates a click on a general slot, see [`ItemStack::click`].
+    pub fn click(&mut self, index: usize, right: bool) {
+        let Some((slot, temp)) = self.slot_and_temp(index) else {
+            return;
+        };
+        slot.click(temp, right);
+        self.dirty = true;
+    }
+
+    /// Moves as many items as possible from the given stack into the general slots, hotbar first,
+    /// topping up stacks of the same item before filling empty slots.
+    pub fn insert(&mut self, stack: &mut ItemStack) {
+        let (rest, hotbar) = self.main.split_at_mut(27);
+        for fill_empty in [false, true] {
+            for slot in hotbar.iter_mut().chain(rest.iter_mut()) {
+                if stack.is_empty() {
+                    break;
+                }
+                if slot.is_empty() == fill_empty {
+                    let count = stack.count;
+                    slot.take_from(stack, count);
+                }
             }
         }
+        self.dirty = true;
     }
 
     /// Returns all slots, including the temporary slot, as a single vector of item stacks. The
@@ -296,11 +487,27 @@ impl Inventory {
         &mut self.main[3 * 9 + index]
     }
 
-    /// Clears the inventory by setting all general slots to empty and the temporary slot to empty.
+    /// Fills a hotbar slot with a full stack of an item, replacing what was in it. This is how
+    /// items are taken from the creative picker.
+    pub fn pick(&mut self, hotbar_index: usize, item: ItemId) {
+        let max_stack = item_registry().get(item).unwrap().max_stack;
+        *self.hotbar_slot_mut(hotbar_index) = ItemStack::new(item, max_stack);
+        self.dirty = true;
+    }
+
+    /// Swaps the selected hotbar slot with the offhand slot.
+    pub fn swap_offhand(&mut self, hotbar_index: usize) {
+        std::mem::swap(&mut self.main[3 * 9 + hotbar_index], &mut self.offhand);
+        self.dirty = true;
+    }
+
+    /// Clears the inventory by setting all general slots, the offhand slot and the temporary slot
+    /// to empty.
     pub fn clear(&mut self) {
         for slot in self.main.iter_mut() {
             *slot = ItemStack::empty();
         }
+        self.offhand = ItemStack::empty();
         self.temp = ItemStack::empty();
         self.dirty = true;
     }
@@ -1,5 +1,8 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _, ser::Error as _};
+
 use crate::{
     block::BlockId,
+    item::Tool,
     registry::{Def, DefId, LazyId, Registry, RegistryToken},
 };
 
@@ -16,10 +19,31 @@ impl DefId for ItemId {
     }
 }
 
+// IDs depend on the order items were registered in, so they are serialized as identifiers to stay
+// valid between different builds of the game.
+impl Serialize for ItemId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let def = item_registry()
+            .get(*self)
+            .ok_or_else(|| S::Error::custom("unregistered item ID"))?;
+        serializer.serialize_str(def.ident)
+    }
+}
+
+impl<'de> Deserialize<'de> for ItemId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ident = String::deserialize(deserializer)?;
+        item_registry()
+            .get_id(&ident)
+            .ok_or_else(|| D::Error::custom(format!("unknown item identifier: {}", ident)))
+    }
+}
+
 pub struct ItemDef {
     pub ident: &'static str,
     pub assoc_block: Option<&'static LazyId<BlockId>>,
     pub max_stack: u16,
+    pub tool: Option<Tool>,
 }
 
 impl Def for ItemDef {
@@ -47,6 +71,21 @@ pub struct ItemRegistration {
 
 inventory::collect!(ItemRegistration);
 
+/// The items registered with [`register_item`], waiting for the registry to be initialized.
+static RUNTIME_ITEMS: std::sync::Mutex<Vec<ItemDef>> = std::sync::Mutex::new(Vec::new());
+
+/// Registers an item that isn't defined with [`define_items!`](crate::define_items), like the
+/// item placing a block from [`register_block`](crate::block::register_block). This has to happen
+/// before [`init_item_registry`], and the items come after the built in ones.
+pub fn register_item(def: ItemDef) {
+    assert!(
+        ITEM_REGISTRY.get().is_none(),
+        "item {} registered after init_item_registry",
+        def.ident
+    );
+    RUNTIME_ITEMS.lock().unwrap().push(def);
+}
+
 pub fn init_item_registry() {
     let mut registry = ItemRegistry::new();
 
@@ -60,6 +99,11 @@ pub fn init_item_registry() {
             .set(id)
             .unwrap_or_else(|_| panic!("item static for {} set twice", def_ident));
     }
+    for def in std::mem::take(&mut *RUNTIME_ITEMS.lock().unwrap()) {
+        registry
+            .register(def)
+            .unwrap_or_else(|e| panic!("duplicate item ident: {}", e.ident));
+    }
 
     ITEM_REGISTRY
         .set(registry)
@@ -74,6 +118,7 @@ macro_rules! define_items {
                 ident: $ident:expr
                 $(, block: $assoc_block:expr)?
                 $(, max_stack: $max_stack:expr)?
+                $(, tool: $tool:expr)?
                 $(,)?
             }
         ),* $(,)?
@@ -90,6 +135,7 @@ macro_rules! define_items {
                             ident: $ident,
                             assoc_block: define_items!(@assoc_block $( $assoc_block )?),
                             max_stack: define_items!(@max_stack $( $max_stack )?),
+                            tool: define_items!(@tool $( $tool )?),
                         },
                         id_slot: &$name,
                     }
@@ -103,4 +149,7 @@ macro_rules! define_items {
 
     (@max_stack $max_stack:expr) => { $max_stack };
     (@max_stack) => { 64 };
+
+    (@tool $tool:expr) => { Some($tool) };
+    (@tool) => { None };
 }
@@ -46,6 +46,7 @@ impl Saveable for ItemStack {
         let item_data = self.item.save();
         data.extend_from_slice(&item_data);
         data.extend_from_slice(&self.count.to_le_bytes());
+        data.extend_from_slice(&self.damage.to_le_bytes());
         data
     }
 
@@ -55,7 +56,16 @@ impl Saveable for ItemStack {
     {
         let item = ItemId::load(data, version)?;
         let count = read_u16(data, "ItemStack::count")?;
-        Ok(ItemStack { item, count })
+        let damage = if version >= 0x09 {
+            read_u16(data, "ItemStack::damage")?
+        } else {
+            0
+        };
+        Ok(ItemStack {
+            item,
+            count,
+            damage,
+        })
     }
 }
 
@@ -66,6 +76,7 @@ impl Saveable for Inventory {
             let slot_data = slot.save();
             data.extend_from_slice(&slot_data);
         }
+        data.extend_from_slice(&self.offhand.save());
         data
     }
 
@@ -78,6 +89,9 @@ impl Saveable for Inventory {
             let slot_data = ItemStack::load(data, version)?;
             *slot = slot_data;
         }
+        if version >= 0x09 {
+            inventory.offhand = ItemStack::load(data, version)?;
+        }
         Ok(inventory)
     }
 }
@@ -0,0 +1,45 @@
+//! Tools, which break blocks of some materials faster and wear out while doing so.
+
+use crate::block::{BlockDef, Material};
+
+/// How much slower blocks that need a tool break without one.
+const NO_TOOL_PENALTY: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolKind {
+    Pickaxe,
+    Shovel,
+    Axe,
+}
+
+impl ToolKind {
+    /// Returns whether the tool breaks blocks of the material faster.
+    pub fn is_effective_on(self, material: Material) -> bool {
+        match self {
+            ToolKind::Pickaxe => matches!(material, Material::Stone | Material::Metal),
+            ToolKind::Shovel => material == Material::Soil,
+            ToolKind::Axe => matches!(material, Material::Wood | Material::Plant),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tool {
+    pub kind: ToolKind,
+    /// How many times faster the tool breaks blocks it is effective on than a hand.
+    pub speed: f32,
+    /// The number of blocks the tool can break before it is used up.
+    pub durability: u16,
+}
+
+/// Returns the number of seconds it takes to break the block with the given tool, or by hand if
+/// there is none.
+pub fn break_time(block_def: &BlockDef, tool: Option<&Tool>) -> f32 {
+    match tool {
+        Some(tool) if tool.kind.is_effective_on(block_def.material) => {
+            block_def.hardness / tool.speed
+        }
+        _ if block_def.material.needs_tool() => block_def.hardness * NO_TOOL_PENALTY,
+        _ => block_def.hardness,
+    }
+}
@@ -5,12 +5,16 @@
 use glam::{IVec3, Vec3};
 pub mod axis;
 pub mod block;
+pub mod chatlog;
 pub mod command;
 pub mod datapack;
 pub mod direction;
 pub mod entity;
 pub mod item;
+pub mod mirror;
+pub mod net;
 pub mod physics;
+pub mod preferences;
 pub mod protocol;
 pub mod registry;
 pub mod saving;
@@ -19,9 +23,14 @@ pub mod textcomponent;
 pub mod uniquequeue;
 pub mod world;
 
+/// Registers the blocks, items and entities. Only the first call does anything.
 pub fn init() {
-    block::init_block_registry();
-    item::init_item_registry();
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        block::init_block_registry();
+        item::init_item_registry();
+        entity::init_entity_registry();
+    });
 }
 
 pub(crate) fn aabb_overlap(a_min: Vec3, a_max: Vec3, b_min: Vec3, b_max: Vec3) -> bool {
@@ -0,0 +1,77 @@
+//! Mirrored building, which repeats every block the player places or breaks on the other side of
+//! one or two planes.
+
+use glam::IVec3;
+use serde::{Deserialize, Serialize};
+
+/// The planes blocks are mirrored over, which go through the center of the origin block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mirror {
+    pub origin: IVec3,
+    /// Whether blocks are mirrored over the plane facing the X axis.
+    pub x: bool,
+    /// Whether blocks are mirrored over the plane facing the Z axis.
+    pub z: bool,
+}
+
+impl Mirror {
+    /// Returns the next mirror when cycling through the planes, from the X plane to the Z plane
+    /// to both planes and then to no mirroring.
+    pub fn cycle(mirror: Option<Mirror>, origin: IVec3) -> Option<Mirror> {
+        match mirror {
+            None => Some(Mirror {
+                origin,
+                x: true,
+                z: false,
+            }),
+            Some(Mirror {
+                x: true, z: false, ..
+            }) => Some(Mirror {
+                origin,
+                x: false,
+                z: true,
+            }),
+            Some(Mirror {
+                x: false, z: true, ..
+            }) => Some(Mirror {
+                origin,
+                x: true,
+                z: true,
+            }),
+            Some(_) => None,
+        }
+    }
+
+    /// Returns the positions mirroring a block position, without the position itself.
+    pub fn mirrored(&self, position: IVec3) -> Vec<IVec3> {
+        let flip_x = IVec3::new(2 * self.origin.x - position.x, position.y, position.z);
+        let flip_z = IVec3::new(position.x, position.y, 2 * self.origin.z - position.z);
+        let flip_xz = IVec3::new(flip_x.x, position.y, flip_z.z);
+        let mut positions = Vec::new();
+        if self.x {
+            positions.push(flip_x);
+        }
+        if self.z {
+            positions.push(flip_z);
+        }
+        if self.x && self.z {
+            positions.push(flip_xz);
+        }
+        positions.retain(|&mirrored| mirrored != position);
+        positions.dedup();
+        positions
+    }
+
+    /// Describes the planes for showing in chat.
+    pub fn describe(&self) -> String {
+        let planes = match (self.x, self.z) {
+            (true, true) => "X and Z",
+            (true, false) => "X",
+            _ => "Z",
+        };
+        format!(
+            "Mirroring over the {} plane through {} {} {}",
+            planes, self.origin.x, self.origin.y, self.origin.z
+        )
+    }
+}
@@ -0,0 +1,60 @@
+//! Sending protocol messages over a byte stream, like a TCP connection to a dedicated server.
+//!
+//! Every message is sent as a frame of a 4 byte little endian length followed by that many bytes
+//! of the message in the binary encoding of [`wire`].
+
+pub mod wire;
+
+use std::io::{self, Read, Write};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The port dedicated servers listen on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 30565;
+
+/// The largest frame accepted, so that a broken or malicious peer can't make the other side
+/// allocate unbounded memory.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Writes a message as a length prefixed frame. The writer isn't flushed.
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let data = wire::encode(message)?;
+    if data.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Message of {} bytes is too large to send", data.len()),
+        ));
+    }
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(&data)
+}
+
+/// Reads a message written by [`write_message`], blocking until the whole frame arrived.
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes is too large", len),
+        ));
+    }
+    let mut data = vec![0; len];
+    reader.read_exact(&mut data)?;
+    Ok(wire::decode(&data)?)
+}
+
+/// Adds the [`DEFAULT_PORT`] to an address without a port, like `example.com` or `[::1]`.
+pub fn with_default_port(address: &str) -> String {
+    let address = address.trim();
+    match address.matches(':').count() {
+        0 => format!("{}:{}", address, DEFAULT_PORT),
+        // A bracketed IPv6 address without a port
+        _ if address.ends_with(']') => format!("{}:{}", address, DEFAULT_PORT),
+        1 => address.to_string(),
+        // A bare IPv6 address, which can't have a port without brackets
+        _ if !address.starts_with('[') => format!("[{}]:{}", address, DEFAULT_PORT),
+        _ => address.to_string(),
+    }
+}
@@ -0,0 +1,812 @@
+//! The compact binary encoding protocol messages are sent in, see [`encode`] and [`decode`].
+//!
+//! Values are written in the order serde visits them, without any field names or type tags:
+//!
+//! - Unsigned integers wider than a byte are varints: 7 bits per byte, lowest first, with the high
+//!   bit set on every byte but the last. Signed ones are zigzag encoded first, so that small
+//!   negative numbers like the coordinates of nearby positions stay short too.
+//! - `u8`, `i8` and `bool` are single bytes, and floats are their little endian bytes.
+//! - Strings, byte arrays, sequences and maps start with their length as a varint.
+//! - Options are a byte of 0 or 1 followed by the value, and enum variants are their index as a
+//!   varint followed by their fields.
+//! - Structs and tuples are just their fields one after another.
+//!
+//! As nothing in the encoding tells what type a value has, it can only be decoded into the type it
+//! was encoded from. Types skipping fields or deserializing whatever they find, like untagged enums,
+//! can't be sent this way.
+
+use std::fmt;
+
+use serde::{
+    Serialize,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor,
+    },
+    ser,
+};
+
+/// An error while encoding or decoding a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireError(String);
+
+impl WireError {
+    fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for WireError {}
+
+impl ser::Error for WireError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for WireError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl From<WireError> for std::io::Error {
+    fn from(err: WireError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Encodes a value.
+pub fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, WireError> {
+    let mut encoder = Encoder { data: Vec::new() };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.data)
+}
+
+/// Decodes a value encoded by [`encode`]. All of the data has to be used up by the value.
+pub fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T, WireError> {
+    let mut decoder = Decoder { data };
+    let value = T::deserialize(&mut decoder)?;
+    if !decoder.data.is_empty() {
+        return Err(WireError::new(format!(
+            "{} bytes left over after the value",
+            decoder.data.len()
+        )));
+    }
+    Ok(value)
+}
+
+struct Encoder {
+    data: Vec<u8>,
+}
+
+impl Encoder {
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.data.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.data.push(value as u8);
+    }
+
+    fn write_signed(&mut self, value: i64) {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), WireError> {
+        let len = len.ok_or_else(|| WireError::new("sequences must have a known length"))?;
+        self.write_varint(len as u64);
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), WireError> {
+        self.data.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), WireError> {
+        self.data.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), WireError> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), WireError> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), WireError> {
+        self.write_signed(v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), WireError> {
+        self.data.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), WireError> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), WireError> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), WireError> {
+        self.write_varint(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), WireError> {
+        self.data.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), WireError> {
+        self.data.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), WireError> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), WireError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), WireError> {
+        self.write_varint(v.len() as u64);
+        self.data.extend(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), WireError> {
+        self.data.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), WireError> {
+        self.data.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), WireError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), WireError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), WireError> {
+        self.write_varint(variant_index.into());
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), WireError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), WireError> {
+        self.write_varint(variant_index.into());
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, WireError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, WireError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, WireError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, WireError> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, WireError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, WireError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, WireError> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), WireError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    // Fields are only told apart by their position, so none can be left out
+    fn skip_field(&mut self, key: &'static str) -> Result<(), WireError> {
+        Err(WireError::new(format!("can't skip the field {}", key)))
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Encoder {
+    type Ok = ();
+    type Error = WireError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), WireError> {
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), WireError> {
+        Err(WireError::new(format!("can't skip the field {}", key)))
+    }
+
+    fn end(self) -> Result<(), WireError> {
+        Ok(())
+    }
+}
+
+struct Decoder<'de> {
+    /// The data that is left to decode.
+    data: &'de [u8],
+}
+
+impl<'de> Decoder<'de> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], WireError> {
+        if len > self.data.len() {
+            return Err(WireError::new("unexpected end of data"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, WireError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], WireError> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    fn read_varint(&mut self) -> Result<u64, WireError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            if shift == 63 && byte > 1 {
+                break;
+            }
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WireError::new("varint is too large"))
+    }
+
+    fn read_signed(&mut self) -> Result<i64, WireError> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Reads a varint that has to fit into `T`.
+    fn read_uint<T: TryFrom<u64>>(&mut self) -> Result<T, WireError> {
+        let value = self.read_varint()?;
+        T::try_from(value).map_err(|_| WireError::new(format!("{} is out of range", value)))
+    }
+
+    /// Reads a zigzag encoded varint that has to fit into `T`.
+    fn read_int<T: TryFrom<i64>>(&mut self) -> Result<T, WireError> {
+        let value = self.read_signed()?;
+        T::try_from(value).map_err(|_| WireError::new(format!("{} is out of range", value)))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = WireError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, WireError> {
+        Err(WireError::new(
+            "can't decode a value without knowing its type",
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        match self.read_byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            byte => Err(WireError::new(format!("invalid bool {}", byte))),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_i8(self.read_byte()? as i8)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_i16(self.read_int()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_i32(self.read_int()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_i64(self.read_signed()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_u8(self.read_byte()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_u16(self.read_uint()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_u32(self.read_uint()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_u64(self.read_varint()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_f32(f32::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_f64(f64::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        let value = self.read_uint()?;
+        let c = char::from_u32(value)
+            .ok_or_else(|| WireError::new(format!("invalid char {}", value)))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        let len = self.read_uint()?;
+        let s = std::str::from_utf8(self.read_bytes(len)?)
+            .map_err(|e| WireError::new(format!("invalid string: {}", e)))?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        let len = self.read_uint()?;
+        visitor.visit_borrowed_bytes(self.read_bytes(len)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        match self.read_byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            byte => Err(WireError::new(format!("invalid option tag {}", byte))),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        let remaining = self.read_uint()?;
+        visitor.visit_seq(Elements {
+            decoder: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        visitor.visit_seq(Elements {
+            decoder: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        let remaining = self.read_uint()?;
+        visitor.visit_map(Elements {
+            decoder: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, WireError> {
+        visitor.visit_u32(self.read_uint()?)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, WireError> {
+        Err(WireError::new(
+            "can't skip a value without knowing its type",
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The elements of a sequence, or the keys and values of a map.
+struct Elements<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = WireError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, WireError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_, 'de> {
+    type Error = WireError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, WireError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, WireError> {
+        seed.deserialize(&mut *self.decoder)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut Decoder<'de> {
+    type Error = WireError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), WireError> {
+        let index: u32 = self.read_uint()?;
+        let variant = seed.deserialize(IntoDeserializer::<WireError>::into_deserializer(index))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut Decoder<'de> {
+    type Error = WireError;
+
+    fn unit_variant(self) -> Result<(), WireError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, WireError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, WireError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, WireError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec3, Vec3};
+
+    use super::*;
+    use crate::{
+        block::{BlockState, blocks},
+        item::Inventory,
+        preferences::Preferences,
+        protocol::{BlockUpdate, BlockUpdateKind, C2SMessage, MoveInstructions, S2CMessage},
+        world::chunk::Chunk,
+    };
+
+    #[test]
+    fn test_wire_round_trip() {
+        crate::init();
+
+        assert_eq!(encode(&IVec3::new(1, -1, 63)).unwrap(), [2, 1, 126]);
+        for value in [0, 1, -1, 127, 300, i64::MIN, i64::MAX] {
+            assert_eq!(decode::<i64>(&encode(&value).unwrap()).unwrap(), value);
+        }
+        assert_eq!(
+            decode::<u64>(&encode(&u64::MAX).unwrap()).unwrap(),
+            u64::MAX
+        );
+        assert!(decode::<u8>(&[1, 2]).is_err());
+        assert!(decode::<String>(&[5, b'a']).is_err());
+
+        let messages = [
+            C2SMessage::Connect {
+                username: "Wire".to_string(),
+                password: "pässword".to_string(),
+                preferences: Preferences::default(),
+            },
+            C2SMessage::Move(MoveInstructions {
+                forward: -1,
+                jump: true,
+                yaw: 90.5,
+                ..Default::default()
+            }),
+            C2SMessage::RequestChunks {
+                chunk_positions: vec![IVec3::new(-3, 0, 2), IVec3::new(1000, -64, 7)],
+            },
+            C2SMessage::Disconnect,
+        ];
+        for message in messages {
+            let decoded: C2SMessage = decode(&encode(&message).unwrap()).unwrap();
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+        }
+
+        let mut chunk = Chunk::new();
+        chunk.set_block(IVec3::new(0, 0, 0), *blocks::STONE, BlockState::none());
+        chunk.set_block(IVec3::new(5, 9, 3), *blocks::GRASS, BlockState::new(1, 2));
+        chunk.set_block(IVec3::new(15, 15, 15), *blocks::DIRT, BlockState::none());
+        let messages = [
+            S2CMessage::Connected {
+                user_id: 7,
+                entity_id: u64::MAX,
+                inventory: Inventory::new(),
+                sky_seed: 1234,
+            },
+            S2CMessage::PlayerMoved {
+                entity_id: 3,
+                position: Vec3::new(0.5, -12.25, 1e9),
+                yaw: 180.0,
+                pitch: -45.0,
+            },
+            S2CMessage::BlocksUpdated {
+                updates: vec![BlockUpdate {
+                    position: IVec3::new(-1, 2, -3),
+                    block: *blocks::STONE,
+                    block_state: BlockState::none(),
+                    urgent: true,
+                    kind: BlockUpdateKind::Placed,
+                }],
+            },
+            S2CMessage::ChatMessage {
+                message: "%b7FHello%r %c[/help 2]world".parse().unwrap(),
+            },
+            S2CMessage::ChunkData {
+                chunk_position: IVec3::new(4, -2, 0),
+                chunk: Box::new(chunk),
+            },
+        ];
+        for message in messages {
+            let data = encode(&message).unwrap();
+            if matches!(message, S2CMessage::ChunkData { .. }) {
+                // Four blocks in the palette take two bits per block
+                assert!(data.len() < 1200, "chunk took {} bytes", data.len());
+            }
+            let decoded: S2CMessage = decode(&data).unwrap();
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+        }
+    }
+}
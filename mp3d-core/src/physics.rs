@@ -1,8 +1,12 @@
 //! Physics implementation for entities in Mineplace3D.
 
-use glam::Vec3;
+use glam::{IVec3, Vec3};
 
-use crate::{axis::Axis, entity::MoveInput};
+use crate::{
+    axis::Axis,
+    block::{BlockId, BlockState, block_registry},
+    entity::MoveInput,
+};
 
 pub const GRAVITY: f32 = 32.0;
 pub const MAX_FALL_SPEED: f32 = 10000.0;
@@ -14,6 +18,11 @@ pub const GROUND_ACCEL: f32 = 14.0;
 pub const AIR_ACCEL: f32 = 4.0;
 pub const FLY_ACCEL: f32 = 10.0;
 const SWEEP_ITERATIONS: u32 = 16;
+/// The height of the eyes of a player above their feet.
+pub const EYE_HEIGHT: f32 = 1.62;
+/// How far away a player can interact with blocks.
+pub const REACH_DISTANCE: f32 = 5.0;
+const RAY_STEP: f32 = 0.003;
 
 pub trait CollisionWorld {
     /// Checks for collisions between an entity (using its position, width, and height) and the
@@ -224,3 +233,49 @@ fn sweep_axis(
     }
     (with_axis(pos, safe), true)
 }
+
+/// Returns the unit vector a player with the given yaw and pitch, in degrees, is looking along.
+pub fn look_direction(yaw: f32, pitch: f32) -> Vec3 {
+    let yaw_rad = yaw.to_radians();
+    let pitch_rad = pitch.to_radians();
+    Vec3::new(
+        yaw_rad.sin() * pitch_rad.cos(),
+        -pitch_rad.sin(),
+        yaw_rad.cos() * pitch_rad.cos(),
+    )
+    .normalize()
+}
+
+/// Casts a ray through the blocks returned by `get_block`, returning the position of the first
+/// visible block hit and the normal of the hit face. The ray stops at unloaded blocks.
+pub fn cast_ray(
+    get_block: impl Fn(IVec3) -> Option<(BlockId, BlockState)>,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<(IVec3, IVec3)> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec3::ZERO || !origin.is_finite() || !direction.is_finite() {
+        return None;
+    }
+    let mut pos = origin;
+
+    for _ in 0..(max_distance / RAY_STEP) as usize {
+        let block_pos = pos.floor().as_ivec3();
+
+        let (block, state) = get_block(block_pos)?;
+
+        let local = pos - block_pos.as_vec3();
+
+        let block_def = block_registry().get(block).unwrap();
+        if block_def.visible
+            && let Some(normal) = block_def.ray_intersect(local, direction, state)
+        {
+            return Some((block_pos, normal));
+        }
+
+        pos += direction * RAY_STEP;
+    }
+
+    None
+}
@@ -0,0 +1,102 @@
+//! Display preferences of a player.
+//!
+//! The client sends its preferences when connecting, so that the server can format numbers,
+//! times and distances in the messages it sends the way the player wants them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::DAY_LENGTH;
+
+/// The unit system used for distances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Units {
+    /// One block is one meter.
+    #[default]
+    Metric,
+    /// Distances are shown in feet.
+    Imperial,
+}
+
+impl Units {
+    pub const ALL: [Units; 2] = [Units::Metric, Units::Imperial];
+
+    /// Returns the next unit system in [`Units::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the unit system.
+    pub fn name(self) -> &'static str {
+        match self {
+            Units::Metric => "Metric",
+            Units::Imperial => "Imperial",
+        }
+    }
+}
+
+/// Languages whose numbers use a decimal comma instead of a decimal point.
+const DECIMAL_COMMA_LANGUAGES: [&str; 14] = [
+    "cs", "da", "de", "es", "fi", "fr", "it", "nb", "nl", "pl", "pt", "ru", "sv", "tr",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preferences {
+    /// The locale of the player, like `en_US`.
+    pub locale: String,
+    /// Whether times are shown on a 24 hour clock instead of a 12 hour clock.
+    pub clock_24h: bool,
+    pub units: Units,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            locale: "en_US".to_string(),
+            clock_24h: true,
+            units: Units::Metric,
+        }
+    }
+}
+
+impl Preferences {
+    /// Returns the language part of the locale, like `en` for `en_US`.
+    pub fn language(&self) -> &str {
+        self.locale
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or(&self.locale)
+    }
+
+    /// Formats a number with the given number of decimals and the decimal separator of the
+    /// locale.
+    pub fn format_number(&self, value: f32, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value);
+        if DECIMAL_COMMA_LANGUAGES.contains(&self.language()) {
+            formatted.replace('.', ",")
+        } else {
+            formatted
+        }
+    }
+
+    /// Formats a distance in blocks in the preferred units.
+    pub fn format_distance(&self, blocks: f32) -> String {
+        match self.units {
+            Units::Metric => format!("{} m", self.format_number(blocks, 2)),
+            Units::Imperial => format!("{} ft", self.format_number(blocks * 3.28084, 2)),
+        }
+    }
+
+    /// Formats a time in ticks as the day and a clock time, where a day starts at 6:00.
+    pub fn format_time(&self, time: u64) -> String {
+        let minutes = (time % DAY_LENGTH * 24 * 60 / DAY_LENGTH + 6 * 60) % (24 * 60);
+        let (hours, minutes) = (minutes / 60, minutes % 60);
+        let day = time / DAY_LENGTH + 1;
+        if self.clock_24h {
+            format!("Day {}, {:02}:{:02}", day, hours, minutes)
+        } else {
+            let suffix = if hours < 12 { "AM" } else { "PM" };
+            let hours = (hours + 11) % 12 + 1;
+            format!("Day {}, {}:{:02} {}", day, hours, minutes, suffix)
+        }
+    }
+}
@@ -4,16 +4,20 @@
 //! multiplayer modes of the game.
 
 use glam::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     block::{BlockId, BlockState},
     direction::Direction,
-    textcomponent::TextComponent,
+    item::{ItemId, ItemStack},
+    mirror::Mirror,
+    preferences::Preferences,
+    textcomponent::{TextComponent, sanitize},
     world::chunk::Chunk,
 };
 
 /// Move instructions for the player.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct MoveInstructions {
     /// Forward movement: -1 (backward), 0 (none), 1 (forward), 2 (sprint).
     /// Invalid values will be treated as 0.
@@ -31,9 +35,117 @@ pub struct MoveInstructions {
     pub pitch: f32,
 }
 
+/// The most blocks a single [`C2SMessage::PlaceBlocks`] can place.
+pub const MAX_PLACED_BLOCKS: usize = 256;
+
+/// The largest pitch, in degrees, a player can look up or down.
+pub const MAX_PITCH: f32 = 89.9;
+
+/// A recorded camera position of a flythrough, see [`S2CMessage::PlayCameraPath`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    /// The position of the eye.
+    pub position: Vec3,
+    /// Yaw angle in degrees.
+    pub yaw: f32,
+    /// Pitch angle in degrees.
+    pub pitch: f32,
+}
+
+/// What killed a player, see [`DeathEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Fell too far below the world floor.
+    Void,
+    /// Hit the ground too hard.
+    Fall,
+    /// Killed with the /kill command.
+    Killed,
+}
+
+impl DeathCause {
+    /// The name of the cause, as used in translation keys.
+    pub fn name(self) -> &'static str {
+        match self {
+            DeathCause::Void => "void",
+            DeathCause::Fall => "fall",
+            DeathCause::Killed => "kill",
+        }
+    }
+}
+
+/// The death of a player, sent to every player so that clients can show it in their language.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeathEvent {
+    pub victim: String,
+    /// The player responsible for the death, if any.
+    pub attacker: Option<String>,
+    pub cause: DeathCause,
+}
+
+impl DeathEvent {
+    /// The translation key of the death message, `death.<cause>` or `death.<cause>.player` if
+    /// there is an attacker.
+    pub fn translation_key(&self) -> String {
+        match self.attacker {
+            Some(_) => format!("death.{}.player", self.cause.name()),
+            None => format!("death.{}", self.cause.name()),
+        }
+    }
+
+    /// Builds a message from a translated template, replacing `{victim}` and `{attacker}` with
+    /// the names of the players. Templates with invalid formatting codes are shown as written.
+    pub fn message(&self, template: &str) -> TextComponent {
+        let attacker = self.attacker.as_deref().unwrap_or_default();
+        let fill = |template: &str| {
+            template
+                .replace("{victim}", &sanitize(&self.victim))
+                .replace("{attacker}", &sanitize(attacker))
+        };
+        fill(template)
+            .parse()
+            .unwrap_or_else(|_| fill(&sanitize(template)).parse().unwrap())
+    }
+}
+
+/// The precision, in degrees, of the yaw and pitch sent by clients.
+pub const LOOK_PRECISION: f32 = 0.01;
+
+impl MoveInstructions {
+    /// Rounds the yaw and pitch to [`LOOK_PRECISION`], so that tiny mouse movements don't cause
+    /// a new message to be sent.
+    pub fn quantized(self) -> Self {
+        Self {
+            yaw: (self.yaw / LOOK_PRECISION).round() * LOOK_PRECISION,
+            pitch: (self.pitch / LOOK_PRECISION).round() * LOOK_PRECISION,
+            ..self
+        }
+    }
+
+    /// Makes the instructions safe to apply to a player. Non-finite angles are replaced by the
+    /// previous ones, the pitch is clamped to [`MAX_PITCH`] and the yaw is wrapped to `0..360`.
+    pub fn sanitized(self, previous_yaw: f32, previous_pitch: f32) -> Self {
+        let yaw = if self.yaw.is_finite() {
+            self.yaw
+        } else {
+            previous_yaw
+        };
+        let pitch = if self.pitch.is_finite() {
+            self.pitch
+        } else {
+            previous_pitch
+        };
+        Self {
+            yaw: yaw.rem_euclid(360.0),
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            ..self
+        }
+    }
+}
+
 /// The type of block update, which can be used to determine how the client should animate the
 /// update.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockUpdateKind {
     /// A block was placed by a player.
     Placed,
@@ -43,10 +155,17 @@ pub enum BlockUpdateKind {
     RandomTick,
     /// A block was affected by an interaction result.
     Interaction,
+    /// The block didn't change, but is resent because a client may have shown it wrongly, like
+    /// when a placement was rejected.
+    Correction,
+    /// A block was restored by /rollback.
+    Rollback,
+    /// A block was changed by an entity.
+    Entity,
 }
 
 /// Represents an update to a block at a specified position with a given block and block state.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BlockUpdate {
     pub position: IVec3,
     pub block: BlockId,
@@ -56,49 +175,124 @@ pub struct BlockUpdate {
 }
 
 /// Messages sent from the client to the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum C2SMessage {
     /// Request to join a world. This contains credentials to register the player or log in if the
-    /// player already has an account.
-    Connect { username: String, password: String },
+    /// player already has an account, and the display preferences of the player.
+    Connect {
+        username: String,
+        password: String,
+        preferences: Preferences,
+    },
     /// Request to leave a world.
     Disconnect,
-    /// Request to move the player.
+    /// Request to move the player. The instructions stay in effect until the next `Move`, so
+    /// clients may skip sending instructions that didn't change.
     Move(MoveInstructions),
     /// Request for chunk data.
     RequestChunks { chunk_positions: Vec<IVec3> },
     /// Request to send a chat message or execute a command.
     SendMessage { message: String },
-    /// Request for interaction with / placement of / removal of a block. The face is a number
-    /// from 0 to 5 in the order of NSEWUD. No block data is sent with this message, so the server
-    /// will determine the block being placed (if the targetted block is not interactable)
+    /// Request for interaction with / placement of / removal of the block the player is looking
+    /// at. The server casts a ray from the eyes of the player along `look` to find the block and
+    /// face, so clients can't reach blocks they aren't looking at. `slot` is the hotbar slot
+    /// selected when clicking, which decides the block being placed (if the targetted block is
+    /// not interactable).
     BlockClick {
-        position: IVec3,
-        face: Direction,
+        look: Vec3,
+        slot: usize,
         right: bool,
     },
+    /// Request to repeat the last block the player placed or broke at a position mirroring it
+    /// over the planes set with [`C2SMessage::SetMirror`]. `place` places the held block there if
+    /// it is empty and the same as the placed block, otherwise a block of the same type as the
+    /// broken one is broken there. Every mirrored position can be edited once per edit.
+    SetBlock { position: IVec3, place: bool },
+    /// Request to set the planes block edits are mirrored over, or to stop mirroring.
+    SetMirror { mirror: Option<Mirror> },
+    /// Request to place the block in the selected hotbar slot at many positions at once, like a
+    /// line or a rectangle of blocks, as if placed against a block on the given face. Positions
+    /// that aren't empty are skipped, and at most [`MAX_PLACED_BLOCKS`] are placed.
+    PlaceBlocks {
+        positions: Vec<IVec3>,
+        face: Direction,
+        slot: usize,
+    },
+    /// Request to stop breaking a block, sent when the player lets go of the button. Breaking
+    /// starts with a left [`C2SMessage::BlockClick`].
+    StopBreaking,
+    /// Request to swap the selected hotbar slot with the offhand slot.
+    SwapOffhand,
+    /// Request to stop spectating, started with the /spectate command.
+    StopSpectating,
     /// Request to click on an inventory slot.
     InventoryClick { idx: usize, right: bool },
     /// Request to change the hotbar slot.
     HotbarChange { idx: usize },
+    /// Request to fill a hotbar slot with a full stack of an item, picked from the list of all
+    /// items. Only allowed in singleplayer and for operators.
+    CreativePick { item: ItemId, slot: usize },
+    /// Request to click on a slot of the open container with the given ID. The slots of the
+    /// container come first, followed by the 36 general slots of the inventory. A `shift` click
+    /// moves the whole stack between the container and the inventory.
+    ContainerClick {
+        id: u64,
+        idx: usize,
+        right: bool,
+        shift: bool,
+    },
+    /// Request to spread the stack held by the cursor over slots of the open container, in the
+    /// order they were dragged over. A left drag splits it evenly, a `right` drag puts one item
+    /// in every slot.
+    ContainerDistribute {
+        id: u64,
+        slots: Vec<usize>,
+        right: bool,
+    },
+    /// Request to collect items like the stack held by the cursor from all slots of the open
+    /// container, sent when double clicking a slot.
+    ContainerCollect { id: u64 },
+    /// Request to close the open container with the given ID.
+    CloseContainer { id: u64 },
+    /// Request to come back to life at the spawn position after dying.
+    Respawn,
 }
 
 /// Messages sent from the server to the client.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum S2CMessage {
     /// Confirmation of connection to a world.
     Connected {
         user_id: u64,
         entity_id: u64,
         inventory: crate::item::Inventory,
+        /// The seed the stars of the world are generated from, see [`crate::world::sky`].
+        sky_seed: u64,
     },
     /// Notification of connection failure with a reason.
     ConnectionFailed { reason: String },
+    /// Notification that the player was kicked or isn't allowed to join, with the reason. The
+    /// server closes the connection after sending it.
+    Kicked { reason: String },
+    /// Asks the client to leave and join the server at the address instead, like when a lobby
+    /// sends players to a game server.
+    Transfer { address: String },
     /// Notification of disconnection from a world.
     Disconnected { user_id: u64 },
-    /// An entity has spawned in the world.
+    /// An entity has spawned in the world. The type is the identifier of the entity type, like
+    /// `"player"`, so that clients can tell how to draw the entity.
     EntitySpawned {
         entity_id: u64,
-        entity_type: u8,
+        entity_type: String,
+        entity_snapshot: Vec<u8>,
+        /// The name shown above the entity, which is the username for players.
+        name: Option<String>,
+    },
+    /// An entity was taken out of the world, like the player of someone who left.
+    EntityRemoved { entity_id: u64 },
+    /// Update of an entity other than a player whose snapshot changed, like a mob that moved.
+    EntityUpdated {
+        entity_id: u64,
         entity_snapshot: Vec<u8>,
     },
     /// Update of a player's position, yaw, and pitch.
@@ -110,6 +304,24 @@ pub enum S2CMessage {
     },
     /// Update of a player's inventory.
     InventoryUpdated { inventory: crate::item::Inventory },
+    /// Update of only the slots of a player's inventory that changed, see
+    /// [`crate::item::Inventory::slot`]. `temp` is only set when the temporary stack changed.
+    InventorySlotsUpdated {
+        slots: Vec<(usize, crate::item::ItemStack)>,
+        temp: Option<crate::item::ItemStack>,
+    },
+    /// Update of how far the player is in breaking the block they are looking at, from `0.0` to
+    /// `1.0`. `0.0` means they aren't breaking anything.
+    BreakProgress { progress: f32 },
+    /// The player opened a container with `rows` rows of 9 slots. The ID identifies the container
+    /// in later messages until it is closed, and its contents follow in a
+    /// [`S2CMessage::ContainerUpdated`].
+    OpenContainer { id: u64, rows: u8, title: String },
+    /// Update of the contents of the open container with the given ID.
+    ContainerUpdated { id: u64, slots: Vec<ItemStack> },
+    /// The open container with the given ID was closed by the server, like when the player walked
+    /// away from it or it was broken.
+    ContainerClosed { id: u64 },
     /// Update of multiple blocks changed in one tick.
     BlocksUpdated { updates: Vec<BlockUpdate> },
     /// Delivery of chunk data.
@@ -121,4 +333,30 @@ pub enum S2CMessage {
     ChatMessage { message: TextComponent },
     /// Notification of change of selected hotbar slot.
     HotbarChanged { idx: usize },
+    /// The current world time in ticks, sent periodically and whenever the time jumps. `advancing`
+    /// is false if the daylight cycle is stopped.
+    TimeUpdate { time: u64, advancing: bool },
+    /// The team color code of every player in a team, sent on connect and whenever the teams
+    /// change.
+    TeamsUpdated { colors: Vec<(String, u8)> },
+    /// The client should fly the camera smoothly through the keyframes over `duration` seconds,
+    /// hiding the HUD until it is done.
+    PlayCameraPath {
+        keyframes: Vec<CameraKeyframe>,
+        duration: f32,
+    },
+    /// The client should stop playing a flythrough.
+    StopCameraPath,
+    /// The player started spectating another player, whose view follows in
+    /// [`S2CMessage::SpectatorCamera`] every tick until [`S2CMessage::SpectateStopped`].
+    SpectateStarted { username: String },
+    /// The eye position and looking direction of the spectated player.
+    SpectatorCamera { camera: CameraKeyframe },
+    /// The player stopped spectating, like when the spectated player left.
+    SpectateStopped,
+    /// A player died, to be shown in the chat and the kill feed.
+    PlayerDied { event: DeathEvent },
+    /// The health of a player changed. The player died if it is 0, and stays dead until it is
+    /// restored after a [`C2SMessage::Respawn`].
+    HealthChanged { entity_id: u64, health: f32 },
 }
@@ -12,6 +12,27 @@ mod sealed {
 }
 pub use sealed::RegistryToken;
 
+/// The namespace of everything in the base game. Identifiers without a namespace are in it, so
+/// `stone` and `mineplace:stone` name the same block, while blocks added by mods are named like
+/// `mymod:ruby_block`.
+pub const DEFAULT_NAMESPACE: &str = "mineplace";
+
+/// Removes the default namespace from an identifier, which is how identifiers are kept in a
+/// registry.
+pub fn strip_default_namespace(ident: &str) -> &str {
+    ident
+        .strip_prefix(DEFAULT_NAMESPACE)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(ident)
+}
+
+/// Returns the path, without an extension, of the files for an identifier in resource and data
+/// packs. Namespaced identifiers get a folder for their namespace, so `mymod:ruby_block` becomes
+/// `mymod/ruby_block`.
+pub fn ident_path(ident: &str) -> String {
+    strip_default_namespace(ident).replace(':', "/")
+}
+
 pub trait DefId: Copy + Eq + std::hash::Hash {
     fn new(v: usize, _token: RegistryToken) -> Self;
     fn get(&self) -> usize;
@@ -30,6 +51,12 @@ impl<Id: DefId> LazyId<Id> {
     }
 }
 
+impl<Id: DefId> Default for LazyId<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<Id: DefId> std::ops::Deref for LazyId<Id> {
     type Target = Id;
     fn deref(&self) -> &Self::Target {
@@ -57,6 +84,12 @@ pub struct Registry<Entry: Def> {
     str_to_id: FxHashMap<&'static str, Entry::Id>,
 }
 
+impl<Entry: Def> Default for Registry<Entry> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<Entry: Def> Registry<Entry> {
     pub fn new() -> Self {
         Registry {
@@ -66,7 +99,7 @@ impl<Entry: Def> Registry<Entry> {
     }
 
     pub fn register(&mut self, def: Entry) -> Result<Entry::Id, DuplicateIdent> {
-        let str_id = def.ident();
+        let str_id = strip_default_namespace(def.ident());
         if self.str_to_id.contains_key(str_id) {
             return Err(DuplicateIdent { ident: str_id });
         }
@@ -101,7 +134,7 @@ impl<Entry: Def> Registry<Entry> {
 
     // #[inline]
     pub fn get_id(&self, str_id: &str) -> Option<Entry::Id> {
-        self.str_to_id.get(str_id).copied()
+        self.str_to_id.get(strip_default_namespace(str_id)).copied()
     }
 
     // #[inline]
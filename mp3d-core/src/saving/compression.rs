@@ -0,0 +1,68 @@
+//! Compression of saved chunk data.
+
+use crate::saving::WorldLoadError;
+
+/// The zstd level used for chunks, trading a little ratio for fast saving.
+const ZSTD_LEVEL: i32 = 3;
+
+/// How the data in a chunk file is compressed. Every file records the compression it was written
+/// with, so changing it for a world only affects chunks saved afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Compression {
+    /// Stored as is.
+    None = 0,
+    /// LZ4, which is very fast but compresses less.
+    Lz4 = 1,
+    /// Zstandard, which compresses well while still being fast.
+    #[default]
+    Zstd = 2,
+}
+
+impl Compression {
+    /// All compressions, indexed by their saved value.
+    pub const ALL: [Compression; 3] = [Compression::None, Compression::Lz4, Compression::Zstd];
+
+    /// Gets a compression from its saved value.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// Gets a compression from its name, as used in commands.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|compression| compression.name() == name)
+    }
+
+    /// The name of the compression, as used in commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Compresses data.
+    pub fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::None => data.to_vec(),
+            Compression::Lz4 => lz4_flex::compress_prepend_size(data),
+            // Compressing from memory into memory can't fail
+            Compression::Zstd => zstd::encode_all(data, ZSTD_LEVEL).unwrap(),
+        }
+    }
+
+    /// Decompresses data written by [`Compression::compress`].
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, WorldLoadError> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(data)
+                .map_err(|e| WorldLoadError::InvalidSaveFormat(format!("Invalid LZ4 data: {}", e))),
+            Compression::Zstd => zstd::decode_all(data).map_err(|e| {
+                WorldLoadError::InvalidSaveFormat(format!("Invalid zstd data: {}", e))
+            }),
+        }
+    }
+}
@@ -6,6 +6,8 @@
 pub enum WorldLoadError {
     MissingSaveFile(std::path::PathBuf),
     InvalidSaveFormat(String),
+    /// Migrating the save to the current format failed, see [`crate::saving::migration`].
+    MigrationFailed(String),
 }
 
 impl std::fmt::Display for WorldLoadError {
@@ -15,6 +17,7 @@ impl std::fmt::Display for WorldLoadError {
                 write!(f, "Save file not found: {}", path.display())
             }
             WorldLoadError::InvalidSaveFormat(msg) => write!(f, "Invalid save format: {}", msg),
+            WorldLoadError::MigrationFailed(msg) => write!(f, "Save migration failed: {}", msg),
         }
     }
 }
@@ -38,6 +41,9 @@ impl From<WorldLoadError> for std::io::Error {
                 std::io::ErrorKind::InvalidData,
                 format!("Invalid save format: {}", msg),
             ),
+            WorldLoadError::MigrationFailed(msg) => {
+                std::io::Error::other(format!("Save migration failed: {}", msg))
+            }
         }
     }
 }
@@ -0,0 +1,357 @@
+//! Upgrading saves written by older versions of the game to the current save format.
+//!
+//! Every change to the save format bumps [`SAVE_VERSION`] and adds a [`Migration`] to
+//! [`MIGRATIONS`], which rewrites the files of a save in the previous version to the new one.
+//! [`migrate`] runs the migrations a save needs in order when the world is loaded, after copying
+//! the save to a backup folder, so a failed migration never loses a world.
+//!
+//! Saves older than [`FIRST_MIGRATED_VERSION`] predate migrations and are never migrated. The
+//! loader still reads them directly, as it handles every version, and they are written in the
+//! current version the next time the world is saved.
+
+use std::path::{Path, PathBuf};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    entity::MAX_HEALTH,
+    saving::{SAVE_VERSION, WorldLoadError, compression::Compression},
+    world::chunkio::{self, RegionChunks},
+};
+
+/// The oldest save version that is upgraded through migrations.
+pub const FIRST_MIGRATED_VERSION: u8 = 0x0A;
+
+/// The name of the folder in a save that backups are copied to before migrating.
+pub const BACKUPS_DIR: &str = "backups";
+
+/// A step upgrading a save from one version to the next.
+pub struct Migration {
+    /// The version upgraded from. The save is in version `from + 1` afterwards.
+    pub from: u8,
+    /// What the migration changes, for logging.
+    pub description: &'static str,
+    /// Rewrites the files of the save in the given folder. The version in "save.bin" is updated
+    /// by [`migrate`] afterwards, so it doesn't need to be changed here.
+    pub apply: fn(&Path) -> Result<(), WorldLoadError>,
+}
+
+/// All migrations, ordered by the version they upgrade from.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0x0A,
+        description: "Record the game version that last saved the world in save.bin",
+        apply: add_saved_with,
+    },
+    Migration {
+        from: 0x0B,
+        description: "Record the compression of every chunk file",
+        apply: add_chunk_compression,
+    },
+    Migration {
+        from: 0x0C,
+        description: "Save entities with the chunk they are in instead of in entities.bin",
+        apply: move_entities_to_chunks,
+    },
+    Migration {
+        from: 0x0D,
+        description: "Pack the chunk files into region files",
+        apply: pack_chunks_into_regions,
+    },
+    Migration {
+        from: 0x0E,
+        description: "Record the health of every player",
+        apply: add_player_health,
+    },
+];
+
+/// 0x0A to 0x0B: "save.bin" ends with the game version that last saved the world. It isn't known
+/// for older saves, so it is left empty.
+fn add_saved_with(path: &Path) -> Result<(), WorldLoadError> {
+    let save_path = path.join("save.bin");
+    let mut save_content = std::fs::read(&save_path)
+        .map_err(|_| WorldLoadError::MissingSaveFile(save_path.clone()))?;
+    save_content.push(0);
+    std::fs::write(&save_path, save_content).map_err(|e| io_error(&save_path, e))
+}
+
+/// 0x0B to 0x0C: chunk files start with the compression of the rest of the file, and "save.bin"
+/// ends with the compression to save chunks with. Existing chunk files aren't compressed, while
+/// the world compresses chunks with the default from now on.
+fn add_chunk_compression(path: &Path) -> Result<(), WorldLoadError> {
+    let chunks_dir = path.join("chunks");
+    for entry in std::fs::read_dir(&chunks_dir).map_err(|e| io_error(&chunks_dir, e))? {
+        let chunk_path = entry.map_err(|e| io_error(&chunks_dir, e))?.path();
+        if chunk_path.extension().is_none_or(|ext| ext != "bin") {
+            continue;
+        }
+        let chunk_data = std::fs::read(&chunk_path).map_err(|e| io_error(&chunk_path, e))?;
+        let mut migrated = vec![Compression::None as u8];
+        migrated.extend(chunk_data);
+        std::fs::write(&chunk_path, migrated).map_err(|e| io_error(&chunk_path, e))?;
+    }
+
+    let save_path = path.join("save.bin");
+    let mut save_content = std::fs::read(&save_path)
+        .map_err(|_| WorldLoadError::MissingSaveFile(save_path.clone()))?;
+    save_content.push(Compression::default() as u8);
+    std::fs::write(&save_path, save_content).map_err(|e| io_error(&save_path, e))
+}
+
+/// 0x0C to 0x0D: chunk files end with the entities in the chunk, and "entities.bin" is gone. No
+/// entity type other than players, which are saved separately, could be loaded from
+/// "entities.bin" in 0x0C, so it can only be empty.
+fn move_entities_to_chunks(path: &Path) -> Result<(), WorldLoadError> {
+    let entities_path = path.join("entities.bin");
+    if let Ok(entities_data) = std::fs::read(&entities_path) {
+        if entities_data.iter().take(8).any(|&byte| byte != 0) {
+            return Err(WorldLoadError::MigrationFailed(
+                "entities.bin holds entities of unknown types".to_string(),
+            ));
+        }
+        std::fs::remove_file(&entities_path).map_err(|e| io_error(&entities_path, e))?;
+    }
+
+    let chunks_dir = path.join("chunks");
+    for entry in std::fs::read_dir(&chunks_dir).map_err(|e| io_error(&chunks_dir, e))? {
+        let chunk_path = entry.map_err(|e| io_error(&chunks_dir, e))?.path();
+        if chunk_path.extension().is_none_or(|ext| ext != "bin") {
+            continue;
+        }
+        let chunk_data = std::fs::read(&chunk_path).map_err(|e| io_error(&chunk_path, e))?;
+        let Some(compression) = chunk_data.first().copied().and_then(Compression::from_u8) else {
+            return Err(WorldLoadError::InvalidSaveFormat(format!(
+                "Invalid compression in {}",
+                chunk_path.display()
+            )));
+        };
+        let mut data = compression.decompress(&chunk_data[1..])?;
+        // No entities
+        data.extend(0u32.to_le_bytes());
+        let mut migrated = vec![compression as u8];
+        migrated.extend(compression.compress(&data));
+        std::fs::write(&chunk_path, migrated).map_err(|e| io_error(&chunk_path, e))?;
+    }
+    Ok(())
+}
+
+/// 0x0D to 0x0E: chunks are stored in region files in "regions" instead of a file per chunk in
+/// "chunks", which is removed. The data of every chunk stays the same.
+fn pack_chunks_into_regions(path: &Path) -> Result<(), WorldLoadError> {
+    let chunks_dir = path.join("chunks");
+    let mut regions: FxHashMap<PathBuf, RegionChunks> = FxHashMap::default();
+    for entry in std::fs::read_dir(&chunks_dir).map_err(|e| io_error(&chunks_dir, e))? {
+        let chunk_path = entry.map_err(|e| io_error(&chunks_dir, e))?.path();
+        let Some(chunk_pos) = chunk_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| chunkio::file_name_pos(name, "chunk_"))
+        else {
+            continue;
+        };
+        let chunk_data = std::fs::read(&chunk_path).map_err(|e| io_error(&chunk_path, e))?;
+        regions
+            .entry(chunkio::region_file_path(path, chunk_pos))
+            .or_default()
+            .insert(chunk_pos, chunk_data);
+    }
+
+    let regions_dir = path.join("regions");
+    std::fs::create_dir_all(&regions_dir).map_err(|e| io_error(&regions_dir, e))?;
+    for (region_path, chunks) in regions {
+        chunkio::write_region(&region_path, &chunks).map_err(|e| io_error(&region_path, e))?;
+    }
+    std::fs::remove_dir_all(&chunks_dir).map_err(|e| io_error(&chunks_dir, e))
+}
+
+/// 0x0E to 0x0F: player files end with the health of the player. Nobody could get hurt before, so
+/// every player has full health.
+fn add_player_health(path: &Path) -> Result<(), WorldLoadError> {
+    let players_dir = path.join("players");
+    if !players_dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&players_dir).map_err(|e| io_error(&players_dir, e))? {
+        let player_path = entry.map_err(|e| io_error(&players_dir, e))?.path();
+        if player_path.extension().is_none_or(|ext| ext != "bin") {
+            continue;
+        }
+        let mut player_data = std::fs::read(&player_path).map_err(|e| io_error(&player_path, e))?;
+        player_data.extend_from_slice(&MAX_HEALTH.to_le_bytes());
+        std::fs::write(&player_path, player_data).map_err(|e| io_error(&player_path, e))?;
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> WorldLoadError {
+    WorldLoadError::MigrationFailed(format!("{}: {}", path.display(), error))
+}
+
+/// Reads the save format version of the save in the given folder.
+pub fn save_version(path: &Path) -> Result<u8, WorldLoadError> {
+    let save_path = path.join("save.bin");
+    let save_content =
+        std::fs::read(&save_path).map_err(|_| WorldLoadError::MissingSaveFile(save_path))?;
+    save_content
+        .first()
+        .copied()
+        .ok_or_else(|| WorldLoadError::InvalidSaveFormat("Save file is empty".to_string()))
+}
+
+/// Returns the migrations upgrading a save in `version` to [`SAVE_VERSION`], in the order they
+/// have to run. Saves older than [`FIRST_MIGRATED_VERSION`] need none, as the migrations expect
+/// the files of a 0x0A save. Fails for saves written by a newer version of the game.
+pub fn plan(version: u8) -> Result<Vec<&'static Migration>, WorldLoadError> {
+    if version > SAVE_VERSION {
+        return Err(WorldLoadError::InvalidSaveFormat(format!(
+            "Unsupported save version: {}",
+            version
+        )));
+    }
+    if version < FIRST_MIGRATED_VERSION {
+        return Ok(Vec::new());
+    }
+    let steps: Vec<_> = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.from >= version)
+        .collect();
+    debug_assert!(
+        steps
+            .iter()
+            .zip(version..)
+            .all(|(migration, from)| migration.from == from),
+        "Migrations must be ordered without gaps"
+    );
+    Ok(steps)
+}
+
+/// Upgrades the save in the given folder to [`SAVE_VERSION`], returning the migrations that ran.
+///
+/// Before anything is changed the whole save is copied to `backups/v{version}` inside it. With
+/// `dry_run` nothing is written at all and the migrations that would run are returned.
+pub fn migrate(path: &Path, dry_run: bool) -> Result<Vec<&'static Migration>, WorldLoadError> {
+    let version = save_version(path)?;
+    let steps = plan(version)?;
+    if steps.is_empty() || dry_run {
+        return Ok(steps);
+    }
+
+    let backup_path = backup(path, version)?;
+    log::info!(
+        "Backed up the save in version {} to {}",
+        version,
+        backup_path.display()
+    );
+
+    let save_path = path.join("save.bin");
+    for migration in &steps {
+        log::info!(
+            "Migrating save from version {} to {}: {}",
+            migration.from,
+            migration.from + 1,
+            migration.description
+        );
+        (migration.apply)(path)?;
+
+        let mut save_content = std::fs::read(&save_path).map_err(|e| io_error(&save_path, e))?;
+        save_content[0] = migration.from + 1;
+        std::fs::write(&save_path, save_content).map_err(|e| io_error(&save_path, e))?;
+    }
+    Ok(steps)
+}
+
+/// Copies the save to a backup folder named after its version, returning the backup's path.
+fn backup(path: &Path, version: u8) -> Result<PathBuf, WorldLoadError> {
+    let backups_dir = path.join(BACKUPS_DIR);
+    let mut backup_path = backups_dir.join(format!("v{}", version));
+    let mut attempt = 1;
+    while backup_path.exists() {
+        attempt += 1;
+        backup_path = backups_dir.join(format!("v{}-{}", version, attempt));
+    }
+    copy_dir(path, &backup_path, &backups_dir)?;
+    Ok(backup_path)
+}
+
+/// Recursively copies a folder, skipping `skip` so backups aren't copied into themselves.
+fn copy_dir(from: &Path, to: &Path, skip: &Path) -> Result<(), WorldLoadError> {
+    std::fs::create_dir_all(to).map_err(|e| io_error(to, e))?;
+    for entry in std::fs::read_dir(from).map_err(|e| io_error(from, e))? {
+        let entry = entry.map_err(|e| io_error(from, e))?;
+        let entry_path = entry.path();
+        if entry_path == skip {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry_path.is_dir() {
+            copy_dir(&entry_path, &target, skip)?;
+        } else {
+            std::fs::copy(&entry_path, &target).map_err(|e| io_error(&entry_path, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec3;
+
+    use super::*;
+
+    #[test]
+    fn test_migrate_backs_up_and_upgrades() {
+        let path = std::env::temp_dir().join(format!("mp3d-migration-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(path.join("chunks")).unwrap();
+        let old_save = vec![0x0A, 1, 2, 3];
+        std::fs::write(path.join("save.bin"), &old_save).unwrap();
+        std::fs::write(path.join("chunks").join("chunk_0_0_0.bin"), [0, 0]).unwrap();
+
+        let planned = migrate(&path, true).unwrap();
+        assert_eq!(planned.len(), MIGRATIONS.len());
+        assert_eq!(std::fs::read(path.join("save.bin")).unwrap(), old_save);
+        assert!(!path.join(BACKUPS_DIR).exists());
+
+        migrate(&path, false).unwrap();
+        assert_eq!(save_version(&path).unwrap(), SAVE_VERSION);
+        let backup_path = path.join(BACKUPS_DIR).join("v10");
+        assert_eq!(
+            std::fs::read(backup_path.join("save.bin")).unwrap(),
+            old_save
+        );
+        assert!(backup_path.join("chunks").join("chunk_0_0_0.bin").exists());
+        assert!(!path.join("chunks").exists());
+        let region_path = chunkio::region_file_path(&path, IVec3::ZERO);
+        let region = chunkio::read_region(&region_path, IVec3::ZERO).unwrap();
+        assert_eq!(region.len(), 1);
+        assert_eq!(
+            region[&IVec3::ZERO],
+            [Compression::None as u8, 0, 0, 0, 0, 0, 0]
+        );
+
+        assert!(migrate(&path, false).unwrap().is_empty());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_skips_migrations_before_first_migrated_version() {
+        let path =
+            std::env::temp_dir().join(format!("mp3d-migration-legacy-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(path.join("chunks")).unwrap();
+        std::fs::create_dir_all(path.join("players")).unwrap();
+        // 0x06: version, generator version, seed and time
+        let mut old_save = vec![0x06, 0x02];
+        old_save.extend(42i32.to_le_bytes());
+        old_save.extend(1234u64.to_le_bytes());
+        std::fs::write(path.join("save.bin"), &old_save).unwrap();
+        std::fs::write(path.join("entities.bin"), 0u64.to_le_bytes()).unwrap();
+
+        assert!(migrate(&path, true).unwrap().is_empty());
+        let world = crate::world::World::load(&path).unwrap();
+        assert_eq!(world.time, 1234);
+        assert_eq!(std::fs::read(path.join("save.bin")).unwrap(), old_save);
+        assert!(!path.join(BACKUPS_DIR).exists());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
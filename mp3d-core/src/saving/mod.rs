@@ -2,7 +2,7 @@
 //! versioned format.
 
 /// The current version of the world save format (in beta).
-pub const SAVE_VERSION: u8 = 0x06;
+pub const SAVE_VERSION: u8 = 0x0F;
 
 /// The current generator version. 0x00 is used for alpha generators and 0x01 and onwards are used
 /// for beta generators.
@@ -16,7 +16,9 @@ pub trait Saveable {
         Self: Sized;
 }
 
+pub mod compression;
 pub mod error;
 pub mod io;
+pub mod migration;
 
 pub use error::WorldLoadError;
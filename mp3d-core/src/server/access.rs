@@ -0,0 +1,67 @@
+//! Who may join a server, and who may decide that.
+//!
+//! Banned players can't join, and while the whitelist is on only the players on it can. Bans and
+//! the whitelist are managed with `/ban`, `/unban`, `/whitelist` and `/kick`, which only operators
+//! can use. Operators are added with `op <name>` in the console of a dedicated server, or by
+//! listing them under `operators` in `access.json` in the world folder.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The reason given to players who are kicked without one.
+pub const DEFAULT_KICK_REASON: &str = "Kicked by an operator";
+/// The reason given to banned players if the ban has none.
+pub const DEFAULT_BAN_REASON: &str = "Banned by an operator";
+
+/// What is saved to `access.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessRules {
+    /// Whether only the players on the whitelist can join.
+    pub whitelist_enabled: bool,
+    pub whitelist: BTreeSet<String>,
+    /// The banned players with the reason of their ban.
+    pub bans: BTreeMap<String, String>,
+    /// The players allowed to kick, ban and manage the whitelist.
+    pub operators: BTreeSet<String>,
+}
+
+/// The access rules of a world.
+pub struct AccessList {
+    pub rules: AccessRules,
+    pub file_path: PathBuf,
+}
+
+impl AccessList {
+    pub fn load(file_path: PathBuf) -> Self {
+        let rules = std::fs::read(&file_path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        Self { rules, file_path }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(&self.rules)?;
+        std::fs::write(&self.file_path, data)
+    }
+
+    /// Returns why the player can't join, if they can't.
+    pub fn check(&self, username: &str) -> Result<(), String> {
+        if let Some(reason) = self.rules.bans.get(username) {
+            return Err(format!("You are banned from this server: {}", reason));
+        }
+        if self.rules.whitelist_enabled && !self.rules.whitelist.contains(username) {
+            return Err("You are not whitelisted on this server".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_operator(&self, username: &str) -> bool {
+        self.rules.operators.contains(username)
+    }
+}
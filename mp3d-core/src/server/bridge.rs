@@ -0,0 +1,349 @@
+//! Bridging the chat of a server to other chats, like a Discord channel.
+//!
+//! A bridge is set up with a `bridge.json` in the world folder:
+//!
+//! ```json
+//! {
+//!     "webhook": "https://discord.com/api/webhooks/...",
+//!     "listen": "127.0.0.1:25566",
+//!     "token": "a long random secret"
+//! }
+//! ```
+//!
+//! Every field is optional. With a `webhook`, chat messages, players joining and leaving and
+//! deaths are posted to that URL as `{"content": "...", "event": "chat", "player": "..."}`, which
+//! Discord webhooks show as a message. With `listen`, messages from elsewhere are accepted as
+//! `POST /chat` requests with a body like `{"name": "...", "message": "..."}` on that address and
+//! shown in chat behind `[Bridge]`. If a `token` is set, those requests need an
+//! `Authorization: Bearer <token>` header, which should always be the case unless the address is
+//! only reachable from the machine itself.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    process::{Command, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    protocol::DeathEvent,
+    textcomponent::{TextComponent, sanitize},
+};
+
+/// How long posting to the webhook may take before giving up, in seconds.
+const TIMEOUT_SECONDS: u32 = 10;
+/// The largest body accepted by the inbound endpoint, in bytes.
+const MAX_REQUEST_SIZE: usize = 4096;
+/// How many bytes a request to the inbound endpoint can have besides the body, for the request
+/// line and the headers. Nothing past these and [`MAX_REQUEST_SIZE`] is read.
+const MAX_HEADER_SIZE: usize = 8192;
+/// The most requests to the inbound endpoint handled at once. Further connections are closed
+/// right away.
+const MAX_CONNECTIONS: usize = 8;
+/// The longest name and message accepted from the inbound endpoint, in characters.
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// The contents of `bridge.json`, see the [module documentation](self).
+#[derive(Debug, Default, Deserialize)]
+pub struct BridgeConfig {
+    pub webhook: Option<String>,
+    pub listen: Option<String>,
+    pub token: Option<String>,
+}
+
+/// Something that happened on the server, posted to the webhook.
+#[derive(Debug, Serialize)]
+struct WebhookPost {
+    /// The event as a line of text, which is what Discord shows.
+    content: String,
+    event: &'static str,
+    player: String,
+    /// Keeps Discord from pinging anyone named in a message.
+    allowed_mentions: AllowedMentions,
+}
+
+#[derive(Debug, Serialize)]
+struct AllowedMentions {
+    parse: [&'static str; 0],
+}
+
+/// A message sent to the inbound endpoint.
+#[derive(Debug, Deserialize)]
+pub struct BridgeMessage {
+    pub name: String,
+    pub message: String,
+}
+
+impl BridgeMessage {
+    /// The message as shown in chat. Formatting codes in the message are shown as written and
+    /// line breaks as spaces.
+    pub fn text(&self) -> TextComponent {
+        let clean = |text: &str| sanitize(&text.replace(char::is_control, " "));
+        format!(
+            "%b7F[Bridge]%r {}: {}",
+            clean(&self.name),
+            clean(&self.message)
+        )
+        .parse()
+        .unwrap()
+    }
+}
+
+/// A chat bridge, see the [module documentation](self).
+pub struct ChatBridge {
+    /// The posts waiting to be sent to the webhook.
+    posts: Option<Sender<WebhookPost>>,
+    messages: Option<Receiver<BridgeMessage>>,
+}
+
+impl ChatBridge {
+    /// Loads the bridge configured in the `bridge.json` at `path`, returning `None` if there is
+    /// none.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let config = std::fs::read_to_string(path)?;
+        let config: BridgeConfig = serde_json::from_str(&config)?;
+        Self::new(config).map(Some)
+    }
+
+    /// Starts posting to the webhook and listening for messages, if configured to.
+    pub fn new(config: BridgeConfig) -> io::Result<Self> {
+        let posts = config.webhook.map(|url| {
+            let (posts, receiver) = mpsc::channel();
+            thread::spawn(move || post_to_webhook(&url, receiver));
+            posts
+        });
+
+        let messages = match config.listen {
+            Some(address) => {
+                let listener = TcpListener::bind(&address)?;
+                if config.token.is_none() {
+                    log::warn!(
+                        "The chat bridge on {} has no token, anyone who can reach it can chat",
+                        address
+                    );
+                }
+                log::info!("Accepting bridged chat messages on {}", address);
+                let (sender, messages) = mpsc::channel();
+                let token = config.token;
+                let connections = Arc::new(AtomicUsize::new(0));
+                thread::spawn(move || {
+                    for stream in listener.incoming() {
+                        let Ok(stream) = stream else {
+                            continue;
+                        };
+                        if connections.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                            connections.fetch_sub(1, Ordering::AcqRel);
+                            log::debug!("Too many bridged chat requests, closed a connection");
+                            continue;
+                        }
+                        let sender = sender.clone();
+                        let token = token.clone();
+                        let connections = connections.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = receive(stream, token.as_deref(), &sender) {
+                                log::debug!("Bridged chat request failed: {}", e);
+                            }
+                            connections.fetch_sub(1, Ordering::AcqRel);
+                        });
+                    }
+                });
+                Some(messages)
+            }
+            None => None,
+        };
+
+        Ok(Self { posts, messages })
+    }
+
+    fn post(&self, event: &'static str, player: &str, content: String) {
+        if let Some(posts) = &self.posts {
+            let _ = posts.send(WebhookPost {
+                content,
+                event,
+                player: player.to_string(),
+                allowed_mentions: AllowedMentions { parse: [] },
+            });
+        }
+    }
+
+    /// Posts a chat message, with its formatting codes left out.
+    pub fn chat(&self, player: &str, message: &str) {
+        let Ok(message) = message.parse::<TextComponent>() else {
+            return;
+        };
+        self.post(
+            "chat",
+            player,
+            format!("{}: {}", player, message.plain_text()),
+        );
+    }
+
+    pub fn joined(&self, player: &str) {
+        self.post("join", player, format!("{} joined the server", player));
+    }
+
+    pub fn left(&self, player: &str) {
+        self.post("leave", player, format!("{} left the server", player));
+    }
+
+    /// Posts a death. The server has no translations, so the cause is given by its name.
+    pub fn died(&self, event: &DeathEvent) {
+        let content = match &event.attacker {
+            Some(attacker) => format!(
+                "{} died ({}, by {})",
+                event.victim,
+                event.cause.name(),
+                attacker
+            ),
+            None => format!("{} died ({})", event.victim, event.cause.name()),
+        };
+        self.post("death", &event.victim, content);
+    }
+
+    /// Returns the messages received since the last call.
+    pub fn poll(&self) -> Vec<BridgeMessage> {
+        self.messages
+            .as_ref()
+            .map(|messages| messages.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Posts everything sent to `posts` to the webhook at `url` in order, with the `curl` command,
+/// until the bridge is dropped.
+fn post_to_webhook(url: &str, posts: Receiver<WebhookPost>) {
+    let mut failed = false;
+    for post in posts {
+        let result = Command::new("curl")
+            .args(["--silent", "--fail", "--max-time"])
+            .arg(TIMEOUT_SECONDS.to_string())
+            .args(["--header", "Content-Type: application/json", "--user-agent"])
+            .arg(format!("mineplace3d/{}", env!("CARGO_PKG_VERSION")))
+            .args(["--data-binary", "@-", "--output", "/dev/null"])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut curl| {
+                curl.stdin
+                    .take()
+                    .unwrap()
+                    .write_all(&serde_json::to_vec(&post)?)?;
+                curl.wait()
+            });
+        // Only the first of a run of failures is logged, so that a broken webhook doesn't flood
+        // the log
+        match result {
+            Ok(status) if status.success() => failed = false,
+            Ok(status) => {
+                if !failed {
+                    log::warn!("Posting to the chat bridge webhook failed with {}", status);
+                }
+                failed = true;
+            }
+            Err(e) => {
+                if !failed {
+                    log::warn!("Failed to run curl for the chat bridge webhook: {}", e);
+                }
+                failed = true;
+            }
+        }
+    }
+}
+
+/// Compares a token given in a request with the configured one. Takes as long no matter how much
+/// of the token matches, so the time it takes doesn't give the token away.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Answers one request to the inbound endpoint, passing the message on to the server.
+fn receive(
+    stream: TcpStream,
+    token: Option<&str>,
+    sender: &Sender<BridgeMessage>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new((&stream).take((MAX_HEADER_SIZE + MAX_REQUEST_SIZE) as u64));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut authorized = token.is_none();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().unwrap_or(usize::MAX);
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorized |= token.is_some_and(|token| {
+                value
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|given| tokens_match(given, token))
+            });
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = if (parts.next(), parts.next()) != (Some("POST"), Some("/chat")) {
+        ("404 Not Found", "Messages are sent with POST /chat")
+    } else if !authorized {
+        ("401 Unauthorized", "Missing or wrong token")
+    } else if content_length > MAX_REQUEST_SIZE {
+        ("413 Payload Too Large", "The message is too long")
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        match serde_json::from_slice::<BridgeMessage>(&body) {
+            Ok(message)
+                if !message.name.trim().is_empty()
+                    && !message.message.trim().is_empty()
+                    && message.name.chars().count() <= MAX_NAME_LENGTH
+                    && message.message.chars().count() <= MAX_MESSAGE_LENGTH =>
+            {
+                let _ = sender.send(message);
+                ("200 OK", "Sent")
+            }
+            Ok(_) => (
+                "400 Bad Request",
+                "The name or message is empty or too long",
+            ),
+            Err(_) => (
+                "400 Bad Request",
+                "Expected {\"name\": \"...\", \"message\": \"...\"}",
+            ),
+        }
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,138 @@
+//! Protected regions of the world.
+//!
+//! A region is a named cuboid claimed by a player. Only the owner and the players and teams they
+//! trust can change blocks inside of it.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use glam::IVec3;
+use serde::{Deserialize, Serialize};
+
+/// The most blocks a single region can hold, unless an operator claims it, like a 128 by 128 area
+/// 64 blocks high.
+pub const MAX_REGION_VOLUME: u64 = 128 * 128 * 64;
+/// The most blocks all regions of a player can hold together, unless an operator claims them.
+pub const MAX_OWNED_VOLUME: u64 = 4 * MAX_REGION_VOLUME;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Region {
+    pub owner: String,
+    /// The lowest corner of the region, inclusive.
+    pub min: [i32; 3],
+    /// The highest corner of the region, inclusive.
+    pub max: [i32; 3],
+    /// The players allowed to build in the region besides the owner. Whole teams are trusted
+    /// with entries of the form `team:<name>`.
+    pub trusted: BTreeSet<String>,
+}
+
+impl Region {
+    /// Creates a region owned by `owner` spanning the cuboid between the two corners.
+    pub fn new(owner: String, a: IVec3, b: IVec3) -> Self {
+        Self {
+            owner,
+            min: a.min(b).to_array(),
+            max: a.max(b).to_array(),
+            trusted: BTreeSet::new(),
+        }
+    }
+
+    /// Returns how many blocks the region holds.
+    pub fn volume(&self) -> u64 {
+        let size = (IVec3::from(self.max) - IVec3::from(self.min)).as_i64vec3() + 1;
+        (size.x * size.y * size.z) as u64
+    }
+
+    pub fn contains(&self, pos: IVec3) -> bool {
+        pos.cmpge(IVec3::from(self.min)).all() && pos.cmple(IVec3::from(self.max)).all()
+    }
+
+    pub fn intersects(&self, other: &Region) -> bool {
+        IVec3::from(self.min).cmple(IVec3::from(other.max)).all()
+            && IVec3::from(other.min).cmple(IVec3::from(self.max)).all()
+    }
+
+    /// Returns whether the player, who is in the given team, is allowed to change blocks in the
+    /// region.
+    pub fn can_build(&self, username: &str, team: Option<&str>) -> bool {
+        self.owner == username
+            || self.trusted.contains(username)
+            || team.is_some_and(|team| self.trusted.contains(&format!("team:{}", team)))
+    }
+}
+
+/// The protected regions of a world, keyed by name.
+pub struct ClaimDatabase {
+    pub regions: BTreeMap<String, Region>,
+    pub file_path: PathBuf,
+}
+
+impl ClaimDatabase {
+    pub fn load(file_path: PathBuf) -> Self {
+        if let Ok(data) = std::fs::read(&file_path)
+            && let Ok(regions) = serde_json::from_slice(&data)
+        {
+            return Self { regions, file_path };
+        }
+        Self {
+            regions: BTreeMap::new(),
+            file_path,
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let data = serde_json::to_vec(&self.regions)?;
+        std::fs::write(&self.file_path, data)
+    }
+
+    /// Claims a new region, failing if the name is taken or if it overlaps a region of another
+    /// player. Unless `unlimited`, the region can't be larger than [`MAX_REGION_VOLUME`] and the
+    /// owner's regions can't hold more than [`MAX_OWNED_VOLUME`] blocks together.
+    pub fn claim(&mut self, name: String, region: Region, unlimited: bool) -> Result<(), String> {
+        if self.regions.contains_key(&name) {
+            return Err(format!("A region named '{}' already exists", name));
+        }
+        if !unlimited {
+            let volume = region.volume();
+            if volume > MAX_REGION_VOLUME {
+                return Err(format!(
+                    "The region holds {} blocks, but at most {} can be claimed at once",
+                    volume, MAX_REGION_VOLUME
+                ));
+            }
+            let owned: u64 = self
+                .regions
+                .values()
+                .filter(|other| other.owner == region.owner)
+                .map(Region::volume)
+                .sum();
+            if owned + volume > MAX_OWNED_VOLUME {
+                return Err(format!(
+                    "Your regions would hold {} blocks, but at most {} can be claimed in total",
+                    owned + volume,
+                    MAX_OWNED_VOLUME
+                ));
+            }
+        }
+        if let Some((other_name, _)) = self
+            .regions
+            .iter()
+            .find(|(_, other)| other.owner != region.owner && other.intersects(&region))
+        {
+            return Err(format!("The region overlaps '{}'", other_name));
+        }
+        self.regions.insert(name, region);
+        Ok(())
+    }
+
+    /// Returns the name of a region protecting `pos` from the player, if there is one.
+    pub fn protected_by(&self, username: &str, team: Option<&str>, pos: IVec3) -> Option<&str> {
+        self.regions
+            .iter()
+            .find(|(_, region)| region.contains(pos) && !region.can_build(username, team))
+            .map(|(name, _)| name.as_str())
+    }
+}
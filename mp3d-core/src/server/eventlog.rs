@@ -0,0 +1,214 @@
+//! A rolling log of what players did to the world and to each other, so that moderators can look
+//! into griefing and complaints with the /log command.
+//!
+//! Only the latest [`MAX_EVENTS`] events are kept. The log is kept in memory unless persisting is
+//! turned on, in which case it is saved with the world. The block edits in the log can be undone
+//! with [`EventLog::rollback`].
+
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use glam::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{BlockState, block_registry, blocks},
+    protocol::BlockUpdateKind,
+    world::{World, chunk::CHUNK_SIZE},
+};
+
+/// The most events kept in the log, older ones are dropped.
+pub const MAX_EVENTS: usize = 8192;
+
+/// What a player did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    Placed {
+        block: String,
+    },
+    Broke {
+        block: String,
+        /// The bits of the state of the block, see [`BlockState::bits`].
+        #[serde(default)]
+        state: u32,
+    },
+    OpenedContainer,
+    Died {
+        cause: String,
+        attacker: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    /// When the event happened, in seconds since the Unix epoch.
+    pub time: u64,
+    pub player: String,
+    pub position: [i32; 3],
+    pub kind: EventKind,
+}
+
+impl LoggedEvent {
+    /// Describes the event, like "placed stone".
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            EventKind::Placed { block } => format!("placed {}", block),
+            EventKind::Broke { block, .. } => format!("broke {}", block),
+            EventKind::OpenedContainer => "opened a container".to_string(),
+            EventKind::Died {
+                cause,
+                attacker: Some(attacker),
+            } => format!("was killed by {} ({})", attacker, cause),
+            EventKind::Died {
+                cause,
+                attacker: None,
+            } => format!("died ({})", cause),
+        }
+    }
+
+    /// Returns whether the player took part in the event, either doing it or as the attacker of a
+    /// death.
+    fn involves(&self, player: &str) -> bool {
+        self.player == player
+            || matches!(&self.kind, EventKind::Died { attacker: Some(attacker), .. } if attacker == player)
+    }
+}
+
+/// Which events to look up in the log.
+pub enum EventFilter<'a> {
+    /// The events a player took part in.
+    Player(&'a str),
+    /// The events in a chunk.
+    Chunk(IVec3),
+}
+
+pub struct EventLog {
+    pub events: VecDeque<LoggedEvent>,
+    /// Whether the log is saved with the world. If not, the file is removed on the next save.
+    pub persist: bool,
+    pub file_path: PathBuf,
+}
+
+impl EventLog {
+    /// Loads the log if it was persisted, otherwise starts an empty log that isn't persisted.
+    pub fn load(file_path: PathBuf) -> Self {
+        if let Ok(data) = std::fs::read(&file_path)
+            && let Ok(events) = serde_json::from_slice(&data)
+        {
+            return Self {
+                events,
+                persist: true,
+                file_path,
+            };
+        }
+        Self {
+            events: VecDeque::new(),
+            persist: false,
+            file_path,
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        if !self.persist {
+            return match std::fs::remove_file(&self.file_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let data = serde_json::to_vec(&self.events)?;
+        std::fs::write(&self.file_path, data)
+    }
+
+    /// Adds an event that happens now, dropping the oldest event if the log is full.
+    pub fn push(&mut self, player: &str, position: IVec3, kind: EventKind) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(LoggedEvent {
+            time: now(),
+            player: player.to_string(),
+            position: position.to_array(),
+            kind,
+        });
+    }
+
+    /// Returns the events matching the filter from the last `seconds` seconds, the newest first.
+    pub fn query(&self, filter: EventFilter, seconds: u64) -> impl Iterator<Item = &LoggedEvent> {
+        let since = now().saturating_sub(seconds);
+        self.events
+            .iter()
+            .rev()
+            .take_while(move |event| event.time >= since)
+            .filter(move |event| match filter {
+                EventFilter::Player(player) => event.involves(player),
+                EventFilter::Chunk(chunk) => {
+                    IVec3::from(event.position).div_euclid(IVec3::splat(CHUNK_SIZE as i32)) == chunk
+                }
+            })
+    }
+}
+
+/// The number of block edits undone by [`EventLog::rollback`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rollback {
+    pub reverted: usize,
+    /// Edits that weren't undone because the block was changed again by someone else.
+    pub skipped: usize,
+}
+
+impl EventLog {
+    /// Undoes the blocks a player placed and broke in the last `seconds` seconds within `radius`
+    /// blocks of `center`. Edits are undone from the newest to the oldest, so a block the player
+    /// changed several times ends up as it was before the first change. Blocks changed since by
+    /// someone else are left alone.
+    pub fn rollback(
+        &self,
+        world: &mut World,
+        player: &str,
+        seconds: u64,
+        center: Vec3,
+        radius: f32,
+    ) -> Rollback {
+        let mut result = Rollback::default();
+        for event in self.query(EventFilter::Player(player), seconds) {
+            let position = IVec3::from(event.position);
+            if event.player != player
+                || (position.as_vec3() + Vec3::splat(0.5)).distance(center) > radius
+            {
+                continue;
+            }
+            // A placed block is only removed if it is still there and holds no items, and a broken
+            // block is only restored if nothing was put in its place
+            let (expected, block, state) = match &event.kind {
+                EventKind::Placed { block } => (
+                    block_registry().get_id(block),
+                    *blocks::AIR,
+                    BlockState::none(),
+                ),
+                EventKind::Broke { block, state } => match block_registry().get_id(block) {
+                    Some(block) => (Some(*blocks::AIR), block, BlockState::from_bits(*state)),
+                    None => (None, *blocks::AIR, BlockState::none()),
+                },
+                _ => continue,
+            };
+            let current = world.get_block_or_new(position).map(|(block, _)| block);
+            if expected.is_none() || current != expected || world.holds_items(position) {
+                result.skipped += 1;
+                continue;
+            }
+            world.normal_set_block_at(position, block, state, BlockUpdateKind::Rollback);
+            result.reverted += 1;
+        }
+        result
+    }
+}
+
+/// The current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
@@ -0,0 +1,246 @@
+//! Hosting a [`Server`] over TCP, so that players on other machines can connect to it.
+//!
+//! Every connection gets a thread reading its messages and a thread writing the messages sent to
+//! it, so that a slow client can't hold up the server. The server itself only runs in
+//! [`Host::tick`], on the thread owning the host.
+//!
+//! Both directions are bounded. Readers wait while [`MAX_QUEUED_EVENTS`] messages are waiting for
+//! the server, and a client with [`MAX_QUEUED_MESSAGES`] messages still to be written to it is
+//! disconnected, as it doesn't keep up with what it is sent.
+
+use std::{
+    io::{self, BufReader, BufWriter, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread,
+    time::Duration,
+};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    net::{read_message, write_message},
+    protocol::{C2SMessage, S2CMessage},
+    server::Server,
+};
+
+/// The most messages received from all connections that wait to be handled by the server.
+const MAX_QUEUED_EVENTS: usize = 4096;
+/// The most messages waiting to be written to a connection, about twice the chunks within the
+/// largest render distance.
+const MAX_QUEUED_MESSAGES: usize = 16384;
+/// How long writing to a connection may block before it is closed.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Something that happened on one of the connection threads.
+enum HostEvent {
+    Opened {
+        connection_id: u64,
+        outgoing: SyncSender<S2CMessage>,
+    },
+    Message {
+        connection_id: u64,
+        message: C2SMessage,
+    },
+    Closed {
+        connection_id: u64,
+    },
+}
+
+/// A server accepting players over TCP.
+pub struct Host {
+    pub server: Server,
+    pub address: SocketAddr,
+    events: Receiver<HostEvent>,
+    /// The messages waiting to be written to each open connection.
+    outgoing: FxHashMap<u64, SyncSender<S2CMessage>>,
+}
+
+impl Host {
+    /// Starts accepting connections on the given address.
+    pub fn bind(server: Server, address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (events_tx, events) = mpsc::sync_channel(MAX_QUEUED_EVENTS);
+        thread::Builder::new()
+            .name("accept".to_string())
+            .spawn(move || accept_connections(listener, events_tx))?;
+        log::info!("Listening on {}", address);
+        Ok(Self {
+            server,
+            address,
+            events,
+            outgoing: FxHashMap::default(),
+        })
+    }
+
+    /// Handles the messages received since the last tick, ticks the server and sends every player
+    /// the messages the server has for them.
+    pub fn tick(&mut self, tps: u8) {
+        // Only the messages already waiting, as busy connections keep adding more
+        let events: Vec<_> = self.events.try_iter().take(MAX_QUEUED_EVENTS).collect();
+        for event in events {
+            match event {
+                HostEvent::Opened {
+                    connection_id,
+                    outgoing,
+                } => {
+                    self.outgoing.insert(connection_id, outgoing);
+                }
+                HostEvent::Message {
+                    connection_id,
+                    message,
+                } => {
+                    // Messages still arriving from a kicked player are ignored
+                    if !self.outgoing.contains_key(&connection_id) {
+                        continue;
+                    }
+                    if let Some(response) = self.server.handle_message(connection_id, message) {
+                        // Players who aren't allowed to join are told why and disconnected
+                        let kicked = matches!(response, S2CMessage::Kicked { .. });
+                        self.send(connection_id, response);
+                        if kicked {
+                            self.outgoing.remove(&connection_id);
+                        }
+                    }
+                }
+                HostEvent::Closed { connection_id } => {
+                    self.outgoing.remove(&connection_id);
+                    self.server
+                        .handle_message(connection_id, C2SMessage::Disconnect);
+                }
+            }
+        }
+
+        self.server.tick(tps);
+
+        let mut kicked = Vec::new();
+        for (&connection_id, user_id) in &self.server.connections {
+            if let Some(session) = self.server.sessions.get_mut(user_id)
+                && let Some(outgoing) = self.outgoing.get(&connection_id)
+            {
+                let too_slow = session.pending_messages.drain(..).any(|message| {
+                    matches!(outgoing.try_send(message), Err(TrySendError::Full(_)))
+                });
+                if too_slow {
+                    log::warn!("Connection {} is too slow, disconnecting", connection_id);
+                }
+                if session.kicked || too_slow {
+                    kicked.push(connection_id);
+                }
+            }
+        }
+        for connection_id in kicked {
+            self.disconnect(connection_id);
+        }
+    }
+
+    fn send(&mut self, connection_id: u64, message: S2CMessage) {
+        if let Some(outgoing) = self.outgoing.get(&connection_id)
+            && let Err(TrySendError::Full(_)) = outgoing.try_send(message)
+        {
+            log::warn!("Connection {} is too slow, disconnecting", connection_id);
+            self.disconnect(connection_id);
+        }
+    }
+
+    /// Disconnects a player from the server. Dropping the sender closes the connection once the
+    /// messages before it are written.
+    fn disconnect(&mut self, connection_id: u64) {
+        if self.outgoing.remove(&connection_id).is_some() {
+            self.server
+                .handle_message(connection_id, C2SMessage::Disconnect);
+        }
+    }
+}
+
+/// Accepts connections until the host is dropped. Connection IDs start at 1, as 0 is used by the
+/// local connection of singleplayer.
+fn accept_connections(listener: TcpListener, events: SyncSender<HostEvent>) {
+    for (connection_id, stream) in (1..).zip(listener.incoming()) {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("Failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+        match open_connection(connection_id, stream, events.clone()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return,
+            Err(e) => log::warn!("Failed to open connection {}: {}", connection_id, e),
+        }
+    }
+}
+
+/// Starts the threads reading and writing the messages of a new connection.
+fn open_connection(
+    connection_id: u64,
+    stream: TcpStream,
+    events: SyncSender<HostEvent>,
+) -> io::Result<()> {
+    let peer = stream.peer_addr()?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let reader = stream.try_clone()?;
+
+    let (outgoing, messages) = mpsc::sync_channel(MAX_QUEUED_MESSAGES);
+    events
+        .send(HostEvent::Opened {
+            connection_id,
+            outgoing,
+        })
+        .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+    log::info!("Connection {} opened from {}", connection_id, peer);
+
+    thread::Builder::new()
+        .name(format!("connection {} writer", connection_id))
+        .spawn(move || write_messages(stream, messages))?;
+    thread::Builder::new()
+        .name(format!("connection {} reader", connection_id))
+        .spawn(move || {
+            let mut reader = BufReader::new(reader);
+            loop {
+                match read_message(&mut reader) {
+                    Ok(message) => {
+                        let event = HostEvent::Message {
+                            connection_id,
+                            message,
+                        };
+                        if events.send(event).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        if e.kind() != io::ErrorKind::UnexpectedEof {
+                            log::warn!("Connection {} failed: {}", connection_id, e);
+                        }
+                        log::info!("Connection {} closed", connection_id);
+                        let _ = events.send(HostEvent::Closed { connection_id });
+                        return;
+                    }
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Writes messages to a connection until it is closed, flushing whenever no more messages are
+/// waiting.
+fn write_messages(stream: TcpStream, messages: Receiver<S2CMessage>) {
+    let mut writer = BufWriter::new(&stream);
+    while let Ok(message) = messages.recv() {
+        let result = write_message(&mut writer, &message)
+            .and_then(|_| {
+                messages
+                    .try_iter()
+                    .try_for_each(|m| write_message(&mut writer, &m))
+            })
+            .and_then(|_| writer.flush());
+        if result.is_err() {
+            break;
+        }
+    }
+    // Wakes up the reader, so that the connection is closed on both ends
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+}
@@ -1,21 +1,38 @@
 //! Server code for handling client connections and requests.
 //!
-//! Note that this does not include networking, for that please check mp3d-server (doesn't exist
-//! yet) and instead focuses on the server-side logic.
+//! This focuses on the server-side logic. Hosting a server over TCP is done by [`host::Host`],
+//! which the mp3d-server binary uses to run a dedicated server.
 
 use std::{collections::HashMap, path::PathBuf};
 
 use fxhash::FxHashMap;
-use glam::Vec3;
+use glam::{IVec3, Vec3};
 
 use crate::{
+    block::{BlockId, BlockState, block_registry, blocks, container::Container},
+    chatlog::ChatLog,
     command::{CommandContext, CommandManager, commands},
-    entity::{Entity, PlayerEntity},
+    direction::Direction,
+    entity::{BLOCK_EDIT_COOLDOWN, Entity, MAX_HEALTH, MoveInput, PlayerEntity, entities},
+    item::{Inventory, OFFHAND_SLOT, item_registry, items},
+    mirror::Mirror,
+    physics,
+    preferences::Preferences,
     protocol::*,
-    world::{World, chunk::CHUNK_SIZE},
+    textcomponent::{TextComponent, sanitize},
+    world::{World, chunk::CHUNK_SIZE, generation::WORLD_FLOOR, sky},
 };
 
+pub mod access;
+pub mod bridge;
+pub mod claims;
+pub mod eventlog;
+pub mod host;
+pub mod replay;
+pub mod teams;
+pub mod tickets;
 pub mod user;
+pub mod webmap;
 
 /// The maximum distance (in chunks) that the server will keep loaded around players.
 pub const MAX_RENDER_DIST: i32 = 12;
@@ -24,6 +41,55 @@ pub const MAX_RENDER_DIST: i32 = 12;
 /// roots.
 pub const MAX_RENDER_DIST_SQ: i32 = MAX_RENDER_DIST * MAX_RENDER_DIST;
 
+/// How often the world time is sent to the players, in ticks. Jumps in time are sent immediately.
+const TIME_SYNC_INTERVAL: u64 = 240;
+
+/// How far, in blocks, the eyes of a player can be from the center of an open container before it
+/// is closed. This is a bit more than [`physics::REACH_DISTANCE`], so that walking around while
+/// using the container doesn't close it.
+const CONTAINER_DISTANCE: f32 = physics::REACH_DISTANCE + 1.5;
+
+/// How far, in blocks, a mirrored edit can be from the eyes of the player.
+const MIRROR_DISTANCE: f32 = 64.0;
+/// How far, in blocks, the blocks of a [`C2SMessage::PlaceBlocks`] can be from the eyes of the
+/// player.
+const BATCH_DISTANCE: f32 = 64.0;
+/// How often the changed chunks are saved in the background, in seconds. Chunks are only
+/// autosaved once the world was saved, so that new worlds don't leave half a save behind.
+const CHUNK_AUTOSAVE_INTERVAL: u64 = 120;
+/// Where new players appear and dead players respawn.
+const SPAWN_POSITION: Vec3 = Vec3::new(0.0, 25.0, 0.0);
+/// How far below the world floor, in blocks, players die in the void.
+const VOID_DEATH_DEPTH: f32 = 64.0;
+/// How many slots of a player's inventory can change in a tick before the whole inventory is sent
+/// instead of the changed slots.
+const FULL_INVENTORY_UPDATE_SLOTS: usize = 18;
+
+/// Creates the chat message showing a command error. Errors may contain formatting codes, with
+/// the input they echo sanitized, but an error that doesn't parse is shown as it is instead.
+fn command_error(err: &str) -> TextComponent {
+    format!("%bC3Error executing command: %bD3{}%r", err)
+        .parse()
+        .unwrap_or_else(|_| {
+            format!("%bC3Error executing command: %bD3{}%r", sanitize(err))
+                .parse()
+                .unwrap()
+        })
+}
+
+/// Creates the message telling players about a new entity.
+fn entity_spawned(entity: &dyn Entity) -> S2CMessage {
+    S2CMessage::EntitySpawned {
+        entity_id: entity.id(),
+        entity_type: entity.ident().to_string(),
+        entity_snapshot: entity.snapshot(),
+        name: entity
+            .as_any()
+            .downcast_ref::<PlayerEntity>()
+            .map(|player| player.username.clone()),
+    }
+}
+
 fn broadcast_message(
     sessions: &mut FxHashMap<u64, PlayerSession>,
     sender_id: Option<u64>,
@@ -41,20 +107,129 @@ pub struct PlayerSession {
     pub user_id: u64,
     pub entity_id: u64,
     pub username: String,
+    pub preferences: Preferences,
     pub pending_messages: Vec<S2CMessage>,
+    /// The block the player is breaking while holding the button down.
+    pub breaking: Option<BlockBreaking>,
+    /// The container the player has open.
+    pub container: Option<ContainerView>,
+    /// The planes the player mirrors block edits over, see [`C2SMessage::SetMirror`].
+    pub mirror: Option<Mirror>,
+    /// The last block the player placed or broke while mirroring, with the positions
+    /// [`C2SMessage::SetBlock`] can still repeat it at.
+    pub mirror_edit: Option<MirrorEdit>,
+    /// The camera positions recorded with the /camera command.
+    pub camera_keyframes: Vec<CameraKeyframe>,
+    /// The entity ID of the player this player is spectating, whose view is sent to them every
+    /// tick.
+    pub spectating: Option<u64>,
+    /// The inventory as the client last got it, to only send the slots that changed since.
+    pub sent_inventory: Inventory,
+    /// The yaw and pitch of the player as the other players last got them, so that turning
+    /// without moving is sent too.
+    pub sent_look: (f32, f32),
+    /// Whether the player was kicked, which closes their connection once the pending messages
+    /// telling them so are sent.
+    pub kicked: bool,
+}
+
+/// The progress of a player breaking a block.
+pub struct BlockBreaking {
+    pub position: IVec3,
+    /// From `0.0` when the player starts breaking the block to `1.0` when it breaks.
+    pub progress: f32,
+}
+
+/// A block edit that can be repeated at the positions mirroring it.
+pub struct MirrorEdit {
+    /// The block that was placed or broken.
+    pub block: BlockId,
+    /// Whether the block was placed rather than broken.
+    pub place: bool,
+    /// The mirrored positions the edit wasn't repeated at yet.
+    pub positions: Vec<IVec3>,
+}
+
+/// A container opened by a player.
+pub struct ContainerView {
+    /// The ID the client refers to the container with, unique for every opening.
+    pub id: u64,
+    pub position: IVec3,
 }
 
 impl PlayerSession {
+    /// Remembers a block the player placed or broke, so that [`C2SMessage::SetBlock`] can repeat
+    /// it at the positions mirroring it.
+    fn record_mirror_edit(&mut self, block: BlockId, place: bool, position: IVec3) {
+        self.mirror_edit = self.mirror.map(|mirror| MirrorEdit {
+            block,
+            place,
+            positions: mirror.mirrored(position),
+        });
+    }
+
+    /// Kicks the player from the server, telling them why.
+    pub fn kick(&mut self, reason: String) {
+        log::info!("Kicked {}: {}", self.username, reason);
+        self.pending_messages.push(S2CMessage::Kicked { reason });
+        self.kicked = true;
+    }
+
+    /// Sends a chat message from the player to everyone.
     pub fn send_chat_message(
         self_id: u64,
         sessions: &mut FxHashMap<u64, PlayerSession>,
+        teams: &teams::TeamDatabase,
         message: &str,
+    ) {
+        Self::send_to(self_id, sessions, teams, message, false);
+    }
+
+    /// Sends a chat message from the player to the members of their team. Fails if the player
+    /// isn't in a team.
+    pub fn send_team_message(
+        self_id: u64,
+        sessions: &mut FxHashMap<u64, PlayerSession>,
+        teams: &teams::TeamDatabase,
+        message: &str,
+    ) -> Result<(), String> {
+        let session = sessions.get(&self_id).ok_or("You aren't connected")?;
+        if teams.team_of(&session.username).is_none() {
+            return Err("You aren't in a team".to_string());
+        }
+        Self::send_to(self_id, sessions, teams, message, true);
+        Ok(())
+    }
+
+    fn send_to(
+        self_id: u64,
+        sessions: &mut FxHashMap<u64, PlayerSession>,
+        teams: &teams::TeamDatabase,
+        message: &str,
+        team_only: bool,
     ) {
         if let Some(session) = sessions.get_mut(&self_id) {
             let username = session.username.clone();
-            if let Ok(c) = format!("{}%r: {}", username, message).parse() {
-                broadcast_message(sessions, None, S2CMessage::ChatMessage { message: c });
-                log::info!("{}: {}", username, message);
+            let team = teams.team_of(&username);
+            let prefix = match team {
+                Some((name, _)) if team_only => format!("[{}] ", sanitize(name)),
+                _ => String::new(),
+            };
+            // Players can color their messages, but not make others run commands by clicking them
+            if let Ok(c) = format!("{}{}%r: {}", prefix, teams.colored_name(&username), message)
+                .parse::<TextComponent>()
+                .map(TextComponent::without_commands)
+            {
+                for session in sessions.values_mut() {
+                    if !team_only
+                        || team.is_some_and(|(_, t)| t.members.contains(&session.username))
+                    {
+                        session
+                            .pending_messages
+                            .push(S2CMessage::ChatMessage { message: c.clone() });
+                    }
+                }
+                log::info!("{}{}: {}", prefix, username, message);
             } else {
                 session.pending_messages.push(S2CMessage::ChatMessage {
                     message: "%bC3Error: Make sure your message doesn't contain invalid formatting codes.%r".parse().unwrap(),
@@ -78,8 +253,34 @@ pub struct Server {
     pub singleplayer: bool,
     pub save_path: PathBuf,
     pub user_db: user::UserDatabase,
+    pub claims: claims::ClaimDatabase,
+    pub teams: teams::TeamDatabase,
+    /// The bans, the whitelist and the operators.
+    pub access: access::AccessList,
+    pub event_log: eventlog::EventLog,
     pub command_manager: CommandManager,
+    /// Where the chat messages and commands of the players are logged, if anywhere.
+    pub chat_log: Option<ChatLog>,
+    /// Where the chat is bridged to, if anywhere.
+    pub chat_bridge: Option<bridge::ChatBridge>,
     pub tps: u8,
+    /// The world time after the previous tick, used to notice when the time jumps.
+    last_time: u64,
+    /// Whether the time advanced in the previous tick.
+    time_advancing: bool,
+    /// The snapshots last sent of the entities other than players, to send them again only when
+    /// they change.
+    sent_snapshots: FxHashMap<u64, Vec<u8>>,
+    /// The ID given to the next opened container.
+    next_container_id: u64,
+    /// The chunks each connection keeps loaded, see [`tickets`].
+    chunk_tickets: tickets::ChunkTickets,
+    /// The number of times the server ticked.
+    ticks: u64,
+    /// The tick recording started at.
+    recording_start: u64,
+    /// The messages received since recording started, see [`Server::start_recording`].
+    recording: Option<replay::InputScript>,
 }
 
 impl Server {
@@ -96,8 +297,22 @@ impl Server {
             singleplayer,
             save_path: save_path.clone(),
             user_db: user::UserDatabase::load(save_path.join("users.json")),
+            claims: claims::ClaimDatabase::load(save_path.join("claims.json")),
+            teams: teams::TeamDatabase::load(save_path.join("teams.json")),
+            access: access::AccessList::load(save_path.join("access.json")),
+            event_log: eventlog::EventLog::load(save_path.join("eventlog.json")),
             command_manager,
             tps: 48,
+            last_time: 0,
+            time_advancing: true,
+            sent_snapshots: FxHashMap::default(),
+            next_container_id: 1,
+            chunk_tickets: tickets::ChunkTickets::new(),
+            ticks: 0,
+            recording_start: 0,
+            recording: None,
+            chat_log: None,
+            chat_bridge: None,
         }
     }
 
@@ -131,6 +346,19 @@ impl Server {
         sessions.get_mut(user_id)
     }
 
+    /// Starts recording the messages received from clients, to replay them later with
+    /// [`replay::replay`].
+    pub fn start_recording(&mut self) {
+        self.recording = Some(replay::InputScript::new());
+        self.recording_start = self.ticks;
+    }
+
+    /// Stops recording, returning the messages received since [`Server::start_recording`]. The
+    /// ticks of the messages are counted from the start of the recording.
+    pub fn stop_recording(&mut self) -> Option<replay::InputScript> {
+        self.recording.take()
+    }
+
     /// Handles messages received from clients, and prepares responses. Note that this does not
     /// tick the server, that must be done separately.
     pub fn handle_message(
@@ -138,8 +366,20 @@ impl Server {
         connection_id: u64,
         message: C2SMessage,
     ) -> Option<S2CMessage> {
+        if let Some(recording) = &mut self.recording {
+            recording.push(
+                self.ticks - self.recording_start,
+                connection_id,
+                message.clone(),
+            );
+        }
+
         match message {
-            C2SMessage::Connect { username, password } => {
+            C2SMessage::Connect {
+                username,
+                password,
+                preferences,
+            } => {
                 log::info!(
                     "Connection attempt from {} with username '{}'",
                     connection_id,
@@ -157,6 +397,37 @@ impl Server {
                     });
                 }
 
+                // A second session would be left behind when the connection closes, and two
+                // sessions of a player would overwrite each other's saved player
+                if self.connections.contains_key(&connection_id) {
+                    log::warn!(
+                        "Connection from {} rejected: it is already connected",
+                        connection_id
+                    );
+                    return Some(S2CMessage::ConnectionFailed {
+                        reason: "Already connected".to_string(),
+                    });
+                }
+                if self
+                    .sessions
+                    .values()
+                    .any(|session| session.username == username)
+                {
+                    log::warn!(
+                        "Connection from {} rejected: '{}' is already online",
+                        connection_id,
+                        username
+                    );
+                    return Some(S2CMessage::ConnectionFailed {
+                        reason: format!("A player named '{}' is already online", username),
+                    });
+                }
+
+                if let Err(reason) = self.access.check(&username) {
+                    log::warn!("Connection from {} rejected: {}", connection_id, reason);
+                    return Some(S2CMessage::Kicked { reason });
+                }
+
                 let auth_result = self.user_db.login_or_register(username.clone(), password);
 
                 match auth_result {
@@ -171,7 +442,7 @@ impl Server {
                         {
                             entity
                         } else {
-                            PlayerEntity::new(username.clone(), Vec3::new(0.0, 25.0, 0.0))
+                            PlayerEntity::new(username.clone(), SPAWN_POSITION)
                         };
                         self.world.load_around(entity.position().as_ivec3());
                         let inventory = entity.inventory.clone();
@@ -182,27 +453,52 @@ impl Server {
                                 user_id,
                                 entity_id,
                                 username: username.clone(),
-                                pending_messages: vec![S2CMessage::Connected {
-                                    user_id,
-                                    entity_id,
-                                    inventory,
-                                }],
+                                preferences,
+                                breaking: None,
+                                container: None,
+                                mirror: None,
+                                mirror_edit: None,
+                                camera_keyframes: Vec::new(),
+                                spectating: None,
+                                sent_inventory: inventory.clone(),
+                                sent_look: (0.0, 0.0),
+                                kicked: false,
+                                pending_messages: vec![
+                                    S2CMessage::Connected {
+                                        user_id,
+                                        entity_id,
+                                        inventory,
+                                        sky_seed: sky::sky_seed(self.world.generator.seed()),
+                                    },
+                                    S2CMessage::TimeUpdate {
+                                        time: self.world.time,
+                                        advancing: self.world.game_rules.daylight_cycle,
+                                    },
+                                    S2CMessage::TeamsUpdated {
+                                        colors: self.teams.colors(),
+                                    },
+                                ],
                             },
                         );
+                        // The new player needs to know about the entities already in the world,
+                        // while its own entity is sent to everyone below
+                        let others = self
+                            .world
+                            .entities
+                            .values()
+                            .filter(|entity| entity.id() != entity_id)
+                            .map(|entity| entity_spawned(entity.as_ref()));
+                        self.sessions
+                            .get_mut(&user_id)
+                            .unwrap()
+                            .pending_messages
+                            .extend(others);
                         self.connections.insert(connection_id, user_id);
                         self.entity_to_user.insert(entity_id, user_id);
                         broadcast_message(
                             &mut self.sessions,
                             None,
-                            S2CMessage::EntitySpawned {
-                                entity_id,
-                                entity_type: crate::entity::EntityType::Player as u8,
-                                entity_snapshot: self
-                                    .world
-                                    .get_entity::<PlayerEntity>(entity_id)
-                                    .unwrap()
-                                    .snapshot(),
-                            },
+                            entity_spawned(self.world.entities[&entity_id].as_ref()),
                         );
                         log::info!(
                             "User '{}' connected with user ID {} and entity ID {}",
@@ -210,6 +506,9 @@ impl Server {
                             user_id,
                             entity_id
                         );
+                        if let Some(bridge) = &self.chat_bridge {
+                            bridge.joined(&username);
+                        }
                     }
                     Err(reason) => {
                         log::warn!("Connection from {} rejected: {}", connection_id, reason);
@@ -219,6 +518,7 @@ impl Server {
             }
             C2SMessage::Disconnect => {
                 let user_id = self.connections.remove(&connection_id)?;
+                self.chunk_tickets.remove(connection_id);
 
                 if let Some(session) = self.sessions.remove(&user_id) {
                     if let Some(entity) = self.world.remove_entity(session.entity_id)
@@ -234,57 +534,67 @@ impl Server {
                         None,
                         S2CMessage::Disconnected { user_id },
                     );
+                    broadcast_message(
+                        &mut self.sessions,
+                        None,
+                        S2CMessage::EntityRemoved {
+                            entity_id: session.entity_id,
+                        },
+                    );
                     log::info!(
                         "User '{}' with user ID {} disconnected",
                         session.username,
                         user_id
                     );
+                    if let Some(bridge) = &self.chat_bridge {
+                        bridge.left(&session.username);
+                    }
                 }
             }
-            C2SMessage::Move(MoveInstructions {
-                forward,
-                strafe,
-                jump,
-                sneak,
-                yaw,
-                pitch,
-            }) => {
+            C2SMessage::Move(instructions) => {
+                // Moving gets the player out of bed
+                if (instructions.forward != 0 || instructions.strafe != 0 || instructions.jump)
+                    && let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get(user_id)
+                {
+                    self.world.sleeping.remove(&session.entity_id);
+                }
                 if let Some(user_id) = self.connections.get(&connection_id)
                     && let Some(session) = self.sessions.get(user_id)
                     && let Some(entity) =
                         self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                    && !entity.is_dead()
                 {
-                    entity.yaw = yaw;
-                    entity.pitch = pitch;
-                    entity.input = MoveInstructions {
-                        forward,
-                        strafe,
-                        jump,
-                        sneak,
-                        yaw,
-                        pitch,
-                    }
-                    .into();
+                    let instructions = instructions.sanitized(entity.yaw, entity.pitch);
+                    entity.yaw = instructions.yaw;
+                    entity.pitch = instructions.pitch;
+                    entity.input = instructions.into();
                 }
             }
             C2SMessage::RequestChunks { chunk_positions } => {
+                // Spectators get the chunks around the player they are watching
                 if let Some(user_id) = self.connections.get(&connection_id)
                     && let Some(session) = self.sessions.get_mut(user_id)
                     && let Some(pos) = self
                         .world
-                        .get_entity::<PlayerEntity>(session.entity_id)
+                        .get_entity::<PlayerEntity>(session.spectating.unwrap_or(session.entity_id))
                         .map(|e| e.position / CHUNK_SIZE as f32)
                 {
                     for chunk_position in chunk_positions {
                         let cp_float = chunk_position.as_vec3() + Vec3::splat(0.5);
-                        if cp_float.distance_squared(pos) > MAX_RENDER_DIST_SQ as f32 {
+                        let distance_sq = cp_float.distance_squared(pos);
+                        if distance_sq > MAX_RENDER_DIST_SQ as f32 {
                             continue;
                         }
-                        let chunk = self.world.get_chunk_or_new(chunk_position);
-                        session.pending_messages.push(S2CMessage::ChunkData {
-                            chunk_position,
-                            chunk: Box::new(chunk.clone()),
-                        });
+                        // Chunks that aren't loaded yet are generated in the background, the
+                        // client keeps requesting them until they are sent.
+                        match self.world.chunks.get(&chunk_position) {
+                            Some(chunk) => session.pending_messages.push(S2CMessage::ChunkData {
+                                chunk_position,
+                                chunk: Box::new(chunk.clone()),
+                            }),
+                            None => self.world.request_chunk(chunk_position, distance_sq as u32),
+                        }
                     }
                 }
             }
@@ -293,13 +603,23 @@ impl Server {
                     Some(uid) => *uid,
                     None => return None,
                 };
+                if let Some(chat_log) = &mut self.chat_log
+                    && let Some(session) = self.sessions.get(&user_id)
+                {
+                    chat_log.write(&format!("<{}> {}", session.username, message));
+                }
                 let mut ctx = CommandContext {
                     connections: &self.connections,
                     sessions: &mut self.sessions,
                     world: &mut self.world,
+                    claims: &mut self.claims,
+                    teams: &mut self.teams,
+                    access: &mut self.access,
+                    event_log: &mut self.event_log,
                     command_manager: &self.command_manager,
                     connection_id,
                     tps: self.tps,
+                    singleplayer: self.singleplayer,
                 };
                 let args = message.split_whitespace().collect::<Vec<_>>();
                 let status = self.command_manager.execute(&mut ctx, &args);
@@ -313,7 +633,17 @@ impl Server {
                         }
                     }
                     Ok(None) => {
-                        PlayerSession::send_chat_message(user_id, &mut self.sessions, &message);
+                        PlayerSession::send_chat_message(
+                            user_id,
+                            &mut self.sessions,
+                            &self.teams,
+                            &message,
+                        );
+                        if let Some(bridge) = &self.chat_bridge
+                            && let Some(session) = self.sessions.get(&user_id)
+                        {
+                            bridge.chat(&session.username, &message);
+                        }
                     }
                     Err(err) => {
                         if let Some(session) = self.sessions.get_mut(&user_id) {
@@ -324,38 +654,208 @@ impl Server {
                                 err
                             );
                             session.pending_messages.push(S2CMessage::ChatMessage {
-                                message: format!("%bC3Error executing command: %bD3{}%r", err)
-                                    .parse()
-                                    .unwrap(),
+                                message: command_error(&err),
                             });
                         }
                     }
                 }
             }
-            C2SMessage::BlockClick {
-                position,
-                face,
-                right,
-            } => {
+            C2SMessage::BlockClick { look, slot, right } => {
+                if slot >= 9 {
+                    return None;
+                }
                 if let Some(user_id) = self.connections.get(&connection_id)
                     && let Some(session) = self.sessions.get_mut(user_id)
-                    && let Some(player_pos) = self
-                        .world
-                        .get_entity::<PlayerEntity>(session.entity_id)
-                        .map(|e| e.position)
+                    && let Some(player) =
+                        self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                    && !player.is_dead()
                 {
-                    if position.as_vec3().distance_squared(player_pos) > 25.0 {
+                    player.hotbar_index = slot;
+                    if right && player.cooldown > 0 {
+                        return None;
+                    }
+                    let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+                    let (position, normal) =
+                        self.world.cast_ray(eye, look, physics::REACH_DISTANCE)?;
+                    let face = Direction::try_from(normal).ok()?;
+                    let team = self.teams.team_of(&session.username).map(|(name, _)| name);
+                    let protected_by = self
+                        .claims
+                        .protected_by(&session.username, team, position)
+                        .or(right
+                            .then(|| {
+                                self.claims
+                                    .protected_by(&session.username, team, position + face)
+                            })
+                            .flatten());
+                    if let Some(region) = protected_by {
+                        session.pending_messages.push(S2CMessage::ChatMessage {
+                            message: format!(
+                                "%bC3This area is protected by region '{}'.%r",
+                                sanitize(region)
+                            )
+                            .parse()
+                            .unwrap(),
+                        });
                         return None;
                     }
                     if right {
+                        if let Some(player) =
+                            self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                        {
+                            player.cooldown = BLOCK_EDIT_COOLDOWN;
+                        }
+                        let place_pos = position + face;
+                        let before = self.world.get_block_at(place_pos).map(|(block, _)| block);
                         self.world
                             .block_interaction(session.entity_id, position, face);
+                        if let Some((block, _)) = self.world.get_block_at(place_pos)
+                            && Some(block) != before
+                        {
+                            session.record_mirror_edit(block, true, place_pos);
+                        }
                     } else {
+                        // The block breaks over the next ticks, see `Server::tick_breaking`
+                        session.breaking = Some(BlockBreaking {
+                            position,
+                            progress: 0.0,
+                        });
+                    }
+                }
+            }
+            C2SMessage::SetBlock { position, place } => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                    && let Some(edit) = &mut session.mirror_edit
+                    && edit.place == place
+                    && let Some(index) = edit.positions.iter().position(|&p| p == position)
+                    && let Some(player) = self.world.get_entity::<PlayerEntity>(session.entity_id)
+                    && !player.is_dead()
+                {
+                    edit.positions.swap_remove(index);
+                    let edited = edit.block;
+                    let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+                    if eye.distance(position.as_vec3() + Vec3::splat(0.5)) > MIRROR_DISTANCE {
+                        return None;
+                    }
+                    let team = self.teams.team_of(&session.username).map(|(name, _)| name);
+                    if self
+                        .claims
+                        .protected_by(&session.username, team, position)
+                        .is_some()
+                    {
+                        return None;
+                    }
+                    let held = player.inventory.hotbar_slot(player.hotbar_index);
+                    let held_block = (held.count > 0)
+                        .then(|| item_registry().get(held.item).unwrap().assoc_block)
+                        .flatten();
+                    let (block, _) = self.world.get_block_at(position)?;
+                    if !place && block == edited {
                         self.world.break_block(session.entity_id, position);
+                    } else if place
+                        && block == *blocks::AIR
+                        && let Some(held_block) = held_block
+                        && **held_block == edited
+                        && let Some(state) = BlockState::default_state(
+                            block_registry().get(**held_block)?.state_type,
+                        )
+                    {
+                        self.world.try_place_block(
+                            session.entity_id,
+                            position,
+                            **held_block,
+                            state,
+                        );
+                    }
+                }
+            }
+            C2SMessage::PlaceBlocks {
+                positions,
+                face,
+                slot,
+            } => {
+                if slot >= 9 || positions.len() > MAX_PLACED_BLOCKS {
+                    return None;
+                }
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                    && let Some(player) =
+                        self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                    && !player.is_dead()
+                {
+                    player.hotbar_index = slot;
+                    if player.cooldown > 0 {
+                        return None;
+                    }
+                    player.cooldown = BLOCK_EDIT_COOLDOWN;
+                    let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+                    let team = self.teams.team_of(&session.username).map(|(name, _)| name);
+                    for position in positions {
+                        let allowed = eye.distance(position.as_vec3() + Vec3::splat(0.5))
+                            <= BATCH_DISTANCE
+                            && self
+                                .claims
+                                .protected_by(&session.username, team, position)
+                                .is_none()
+                            && self
+                                .world
+                                .get_block_at(position)
+                                .is_some_and(|(block, _)| block == *blocks::AIR);
+                        if allowed {
+                            self.world
+                                .place_held_block(session.entity_id, position, face);
+                        }
                     }
                 }
             }
+            C2SMessage::StopBreaking => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                    && session.breaking.take().is_some()
+                {
+                    session
+                        .pending_messages
+                        .push(S2CMessage::BreakProgress { progress: 0.0 });
+                }
+            }
+            C2SMessage::SwapOffhand => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get(user_id)
+                    && let Some(player_entity) =
+                        self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                {
+                    player_entity
+                        .inventory
+                        .swap_offhand(player_entity.hotbar_index);
+                }
+            }
+            C2SMessage::Respawn => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get(user_id)
+                {
+                    self.respawn_player(session.entity_id);
+                }
+            }
+            C2SMessage::SetMirror { mirror } => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                {
+                    session.mirror = mirror;
+                    session.mirror_edit = None;
+                }
+            }
+            C2SMessage::StopSpectating => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                {
+                    session.spectating = None;
+                }
+            }
             C2SMessage::InventoryClick { idx, right } => {
+                if idx > OFFHAND_SLOT {
+                    return None;
+                }
                 if let Some(user_id) = self.connections.get(&connection_id)
                     && let Some(session) = self.sessions.get_mut(user_id)
                     && let Some(player_entity) =
@@ -365,6 +865,9 @@ impl Server {
                 }
             }
             C2SMessage::HotbarChange { idx } => {
+                if idx >= 9 {
+                    return None;
+                }
                 if let Some(user_id) = self.connections.get(&connection_id)
                     && let Some(session) = self.sessions.get_mut(user_id)
                     && let Some(player_entity) =
@@ -373,44 +876,525 @@ impl Server {
                     player_entity.hotbar_index = idx;
                 }
             }
+            C2SMessage::CreativePick { item, slot } => {
+                if slot >= 9 || item == *items::AIR || item_registry().get(item).is_none() {
+                    return None;
+                }
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get(user_id)
+                    && (self.singleplayer || self.access.is_operator(&session.username))
+                    && let Some(player_entity) =
+                        self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                {
+                    player_entity.inventory.pick(slot, item);
+                }
+            }
+            C2SMessage::ContainerClick {
+                id,
+                idx,
+                right,
+                shift,
+            } => {
+                self.container_action(connection_id, id, |container, inventory| {
+                    container.click(inventory, idx, right, shift)
+                });
+            }
+            C2SMessage::ContainerDistribute { id, slots, right } => {
+                self.container_action(connection_id, id, |container, inventory| {
+                    container.distribute(inventory, &slots, right)
+                });
+            }
+            C2SMessage::ContainerCollect { id } => {
+                self.container_action(connection_id, id, |container, inventory| {
+                    container.collect(inventory)
+                });
+            }
+            C2SMessage::CloseContainer { id } => {
+                if let Some(user_id) = self.connections.get(&connection_id)
+                    && let Some(session) = self.sessions.get_mut(user_id)
+                    && session.container.as_ref().is_some_and(|view| view.id == id)
+                {
+                    session.container = None;
+                }
+            }
         }
         None
     }
 
-    /// Ticks the server.
-    pub fn tick(&mut self, tps: u8) {
-        // Unload chunks that have no players nearby
-        let player_positions: Vec<_> = self
+    /// Applies an action of a player to the container they have open, if `id` is the ID of that
+    /// container and the player is still close to it. If the action fails, returning false, the
+    /// container and the inventory are sent to the player again to undo what the client predicted.
+    fn container_action(
+        &mut self,
+        connection_id: u64,
+        id: u64,
+        action: impl FnOnce(&mut Container, &mut Inventory) -> bool,
+    ) {
+        let Some(session) = self
+            .connections
+            .get(&connection_id)
+            .and_then(|user_id| self.sessions.get(user_id))
+        else {
+            return;
+        };
+        let Some(position) = session
+            .container
+            .as_ref()
+            .filter(|view| view.id == id)
+            .map(|view| view.position)
+        else {
+            return;
+        };
+        // Taken out of the world while clicking, so the player can be borrowed too
+        let Some(mut container) = self.world.containers.remove(&position) else {
+            return; // Closed in the next tick
+        };
+        if let Some(player) = self.world.get_entity_mut::<PlayerEntity>(session.entity_id) {
+            let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+            if eye.distance(position.as_vec3() + Vec3::splat(0.5)) > CONTAINER_DISTANCE
+                || !action(&mut container, &mut player.inventory)
+            {
+                container.dirty = true;
+                player.inventory.dirty = true;
+            }
+        }
+        self.world.containers.insert(position, container);
+    }
+
+    /// Opens the containers players clicked on, closes the ones they can't use anymore and sends
+    /// the changed contents to the players viewing them.
+    fn tick_containers(&mut self) {
+        for (entity_id, position) in std::mem::take(&mut self.world.container_opens) {
+            if let Some(player) = self.world.get_entity::<PlayerEntity>(entity_id) {
+                self.event_log.push(
+                    &player.username,
+                    position,
+                    eventlog::EventKind::OpenedContainer,
+                );
+            }
+            let Some(session) = Self::get_session_by_entity_mut(
+                &self.entity_to_user,
+                &mut self.sessions,
+                entity_id,
+            ) else {
+                continue;
+            };
+            let Some(container) = self.world.containers.get(&position) else {
+                continue;
+            };
+            let id = self.next_container_id;
+            self.next_container_id += 1;
+            session.container = Some(ContainerView { id, position });
+            session.pending_messages.push(S2CMessage::OpenContainer {
+                id,
+                rows: container.rows(),
+                title: container.title.clone(),
+            });
+            session.pending_messages.push(S2CMessage::ContainerUpdated {
+                id,
+                slots: container.slots.clone(),
+            });
+        }
+
+        for session in self.sessions.values_mut() {
+            let Some(view) = &session.container else {
+                continue;
+            };
+            let in_reach = self
+                .world
+                .get_entity::<PlayerEntity>(session.entity_id)
+                .is_some_and(|player| {
+                    let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+                    eye.distance(view.position.as_vec3() + Vec3::splat(0.5)) <= CONTAINER_DISTANCE
+                });
+            match self.world.containers.get(&view.position) {
+                Some(container) if in_reach => {
+                    if container.dirty {
+                        session.pending_messages.push(S2CMessage::ContainerUpdated {
+                            id: view.id,
+                            slots: container.slots.clone(),
+                        });
+                    }
+                }
+                _ => {
+                    session
+                        .pending_messages
+                        .push(S2CMessage::ContainerClosed { id: view.id });
+                    session.container = None;
+                }
+            }
+        }
+
+        for container in self.world.containers.values_mut() {
+            container.dirty = false;
+        }
+    }
+
+    /// Sends spectators the view of the player they are watching, stopping once that player left.
+    fn tick_spectators(&mut self) {
+        for session in self.sessions.values_mut() {
+            let Some(target) = session.spectating else {
+                continue;
+            };
+            match self.world.get_entity::<PlayerEntity>(target) {
+                Some(player) => session.pending_messages.push(S2CMessage::SpectatorCamera {
+                    camera: CameraKeyframe {
+                        position: player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0),
+                        yaw: player.yaw,
+                        pitch: player.pitch,
+                    },
+                }),
+                None => {
+                    session.spectating = None;
+                    session.pending_messages.push(S2CMessage::SpectateStopped);
+                    session.pending_messages.push(S2CMessage::ChatMessage {
+                        message: "The player you were spectating left%r".parse().unwrap(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Logs the blocks players placed and broke, see [`eventlog`].
+    fn tick_event_log(&mut self) {
+        let edits = std::mem::take(&mut self.world.player_edits);
+        for (entity_id, position, block, state, placed) in edits {
+            let Some(player) = self.world.get_entity::<PlayerEntity>(entity_id) else {
+                continue;
+            };
+            let block = block_registry().get(block).unwrap().ident.to_string();
+            let kind = if placed {
+                eventlog::EventKind::Placed { block }
+            } else {
+                eventlog::EventKind::Broke {
+                    block,
+                    state: state.bits(),
+                }
+            };
+            self.event_log.push(&player.username, position, kind);
+        }
+    }
+
+    /// Shows the messages that came in over the chat bridge to everyone.
+    fn tick_chat_bridge(&mut self) {
+        let Some(bridge) = &self.chat_bridge else {
+            return;
+        };
+        for message in bridge.poll() {
+            log::info!("[Bridge] {}: {}", message.name, message.message);
+            if let Some(chat_log) = &mut self.chat_log {
+                chat_log.write(&format!("[Bridge] <{}> {}", message.name, message.message));
+            }
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::ChatMessage {
+                    message: message.text(),
+                },
+            );
+        }
+    }
+
+    /// Kills the players that fell too far below the world floor.
+    fn tick_void(&mut self) {
+        let fallen: Vec<_> = self
             .sessions
             .values()
-            .filter_map(|session| {
+            .filter(|session| {
                 self.world
                     .get_entity::<PlayerEntity>(session.entity_id)
-                    .map(|entity| entity.position / CHUNK_SIZE as f32)
+                    .is_some_and(|player| {
+                        !player.is_dead()
+                            && player.position.y < WORLD_FLOOR as f32 - VOID_DEATH_DEPTH
+                    })
             })
+            .map(|session| session.entity_id)
             .collect();
-        self.world.chunks.retain(|&pos, _| {
-            let pos = pos.as_vec3() + Vec3::splat(0.5);
-            player_positions
-                .iter()
-                .any(|player_pos| pos.distance_squared(*player_pos) as i32 <= MAX_RENDER_DIST_SQ)
-        });
+        for entity_id in fallen {
+            self.kill_player(entity_id, DeathCause::Void, None);
+        }
+    }
 
-        self.tps = tps;
-        self.world.tick(tps);
+    /// Hurts the players that landed after falling too far, unless the `fall_damage` game rule is
+    /// off.
+    fn tick_fall_damage(&mut self) {
+        let landed: Vec<_> = self
+            .sessions
+            .values()
+            .filter_map(|session| {
+                let player = self
+                    .world
+                    .get_entity_mut::<PlayerEntity>(session.entity_id)?;
+                let damage = std::mem::take(&mut player.fall_damage);
+                (damage > 0.0).then_some((session.entity_id, damage))
+            })
+            .collect();
+        if !self.world.game_rules.fall_damage {
+            return;
+        }
+        for (entity_id, damage) in landed {
+            let damage = self.world.difficulty.scale_damage(damage);
+            self.damage_player(entity_id, damage, DeathCause::Fall, None);
+        }
+    }
 
-        let pending_changes = std::mem::take(&mut self.world.pending_changes).collect::<Vec<_>>();
+    /// Applies the changes to the health of players queued in [`World::player_damage`].
+    fn tick_player_damage(&mut self) {
+        for (entity_id, damage, cause, attacker) in std::mem::take(&mut self.world.player_damage) {
+            if damage < 0.0 {
+                self.heal_player(entity_id, -damage);
+            } else {
+                self.damage_player(entity_id, damage, cause, attacker);
+            }
+        }
+    }
+
+    /// Takes `amount` health from a player, killing them if none is left.
+    pub fn damage_player(
+        &mut self,
+        entity_id: u64,
+        amount: f32,
+        cause: DeathCause,
+        attacker: Option<String>,
+    ) {
+        let Some(player) = self.world.get_entity_mut::<PlayerEntity>(entity_id) else {
+            return;
+        };
+        if player.is_dead() || amount <= 0.0 {
+            return;
+        }
+        player.health = (player.health - amount).max(0.0);
+        if player.is_dead() {
+            self.kill_player(entity_id, cause, attacker);
+        } else {
+            let health = player.health;
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::HealthChanged { entity_id, health },
+            );
+        }
+    }
+
+    /// Gives a player `amount` health, up to [`MAX_HEALTH`]. Dead players have to respawn first.
+    pub fn heal_player(&mut self, entity_id: u64, amount: f32) {
+        let Some(player) = self.world.get_entity_mut::<PlayerEntity>(entity_id) else {
+            return;
+        };
+        if player.is_dead() {
+            return;
+        }
+        player.health = (player.health + amount).min(MAX_HEALTH);
+        let health = player.health;
         broadcast_message(
             &mut self.sessions,
             None,
-            S2CMessage::BlocksUpdated {
-                updates: pending_changes,
+            S2CMessage::HealthChanged { entity_id, health },
+        );
+    }
+
+    /// Kills a player, telling everyone how they died. The player stays where they died until
+    /// they ask to respawn.
+    pub fn kill_player(&mut self, entity_id: u64, cause: DeathCause, attacker: Option<String>) {
+        self.world.sleeping.remove(&entity_id);
+        let Some(player) = self.world.get_entity_mut::<PlayerEntity>(entity_id) else {
+            return;
+        };
+        self.event_log.push(
+            &player.username,
+            player.position.floor().as_ivec3(),
+            eventlog::EventKind::Died {
+                cause: cause.name().to_string(),
+                attacker: attacker.clone(),
             },
         );
+        player.health = 0.0;
+        player.velocity = Vec3::ZERO;
+        player.input = MoveInput::default();
+        let event = DeathEvent {
+            victim: player.username.clone(),
+            attacker,
+            cause,
+        };
+        log::info!(
+            "Player '{}' died: {}",
+            event.victim,
+            event.translation_key()
+        );
+        broadcast_message(
+            &mut self.sessions,
+            None,
+            S2CMessage::HealthChanged {
+                entity_id,
+                health: 0.0,
+            },
+        );
+        if let Some(bridge) = &self.chat_bridge {
+            bridge.died(&event);
+        }
+        broadcast_message(&mut self.sessions, None, S2CMessage::PlayerDied { event });
+    }
 
-        for entity in self.world.entities.values() {
-            if let Some(entity) = entity.as_any().downcast_ref::<PlayerEntity>() {
-                if entity.velocity.length_squared() > 0.0 {
+    /// Brings a dead player back to life at the spawn position with full health.
+    pub fn respawn_player(&mut self, entity_id: u64) {
+        let Some(player) = self.world.get_entity_mut::<PlayerEntity>(entity_id) else {
+            return;
+        };
+        if !player.is_dead() {
+            return;
+        }
+        player.position = SPAWN_POSITION;
+        player.velocity = Vec3::ZERO;
+        player.fall_damage = 0.0;
+        player.health = MAX_HEALTH;
+        let moved = S2CMessage::PlayerMoved {
+            entity_id,
+            position: player.position,
+            yaw: player.yaw,
+            pitch: player.pitch,
+        };
+        self.world.load_around(SPAWN_POSITION.as_ivec3());
+        broadcast_message(&mut self.sessions, None, moved);
+        broadcast_message(
+            &mut self.sessions,
+            None,
+            S2CMessage::HealthChanged {
+                entity_id,
+                health: MAX_HEALTH,
+            },
+        );
+    }
+
+    /// Advances the blocks players are breaking, breaking them once they are done. The player
+    /// breaks whatever they are looking at, so looking at another block starts over.
+    fn tick_breaking(&mut self) {
+        for session in self.sessions.values_mut() {
+            let Some(breaking) = &mut session.breaking else {
+                continue;
+            };
+            let Some(player) = self.world.get_entity::<PlayerEntity>(session.entity_id) else {
+                continue;
+            };
+            if player.cooldown > 0 || player.is_dead() {
+                continue;
+            }
+            let eye = player.position + Vec3::new(0.0, physics::EYE_HEIGHT, 0.0);
+            let look = physics::look_direction(player.yaw, player.pitch);
+            let target = self.world.cast_ray(eye, look, physics::REACH_DISTANCE);
+            let team = self.teams.team_of(&session.username).map(|(name, _)| name);
+            let Some((position, _)) = target.filter(|(position, _)| {
+                self.claims
+                    .protected_by(&session.username, team, *position)
+                    .is_none()
+            }) else {
+                if breaking.progress > 0.0 {
+                    breaking.progress = 0.0;
+                    session
+                        .pending_messages
+                        .push(S2CMessage::BreakProgress { progress: 0.0 });
+                }
+                continue;
+            };
+            if position != breaking.position {
+                breaking.position = position;
+                breaking.progress = 0.0;
+            }
+
+            let Some((block, _)) = self.world.get_block_at(position) else {
+                continue;
+            };
+            let block_def = block_registry().get(block).unwrap();
+            let (tool_slot, break_time) = player.break_tool(block_def);
+            breaking.progress += 1.0 / (break_time * self.tps as f32);
+            let mut broke = false;
+            if breaking.progress >= 1.0 {
+                breaking.progress = 0.0;
+                broke = self.world.break_block(session.entity_id, position);
+                if broke
+                    && let Some(player) =
+                        self.world.get_entity_mut::<PlayerEntity>(session.entity_id)
+                {
+                    if let Some(slot) = tool_slot
+                        && block_def.hardness > 0.0
+                    {
+                        player.inventory.slot_mut(slot).unwrap().wear();
+                        player.inventory.dirty = true;
+                    }
+                    player.cooldown = BLOCK_EDIT_COOLDOWN;
+                } else if !broke && self.world.holds_items(position) {
+                    session.pending_messages.push(S2CMessage::ChatMessage {
+                        message: "%bC3The items in this block don't fit into your inventory.%r"
+                            .parse()
+                            .unwrap(),
+                    });
+                }
+            }
+            session.pending_messages.push(S2CMessage::BreakProgress {
+                progress: breaking.progress,
+            });
+            if broke {
+                session.record_mirror_edit(block, false, position);
+            }
+        }
+    }
+
+    /// Ticks the server.
+    pub fn tick(&mut self, tps: u8) {
+        self.ticks += 1;
+
+        self.tick_chunk_tickets();
+        self.world.poll_generated_chunks();
+
+        self.tps = tps;
+        let world_ticks = self.world.tick(tps);
+        self.tick_time(world_ticks);
+        self.tick_breaking();
+        self.tick_containers();
+        self.tick_spectators();
+        self.tick_fall_damage();
+        self.tick_player_damage();
+        self.tick_void();
+        self.tick_event_log();
+        self.tick_chat_bridge();
+        for entity_id in std::mem::take(&mut self.world.spawned_entities) {
+            if let Some(entity) = self.world.entities.get(&entity_id) {
+                broadcast_message(&mut self.sessions, None, entity_spawned(entity.as_ref()));
+            }
+        }
+        self.tick_entity_updates();
+        self.tick_chunk_saving();
+
+        if std::mem::take(&mut self.teams.changed) {
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::TeamsUpdated {
+                    colors: self.teams.colors(),
+                },
+            );
+        }
+
+        let pending_changes = std::mem::take(&mut self.world.pending_changes).collect::<Vec<_>>();
+        if !pending_changes.is_empty() {
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::BlocksUpdated {
+                    updates: pending_changes,
+                },
+            );
+        }
+
+        for entity in self.world.entities.values_mut() {
+            if let Some(entity) = entity.as_any_mut().downcast_mut::<PlayerEntity>() {
+                let look = (entity.yaw, entity.pitch);
+                let turned = Self::get_session_by_entity_mut(
+                    &self.entity_to_user,
+                    &mut self.sessions,
+                    entity.id(),
+                )
+                .is_some_and(|session| std::mem::replace(&mut session.sent_look, look) != look);
+                if entity.velocity.length_squared() > 0.0 || turned {
                     broadcast_message(
                         &mut self.sessions,
                         None,
@@ -430,9 +1414,22 @@ impl Server {
                         entity.id(),
                     )
                 {
-                    session.pending_messages.push(S2CMessage::InventoryUpdated {
-                        inventory: entity.inventory.clone(),
-                    });
+                    entity.inventory.dirty = false;
+                    let inventory = &entity.inventory;
+                    let sent = &session.sent_inventory;
+                    let slots = inventory.changed_slots(sent);
+                    let temp = (inventory.temp != sent.temp).then_some(inventory.temp);
+                    // Sending every slot is smaller once most of them changed
+                    if slots.len() > FULL_INVENTORY_UPDATE_SLOTS {
+                        session.pending_messages.push(S2CMessage::InventoryUpdated {
+                            inventory: inventory.clone(),
+                        });
+                    } else if !slots.is_empty() || temp.is_some() {
+                        session
+                            .pending_messages
+                            .push(S2CMessage::InventorySlotsUpdated { slots, temp });
+                    }
+                    session.sent_inventory = inventory.clone();
                 }
             }
         }
@@ -440,27 +1437,168 @@ impl Server {
 }
 
 impl Server {
-    /// Saves the server state to disk, including the world and user database.
-    pub fn save(&self) -> std::io::Result<()> {
+    /// Sends the entities other than players whose snapshot changed since it was last sent.
+    fn tick_entity_updates(&mut self) {
+        self.sent_snapshots
+            .retain(|entity_id, _| self.world.entities.contains_key(entity_id));
+        for entity in self.world.entities.values() {
+            if entity.entity_type() == *entities::PLAYER {
+                continue;
+            }
+            let snapshot = entity.snapshot();
+            match self.sent_snapshots.get_mut(&entity.id()) {
+                Some(sent) if *sent == snapshot => {}
+                // Spawning sent the snapshot already
+                None => {
+                    self.sent_snapshots.insert(entity.id(), snapshot);
+                }
+                Some(sent) => {
+                    *sent = snapshot.clone();
+                    broadcast_message(
+                        &mut self.sessions,
+                        None,
+                        S2CMessage::EntityUpdated {
+                            entity_id: entity.id(),
+                            entity_snapshot: snapshot,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Moves the chunk tickets of every connection to its player and unloads the chunks without
+    /// a ticket. Unloaded chunks with unsaved changes are saved right away.
+    fn tick_chunk_tickets(&mut self) {
+        for (connection_id, user_id) in &self.connections {
+            if let Some(session) = self.sessions.get(user_id)
+                && let Some(entity) = self
+                    .world
+                    .get_entity::<PlayerEntity>(session.spectating.unwrap_or(session.entity_id))
+            {
+                let center = (entity.position / CHUNK_SIZE as f32).floor().as_ivec3();
+                self.chunk_tickets.update(*connection_id, center);
+            }
+        }
+        self.chunk_tickets
+            .retain(|connection_id| self.connections.contains_key(&connection_id));
+
+        let tickets = &self.chunk_tickets;
+        let unloaded = self.world.unload_chunks(|pos| tickets.is_needed(pos));
+        self.world
+            .cancel_chunk_requests(|pos| tickets.is_needed(pos));
+        if unloaded
+            .iter()
+            .any(|&chunk_pos| self.world.has_unsaved_changes(chunk_pos))
+            && self.was_saved()
+        {
+            self.world.save_dirty_chunks(&self.save_path);
+        }
+    }
+
+    /// Returns whether the world was saved before. Chunks are only saved by themselves after that,
+    /// so that new worlds don't leave half a save behind.
+    fn was_saved(&self) -> bool {
+        self.save_path.join("save.bin").exists()
+    }
+
+    /// Autosaves the changed chunks in the background and logs the errors writing them.
+    fn tick_chunk_saving(&mut self) {
+        if self
+            .ticks
+            .is_multiple_of(CHUNK_AUTOSAVE_INTERVAL * self.tps.max(1) as u64)
+            && self.was_saved()
+        {
+            self.world.save_dirty_chunks(&self.save_path);
+        }
+        for error in self.world.chunk_io_errors() {
+            log::error!("{}", error);
+        }
+    }
+
+    /// Skips the night once all players are in bed and keeps the clocks of the players in sync.
+    /// `world_ticks` is how many times the world ticked, see [`World::tick`].
+    fn tick_time(&mut self, world_ticks: u32) {
+        if !self.world.is_night() {
+            self.world.sleeping.clear();
+        } else if !self.sessions.is_empty()
+            && self
+                .sessions
+                .values()
+                .all(|session| self.world.sleeping.contains(&session.entity_id))
+        {
+            self.world.skip_to_morning();
+            log::info!("All players are sleeping, skipping the night");
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::ChatMessage {
+                    message: "Sleeping through the night...%r".parse().unwrap(),
+                },
+            );
+        }
+
+        let time = self.world.time;
+        let advanced = self.world.game_rules.daylight_cycle as u64 * world_ticks as u64;
+        let advancing = self.world.game_rules.daylight_cycle && !self.world.tick_control.frozen;
+        if time != self.last_time + advanced
+            || advancing != self.time_advancing
+            || (advanced > 0 && time.is_multiple_of(TIME_SYNC_INTERVAL))
+        {
+            broadcast_message(
+                &mut self.sessions,
+                None,
+                S2CMessage::TimeUpdate { time, advancing },
+            );
+        }
+        self.last_time = time;
+        self.time_advancing = advancing;
+    }
+
+    /// Saves the server state to disk, including the world, user database, claims, teams and the
+    /// event log if it is persisted.
+    pub fn save(&mut self) -> std::io::Result<()> {
         self.world.save(&self.save_path)?;
         self.user_db.save()?;
+        self.claims.save()?;
+        self.teams.save()?;
+        self.access.save()?;
+        self.event_log.save()?;
         Ok(())
     }
 
-    /// Loads the server state from disk, including the world and user database.
+    /// Loads the server state from disk, including the world, user database, claims, teams and
+    /// access rules.
     pub fn load(singleplayer: bool, save_path: PathBuf) -> std::io::Result<Self> {
         let mut command_manager = CommandManager::new();
         commands::init_command_mgr(&mut command_manager);
+        let world = World::load(&save_path)?;
+        let last_time = world.time;
+        let time_advancing = world.game_rules.daylight_cycle;
         Ok(Self {
             sessions: FxHashMap::default(),
             connections: FxHashMap::default(),
             entity_to_user: FxHashMap::default(),
-            world: World::load(&save_path)?,
+            world,
             singleplayer,
             save_path: save_path.clone(),
             user_db: user::UserDatabase::load(save_path.join("users.json")),
+            claims: claims::ClaimDatabase::load(save_path.join("claims.json")),
+            teams: teams::TeamDatabase::load(save_path.join("teams.json")),
+            access: access::AccessList::load(save_path.join("access.json")),
+            event_log: eventlog::EventLog::load(save_path.join("eventlog.json")),
             command_manager,
             tps: 48,
+            last_time,
+            time_advancing,
+            sent_snapshots: FxHashMap::default(),
+            next_container_id: 1,
+            chunk_tickets: tickets::ChunkTickets::new(),
+            ticks: 0,
+            recording_start: 0,
+            recording: None,
+            chat_log: None,
+            chat_bridge: None,
         })
     }
 }
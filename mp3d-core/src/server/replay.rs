@@ -0,0 +1,121 @@
+//! Recording and replaying client input against a deterministic server, so that gameplay can be
+//! checked for regressions without a client or graphics.
+//!
+//! A server records the messages it handles with [`Server::start_recording`]. Replaying the
+//! recorded [`InputScript`] with [`replay`] on a new server in deterministic mode (see
+//! [`World::set_deterministic`](crate::world::World::set_deterministic)) ends in the same world
+//! every time, which is compared through [`World::state_hash`](crate::world::World::state_hash).
+
+use std::path::PathBuf;
+
+use crate::{protocol::C2SMessage, server::Server};
+
+/// A message received from a client during a recording.
+#[derive(Clone, Debug)]
+pub struct ScriptStep {
+    /// The number of server ticks before the message was received.
+    pub tick: u64,
+    pub connection_id: u64,
+    pub message: C2SMessage,
+}
+
+/// The messages received from clients during a recording, in the order they were received.
+#[derive(Clone, Debug, Default)]
+pub struct InputScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a message received after `tick` server ticks. Messages must be added in order.
+    pub fn push(&mut self, tick: u64, connection_id: u64, message: C2SMessage) {
+        self.steps.push(ScriptStep {
+            tick,
+            connection_id,
+            message,
+        });
+    }
+
+    /// Adds a message received after `tick` server ticks, for writing scripts by hand.
+    pub fn at(mut self, tick: u64, connection_id: u64, message: C2SMessage) -> Self {
+        self.push(tick, connection_id, message);
+        self
+    }
+}
+
+/// Replays a script on a new deterministic singleplayer server with the given world seed,
+/// running `ticks` server ticks at `tps` ticks per second. Returns the hash of the world at the
+/// end. Nothing is saved, so `save_path` only needs to exist if the server should start with the
+/// users, claims and teams stored there.
+pub fn replay(seed: i32, save_path: PathBuf, script: &InputScript, ticks: u64, tps: u8) -> u64 {
+    let mut server = Server::new(true, seed, save_path);
+    server.world.set_deterministic();
+
+    let mut steps = script.steps.iter().peekable();
+    for tick in 0..ticks {
+        while let Some(step) = steps.next_if(|step| step.tick <= tick) {
+            server.handle_message(step.connection_id, step.message.clone());
+        }
+        server.tick(tps);
+    }
+    server.world.state_hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec3;
+
+    use super::*;
+    use crate::{preferences::Preferences, protocol::MoveInstructions};
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        crate::init();
+
+        let move_forward = MoveInstructions {
+            forward: 1,
+            yaw: 45.0,
+            ..Default::default()
+        };
+        let script = InputScript::new()
+            .at(
+                0,
+                0,
+                C2SMessage::Connect {
+                    username: "Replay".to_string(),
+                    password: "SINGLEPLAYER".to_string(),
+                    preferences: Preferences::default(),
+                },
+            )
+            .at(
+                0,
+                0,
+                C2SMessage::RequestChunks {
+                    chunk_positions: vec![IVec3::new(0, 0, 0), IVec3::new(0, 1, 0)],
+                },
+            )
+            .at(10, 0, C2SMessage::Move(move_forward))
+            .at(30, 0, C2SMessage::Move(MoveInstructions::default()))
+            .at(
+                40,
+                0,
+                C2SMessage::SendMessage {
+                    message: "/setblock stone 0 20 0".to_string(),
+                },
+            );
+        let save_path = std::env::temp_dir().join("mp3d-replay-test");
+
+        let first = replay(1234, save_path.clone(), &script, 60, 48);
+        let second = replay(1234, save_path.clone(), &script, 60, 48);
+        assert_eq!(first, second);
+
+        // The hash has to notice the placed block
+        let without_edit = InputScript {
+            steps: script.steps[..script.steps.len() - 1].to_vec(),
+        };
+        assert_ne!(first, replay(1234, save_path, &without_edit, 60, 48));
+    }
+}
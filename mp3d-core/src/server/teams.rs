@@ -0,0 +1,237 @@
+//! Teams of players.
+//!
+//! Every player is in at most one team. The team color is shown on the names of its members in
+//! chat and on name tags, and teams can talk in their own chat channel with `/team chat`. Players
+//! can only join a team the owner invited them to, and only the owner can change its settings.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The color of a team.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TeamColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Aqua,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl TeamColor {
+    pub const ALL: [TeamColor; 8] = [
+        TeamColor::Red,
+        TeamColor::Orange,
+        TeamColor::Yellow,
+        TeamColor::Green,
+        TeamColor::Aqua,
+        TeamColor::Blue,
+        TeamColor::Purple,
+        TeamColor::Gray,
+    ];
+
+    /// Returns the lowercase name of the color, as used in commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            TeamColor::Red => "red",
+            TeamColor::Orange => "orange",
+            TeamColor::Yellow => "yellow",
+            TeamColor::Green => "green",
+            TeamColor::Aqua => "aqua",
+            TeamColor::Blue => "blue",
+            TeamColor::Purple => "purple",
+            TeamColor::Gray => "gray",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+
+    /// Returns the basic color code of the color, see
+    /// [`TextComponentColor::Basic`](crate::textcomponent::TextComponentColor::Basic).
+    pub fn code(self) -> u8 {
+        match self {
+            TeamColor::Red => 0xC3,
+            TeamColor::Orange => 0xE3,
+            TeamColor::Yellow => 0xF3,
+            TeamColor::Green => 0x33,
+            TeamColor::Aqua => 0x3F,
+            TeamColor::Blue => 0x1F,
+            TeamColor::Purple => 0xCF,
+            TeamColor::Gray => 0xAB,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    pub color: TeamColor,
+    /// The member who created the team, who invites players and changes the settings. When the
+    /// owner leaves, or for teams saved before teams had owners, the member whose name comes first
+    /// is the owner.
+    #[serde(default)]
+    pub owner: String,
+    pub members: BTreeSet<String>,
+    /// The players the owner invited who haven't joined yet.
+    #[serde(default)]
+    pub invited: BTreeSet<String>,
+    /// Whether members of the team can hurt each other.
+    pub friendly_fire: bool,
+}
+
+/// The teams of a world, keyed by name.
+pub struct TeamDatabase {
+    pub teams: BTreeMap<String, Team>,
+    pub file_path: PathBuf,
+    /// Whether the teams changed since they were last sent to the players.
+    pub changed: bool,
+}
+
+impl TeamDatabase {
+    pub fn load(file_path: PathBuf) -> Self {
+        if let Ok(data) = std::fs::read(&file_path)
+            && let Ok(mut teams) = serde_json::from_slice::<BTreeMap<String, Team>>(&data)
+        {
+            for team in teams.values_mut() {
+                if !team.members.contains(&team.owner) {
+                    team.owner = team.members.first().cloned().unwrap_or_default();
+                }
+            }
+            return Self {
+                teams,
+                file_path,
+                changed: false,
+            };
+        }
+        Self {
+            teams: BTreeMap::new(),
+            file_path,
+            changed: false,
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let data = serde_json::to_vec(&self.teams)?;
+        std::fs::write(&self.file_path, data)
+    }
+
+    /// Returns the name and the team of the player, if they are in one.
+    pub fn team_of(&self, username: &str) -> Option<(&str, &Team)> {
+        self.teams
+            .iter()
+            .find(|(_, team)| team.members.contains(username))
+            .map(|(name, team)| (name.as_str(), team))
+    }
+
+    /// Creates a new team with the player as its only member.
+    pub fn create(&mut self, name: String, color: TeamColor, owner: String) -> Result<(), String> {
+        if self.teams.contains_key(&name) {
+            return Err(format!("A team named '{}' already exists", name));
+        }
+        self.leave(&owner);
+        self.teams.insert(
+            name,
+            Team {
+                color,
+                owner: owner.clone(),
+                members: BTreeSet::from([owner]),
+                invited: BTreeSet::new(),
+                friendly_fire: false,
+            },
+        );
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Returns the name and the team the player owns, failing if they aren't the owner of a team.
+    pub fn owned_team_mut(&mut self, username: &str) -> Result<(String, &mut Team), String> {
+        let (name, team) = self.team_of(username).ok_or("You aren't in a team")?;
+        if team.owner != username {
+            return Err(format!("Only the owner of '{}' can do that", name));
+        }
+        let name = name.to_string();
+        let team = self.teams.get_mut(&name).unwrap();
+        Ok((name, team))
+    }
+
+    /// Invites the player to the team the owner owns, returning the name of the team.
+    pub fn invite(&mut self, owner: &str, username: String) -> Result<String, String> {
+        let (name, team) = self.owned_team_mut(owner)?;
+        if team.members.contains(&username) {
+            return Err(format!("{} is already in '{}'", username, name));
+        }
+        team.invited.insert(username);
+        Ok(name)
+    }
+
+    /// Moves the player into the team, if they were invited to it.
+    pub fn join(&mut self, name: &str, username: String) -> Result<(), String> {
+        let Some(team) = self.teams.get(name) else {
+            return Err(format!("Unknown team: '{}'", name));
+        };
+        if team.members.contains(&username) {
+            return Err(format!("You are already in '{}'", name));
+        }
+        if !team.invited.contains(&username) {
+            return Err(format!("You weren't invited to '{}'", name));
+        }
+        self.leave(&username);
+        let team = self.teams.get_mut(name).unwrap();
+        team.invited.remove(&username);
+        team.members.insert(username);
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Removes the player from their team, returning the name of the team. Teams without members
+    /// are removed, and the member whose name comes first becomes the owner if the owner leaves.
+    pub fn leave(&mut self, username: &str) -> Option<String> {
+        let name = self.team_of(username)?.0.to_string();
+        let team = self.teams.get_mut(&name).unwrap();
+        team.members.remove(username);
+        if team.members.is_empty() {
+            self.teams.remove(&name);
+        } else if team.owner == username {
+            team.owner = team.members.first().unwrap().clone();
+        }
+        self.changed = true;
+        Some(name)
+    }
+
+    /// Returns whether `attacker` is allowed to hurt `victim`.
+    pub fn can_damage(&self, attacker: &str, victim: &str) -> bool {
+        match self.team_of(attacker) {
+            Some((_, team)) => team.friendly_fire || !team.members.contains(victim),
+            None => true,
+        }
+    }
+
+    /// Returns the team color code of every player in a team, which is what clients need to know
+    /// about the teams.
+    pub fn colors(&self) -> Vec<(String, u8)> {
+        self.teams
+            .values()
+            .flat_map(|team| {
+                team.members
+                    .iter()
+                    .map(|member| (member.clone(), team.color.code()))
+            })
+            .collect()
+    }
+
+    /// Formats the name of the player in their team color.
+    pub fn colored_name(&self, username: &str) -> String {
+        let name = crate::textcomponent::sanitize(username);
+        match self.team_of(username) {
+            Some((_, team)) => format!("%b{:02X}{}%r", team.color.code(), name),
+            None => name,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Chunk tickets, deciding which chunks the server keeps loaded.
+//!
+//! Every connection holds a ticket for each chunk within [`TICKET_RADIUS`] of the player it sees
+//! the world from, which is the player it spectates if it spectates one. A chunk is needed as
+//! long as any connection holds a ticket for it, and the server unloads the chunks nobody needs.
+//! The tickets of a connection only change when its player crosses into another chunk.
+
+use fxhash::FxHashMap;
+use glam::IVec3;
+
+use crate::server::MAX_RENDER_DIST;
+
+/// How far, in chunks, the tickets of a connection reach from the chunk its player is in. This is
+/// one more than [`MAX_RENDER_DIST`], as players can request chunks up to that distance from
+/// anywhere within their chunk.
+pub const TICKET_RADIUS: i32 = MAX_RENDER_DIST + 1;
+
+/// The chunk tickets of all connections, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct ChunkTickets {
+    /// The chunk each connection holds tickets around.
+    centers: FxHashMap<u64, IVec3>,
+    /// How many connections hold a ticket for each chunk.
+    counts: FxHashMap<IVec3, u32>,
+}
+
+impl ChunkTickets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the tickets of a connection to be around the chunk at `center`.
+    pub fn update(&mut self, connection_id: u64, center: IVec3) {
+        match self.centers.insert(connection_id, center) {
+            Some(old) if old == center => return,
+            Some(old) => self.release(old),
+            None => {}
+        }
+        for pos in tickets_around(center) {
+            *self.counts.entry(pos).or_default() += 1;
+        }
+    }
+
+    /// Drops the tickets of a connection.
+    pub fn remove(&mut self, connection_id: u64) {
+        if let Some(center) = self.centers.remove(&connection_id) {
+            self.release(center);
+        }
+    }
+
+    /// Drops the tickets of all connections for which `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(u64) -> bool) {
+        let removed: Vec<_> = self
+            .centers
+            .keys()
+            .copied()
+            .filter(|&connection_id| !keep(connection_id))
+            .collect();
+        for connection_id in removed {
+            self.remove(connection_id);
+        }
+    }
+
+    /// Returns whether any connection holds a ticket for the chunk.
+    pub fn is_needed(&self, chunk_pos: IVec3) -> bool {
+        self.counts.contains_key(&chunk_pos)
+    }
+
+    /// The number of chunks with a ticket.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns whether no chunk has a ticket.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Drops one ticket for each chunk around `center`.
+    fn release(&mut self, center: IVec3) {
+        for pos in tickets_around(center) {
+            if let Some(count) = self.counts.get_mut(&pos) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&pos);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the positions of the chunks within [`TICKET_RADIUS`] of `center`.
+fn tickets_around(center: IVec3) -> impl Iterator<Item = IVec3> {
+    (-TICKET_RADIUS..=TICKET_RADIUS).flat_map(move |x| {
+        (-TICKET_RADIUS..=TICKET_RADIUS).flat_map(move |y| {
+            (-TICKET_RADIUS..=TICKET_RADIUS).filter_map(move |z| {
+                let offset = IVec3::new(x, y, z);
+                (offset.length_squared() <= TICKET_RADIUS * TICKET_RADIUS)
+                    .then_some(center + offset)
+            })
+        })
+    })
+}
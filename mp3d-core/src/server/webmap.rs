@@ -0,0 +1,480 @@
+//! A web map of the world, which server owners and players can look at in a browser.
+//!
+//! [`WebMap::update`] looks up where the players are every [`PLAYER_INTERVAL`] on the thread
+//! running the server. Every [`RENDER_INTERVAL`] it copies the chunks that changed or were loaded
+//! since the last render, which a background thread renders to top down tiles. Each pixel of a
+//! tile is the top visible block of a column, colored with the map colors of the datapack and
+//! shaded by the height difference to the block north of it. Tiles are kept after their chunks
+//! unload, so the map shows what was explored since the server started, up to a limit.
+//!
+//! A background thread serves the map over HTTP:
+//! - `/` the page showing the map, which pans by dragging and zooms with the mouse wheel.
+//! - `/tiles.json` the rendered tiles as `{"size": 128, "version": 3, "tiles": [[x, z], ...]}`.
+//! - `/tiles/<x>/<z>.bmp` a tile, covering the blocks from `x * size` and `z * size` on.
+//! - `/players.json` the players as `[{"name": "...", "x": 0.0, "y": 0.0, "z": 0.0}, ...]`.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use fxhash::{FxHashMap, FxHashSet};
+use glam::IVec3;
+use serde::Serialize;
+
+use crate::{
+    block::{BlockId, block_registry},
+    server::Server,
+    world::{
+        World,
+        chunk::{CHUNK_SIZE, Chunk},
+    },
+};
+
+/// The number of chunks along each side of a tile.
+pub const TILE_CHUNKS: i32 = 8;
+/// The number of blocks, and pixels, along each side of a tile.
+const TILE_SIZE: usize = TILE_CHUNKS as usize * CHUNK_SIZE;
+/// How often the chunks changed or loaded since the last render are rendered.
+pub const RENDER_INTERVAL: Duration = Duration::from_secs(30);
+/// The most tiles kept, about 50 MB of pixels. The tiles rendered longest ago are dropped first.
+const MAX_TILES: usize = 1024;
+/// How often the positions of the players are updated.
+pub const PLAYER_INTERVAL: Duration = Duration::from_secs(1);
+/// The color of blocks without a map color.
+const DEFAULT_COLOR: [u8; 3] = [128, 128, 128];
+/// The color of columns with nothing visible in them, the same as the background of the page.
+const EMPTY_COLOR: [u8; 3] = [24, 24, 36];
+/// How much lighter or darker a block is drawn when it is higher or lower than the one north of
+/// it.
+const SLOPE_SHADE: f32 = 0.15;
+/// The longest request line read from a request, in bytes. Nothing past it is read.
+const MAX_HEADER_SIZE: usize = 8192;
+/// The most requests handled at once. Further connections are closed right away.
+const MAX_CONNECTIONS: usize = 16;
+
+const MAP_PAGE: &str = include_str!("webmap.html");
+
+/// A player shown on the map.
+#[derive(Debug, Clone, Serialize)]
+struct PlayerMarker {
+    name: String,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[derive(Serialize)]
+struct TileList {
+    size: usize,
+    /// Counts up every time tiles are rendered, so the page knows to load them again.
+    version: u64,
+    tiles: Vec<[i32; 2]>,
+}
+
+/// A rendered tile of the map.
+struct Tile {
+    /// The pixels as RGB, row by row from the north.
+    pixels: Vec<u8>,
+    /// The [`MapData::version`] the tile was last rendered in, to evict the oldest tiles first.
+    version: u64,
+}
+
+/// What the map shows, shared with the threads rendering and serving it.
+#[derive(Default)]
+struct MapData {
+    tiles: FxHashMap<(i32, i32), Tile>,
+    version: u64,
+    players: Vec<PlayerMarker>,
+}
+
+/// The chunks to render, copied from the world so that the render thread doesn't need it.
+struct RenderJob {
+    colors: FxHashMap<BlockId, [u8; 3]>,
+    /// The chunk columns to draw.
+    columns: Vec<(i32, i32)>,
+    /// The loaded chunks of the columns to draw and of the columns north of them, which shade
+    /// their first row.
+    chunks: FxHashMap<IVec3, Chunk>,
+}
+
+/// A web map served over HTTP, see the [module documentation](self).
+pub struct WebMap {
+    pub address: SocketAddr,
+    data: Arc<Mutex<MapData>>,
+    jobs: Sender<RenderJob>,
+    /// Whether the render thread is still busy with the last job.
+    rendering: Arc<AtomicBool>,
+    /// The loaded chunks that were rendered, to find the chunks loaded since.
+    rendered: FxHashSet<IVec3>,
+    /// The chunk columns waiting to be rendered.
+    stale: FxHashSet<(i32, i32)>,
+    last_render: Option<Instant>,
+    last_players: Option<Instant>,
+}
+
+impl WebMap {
+    /// Starts serving the map on the given address.
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let data = Arc::new(Mutex::new(MapData::default()));
+        let shared = data.clone();
+        let connections = Arc::new(AtomicUsize::new(0));
+        thread::Builder::new()
+            .name("web map".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    if connections.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                        connections.fetch_sub(1, Ordering::AcqRel);
+                        log::debug!("Too many web map requests, closed a connection");
+                        continue;
+                    }
+                    let data = shared.clone();
+                    let connections = connections.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve(stream, &data) {
+                            log::debug!("Web map request failed: {}", e);
+                        }
+                        connections.fetch_sub(1, Ordering::AcqRel);
+                    });
+                }
+            })?;
+
+        let (jobs, job_rx) = mpsc::channel::<RenderJob>();
+        let rendering = Arc::new(AtomicBool::new(false));
+        let (shared, busy) = (data.clone(), rendering.clone());
+        thread::Builder::new()
+            .name("web map render".to_string())
+            .spawn(move || {
+                for job in job_rx {
+                    let start = Instant::now();
+                    let count = render(&job, &shared);
+                    log::debug!(
+                        "Rendered {} web map tiles in {:.1?}",
+                        count,
+                        start.elapsed()
+                    );
+                    busy.store(false, Ordering::Release);
+                }
+            })?;
+        log::info!("Serving the web map on http://{}", address);
+        Ok(Self {
+            address,
+            data,
+            jobs,
+            rendering,
+            rendered: FxHashSet::default(),
+            stale: FxHashSet::default(),
+            last_render: None,
+            last_players: None,
+        })
+    }
+
+    /// Looks up the players and hands the chunks changed or loaded since the last render to the
+    /// render thread when it is time to.
+    pub fn update(&mut self, server: &mut Server) {
+        if self
+            .last_players
+            .is_none_or(|last| last.elapsed() >= PLAYER_INTERVAL)
+        {
+            self.last_players = Some(Instant::now());
+            let players = server
+                .sessions
+                .values()
+                .filter_map(|session| {
+                    let position = server.world.entities.get(&session.entity_id)?.position();
+                    Some(PlayerMarker {
+                        name: session.username.clone(),
+                        x: position.x,
+                        y: position.y,
+                        z: position.z,
+                    })
+                })
+                .collect();
+            self.data.lock().unwrap().players = players;
+        }
+
+        if self
+            .last_render
+            .is_none_or(|last| last.elapsed() >= RENDER_INTERVAL)
+        {
+            self.last_render = Some(Instant::now());
+            let world = &mut server.world;
+            let mut changed = world.take_changed_chunks();
+            self.rendered.retain(|pos| world.chunks.contains_key(pos));
+            for pos in world.chunks.keys() {
+                if self.rendered.insert(*pos) {
+                    changed.insert(*pos);
+                }
+            }
+            // The column south of a chunk is shaded by it too
+            for pos in changed {
+                self.stale.insert((pos.x, pos.z));
+                self.stale.insert((pos.x, pos.z + 1));
+            }
+            if !self.stale.is_empty() && !self.rendering.load(Ordering::Acquire) {
+                let job = self.snapshot(world);
+                self.rendering.store(true, Ordering::Release);
+                if self.jobs.send(job).is_err() {
+                    log::error!("The web map render thread stopped");
+                }
+            }
+        }
+    }
+
+    /// Copies the chunks of the stale columns out of the world for rendering.
+    fn snapshot(&mut self, world: &mut World) -> RenderJob {
+        let columns: Vec<_> = self.stale.drain().collect();
+        let mut needed: FxHashSet<(i32, i32)> = FxHashSet::default();
+        for &(x, z) in &columns {
+            needed.insert((x, z));
+            needed.insert((x, z - 1));
+        }
+        let chunks = world
+            .chunks
+            .iter()
+            .filter(|(pos, _)| needed.contains(&(pos.x, pos.z)))
+            .map(|(pos, chunk)| (*pos, chunk.clone()))
+            .collect();
+        RenderJob {
+            colors: world.map_colors(),
+            columns,
+            chunks,
+        }
+    }
+}
+
+/// Renders the tiles of the columns in the job and evicts the oldest tiles beyond [`MAX_TILES`],
+/// returning how many tiles were rendered.
+fn render(job: &RenderJob, data: &Mutex<MapData>) -> usize {
+    let mut columns: FxHashMap<(i32, i32), Vec<i32>> = FxHashMap::default();
+    for pos in job.chunks.keys() {
+        columns.entry((pos.x, pos.z)).or_default().push(pos.y);
+    }
+    for ys in columns.values_mut() {
+        ys.sort_unstable_by(|a, b| b.cmp(a));
+    }
+    let mut tiles: FxHashMap<(i32, i32), Vec<(i32, i32)>> = FxHashMap::default();
+    for column in &job.columns {
+        if columns.contains_key(column) {
+            let tile = (
+                column.0.div_euclid(TILE_CHUNKS),
+                column.1.div_euclid(TILE_CHUNKS),
+            );
+            tiles.entry(tile).or_default().push(*column);
+        }
+    }
+
+    let version = data.lock().unwrap().version + 1;
+    for (tile, tile_columns) in &tiles {
+        // Render into a copy, so that the tiles can be served in the meantime
+        let mut pixels = data
+            .lock()
+            .unwrap()
+            .tiles
+            .get(tile)
+            .map(|tile| tile.pixels.clone())
+            .unwrap_or_else(|| EMPTY_COLOR.repeat(TILE_SIZE * TILE_SIZE));
+        render_tile(job, &columns, *tile, tile_columns, &mut pixels);
+        data.lock()
+            .unwrap()
+            .tiles
+            .insert(*tile, Tile { pixels, version });
+    }
+
+    let mut data = data.lock().unwrap();
+    data.version = version;
+    if data.tiles.len() > MAX_TILES {
+        let mut by_age: Vec<_> = data
+            .tiles
+            .iter()
+            .map(|(pos, tile)| (tile.version, *pos))
+            .collect();
+        by_age.sort_unstable();
+        let excess = data.tiles.len() - MAX_TILES;
+        for (_, pos) in by_age.into_iter().take(excess) {
+            data.tiles.remove(&pos);
+        }
+    }
+    tiles.len()
+}
+
+/// Returns the height and the block of the top visible block in the column at `x` and `z`
+/// within the chunk column, whose loaded chunks are given from the top down.
+fn surface(
+    chunks: &FxHashMap<IVec3, Chunk>,
+    column: (i32, i32),
+    ys: &[i32],
+    x: i32,
+    z: i32,
+) -> Option<(i32, BlockId)> {
+    let registry = block_registry();
+    for &cy in ys {
+        let chunk = &chunks[&IVec3::new(column.0, cy, column.1)];
+        for y in (0..CHUNK_SIZE as i32).rev() {
+            let Some((block, _)) = chunk.get_block(IVec3::new(x, y, z)) else {
+                continue;
+            };
+            if registry.get(block).is_some_and(|def| def.visible) {
+                return Some((cy * CHUNK_SIZE as i32 + y, block));
+            }
+        }
+    }
+    None
+}
+
+/// Draws the given chunk columns of a tile into its pixels, leaving the rest as they were.
+fn render_tile(
+    job: &RenderJob,
+    columns: &FxHashMap<(i32, i32), Vec<i32>>,
+    tile: (i32, i32),
+    tile_columns: &[(i32, i32)],
+    pixels: &mut [u8],
+) {
+    let size = CHUNK_SIZE as i32;
+    for &column in tile_columns {
+        let ys = &columns[&column];
+        // The row north of the chunk shades its first row, if that chunk is loaded too
+        let north = columns.get(&(column.0, column.1 - 1));
+        let mut heights = [[None; CHUNK_SIZE]; CHUNK_SIZE];
+        for z in 0..size {
+            for x in 0..size {
+                let Some((height, block)) = surface(&job.chunks, column, ys, x, z) else {
+                    continue;
+                };
+                heights[z as usize][x as usize] = Some(height);
+                let north_height = if z > 0 {
+                    heights[z as usize - 1][x as usize]
+                } else {
+                    north.and_then(|ys| {
+                        surface(&job.chunks, (column.0, column.1 - 1), ys, x, size - 1)
+                            .map(|(h, _)| h)
+                    })
+                };
+                let shade = match north_height {
+                    Some(north) if height > north => 1.0 + SLOPE_SHADE,
+                    Some(north) if height < north => 1.0 - SLOPE_SHADE,
+                    _ => 1.0,
+                };
+                let color = job.colors.get(&block).copied().unwrap_or(DEFAULT_COLOR);
+
+                let px = (column.0 - tile.0 * TILE_CHUNKS) * size + x;
+                let pz = (column.1 - tile.1 * TILE_CHUNKS) * size + z;
+                let index = (pz as usize * TILE_SIZE + px as usize) * 3;
+                for (i, channel) in color.into_iter().enumerate() {
+                    pixels[index + i] = (channel as f32 * shade).min(255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Encodes RGB pixels, row by row from the top, as a 24 bit BMP image, which every browser shows
+/// without needing an image encoder.
+fn encode_bmp(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let row_size = (width * 3).div_ceil(4) * 4;
+    let image_size = row_size * height;
+    let file_size = 54 + image_size;
+    let mut bmp = Vec::with_capacity(file_size);
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(file_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    // A negative height stores the rows from the top down
+    bmp.extend_from_slice(&(-(height as i32)).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&(image_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 16]);
+    for row in pixels.chunks(width * 3) {
+        for pixel in row.chunks(3) {
+            bmp.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+        }
+        bmp.resize(bmp.len() + row_size - width * 3, 0);
+    }
+    bmp
+}
+
+/// Answers one HTTP request for the map.
+fn serve(mut stream: TcpStream, data: &Mutex<MapData>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+    let mut request_line = String::new();
+    BufReader::new((&stream).take(MAX_HEADER_SIZE as u64)).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", b"Bad request");
+    };
+    if method != "GET" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Only GET is supported",
+        );
+    }
+    let path = target.split('?').next().unwrap_or_default();
+
+    let (content_type, body) = {
+        let data = data.lock().unwrap();
+        match path {
+            "/" | "/index.html" => ("text/html; charset=utf-8", MAP_PAGE.as_bytes().to_vec()),
+            "/players.json" => ("application/json", serde_json::to_vec(&data.players)?),
+            "/tiles.json" => {
+                let list = TileList {
+                    size: TILE_SIZE,
+                    version: data.version,
+                    tiles: data.tiles.keys().map(|&(x, z)| [x, z]).collect(),
+                };
+                ("application/json", serde_json::to_vec(&list)?)
+            }
+            _ => {
+                let tile = path
+                    .strip_prefix("/tiles/")
+                    .and_then(|tile| tile.strip_suffix(".bmp"))
+                    .and_then(|tile| tile.split_once('/'))
+                    .and_then(|(x, z)| Some((x.parse().ok()?, z.parse().ok()?)));
+                match tile.and_then(|tile| data.tiles.get(&tile)) {
+                    Some(tile) => ("image/bmp", encode_bmp(TILE_SIZE, TILE_SIZE, &tile.pixels)),
+                    None => {
+                        drop(data);
+                        return respond(&mut stream, "404 Not Found", "text/plain", b"Not found");
+                    }
+                }
+            }
+        }
+    };
+    respond(&mut stream, "200 OK", content_type, &body)
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
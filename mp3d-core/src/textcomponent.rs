@@ -1,10 +1,11 @@
 use glam::Vec4;
+use serde::{Deserialize, Serialize};
 
 pub fn sanitize(str: &str) -> String {
     str.replace("%", "%%")
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TextComponent {
     pub parts: Vec<TextComponentPart>,
 }
@@ -22,6 +23,28 @@ impl StyledChar {
 }
 
 impl TextComponent {
+    /// Returns the text without its colors.
+    pub fn plain_text(&self) -> String {
+        self.parts.iter().map(|part| part.text.as_str()).collect()
+    }
+
+    /// Removes what could make others run commands from text written by players: clicks, which
+    /// send a message, and suggestions other than a teleport to a block position like the ones
+    /// of the `[coords]` chat shortcut. Colors and hover texts stay.
+    pub fn without_commands(mut self) -> Self {
+        for part in &mut self.parts {
+            part.click = None;
+            if part
+                .suggest
+                .as_deref()
+                .is_some_and(|suggest| !is_teleport(suggest))
+            {
+                part.suggest = None;
+            }
+        }
+        self
+    }
+
     pub fn to_styled_chars(&self) -> Vec<StyledChar> {
         let mut styled_chars = Vec::new();
         for part in &self.parts {
@@ -48,6 +71,9 @@ impl TextComponent {
                         current_parts.push(TextComponentPart {
                             text: current_text.clone(),
                             color: part.color,
+                            click: part.click.clone(),
+                            suggest: part.suggest.clone(),
+                            hover: part.hover.clone(),
                         });
                         current_text.clear();
                     }
@@ -64,6 +90,9 @@ impl TextComponent {
                 current_parts.push(TextComponentPart {
                     text: current_text,
                     color: part.color,
+                    click: part.click.clone(),
+                    suggest: part.suggest.clone(),
+                    hover: part.hover.clone(),
                 });
             }
         }
@@ -80,7 +109,7 @@ impl TextComponent {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TextComponentColor {
     Basic(u8),
     Hex(Vec4),
@@ -108,10 +137,20 @@ impl From<TextComponentColor> for Vec4 {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TextComponentPart {
     pub text: String,
     pub color: TextComponentColor,
+    /// The chat message or command sent when the part is clicked in the chat.
+    #[serde(default)]
+    pub click: Option<String>,
+    /// The text put into the chat input when the part is clicked, for the player to change or
+    /// send.
+    #[serde(default)]
+    pub suggest: Option<String>,
+    /// The text shown when the mouse is over the part in the chat.
+    #[serde(default)]
+    pub hover: Option<String>,
 }
 
 impl std::str::FromStr for TextComponent {
@@ -122,12 +161,18 @@ impl std::str::FromStr for TextComponent {
         let mut chars = s.chars().peekable();
         let mut current_text = String::new();
         let mut current_color = TextComponentColor::None;
+        let mut current_click: Option<String> = None;
+        let mut current_suggest: Option<String> = None;
+        let mut current_hover: Option<String> = None;
         while let Some(c) = chars.next() {
             if c == '%' {
                 if !current_text.is_empty() {
                     parts.push(TextComponentPart {
                         text: current_text.clone(),
                         color: current_color,
+                        click: current_click.clone(),
+                        suggest: current_suggest.clone(),
+                        hover: current_hover.clone(),
                     });
                     current_text.clear();
                 }
@@ -176,9 +221,19 @@ impl std::str::FromStr for TextComponent {
                             a as f32 / 255.0,
                         ));
                     }
-                    // Reset color
+                    // Make the following text send a message when clicked, like `%c[/help 2]`
+                    Some('c') => current_click = Some(bracketed(&mut chars, "click")?),
+                    // Make the following text put a message into the chat input when clicked,
+                    // like `%s[/tp 1 2 3]`
+                    Some('s') => current_suggest = Some(bracketed(&mut chars, "suggest")?),
+                    // Show a text when the mouse is over the following text, like `%h[Oak Log]`
+                    Some('h') => current_hover = Some(bracketed(&mut chars, "hover")?),
+                    // Reset color, click and hover
                     Some('r') => {
                         current_color = TextComponentColor::None;
+                        current_click = None;
+                        current_suggest = None;
+                        current_hover = None;
                     }
                     // Just a normal '%' character
                     Some('%') => current_text.push('%'),
@@ -194,6 +249,9 @@ impl std::str::FromStr for TextComponent {
             parts.push(TextComponentPart {
                 text: current_text,
                 color: current_color,
+                click: current_click,
+                suggest: current_suggest,
+                hover: current_hover,
             });
         }
 
@@ -201,6 +259,29 @@ impl std::str::FromStr for TextComponent {
     }
 }
 
+/// Returns whether a message is a teleport to a block position, like `/tp 1 -2 3`.
+fn is_teleport(message: &str) -> bool {
+    let mut words = message.split(' ');
+    words.next() == Some("/tp")
+        && words.clone().count() == 3
+        && words.all(|word| word.parse::<i32>().is_ok())
+}
+
+/// Reads the `[...]` following a format code like `%c`, returning the text between the brackets.
+fn bracketed(chars: &mut impl Iterator<Item = char>, code: &str) -> Result<String, String> {
+    if chars.next() != Some('[') {
+        return Err(format!("Expected '[' after {} code", code));
+    }
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            Some(']') => return Ok(text),
+            Some(c) => text.push(c),
+            None => return Err(format!("Unterminated {} code", code)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
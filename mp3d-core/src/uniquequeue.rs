@@ -43,6 +43,11 @@ impl<T: std::hash::Hash + Eq + Copy> UniqueQueue<T> {
         self.queue.len()
     }
 
+    /// Reorders the queue so that items are popped in ascending order of the given key.
+    pub fn sort_by_key<K: Ord>(&mut self, f: impl FnMut(&T) -> K) {
+        self.queue.make_contiguous().sort_by_key(f);
+    }
+
     pub fn drain(&mut self, max: usize) -> Vec<T> {
         let mut items = Vec::new();
         for _ in 0..max {
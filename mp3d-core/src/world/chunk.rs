@@ -1,10 +1,12 @@
 //! A 16x16x16 chunk in a voxel engine.
 
 use glam::IVec3;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
 
 use crate::{
     block::{BlockId, BlockState, CollisionShape, block_registry, blocks},
     direction::Direction,
+    world::light::LightChannel,
 };
 
 pub const CHUNK_SIZE: usize = 16;
@@ -15,6 +17,9 @@ pub struct Chunk {
     block_palette: Vec<BlockId>,
     blocks: [u16; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
     block_states: [BlockState; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
+    /// The light levels of the blocks, the sky light in the high four bits and the block light in
+    /// the low four. See [`crate::world::light`] for how they are kept up to date.
+    light: [u8; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
 }
 
 impl Chunk {
@@ -24,6 +29,7 @@ impl Chunk {
             block_palette: vec![*blocks::AIR],
             blocks: [0; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
             block_states: [BlockState::none(); CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
+            light: [0; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
         }
     }
 
@@ -51,12 +57,55 @@ impl Chunk {
         self.block_states[index] = state;
     }
 
+    /// Gets the light level of the given kind at the given local position within the chunk.
+    pub fn get_light(&self, local_pos: IVec3, channel: LightChannel) -> u8 {
+        let index = local_pos.x as usize
+            + CHUNK_SIZE * (local_pos.y as usize + CHUNK_SIZE * local_pos.z as usize);
+        match channel {
+            LightChannel::Sky => self.light[index] >> 4,
+            LightChannel::Block => self.light[index] & 0x0F,
+        }
+    }
+
+    /// Sets the light level of the given kind at the given local position within the chunk.
+    pub fn set_light(&mut self, local_pos: IVec3, channel: LightChannel, level: u8) {
+        let index = local_pos.x as usize
+            + CHUNK_SIZE * (local_pos.y as usize + CHUNK_SIZE * local_pos.z as usize);
+        let light = &mut self.light[index];
+        *light = match channel {
+            LightChannel::Sky => (*light & 0x0F) | (level << 4),
+            LightChannel::Block => (*light & 0xF0) | (level & 0x0F),
+        };
+    }
+
+    /// Returns the local positions of the blocks that aren't a registered block, either because
+    /// their palette index is past the end of the palette or because the palette holds an unknown
+    /// block ID.
+    pub fn invalid_blocks(&self) -> Vec<IVec3> {
+        let mut invalid = Vec::new();
+        for (index, &palette_index) in self.blocks.iter().enumerate() {
+            let valid = self
+                .block_palette
+                .get(palette_index as usize)
+                .is_some_and(|&block| block_registry().get(block).is_some());
+            if !valid {
+                invalid.push(IVec3::new(
+                    (index % CHUNK_SIZE) as i32,
+                    (index / CHUNK_SIZE % CHUNK_SIZE) as i32,
+                    (index / (CHUNK_SIZE * CHUNK_SIZE)) as i32,
+                ));
+            }
+        }
+        invalid
+    }
+
     /// Random ticks N random blocks in the chunk.
     pub fn random_tick(
         &self,
         n: usize,
         chunks: &fxhash::FxHashMap<IVec3, Chunk>,
         chunk_pos: IVec3,
+        rng: &mut impl rand::Rng,
     ) -> Vec<(IVec3, BlockId, BlockState)> {
         let neighbors = [
             IVec3::new(-1, -1, -1), // Y -1 Z -1
@@ -147,9 +196,9 @@ impl Chunk {
 
         let mut updates = Vec::new();
         for _ in 0..n {
-            let x = rand::random::<u8>() as usize % CHUNK_SIZE;
-            let y = rand::random::<u8>() as usize % CHUNK_SIZE;
-            let z = rand::random::<u8>() as usize % CHUNK_SIZE;
+            let x = rng.random::<u8>() as usize % CHUNK_SIZE;
+            let y = rng.random::<u8>() as usize % CHUNK_SIZE;
+            let z = rng.random::<u8>() as usize % CHUNK_SIZE;
             let global_pos = IVec3::new(
                 chunk_pos.x * CHUNK_SIZE as i32 + x as i32,
                 chunk_pos.y * CHUNK_SIZE as i32 + y as i32,
@@ -219,3 +268,97 @@ impl Default for Chunk {
         Self::new()
     }
 }
+
+/// The serialized form of a [`Chunk`], kept small as chunks make up most of what is sent to
+/// clients. Light isn't part of it, it is worked out again from the blocks.
+#[derive(Serialize, Deserialize)]
+struct ChunkData {
+    block_palette: Vec<BlockId>,
+    /// The palette indices of the blocks, packed with as few bits as the palette needs, see
+    /// [`index_bits`].
+    blocks: Vec<u8>,
+    /// The block states in order as runs of blocks with the same state, given as the length of the
+    /// run and the state. Most blocks have no state, so there are usually only a few runs.
+    block_states: Vec<(u16, BlockState)>,
+}
+
+/// Returns the number of bits needed for every palette index of a palette with `len` blocks, which
+/// is 0 if there is only one block.
+fn index_bits(len: usize) -> usize {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as usize
+}
+
+impl Serialize for Chunk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bits = index_bits(self.block_palette.len());
+        let mut blocks = vec![0; (self.blocks.len() * bits).div_ceil(8)];
+        for (i, &index) in self.blocks.iter().enumerate() {
+            for bit in 0..bits {
+                if index >> bit & 1 == 1 {
+                    let pos = i * bits + bit;
+                    blocks[pos / 8] |= 1 << (pos % 8);
+                }
+            }
+        }
+
+        let mut block_states: Vec<(u16, BlockState)> = Vec::new();
+        for &state in &self.block_states {
+            match block_states.last_mut() {
+                Some((run, last)) if *last == state => *run += 1,
+                _ => block_states.push((1, state)),
+            }
+        }
+
+        ChunkData {
+            block_palette: self.block_palette.clone(),
+            blocks,
+            block_states,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Chunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = ChunkData::deserialize(deserializer)?;
+        const VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+        if data.block_palette.is_empty() {
+            return Err(D::Error::invalid_length(
+                0,
+                &"at least one block in the palette",
+            ));
+        }
+        let bits = index_bits(data.block_palette.len());
+        if bits > 16 || data.blocks.len() != (VOLUME * bits).div_ceil(8) {
+            return Err(D::Error::invalid_length(
+                data.blocks.len(),
+                &"the packed indices of 4096 blocks",
+            ));
+        }
+        let mut blocks = [0; VOLUME];
+        for (i, index) in blocks.iter_mut().enumerate() {
+            for bit in 0..bits {
+                let pos = i * bits + bit;
+                *index |= u16::from(data.blocks[pos / 8] >> (pos % 8) & 1) << bit;
+            }
+        }
+
+        let states_error = |len| D::Error::invalid_length(len, &"4096 block states");
+        let mut block_states = Vec::with_capacity(VOLUME);
+        for (run, state) in data.block_states {
+            let len = block_states.len() + run as usize;
+            if len > VOLUME {
+                return Err(states_error(len));
+            }
+            block_states.extend(std::iter::repeat_n(state, run as usize));
+        }
+        Ok(Chunk {
+            block_palette: data.block_palette,
+            blocks,
+            block_states: block_states
+                .try_into()
+                .map_err(|states: Vec<_>| states_error(states.len()))?,
+            light: [0; VOLUME],
+        })
+    }
+}
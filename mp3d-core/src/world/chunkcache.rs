@@ -0,0 +1,84 @@
+//! Keeping recently unloaded chunks in memory, so that going back to an area doesn't generate its
+//! chunks again.
+
+use fxhash::FxHashMap;
+use glam::IVec3;
+
+use crate::world::chunk::Chunk;
+
+/// The number of unloaded chunks kept by default.
+pub const DEFAULT_CHUNK_CACHE_SIZE: usize = 1024;
+
+/// A least recently used cache of unloaded chunks. Chunks are still changed through the world
+/// while they are cached, so a cached chunk is always the same as loading it again would be.
+pub struct ChunkCache {
+    chunks: FxHashMap<IVec3, (Chunk, u64)>,
+    capacity: usize,
+    /// Increased on every insert, to order the chunks by when they were unloaded.
+    clock: u64,
+}
+
+impl ChunkCache {
+    /// Creates a cache holding at most `capacity` chunks.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            chunks: FxHashMap::default(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    /// Adds an unloaded chunk, dropping the chunk unloaded the longest time ago if the cache is
+    /// full.
+    pub fn insert(&mut self, chunk_pos: IVec3, chunk: Chunk) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.chunks.len() >= self.capacity
+            && !self.chunks.contains_key(&chunk_pos)
+            && let Some(oldest) = self
+                .chunks
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(pos, _)| *pos)
+        {
+            self.chunks.remove(&oldest);
+        }
+        self.clock += 1;
+        self.chunks.insert(chunk_pos, (chunk, self.clock));
+    }
+
+    /// Changes how many chunks the cache holds, dropping the chunks unloaded the longest time ago
+    /// if it holds more.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.chunks.len() <= capacity {
+            return;
+        }
+        let mut by_age: Vec<_> = self
+            .chunks
+            .iter()
+            .map(|(pos, (_, used))| (*used, *pos))
+            .collect();
+        by_age.sort_unstable_by_key(|(used, _)| *used);
+        let excess = self.chunks.len() - capacity;
+        for (_, pos) in &by_age[..excess] {
+            self.chunks.remove(pos);
+        }
+    }
+
+    /// Takes a chunk out of the cache to load it again.
+    pub fn take(&mut self, chunk_pos: IVec3) -> Option<Chunk> {
+        self.chunks.remove(&chunk_pos).map(|(chunk, _)| chunk)
+    }
+
+    /// The number of cached chunks.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns whether no chunks are cached.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
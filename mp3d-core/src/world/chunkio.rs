@@ -0,0 +1,313 @@
+//! A worker thread writing chunks to region files in the background, so that saving chunks never
+//! blocks the thread ticking the world.
+//!
+//! The world sends snapshots of the changes to its chunks, which the worker encodes, compresses
+//! and writes. Snapshots of the same chunk that pile up while the worker is busy are coalesced,
+//! so only the newest one is written. Errors are sent back to be logged by the server.
+//!
+//! Chunks are stored in region files holding [`REGION_FILE_SIZE`] chunks along each axis, so that
+//! a big world doesn't turn into tens of thousands of tiny files. A region file is rewritten as a
+//! whole whenever a chunk in it is written, once per batch of writes.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread::JoinHandle,
+};
+
+use fxhash::FxHashMap;
+use glam::IVec3;
+
+use crate::{
+    block::{BlockId, BlockState},
+    saving::{Saveable, compression::Compression},
+    world::entitystore::{SavedEntity, write_entities},
+};
+
+/// The size of a region file, in chunks along each axis.
+pub const REGION_FILE_SIZE: i32 = 8;
+
+/// The changes done to a chunk, as saved in its region file.
+pub type ChunkChanges = FxHashMap<IVec3, (BlockId, BlockState)>;
+
+/// The encoded chunks of a region file by chunk position, see [`World::save`] for the format.
+///
+/// [`World::save`]: crate::world::World::save
+pub type RegionChunks = FxHashMap<IVec3, Vec<u8>>;
+
+/// Returns the path of the region file holding a chunk in the save at `save_path`.
+pub fn region_file_path(save_path: &Path, chunk_pos: IVec3) -> PathBuf {
+    let region = chunk_pos.div_euclid(IVec3::splat(REGION_FILE_SIZE));
+    save_path
+        .join("regions")
+        .join(format!("region_{}_{}_{}.bin", region.x, region.y, region.z))
+}
+
+/// Parses the position in a file name like "region_1_-2_3.bin", given the part before it.
+pub fn file_name_pos(file_name: &str, prefix: &str) -> Option<IVec3> {
+    let parts: Vec<i32> = file_name
+        .strip_prefix(prefix)?
+        .strip_suffix(".bin")?
+        .split('_')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [x, y, z] => Some(IVec3::new(x, y, z)),
+        _ => None,
+    }
+}
+
+/// Reads the chunks of a region file. A missing file holds no chunks.
+pub fn read_region(path: &Path, region: IVec3) -> std::io::Result<RegionChunks> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(RegionChunks::default()),
+        Err(e) => return Err(e),
+    };
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "Truncated region file");
+
+    let count = u16::from_le_bytes(data.get(0..2).ok_or_else(invalid)?.try_into().unwrap());
+    let mut entries = Vec::with_capacity(count as usize);
+    let mut cursor = 2;
+    for _ in 0..count {
+        let entry = data.get(cursor..cursor + 7).ok_or_else(invalid)?;
+        let local_pos = IVec3::new(entry[0] as i32, entry[1] as i32, entry[2] as i32);
+        let len = u32::from_le_bytes(entry[3..7].try_into().unwrap()) as usize;
+        entries.push((region * REGION_FILE_SIZE + local_pos, len));
+        cursor += 7;
+    }
+
+    let mut chunks = RegionChunks::default();
+    for (chunk_pos, len) in entries {
+        let end = cursor.checked_add(len).ok_or_else(invalid)?;
+        let chunk_data = data.get(cursor..end).ok_or_else(invalid)?;
+        chunks.insert(chunk_pos, chunk_data.to_vec());
+        cursor = end;
+    }
+    Ok(chunks)
+}
+
+/// Writes a region file next to its final path and moves it over, so that a crash while writing
+/// never leaves a half written region. Chunks are written in order of position.
+pub fn write_region(path: &Path, chunks: &RegionChunks) -> std::io::Result<()> {
+    let mut positions: Vec<_> = chunks.keys().copied().collect();
+    positions.sort_unstable_by_key(|pos| pos.to_array());
+
+    let mut data = (positions.len() as u16).to_le_bytes().to_vec();
+    for pos in &positions {
+        let local_pos = pos.rem_euclid(IVec3::splat(REGION_FILE_SIZE));
+        data.extend([local_pos.x as u8, local_pos.y as u8, local_pos.z as u8]);
+        data.extend((chunks[pos].len() as u32).to_le_bytes());
+    }
+    for pos in &positions {
+        data.extend(&chunks[pos]);
+    }
+
+    let temp_path = path.with_extension("bin.tmp");
+    std::fs::write(&temp_path, data)?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// A chunk to write to its region file.
+struct ChunkWrite {
+    path: PathBuf,
+    chunk_pos: IVec3,
+    changes: ChunkChanges,
+    entities: Vec<SavedEntity>,
+    compression: Compression,
+}
+
+enum Job {
+    Write(ChunkWrite),
+    /// Answered once every write queued before it is done.
+    Flush(mpsc::Sender<()>),
+}
+
+/// A region file that couldn't be written.
+#[derive(Debug)]
+pub struct ChunkWriteError {
+    pub path: PathBuf,
+    pub error: std::io::Error,
+}
+
+impl std::fmt::Display for ChunkWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to write {}: {}", self.path.display(), self.error)
+    }
+}
+
+/// Writes region files on a dedicated thread.
+pub struct ChunkWriter {
+    jobs: Option<mpsc::Sender<Job>>,
+    errors: mpsc::Receiver<ChunkWriteError>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ChunkWriter {
+    /// Starts the worker thread.
+    pub fn new() -> Self {
+        let (jobs, job_receiver) = mpsc::channel();
+        let (error_sender, errors) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("chunk-io".to_string())
+            .spawn(move || worker(&job_receiver, &error_sender))
+            .expect("Failed to spawn chunk IO worker");
+        Self {
+            jobs: Some(jobs),
+            errors,
+            worker: Some(worker),
+        }
+    }
+
+    /// Queues a chunk to be written to its region file in the "regions" folder of the save at
+    /// `save_path`.
+    pub fn write(
+        &self,
+        save_path: &Path,
+        chunk_pos: IVec3,
+        changes: ChunkChanges,
+        entities: Vec<SavedEntity>,
+        compression: Compression,
+    ) {
+        self.send(Job::Write(ChunkWrite {
+            path: region_file_path(save_path, chunk_pos),
+            chunk_pos,
+            changes,
+            entities,
+            compression,
+        }));
+    }
+
+    /// Blocks until every queued chunk is written.
+    pub fn flush(&self) {
+        let (sender, receiver) = mpsc::channel();
+        self.send(Job::Flush(sender));
+        let _ = receiver.recv();
+    }
+
+    /// Returns the errors that happened since the last call.
+    pub fn errors(&self) -> Vec<ChunkWriteError> {
+        self.errors.try_iter().collect()
+    }
+
+    fn send(&self, job: Job) {
+        let sent = self
+            .jobs
+            .as_ref()
+            .is_some_and(|jobs| jobs.send(job).is_ok());
+        if !sent {
+            log::error!("Chunk IO worker stopped, chunks can't be saved");
+        }
+    }
+}
+
+impl Default for ChunkWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ChunkWriter {
+    fn drop(&mut self) {
+        // Closing the channel makes the worker finish the queued writes and stop.
+        self.jobs.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        for error in self.errors.try_iter() {
+            log::error!("{}", error);
+        }
+    }
+}
+
+fn worker(jobs: &mpsc::Receiver<Job>, errors: &mpsc::Sender<ChunkWriteError>) {
+    while let Ok(first) = jobs.recv() {
+        // Take everything queued so far as one batch, keeping only the newest write per chunk
+        let mut regions: FxHashMap<PathBuf, FxHashMap<IVec3, ChunkWrite>> = FxHashMap::default();
+        let mut flushes = Vec::new();
+        let mut written = 0;
+        let mut coalesced = 0;
+        for job in std::iter::once(first).chain(jobs.try_iter()) {
+            match job {
+                Job::Write(write) => {
+                    let region = regions.entry(write.path.clone()).or_default();
+                    if region.insert(write.chunk_pos, write).is_some() {
+                        coalesced += 1;
+                    } else {
+                        written += 1;
+                    }
+                }
+                Job::Flush(done) => flushes.push(done),
+            }
+        }
+
+        if written > 0 {
+            log::debug!(
+                "Writing {} chunk(s) to {} region(s), {} coalesced",
+                written,
+                regions.len(),
+                coalesced
+            );
+        }
+        let mut created_dirs = Vec::new();
+        for (path, writes) in regions {
+            if let Some(dir) = path.parent()
+                && !created_dirs.iter().any(|created| created == dir)
+            {
+                if let Err(error) = std::fs::create_dir_all(dir) {
+                    let _ = errors.send(ChunkWriteError {
+                        path: dir.to_path_buf(),
+                        error,
+                    });
+                    continue;
+                }
+                created_dirs.push(dir.to_path_buf());
+            }
+            if let Err(error) = update_region(&path, writes) {
+                let _ = errors.send(ChunkWriteError { path, error });
+            }
+        }
+
+        for done in flushes {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// Replaces chunks in a region file. Chunks without changes or entities are removed from it, and
+/// the file is removed once it holds no chunks.
+fn update_region(path: &Path, writes: FxHashMap<IVec3, ChunkWrite>) -> std::io::Result<()> {
+    let region = writes
+        .keys()
+        .next()
+        .map(|pos| pos.div_euclid(IVec3::splat(REGION_FILE_SIZE)))
+        .unwrap_or_default();
+    let mut chunks = read_region(path, region)?;
+    for (chunk_pos, write) in writes {
+        if write.changes.is_empty() && write.entities.is_empty() {
+            chunks.remove(&chunk_pos);
+        } else {
+            chunks.insert(chunk_pos, encode_chunk(&write));
+        }
+    }
+    if chunks.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    write_region(path, &chunks)
+}
+
+/// Encodes and compresses a chunk. See [`World::save`](crate::world::World::save) for the format.
+fn encode_chunk(write: &ChunkWrite) -> Vec<u8> {
+    let mut data = (write.changes.len() as u16).to_le_bytes().to_vec();
+    for (local_pos, (block, state)) in &write.changes {
+        data.extend([local_pos.x as u8, local_pos.y as u8, local_pos.z as u8]);
+        data.extend((*block, *state).save());
+    }
+    write_entities(&mut data, &write.entities);
+    let mut chunk_data = vec![write.compression as u8];
+    chunk_data.extend(write.compression.compress(&data));
+    chunk_data
+}
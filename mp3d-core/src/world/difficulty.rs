@@ -0,0 +1,66 @@
+//! The difficulty of a world.
+
+/// How hard a world is. The difficulty scales the damage players take and decides whether hostile
+/// mobs spawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// No hostile mobs spawn and players take no damage.
+    Peaceful,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// All difficulties, from easiest to hardest.
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Peaceful,
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+    ];
+
+    /// Returns the next difficulty in [`Difficulty::ALL`], wrapping around at the end.
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Returns a human readable name for the difficulty.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "Peaceful",
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Returns the difficulty with the given name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the difficulty saved as the given byte.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// Scales damage dealt to a player according to the difficulty.
+    pub fn scale_damage(self, damage: f32) -> f32 {
+        damage
+            * match self {
+                Difficulty::Peaceful => 0.0,
+                Difficulty::Easy => 0.5,
+                Difficulty::Normal => 1.0,
+                Difficulty::Hard => 1.5,
+            }
+    }
+
+    /// Returns whether hostile mobs may spawn.
+    pub fn allows_hostile_spawns(self) -> bool {
+        self != Difficulty::Peaceful
+    }
+}
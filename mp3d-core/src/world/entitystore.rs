@@ -0,0 +1,88 @@
+//! Saving entities other than players with the chunk they are in, so that they are loaded and
+//! unloaded together with their part of the world.
+//!
+//! An entity belongs to the chunk containing its position, which is the bottom center of its
+//! hitbox. Chunk coordinates are rounded down, so an entity exactly on a chunk border belongs to
+//! the chunk on the positive side of it, and an entity straddling a border is only ever stored
+//! once no matter how much of it reaches into the neighbouring chunks.
+
+use glam::{IVec3, Vec3};
+
+use crate::{
+    entity::{Entity, entity_registry},
+    saving::{WorldLoadError, io::*},
+    world::chunk::CHUNK_SIZE,
+};
+
+/// An entity as saved in a chunk file, as the identifier of its type and its data.
+pub type SavedEntity = (&'static str, Vec<u8>);
+
+/// Returns the chunk an entity at the given position is saved in. Positions that aren't a number
+/// are saved in the chunk at the origin.
+pub fn entity_chunk(position: Vec3) -> IVec3 {
+    if !position.is_finite() {
+        return IVec3::ZERO;
+    }
+    (position / CHUNK_SIZE as f32).floor().as_ivec3()
+}
+
+/// Saves an entity with its type.
+pub fn save_entity(entity: &dyn Entity) -> SavedEntity {
+    (entity.ident(), entity.save())
+}
+
+/// Creates an entity from the identifier of its type and its saved data.
+pub fn load_entity(
+    ident: &str,
+    data: Vec<u8>,
+    version: u8,
+) -> Result<Box<dyn Entity>, WorldLoadError> {
+    let def = entity_registry()
+        .get_id(ident)
+        .and_then(|id| entity_registry().get(id))
+        .ok_or_else(|| {
+            WorldLoadError::InvalidSaveFormat(format!("Unknown entity type: '{}'", ident))
+        })?;
+    let load = def.load.ok_or_else(|| {
+        WorldLoadError::InvalidSaveFormat(format!(
+            "Entities of type '{}' aren't saved with chunks",
+            ident
+        ))
+    })?;
+    load(&mut data.into_iter(), version)
+}
+
+/// Reads the entities at the end of a chunk file.
+///
+/// - 4 bytes: number of entities (N)
+/// - N times
+///   - 1 byte: length of the entity type identifier (I)
+///   - I bytes: entity type identifier (UTF-8 string)
+///   - 4 bytes: length of entity data (M)
+///   - M bytes: entity data (format defined by each entity type)
+pub fn read_entities(
+    data: &mut impl Iterator<Item = u8>,
+    version: u8,
+) -> Result<Vec<Box<dyn Entity>>, WorldLoadError> {
+    let count = read_u32(data, "Entity count")?;
+    (0..count)
+        .map(|_| {
+            let ident_len = read_u8(data, "Entity type length")?;
+            let ident = read_string(data, ident_len as usize, "Entity type")?;
+            let len = read_u32(data, "Entity data length")?;
+            let entity_data = take_exact(data, len as usize, "Entity data")?;
+            load_entity(&ident, entity_data, version)
+        })
+        .collect()
+}
+
+/// Writes entities in the format read by [`read_entities`].
+pub fn write_entities(data: &mut Vec<u8>, entities: &[SavedEntity]) {
+    data.extend((entities.len() as u32).to_le_bytes());
+    for (ident, entity_data) in entities {
+        data.push(ident.len() as u8);
+        data.extend(ident.as_bytes());
+        data.extend((entity_data.len() as u32).to_le_bytes());
+        data.extend(entity_data);
+    }
+}
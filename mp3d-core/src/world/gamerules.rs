@@ -0,0 +1,104 @@
+//! Game rules, which toggle parts of the game logic for a single world.
+
+use crate::saving::{Saveable, WorldLoadError, io::*};
+
+/// The game rules of a world. These can be changed in game with the `/gamerule` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRules {
+    /// Whether players keep their inventory when they die.
+    pub keep_inventory: bool,
+    /// Whether mobs spawn naturally.
+    pub mob_spawning: bool,
+    /// Whether the time of day advances.
+    pub daylight_cycle: bool,
+    /// Whether players take damage when falling.
+    pub fall_damage: bool,
+    /// Whether explosions destroy blocks.
+    pub tnt_griefing: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            keep_inventory: false,
+            mob_spawning: true,
+            daylight_cycle: true,
+            fall_damage: true,
+            tnt_griefing: true,
+        }
+    }
+}
+
+impl GameRules {
+    /// The names of all game rules.
+    pub const NAMES: [&'static str; 5] = [
+        "keep_inventory",
+        "mob_spawning",
+        "daylight_cycle",
+        "fall_damage",
+        "tnt_griefing",
+    ];
+
+    /// Gets the value of the game rule with the given name, or `None` if there is no such rule.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "keep_inventory" => Some(self.keep_inventory),
+            "mob_spawning" => Some(self.mob_spawning),
+            "daylight_cycle" => Some(self.daylight_cycle),
+            "fall_damage" => Some(self.fall_damage),
+            "tnt_griefing" => Some(self.tnt_griefing),
+            _ => None,
+        }
+    }
+
+    /// Sets the value of the game rule with the given name.
+    pub fn set(&mut self, name: &str, value: bool) -> Result<(), String> {
+        let rule = match name {
+            "keep_inventory" => &mut self.keep_inventory,
+            "mob_spawning" => &mut self.mob_spawning,
+            "daylight_cycle" => &mut self.daylight_cycle,
+            "fall_damage" => &mut self.fall_damage,
+            "tnt_griefing" => &mut self.tnt_griefing,
+            _ => return Err(format!("Unknown game rule: '{}'", name)),
+        };
+        *rule = value;
+        Ok(())
+    }
+}
+
+/// Game rules are saved by name, so that rules can be added or removed without breaking older
+/// saves.
+///
+/// - 1 byte: number of rules (N)
+/// - N times
+///   - 1 byte: length of rule name (M)
+///   - M bytes: rule name (UTF-8 string)
+///   - 1 byte: value (u8, 0 or 1)
+impl Saveable for GameRules {
+    fn save(&self) -> Vec<u8> {
+        let mut data = vec![Self::NAMES.len() as u8];
+        for name in Self::NAMES {
+            data.push(name.len() as u8);
+            data.extend(name.as_bytes());
+            data.push(self.get(name).unwrap() as u8);
+        }
+        data
+    }
+
+    fn load<I: Iterator<Item = u8>>(data: &mut I, _version: u8) -> Result<Self, WorldLoadError>
+    where
+        Self: Sized,
+    {
+        let mut rules = GameRules::default();
+        let count = read_u8(data, "GameRules::count")?;
+        for _ in 0..count {
+            let name_len = read_u8(data, "GameRules::name_len")? as usize;
+            let name = read_string(data, name_len, "GameRules::name")?;
+            let value = read_u8(data, "GameRules::value")? != 0;
+            if rules.set(&name, value).is_err() {
+                log::warn!("Ignoring unknown game rule '{}' in save", name);
+            }
+        }
+        Ok(rules)
+    }
+}
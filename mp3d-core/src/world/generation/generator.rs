@@ -91,6 +91,67 @@ impl Generator {
         }
     }
 
+    /// Returns the height of the terrain at the given global X and Z, one above its top grass
+    /// block. Caves aren't taken into account, so the terrain is lower where one reaches the
+    /// surface.
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        match self {
+            // The terrain of V01 has the same shape, V02 only adds to it
+            Generator::V01 { noise, .. } => Self::get_height_v02(noise, x, z) as i32,
+            Generator::V02 { noise1, .. } => Self::get_height_v02(noise1, x, z) as i32,
+        }
+    }
+
+    /// Returns the structures placed in the column of chunks at the given chunk X and Z, without
+    /// generating any blocks. Structures are positioned on the terrain, so the column is the same
+    /// at every height.
+    pub fn structures_in_column(&self, chunk_x: i32, chunk_z: i32) -> Vec<Structure> {
+        match self {
+            Generator::V01 { .. } => Vec::new(),
+            Generator::V02 { noise1, noise2, .. } => Self::generate_structures_in_chunk_v02(
+                noise1,
+                noise2,
+                IVec3::new(chunk_x, 0, chunk_z),
+            ),
+        }
+    }
+
+    /// Finds the structure of the kind with the given [name](StructureData::name) that is
+    /// horizontally closest to `from`, searching columns of chunks in growing squares around it
+    /// up to `max_radius` chunks away.
+    pub fn locate_structure(&self, name: &str, from: IVec3, max_radius: i32) -> Option<Structure> {
+        let center = from.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let distance_squared = |structure: &Structure| {
+            let offset = structure.pos - from;
+            offset.x * offset.x + offset.z * offset.z
+        };
+        let mut nearest: Option<Structure> = None;
+        for radius in 0..=max_radius {
+            for x in -radius..=radius {
+                for z in -radius..=radius {
+                    if x.abs() != radius && z.abs() != radius {
+                        continue;
+                    }
+                    for structure in self.structures_in_column(center.x + x, center.z + z) {
+                        if structure.data.name() == name
+                            && nearest.is_none_or(|nearest| {
+                                distance_squared(&structure) < distance_squared(&nearest)
+                            })
+                        {
+                            nearest = Some(structure);
+                        }
+                    }
+                }
+            }
+            // Every column further out is at least this far away
+            let searched = radius * CHUNK_SIZE as i32;
+            if nearest.is_some_and(|nearest| distance_squared(&nearest) <= searched * searched) {
+                break;
+            }
+        }
+        nearest
+    }
+
     fn apply_structures_to_chunk(chunk: &mut Chunk, chunk_pos: IVec3, structures: Vec<Structure>) {
         for structure in structures {
             match structure.data {
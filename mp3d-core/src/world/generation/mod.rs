@@ -1,6 +1,11 @@
 pub mod generator;
+pub mod pool;
 pub mod structure;
 mod v01;
 mod v02;
 
 pub use generator::Generator;
+pub use pool::GenerationPool;
+
+/// The lowest Y level that terrain is generated at, everything below is empty.
+pub const WORLD_FLOOR: i32 = -48;
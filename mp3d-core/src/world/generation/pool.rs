@@ -0,0 +1,181 @@
+//! A pool of worker threads generating chunks in the background.
+//!
+//! Chunks are queued with a priority (usually the squared distance to the closest player) and
+//! generated nearest first. The queue is bounded, so when it is full the farthest chunks are
+//! dropped, and queued chunks can be cancelled before a worker picks them up.
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Condvar, Mutex, mpsc},
+    thread::JoinHandle,
+};
+
+use fxhash::{FxHashMap, FxHashSet};
+use glam::IVec3;
+
+use crate::world::{chunk::Chunk, generation::Generator};
+
+/// The maximum number of chunks waiting to be generated.
+pub const MAX_QUEUED_CHUNKS: usize = 1024;
+
+/// Chunks waiting for a worker, ordered by priority and then position so that both the nearest
+/// and the farthest chunk can be taken cheaply.
+#[derive(Default)]
+struct Queue {
+    ordered: BTreeSet<(u32, [i32; 3])>,
+    priorities: FxHashMap<IVec3, u32>,
+    shutdown: bool,
+}
+
+impl Queue {
+    fn remove(&mut self, chunk_pos: IVec3) -> bool {
+        match self.priorities.remove(&chunk_pos) {
+            Some(priority) => self.ordered.remove(&(priority, chunk_pos.to_array())),
+            None => false,
+        }
+    }
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+}
+
+/// Generates chunks on a set of worker threads sized to the available cores.
+pub struct GenerationPool {
+    shared: Arc<Shared>,
+    results: mpsc::Receiver<(IVec3, Chunk)>,
+    workers: Vec<JoinHandle<()>>,
+    /// Chunks that are either queued or currently being generated.
+    pending: FxHashSet<IVec3>,
+}
+
+impl GenerationPool {
+    /// Creates a new pool generating chunks with the given generator.
+    pub fn new(generator: Arc<Generator>) -> Self {
+        // Leave one core for the thread ticking the world.
+        let worker_count = std::thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1))
+            .unwrap_or(1)
+            .max(1);
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            available: Condvar::new(),
+        });
+        let (sender, results) = mpsc::channel();
+
+        let workers = (0..worker_count)
+            .map(|i| {
+                let shared = shared.clone();
+                let generator = generator.clone();
+                let sender = sender.clone();
+                std::thread::Builder::new()
+                    .name(format!("chunk-gen-{}", i))
+                    .spawn(move || worker(&shared, &generator, &sender))
+                    .expect("Failed to spawn chunk generation worker")
+            })
+            .collect();
+        log::info!("Started {} chunk generation worker(s)", worker_count);
+
+        Self {
+            shared,
+            results,
+            workers,
+            pending: FxHashSet::default(),
+        }
+    }
+
+    /// Queues a chunk for generation, lower priorities are generated first. Requesting a chunk
+    /// that is already queued updates its priority.
+    pub fn request(&mut self, chunk_pos: IVec3, priority: u32) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.remove(chunk_pos) {
+            queue.ordered.insert((priority, chunk_pos.to_array()));
+            queue.priorities.insert(chunk_pos, priority);
+            return;
+        }
+        if self.pending.contains(&chunk_pos) {
+            // Already being generated
+            return;
+        }
+
+        if queue.ordered.len() >= MAX_QUEUED_CHUNKS {
+            match queue.ordered.last() {
+                Some(&(farthest, pos)) if farthest > priority => {
+                    queue.remove(IVec3::from_array(pos));
+                    self.pending.remove(&IVec3::from_array(pos));
+                }
+                _ => return,
+            }
+        }
+
+        queue.ordered.insert((priority, chunk_pos.to_array()));
+        queue.priorities.insert(chunk_pos, priority);
+        self.pending.insert(chunk_pos);
+        self.shared.available.notify_one();
+    }
+
+    /// Returns whether the chunk is queued or being generated.
+    pub fn is_pending(&self, chunk_pos: IVec3) -> bool {
+        self.pending.contains(&chunk_pos)
+    }
+
+    /// Removes all queued chunks for which `keep` returns false. Chunks that a worker already
+    /// started generating are not affected.
+    pub fn cancel_unless(&mut self, mut keep: impl FnMut(IVec3) -> bool) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let cancelled = queue
+            .priorities
+            .keys()
+            .copied()
+            .filter(|pos| !keep(*pos))
+            .collect::<Vec<_>>();
+        for pos in cancelled {
+            queue.remove(pos);
+            self.pending.remove(&pos);
+        }
+    }
+
+    /// Returns all chunks that finished generating since the last call.
+    pub fn poll(&mut self) -> Vec<(IVec3, Chunk)> {
+        let finished = self.results.try_iter().collect::<Vec<_>>();
+        for (pos, _) in &finished {
+            self.pending.remove(pos);
+        }
+        finished
+    }
+}
+
+impl Drop for GenerationPool {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().shutdown = true;
+        self.shared.available.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker(shared: &Shared, generator: &Generator, sender: &mpsc::Sender<(IVec3, Chunk)>) {
+    loop {
+        let chunk_pos = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if queue.shutdown {
+                    return;
+                }
+                if let Some((_, pos)) = queue.ordered.pop_first() {
+                    let pos = IVec3::from_array(pos);
+                    queue.priorities.remove(&pos);
+                    break pos;
+                }
+                queue = shared.available.wait(queue).unwrap();
+            }
+        };
+
+        let chunk = generator.generate_chunk(chunk_pos);
+        if sender.send((chunk_pos, chunk)).is_err() {
+            return;
+        }
+    }
+}
@@ -5,6 +5,24 @@ pub enum StructureData {
     Tree { trunk_height: u8 },
 }
 
+impl StructureData {
+    /// The names of every kind of structure, as used by /locate.
+    pub const NAMES: &[&str] = &["tree"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StructureData::Tree { .. } => "tree",
+        }
+    }
+
+    /// Returns how far the structure reaches above its position.
+    pub fn height(self) -> i32 {
+        match self {
+            StructureData::Tree { trunk_height } => trunk_height as i32 + 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Structure {
     pub data: StructureData,
@@ -29,7 +29,7 @@ impl Generator {
                     let global_y = chunk_pos.y * CHUNK_SIZE as i32 + y as i32;
                     let local = IVec3::new(x as i32, y as i32, z as i32);
 
-                    if global_y < -48 {
+                    if global_y < super::WORLD_FLOOR {
                         continue;
                     }
                     let is_cave = noise.get_noise_3d(
@@ -51,7 +51,7 @@ impl Generator {
                     let global_y = chunk_pos.y * CHUNK_SIZE as i32 + y as i32;
                     let local = IVec3::new(x as i32, y as i32, z as i32);
 
-                    if global_y < -48 {
+                    if global_y < super::WORLD_FLOOR {
                         continue;
                     }
                     let is_cave = noise1.get_noise_3d(
@@ -109,7 +109,7 @@ impl Generator {
         structures
     }
 
-    fn generate_structures_in_chunk_v02(
+    pub(super) fn generate_structures_in_chunk_v02(
         noise1: &fastnoise_lite::FastNoiseLite,
         noise2: &fastnoise_lite::FastNoiseLite,
         chunk_pos: IVec3,
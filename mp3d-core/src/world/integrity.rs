@@ -0,0 +1,177 @@
+//! Checking the loaded part of a world for data that can't be right, like blocks that aren't
+//! registered, and repairing it. This catches broken saves and bugs in code changing the world.
+
+use glam::{IVec3, Vec3};
+
+use crate::{
+    block::{BlockId, BlockState, block_registry, blocks, container},
+    protocol::BlockUpdateKind,
+    world::{World, chunk::CHUNK_SIZE},
+};
+
+/// The farthest an entity can be from the origin along any axis.
+pub const MAX_ENTITY_COORDINATE: f32 = 1_000_000.0;
+
+/// Where entities that are out of range are moved to when repairing.
+const RESCUE_POSITION: Vec3 = Vec3::new(0.0, 25.0, 0.0);
+
+/// Something wrong found in a world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    /// A block that isn't registered. Repairing replaces it with air.
+    InvalidBlock { position: IVec3 },
+    /// A block whose state doesn't belong to its block. Repairing resets it to the default state
+    /// of the block.
+    InvalidState {
+        position: IVec3,
+        block: BlockId,
+        state: BlockState,
+    },
+    /// A container at a position whose block doesn't store items. Repairing removes it and its
+    /// contents.
+    OrphanedContainer { position: IVec3 },
+    /// An entity at a position that isn't a number or is farther than
+    /// [`MAX_ENTITY_COORDINATE`]. Repairing moves it back to the spawn.
+    EntityOutOfRange { entity_id: u64, position: Vec3 },
+}
+
+impl Problem {
+    /// Describes the problem for showing to players.
+    pub fn describe(&self) -> String {
+        match self {
+            Problem::InvalidBlock { position } => format!(
+                "Unknown block at {} {} {}",
+                position.x, position.y, position.z
+            ),
+            Problem::InvalidState {
+                position,
+                block,
+                state,
+            } => format!(
+                "Block '{}' at {} {} {} has invalid state {:#010x}",
+                block_registry().get(*block).unwrap().ident,
+                position.x,
+                position.y,
+                position.z,
+                state.bits()
+            ),
+            Problem::OrphanedContainer { position } => format!(
+                "Container without a chest at {} {} {}",
+                position.x, position.y, position.z
+            ),
+            Problem::EntityOutOfRange {
+                entity_id,
+                position,
+            } => format!(
+                "Entity {} is out of range at {:.1} {:.1} {:.1}",
+                entity_id, position.x, position.y, position.z
+            ),
+        }
+    }
+}
+
+/// Returns whether a block state is valid for a block: it has the state type of the block and
+/// data that type allows.
+fn valid_state(block: BlockId, state: BlockState) -> bool {
+    let def = block_registry().get(block).unwrap();
+    state.state_type() == def.state_type
+        && BlockState::possible_data_values(def.state_type)
+            .is_none_or(|values| values.is_empty() || values.contains(&state.data()))
+}
+
+impl World {
+    /// Checks the loaded chunks, the containers and the entities for problems, repairing them if
+    /// `repair` is true. Returns the problems found, which are all fixed if repairing.
+    pub fn verify(&mut self, repair: bool) -> Vec<Problem> {
+        let mut problems = Vec::new();
+
+        let mut chunk_positions: Vec<_> = self.chunks.keys().copied().collect();
+        chunk_positions.sort_unstable_by_key(|pos| pos.to_array());
+        for chunk_pos in chunk_positions {
+            let chunk = &self.chunks[&chunk_pos];
+            let origin = chunk_pos * CHUNK_SIZE as i32;
+            for local_pos in chunk.invalid_blocks() {
+                problems.push(Problem::InvalidBlock {
+                    position: origin + local_pos,
+                });
+            }
+            for x in 0..CHUNK_SIZE as i32 {
+                for y in 0..CHUNK_SIZE as i32 {
+                    for z in 0..CHUNK_SIZE as i32 {
+                        let local_pos = IVec3::new(x, y, z);
+                        if let Some((block, &state)) = chunk.get_block(local_pos)
+                            && block_registry().get(block).is_some()
+                            && !valid_state(block, state)
+                        {
+                            problems.push(Problem::InvalidState {
+                                position: origin + local_pos,
+                                block,
+                                state,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut container_positions: Vec<_> = self.containers.keys().copied().collect();
+        container_positions.sort_unstable_by_key(|pos| pos.to_array());
+        for position in container_positions {
+            // Containers in chunks that aren't loaded can't be checked
+            if let Some((block, _)) = self.get_block_at(position)
+                && !container::holds_container(block)
+            {
+                problems.push(Problem::OrphanedContainer { position });
+            }
+        }
+
+        let mut entity_ids: Vec<_> = self.entities.keys().copied().collect();
+        entity_ids.sort_unstable();
+        for entity_id in entity_ids {
+            let position = self.entities[&entity_id].position();
+            if !position.is_finite() || position.abs().max_element() > MAX_ENTITY_COORDINATE {
+                problems.push(Problem::EntityOutOfRange {
+                    entity_id,
+                    position,
+                });
+            }
+        }
+
+        if repair {
+            for problem in &problems {
+                self.repair(problem);
+            }
+        }
+        problems
+    }
+
+    /// Fixes a problem found by [`World::verify`].
+    fn repair(&mut self, problem: &Problem) {
+        match *problem {
+            Problem::InvalidBlock { position } => {
+                self.normal_set_block_at(
+                    position,
+                    *blocks::AIR,
+                    BlockState::none(),
+                    BlockUpdateKind::Correction,
+                );
+            }
+            Problem::InvalidState {
+                position, block, ..
+            } => {
+                let def = block_registry().get(block).unwrap();
+                let state = BlockState::default_state(def.state_type).unwrap_or(BlockState::none());
+                self.normal_set_block_at(position, block, state, BlockUpdateKind::Correction);
+            }
+            Problem::OrphanedContainer { position } => {
+                self.containers.remove(&position);
+            }
+            Problem::EntityOutOfRange { entity_id, .. } => {
+                if let Some(entity) = self.entities.get_mut(&entity_id) {
+                    *entity.position_mut() = RESCUE_POSITION;
+                    self.load_around(RESCUE_POSITION.as_ivec3());
+                }
+            }
+        }
+    }
+}
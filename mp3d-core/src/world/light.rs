@@ -0,0 +1,293 @@
+//! Light levels of the blocks, from the sky and from blocks giving off light.
+//!
+//! Every block has a sky light and a block light level from 0 to [`MAX_LIGHT`]. Sunlight falls
+//! straight down without getting dimmer until it hits something. From there both kinds of light
+//! spread to the neighbouring blocks, one level dimmer with every block, or more through blocks
+//! with a [`light_opacity`](crate::block::BlockDef::light_opacity).
+//!
+//! Light is kept up to date with breadth first flood fills. When a block changes, the light that
+//! came through it is taken away first, then the light around the hole is spread back in. Only
+//! loaded chunks are lit, and the top of a chunk with nothing loaded above it is taken to be open
+//! to the sky until the chunk above arrives. Light isn't saved or sent over the network, it is
+//! worked out from the blocks wherever it's needed.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::BuildHasher,
+};
+
+use fxhash::FxHashSet;
+use glam::IVec3;
+
+use crate::{
+    block::{BlockDef, block_registry},
+    direction::Direction,
+    world::chunk::{CHUNK_SIZE, Chunk},
+};
+
+/// The brightest light level, that of direct sunlight.
+pub const MAX_LIGHT: u8 = 15;
+
+/// The two kinds of light a block has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightChannel {
+    /// Light from the sky.
+    Sky,
+    /// Light given off by blocks.
+    Block,
+}
+
+impl LightChannel {
+    pub const ALL: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+}
+
+/// The loaded chunks light is spread through.
+pub trait LightWorld {
+    fn chunk(&self, chunk_pos: IVec3) -> Option<&Chunk>;
+    fn chunk_mut(&mut self, chunk_pos: IVec3) -> Option<&mut Chunk>;
+}
+
+impl<S: BuildHasher> LightWorld for HashMap<IVec3, Chunk, S> {
+    fn chunk(&self, chunk_pos: IVec3) -> Option<&Chunk> {
+        self.get(&chunk_pos)
+    }
+
+    fn chunk_mut(&mut self, chunk_pos: IVec3) -> Option<&mut Chunk> {
+        self.get_mut(&chunk_pos)
+    }
+}
+
+/// Lights a chunk that was just loaded, and fixes up the light of the loaded chunks around it.
+///
+/// Returns the chunks with a block within one block of a changed light level, which are the
+/// chunks whose meshes may have to be rebuilt.
+pub fn light_chunk(world: &mut impl LightWorld, chunk_pos: IVec3) -> FxHashSet<IVec3> {
+    let mut lighter = Lighter {
+        world,
+        changed: FxHashSet::default(),
+    };
+    let origin = chunk_pos * CHUNK_SIZE as i32;
+    for channel in LightChannel::ALL {
+        // The top of the chunk below was lit as if it was open to the sky
+        let below = chunk_pos - IVec3::Y;
+        let mut queue = if channel == LightChannel::Sky && lighter.world.chunk(below).is_some() {
+            let top: Vec<_> = layer(below, Direction::Up).collect();
+            lighter.unlight(&top, channel)
+        } else {
+            VecDeque::new()
+        };
+
+        for x in 0..CHUNK_SIZE as i32 {
+            for y in 0..CHUNK_SIZE as i32 {
+                for z in 0..CHUNK_SIZE as i32 {
+                    let pos = origin + IVec3::new(x, y, z);
+                    let level = lighter.source(pos, channel);
+                    if level > 0 {
+                        lighter.set_light(pos, channel, level);
+                        queue.push_back(pos);
+                    }
+                }
+            }
+        }
+        // Let the light of the neighbouring chunks in
+        for dir in Direction::ALL {
+            let neighbor = chunk_pos + dir;
+            if lighter.world.chunk(neighbor).is_some() {
+                queue.extend(layer(neighbor, dir.opposite()));
+            }
+        }
+
+        lighter.spread(queue, channel);
+    }
+    lighter.changed
+}
+
+/// Updates the light around a block that changed, given its world position.
+///
+/// Returns the chunks whose meshes may have to be rebuilt, like [`light_chunk`].
+pub fn relight_block(world: &mut impl LightWorld, world_pos: IVec3) -> FxHashSet<IVec3> {
+    let mut lighter = Lighter {
+        world,
+        changed: FxHashSet::default(),
+    };
+    for channel in LightChannel::ALL {
+        let queue = lighter.unlight(&[world_pos], channel);
+        lighter.spread(queue, channel);
+    }
+    lighter.changed
+}
+
+/// Returns the world positions of the blocks on one side of a chunk.
+pub fn layer(chunk_pos: IVec3, side: Direction) -> impl Iterator<Item = IVec3> {
+    let max = CHUNK_SIZE as i32 - 1;
+    let origin = chunk_pos * CHUNK_SIZE as i32;
+    (0..=max).flat_map(move |a| {
+        (0..=max).map(move |b| {
+            let local = match side {
+                Direction::North => IVec3::new(a, b, 0),
+                Direction::South => IVec3::new(a, b, max),
+                Direction::West => IVec3::new(0, a, b),
+                Direction::East => IVec3::new(max, a, b),
+                Direction::Down => IVec3::new(a, 0, b),
+                Direction::Up => IVec3::new(a, max, b),
+            };
+            origin + local
+        })
+    })
+}
+
+/// Returns the light level a block gets from a neighbour with the given level in direction `dir`
+/// from it, if the block has the given opacity.
+fn spread_level(level: u8, channel: LightChannel, dir: Direction, opacity: u8) -> u8 {
+    if channel == LightChannel::Sky && dir == Direction::Down && level == MAX_LIGHT && opacity == 0
+    {
+        MAX_LIGHT
+    } else {
+        level.saturating_sub(opacity.max(1))
+    }
+}
+
+struct Lighter<'a, W> {
+    world: &'a mut W,
+    changed: FxHashSet<IVec3>,
+}
+
+impl<W: LightWorld> Lighter<'_, W> {
+    fn block(&self, world_pos: IVec3) -> Option<&'static BlockDef> {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let (block, _) = self.world.chunk(chunk_pos)?.get_block(local_pos)?;
+        block_registry().get(block)
+    }
+
+    /// Gets the light level at a world position, if its chunk is loaded.
+    fn light(&self, world_pos: IVec3, channel: LightChannel) -> Option<u8> {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        Some(self.world.chunk(chunk_pos)?.get_light(local_pos, channel))
+    }
+
+    fn set_light(&mut self, world_pos: IVec3, channel: LightChannel, level: u8) {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let Some(chunk) = self.world.chunk_mut(chunk_pos) else {
+            return;
+        };
+        if chunk.get_light(local_pos, channel) == level {
+            return;
+        }
+        chunk.set_light(local_pos, channel, level);
+
+        // Blocks on the edge of a chunk are also meshed with the chunks next to them
+        let axis_offsets = |local: i32| -> &'static [i32] {
+            if local == 0 {
+                &[0, -1]
+            } else if local == CHUNK_SIZE as i32 - 1 {
+                &[0, 1]
+            } else {
+                &[0]
+            }
+        };
+        for &dx in axis_offsets(local_pos.x) {
+            for &dy in axis_offsets(local_pos.y) {
+                for &dz in axis_offsets(local_pos.z) {
+                    self.changed.insert(chunk_pos + IVec3::new(dx, dy, dz));
+                }
+            }
+        }
+    }
+
+    /// Returns the light a block gives off by itself: its emission for block light, and for sky
+    /// light whether it is at the top of the loaded world.
+    fn source(&self, world_pos: IVec3, channel: LightChannel) -> u8 {
+        let Some(block) = self.block(world_pos) else {
+            return 0;
+        };
+        match channel {
+            LightChannel::Block => block.light_emission,
+            LightChannel::Sky => {
+                let above = (world_pos + Direction::Up).div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+                if self.world.chunk(above).is_none() {
+                    MAX_LIGHT.saturating_sub(block.light_opacity)
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Spreads light out from the queued blocks until it fades out.
+    fn spread(&mut self, mut queue: VecDeque<IVec3>, channel: LightChannel) {
+        while let Some(pos) = queue.pop_front() {
+            let Some(level) = self.light(pos, channel) else {
+                continue;
+            };
+            if level <= 1 {
+                continue;
+            }
+            for dir in Direction::ALL {
+                let neighbor = pos + dir;
+                let (Some(current), Some(block)) =
+                    (self.light(neighbor, channel), self.block(neighbor))
+                else {
+                    continue;
+                };
+                let new = spread_level(level, channel, dir, block.light_opacity);
+                if new > current {
+                    self.set_light(neighbor, channel, new);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    /// Takes away the light at the given blocks and all the light that came from it. Returns the
+    /// blocks around the darkened area that still have light to spread back in, along with the
+    /// light sources that were darkened.
+    fn unlight(&mut self, positions: &[IVec3], channel: LightChannel) -> VecDeque<IVec3> {
+        let mut removed = VecDeque::new();
+        let mut relit = VecDeque::new();
+        for &pos in positions {
+            let Some(level) = self.light(pos, channel) else {
+                continue;
+            };
+            self.set_light(pos, channel, 0);
+            removed.push_back((pos, level));
+            let source = self.source(pos, channel);
+            if source > 0 {
+                self.set_light(pos, channel, source);
+                relit.push_back(pos);
+            }
+            // Light around a block that became see-through has to come back in through it
+            relit.extend(Direction::ALL.map(|dir| pos + dir));
+        }
+
+        while let Some((pos, level)) = removed.pop_front() {
+            for dir in Direction::ALL {
+                let neighbor = pos + dir;
+                let Some(current) = self.light(neighbor, channel) else {
+                    continue;
+                };
+                if current == 0 {
+                    continue;
+                }
+                let falling_sunlight = channel == LightChannel::Sky
+                    && dir == Direction::Down
+                    && level == MAX_LIGHT
+                    && current == MAX_LIGHT;
+                if current < level || falling_sunlight {
+                    self.set_light(neighbor, channel, 0);
+                    removed.push_back((neighbor, current));
+                    let source = self.source(neighbor, channel);
+                    if source > 0 {
+                        self.set_light(neighbor, channel, source);
+                        relit.push_back(neighbor);
+                    }
+                } else {
+                    relit.push_back(neighbor);
+                }
+            }
+        }
+        relit
+    }
+}
@@ -5,35 +5,94 @@
 //! and accessing chunks, as well as handling world generation and updates.
 
 pub mod chunk;
+pub mod chunkcache;
+pub mod chunkio;
+pub mod difficulty;
+pub mod entitystore;
+pub mod gamerules;
 pub mod generation;
+pub mod integrity;
+pub mod light;
+pub mod parallel;
+pub mod sky;
+pub mod spawning;
+pub mod tickbudget;
+pub mod tickcontrol;
+
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
 
-use std::collections::HashMap;
-
-use fxhash::{FxHashMap, hash64};
+use fxhash::{FxHashMap, FxHashSet, FxHasher64, hash64};
 use glam::{IVec3, Vec3};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::{
-    block::{BlockId, BlockState, block_registry, blocks},
+    block::{BlockId, BlockState, block_registry, blocks, container::Container},
     datapack::GameData,
     direction::Direction,
-    entity::{Entity, EntityType, PlayerEntity},
+    entity::{Entity, PlayerEntity, entities},
     item::{item_registry, items},
-    physics::CollisionWorld,
-    protocol::{BlockUpdate, BlockUpdateKind},
-    saving::{GENERATOR_VERSION, SAVE_VERSION, Saveable, WorldLoadError, io::*},
+    physics::{self, CollisionWorld},
+    protocol::{BlockUpdate, BlockUpdateKind, DeathCause},
+    saving::{
+        GENERATOR_VERSION, SAVE_VERSION, Saveable, WorldLoadError, compression::Compression, io::*,
+        migration,
+    },
     uniquequeue::UniqueQueue,
     world::{
         chunk::{CHUNK_SIZE, Chunk},
-        generation::Generator,
+        chunkcache::{ChunkCache, DEFAULT_CHUNK_CACHE_SIZE},
+        chunkio::{ChunkWriteError, ChunkWriter},
+        difficulty::Difficulty,
+        entitystore::{SavedEntity, entity_chunk},
+        gamerules::GameRules,
+        generation::{GenerationPool, Generator},
+        parallel::WorldView,
+        tickbudget::TickBudget,
+        tickcontrol::TickControl,
     },
 };
 
+/// The length of a day in ticks. A day starts at sunrise, the sun sets halfway through it.
+pub const DAY_LENGTH: u64 = 24000;
+
 /// A world consisting of multiple chunks. Each chunk contains a 16x16x16 grid of blocks.
 pub struct World {
     pub chunks: FxHashMap<IVec3, Chunk>,
     pub entities: FxHashMap<u64, Box<dyn Entity>>,
-    pub generator: Generator,
+    pub generator: Arc<Generator>,
     pub time: u64,
+    /// The entity IDs of the players lying in a bed, waiting for the night to be skipped.
+    pub sleeping: FxHashSet<u64>,
+    pub game_rules: GameRules,
+    pub difficulty: Difficulty,
+    /// The version of the game that last saved the world, [`None`] for new worlds and saves from
+    /// before it was recorded.
+    pub saved_with: Option<String>,
+    /// How chunk files are compressed when the world is saved.
+    pub chunk_compression: Compression,
+    /// How often the world ticks, changed with the /tick command for debugging.
+    pub tick_control: TickControl,
+    /// The work carried over between ticks and the measured cost of every chunk.
+    pub tick_budget: TickBudget,
+    /// The containers of the blocks storing items, keyed by world position.
+    pub containers: FxHashMap<IVec3, Container>,
+    /// The containers players opened, as the entity ID of the player and the position of the
+    /// container, waiting for the server to send them to the players.
+    pub container_opens: Vec<(u64, IVec3)>,
+    /// The IDs of the entities other than players that were added to the world, waiting for the
+    /// server to tell the players about them.
+    pub spawned_entities: Vec<u64>,
+    /// The blocks players placed and broke, as the entity ID of the player, the position, the
+    /// block with its state and whether it was placed, waiting for the server to log them.
+    pub player_edits: Vec<(u64, IVec3, BlockId, BlockState, bool)>,
+    /// Changes to the health of players made outside of the server, like by commands, as the
+    /// entity ID of the player, the damage taken (negative to heal), what dealt it and the player
+    /// responsible, waiting for the server to apply them.
+    pub player_damage: Vec<(u64, f32, DeathCause, Option<String>)>,
 
     // Storage of player data, keyed by username. This is used to store player data when they are
     // not currently in the world.
@@ -48,34 +107,199 @@ pub struct World {
     /// other entities.
     changes: FxHashMap<IVec3, FxHashMap<IVec3, (BlockId, BlockState)>>,
 
+    /// Generates requested chunks in the background, see [`World::request_chunk`].
+    generation: GenerationPool,
+
     game_data: GameData,
+
+    /// Whether the world runs the same way every time, see [`World::set_deterministic`].
+    deterministic: bool,
+    /// The chunks changed since they were last saved, see [`World::save_dirty_chunks`].
+    dirty_chunks: FxHashSet<IVec3>,
+    /// The chunks whose blocks changed since [`World::take_changed_chunks`] was last called, or
+    /// `None` if it never was.
+    changed_chunks: Option<FxHashSet<IVec3>>,
+    /// Writes chunk files in the background.
+    chunk_writer: ChunkWriter,
+    /// The entities other than players in chunks that aren't loaded, spawned when their chunk is
+    /// loaded. See [`entitystore`] for which chunk an entity belongs to.
+    unloaded_entities: FxHashMap<IVec3, Vec<Box<dyn Entity>>>,
+    /// The chunks whose files held entities when they were last written.
+    entity_chunks: FxHashSet<IVec3>,
+    /// Recently unloaded chunks, see [`World::unload_chunks`].
+    chunk_cache: ChunkCache,
+    /// The most chunks kept in memory, see [`World::set_max_loaded_chunks`].
+    max_loaded_chunks: Option<usize>,
+    /// The source of randomness for ticking, like random block ticks and loot rolls.
+    rng: StdRng,
 }
 
 impl World {
     /// Creates a new empty world.
     pub fn new(seed: i32) -> Self {
-        let generator = Generator::new(GENERATOR_VERSION, seed).unwrap();
+        let generator = Arc::new(Generator::new(GENERATOR_VERSION, seed).unwrap());
         let chunks = FxHashMap::default();
         World {
             chunks,
             entities: FxHashMap::default(),
+            generation: GenerationPool::new(generator.clone()),
             generator,
             time: 0,
+            sleeping: FxHashSet::default(),
+            game_rules: GameRules::default(),
+            difficulty: Difficulty::default(),
+            saved_with: None,
+            chunk_compression: Compression::default(),
+            tick_control: TickControl::default(),
+            tick_budget: TickBudget::default(),
+            containers: FxHashMap::default(),
+            container_opens: Vec::new(),
+            spawned_entities: Vec::new(),
+            player_edits: Vec::new(),
+            player_damage: Vec::new(),
             player_cache: HashMap::new(),
             pending_changes: PendingChanges::default(),
             changes: FxHashMap::default(),
             game_data: GameData::new(),
+            deterministic: false,
+            rng: StdRng::from_os_rng(),
+            dirty_chunks: FxHashSet::default(),
+            changed_chunks: None,
+            unloaded_entities: FxHashMap::default(),
+            entity_chunks: FxHashSet::default(),
+            chunk_writer: ChunkWriter::new(),
+            chunk_cache: ChunkCache::new(DEFAULT_CHUNK_CACHE_SIZE),
+            max_loaded_chunks: None,
         }
     }
 
+    /// Makes the world run the same way every time given the same input, for replaying recorded
+    /// input in tests. Randomness is seeded from the world seed, chunks and entities are ticked
+    /// in order of position and ID, and chunks are generated right away instead of in the
+    /// background.
+    pub fn set_deterministic(&mut self) {
+        self.deterministic = true;
+        self.rng = StdRng::seed_from_u64(self.generator.seed() as u64);
+    }
+
+    /// Returns a hash of the loaded blocks, the entities and the time, to check that two worlds
+    /// ended up the same.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = FxHasher64::default();
+        let mut chunk_positions: Vec<_> = self.chunks.keys().copied().collect();
+        chunk_positions.sort_unstable_by_key(|pos| pos.to_array());
+        for chunk_pos in chunk_positions {
+            chunk_pos.to_array().hash(&mut hasher);
+            let chunk = &self.chunks[&chunk_pos];
+            for x in 0..CHUNK_SIZE as i32 {
+                for y in 0..CHUNK_SIZE as i32 {
+                    for z in 0..CHUNK_SIZE as i32 {
+                        chunk.get_block(IVec3::new(x, y, z)).hash(&mut hasher);
+                    }
+                }
+            }
+        }
+        let mut entity_ids: Vec<_> = self.entities.keys().copied().collect();
+        entity_ids.sort_unstable();
+        for entity_id in entity_ids {
+            self.entities[&entity_id].snapshot().hash(&mut hasher);
+        }
+        self.time.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the ticks since the start of the current day.
+    pub fn time_of_day(&self) -> u64 {
+        self.time % DAY_LENGTH
+    }
+
+    /// Returns whether it is night, which is the second half of the day.
+    pub fn is_night(&self) -> bool {
+        self.time_of_day() >= DAY_LENGTH / 2
+    }
+
+    /// Advances the time to the next sunrise.
+    pub fn skip_to_morning(&mut self) {
+        self.time = (self.time / DAY_LENGTH + 1) * DAY_LENGTH;
+        self.sleeping.clear();
+    }
+
     /// Gets a block at the given world position.
     pub fn get_block_at(&self, world_pos: IVec3) -> Option<(BlockId, &BlockState)> {
+        self.view().get_block_at(world_pos)
+    }
+
+    /// Works out the sky and block light levels at a world position from the loaded blocks, as the
+    /// server doesn't keep light. Returns `None` if its chunk isn't loaded.
+    ///
+    /// Only the chunks around it are lit, along with the loaded chunks above them that sunlight
+    /// comes down through, so this is meant for inspecting single blocks.
+    pub fn light_at(&self, world_pos: IVec3) -> Option<(u8, u8)> {
         let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
         let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        if !self.chunks.contains_key(&chunk_pos) {
+            return None;
+        }
+
+        // Light spreads less than a chunk, so the chunks next to it are enough
+        let mut lit: FxHashMap<IVec3, Chunk> = FxHashMap::default();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let column = chunk_pos + IVec3::new(x, 0, z);
+                let mut top = 0;
+                while self.chunks.contains_key(&(column + IVec3::Y * (top + 1))) {
+                    top += 1;
+                }
+                // Lighting from the top down spreads sunlight only once
+                for y in (-1..=top).rev() {
+                    let pos = column + IVec3::Y * y;
+                    if let Some(chunk) = self.chunks.get(&pos) {
+                        lit.insert(pos, chunk.clone());
+                        light::light_chunk(&mut lit, pos);
+                    }
+                }
+            }
+        }
+        let chunk = &lit[&chunk_pos];
+        Some((
+            chunk.get_light(local_pos, light::LightChannel::Sky),
+            chunk.get_light(local_pos, light::LightChannel::Block),
+        ))
+    }
 
-        self.chunks
-            .get(&chunk_pos)
-            .and_then(|c| c.get_block(local_pos))
+    /// Returns the colors of blocks on maps, see [`GameData::map_colors`].
+    pub fn map_colors(&mut self) -> FxHashMap<BlockId, [u8; 3]> {
+        self.game_data.map_colors().clone()
+    }
+
+    /// Returns the chunks whose blocks changed since the last call, for keeping something showing
+    /// the world up to date. Changes are only tracked from the first call on.
+    pub fn take_changed_chunks(&mut self) -> FxHashSet<IVec3> {
+        self.changed_chunks
+            .replace(FxHashSet::default())
+            .unwrap_or_default()
+    }
+
+    /// Returns read only access to the loaded blocks, which can be shared between threads.
+    pub fn view(&self) -> WorldView<'_> {
+        WorldView {
+            chunks: &self.chunks,
+        }
+    }
+
+    /// Casts a ray through the loaded blocks, see [`physics::cast_ray`].
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> Option<(IVec3, IVec3)> {
+        physics::cast_ray(
+            |pos| self.get_block_at(pos).map(|(block, state)| (block, *state)),
+            origin,
+            direction,
+            max_distance,
+        )
     }
 
     /// Gets a block at the given world position, or generates a new chunk and returns the block if
@@ -101,10 +325,15 @@ impl World {
         let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
         let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
 
+        self.remove_replaced_container(world_pos, block);
         self.changes
             .entry(chunk_pos)
             .or_default()
             .insert(local_pos, (block, state));
+        self.dirty_chunks.insert(chunk_pos);
+        if let Some(changed) = &mut self.changed_chunks {
+            changed.insert(chunk_pos);
+        }
         self.pending_changes.push(BlockUpdate {
             position: world_pos,
             block,
@@ -130,10 +359,15 @@ impl World {
         let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
         let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
 
+        self.remove_replaced_container(world_pos, block);
         self.changes
             .entry(chunk_pos)
             .or_default()
             .insert(local_pos, (block, state));
+        self.dirty_chunks.insert(chunk_pos);
+        if let Some(changed) = &mut self.changed_chunks {
+            changed.insert(chunk_pos);
+        }
         self.pending_changes.push(BlockUpdate {
             position: world_pos,
             block,
@@ -145,6 +379,28 @@ impl World {
         chunk.set_block(local_pos, block, state);
     }
 
+    /// Removes the container at the given world position if its block is replaced by another one,
+    /// losing the contents. Whatever replaces blocks should leave those that
+    /// [`World::holds_items`] alone.
+    fn remove_replaced_container(&mut self, world_pos: IVec3, block: BlockId) {
+        if self.containers.contains_key(&world_pos)
+            && self
+                .get_block_at(world_pos)
+                .is_some_and(|(old, _)| old != block)
+            && let Some(container) = self.containers.remove(&world_pos)
+            && container.slots.iter().any(|stack| !stack.is_empty())
+        {
+            log::warn!("Replaced the container at {} with items in it", world_pos);
+        }
+    }
+
+    /// Returns whether the block at the given world position is a container with items in it.
+    pub fn holds_items(&self, world_pos: IVec3) -> bool {
+        self.containers
+            .get(&world_pos)
+            .is_some_and(|container| container.slots.iter().any(|stack| !stack.is_empty()))
+    }
+
     /// Creates a new chunk at the specified coordinates in chunk space, applying all changes done
     /// to the chunk. Note that this function doesnt automatically insert the new chunk into the
     /// world.
@@ -170,9 +426,173 @@ impl World {
     /// Gets a mutable reference to a chunk at the given chunk position, or loads it if it doesn't
     /// exist.
     pub fn get_chunk_mut_or_new(&mut self, chunk_pos: IVec3) -> &mut Chunk {
-        self.chunks
-            .entry(chunk_pos)
-            .or_insert_with(|| Self::load_chunk(&self.generator, &self.changes, chunk_pos))
+        if !self.chunks.contains_key(&chunk_pos) {
+            let chunk = self
+                .chunk_cache
+                .take(chunk_pos)
+                .unwrap_or_else(|| Self::load_chunk(&self.generator, &self.changes, chunk_pos));
+            self.insert_chunk(chunk_pos, chunk);
+        }
+        self.chunks.get_mut(&chunk_pos).unwrap()
+    }
+
+    /// Inserts a loaded chunk into the world and spawns the entities saved in it.
+    fn insert_chunk(&mut self, chunk_pos: IVec3, chunk: Chunk) {
+        self.chunks.insert(chunk_pos, chunk);
+        for entity in self
+            .unloaded_entities
+            .remove(&chunk_pos)
+            .unwrap_or_default()
+        {
+            self.add_entity(entity);
+        }
+    }
+
+    /// Queues a chunk to be generated in the background if it isn't loaded yet. Chunks with a lower
+    /// priority are generated first. Use [`World::poll_generated_chunks`] to insert the finished
+    /// chunks into the world. Recently unloaded chunks are loaded again right away.
+    pub fn request_chunk(&mut self, chunk_pos: IVec3, priority: u32) {
+        if self.deterministic {
+            self.get_chunk_or_new(chunk_pos);
+        } else if !self.chunks.contains_key(&chunk_pos) {
+            match self.chunk_cache.take(chunk_pos) {
+                Some(chunk) => self.insert_chunk(chunk_pos, chunk),
+                None => self.generation.request(chunk_pos, priority),
+            }
+        }
+    }
+
+    /// Unloads all chunks for which `keep` returns false, returning their positions. They are kept
+    /// in a cache of recently unloaded chunks for a while, so they don't have to be generated
+    /// again if they are needed soon. Entities other than players in the unloaded chunks are
+    /// unloaded with them.
+    pub fn unload_chunks(&mut self, mut keep: impl FnMut(IVec3) -> bool) -> Vec<IVec3> {
+        let unloaded_entities: Vec<_> = self
+            .entities
+            .iter()
+            .filter(|(_, entity)| {
+                entity.entity_type() != *entities::PLAYER && !keep(entity_chunk(entity.position()))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for entity_id in unloaded_entities {
+            let entity = self.entities.remove(&entity_id).unwrap();
+            self.unloaded_entities
+                .entry(entity_chunk(entity.position()))
+                .or_default()
+                .push(entity);
+        }
+
+        let unloaded: Vec<_> = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|&pos| !keep(pos))
+            .collect();
+        let chunks: Vec<_> = unloaded
+            .iter()
+            .map(|chunk_pos| self.chunks.remove(chunk_pos).unwrap())
+            .collect();
+        if let Some(max_loaded_chunks) = self.max_loaded_chunks {
+            self.chunk_cache
+                .set_capacity(max_loaded_chunks.saturating_sub(self.chunks.len()));
+        }
+        for (chunk_pos, chunk) in unloaded.iter().zip(chunks) {
+            self.chunk_cache.insert(*chunk_pos, chunk);
+        }
+        unloaded
+    }
+
+    /// Limits the chunks kept in memory, loaded or cached, to `max_loaded_chunks`, or to the
+    /// loaded chunks and [`DEFAULT_CHUNK_CACHE_SIZE`] cached ones if `None`. Only cached chunks
+    /// are dropped to stay within the limit, the ones unloaded the longest time ago first, so
+    /// the chunks still in use can go over it. Their changes are kept, so dropped chunks load
+    /// again the same way they were.
+    pub fn set_max_loaded_chunks(&mut self, max_loaded_chunks: Option<usize>) {
+        self.max_loaded_chunks = max_loaded_chunks;
+        self.chunk_cache.set_capacity(match max_loaded_chunks {
+            Some(max_loaded_chunks) => max_loaded_chunks.saturating_sub(self.chunks.len()),
+            None => DEFAULT_CHUNK_CACHE_SIZE,
+        });
+    }
+
+    /// Returns whether the chunk was changed since it was last saved.
+    pub fn has_unsaved_changes(&self, chunk_pos: IVec3) -> bool {
+        self.dirty_chunks.contains(&chunk_pos)
+    }
+
+    /// Queues the chunks changed since they were last saved to be written to the save at `path`
+    /// in the background. Errors are reported through [`World::chunk_io_errors`].
+    pub fn save_dirty_chunks(&mut self, path: &std::path::Path) {
+        let dirty = std::mem::take(&mut self.dirty_chunks);
+        self.queue_chunk_writes(path, dirty);
+    }
+
+    /// Queues the files of the given chunks to be written, with the changes and the entities of
+    /// each chunk. Chunks with entities are always written, as are chunks that held entities when
+    /// they were last written, so that entities that moved to another chunk aren't saved twice.
+    fn queue_chunk_writes(&mut self, path: &std::path::Path, mut chunks: FxHashSet<IVec3>) {
+        let mut entities: FxHashMap<IVec3, Vec<SavedEntity>> = FxHashMap::default();
+        let loaded = self
+            .entities
+            .values()
+            .filter(|entity| entity.entity_type() != *entities::PLAYER);
+        let unloaded = self.unloaded_entities.values().flatten();
+        for entity in loaded.chain(unloaded) {
+            entities
+                .entry(entity_chunk(entity.position()))
+                .or_default()
+                .push(entitystore::save_entity(entity.as_ref()));
+        }
+
+        chunks.extend(entities.keys().copied());
+        chunks.extend(self.entity_chunks.drain());
+        self.entity_chunks = entities.keys().copied().collect();
+        for chunk_pos in chunks {
+            self.chunk_writer.write(
+                path,
+                chunk_pos,
+                self.changes.get(&chunk_pos).cloned().unwrap_or_default(),
+                entities.remove(&chunk_pos).unwrap_or_default(),
+                self.chunk_compression,
+            );
+        }
+    }
+
+    /// Returns the errors writing chunks in the background since the last call.
+    pub fn chunk_io_errors(&self) -> Vec<ChunkWriteError> {
+        self.chunk_writer.errors()
+    }
+
+    /// The number of unloaded chunks kept in memory.
+    pub fn cached_chunk_count(&self) -> usize {
+        self.chunk_cache.len()
+    }
+
+    /// Cancels queued chunk generation for all chunks for which `keep` returns false.
+    pub fn cancel_chunk_requests(&mut self, keep: impl FnMut(IVec3) -> bool) {
+        self.generation.cancel_unless(keep);
+    }
+
+    /// Inserts all chunks generated in the background since the last call into the world,
+    /// applying the changes done to them. Returns the positions of the inserted chunks.
+    pub fn poll_generated_chunks(&mut self) -> Vec<IVec3> {
+        let mut inserted = Vec::new();
+        for (chunk_pos, mut chunk) in self.generation.poll() {
+            // The chunk might have been loaded synchronously in the meantime, e.g. by a block
+            // being set in it, in which case that version is kept.
+            if self.chunks.contains_key(&chunk_pos) {
+                continue;
+            }
+            if let Some(changes) = self.changes.get(&chunk_pos) {
+                for (local_pos, (block, state)) in changes {
+                    chunk.set_block(*local_pos, *block, *state);
+                }
+            }
+            self.insert_chunk(chunk_pos, chunk);
+            inserted.push(chunk_pos);
+        }
+        inserted
     }
 
     /// Gets the ID of the next available entity.
@@ -202,6 +622,9 @@ impl World {
     pub fn add_entity(&mut self, mut entity: Box<dyn Entity>) -> u64 {
         let entity_id = self.next_entity_id();
         entity.set_id(entity_id);
+        if entity.entity_type() != *entities::PLAYER {
+            self.spawned_entities.push(entity_id);
+        }
         self.entities.insert(entity_id, entity);
         entity_id
     }
@@ -226,26 +649,94 @@ impl World {
     }
 
     /// Updates the world. The optimal TPS (Ticks Per Second) is 48.
-    pub fn tick(&mut self, tps: u8) {
-        let mut updates = Vec::new();
-        for (pos, chunk) in &self.chunks {
-            updates.extend_from_slice(&chunk.random_tick(5, &self.chunks, *pos));
+    ///
+    /// Players tick once per call, everything else ticks as often as the [`TickControl`] says,
+    /// which is returned.
+    pub fn tick(&mut self, tps: u8) -> u32 {
+        let world_ticks = self.tick_control.world_ticks(tps);
+        let mut chunk_positions: Vec<_> = self.chunks.keys().copied().collect();
+        if self.deterministic {
+            chunk_positions.sort_unstable_by_key(|pos| pos.to_array());
         }
-        for update in updates {
-            self.normal_set_block_at(update.0, update.1, update.2, BlockUpdateKind::RandomTick);
+        for _ in 0..world_ticks {
+            let ticked = self.tick_budget.random_tick_chunks(&mut chunk_positions);
+            let seed = self.rng.random();
+            let effects = parallel::random_tick(self.view(), &ticked, seed);
+            for (pos, time) in effects.timings {
+                self.tick_budget.record(pos, time);
+            }
+            for (pos, block, state) in effects.block_changes {
+                self.normal_set_block_at(pos, block, state, BlockUpdateKind::RandomTick);
+            }
         }
 
-        let entity_ids: Vec<u64> = self.entities.keys().cloned().collect();
-        for entity_id in entity_ids {
-            if let Some(mut entity) = self.entities.remove(&entity_id) {
-                entity.tick(self, tps);
+        // Players are always ticked, other entities up to a budget per chunk
+        let mut by_chunk: FxHashMap<IVec3, Vec<u64>> = FxHashMap::default();
+        let mut entity_ids = Vec::new();
+        for (id, entity) in &self.entities {
+            if entity.entity_type() == *entities::PLAYER {
+                entity_ids.push(*id);
+            } else {
+                by_chunk
+                    .entry(entity_chunk(entity.position()))
+                    .or_default()
+                    .push(*id);
+            }
+        }
+        for (chunk_pos, ids) in by_chunk {
+            entity_ids.extend(self.tick_budget.entities_to_tick(chunk_pos, ids));
+        }
 
-                if !entity.requests_removal() {
-                    self.entities.insert(entity_id, entity);
-                }
+        // Entities are taken out while ticking in parallel, and put back in order of ID along with
+        // the blocks they changed so the world ends up the same every time
+        let ticking: Vec<_> = entity_ids
+            .into_iter()
+            .filter_map(|id| self.entities.remove(&id))
+            .collect();
+        let (ticked, effects) = parallel::tick_entities(self.view(), ticking, world_ticks, tps);
+        for (pos, time) in effects.timings {
+            self.tick_budget.record(pos, time);
+        }
+        for (pos, block, state) in effects.block_changes {
+            self.normal_set_block_at(pos, block, state, BlockUpdateKind::Entity);
+        }
+        for entity in ticked {
+            if !entity.requests_removal() {
+                self.entities.insert(entity.id(), entity);
             }
         }
-        self.time += 1;
+        self.tick_budget.finish(
+            |pos| self.chunks.contains_key(&pos),
+            |id| self.entities.contains_key(&id),
+        );
+        self.spawn_mobs(world_ticks);
+        if self.game_rules.daylight_cycle {
+            self.time += world_ticks as u64;
+        }
+        world_ticks
+    }
+
+    /// Returns whether a block placed at the given world position would intersect any entity.
+    pub fn block_intersects_entity(&self, pos: IVec3, block: BlockId, state: BlockState) -> bool {
+        let block_def = block_registry().get(block).unwrap();
+        self.entities.values().any(|entity| {
+            let (width, height) = entity.dimensions();
+            block_def.collides_with_player(width, height, entity.position() - pos.as_vec3(), state)
+        })
+    }
+
+    /// Sends the block at the given world position to the players again, undoing anything a
+    /// client may have predicted there.
+    pub fn resend_block_at(&mut self, pos: IVec3) {
+        if let Some((block, state)) = self.get_block_at(pos).map(|(b, s)| (b, *s)) {
+            self.pending_changes.push(BlockUpdate {
+                position: pos,
+                block,
+                block_state: state,
+                urgent: true,
+                kind: BlockUpdateKind::Correction,
+            });
+        }
     }
 
     pub fn try_place_block(
@@ -255,23 +746,19 @@ impl World {
         block: BlockId,
         state: BlockState,
     ) -> bool {
-        let player_pos = match self.get_entity::<PlayerEntity>(player_entity_id) {
-            Some(p) => p.position,
-            None => return false,
-        };
-
-        let old_block = self
-            .get_block_at(pos)
-            .map(|(b, _)| b)
-            .unwrap_or(*blocks::AIR);
-
-        self.urgent_set_block_at(pos, block, state, BlockUpdateKind::Placed);
+        if self.get_entity::<PlayerEntity>(player_entity_id).is_none() {
+            return false;
+        }
 
-        if self.collides(player_pos, PlayerEntity::width(), PlayerEntity::height()) {
-            self.urgent_set_block_at(pos, old_block, BlockState::none(), BlockUpdateKind::Removed);
+        if self.block_intersects_entity(pos, block, state) {
+            self.resend_block_at(pos);
             return false;
         }
 
+        self.urgent_set_block_at(pos, block, state, BlockUpdateKind::Placed);
+        self.player_edits
+            .push((player_entity_id, pos, block, state, true));
+
         if let Some(player) = self.get_entity_mut::<PlayerEntity>(player_entity_id) {
             let inv = &mut player.inventory;
             let slot = inv.hotbar_slot_mut(player.hotbar_index);
@@ -288,54 +775,68 @@ impl World {
     /// Handles a block interaction at the given world position and face index. If the block is not
     /// interactive, this will attempt to place a block on the face that was clicked.
     pub fn block_interaction(&mut self, player_entity_id: u64, block_pos: IVec3, face: Direction) {
+        if self.get_entity::<PlayerEntity>(player_entity_id).is_none() {
+            return;
+        }
+
+        if let Some((id, state)) = self.get_block_at(block_pos).map(|(b, s)| (b, *s)) {
+            let def = block_registry().get(id).unwrap();
+            if let Some(on_click) = &def.on_click
+                && on_click(id, self, player_entity_id, block_pos, state, face)
+            {
+                return; // hook fully handled the interaction
+            }
+        }
+
+        self.place_held_block(player_entity_id, block_pos + face, face);
+    }
+
+    /// Places the block held by a player at the given position, as if placed against a block on
+    /// the given face. Returns whether the block was placed.
+    pub fn place_held_block(&mut self, player_entity_id: u64, pos: IVec3, face: Direction) -> bool {
         let (item_count, place_block) = match self.get_entity::<PlayerEntity>(player_entity_id) {
             Some(p) => {
                 let stack = p.inventory.hotbar_slot(p.hotbar_index);
                 let assoc_block = item_registry().get(stack.item).unwrap().assoc_block;
                 (stack.count, assoc_block)
             }
-            None => return,
+            None => return false,
         };
-
-        if let Some((id, state)) = self.get_block_at(block_pos).map(|(b, s)| (b, *s)) {
-            let def = block_registry().get(id).unwrap();
-            if let Some(on_click) = &def.on_click {
-                if on_click(id, self, player_entity_id, block_pos, state, face) {
-                    return; // hook fully handled the interaction
-                }
-            }
-        }
-
-        let place_pos = block_pos + face;
         if item_count == 0 {
-            return;
-        }
-        if let Some(block) = place_block {
-            let def = block_registry().get(**block).unwrap();
-            let state = if let Some(on_place) = &def.on_place {
-                (on_place)(**block, self, player_entity_id, place_pos, face)
-            } else if let Some(bs) = BlockState::default_state(def.state_type) {
-                bs
-            } else {
-                return;
-            };
-            self.try_place_block(player_entity_id, place_pos, **block, state);
+            return false;
         }
+        let Some(block) = place_block else {
+            return false;
+        };
+        let def = block_registry().get(**block).unwrap();
+        let state = if let Some(on_place) = &def.on_place {
+            (on_place)(**block, self, player_entity_id, pos, face)
+        } else if let Some(bs) = BlockState::default_state(def.state_type) {
+            bs
+        } else {
+            return false;
+        };
+        self.try_place_block(player_entity_id, pos, **block, state)
     }
 
-    pub fn break_block(&mut self, player_entity_id: u64, block_pos: IVec3) {
+    /// Breaks the block for the player, giving them its drops. Returns whether the block broke,
+    /// as blocks without drops and those refusing in their `on_break` stay.
+    pub fn break_block(&mut self, player_entity_id: u64, block_pos: IVec3) -> bool {
         let (block, state) = match self.get_block_at(block_pos) {
             Some((b, s)) => (b, *s),
-            None => return,
+            None => return false,
         };
 
         let block_def = block_registry().get(block).unwrap();
-        if let Some(on_break) = &block_def.on_break {
-            on_break(block, self, player_entity_id, block_pos, state);
+        if let Some(on_break) = &block_def.on_break
+            && !on_break(block, self, player_entity_id, block_pos, state)
+        {
+            self.resend_block_at(block_pos);
+            return false;
         }
 
         let Some(loot_table_entry) = self.game_data.get_block_drops(block) else {
-            return;
+            return false;
         };
         let drops = &loot_table_entry.drops;
         let drops = drops.get(&state.data()).cloned().unwrap_or_default();
@@ -346,18 +847,22 @@ impl World {
             crate::block::BlockState::none(),
             crate::protocol::BlockUpdateKind::Removed,
         );
+        self.player_edits
+            .push((player_entity_id, block_pos, block, state, false));
 
-        let player = match self.get_entity_mut::<PlayerEntity>(player_entity_id) {
-            Some(p) => p,
-            None => return,
+        let Some(player) = self
+            .entities
+            .get_mut(&player_entity_id)
+            .and_then(|e| e.as_any_mut().downcast_mut::<PlayerEntity>())
+        else {
+            return true;
         };
 
         for (item, drop_entry) in drops {
             let count = if drop_entry.max == drop_entry.min {
                 drop_entry.min
             } else {
-                let mut rng = rand::rng();
-                let roll = rand::Rng::random_range(&mut rng, 0.0..1.0);
+                let roll = self.rng.random_range(0.0..1.0);
                 if roll < drop_entry.min_chance {
                     drop_entry.min
                 } else if roll < drop_entry.max_chance {
@@ -383,36 +888,13 @@ impl World {
             // inventory
             player.inventory.add_stack(item, count as u16);
         }
+        true
     }
 }
 
 impl CollisionWorld for World {
     fn collides(&self, pos: Vec3, width: f32, height: f32) -> bool {
-        let min_block_pos = (pos - Vec3::splat(width / 2.0)).floor().as_ivec3();
-        let max_block_pos = (pos + Vec3::new(width / 2.0, height, width / 2.0))
-            .floor()
-            .as_ivec3();
-
-        for x in min_block_pos.x..=max_block_pos.x {
-            for y in min_block_pos.y..=max_block_pos.y {
-                for z in min_block_pos.z..=max_block_pos.z {
-                    let block_pos = IVec3::new(x, y, z);
-                    if let Some((block, block_state)) = self.get_block_at(block_pos)
-                        && let Some(block) = block_registry().get(block)
-                        && block.collides_with_player(
-                            width,
-                            height,
-                            pos - block_pos.as_vec3(),
-                            *block_state,
-                        )
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        false
+        self.view().collides(pos, width, height)
     }
 }
 
@@ -498,38 +980,55 @@ impl Iterator for PendingChanges {
 impl World {
     /// Saves the world to a folder.
     ///
-    /// All modified chunks are saved to the "chunks" subfolder, with filenames in the format
-    /// "chunk_x_y_z.bin". The entity data is saved to "entities.bin". The player data is contained
-    /// in the "players" subfolder, with filenames in the format "{hashed_username}.bin", which
-    /// contains the position, rotation, and other relevant data for each player. Note that the
-    /// players, even though they are entities, aren't stored in the entities.bin file, since they
-    /// are linked to user accounts and need to be loaded and linked to the accounts when they
+    /// All modified chunks are saved to region files in the "regions" subfolder, with filenames in
+    /// the format "region_x_y_z.bin", together with the entities in them (see [`entitystore`]).
+    /// Each region file holds up to [`chunkio::REGION_FILE_SIZE`] chunks along each axis. The player
+    /// data is contained in the "players" subfolder, with filenames in the format
+    /// "{hashed_username}.bin", which contains the position, rotation, and other relevant data for
+    /// each player. Note that the players, even though they are entities, aren't stored with the
+    /// chunks, since they are linked to user accounts and need to be loaded and linked to the accounts when they
     /// join, so they are stored separately in the "players" subfolder. The folder also contains a
     /// "save.bin" file with metadata about the world, such as the seed, generation settings, and
     /// also the version of the save format, so that future versions of the game can maintain
     /// compatibility with older saves. The entity IDs aren't stored in the world save, since they
     /// can be generated on load anyways.
     ///
-    /// # chunks/chunk_x_y_z.bin
+    /// The chunks are written by the chunk IO worker like [`World::save_dirty_chunks`], but this
+    /// waits until they are all written.
+    ///
+    /// # regions/region_x_y_z.bin
+    /// - 2 bytes: number of chunks in the region (N)
+    /// - N times, in order of position
+    ///   - 3 bytes: chunk position (x, y, z) within the region (0-7)
+    ///   - 4 bytes: length of the chunk data (u32)
+    /// - N times: chunk data, in the same order
+    ///
+    /// # Chunk data
+    /// - 1 byte: compression of the rest of the chunk (u8, index into [`Compression::ALL`])
     /// - 2 bytes: number of changes in the chunk (N)
     /// - N times
     ///   - 3 bytes: local block position (x, y, z) within the chunk (0-15)
     ///   - 1 byte: length of the block identifier (M)
     ///   - M bytes: block identifier (UTF-8 string)
     ///   - 4 bytes: block state data (u32)
+    /// - entities (format defined by [`entitystore::write_entities`])
     ///
     /// # save.bin
     /// - 1 byte: save format version (u8)
     /// - 1 byte: generator version (u8)
     /// - 4 bytes: world seed (i32)
     /// - 8 bytes: current time in ticks (u64)
+    /// - game rules (format defined by the [`Saveable`] implementation of [`GameRules`])
+    /// - 1 byte: difficulty (u8, index into [`Difficulty::ALL`])
+    /// - 1 byte: length of the game version that saved the world (V)
+    /// - V bytes: game version (UTF-8 string)
+    /// - 1 byte: compression of chunks saved from now on (u8, index into [`Compression::ALL`])
     ///
-    /// # entities.bin
-    /// - 8 bytes: number of entities (N)
+    /// # containers.bin
+    /// - 4 bytes: number of containers (N)
     /// - N times
-    ///   - 1 byte: entity type (u8)
-    ///   - 4 bytes: length of entity data (M)
-    ///   - M bytes: entity data (format defined by each entity type)
+    ///   - 12 bytes: world position (3 i32 values for x, y, z)
+    ///   - container data (format defined by the [`Saveable`] implementation of [`Container`])
     ///
     /// # players/{hashed_username}.bin
     /// - 1 byte: length of username (U)
@@ -538,64 +1037,68 @@ impl World {
     /// - 12 bytes: velocity (3 f32 values for x, y, z)
     /// - 4 bytes: yaw (f32)
     /// - 4 bytes: pitch (f32)
-    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+    /// - inventory (format defined by the [`Saveable`] implementation of
+    ///   [`crate::item::Inventory`])
+    /// - 1 byte: flying (0 or 1)
+    /// - 4 bytes: health (f32)
+    pub fn save(&mut self, path: &std::path::Path) -> std::io::Result<()> {
         let mut save_file = std::fs::File::create(path.join("save.bin"))?;
         std::io::Write::write_all(&mut save_file, &[SAVE_VERSION])?;
         std::io::Write::write_all(&mut save_file, &self.generator.save())?;
         std::io::Write::write_all(&mut save_file, &self.time.to_le_bytes())?;
+        std::io::Write::write_all(&mut save_file, &self.game_rules.save())?;
+        std::io::Write::write_all(&mut save_file, &[self.difficulty as u8])?;
+        let game_version = env!("CARGO_PKG_VERSION");
+        std::io::Write::write_all(&mut save_file, &[game_version.len() as u8])?;
+        std::io::Write::write_all(&mut save_file, game_version.as_bytes())?;
+        std::io::Write::write_all(&mut save_file, &[self.chunk_compression as u8])?;
 
         log::info!("Saved save.bin");
 
-        std::fs::create_dir_all(path.join("chunks"))?;
-        for (chunk_pos, changes) in &self.changes {
-            let chunk_path = path.join("chunks").join(format!(
-                "chunk_{}_{}_{}.bin",
-                chunk_pos.x, chunk_pos.y, chunk_pos.z
-            ));
-            let mut chunk_file = std::fs::File::create(chunk_path)?;
-            let change_count = changes.len() as u16;
-            std::io::Write::write_all(&mut chunk_file, &change_count.to_le_bytes())?;
-            for (local_pos, (block, state)) in changes {
-                std::io::Write::write_all(
-                    &mut chunk_file,
-                    &[local_pos.x as u8, local_pos.y as u8, local_pos.z as u8],
-                )?;
-                let data = (*block, *state).save();
-                std::io::Write::write_all(&mut chunk_file, data.as_slice())?;
+        // Loading needs the folder even if no chunk was changed yet
+        std::fs::create_dir_all(path.join("regions"))?;
+        let chunks = self.changes.keys().copied().collect();
+        self.queue_chunk_writes(path, chunks);
+        self.dirty_chunks.clear();
+        self.chunk_writer.flush();
+        let errors = self.chunk_writer.errors();
+        for error in &errors {
+            log::error!("{}", error);
+        }
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error.error);
+        }
+
+        log::info!("Saved chunks and entities");
+
+        let mut containers_file = std::fs::File::create(path.join("containers.bin"))?;
+        let container_count = self.containers.len() as u32;
+        std::io::Write::write_all(&mut containers_file, &container_count.to_le_bytes())?;
+        for (pos, container) in &self.containers {
+            for coord in pos.to_array() {
+                std::io::Write::write_all(&mut containers_file, &coord.to_le_bytes())?;
             }
+            std::io::Write::write_all(&mut containers_file, &container.save())?;
         }
 
-        log::info!("Saved chunks");
+        log::info!("Saved containers");
 
-        let mut entities_file = std::fs::File::create(path.join("entities.bin"))?;
         std::fs::create_dir_all(path.join("players"))?;
-        let entity_count = self
+        for player in self
             .entities
             .values()
-            .filter(|e| e.entity_type() != EntityType::Player)
-            .count() as u64;
-        std::io::Write::write_all(&mut entities_file, &entity_count.to_le_bytes())?;
-        for entity in self.entities.values() {
-            let entity_type = entity.entity_type() as u8;
-            if entity_type == EntityType::Player as u8 {
-                let player = entity.as_any().downcast_ref::<PlayerEntity>().unwrap();
-                let player_data = player.save();
-                let hashed_username = hash64(player.username.as_bytes());
-                let player_path = path
-                    .join("players")
-                    .join(format!("{}.bin", hashed_username));
-                let mut player_file = std::fs::File::create(player_path)?;
-                std::io::Write::write_all(&mut player_file, &player_data)?;
-            } else {
-                let entity_data = entity.save();
-                let entity_data_len = entity_data.len() as u32;
-                std::io::Write::write_all(&mut entities_file, &[entity_type])?;
-                std::io::Write::write_all(&mut entities_file, &entity_data_len.to_le_bytes())?;
-                std::io::Write::write_all(&mut entities_file, &entity_data)?;
-            }
+            .filter_map(|entity| entity.as_any().downcast_ref::<PlayerEntity>())
+        {
+            let player_data = player.save();
+            let hashed_username = hash64(player.username.as_bytes());
+            let player_path = path
+                .join("players")
+                .join(format!("{}.bin", hashed_username));
+            let mut player_file = std::fs::File::create(player_path)?;
+            std::io::Write::write_all(&mut player_file, &player_data)?;
         }
 
-        log::info!("Saved entities and logged-in players");
+        log::info!("Saved logged-in players");
 
         for cached in self.player_cache.values() {
             let player_data = cached.save();
@@ -613,13 +1116,17 @@ impl World {
     }
 
     /// Loads a world from a folder. The folder should have the same structure as described in the
-    /// `save` method.
+    /// `save` method. Saves in older formats are migrated to the current one first, see
+    /// [`migration::migrate`].
     pub fn load(path: &std::path::Path) -> Result<Self, WorldLoadError> {
+        migration::migrate(path, false)?;
         let save_content = std::fs::read(path.join("save.bin"))
             .map_err(|_| WorldLoadError::MissingSaveFile(path.join("save.bin")))?;
         let mut save_iter = save_content.into_iter();
         match save_iter.next() {
-            Some(version) if version <= 0x06 => load_v0_to_v6(path, &mut save_iter, version),
+            Some(version) if version <= SAVE_VERSION => {
+                load_v0_to_v12(path, &mut save_iter, version)
+            }
             Some(version) => Err(WorldLoadError::InvalidSaveFormat(format!(
                 "Unsupported save version: {}",
                 version
@@ -631,15 +1138,51 @@ impl World {
     }
 }
 
-fn load_v0_to_v6(
+/// Reads the data of every saved chunk, from region files or from the chunk files used before
+/// save version 0x0E.
+fn read_saved_chunks(
+    path: &std::path::Path,
+    version: u8,
+) -> Result<Vec<(IVec3, Vec<u8>)>, WorldLoadError> {
+    let (dir, prefix) = if version >= 0x0E {
+        (path.join("regions"), "region_")
+    } else {
+        (path.join("chunks"), "chunk_")
+    };
+    let entries = std::fs::read_dir(&dir).map_err(|_| WorldLoadError::MissingSaveFile(dir))?;
+    let mut chunks = Vec::new();
+    for entry in entries {
+        let entry_path = entry
+            .map_err(|e| WorldLoadError::InvalidSaveFormat(e.to_string()))?
+            .path();
+        let Some(pos) = entry_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| chunkio::file_name_pos(name, prefix))
+        else {
+            continue;
+        };
+        let read_error = |e: std::io::Error| {
+            WorldLoadError::InvalidSaveFormat(format!("{}: {}", entry_path.display(), e))
+        };
+        if version >= 0x0E {
+            chunks.extend(chunkio::read_region(&entry_path, pos).map_err(read_error)?);
+        } else {
+            chunks.push((pos, std::fs::read(&entry_path).map_err(read_error)?));
+        }
+    }
+    Ok(chunks)
+}
+
+fn load_v0_to_v12(
     path: &std::path::Path,
     save_iter: &mut impl Iterator<Item = u8>,
     version: u8,
 ) -> Result<World, WorldLoadError> {
     // GENERATOR
-    let generator = Generator::load(save_iter, version).map_err(|e| {
+    let generator = Arc::new(Generator::load(save_iter, version).map_err(|e| {
         WorldLoadError::InvalidSaveFormat(format!("Failed to load generator: {}", e))
-    })?;
+    })?);
 
     // TIME
     let time = if version >= 0x05 {
@@ -649,41 +1192,90 @@ fn load_v0_to_v6(
         0
     };
 
+    // GAME RULES
+    let game_rules = if version >= 0x07 {
+        GameRules::load(save_iter, version)?
+    } else {
+        GameRules::default()
+    };
+
+    // DIFFICULTY
+    let difficulty = if version >= 0x08 {
+        let value = read_u8(save_iter, "World::difficulty")?;
+        Difficulty::from_u8(value).ok_or_else(|| {
+            WorldLoadError::InvalidSaveFormat(format!("Invalid difficulty: {}", value))
+        })?
+    } else {
+        Difficulty::default()
+    };
+
+    // SAVED WITH
+    let saved_with = if version >= 0x0B {
+        let len = read_u8(save_iter, "World::saved_with length")?;
+        let game_version = read_string(save_iter, len as usize, "World::saved_with")?;
+        (!game_version.is_empty()).then_some(game_version)
+    } else {
+        None
+    };
+    // CHUNK COMPRESSION
+    let chunk_compression = if version >= 0x0C {
+        let value = read_u8(save_iter, "World::chunk_compression")?;
+        Compression::from_u8(value).ok_or_else(|| {
+            WorldLoadError::InvalidSaveFormat(format!("Invalid chunk compression: {}", value))
+        })?
+    } else {
+        Compression::None
+    };
+
+    if let Some(game_version) = &saved_with {
+        log::info!("Loading world last saved with version {}", game_version);
+    }
+
     let mut world = World {
         chunks: FxHashMap::default(),
         entities: FxHashMap::default(),
+        generation: GenerationPool::new(generator.clone()),
         generator,
         time,
+        sleeping: FxHashSet::default(),
+        game_rules,
+        difficulty,
+        saved_with,
+        chunk_compression,
+        tick_control: TickControl::default(),
+        tick_budget: TickBudget::default(),
+        containers: FxHashMap::default(),
+        container_opens: Vec::new(),
+        spawned_entities: Vec::new(),
+        player_edits: Vec::new(),
+        player_damage: Vec::new(),
         player_cache: HashMap::new(),
         pending_changes: PendingChanges::default(),
         changes: FxHashMap::default(),
         game_data: GameData::new(),
+        deterministic: false,
+        rng: StdRng::from_os_rng(),
+        dirty_chunks: FxHashSet::default(),
+        changed_chunks: None,
+        unloaded_entities: FxHashMap::default(),
+        entity_chunks: FxHashSet::default(),
+        chunk_writer: ChunkWriter::new(),
+        chunk_cache: ChunkCache::new(DEFAULT_CHUNK_CACHE_SIZE),
+        max_loaded_chunks: None,
     };
 
     // CHUNKS
-    let chunks_dir = path.join("chunks");
-    if !chunks_dir.exists() {
-        return Err(WorldLoadError::MissingSaveFile(chunks_dir));
-    }
-    for entry in std::fs::read_dir(chunks_dir).unwrap() {
-        let entry = entry.unwrap();
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_str().unwrap();
-        if !file_name_str.starts_with("chunk_") || !file_name_str.ends_with(".bin") {
-            continue;
-        }
-        let parts: Vec<&str> = file_name_str[6..file_name_str.len() - 4]
-            .split('_')
-            .collect();
-        if parts.len() != 3 {
-            continue;
+    for (chunk_pos, mut chunk_data) in read_saved_chunks(path, version)? {
+        if version >= 0x0C {
+            let compression = chunk_data.first().copied().and_then(Compression::from_u8);
+            let Some(compression) = compression else {
+                return Err(WorldLoadError::InvalidSaveFormat(format!(
+                    "Invalid compression in chunk {}",
+                    chunk_pos
+                )));
+            };
+            chunk_data = compression.decompress(&chunk_data[1..])?;
         }
-        let chunk_pos = IVec3::new(
-            parts[0].parse().unwrap(),
-            parts[1].parse().unwrap(),
-            parts[2].parse().unwrap(),
-        );
-        let chunk_data = std::fs::read(entry.path()).unwrap();
         let mut chunk_iter = chunk_data.into_iter();
         let change_count = read_u16(&mut chunk_iter, "Chunk change count")?;
         for _ in 0..change_count {
@@ -695,39 +1287,48 @@ fn load_v0_to_v6(
                 .or_default()
                 .insert(local_pos, block_and_state);
         }
+        if version >= 0x0D {
+            let entities = entitystore::read_entities(&mut chunk_iter, version)?;
+            if !entities.is_empty() {
+                world.entity_chunks.insert(chunk_pos);
+                world.unloaded_entities.insert(chunk_pos, entities);
+            }
+        }
 
         // In 0x06 the redundant chunk data was removed. We don't handle loading the many many bytes
         // for versions before 0x06 simply because there was nothing else after the chunk data.
     }
 
+    // CONTAINERS
+    if version >= 0x0A {
+        let containers_path = path.join("containers.bin");
+        let containers_data = std::fs::read(&containers_path)
+            .map_err(|_| WorldLoadError::MissingSaveFile(containers_path))?;
+        let mut containers_iter = containers_data.into_iter();
+        let container_count = read_u32(&mut containers_iter, "Container count")?;
+        for _ in 0..container_count {
+            let pos = read_ivec3(&mut containers_iter, "Container position")?;
+            let container = Container::load(&mut containers_iter, version)?;
+            world.containers.insert(pos, container);
+        }
+    }
+
     // ENTITIES
-    let entities_path = path.join("entities.bin");
-    if !entities_path.exists() {
-        return Err(WorldLoadError::MissingSaveFile(entities_path));
-    }
-    let entities_data = std::fs::read(entities_path).unwrap();
-    let mut entities_iter = entities_data.into_iter();
-    let entity_count = read_u64(&mut entities_iter, "Entity count")?;
-    #[allow(clippy::never_loop)]
-    #[allow(unreachable_code, unused_variables)]
-    for _ in 0..entity_count {
-        let entity_type = read_u8(&mut entities_iter, "Entity type")?;
-        let entity_data_len = read_u32(&mut entities_iter, "Entity data length")?;
-        let entity_data = take_exact(&mut entities_iter, entity_data_len as usize, "Entity data")?;
-        let entity: Box<dyn Entity> = match entity_type {
-            x if x == EntityType::Player as u8 => {
-                return Err(WorldLoadError::InvalidSaveFormat(
-                    "Player entities should be stored in the players folder".to_string(),
-                ));
-            }
-            _ => {
-                return Err(WorldLoadError::InvalidSaveFormat(format!(
-                    "Unknown entity type: {}",
-                    entity_type
-                )));
-            }
-        };
-        world.add_entity(entity);
+    // Before 0x0D the entities were saved in entities.bin instead of with their chunks
+    if version < 0x0D {
+        let entities_path = path.join("entities.bin");
+        if !entities_path.exists() {
+            return Err(WorldLoadError::MissingSaveFile(entities_path));
+        }
+        let entities_data = std::fs::read(entities_path).unwrap();
+        let mut entities_iter = entities_data.into_iter();
+        // Entity types were saved as numbers then, and only players had one
+        let entity_count = read_u64(&mut entities_iter, "Entity count")?;
+        if entity_count > 0 {
+            return Err(WorldLoadError::InvalidSaveFormat(
+                "entities.bin holds entities of unknown types".to_string(),
+            ));
+        }
     }
 
     let players_dir = path.join("players");
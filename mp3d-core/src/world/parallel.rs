@@ -0,0 +1,191 @@
+//! Ticking the world on several threads at once.
+//!
+//! The loaded chunks are split into regions of [`REGION_SIZE`] chunks along each axis, which are
+//! ticked in parallel. While ticking, chunks and entities only get to read the world through a
+//! [`WorldView`]. Changes to blocks are queued in [`TickEffects`] instead, and applied one region
+//! after the other once every region is done. This way no region ever sees a half ticked
+//! neighbour, and the world ends up the same no matter how the threads were scheduled.
+
+use std::time::{Duration, Instant};
+
+use fxhash::{FxHashMap, hash64};
+use glam::{IVec3, Vec3};
+use rand::{SeedableRng, rngs::StdRng};
+use rayon::prelude::*;
+
+use crate::{
+    block::{BlockId, BlockState, block_registry},
+    entity::{Entity, entities},
+    physics::CollisionWorld,
+    world::{
+        chunk::{CHUNK_SIZE, Chunk},
+        entitystore::entity_chunk,
+    },
+};
+
+/// The size of a region ticked on one thread, in chunks along each axis.
+pub const REGION_SIZE: i32 = 4;
+
+/// The number of blocks random ticked in each chunk per world tick.
+const RANDOM_TICKS_PER_CHUNK: usize = 5;
+
+/// Returns the region a chunk belongs to.
+pub fn region_of(chunk_pos: IVec3) -> IVec3 {
+    chunk_pos.div_euclid(IVec3::splat(REGION_SIZE))
+}
+
+/// Read only access to the loaded blocks of the world, shared by the threads ticking it.
+#[derive(Clone, Copy)]
+pub struct WorldView<'a> {
+    pub chunks: &'a FxHashMap<IVec3, Chunk>,
+}
+
+impl<'a> WorldView<'a> {
+    /// Gets the block and block state at the given world position, if its chunk is loaded.
+    pub fn get_block_at(&self, world_pos: IVec3) -> Option<(BlockId, &'a BlockState)> {
+        let chunk_pos = world_pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        let local_pos = world_pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32));
+        self.chunks
+            .get(&chunk_pos)
+            .and_then(|c| c.get_block(local_pos))
+    }
+}
+
+impl CollisionWorld for WorldView<'_> {
+    fn collides(&self, pos: Vec3, width: f32, height: f32) -> bool {
+        let min_block_pos = (pos - Vec3::splat(width / 2.0)).floor().as_ivec3();
+        let max_block_pos = (pos + Vec3::new(width / 2.0, height, width / 2.0))
+            .floor()
+            .as_ivec3();
+
+        for x in min_block_pos.x..=max_block_pos.x {
+            for y in min_block_pos.y..=max_block_pos.y {
+                for z in min_block_pos.z..=max_block_pos.z {
+                    let block_pos = IVec3::new(x, y, z);
+                    if let Some((block, block_state)) = self.get_block_at(block_pos)
+                        && let Some(block) = block_registry().get(block)
+                        && block.collides_with_player(
+                            width,
+                            height,
+                            pos - block_pos.as_vec3(),
+                            *block_state,
+                        )
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Changes to the world made while ticking, applied after every region is done.
+#[derive(Debug, Default)]
+pub struct TickEffects {
+    /// Blocks to set, as the world position, the block and its state.
+    pub block_changes: Vec<(IVec3, BlockId, BlockState)>,
+    /// The time spent on each chunk, see [`crate::world::tickbudget::TickBudget::record`].
+    pub(super) timings: Vec<(IVec3, Duration)>,
+}
+
+impl TickEffects {
+    /// Queues a block to be set once the tick is done.
+    pub fn set_block(&mut self, world_pos: IVec3, block: BlockId, state: BlockState) {
+        self.block_changes.push((world_pos, block, state));
+    }
+
+    fn append(&mut self, other: TickEffects) {
+        self.block_changes.extend(other.block_changes);
+        self.timings.extend(other.timings);
+    }
+}
+
+/// Groups items by the region of their chunk, with the regions sorted so that the results don't
+/// depend on the order of the input.
+fn by_region<T>(items: impl IntoIterator<Item = (IVec3, T)>) -> Vec<(IVec3, Vec<T>)> {
+    let mut regions: FxHashMap<IVec3, Vec<T>> = FxHashMap::default();
+    for (chunk_pos, item) in items {
+        regions.entry(region_of(chunk_pos)).or_default().push(item);
+    }
+    let mut regions: Vec<_> = regions.into_iter().collect();
+    regions.sort_unstable_by_key(|(region, _)| region.to_array());
+    regions
+}
+
+/// Random ticks the given chunks, one region per thread. Each region gets its own source of
+/// randomness derived from `seed`, so the ticks are the same however the regions are spread over
+/// the threads.
+pub fn random_tick(view: WorldView, chunk_positions: &[IVec3], seed: u64) -> TickEffects {
+    let regions = by_region(chunk_positions.iter().map(|pos| (*pos, *pos)));
+    let results: Vec<TickEffects> = regions
+        .into_par_iter()
+        .map(|(region, mut positions)| {
+            positions.sort_unstable_by_key(|pos| pos.to_array());
+            let mut rng = StdRng::seed_from_u64(seed ^ hash64(&region.to_array()));
+            let mut effects = TickEffects::default();
+            for pos in positions {
+                let start = Instant::now();
+                let chunk = &view.chunks[&pos];
+                effects.block_changes.extend(chunk.random_tick(
+                    RANDOM_TICKS_PER_CHUNK,
+                    view.chunks,
+                    pos,
+                    &mut rng,
+                ));
+                effects.timings.push((pos, start.elapsed()));
+            }
+            effects
+        })
+        .collect();
+    let mut effects = TickEffects::default();
+    for result in results {
+        effects.append(result);
+    }
+    effects
+}
+
+/// Ticks entities taken out of the world, one region per thread. Players tick once, other entities
+/// `world_ticks` times. The entities are returned in order of ID with the effects of their ticks.
+pub fn tick_entities(
+    view: WorldView,
+    entities: Vec<Box<dyn Entity>>,
+    world_ticks: u32,
+    tps: u8,
+) -> (Vec<Box<dyn Entity>>, TickEffects) {
+    let regions = by_region(
+        entities
+            .into_iter()
+            .map(|entity| (entity_chunk(entity.position()), entity)),
+    );
+    let results: Vec<(Vec<Box<dyn Entity>>, TickEffects)> = regions
+        .into_par_iter()
+        .map(|(_, mut entities)| {
+            entities.sort_unstable_by_key(|entity| entity.id());
+            let mut effects = TickEffects::default();
+            for entity in &mut entities {
+                let is_player = entity.entity_type() == *entities::PLAYER;
+                let ticks = if is_player { 1 } else { world_ticks };
+                let chunk_pos = entity_chunk(entity.position());
+                let start = Instant::now();
+                for _ in 0..ticks {
+                    entity.tick(&view, &mut effects, tps);
+                }
+                if !is_player {
+                    effects.timings.push((chunk_pos, start.elapsed()));
+                }
+            }
+            (entities, effects)
+        })
+        .collect();
+
+    let mut ticked = Vec::new();
+    let mut effects = TickEffects::default();
+    for (entities, result) in results {
+        ticked.extend(entities);
+        effects.append(result);
+    }
+    ticked.sort_unstable_by_key(|entity| entity.id());
+    (ticked, effects)
+}
@@ -0,0 +1,115 @@
+//! The sun, the moon and the stars, shared by the server and the client so that everyone sees the
+//! same sky.
+//!
+//! The stars are generated from a seed derived from the world seed, so they are the same every
+//! time a world is played but don't give the world seed away to clients. The moon goes through
+//! its phases once every [`MOON_PHASES`] days.
+
+use fxhash::hash64;
+use glam::Vec3;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::world::DAY_LENGTH;
+
+/// The names of the moon phases, in order. A phase lasts one day, starting with a full moon.
+pub const MOON_PHASES: [&str; 8] = [
+    "Full moon",
+    "Waning gibbous",
+    "Last quarter",
+    "Waning crescent",
+    "New moon",
+    "Waxing crescent",
+    "First quarter",
+    "Waxing gibbous",
+];
+
+/// How brightly the sky lights the world at night, relative to the day.
+pub const NIGHT_DAYLIGHT: f32 = 0.2;
+
+/// The number of stars scattered over the sky, not counting the constellations.
+const STAR_COUNT: usize = 1200;
+
+/// The number of constellations, groups of bright stars close to each other.
+const CONSTELLATION_COUNT: usize = 12;
+
+/// A star, as a direction from the viewer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Star {
+    pub direction: Vec3,
+    /// How bright the star is, from 0 to 1.
+    pub brightness: f32,
+}
+
+/// Returns the seed the stars are generated from for a world seed.
+pub fn sky_seed(world_seed: i32) -> u64 {
+    hash64(&("sky", world_seed))
+}
+
+/// Generates the stars for a sky seed, see [`sky_seed`].
+pub fn stars(sky_seed: u64) -> Vec<Star> {
+    let mut rng = StdRng::seed_from_u64(sky_seed);
+    let mut stars: Vec<_> = (0..STAR_COUNT)
+        .map(|_| Star {
+            direction: random_direction(&mut rng),
+            brightness: rng.random_range(0.2f32..1.0).powi(2),
+        })
+        .collect();
+    for _ in 0..CONSTELLATION_COUNT {
+        let center = random_direction(&mut rng);
+        for _ in 0..rng.random_range(4..9) {
+            let offset = random_direction(&mut rng) * rng.random_range(0.05..0.2);
+            stars.push(Star {
+                direction: (center + offset).normalize(),
+                brightness: rng.random_range(0.8..1.0),
+            });
+        }
+    }
+    stars
+}
+
+/// Returns a random direction, evenly spread over all directions.
+fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    let y: f32 = rng.random_range(-1.0..1.0);
+    let angle: f32 = rng.random_range(0.0..std::f32::consts::TAU);
+    let radius = (1.0 - y * y).sqrt();
+    Vec3::new(radius * angle.cos(), y, radius * angle.sin())
+}
+
+/// Returns the phase of the moon at the given world time, as an index into [`MOON_PHASES`].
+pub fn moon_phase(time: u64) -> usize {
+    (time / DAY_LENGTH) as usize % MOON_PHASES.len()
+}
+
+/// Returns how much of the moon is lit in a phase, from 0 for a new moon to 1 for a full moon.
+pub fn moon_illumination(phase: usize) -> f32 {
+    let angle = phase as f32 / MOON_PHASES.len() as f32 * std::f32::consts::TAU;
+    (1.0 + angle.cos()) / 2.0
+}
+
+/// Returns how far the sun has moved along its path at the given world time, in radians from
+/// sunrise.
+pub fn sun_angle(time: f64) -> f32 {
+    (time % DAY_LENGTH as f64 / DAY_LENGTH as f64) as f32 * std::f32::consts::TAU
+}
+
+/// Returns how brightly the sky lights the world at the given world time, from
+/// [`NIGHT_DAYLIGHT`] at night to 1 during the day, fading while the sun is close to the horizon.
+pub fn daylight(time: f64) -> f32 {
+    let height = sun_angle(time).sin();
+    let t = ((height + 0.2) / 0.4).clamp(0.0, 1.0);
+    NIGHT_DAYLIGHT + (1.0 - NIGHT_DAYLIGHT) * t * t * (3.0 - 2.0 * t)
+}
+
+/// Returns the direction of the sun at the given world time. The sun rises at the start of the
+/// day towards `sunrise_yaw`, in degrees from the positive X axis towards the positive Z axis, is
+/// highest at noon and sets on the opposite side halfway through the day. The moon is always on
+/// the opposite side of the sky.
+pub fn sun_direction(time: f64, sunrise_yaw: f32) -> Vec3 {
+    let angle = sun_angle(time);
+    let sunrise = Vec3::new(
+        sunrise_yaw.to_radians().cos(),
+        0.0,
+        sunrise_yaw.to_radians().sin(),
+    );
+    sunrise * angle.cos() + Vec3::Y * angle.sin()
+}
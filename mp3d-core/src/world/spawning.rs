@@ -0,0 +1,95 @@
+//! Spawning mobs around the players while the `mob_spawning` game rule is on.
+//!
+//! Every world tick there is a chance that a critter spawns on grass at a random spot around a
+//! random player, unless there are enough critters around that player already. Spots are far
+//! enough from every player that critters don't pop up right in front of them.
+
+use glam::{IVec3, Vec3};
+use rand::Rng;
+
+use crate::{
+    block::blocks,
+    entity::{CritterEntity, Entity, Summonable, entities, pathfinding},
+    world::World,
+};
+
+/// The chance of trying to spawn a critter each world tick is one in this many.
+const SPAWN_CHANCE: u32 = 96;
+/// How far from the player critters spawn, in blocks.
+const SPAWN_DISTANCE: std::ops::RangeInclusive<f32> = 24.0..=48.0;
+/// How far above or below the player the ground is searched for a spot, in blocks.
+const SPAWN_HEIGHT: i32 = 16;
+/// No more critters spawn around a player once there are this many within
+/// [`CROWD_DISTANCE`].
+const MAX_CRITTERS: usize = 8;
+const CROWD_DISTANCE: f32 = 64.0;
+
+impl World {
+    /// Tries to spawn mobs once for every world tick, see [`World::tick`].
+    pub(super) fn spawn_mobs(&mut self, world_ticks: u32) {
+        if !self.game_rules.mob_spawning {
+            return;
+        }
+        for _ in 0..world_ticks {
+            if self.rng.random_ratio(1, SPAWN_CHANCE) {
+                self.try_spawn_critter();
+            }
+        }
+    }
+
+    fn try_spawn_critter(&mut self) {
+        let mut players: Vec<_> = self
+            .entities
+            .values()
+            .filter(|entity| entity.entity_type() == *entities::PLAYER)
+            .map(|entity| (entity.id(), entity.position()))
+            .collect();
+        if players.is_empty() {
+            return;
+        }
+        players.sort_unstable_by_key(|(id, _)| *id);
+        let (_, player) = players[self.rng.random_range(0..players.len())];
+
+        let crowd = self
+            .entities
+            .values()
+            .filter(|entity| {
+                entity.entity_type() == *entities::CRITTER
+                    && entity.position().distance(player) < CROWD_DISTANCE
+            })
+            .count();
+        if crowd >= MAX_CRITTERS {
+            return;
+        }
+
+        let angle = self.rng.random_range(0.0..std::f32::consts::TAU);
+        let distance = self.rng.random_range(SPAWN_DISTANCE);
+        let column = (player + Vec3::new(angle.cos(), 0.0, angle.sin()) * distance)
+            .floor()
+            .as_ivec3();
+        let Some(pos) = (-SPAWN_HEIGHT..=SPAWN_HEIGHT)
+            .rev()
+            .map(|dy| column + IVec3::Y * dy)
+            .find(|pos| {
+                self.get_block_at(pos - IVec3::Y)
+                    .is_some_and(|(block, _)| block == *blocks::GRASS)
+                    && pathfinding::can_stand(
+                        &self.view(),
+                        *pos,
+                        CritterEntity::width(),
+                        CritterEntity::height(),
+                    )
+            })
+        else {
+            return;
+        };
+        let position = pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+        if players
+            .iter()
+            .any(|(_, player)| player.distance(position) < *SPAWN_DISTANCE.start())
+        {
+            return;
+        }
+        self.add_entity(Box::new(CritterEntity::summon(position)));
+    }
+}
@@ -0,0 +1,152 @@
+//! Limits on the work done in one tick, so that a chunk crowded with entities or a huge loaded
+//! area can't slow down the whole server.
+//!
+//! Work past a budget isn't dropped but carried over: the chunks and entities that missed a tick go
+//! first in the next one. The time spent on every chunk is measured as well, so that the most
+//! expensive chunks can be found with `/debug chunk`.
+
+use std::time::Duration;
+
+use fxhash::FxHashMap;
+use glam::IVec3;
+
+/// The most chunks random ticked in one world tick. With more chunks loaded, every chunk is still
+/// random ticked in turn, just not in every world tick.
+pub const RANDOM_TICK_BUDGET: usize = 2048;
+
+/// The most entities other than players ticked per chunk in one server tick. Players are always
+/// ticked.
+pub const CHUNK_ENTITY_BUDGET: usize = 128;
+
+/// How much of the measured cost of a chunk comes from the latest tick, the rest is the cost of
+/// the earlier ticks. Lower values smooth out spikes more.
+const COST_SMOOTHING: f32 = 0.1;
+
+/// Chunks whose smoothed cost drops below this many microseconds are no longer tracked.
+const MIN_TRACKED_COST: f32 = 0.01;
+
+/// The measured work done on a chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChunkCost {
+    /// The time spent on the chunk per server tick, smoothed over recent ticks, in microseconds.
+    pub micros: f32,
+    /// The entities other than players in the chunk in the latest tick.
+    pub entities: usize,
+    /// The entity ticks carried over to a later tick by [`CHUNK_ENTITY_BUDGET`] in the latest
+    /// tick.
+    pub deferred: usize,
+}
+
+/// Keeps track of the work carried over between ticks and of the cost of every chunk.
+#[derive(Debug, Default)]
+pub struct TickBudget {
+    /// Where random ticking continues in the next world tick, as an index into the sorted
+    /// positions of the loaded chunks.
+    random_tick_cursor: usize,
+    /// The number of server ticks in a row each entity wasn't ticked for.
+    entity_waits: FxHashMap<u64, u32>,
+    costs: FxHashMap<IVec3, ChunkCost>,
+    /// The costs measured during the current tick, folded into `costs` by [`TickBudget::finish`].
+    samples: FxHashMap<IVec3, ChunkCost>,
+    /// The number of chunks random ticked and loaded in the latest world tick.
+    random_ticked: (usize, usize),
+}
+
+impl TickBudget {
+    /// Returns the chunks to random tick in this world tick, given the positions of the loaded
+    /// chunks. All of them are ticked if they fit in [`RANDOM_TICK_BUDGET`], otherwise the next
+    /// ones in turn after the chunks ticked last time.
+    pub fn random_tick_chunks(&mut self, chunk_positions: &mut [IVec3]) -> Vec<IVec3> {
+        let count = chunk_positions.len();
+        self.random_ticked = (count.min(RANDOM_TICK_BUDGET), count);
+        if count <= RANDOM_TICK_BUDGET {
+            return chunk_positions.to_vec();
+        }
+        chunk_positions.sort_unstable_by_key(|pos| pos.to_array());
+        let start = self.random_tick_cursor % count;
+        self.random_tick_cursor = (start + RANDOM_TICK_BUDGET) % count;
+        chunk_positions
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(RANDOM_TICK_BUDGET)
+            .copied()
+            .collect()
+    }
+
+    /// Returns which of the entities in a chunk to tick, given their IDs. The entities that waited
+    /// the longest go first, then the ones with the lowest ID. The others wait for a later tick.
+    pub fn entities_to_tick(&mut self, chunk_pos: IVec3, mut entity_ids: Vec<u64>) -> Vec<u64> {
+        let sample = self.samples.entry(chunk_pos).or_default();
+        sample.entities += entity_ids.len();
+        if entity_ids.len() <= CHUNK_ENTITY_BUDGET {
+            for id in &entity_ids {
+                self.entity_waits.remove(id);
+            }
+            return entity_ids;
+        }
+
+        entity_ids.sort_unstable_by_key(|id| {
+            (
+                std::cmp::Reverse(self.entity_waits.get(id).copied().unwrap_or(0)),
+                *id,
+            )
+        });
+        let waiting = entity_ids.split_off(CHUNK_ENTITY_BUDGET);
+        sample.deferred += waiting.len();
+        for id in waiting {
+            *self.entity_waits.entry(id).or_default() += 1;
+        }
+        for id in &entity_ids {
+            self.entity_waits.remove(id);
+        }
+        entity_ids
+    }
+
+    /// Adds time spent on a chunk during the current tick.
+    pub fn record(&mut self, chunk_pos: IVec3, time: Duration) {
+        self.samples.entry(chunk_pos).or_default().micros += time.as_secs_f32() * 1_000_000.0;
+    }
+
+    /// Folds the costs measured during the tick into the smoothed costs. `is_loaded` tells which
+    /// chunks are still loaded, the others are forgotten, as are entities that no longer exist.
+    pub fn finish(
+        &mut self,
+        is_loaded: impl Fn(IVec3) -> bool,
+        entity_exists: impl Fn(u64) -> bool,
+    ) {
+        let mut samples = std::mem::take(&mut self.samples);
+        for (pos, cost) in &mut self.costs {
+            let sample = samples.remove(pos).unwrap_or_default();
+            cost.micros += (sample.micros - cost.micros) * COST_SMOOTHING;
+            cost.entities = sample.entities;
+            cost.deferred = sample.deferred;
+        }
+        for (pos, sample) in samples {
+            self.costs.insert(
+                pos,
+                ChunkCost {
+                    micros: sample.micros * COST_SMOOTHING,
+                    ..sample
+                },
+            );
+        }
+        self.costs
+            .retain(|&pos, cost| is_loaded(pos) && cost.micros >= MIN_TRACKED_COST);
+        self.entity_waits.retain(|&id, _| entity_exists(id));
+    }
+
+    /// Returns the `count` most expensive chunks, the most expensive first.
+    pub fn most_expensive(&self, count: usize) -> Vec<(IVec3, ChunkCost)> {
+        let mut costs: Vec<_> = self.costs.iter().map(|(pos, cost)| (*pos, *cost)).collect();
+        costs.sort_unstable_by(|a, b| b.1.micros.total_cmp(&a.1.micros));
+        costs.truncate(count);
+        costs
+    }
+
+    /// Returns how many chunks were random ticked in the latest world tick and how many were
+    /// loaded.
+    pub fn random_ticked(&self) -> (usize, usize) {
+        self.random_ticked
+    }
+}
@@ -0,0 +1,48 @@
+//! Debug controls of the world tick, to pause or slow down the world while testing gameplay.
+
+/// The rate at which the world ticks unless it is slowed down or sped up, in ticks per second.
+pub const DEFAULT_TICK_RATE: u8 = 48;
+
+/// Controls how often the world ticks compared to the server. These can be changed in game with
+/// the `/tick` command and aren't saved.
+///
+/// Only random block ticks, the time and entities other than players follow these controls.
+/// Players keep moving and chunks are still sent, so a frozen world can still be looked around.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TickControl {
+    /// Whether the world is paused, only ticking when stepped.
+    pub frozen: bool,
+    /// The world ticks left to run while frozen.
+    pub steps: u32,
+    /// How many times the world ticks per second, when not frozen.
+    pub rate: u8,
+    /// The part of a world tick carried over between server ticks.
+    progress: f32,
+}
+
+impl Default for TickControl {
+    fn default() -> Self {
+        Self {
+            frozen: false,
+            steps: 0,
+            rate: DEFAULT_TICK_RATE,
+            progress: 0.0,
+        }
+    }
+}
+
+impl TickControl {
+    /// Returns how many times the world ticks in a server tick, when the server runs at `tps`
+    /// ticks per second. A frozen world runs one of its steps per server tick.
+    pub fn world_ticks(&mut self, tps: u8) -> u32 {
+        if self.frozen {
+            let ticks = self.steps.min(1);
+            self.steps -= ticks;
+            return ticks;
+        }
+        self.progress += self.rate as f32 / tps.max(1) as f32;
+        let ticks = self.progress.floor();
+        self.progress -= ticks;
+        ticks as u32
+    }
+}
@@ -0,0 +1,228 @@
+//! A dedicated Mineplace3D server, hosting a world for players on other machines.
+//!
+//! Usage: `mp3d-server <world path> [address] [--web-map <address>] [--max-chunks <count>]`. The world is created if it
+//! doesn't exist yet, and the server listens on `0.0.0.0` with the default port unless given an
+//! address. Type `save` to save the world and `stop` to save it and shut the server down, and
+//! `op <name>` or `deop <name>` to let a player use the operator commands like `/ban` or take that
+//! away again. What players say and the commands they run are logged to `logs/chat.log` in the
+//! world folder.
+//!
+//! A `bridge.json` in the world folder bridges the chat to a webhook, like a Discord channel, and
+//! lets other programs send messages to the chat, see [`mp3d_core::server::bridge`].
+//!
+//! With `--web-map`, a map of the explored world and the players on it is served over HTTP on
+//! the given address, see [`mp3d_core::server::webmap`].
+//!
+//! The server keeps the chunks around players loaded and recently unloaded chunks in memory for
+//! a while. With `--max-chunks`, it keeps at most that many chunks in memory, unless the players
+//! need more, see [`mp3d_core::world::World::set_max_loaded_chunks`].
+
+use std::{
+    io::BufRead,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use mp3d_core::{
+    chatlog::{ChatLog, DEFAULT_MAX_LOG_SIZE},
+    net::{DEFAULT_PORT, with_default_port},
+    server::{Server, bridge::ChatBridge, host::Host, webmap::WebMap},
+};
+
+/// How often the whole world is saved, on top of the chunks the server saves by itself.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+fn main() {
+    fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{}][{}][{}] {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(log::LevelFilter::Info)
+        .chain(std::io::stdout())
+        .chain(fern::log_file("server.log").unwrap())
+        .apply()
+        .unwrap();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let web_map_address = take_option(&mut args, "--web-map");
+    let max_loaded_chunks = take_option(&mut args, "--max-chunks")
+        .map(|count| count.parse::<usize>().unwrap_or_else(|_| usage()));
+    let mut args = args.into_iter();
+    let Some(world_path) = args.next().map(PathBuf::from) else {
+        usage();
+    };
+    let address = args
+        .next()
+        .map(|address| with_default_port(&address))
+        .unwrap_or_else(|| format!("0.0.0.0:{}", DEFAULT_PORT));
+
+    log::info!("Mineplace3D server {}", env!("CARGO_PKG_VERSION"));
+    mp3d_core::init();
+
+    let chat_log = ChatLog::new(world_path.join("logs/chat.log"), DEFAULT_MAX_LOG_SIZE);
+    let mut server = match load_or_create(world_path) {
+        Ok(server) => server,
+        Err(e) => {
+            log::error!("Failed to load the world: {}", e);
+            std::process::exit(1);
+        }
+    };
+    server.chat_log = Some(chat_log);
+    server.world.set_max_loaded_chunks(max_loaded_chunks);
+    server.chat_bridge = match ChatBridge::load(&server.save_path.join("bridge.json")) {
+        Ok(bridge) => bridge,
+        Err(e) => {
+            log::error!("Failed to set up the chat bridge: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut host = match Host::bind(server, &address) {
+        Ok(host) => host,
+        Err(e) => {
+            log::error!("Failed to listen on {}: {}", address, e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut web_map = web_map_address.map(|address| match WebMap::bind(&address) {
+        Ok(web_map) => web_map,
+        Err(e) => {
+            log::error!("Failed to serve the web map on {}: {}", address, e);
+            std::process::exit(1);
+        }
+    });
+
+    let console = read_console();
+    let mut next_tick = Instant::now();
+    let mut last_save = Instant::now();
+    loop {
+        let tps = host.server.tps.max(1);
+        host.tick(tps);
+        if let Some(web_map) = &mut web_map {
+            web_map.update(&mut host.server);
+        }
+
+        for line in console.try_iter() {
+            match line.trim() {
+                "save" => save(&mut host.server),
+                "stop" => {
+                    save(&mut host.server);
+                    log::info!("Stopping the server");
+                    return;
+                }
+                "" => {}
+                other => match other.split_once(' ') {
+                    Some(("op", name)) => set_operator(&mut host.server, name.trim(), true),
+                    Some(("deop", name)) => set_operator(&mut host.server, name.trim(), false),
+                    _ => log::warn!(
+                        "Unknown console command '{}', try save, stop, op or deop",
+                        other
+                    ),
+                },
+            }
+        }
+        if last_save.elapsed() >= AUTOSAVE_INTERVAL {
+            save(&mut host.server);
+            last_save = Instant::now();
+        }
+
+        // Falls behind instead of catching up with a burst of ticks after a slow one
+        next_tick += Duration::from_secs(1) / tps as u32;
+        let now = Instant::now();
+        if next_tick > now {
+            thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: mp3d-server <world path> [address] [--web-map <address>] [--max-chunks <count>]"
+    );
+    std::process::exit(1);
+}
+
+/// Removes an option like `--web-map <address>` from the arguments, returning its value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    match args.iter().position(|arg| arg == name) {
+        Some(i) if i + 1 < args.len() => Some(args.drain(i..i + 2).nth(1).unwrap()),
+        Some(_) => usage(),
+        None => None,
+    }
+}
+
+/// Loads the world at the path, or creates a new world there with a random seed.
+fn load_or_create(world_path: PathBuf) -> std::io::Result<Server> {
+    if world_path.join("save.bin").exists() {
+        log::info!("Loading world from {}", world_path.display());
+        return Server::load(false, world_path);
+    }
+    let seed = rand::random();
+    log::info!(
+        "Creating a new world at {} with seed {}",
+        world_path.display(),
+        seed
+    );
+    std::fs::create_dir_all(&world_path)?;
+    let mut server = Server::new(false, seed, world_path);
+    server.save()?;
+    Ok(server)
+}
+
+fn save(server: &mut Server) {
+    log::info!("Saving world...");
+    match server.save() {
+        Ok(()) => log::info!("Saved the world"),
+        Err(e) => log::error!("Failed to save the world: {}", e),
+    }
+}
+
+/// Lets a player use the operator commands, or takes that away.
+fn set_operator(server: &mut Server, name: &str, operator: bool) {
+    let operators = &mut server.access.rules.operators;
+    let changed = if operator {
+        operators.insert(name.to_string())
+    } else {
+        operators.remove(name)
+    };
+    if !changed {
+        log::warn!(
+            "{} {} an operator already",
+            name,
+            if operator { "is" } else { "isn't" }
+        );
+        return;
+    }
+    match server.access.save() {
+        Ok(()) if operator => log::info!("Made {} an operator", name),
+        Ok(()) => log::info!("{} is no longer an operator", name),
+        Err(e) => log::error!("Failed to save the access rules: {}", e),
+    }
+}
+
+/// Reads the lines typed into the console on a separate thread, so that the server keeps ticking
+/// while waiting for input.
+fn read_console() -> Receiver<String> {
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if sender.send(line).is_err() {
+                return;
+            }
+        }
+    });
+    lines
+}